@@ -35,6 +35,7 @@ async fn test_lock_unlock_rpc() -> Result<(), Box<dyn Error>> {
         owner: "dd".to_string(),
         source: "".to_string(),
         quorum: 3,
+        ..Default::default()
     };
     let args = serde_json::to_string(&args)?;
 
@@ -0,0 +1,200 @@
+// Copyright 2024 RustFS Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Audit events for object operations, handed to the [`AuditSink`] in scope as each call
+//! returns. Nothing is recorded outside a [`with_audit_sink`] scope.
+
+use crate::error::Result;
+use crate::store_api::{GetObjectReader, ObjectInfo};
+use async_trait::async_trait;
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use time::OffsetDateTime;
+use tokio::io::{AsyncRead, ReadBuf};
+
+tokio::task_local! {
+    static AUDIT_SINK: Arc<dyn AuditSink>;
+}
+
+/// Runs `fut` with the audit event of every object operation it makes sent to `sink`.
+pub async fn with_audit_sink<F: Future>(sink: Arc<dyn AuditSink>, fut: F) -> F::Output {
+    AUDIT_SINK.scope(sink, fut).await
+}
+
+fn current_sink() -> Option<Arc<dyn AuditSink>> {
+    AUDIT_SINK.try_with(|sink| sink.clone()).ok()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditOperation {
+    GetObject,
+    PutObject,
+    CopyObject,
+    DeleteObject,
+    CompleteMultipartUpload,
+}
+
+impl AuditOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditOperation::GetObject => "s3:GetObject",
+            AuditOperation::PutObject => "s3:PutObject",
+            AuditOperation::CopyObject => "s3:CopyObject",
+            AuditOperation::DeleteObject => "s3:DeleteObject",
+            AuditOperation::CompleteMultipartUpload => "s3:CompleteMultipartUpload",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AuditEvent {
+    pub time: OffsetDateTime,
+    pub operation: AuditOperation,
+    pub bucket: String,
+    pub object: String,
+    /// The error text when the operation failed.
+    pub result: std::result::Result<(), String>,
+    /// Object bytes written, or read by the caller of a GetObject. Zero for deletes and failures.
+    pub bytes: i64,
+}
+
+impl AuditEvent {
+    fn new(operation: AuditOperation, bucket: &str, object: &str) -> Self {
+        Self {
+            time: OffsetDateTime::now_utc(),
+            operation,
+            bucket: bucket.to_owned(),
+            object: object.to_owned(),
+            result: Ok(()),
+            bytes: 0,
+        }
+    }
+}
+
+#[async_trait]
+pub trait AuditSink: Send + Sync {
+    async fn emit(&self, event: AuditEvent);
+}
+
+/// Drops every event, the same as running outside any [`with_audit_sink`] scope.
+pub struct NoopAuditSink;
+
+#[async_trait]
+impl AuditSink for NoopAuditSink {
+    async fn emit(&self, _event: AuditEvent) {}
+}
+
+/// Object bytes written by a put, copy or multipart completion.
+pub(crate) fn written_size(info: &ObjectInfo) -> i64 {
+    info.get_actual_size().unwrap_or(info.size)
+}
+
+/// Emits the event for an operation that returned `res`, with `bytes` taken from its value.
+pub(crate) async fn emit_result<T>(
+    operation: AuditOperation,
+    bucket: &str,
+    object: &str,
+    res: &Result<T>,
+    bytes: impl FnOnce(&T) -> i64,
+) {
+    let Some(sink) = current_sink() else {
+        return;
+    };
+
+    let mut event = AuditEvent::new(operation, bucket, object);
+    match res {
+        Ok(v) => event.bytes = bytes(v),
+        Err(err) => event.result = Err(err.to_string()),
+    }
+    sink.emit(event).await;
+}
+
+/// Runs the operation `fut` and emits its event, with `bytes` taken from the value it returns.
+pub(crate) async fn record<T>(
+    operation: AuditOperation,
+    bucket: &str,
+    object: &str,
+    bytes: impl FnOnce(&T) -> i64,
+    fut: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    let res = fut.await;
+    emit_result(operation, bucket, object, &res, bytes).await;
+    res
+}
+
+/// Runs the GetObject `fut`. A failure is emitted right away, otherwise the event is emitted
+/// with the bytes the caller read once it drops the stream.
+pub(crate) async fn record_get(
+    bucket: &str,
+    object: &str,
+    fut: impl Future<Output = Result<GetObjectReader>>,
+) -> Result<GetObjectReader> {
+    let Some(sink) = current_sink() else {
+        return fut.await;
+    };
+
+    let mut event = AuditEvent::new(AuditOperation::GetObject, bucket, object);
+    let mut reader = match fut.await {
+        Ok(reader) => reader,
+        Err(err) => {
+            event.result = Err(err.to_string());
+            sink.emit(event).await;
+            return Err(err);
+        }
+    };
+
+    reader.stream = Box::new(AuditedReader {
+        inner: reader.stream,
+        sink,
+        event: Some(event),
+    });
+    Ok(reader)
+}
+
+// Counts the bytes read from a GetObject stream, the event goes to the sink when it is dropped.
+struct AuditedReader {
+    inner: Box<dyn AsyncRead + Unpin + Send + Sync>,
+    sink: Arc<dyn AuditSink>,
+    event: Option<AuditEvent>,
+}
+
+impl AsyncRead for AuditedReader {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        let filled = buf.filled().len();
+        let res = Pin::new(&mut self.inner).poll_read(cx, buf);
+        let read = buf.filled().len() - filled;
+        if let (Poll::Ready(res), Some(event)) = (&res, self.event.as_mut()) {
+            match res {
+                Ok(()) => event.bytes += read as i64,
+                Err(err) => event.result = Err(err.to_string()),
+            }
+        }
+        res
+    }
+}
+
+impl Drop for AuditedReader {
+    fn drop(&mut self) {
+        let Some(event) = self.event.take() else {
+            return;
+        };
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            let sink = self.sink.clone();
+            handle.spawn(async move { sink.emit(event).await });
+        }
+    }
+}
@@ -26,8 +26,8 @@ use tokio::io::AsyncRead;
 /// * `disk` - Optional disk reference for file stream reading  
 /// * `bucket` - Bucket name for file path
 /// * `path` - File path within the bucket
-/// * `offset` - Starting offset for reading
-/// * `length` - Length to read
+/// * `offset` - Starting offset in shard data, a multiple of `shard_size`
+/// * `length` - Length of shard data to read
 /// * `shard_size` - Shard size for erasure coding
 /// * `checksum_algo` - Hash algorithm for bitrot verification
 #[allow(clippy::too_many_arguments)]
@@ -41,12 +41,13 @@ pub async fn create_bitrot_reader(
     shard_size: usize,
     checksum_algo: HashAlgorithm,
 ) -> disk::error::Result<Option<BitrotReader<Box<dyn AsyncRead + Send + Sync + Unpin>>>> {
-    // Calculate the total length to read, including the checksum overhead
+    // Every shard on disk is preceded by its checksum
+    let offset = offset / shard_size * checksum_algo.size() + offset;
     let length = length.div_ceil(shard_size) * checksum_algo.size() + length;
 
     if let Some(data) = inline_data {
         // Use inline data
-        let rd = Cursor::new(data.to_vec());
+        let rd = Cursor::new(data[offset.min(data.len())..].to_vec());
         let reader = BitrotReader::new(Box::new(rd) as Box<dyn AsyncRead + Send + Sync + Unpin>, shard_size, checksum_algo);
         Ok(Some(reader))
     } else if let Some(disk) = disk {
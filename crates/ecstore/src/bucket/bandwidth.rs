@@ -0,0 +1,239 @@
+// Copyright 2024 RustFS Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Per replication target bandwidth limits, shared by every transfer to the same ARN.
+
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::time::Instant;
+
+pub static GLOBAL_BANDWIDTH_MONITOR: LazyLock<BandwidthMonitor> = LazyLock::new(BandwidthMonitor::default);
+
+// how often the measured rate is refreshed
+const MEASURE_WINDOW: Duration = Duration::from_secs(1);
+
+struct Bucket {
+    // may go negative, the debt is what the next callers have to wait out
+    tokens: f64,
+    last: Instant,
+}
+
+struct Measure {
+    start: Instant,
+    bytes: u64,
+    rate: u64,
+}
+
+/// Token bucket holding at most one second worth of bytes.
+pub struct Throttle {
+    limit: Option<u64>,
+    bucket: Mutex<Bucket>,
+    measure: Mutex<Measure>,
+}
+
+impl Throttle {
+    pub fn new(limit: Option<u64>) -> Self {
+        let now = Instant::now();
+        Self {
+            limit: limit.filter(|l| *l > 0),
+            bucket: Mutex::new(Bucket {
+                tokens: limit.unwrap_or_default() as f64,
+                last: now,
+            }),
+            measure: Mutex::new(Measure {
+                start: now,
+                bytes: 0,
+                rate: 0,
+            }),
+        }
+    }
+
+    pub fn limit(&self) -> Option<u64> {
+        self.limit
+    }
+
+    /// Accounts `n` bytes and waits until the limit allows them through.
+    pub async fn wait(&self, n: usize) {
+        self.record(n as u64);
+
+        let Some(limit) = self.limit else {
+            return;
+        };
+
+        let delay = {
+            let mut bucket = self.bucket.lock().unwrap();
+            let now = Instant::now();
+            let refill = now.duration_since(bucket.last).as_secs_f64() * limit as f64;
+            bucket.tokens = (bucket.tokens + refill).min(limit as f64) - n as f64;
+            bucket.last = now;
+            if bucket.tokens < 0.0 {
+                Duration::from_secs_f64(-bucket.tokens / limit as f64)
+            } else {
+                Duration::ZERO
+            }
+        };
+
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    fn record(&self, n: u64) {
+        let mut measure = self.measure.lock().unwrap();
+        Self::roll(&mut measure);
+        measure.bytes += n;
+    }
+
+    fn roll(measure: &mut Measure) {
+        let elapsed = measure.start.elapsed();
+        if elapsed >= MEASURE_WINDOW {
+            measure.rate = if elapsed >= MEASURE_WINDOW * 2 {
+                // idle for a whole window
+                0
+            } else {
+                (measure.bytes as f64 / elapsed.as_secs_f64()) as u64
+            };
+            measure.start = Instant::now();
+            measure.bytes = 0;
+        }
+    }
+
+    /// Bytes per second sent over the last full window.
+    pub fn current_bandwidth(&self) -> u64 {
+        let mut measure = self.measure.lock().unwrap();
+        Self::roll(&mut measure);
+        measure.rate
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BandwidthDetails {
+    pub limit_bytes_per_sec: Option<u64>,
+    pub current_bytes_per_sec: u64,
+}
+
+#[derive(Default)]
+pub struct BandwidthMonitor {
+    throttles: RwLock<HashMap<String, Arc<Throttle>>>,
+}
+
+impl BandwidthMonitor {
+    /// Sets the limit for the target, `None` leaves transfers unthrottled but still measured.
+    pub fn set_limit(&self, arn: &str, limit: Option<u64>) {
+        let limit = limit.filter(|l| *l > 0);
+        let mut throttles = self.throttles.write().unwrap();
+        if throttles.get(arn).is_some_and(|t| t.limit() == limit) {
+            return;
+        }
+        throttles.insert(arn.to_string(), Arc::new(Throttle::new(limit)));
+    }
+
+    pub fn remove(&self, arn: &str) {
+        self.throttles.write().unwrap().remove(arn);
+    }
+
+    pub fn throttle(&self, arn: &str) -> Arc<Throttle> {
+        if let Some(throttle) = self.throttles.read().unwrap().get(arn) {
+            return throttle.clone();
+        }
+
+        self.throttles
+            .write()
+            .unwrap()
+            .entry(arn.to_string())
+            .or_insert_with(|| Arc::new(Throttle::new(None)))
+            .clone()
+    }
+
+    pub fn report(&self) -> HashMap<String, BandwidthDetails> {
+        self.throttles
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(arn, t)| {
+                (
+                    arn.clone(),
+                    BandwidthDetails {
+                        limit_bytes_per_sec: t.limit(),
+                        current_bytes_per_sec: t.current_bandwidth(),
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+/// Reads `reader` to the end, pacing it by the throttle.
+pub async fn read_all_throttled<R: AsyncRead + Unpin>(reader: &mut R, throttle: &Throttle) -> std::io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = reader.read(&mut buf).await?;
+        if n == 0 {
+            return Ok(data);
+        }
+        throttle.wait(n).await;
+        data.extend_from_slice(&buf[..n]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the clock is paused and only moves when every task waits, so the elapsed time is exactly what the throttle asked for
+    #[tokio::test(start_paused = true)]
+    async fn test_throttle_caps_throughput() {
+        const LIMIT: u64 = 400_000;
+        const CHUNK: usize = 16 * 1024;
+        const PER_TASK: usize = 300_000;
+
+        let monitor = BandwidthMonitor::default();
+        monitor.set_limit("arn:test", Some(LIMIT));
+
+        let start = Instant::now();
+        let mut tasks = Vec::new();
+        for _ in 0..2 {
+            let throttle = monitor.throttle("arn:test");
+            tasks.push(tokio::spawn(async move {
+                let mut sent = 0;
+                while sent < PER_TASK {
+                    let n = CHUNK.min(PER_TASK - sent);
+                    throttle.wait(n).await;
+                    sent += n;
+                }
+            }));
+        }
+        for task in tasks {
+            task.await.unwrap();
+        }
+        let elapsed = start.elapsed().as_secs_f64();
+
+        // one second of burst, the remaining 200KB at 400KB/s
+        let expected = (2 * PER_TASK) as f64 / LIMIT as f64 - 1.0;
+        assert!((elapsed - expected).abs() < 0.001, "took {elapsed}s, expected {expected}s");
+
+        let report = monitor.report();
+        assert_eq!(report["arn:test"].limit_bytes_per_sec, Some(LIMIT));
+
+        // no limit, no waiting
+        let unlimited = monitor.throttle("arn:other");
+        let start = Instant::now();
+        unlimited.wait(10 * LIMIT as usize).await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        assert_eq!(monitor.report()["arn:other"].limit_bytes_per_sec, None);
+    }
+}
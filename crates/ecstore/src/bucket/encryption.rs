@@ -0,0 +1,120 @@
+// Copyright 2024 RustFS Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::metadata::BucketMetadata;
+use crate::store_api::ObjectOptions;
+use s3s::dto::ServerSideEncryption;
+use s3s::header::{
+    X_AMZ_SERVER_SIDE_ENCRYPTION, X_AMZ_SERVER_SIDE_ENCRYPTION_AWS_KMS_KEY_ID, X_AMZ_SERVER_SIDE_ENCRYPTION_CUSTOMER_ALGORITHM,
+};
+
+/// True when the put already asks for SSE-S3, SSE-KMS or SSE-C.
+pub fn is_encryption_requested(opts: &ObjectOptions) -> bool {
+    opts.user_defined.keys().any(|k| {
+        k.eq_ignore_ascii_case(X_AMZ_SERVER_SIDE_ENCRYPTION.as_str())
+            || k.eq_ignore_ascii_case(X_AMZ_SERVER_SIDE_ENCRYPTION_CUSTOMER_ALGORITHM.as_str())
+    })
+}
+
+/// Applies the bucket default encryption to a put that didn't ask for any, as S3 does.
+///
+/// The first rule with a default sets `x-amz-server-side-encryption`, plus the KMS key id for
+/// SSE-KMS when the rule names one, the put is then encrypted as if the client had asked for it.
+/// An explicit SSE-S3, SSE-KMS or SSE-C request is kept. Only single-part puts get the default,
+/// multipart uploads are stored as sent.
+pub fn apply_default_encryption(meta: &BucketMetadata, opts: &mut ObjectOptions) {
+    if is_encryption_requested(opts) {
+        return;
+    }
+
+    let Some(config) = &meta.sse_config else {
+        return;
+    };
+    let Some(default) = config
+        .rules
+        .iter()
+        .find_map(|rule| rule.apply_server_side_encryption_by_default.as_ref())
+    else {
+        return;
+    };
+
+    let algorithm = default.sse_algorithm.as_str();
+    opts.user_defined
+        .insert(X_AMZ_SERVER_SIDE_ENCRYPTION.as_str().to_string(), algorithm.to_string());
+    if algorithm != ServerSideEncryption::AES256 {
+        if let Some(key_id) = &default.kms_master_key_id {
+            opts.user_defined
+                .insert(X_AMZ_SERVER_SIDE_ENCRYPTION_AWS_KMS_KEY_ID.as_str().to_string(), key_id.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use s3s::dto::{ServerSideEncryptionByDefault, ServerSideEncryptionConfiguration, ServerSideEncryptionRule};
+
+    fn bucket_with_default(sse_algorithm: &str, kms_master_key_id: Option<&str>) -> BucketMetadata {
+        let mut meta = BucketMetadata::new("sse");
+        meta.sse_config = Some(ServerSideEncryptionConfiguration {
+            rules: vec![ServerSideEncryptionRule {
+                apply_server_side_encryption_by_default: Some(ServerSideEncryptionByDefault {
+                    sse_algorithm: ServerSideEncryption::from(sse_algorithm.to_string()),
+                    kms_master_key_id: kms_master_key_id.map(str::to_string),
+                }),
+                bucket_key_enabled: None,
+            }],
+        });
+        meta
+    }
+
+    #[test]
+    fn test_apply_default_encryption() {
+        let sse = "x-amz-server-side-encryption";
+        let kms_key = "x-amz-server-side-encryption-aws-kms-key-id";
+
+        // a plain put picks up the bucket default
+        let meta = bucket_with_default(ServerSideEncryption::AES256, None);
+        let mut opts = ObjectOptions::default();
+        apply_default_encryption(&meta, &mut opts);
+        assert_eq!(opts.user_defined.get(sse).map(String::as_str), Some("AES256"));
+        assert!(!opts.user_defined.contains_key(kms_key));
+
+        let meta = bucket_with_default(ServerSideEncryption::AWS_KMS, Some("my-key"));
+        let mut opts = ObjectOptions::default();
+        apply_default_encryption(&meta, &mut opts);
+        assert_eq!(opts.user_defined.get(sse).map(String::as_str), Some("aws:kms"));
+        assert_eq!(opts.user_defined.get(kms_key).map(String::as_str), Some("my-key"));
+
+        // an explicit SSE-C put is left untouched
+        let mut opts = ObjectOptions::default();
+        opts.user_defined
+            .insert("x-amz-server-side-encryption-customer-algorithm".to_string(), "AES256".to_string());
+        let before = opts.user_defined.clone();
+        apply_default_encryption(&meta, &mut opts);
+        assert_eq!(opts.user_defined, before);
+
+        // as is an explicit SSE-S3 put to an SSE-KMS bucket
+        let mut opts = ObjectOptions::default();
+        opts.user_defined
+            .insert("X-Amz-Server-Side-Encryption".to_string(), "AES256".to_string());
+        apply_default_encryption(&meta, &mut opts);
+        assert_eq!(opts.user_defined.len(), 1);
+
+        // no default configured
+        let mut opts = ObjectOptions::default();
+        apply_default_encryption(&BucketMetadata::new("plain"), &mut opts);
+        assert!(opts.user_defined.is_empty());
+    }
+}
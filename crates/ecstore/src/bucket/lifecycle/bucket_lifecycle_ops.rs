@@ -44,6 +44,7 @@ use super::bucket_lifecycle_audit::{LcAuditEvent, LcEventSrc};
 use super::lifecycle::{self, ExpirationOptions, IlmAction, Lifecycle, TransitionOptions};
 use super::tier_last_day_stats::{DailyAllTierStats, LastDayTierStats};
 use super::tier_sweeper::{Jentry, delete_object_from_remote_tier};
+use crate::bucket::utils::is_meta_bucketname;
 use crate::bucket::{metadata_sys::get_lifecycle_config, versioning_sys::BucketVersioningSys};
 use crate::client::object_api_utils::new_getobjectreader;
 use crate::error::Error;
@@ -803,7 +804,7 @@ impl LifecycleOps for ObjectInfo {
         lifecycle::ObjectOpts {
             name: self.name.clone(),
             user_tags: self.user_tags.clone(),
-            version_id: self.version_id.expect("err").to_string(),
+            version_id: self.version_id.map(|v| v.to_string()).unwrap_or_default(),
             mod_time: self.mod_time,
             size: self.size as usize,
             is_latest: self.is_latest,
@@ -818,6 +819,20 @@ impl LifecycleOps for ObjectInfo {
     }
 }
 
+/// Fills in when, and by which rule, the bucket lifecycle is going to expire `oi`.
+pub async fn set_object_expiration(oi: &mut ObjectInfo) {
+    if oi.delete_marker || is_meta_bucketname(&oi.bucket) {
+        return;
+    }
+    let Ok((lc, _)) = get_lifecycle_config(&oi.bucket).await else {
+        return;
+    };
+    if !lc.has_expiry() {
+        return;
+    }
+    oi.expiration = lc.predict_expiry(&oi.to_lifecycle_opts()).await;
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct S3Location {
     pub bucketname: String,
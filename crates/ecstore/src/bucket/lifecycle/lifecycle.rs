@@ -178,6 +178,7 @@ pub trait Lifecycle {
     async fn filter_rules(&self, obj: &ObjectOpts) -> Option<Vec<LifecycleRule>>;
     async fn eval(&self, obj: &ObjectOpts) -> Event;
     async fn eval_inner(&self, obj: &ObjectOpts, now: OffsetDateTime) -> Event;
+    async fn predict_expiry(&self, obj: &ObjectOpts) -> Option<(OffsetDateTime, String)>;
     //fn set_prediction_headers(&self, w: http.ResponseWriter, obj: ObjectOpts);
     async fn noncurrent_versions_expiration_limit(&self, obj: &ObjectOpts) -> Event;
 }
@@ -556,6 +557,22 @@ impl Lifecycle for BucketLifecycleConfiguration {
         Event::default()
     }
 
+    /// The date and rule id the object is going to be expired on, whether or not it is due yet.
+    async fn predict_expiry(&self, obj: &ObjectOpts) -> Option<(OffsetDateTime, String)> {
+        obj.mod_time?;
+        // a completed transition keeps transition rules from shadowing a later expiration,
+        // and a zero `now` has eval report every rule however far off it is due
+        let obj = ObjectOpts {
+            transition_status: TRANSITION_COMPLETE.to_string(),
+            ..obj.clone()
+        };
+        let event = self.eval_inner(&obj, OffsetDateTime::UNIX_EPOCH).await;
+        if !event.action.delete() || event.action.delete_restored() {
+            return None;
+        }
+        Some((event.due?, event.rule_id))
+    }
+
     async fn noncurrent_versions_expiration_limit(&self, obj: &ObjectOpts) -> Event {
         if let Some(filter_rules) = self.filter_rules(obj).await {
             for rule in filter_rules.iter() {
@@ -567,7 +584,7 @@ impl Lifecycle for BucketLifecycleConfiguration {
                         return Event {
                             action: IlmAction::DeleteVersionAction,
                             rule_id: rule.id.clone().expect("err"),
-                            noncurrent_days: noncurrent_version_expiration.noncurrent_days.expect("noncurrent_days err.") as u32,
+                            noncurrent_days: noncurrent_version_expiration.noncurrent_days.unwrap_or_default() as u32,
                             newer_noncurrent_versions: newer_noncurrent_versions as usize,
                             due: Some(OffsetDateTime::UNIX_EPOCH),
                             storage_class: "".into(),
@@ -576,7 +593,7 @@ impl Lifecycle for BucketLifecycleConfiguration {
                         return Event {
                             action: IlmAction::DeleteVersionAction,
                             rule_id: rule.id.clone().expect("err"),
-                            noncurrent_days: noncurrent_version_expiration.noncurrent_days.expect("noncurrent_days err.") as u32,
+                            noncurrent_days: noncurrent_version_expiration.noncurrent_days.unwrap_or_default() as u32,
                             newer_noncurrent_versions: 0,
                             due: Some(OffsetDateTime::UNIX_EPOCH),
                             storage_class: "".into(),
@@ -636,24 +653,48 @@ impl LifecycleCalculate for Transition {
     }
 }
 
+/// Picks the versions to expire under NewerNoncurrentVersions: everything past the current version
+/// and the `newer_noncurrent_versions` newest noncurrent ones, once its successor has been around for
+/// `noncurrent_days`. `mod_times` may be in any order, the returned indices point into it.
+pub fn newer_noncurrent_overflow(
+    mod_times: &[Option<OffsetDateTime>],
+    newer_noncurrent_versions: usize,
+    noncurrent_days: u32,
+    now: OffsetDateTime,
+) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..mod_times.len()).collect();
+    order.sort_by(|a, b| mod_times[*b].cmp(&mod_times[*a]));
+
+    let mut overflow = Vec::new();
+    for pos in newer_noncurrent_versions + 1..order.len() {
+        // a version turns noncurrent when the next newer one is written
+        let Some(successor_mod_time) = mod_times[order[pos - 1]] else {
+            continue;
+        };
+        if now >= expected_expiry_time(successor_mod_time, noncurrent_days as i32) {
+            overflow.push(order[pos]);
+        }
+    }
+    overflow
+}
+
 pub fn expected_expiry_time(mod_time: OffsetDateTime, days: i32) -> OffsetDateTime {
     if days == 0 {
         return mod_time;
     }
-    let t = mod_time
-        .to_offset(offset!(-0:00:00))
-        .saturating_add(Duration::days(0 /*days as i64*/)); //debug
     let mut hour = 3600;
     if let Ok(env_ilm_hour) = env::var("_RUSTFS_ILM_HOUR") {
-        if let Ok(num_hour) = env_ilm_hour.parse::<usize>() {
-            hour = num_hour;
+        if let Ok(num_hour) = env_ilm_hour.parse::<i64>() {
+            hour = num_hour.max(1);
         }
     }
-    //t.Truncate(24 * hour)
-    t
+    // the day after `days` have passed, truncated to its midnight
+    let day = 24 * hour;
+    let t = mod_time.to_offset(offset!(-0:00:00)).unix_timestamp() + (days as i64 + 1) * day;
+    OffsetDateTime::from_unix_timestamp(t - t.rem_euclid(day)).unwrap_or(mod_time)
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct ObjectOpts {
     pub name: String,
     pub user_tags: String,
@@ -727,3 +768,106 @@ impl Default for TransitionOptions {
         }
     }
 }
+
+impl TransitionOptions {
+    /// Status to record once the restore these options describe has finished.
+    pub fn completed_restore(&self) -> RestoreStatus {
+        RestoreStatus {
+            ongoing: false,
+            expiry: Some(self.restore_expiry),
+        }
+    }
+}
+
+const RESTORE_EXPIRY_FORMAT: &[time::format_description::FormatItem<'static>] =
+    time::macros::format_description!("[weekday repr:short], [day] [month repr:short] [year] [hour]:[minute]:[second] GMT");
+
+/// Restore state of a transitioned object, the value of the `x-amz-restore` header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestoreStatus {
+    pub ongoing: bool,
+    // only set once the restore has completed
+    pub expiry: Option<OffsetDateTime>,
+}
+
+impl RestoreStatus {
+    pub fn ongoing() -> Self {
+        Self {
+            ongoing: true,
+            expiry: None,
+        }
+    }
+
+    pub fn is_ongoing(&self) -> bool {
+        self.ongoing
+    }
+
+    /// Parses `ongoing-request="true"` or `ongoing-request="false", expiry-date="..."`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (ongoing, rest) = match s.trim().split_once(',') {
+            Some((ongoing, rest)) => (ongoing.trim(), Some(rest.trim())),
+            None => (s.trim(), None),
+        };
+
+        let ongoing = match ongoing.strip_prefix("ongoing-request=")?.trim_matches('"') {
+            "true" => true,
+            "false" => false,
+            _ => return None,
+        };
+
+        match (ongoing, rest) {
+            (true, None) => Some(Self::ongoing()),
+            (false, Some(rest)) => {
+                let expiry = rest.strip_prefix("expiry-date=")?.trim_matches('"');
+                let expiry = time::PrimitiveDateTime::parse(expiry, RESTORE_EXPIRY_FORMAT).ok()?;
+                Some(Self {
+                    ongoing: false,
+                    expiry: Some(expiry.assume_utc()),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Display for RestoreStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.expiry {
+            Some(expiry) if !self.ongoing => {
+                let expiry = expiry
+                    .to_offset(time::UtcOffset::UTC)
+                    .format(RESTORE_EXPIRY_FORMAT)
+                    .map_err(|_| std::fmt::Error)?;
+                write!(f, "ongoing-request=\"false\", expiry-date=\"{expiry}\"")
+            }
+            _ => write!(f, "ongoing-request=\"true\""),
+        }
+    }
+}
+
+/// The `x-amz-expiration` header for an object expired on `due` by the rule `rule_id`.
+pub fn expiration_header(due: OffsetDateTime, rule_id: &str) -> Option<String> {
+    let due = due.to_offset(time::UtcOffset::UTC).format(RESTORE_EXPIRY_FORMAT).ok()?;
+    Some(format!("expiry-date=\"{due}\", rule-id=\"{rule_id}\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_newer_noncurrent_overflow() {
+        let now = OffsetDateTime::now_utc();
+        // current version plus 5 noncurrent ones, one hour apart, deliberately shuffled
+        let ages = [3, 0, 5, 1, 4, 2];
+        let mod_times: Vec<_> = ages.iter().map(|h| Some(now - Duration::hours(*h))).collect();
+
+        let mut overflow = newer_noncurrent_overflow(&mod_times, 2, 0, now);
+        overflow.sort();
+        // the three oldest noncurrent versions, 3h, 4h and 5h old
+        assert_eq!(overflow, vec![0, 2, 4]);
+
+        assert!(newer_noncurrent_overflow(&mod_times, 5, 0, now).is_empty());
+        assert_eq!(newer_noncurrent_overflow(&mod_times, 4, 0, now), vec![2]);
+    }
+}
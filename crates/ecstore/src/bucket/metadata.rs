@@ -19,6 +19,7 @@ use super::versioning::VersioningApi;
 use byteorder::{BigEndian, ByteOrder, LittleEndian};
 use rmp_serde::Serializer as rmpSerializer;
 use rustfs_policy::policy::BucketPolicy;
+use rustfs_utils::crypto::{base64_decode, base64_encode};
 use s3s::dto::{
     BucketLifecycleConfiguration, NotificationConfiguration, ObjectLockConfiguration, ReplicationConfiguration,
     ServerSideEncryptionConfiguration, Tagging, VersioningConfiguration,
@@ -27,15 +28,19 @@ use serde::Serializer;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 use time::OffsetDateTime;
-use tracing::error;
+use tracing::{error, warn};
 
 use crate::bucket::target::BucketTarget;
 use crate::bucket::utils::deserialize;
 use crate::config::com::{read_config, save_config};
 use crate::error::{Error, Result};
+use crate::global::{get_global_action_cred, get_global_targets_secret_key};
 use crate::new_object_layer_fn;
 
+use crate::compress::CompressionOpts;
 use crate::disk::BUCKET_META_PREFIX;
 use crate::store::ECStore;
 
@@ -53,6 +58,22 @@ pub const OBJECT_LOCK_CONFIG: &str = "object-lock.xml";
 pub const BUCKET_VERSIONING_CONFIG: &str = "versioning.xml";
 pub const BUCKET_REPLICATION_CONFIG: &str = "replication.xml";
 pub const BUCKET_TARGETS_FILE: &str = "bucket-targets.json";
+pub const BUCKET_TARGETS_META_FILE: &str = "bucket-targets-meta.json";
+pub const BUCKET_WRITE_QUORUM_CONFIG: &str = "write-quorum.json";
+pub const BUCKET_COMPRESSION_CONFIG: &str = "compression.json";
+
+pub const DEFAULT_MAX_CLOCK_SKEW: Duration = Duration::from_secs(15 * 60);
+
+static MAX_CLOCK_SKEW_SECS: AtomicU64 = AtomicU64::new(DEFAULT_MAX_CLOCK_SKEW.as_secs());
+
+/// Set how far ahead of the local clock a bucket config timestamp may be, zero disables the check
+pub fn set_max_clock_skew(skew: Duration) {
+    MAX_CLOCK_SKEW_SECS.store(skew.as_secs(), Ordering::SeqCst);
+}
+
+pub fn get_max_clock_skew() -> Duration {
+    Duration::from_secs(MAX_CLOCK_SKEW_SECS.load(Ordering::SeqCst))
+}
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(rename_all = "PascalCase", default)]
@@ -71,6 +92,8 @@ pub struct BucketMetadata {
     pub replication_config_xml: Vec<u8>,
     pub bucket_targets_config_json: Vec<u8>,
     pub bucket_targets_config_meta_json: Vec<u8>,
+    pub write_quorum_config_json: Vec<u8>,
+    pub compression_config_json: Vec<u8>,
 
     pub policy_config_updated_at: OffsetDateTime,
     pub object_lock_config_updated_at: OffsetDateTime,
@@ -83,6 +106,8 @@ pub struct BucketMetadata {
     pub notification_config_updated_at: OffsetDateTime,
     pub bucket_targets_config_updated_at: OffsetDateTime,
     pub bucket_targets_config_meta_updated_at: OffsetDateTime,
+    pub write_quorum_config_updated_at: OffsetDateTime,
+    pub compression_config_updated_at: OffsetDateTime,
 
     #[serde(skip)]
     pub new_field_updated_at: OffsetDateTime,
@@ -109,6 +134,10 @@ pub struct BucketMetadata {
     pub bucket_target_config: Option<BucketTargets>,
     #[serde(skip)]
     pub bucket_target_config_meta: Option<HashMap<String, String>>,
+    #[serde(skip)]
+    pub write_quorum: Option<usize>,
+    #[serde(skip)]
+    pub compression_config: Option<CompressionOpts>,
 }
 
 impl Default for BucketMetadata {
@@ -128,6 +157,8 @@ impl Default for BucketMetadata {
             replication_config_xml: Default::default(),
             bucket_targets_config_json: Default::default(),
             bucket_targets_config_meta_json: Default::default(),
+            write_quorum_config_json: Default::default(),
+            compression_config_json: Default::default(),
             policy_config_updated_at: OffsetDateTime::UNIX_EPOCH,
             object_lock_config_updated_at: OffsetDateTime::UNIX_EPOCH,
             encryption_config_updated_at: OffsetDateTime::UNIX_EPOCH,
@@ -139,6 +170,8 @@ impl Default for BucketMetadata {
             notification_config_updated_at: OffsetDateTime::UNIX_EPOCH,
             bucket_targets_config_updated_at: OffsetDateTime::UNIX_EPOCH,
             bucket_targets_config_meta_updated_at: OffsetDateTime::UNIX_EPOCH,
+            write_quorum_config_updated_at: OffsetDateTime::UNIX_EPOCH,
+            compression_config_updated_at: OffsetDateTime::UNIX_EPOCH,
             new_field_updated_at: OffsetDateTime::UNIX_EPOCH,
             policy_config: Default::default(),
             notification_config: Default::default(),
@@ -151,6 +184,8 @@ impl Default for BucketMetadata {
             replication_config: Default::default(),
             bucket_target_config: Default::default(),
             bucket_target_config_meta: Default::default(),
+            write_quorum: Default::default(),
+            compression_config: Default::default(),
         }
     }
 }
@@ -174,7 +209,7 @@ impl BucketMetadata {
     }
 
     pub fn object_locking(&self) -> bool {
-        self.lock_enabled || (self.versioning_config.as_ref().is_some_and(|v| v.enabled()))
+        self.lock_enabled || self.object_lock_config.as_ref().is_some_and(|v| v.enabled())
     }
 
     pub fn marshal_msg(&self) -> Result<Vec<u8>> {
@@ -250,10 +285,69 @@ impl BucketMetadata {
         if self.bucket_targets_config_meta_updated_at == OffsetDateTime::UNIX_EPOCH {
             self.bucket_targets_config_meta_updated_at = self.created
         }
+        if self.write_quorum_config_updated_at == OffsetDateTime::UNIX_EPOCH {
+            self.write_quorum_config_updated_at = self.created
+        }
+        if self.compression_config_updated_at == OffsetDateTime::UNIX_EPOCH {
+            self.compression_config_updated_at = self.created
+        }
+    }
+
+    // A timestamp from a node whose clock runs ahead would keep winning over newer updates.
+    fn check_clock_skew(&self, updated: OffsetDateTime, now: OffsetDateTime) -> Result<()> {
+        let skew = get_max_clock_skew();
+        if !skew.is_zero() && updated > now + skew {
+            error!("bucket {} config timestamp {} is ahead of the local clock {}", self.name, updated, now);
+            return Err(Error::ClockSkew);
+        }
+
+        Ok(())
+    }
+
+    // Timestamps stored ahead of the local clock are brought back to it, so the config they
+    // belong to can be replaced again.
+    fn clamp_future_timestamps(&mut self, now: OffsetDateTime) {
+        let skew = get_max_clock_skew();
+        if skew.is_zero() {
+            return;
+        }
+
+        let limit = now + skew;
+        let timestamps = [
+            &mut self.created,
+            &mut self.policy_config_updated_at,
+            &mut self.object_lock_config_updated_at,
+            &mut self.encryption_config_updated_at,
+            &mut self.tagging_config_updated_at,
+            &mut self.quota_config_updated_at,
+            &mut self.replication_config_updated_at,
+            &mut self.versioning_config_updated_at,
+            &mut self.lifecycle_config_updated_at,
+            &mut self.notification_config_updated_at,
+            &mut self.bucket_targets_config_updated_at,
+            &mut self.bucket_targets_config_meta_updated_at,
+            &mut self.write_quorum_config_updated_at,
+            &mut self.compression_config_updated_at,
+        ];
+        for ts in timestamps.into_iter().filter(|ts| **ts > limit) {
+            warn!(
+                "bucket {} metadata timestamp {} is ahead of the local clock, reset to {}",
+                self.name, ts, now
+            );
+            *ts = now;
+        }
     }
 
     pub fn update_config(&mut self, config_file: &str, data: Vec<u8>) -> Result<OffsetDateTime> {
-        let updated = OffsetDateTime::now_utc();
+        self.update_config_at(config_file, data, OffsetDateTime::now_utc())
+    }
+
+    /// Replaces a config with one updated at `updated`, fails with `Error::ClockSkew` when that is
+    /// further ahead of the local clock than the allowed skew.
+    pub fn update_config_at(&mut self, config_file: &str, data: Vec<u8>, updated: OffsetDateTime) -> Result<OffsetDateTime> {
+        let now = OffsetDateTime::now_utc();
+        self.check_clock_skew(updated, now)?;
+        self.clamp_future_timestamps(now);
 
         match config_file {
             BUCKET_POLICY_CONFIG => {
@@ -299,6 +393,18 @@ impl BucketMetadata {
                 self.bucket_targets_config_json = data.clone();
                 self.bucket_targets_config_updated_at = updated;
             }
+            BUCKET_TARGETS_META_FILE => {
+                self.bucket_targets_config_meta_json = data;
+                self.bucket_targets_config_meta_updated_at = updated;
+            }
+            BUCKET_WRITE_QUORUM_CONFIG => {
+                self.write_quorum_config_json = data;
+                self.write_quorum_config_updated_at = updated;
+            }
+            BUCKET_COMPRESSION_CONFIG => {
+                self.compression_config_json = data;
+                self.compression_config_updated_at = updated;
+            }
             _ => return Err(Error::other(format!("config file not found : {config_file}"))),
         }
 
@@ -314,6 +420,9 @@ impl BucketMetadata {
             return Err(Error::other("errServerNotInitialized"));
         };
 
+        self.clamp_future_timestamps(OffsetDateTime::now_utc());
+        // configs written before secrets were sealed are sealed on their next save
+        self.bucket_targets_config_json = seal_target_secrets(&self.bucket_targets_config_json, target_secrets_key().as_deref())?;
         self.parse_all_configs(store.clone())?;
 
         let mut buf: Vec<u8> = vec![0; 4];
@@ -362,11 +471,28 @@ impl BucketMetadata {
         }
         //let temp = self.bucket_targets_config_json.clone();
         if !self.bucket_targets_config_json.is_empty() {
-            let arr: Vec<BucketTarget> = serde_json::from_slice(&self.bucket_targets_config_json)?;
+            let mut arr: Vec<BucketTarget> = serde_json::from_slice(&self.bucket_targets_config_json)?;
+            unseal_target_secrets(&mut arr, target_secrets_key().as_deref())?;
             self.bucket_target_config = Some(BucketTargets { targets: arr });
         } else {
             self.bucket_target_config = Some(BucketTargets::default())
         }
+        self.bucket_target_config_meta = if self.bucket_targets_config_meta_json.is_empty() {
+            None
+        } else {
+            Some(serde_json::from_slice(&self.bucket_targets_config_meta_json)?)
+        };
+        // cleared by writing an empty config, so reset rather than keep the last value
+        self.write_quorum = if self.write_quorum_config_json.is_empty() {
+            None
+        } else {
+            Some(serde_json::from_slice(&self.write_quorum_config_json)?)
+        };
+        self.compression_config = if self.compression_config_json.is_empty() {
+            None
+        } else {
+            Some(serde_json::from_slice(&self.compression_config_json)?)
+        };
 
         Ok(())
     }
@@ -419,6 +545,57 @@ async fn read_bucket_metadata(api: Arc<ECStore>, bucket: &str) -> Result<BucketM
     Ok(bm)
 }
 
+// marks a target secret key encrypted with the targets secret key, plain ones are from older configs
+const SEALED_SECRET_PREFIX: &str = "sealed:";
+
+// A key of its own keeps the target secrets readable when the root credentials are rotated,
+// without one they are sealed with the root secret key, as the IAM config is.
+fn target_secrets_key() -> Option<String> {
+    get_global_targets_secret_key()
+        .map(str::to_owned)
+        .or_else(|| get_global_action_cred().map(|cred| cred.secret_key))
+}
+
+fn require_key(key: Option<&str>) -> Result<&str> {
+    key.ok_or_else(|| Error::other("no key to encrypt bucket target secrets with"))
+}
+
+/// Encrypts the secret keys of the remote targets in a bucket targets config, ones already
+/// encrypted are kept as they are.
+fn seal_target_secrets(json: &[u8], key: Option<&str>) -> Result<Vec<u8>> {
+    if json.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut targets: Vec<BucketTarget> = serde_json::from_slice(json)?;
+    for creds in targets.iter_mut().filter_map(|t| t.credentials.as_mut()) {
+        if creds.secret_key.is_empty() || creds.secret_key.starts_with(SEALED_SECRET_PREFIX) {
+            continue;
+        }
+
+        let key = require_key(key)?;
+        let sealed = rustfs_crypto::encrypt_data(key.as_bytes(), creds.secret_key.as_bytes()).map_err(Error::other)?;
+        creds.secret_key = format!("{SEALED_SECRET_PREFIX}{}", base64_encode(&sealed));
+    }
+
+    Ok(serde_json::to_vec(&targets)?)
+}
+
+fn unseal_target_secrets(targets: &mut [BucketTarget], key: Option<&str>) -> Result<()> {
+    for creds in targets.iter_mut().filter_map(|t| t.credentials.as_mut()) {
+        let Some(sealed) = creds.secret_key.strip_prefix(SEALED_SECRET_PREFIX) else {
+            continue;
+        };
+
+        let key = require_key(key)?;
+        let sealed = base64_decode(sealed.as_bytes()).map_err(Error::other)?;
+        let secret = rustfs_crypto::decrypt_data(key.as_bytes(), &sealed).map_err(Error::other)?;
+        creds.secret_key = String::from_utf8(secret).map_err(Error::other)?;
+    }
+
+    Ok(())
+}
+
 fn _write_time<S>(t: &OffsetDateTime, s: S) -> std::result::Result<S::Ok, S::Error>
 where
     S: Serializer,
@@ -441,6 +618,7 @@ mod test {
 
     #[tokio::test]
     async fn marshal_msg() {
+        let _guard = crate::store::test_util::GLOBAL_SWITCH_LOCK.lock().await;
         // write_time(OffsetDateTime::UNIX_EPOCH).unwrap();
 
         let bm = BucketMetadata::new("dada");
@@ -451,4 +629,87 @@ mod test {
 
         assert_eq!(bm.name, new.name);
     }
+
+    #[test]
+    fn target_secrets_sealed_at_rest() {
+        use crate::bucket::target::Credentials;
+
+        let key = Some("test-targets-secret-key");
+        let target = BucketTarget {
+            credentials: Some(Credentials {
+                access_key: "remote-access".to_string(),
+                secret_key: "remote-secret-key".to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let plain = serde_json::to_vec(&vec![target]).unwrap();
+        assert!(seal_target_secrets(&plain, None).is_err());
+
+        let mut bm = BucketMetadata::new("targets");
+        bm.bucket_targets_config_json = seal_target_secrets(&plain, key).unwrap();
+        let buf = bm.marshal_msg().unwrap();
+        assert!(!buf.windows(17).any(|w| w == b"remote-secret-key"));
+
+        // sealing again leaves sealed secrets alone
+        assert_eq!(
+            seal_target_secrets(&bm.bucket_targets_config_json, key).unwrap(),
+            bm.bucket_targets_config_json
+        );
+
+        let new = BucketMetadata::unmarshal(&buf).unwrap();
+        let mut targets: Vec<BucketTarget> = serde_json::from_slice(&new.bucket_targets_config_json).unwrap();
+        assert!(unseal_target_secrets(&mut targets.clone(), Some("another-key")).is_err());
+        unseal_target_secrets(&mut targets, key).unwrap();
+        let creds = targets[0].credentials.clone().unwrap();
+        assert_eq!(creds.access_key, "remote-access");
+        assert_eq!(creds.secret_key, "remote-secret-key");
+
+        // plain configs from before sealing still load
+        let mut old = BucketMetadata::new("targets");
+        old.bucket_targets_config_json = plain;
+        old.parse_all_configs(crate::store::test_util::test_store()).unwrap();
+        let creds = old.bucket_target_config.unwrap().targets[0].credentials.clone().unwrap();
+        assert_eq!(creds.secret_key, "remote-secret-key");
+    }
+
+    #[test]
+    fn update_config_rejects_future_timestamp() {
+        let mut bm = BucketMetadata::new("skew");
+        let now = OffsetDateTime::now_utc();
+
+        // A little drift between nodes is tolerated.
+        bm.update_config_at(BUCKET_TAGGING_CONFIG, Vec::new(), now + Duration::from_secs(60))
+            .unwrap();
+
+        let err = bm
+            .update_config_at(
+                BUCKET_POLICY_CONFIG,
+                b"{}".to_vec(),
+                now + DEFAULT_MAX_CLOCK_SKEW + Duration::from_secs(60),
+            )
+            .unwrap_err();
+        assert_eq!(err, Error::ClockSkew);
+        assert!(bm.policy_config_json.is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn future_stored_timestamp_is_reset() {
+        let _guard = crate::store::test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let _store = crate::store::test_util::test_store();
+
+        let mut bm = BucketMetadata::new("skew-stored");
+        let future = OffsetDateTime::now_utc() + DEFAULT_MAX_CLOCK_SKEW + Duration::from_secs(3600);
+        bm.policy_config_updated_at = future;
+        bm.tagging_config_updated_at = future;
+
+        // the bad timestamp does not block the update that replaces it, or others
+        let updated = bm.update_config(BUCKET_POLICY_CONFIG, Vec::new()).unwrap();
+        assert_eq!(bm.policy_config_updated_at, updated);
+        assert!(bm.tagging_config_updated_at < future);
+
+        bm.tagging_config_updated_at = future;
+        bm.save().await.unwrap();
+        assert!(bm.tagging_config_updated_at < future);
+    }
 }
@@ -38,6 +38,7 @@ use tracing::error;
 use super::metadata::{BucketMetadata, load_bucket_metadata};
 use super::quota::BucketQuota;
 use super::target::BucketTargets;
+use crate::compress::CompressionOpts;
 
 use lazy_static::lazy_static;
 
@@ -104,6 +105,20 @@ pub async fn get_quota_config(bucket: &str) -> Result<(BucketQuota, OffsetDateTi
     bucket_meta_sys.get_quota_config(bucket).await
 }
 
+pub async fn get_write_quorum_config(bucket: &str) -> Result<(usize, OffsetDateTime)> {
+    let bucket_meta_sys_lock = get_bucket_metadata_sys()?;
+    let bucket_meta_sys = bucket_meta_sys_lock.read().await;
+
+    bucket_meta_sys.get_write_quorum_config(bucket).await
+}
+
+pub async fn get_compression_config(bucket: &str) -> Result<(CompressionOpts, OffsetDateTime)> {
+    let bucket_meta_sys_lock = get_bucket_metadata_sys()?;
+    let bucket_meta_sys = bucket_meta_sys_lock.read().await;
+
+    bucket_meta_sys.get_compression_config(bucket).await
+}
+
 pub async fn get_bucket_targets_config(bucket: &str) -> Result<BucketTargets> {
     let bucket_meta_sys_lock = get_bucket_metadata_sys()?;
     let bucket_meta_sys = bucket_meta_sys_lock.read().await;
@@ -520,6 +535,26 @@ impl BucketMetadataSys {
         }
     }
 
+    pub async fn get_write_quorum_config(&self, bucket: &str) -> Result<(usize, OffsetDateTime)> {
+        let (bm, _) = self.get_config(bucket).await?;
+
+        if let Some(quorum) = bm.write_quorum {
+            Ok((quorum, bm.write_quorum_config_updated_at))
+        } else {
+            Err(Error::ConfigNotFound)
+        }
+    }
+
+    pub async fn get_compression_config(&self, bucket: &str) -> Result<(CompressionOpts, OffsetDateTime)> {
+        let (bm, _) = self.get_config(bucket).await?;
+
+        if let Some(config) = &bm.compression_config {
+            Ok((config.clone(), bm.compression_config_updated_at))
+        } else {
+            Err(Error::ConfigNotFound)
+        }
+    }
+
     pub async fn get_replication_config(&self, bucket: &str) -> Result<(ReplicationConfiguration, OffsetDateTime)> {
         let (bm, reload) = self.get_config(bucket).await?;
 
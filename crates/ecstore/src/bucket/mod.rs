@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod bandwidth;
+pub mod encryption;
 pub mod error;
 pub mod lifecycle;
 pub mod metadata;
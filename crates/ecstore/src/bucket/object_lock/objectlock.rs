@@ -18,10 +18,12 @@ use time::{OffsetDateTime, format_description};
 use s3s::dto::{Date, ObjectLockLegalHold, ObjectLockLegalHoldStatus, ObjectLockRetention, ObjectLockRetentionMode};
 use s3s::header::{X_AMZ_OBJECT_LOCK_LEGAL_HOLD, X_AMZ_OBJECT_LOCK_MODE, X_AMZ_OBJECT_LOCK_RETAIN_UNTIL_DATE};
 
+use crate::error::{Error, Result};
+
 const _ERR_MALFORMED_BUCKET_OBJECT_CONFIG: &str = "invalid bucket object lock config";
 const _ERR_INVALID_RETENTION_DATE: &str = "date must be provided in ISO 8601 format";
 const _ERR_PAST_OBJECTLOCK_RETAIN_DATE: &str = "the retain until date must be in the future";
-const _ERR_UNKNOWN_WORMMODE_DIRECTIVE: &str = "unknown WORM mode directive";
+const ERR_UNKNOWN_WORMMODE_DIRECTIVE: &str = "unknown WORM mode directive";
 const _ERR_OBJECTLOCK_MISSING_CONTENT_MD5: &str =
     "content-MD5 HTTP header is required for Put Object requests with Object Lock parameters";
 const _ERR_OBJECTLOCK_INVALID_HEADERS: &str =
@@ -32,15 +34,27 @@ pub fn utc_now_ntp() -> OffsetDateTime {
     OffsetDateTime::now_utc()
 }
 
+// Lock headers are stored as sent, so the key case is not fixed. A cleared header is empty.
+fn lock_meta<'a>(meta: &'a HashMap<String, String>, key: &str) -> Option<&'a String> {
+    meta.get(key)
+        .or_else(|| meta.iter().find(|(k, _)| k.eq_ignore_ascii_case(key)).map(|(_, v)| v))
+        .filter(|v| !v.is_empty())
+}
+
 pub fn get_object_retention_meta(meta: HashMap<String, String>) -> ObjectLockRetention {
     let mut retain_until_date: Date = Date::from(OffsetDateTime::UNIX_EPOCH);
 
-    let mut mode_str = meta.get(X_AMZ_OBJECT_LOCK_MODE.as_str().to_lowercase().as_str());
-    if mode_str.is_none() {
-        mode_str = Some(&meta[X_AMZ_OBJECT_LOCK_MODE.as_str()]);
-    }
-    let mode = if let Some(mode_str) = mode_str {
-        parse_ret_mode(mode_str.as_str())
+    let mode = if let Some(mode_str) = lock_meta(&meta, X_AMZ_OBJECT_LOCK_MODE.as_str()) {
+        // stored lock headers were validated on the way in, an unknown value locks nothing
+        match parse_ret_mode(mode_str.as_str()) {
+            Ok(mode) => mode,
+            Err(_) => {
+                return ObjectLockRetention {
+                    mode: None,
+                    retain_until_date: None,
+                };
+            }
+        }
     } else {
         return ObjectLockRetention {
             mode: None,
@@ -48,14 +62,9 @@ pub fn get_object_retention_meta(meta: HashMap<String, String>) -> ObjectLockRet
         };
     };
 
-    let mut till_str = meta.get(X_AMZ_OBJECT_LOCK_RETAIN_UNTIL_DATE.as_str().to_lowercase().as_str());
-    if till_str.is_none() {
-        till_str = Some(&meta[X_AMZ_OBJECT_LOCK_RETAIN_UNTIL_DATE.as_str()]);
-    }
-    if let Some(till_str) = till_str {
-        let t = OffsetDateTime::parse(till_str, &format_description::well_known::Iso8601::DEFAULT);
-        if t.is_err() {
-            retain_until_date = Date::from(t.expect("err")); //TODO: utc
+    if let Some(till_str) = lock_meta(&meta, X_AMZ_OBJECT_LOCK_RETAIN_UNTIL_DATE.as_str()) {
+        if let Ok(t) = OffsetDateTime::parse(till_str, &format_description::well_known::Iso8601::DEFAULT) {
+            retain_until_date = Date::from(t);
         }
     }
     ObjectLockRetention {
@@ -65,30 +74,26 @@ pub fn get_object_retention_meta(meta: HashMap<String, String>) -> ObjectLockRet
 }
 
 pub fn get_object_legalhold_meta(meta: HashMap<String, String>) -> ObjectLockLegalHold {
-    let mut hold_str = meta.get(X_AMZ_OBJECT_LOCK_LEGAL_HOLD.as_str().to_lowercase().as_str());
-    if hold_str.is_none() {
-        hold_str = Some(&meta[X_AMZ_OBJECT_LOCK_LEGAL_HOLD.as_str()]);
-    }
-    if let Some(hold_str) = hold_str {
+    if let Some(hold_str) = lock_meta(&meta, X_AMZ_OBJECT_LOCK_LEGAL_HOLD.as_str()) {
         return ObjectLockLegalHold {
-            status: Some(parse_legalhold_status(hold_str)),
+            status: parse_legalhold_status(hold_str).ok(),
         };
     }
     ObjectLockLegalHold { status: None }
 }
 
-pub fn parse_ret_mode(mode_str: &str) -> ObjectLockRetentionMode {
+pub fn parse_ret_mode(mode_str: &str) -> Result<ObjectLockRetentionMode> {
     match mode_str.to_uppercase().as_str() {
-        "GOVERNANCE" => ObjectLockRetentionMode::from_static(ObjectLockRetentionMode::GOVERNANCE),
-        "COMPLIANCE" => ObjectLockRetentionMode::from_static(ObjectLockRetentionMode::COMPLIANCE),
-        _ => unreachable!(),
+        "GOVERNANCE" => Ok(ObjectLockRetentionMode::from_static(ObjectLockRetentionMode::GOVERNANCE)),
+        "COMPLIANCE" => Ok(ObjectLockRetentionMode::from_static(ObjectLockRetentionMode::COMPLIANCE)),
+        _ => Err(Error::other(format!("{ERR_UNKNOWN_WORMMODE_DIRECTIVE}: {mode_str}"))),
     }
 }
 
-pub fn parse_legalhold_status(hold_str: &str) -> ObjectLockLegalHoldStatus {
-    match hold_str {
-        "ON" => ObjectLockLegalHoldStatus::from_static(ObjectLockLegalHoldStatus::ON),
-        "OFF" => ObjectLockLegalHoldStatus::from_static(ObjectLockLegalHoldStatus::OFF),
-        _ => unreachable!(),
+pub fn parse_legalhold_status(hold_str: &str) -> Result<ObjectLockLegalHoldStatus> {
+    match hold_str.to_uppercase().as_str() {
+        "ON" => Ok(ObjectLockLegalHoldStatus::from_static(ObjectLockLegalHoldStatus::ON)),
+        "OFF" => Ok(ObjectLockLegalHoldStatus::from_static(ObjectLockLegalHoldStatus::OFF)),
+        _ => Err(Error::other(format!("unknown legal hold status: {hold_str}"))),
     }
 }
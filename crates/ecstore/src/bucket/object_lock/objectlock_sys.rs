@@ -12,15 +12,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::sync::Arc;
-use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+use time::{Duration, OffsetDateTime};
 
-use s3s::dto::{DefaultRetention, ObjectLockLegalHoldStatus, ObjectLockRetentionMode};
+use s3s::dto::{DefaultRetention, ObjectLockConfiguration, ObjectLockLegalHoldStatus, ObjectLockRetentionMode};
+use s3s::header::{X_AMZ_OBJECT_LOCK_MODE, X_AMZ_OBJECT_LOCK_RETAIN_UNTIL_DATE};
 
 use crate::bucket::metadata_sys::get_object_lock_config;
-use crate::store_api::ObjectInfo;
+use crate::error::{Result, StorageError};
+use crate::store_api::{ObjectInfo, ObjectOptions};
 
-use super::objectlock;
+use super::{ObjectLockApi, objectlock};
 
 pub struct BucketObjectLockSys {}
 
@@ -65,3 +69,101 @@ pub fn enforce_retention_for_deletion(obj_info: &ObjectInfo) -> bool {
     }
     false
 }
+
+/// Reports whether deleting `obj_info` is blocked like [`enforce_retention_for_deletion`], except that an
+/// unexpired GOVERNANCE retention gives way to `bypass_governance`.
+pub fn enforce_retention_bypass_for_delete(obj_info: &ObjectInfo, bypass_governance: bool) -> bool {
+    if !bypass_governance {
+        return enforce_retention_for_deletion(obj_info);
+    }
+    if obj_info.delete_marker {
+        return false;
+    }
+
+    let lhold = objectlock::get_object_legalhold_meta(obj_info.user_defined.clone());
+    if lhold.status.is_some_and(|st| st.as_str() == ObjectLockLegalHoldStatus::ON) {
+        return true;
+    }
+
+    let ret = objectlock::get_object_retention_meta(obj_info.user_defined.clone());
+    ret.mode
+        .is_some_and(|mode| mode.as_str() == ObjectLockRetentionMode::COMPLIANCE)
+        && ret
+            .retain_until_date
+            .is_some_and(|until| OffsetDateTime::from(until) > objectlock::utc_now_ntp())
+}
+
+/// Checks a put that replaces `info` in place. A put that adds a new version leaves the
+/// locked one untouched and is always allowed, whatever hold or retention it carries.
+pub fn check_put_against_lock(info: &ObjectInfo, cfg: &ObjectLockConfiguration) -> Result<()> {
+    if cfg.enabled() && enforce_retention_for_deletion(info) {
+        return Err(StorageError::ObjectLocked(info.bucket.clone(), info.name.clone()));
+    }
+    Ok(())
+}
+
+/// Gives a put that carries no retention of its own the bucket's default retention, counted
+/// from `now`. A year of default retention is 365 days.
+pub fn apply_default_retention(cfg: &ObjectLockConfiguration, opts: &mut ObjectOptions, now: OffsetDateTime) {
+    apply_default_retention_meta(cfg, &mut opts.user_defined, now);
+}
+
+/// [`apply_default_retention`] for the metadata a copy writes to its destination.
+pub fn apply_default_retention_meta(cfg: &ObjectLockConfiguration, meta: &mut HashMap<String, String>, now: OffsetDateTime) {
+    if !cfg.enabled() {
+        return;
+    }
+    let own = objectlock::get_object_retention_meta(meta.clone());
+    if own.mode.is_some() {
+        return;
+    }
+
+    let Some(default) = cfg.rule.as_ref().and_then(|rule| rule.default_retention.as_ref()) else {
+        return;
+    };
+    let Some(mode) = &default.mode else {
+        return;
+    };
+    let days = match (default.days, default.years) {
+        (Some(days), _) => i64::from(days),
+        (None, Some(years)) => i64::from(years) * 365,
+        (None, None) => return,
+    };
+    let Ok(until) = (now + Duration::days(days)).format(&Rfc3339) else {
+        return;
+    };
+
+    meta.insert(X_AMZ_OBJECT_LOCK_MODE.as_str().to_owned(), mode.as_str().to_owned());
+    meta.insert(X_AMZ_OBJECT_LOCK_RETAIN_UNTIL_DATE.as_str().to_owned(), until);
+}
+
+/// Clears the retention in a version's metadata, returns whether there was one. An
+/// active GOVERNANCE retention needs `bypass`, an active COMPLIANCE retention stays.
+pub fn clear_retention_meta(bucket: &str, object: &str, meta: &mut HashMap<String, String>, bypass: bool) -> Result<bool> {
+    let ret = objectlock::get_object_retention_meta(meta.clone());
+    let Some(mode) = ret.mode else {
+        return Ok(false);
+    };
+
+    let active = ret
+        .retain_until_date
+        .is_some_and(|until| OffsetDateTime::from(until) > objectlock::utc_now_ntp());
+    if active {
+        if mode.as_str() == ObjectLockRetentionMode::COMPLIANCE {
+            return Err(StorageError::ComplianceModeLocked(bucket.to_owned(), object.to_owned()));
+        }
+        if !bypass {
+            return Err(StorageError::ObjectLocked(bucket.to_owned(), object.to_owned()));
+        }
+    }
+
+    // metadata updates merge into the stored version, an empty value is how a key is cleared
+    for (k, v) in meta.iter_mut() {
+        if k.eq_ignore_ascii_case(X_AMZ_OBJECT_LOCK_MODE.as_str())
+            || k.eq_ignore_ascii_case(X_AMZ_OBJECT_LOCK_RETAIN_UNTIL_DATE.as_str())
+        {
+            v.clear();
+        }
+    }
+    Ok(true)
+}
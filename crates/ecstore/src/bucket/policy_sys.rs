@@ -14,9 +14,69 @@
 
 use super::{error::BucketMetadataError, metadata_sys::get_bucket_metadata_sys};
 use crate::error::Result;
+use rustfs_policy::policy::action::{Action, S3Action};
 use rustfs_policy::policy::{BucketPolicy, BucketPolicyArgs};
+use std::collections::HashMap;
 use tracing::warn;
 
+/// A request to check against a bucket policy.
+#[derive(Debug, Clone)]
+pub struct PolicyArgs<'a> {
+    pub principal: &'a str,
+    pub action: Action,
+    /// `bucket` or `bucket/object`
+    pub resource: &'a str,
+    pub conditions: &'a HashMap<String, Vec<String>>,
+}
+
+/// Evaluates `args` against `policy`, a matching Deny statement always wins over any Allow.
+pub fn is_allowed(policy: &BucketPolicy, args: &PolicyArgs) -> bool {
+    let (bucket, object) = args.resource.split_once('/').unwrap_or((args.resource, ""));
+
+    policy.is_allowed(&BucketPolicyArgs {
+        account: args.principal,
+        groups: &None,
+        action: args.action,
+        bucket,
+        conditions: args.conditions,
+        is_owner: false,
+        object,
+    })
+}
+
+/// What an unauthenticated client may do with an object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessLevel {
+    None,
+    ReadOnly,
+    WriteOnly,
+    ReadWrite,
+}
+
+/// Resolves the access the policy grants the `*` principal on `object` (`bucket/object`).
+/// Statements guarded by conditions are not counted, their request context is unknown here.
+pub fn anonymous_access(policy: &BucketPolicy, object: &str) -> AccessLevel {
+    let conditions = HashMap::new();
+    let allowed = |action| {
+        is_allowed(
+            policy,
+            &PolicyArgs {
+                principal: "*",
+                action: Action::S3Action(action),
+                resource: object,
+                conditions: &conditions,
+            },
+        )
+    };
+
+    match (allowed(S3Action::GetObjectAction), allowed(S3Action::PutObjectAction)) {
+        (true, true) => AccessLevel::ReadWrite,
+        (true, false) => AccessLevel::ReadOnly,
+        (false, true) => AccessLevel::WriteOnly,
+        (false, false) => AccessLevel::None,
+    }
+}
+
 pub struct PolicySys {}
 
 impl PolicySys {
@@ -42,3 +102,120 @@ impl PolicySys {
         Ok(cfg)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICY: &str = r#"{
+        "Version": "2012-10-17",
+        "Statement": [
+            {
+                "Effect": "Allow",
+                "Principal": {"AWS": ["*"]},
+                "Action": ["s3:GetObject", "s3:PutObject"],
+                "Resource": ["arn:aws:s3:::mybucket/*"]
+            },
+            {
+                "Effect": "Deny",
+                "Principal": {"AWS": ["*"]},
+                "Action": ["s3:GetObject"],
+                "Resource": ["arn:aws:s3:::mybucket/private/*"]
+            },
+            {
+                "Effect": "Allow",
+                "Principal": {"AWS": ["*"]},
+                "Action": ["s3:ListBucket"],
+                "Resource": ["arn:aws:s3:::mybucket"],
+                "Condition": {"StringEquals": {"s3:prefix": ["public/"]}}
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_is_allowed() {
+        let policy: BucketPolicy = serde_json::from_str(POLICY).unwrap();
+
+        let get = Action::S3Action(S3Action::GetObjectAction);
+        let put = Action::S3Action(S3Action::PutObjectAction);
+        let list = Action::S3Action(S3Action::ListBucketAction);
+        let delete = Action::S3Action(S3Action::DeleteObjectAction);
+        let public_prefix = HashMap::from([("prefix".to_string(), vec!["public/".to_string()])]);
+        let other_prefix = HashMap::from([("prefix".to_string(), vec!["private/".to_string()])]);
+        let none = HashMap::new();
+
+        let cases = [
+            (get, "mybucket/docs/a.txt", &none, true),
+            (put, "mybucket/private/a.txt", &none, true),
+            // explicit deny wins over the bucket-wide allow
+            (get, "mybucket/private/a.txt", &none, false),
+            // no statement grants it
+            (delete, "mybucket/docs/a.txt", &none, false),
+            (get, "otherbucket/a.txt", &none, false),
+            (list, "mybucket", &public_prefix, true),
+            // condition mismatch
+            (list, "mybucket", &other_prefix, false),
+            (list, "mybucket", &none, false),
+        ];
+
+        for (action, resource, conditions, expected) in cases {
+            let args = PolicyArgs {
+                principal: "alice",
+                action,
+                resource,
+                conditions,
+            };
+            assert_eq!(is_allowed(&policy, &args), expected, "{action:?} on {resource}");
+        }
+    }
+
+    #[test]
+    fn test_anonymous_access() {
+        let public_read: BucketPolicy = serde_json::from_str(
+            r#"{
+                "Version": "2012-10-17",
+                "Statement": [{
+                    "Effect": "Allow",
+                    "Principal": {"AWS": ["*"]},
+                    "Action": ["s3:GetObject"],
+                    "Resource": ["arn:aws:s3:::mybucket/public/*"]
+                }]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(anonymous_access(&public_read, "mybucket/public/a.txt"), AccessLevel::ReadOnly);
+        assert_eq!(anonymous_access(&public_read, "mybucket/public/nested/b.txt"), AccessLevel::ReadOnly);
+        assert_eq!(anonymous_access(&public_read, "mybucket/private/a.txt"), AccessLevel::None);
+        assert_eq!(anonymous_access(&public_read, "otherbucket/public/a.txt"), AccessLevel::None);
+
+        let locked_prefix: BucketPolicy = serde_json::from_str(
+            r#"{
+                "Version": "2012-10-17",
+                "Statement": [
+                    {
+                        "Effect": "Allow",
+                        "Principal": {"AWS": ["*"]},
+                        "Action": ["s3:*"],
+                        "Resource": ["arn:aws:s3:::mybucket/*"]
+                    },
+                    {
+                        "Effect": "Deny",
+                        "Principal": {"AWS": ["*"]},
+                        "Action": ["s3:PutObject"],
+                        "Resource": ["arn:aws:s3:::mybucket/locked/*"]
+                    },
+                    {
+                        "Effect": "Deny",
+                        "Principal": {"AWS": ["*"]},
+                        "Action": ["s3:GetObject"],
+                        "Resource": ["arn:aws:s3:::mybucket/dropbox/*"]
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(anonymous_access(&locked_prefix, "mybucket/a.txt"), AccessLevel::ReadWrite);
+        assert_eq!(anonymous_access(&locked_prefix, "mybucket/locked/a.txt"), AccessLevel::ReadOnly);
+        assert_eq!(anonymous_access(&locked_prefix, "mybucket/dropbox/a.txt"), AccessLevel::WriteOnly);
+    }
+}
@@ -0,0 +1,200 @@
+// Copyright 2024 RustFS Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::Result;
+use rmp_serde::Serializer as rmpSerializer;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use xxhash_rust::xxh3::xxh3_64;
+
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct Credentials {
+    #[serde(rename = "accessKey")]
+    pub access_key: String,
+    #[serde(rename = "secretKey")]
+    pub secret_key: String,
+    pub session_token: Option<String>,
+    pub expiration: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct LatencyStat {
+    pub curr: u64,
+    pub avg: u64,
+    pub max: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct BucketTarget {
+    #[serde(rename = "sourcebucket")]
+    pub source_bucket: String,
+    pub endpoint: String,
+    pub credentials: Option<Credentials>,
+    #[serde(rename = "targetbucket")]
+    pub target_bucket: String,
+    #[serde(default)]
+    pub secure: bool,
+    pub path: Option<String>,
+    pub api: Option<String>,
+    pub arn: Option<String>,
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+    pub region: Option<String>,
+    // cap on replication traffic to this target, unlimited when unset
+    #[serde(default)]
+    pub bandwidth_limit_bytes_per_sec: Option<u64>,
+    #[serde(rename = "replicationSync", default)]
+    pub replication_sync: bool,
+    pub storage_class: Option<String>,
+    #[serde(rename = "healthCheckDuration", default)]
+    pub health_check_duration: u64,
+    #[serde(rename = "disableProxy", default)]
+    pub disable_proxy: bool,
+    #[serde(rename = "resetBeforeDate", default)]
+    pub reset_before_date: String,
+    pub reset_id: Option<String>,
+    #[serde(rename = "totalDowntime", default)]
+    pub total_downtime: u64,
+    pub last_online: Option<OffsetDateTime>,
+    #[serde(rename = "isOnline", default)]
+    pub online: bool,
+    pub latency: Option<LatencyStat>,
+    pub deployment_id: Option<String>,
+    #[serde(default)]
+    pub edge: bool,
+    #[serde(rename = "edgeSyncBeforeExpiry", default)]
+    pub edge_sync_before_expiry: bool,
+}
+
+impl BucketTarget {
+    pub fn is_empty(&self) -> bool {
+        self.target_bucket.is_empty() && self.endpoint.is_empty() && self.arn.is_none()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct BucketTargets {
+    pub targets: Vec<BucketTarget>,
+}
+
+impl BucketTargets {
+    pub fn marshal_msg(&self) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+
+        self.serialize(&mut rmpSerializer::new(&mut buf).with_struct_map())?;
+
+        Ok(buf)
+    }
+
+    pub fn unmarshal(buf: &[u8]) -> Result<Self> {
+        let t: BucketTargets = rmp_serde::from_slice(buf)?;
+        Ok(t)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.targets.iter().all(|t| t.is_empty())
+    }
+}
+
+// points each target places on the ring, enough to keep the spread even for a handful of targets
+const RING_VNODES: u64 = 128;
+
+/// Picks the target for `object` from a consistent-hash ring over `targets`, so adding or removing
+/// a target only moves the objects that land on its points.
+pub fn select_target<'a>(targets: &'a BucketTargets, object: &str) -> Option<&'a BucketTarget> {
+    let mut ring: Vec<(u64, usize)> = Vec::with_capacity(targets.targets.len() * RING_VNODES as usize);
+    for (idx, target) in targets.targets.iter().enumerate() {
+        let id = target.arn.as_deref().unwrap_or(&target.endpoint);
+        for vnode in 0..RING_VNODES {
+            ring.push((xxh3_64(format!("{id}#{vnode}").as_bytes()), idx));
+        }
+    }
+    if ring.is_empty() {
+        return None;
+    }
+    ring.sort_unstable();
+
+    let hash = xxh3_64(object.as_bytes());
+    let pos = ring.partition_point(|(point, _)| *point < hash) % ring.len();
+    targets.targets.get(ring[pos].1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn targets(arns: &[&str]) -> BucketTargets {
+        BucketTargets {
+            targets: arns
+                .iter()
+                .map(|arn| BucketTarget {
+                    arn: Some(arn.to_string()),
+                    ..Default::default()
+                })
+                .collect(),
+        }
+    }
+
+    fn pick(targets: &BucketTargets, object: &str) -> String {
+        select_target(targets, object).unwrap().arn.clone().unwrap()
+    }
+
+    #[test]
+    fn test_select_target_distribution() {
+        assert!(select_target(&BucketTargets::default(), "obj").is_none());
+
+        let tgts = targets(&["arn:a", "arn:b", "arn:c", "arn:d"]);
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for i in 0..10_000 {
+            *counts.entry(pick(&tgts, &format!("dir/object-{i}"))).or_default() += 1;
+        }
+
+        assert_eq!(counts.len(), 4);
+        for (arn, count) in counts {
+            assert!((1_500..=3_500).contains(&count), "{arn} got {count} of 10000");
+        }
+    }
+
+    #[test]
+    fn test_select_target_stable() {
+        let before = targets(&["arn:a", "arn:b", "arn:c"]);
+        let objects: Vec<String> = (0..5_000).map(|i| format!("object-{i}")).collect();
+
+        // the pick does not depend on the order the targets are configured in
+        let reordered = targets(&["arn:c", "arn:a", "arn:b"]);
+        assert!(objects.iter().all(|o| pick(&before, o) == pick(&reordered, o)));
+
+        // adding a target only takes objects over, it never moves them between the old ones
+        let added = targets(&["arn:a", "arn:b", "arn:c", "arn:d"]);
+        let mut moved = 0;
+        for o in objects.iter() {
+            let (old, new) = (pick(&before, o), pick(&added, o));
+            if old != new {
+                assert_eq!(new, "arn:d");
+                moved += 1;
+            }
+        }
+        assert!(moved > 0 && moved < objects.len() / 2, "moved {moved}");
+
+        // removing a target only moves the objects it held
+        let removed = targets(&["arn:a", "arn:c"]);
+        for o in objects.iter() {
+            let old = pick(&before, o);
+            if old != "arn:b" {
+                assert_eq!(pick(&removed, o), old);
+            }
+        }
+    }
+}
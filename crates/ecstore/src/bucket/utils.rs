@@ -69,6 +69,42 @@ pub fn check_valid_bucket_name_strict(bucket_name: &str) -> Result<()> {
     check_bucket_name_common(bucket_name, true)
 }
 
+/// Checks that `name` can be used for a new bucket: 3 to 63 characters of dot separated DNS
+/// labels made of lowercase letters, digits and hyphens, not an IP address and not a reserved name.
+pub fn validate_bucket_name(name: &str) -> Result<()> {
+    let invalid = |reason: &str| Err(Error::BucketNameInvalid(format!("{name}: {reason}")));
+
+    if name == "rustfs" || is_meta_bucketname(name) {
+        return invalid("bucket name is reserved");
+    }
+    if name.len() < 3 || name.len() > 63 {
+        return invalid("bucket name must be between 3 and 63 characters long");
+    }
+    if name.contains("..") {
+        return invalid("bucket name cannot contain consecutive dots");
+    }
+    if IP_ADDRESS.is_match(name) {
+        return invalid("bucket name cannot be an IP address");
+    }
+
+    for label in name.split('.') {
+        if label.is_empty() {
+            return invalid("bucket name cannot start or end with a dot");
+        }
+        if !label
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+        {
+            return invalid("bucket name can only contain lowercase letters, digits, dots and hyphens");
+        }
+        if label.starts_with('-') || label.ends_with('-') {
+            return invalid("bucket name labels must start and end with a letter or digit");
+        }
+    }
+
+    Ok(())
+}
+
 pub fn check_valid_object_name_prefix(object_name: &str) -> Result<()> {
     if object_name.len() > 1024 {
         return Err(Error::other("Object name cannot be longer than 1024 characters"));
@@ -113,3 +149,41 @@ pub fn serialize<T: xml::Serialize>(val: &T) -> xml::SerResult<Vec<u8>> {
     }
     Ok(buf)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_bucket_name() {
+        let valid = ["abc", "my-bucket", "my.bucket.01", "1bucket", "a-b.c-d", &"a".repeat(63)];
+        for name in valid {
+            assert!(validate_bucket_name(name).is_ok(), "{name} should be valid");
+        }
+
+        let invalid = [
+            ("ab", "between 3 and 63"),
+            (&"a".repeat(64), "between 3 and 63"),
+            ("my..bucket", "consecutive dots"),
+            ("192.168.1.10", "IP address"),
+            ("MyBucket", "lowercase"),
+            ("my_bucket", "lowercase"),
+            ("my bucket", "lowercase"),
+            (".bucket", "start or end with a dot"),
+            ("bucket.", "start or end with a dot"),
+            ("-bucket", "start and end with a letter or digit"),
+            ("bucket-", "start and end with a letter or digit"),
+            ("my-.bucket", "start and end with a letter or digit"),
+            ("my.-bucket", "start and end with a letter or digit"),
+            (RUSTFS_META_BUCKET, "reserved"),
+            (".rustfs.sys/config", "reserved"),
+            ("rustfs", "reserved"),
+        ];
+        for (name, reason) in invalid {
+            match validate_bucket_name(name) {
+                Err(Error::BucketNameInvalid(msg)) => assert!(msg.contains(reason), "{name}: {msg}"),
+                other => panic!("{name} should be rejected for {reason}, got {other:?}"),
+            }
+        }
+    }
+}
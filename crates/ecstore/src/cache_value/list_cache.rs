@@ -0,0 +1,278 @@
+// Copyright 2024 RustFS Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A bounded LRU of recent `list_objects_v2` pages, for browsers and sync tools that
+//! list the same prefix over and over.
+//!
+//! Like the negative cache it is local to the node and off by default, writes through
+//! another node are only seen once the page expires.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use rustfs_utils::path::decode_dir_object;
+
+use crate::store_api::ListObjectsV2Info;
+
+pub static GLOBAL_LIST_CACHE: LazyLock<ListCache> = LazyLock::new(ListCache::default);
+
+/// Enable the listing cache with room for `capacity` pages, zero turns it off
+pub fn set_list_cache(capacity: usize, ttl: Duration) {
+    GLOBAL_LIST_CACHE.configure(capacity, ttl);
+}
+
+/// Everything that shapes a `list_objects_v2` page.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ListKey {
+    pub bucket: String,
+    pub prefix: String,
+    pub delimiter: Option<String>,
+    pub max_keys: i32,
+    pub continuation_token: Option<String>,
+    pub start_after: Option<String>,
+    pub fetch_owner: bool,
+}
+
+struct Entry {
+    info: ListObjectsV2Info,
+    at: Instant,
+    tick: u64,
+}
+
+#[derive(Default)]
+struct Inner {
+    capacity: usize,
+    ttl: Duration,
+    tick: u64,
+    entries: HashMap<ListKey, Entry>,
+    // least recently used first
+    order: BTreeMap<u64, ListKey>,
+    // bumped by every write to the bucket, a listing that raced with one must not be cached
+    generations: HashMap<String, u64>,
+}
+
+impl Inner {
+    fn remove(&mut self, key: &ListKey) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.order.remove(&entry.tick);
+        }
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+}
+
+#[derive(Default)]
+pub struct ListCache {
+    enabled: AtomicBool,
+    inner: Mutex<Inner>,
+}
+
+impl ListCache {
+    pub fn configure(&self, capacity: usize, ttl: Duration) {
+        let mut inner = self.inner.lock().unwrap();
+        let generations = std::mem::take(&mut inner.generations);
+        *inner = Inner {
+            capacity,
+            ttl,
+            generations,
+            ..Default::default()
+        };
+        self.enabled.store(capacity > 0 && !ttl.is_zero(), Ordering::SeqCst);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Generation of the bucket to hand back to [`ListCache::insert`] once the listing is done.
+    pub fn generation(&self, bucket: &str) -> u64 {
+        self.inner
+            .lock()
+            .unwrap()
+            .generations
+            .get(bucket)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the cached page for the key, if it has not expired.
+    pub fn get(&self, key: &ListKey) -> Option<ListObjectsV2Info> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        let ttl = inner.ttl;
+        let (expired, old_tick) = {
+            let entry = inner.entries.get(key)?;
+            (entry.at.elapsed() > ttl, entry.tick)
+        };
+
+        if expired {
+            inner.remove(key);
+            return None;
+        }
+
+        let tick = inner.next_tick();
+        inner.order.remove(&old_tick);
+        inner.order.insert(tick, key.clone());
+        let entry = inner.entries.get_mut(key)?;
+        entry.tick = tick;
+        Some(entry.info.clone())
+    }
+
+    /// Remembers a page, unless something was written since `generation` was taken.
+    pub fn insert(&self, key: ListKey, info: &ListObjectsV2Info, generation: u64) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        if inner.generations.get(&key.bucket).copied().unwrap_or_default() != generation {
+            return;
+        }
+
+        inner.remove(&key);
+        while inner.order.len() >= inner.capacity {
+            let Some((_, old_key)) = inner.order.pop_first() else {
+                break;
+            };
+            inner.remove(&old_key);
+        }
+
+        let tick = inner.next_tick();
+        inner.order.insert(tick, key.clone());
+        inner.entries.insert(
+            key,
+            Entry {
+                info: info.clone(),
+                at: Instant::now(),
+                tick,
+            },
+        );
+    }
+
+    /// Drops every page whose prefix covers the object, `None` drops the whole bucket.
+    pub fn invalidate(&self, bucket: &str, object: Option<&str>) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let object = object.map(decode_dir_object);
+        let mut inner = self.inner.lock().unwrap();
+        *inner.generations.entry(bucket.to_string()).or_default() += 1;
+        let stale: Vec<ListKey> = inner
+            .entries
+            .keys()
+            .filter(|k| k.bucket == bucket && object.as_ref().is_none_or(|o| o.starts_with(&k.prefix)))
+            .cloned()
+            .collect();
+        for key in stale.iter() {
+            inner.remove(key);
+        }
+    }
+
+    /// Invalidates the objects when dropped, i.e. once the write holding it has finished.
+    pub fn invalidate_on_drop<S: AsRef<str>>(&self, bucket: &str, objects: impl IntoIterator<Item = S>) -> InvalidateGuard<'_> {
+        let objects = if self.is_enabled() {
+            objects.into_iter().map(|o| o.as_ref().to_string()).collect()
+        } else {
+            Vec::new()
+        };
+        InvalidateGuard {
+            cache: self,
+            bucket: bucket.to_string(),
+            objects,
+        }
+    }
+}
+
+pub struct InvalidateGuard<'a> {
+    cache: &'a ListCache,
+    bucket: String,
+    objects: Vec<String>,
+}
+
+impl Drop for InvalidateGuard<'_> {
+    fn drop(&mut self) {
+        for object in self.objects.iter() {
+            self.cache.invalidate(&self.bucket, Some(object));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(prefix: &str) -> ListKey {
+        ListKey {
+            bucket: "bucket".to_string(),
+            prefix: prefix.to_string(),
+            delimiter: Some("/".to_string()),
+            max_keys: 1000,
+            continuation_token: None,
+            start_after: None,
+            fetch_owner: false,
+        }
+    }
+
+    fn page(prefix: &str) -> ListObjectsV2Info {
+        ListObjectsV2Info {
+            prefixes: vec![prefix.to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_list_cache_lru_and_invalidation() {
+        let cache = ListCache::default();
+        cache.insert(key("a/"), &page("a/"), cache.generation("bucket"));
+        assert!(cache.get(&key("a/")).is_none(), "disabled cache stores nothing");
+
+        cache.configure(2, Duration::from_secs(60));
+        let generation = cache.generation("bucket");
+        cache.insert(key("a/"), &page("a/"), generation);
+        cache.insert(key("b/"), &page("b/"), generation);
+        // touching "a/" makes "b/" the eviction candidate
+        assert_eq!(cache.get(&key("a/")).unwrap().prefixes, vec!["a/"]);
+        cache.insert(key(""), &page(""), generation);
+        assert!(cache.get(&key("b/")).is_none());
+
+        // a write under "a/" drops "a/" and the bucket root, but nothing else
+        cache.configure(3, Duration::from_secs(60));
+        let generation = cache.generation("bucket");
+        for prefix in ["a/", "b/", ""] {
+            cache.insert(key(prefix), &page(prefix), generation);
+        }
+        cache.invalidate("bucket", Some("a/x"));
+        assert!(cache.get(&key("a/")).is_none());
+        assert!(cache.get(&key("")).is_none());
+        assert!(cache.get(&key("b/")).is_some());
+
+        // a write between listing and insert wins
+        cache.insert(key("a/"), &page("a/"), generation);
+        assert!(cache.get(&key("a/")).is_none());
+
+        cache.configure(2, Duration::from_millis(1));
+        cache.insert(key("a/"), &page("a/"), cache.generation("bucket"));
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(cache.get(&key("a/")).is_none());
+    }
+}
@@ -13,4 +13,7 @@
 // limitations under the License.
 
 // pub mod cache;
+pub mod list_cache;
 pub mod metacache_set;
+pub mod negative_cache;
+pub mod upload_idempotency;
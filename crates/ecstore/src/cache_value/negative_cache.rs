@@ -0,0 +1,243 @@
+// Copyright 2024 RustFS Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A bounded LRU of recent "object not found" answers, so clients that keep asking
+//! for missing keys don't reach the disks every time.
+//!
+//! The cache is local to the node: writes through another node are not seen until
+//! the entry expires, which is why it is off by default and meant for short TTLs.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::error::Error;
+
+pub static GLOBAL_NEGATIVE_CACHE: LazyLock<NegativeCache> = LazyLock::new(NegativeCache::default);
+
+/// Enable the not-found cache with room for `capacity` keys, zero turns it off
+pub fn set_negative_cache(capacity: usize, ttl: Duration) {
+    GLOBAL_NEGATIVE_CACHE.configure(capacity, ttl);
+}
+
+type ObjectKey = (String, String);
+
+struct Entry {
+    err: Error,
+    at: Instant,
+    tick: u64,
+}
+
+#[derive(Default)]
+struct Inner {
+    capacity: usize,
+    ttl: Duration,
+    tick: u64,
+    // bucket/object -> version id ("" for the latest) -> cached error
+    entries: HashMap<ObjectKey, HashMap<String, Entry>>,
+    // least recently used first
+    order: BTreeMap<u64, (ObjectKey, String)>,
+}
+
+impl Inner {
+    fn remove(&mut self, key: &ObjectKey, version_id: &str) {
+        if let Some(versions) = self.entries.get_mut(key) {
+            if let Some(entry) = versions.remove(version_id) {
+                self.order.remove(&entry.tick);
+            }
+            if versions.is_empty() {
+                self.entries.remove(key);
+            }
+        }
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+}
+
+#[derive(Default)]
+pub struct NegativeCache {
+    enabled: AtomicBool,
+    // bumped by every invalidation, a lookup that raced with a write must not be cached
+    generation: AtomicU64,
+    inner: Mutex<Inner>,
+}
+
+impl NegativeCache {
+    pub fn configure(&self, capacity: usize, ttl: Duration) {
+        let mut inner = self.inner.lock().unwrap();
+        *inner = Inner {
+            capacity,
+            ttl,
+            ..Default::default()
+        };
+        self.enabled.store(capacity > 0 && !ttl.is_zero(), Ordering::SeqCst);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Generation to hand back to [`NegativeCache::insert`] once the disks have answered.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    /// Returns the cached not-found error for the key, if it has not expired.
+    pub fn get(&self, bucket: &str, object: &str, version_id: &str) -> Option<Error> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        let key = (bucket.to_string(), object.to_string());
+        let ttl = inner.ttl;
+        let (err, old_tick) = {
+            let entry = inner.entries.get(&key)?.get(version_id)?;
+            if entry.at.elapsed() > ttl {
+                (None, entry.tick)
+            } else {
+                (Some(entry.err.clone()), entry.tick)
+            }
+        };
+
+        if err.is_none() {
+            inner.remove(&key, version_id);
+            return None;
+        }
+
+        let tick = inner.next_tick();
+        inner.order.remove(&old_tick);
+        inner.order.insert(tick, (key.clone(), version_id.to_string()));
+        if let Some(entry) = inner.entries.get_mut(&key).and_then(|v| v.get_mut(version_id)) {
+            entry.tick = tick;
+        }
+
+        err
+    }
+
+    /// Remembers a not-found answer, unless the key was written since `generation` was taken.
+    pub fn insert(&self, bucket: &str, object: &str, version_id: &str, err: Error, generation: u64) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        if self.generation() != generation {
+            return;
+        }
+
+        let key = (bucket.to_string(), object.to_string());
+        inner.remove(&key, version_id);
+        while inner.order.len() >= inner.capacity {
+            let Some((_, (old_key, old_version))) = inner.order.pop_first() else {
+                break;
+            };
+            inner.remove(&old_key, &old_version);
+        }
+
+        let tick = inner.next_tick();
+        inner.order.insert(tick, (key.clone(), version_id.to_string()));
+        inner.entries.entry(key).or_default().insert(
+            version_id.to_string(),
+            Entry {
+                err,
+                at: Instant::now(),
+                tick,
+            },
+        );
+    }
+
+    /// Drops every cached version of the object.
+    pub fn invalidate(&self, bucket: &str, object: &str) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        if let Some(versions) = inner.entries.remove(&(bucket.to_string(), object.to_string())) {
+            for entry in versions.values() {
+                inner.order.remove(&entry.tick);
+            }
+        }
+    }
+
+    /// Invalidates the objects when dropped, i.e. once the write holding it has finished.
+    pub fn invalidate_on_drop<S: AsRef<str>>(&self, bucket: &str, objects: impl IntoIterator<Item = S>) -> InvalidateGuard<'_> {
+        let keys = if self.is_enabled() {
+            objects
+                .into_iter()
+                .map(|o| (bucket.to_string(), o.as_ref().to_string()))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        InvalidateGuard { cache: self, keys }
+    }
+}
+
+pub struct InvalidateGuard<'a> {
+    cache: &'a NegativeCache,
+    keys: Vec<ObjectKey>,
+}
+
+impl Drop for InvalidateGuard<'_> {
+    fn drop(&mut self) {
+        for (bucket, object) in self.keys.iter() {
+            self.cache.invalidate(bucket, object);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn not_found(object: &str) -> Error {
+        Error::ObjectNotFound("bucket".to_string(), object.to_string())
+    }
+
+    #[test]
+    fn test_negative_cache_lru_and_ttl() {
+        let cache = NegativeCache::default();
+        assert!(cache.get("bucket", "a", "").is_none());
+        cache.insert("bucket", "a", "", not_found("a"), cache.generation());
+        assert!(cache.get("bucket", "a", "").is_none(), "disabled cache stores nothing");
+
+        cache.configure(2, Duration::from_secs(60));
+        let generation = cache.generation();
+        cache.insert("bucket", "a", "", not_found("a"), generation);
+        cache.insert("bucket", "b", "", not_found("b"), generation);
+        // touching "a" makes "b" the eviction candidate
+        assert_eq!(cache.get("bucket", "a", ""), Some(not_found("a")));
+        cache.insert("bucket", "c", "", not_found("c"), generation);
+        assert!(cache.get("bucket", "b", "").is_none());
+        assert!(cache.get("bucket", "a", "").is_some());
+        assert!(cache.get("bucket", "c", "").is_some());
+
+        // a write between lookup and insert wins
+        cache.invalidate("bucket", "a");
+        cache.insert("bucket", "a", "", not_found("a"), generation);
+        assert!(cache.get("bucket", "a", "").is_none());
+
+        cache.configure(2, Duration::from_millis(1));
+        cache.insert("bucket", "a", "", not_found("a"), cache.generation());
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(cache.get("bucket", "a", "").is_none());
+    }
+}
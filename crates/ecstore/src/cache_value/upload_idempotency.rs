@@ -0,0 +1,73 @@
+// Copyright 2024 RustFS Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Remembers which upload a `new_multipart_upload` idempotency key started, so a client
+//! retrying the initiate gets the same upload id back instead of leaking a new upload.
+//!
+//! Like the negative cache this is local to the node, a retry that lands on another
+//! node starts a fresh upload.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::sync::OnceCell;
+
+use crate::error::Result;
+use crate::store_api::MultipartUploadResult;
+
+// how long a key keeps returning its upload
+const UPLOAD_IDEMPOTENCY_TTL: Duration = Duration::from_secs(10 * 60);
+
+pub static GLOBAL_UPLOAD_IDEMPOTENCY: LazyLock<UploadIdempotency> = LazyLock::new(UploadIdempotency::default);
+
+// bucket, object, idempotency key
+type UploadKey = (String, String, String);
+
+struct Entry {
+    at: Instant,
+    // concurrent retries wait on the same initiate
+    upload: Arc<OnceCell<MultipartUploadResult>>,
+}
+
+#[derive(Default)]
+pub struct UploadIdempotency {
+    entries: Mutex<HashMap<UploadKey, Entry>>,
+}
+
+impl UploadIdempotency {
+    /// Returns the upload started for `key`, or runs `start` and remembers its result.
+    /// A failed `start` is not remembered, the next retry tries again.
+    pub async fn get_or_start<F, Fut>(&self, bucket: &str, object: &str, key: &str, start: F) -> Result<MultipartUploadResult>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<MultipartUploadResult>>,
+    {
+        let upload = {
+            let mut entries = self.entries.lock().unwrap();
+            entries.retain(|_, entry| entry.at.elapsed() <= UPLOAD_IDEMPOTENCY_TTL);
+            entries
+                .entry((bucket.to_owned(), object.to_owned(), key.to_owned()))
+                .or_insert_with(|| Entry {
+                    at: Instant::now(),
+                    upload: Arc::new(OnceCell::new()),
+                })
+                .upload
+                .clone()
+        };
+
+        upload.get_or_try_init(start).await.cloned()
+    }
+}
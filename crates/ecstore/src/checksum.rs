@@ -325,3 +325,63 @@ pub fn apply_auto_checksum(opts: &mut PutObjectOptions, all_parts: &mut [ObjectP
 
     Ok(())
 }
+
+impl ChecksumMode {
+    /// Parses an `x-amz-checksum-algorithm` value, only the algorithms objects can be stored with are accepted.
+    pub fn from_algorithm(algorithm: &str) -> Option<ChecksumMode> {
+        match algorithm.to_ascii_uppercase().as_str() {
+            "CRC32" => Some(ChecksumMode::ChecksumCRC32),
+            "CRC32C" => Some(ChecksumMode::ChecksumCRC32C),
+            "SHA1" => Some(ChecksumMode::ChecksumSHA1),
+            "SHA256" => Some(ChecksumMode::ChecksumSHA256),
+            _ => None,
+        }
+    }
+}
+
+/// Incremental hasher for the whole-object checksums sent with `x-amz-checksum-*`.
+pub enum ChecksumHasher {
+    Crc32(crc32fast::Hasher),
+    Crc32c(u32),
+    Sha1(sha1::Sha1),
+    Sha256(sha2::Sha256),
+}
+
+impl ChecksumHasher {
+    pub fn new(mode: ChecksumMode) -> Option<Self> {
+        use sha1::Digest as _;
+
+        match mode {
+            ChecksumMode::ChecksumCRC32 => Some(ChecksumHasher::Crc32(crc32fast::Hasher::new())),
+            ChecksumMode::ChecksumCRC32C => Some(ChecksumHasher::Crc32c(0)),
+            ChecksumMode::ChecksumSHA1 => Some(ChecksumHasher::Sha1(sha1::Sha1::new())),
+            ChecksumMode::ChecksumSHA256 => Some(ChecksumHasher::Sha256(sha2::Sha256::new())),
+            _ => None,
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        use sha1::Digest as _;
+
+        match self {
+            ChecksumHasher::Crc32(h) => h.update(data),
+            ChecksumHasher::Crc32c(crc) => *crc = crc32c::crc32c_append(*crc, data),
+            ChecksumHasher::Sha1(h) => h.update(data),
+            ChecksumHasher::Sha256(h) => h.update(data),
+        }
+    }
+
+    /// Base64 of the digest, CRCs in big-endian byte order as S3 encodes them.
+    pub fn finalize(self) -> String {
+        use base64::Engine as _;
+        use sha1::Digest as _;
+
+        let digest = match self {
+            ChecksumHasher::Crc32(h) => h.finalize().to_be_bytes().to_vec(),
+            ChecksumHasher::Crc32c(crc) => crc.to_be_bytes().to_vec(),
+            ChecksumHasher::Sha1(h) => h.finalize().to_vec(),
+            ChecksumHasher::Sha256(h) => h.finalize().to_vec(),
+        };
+        base64::engine::general_purpose::STANDARD.encode(digest)
+    }
+}
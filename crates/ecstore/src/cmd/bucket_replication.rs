@@ -15,7 +15,9 @@
 #![allow(dead_code)]
 // use error::Error;
 use crate::StorageAPI;
-use crate::bucket::metadata_sys::get_replication_config;
+use crate::bucket::bandwidth::{GLOBAL_BANDWIDTH_MONITOR, read_all_throttled};
+use crate::bucket::metadata_sys::{get_bucket_targets_config, get_replication_config};
+use crate::bucket::target::{BucketTarget, BucketTargets, select_target};
 use crate::bucket::versioning_sys::BucketVersioningSys;
 use crate::error::Error;
 use crate::new_object_layer_fn;
@@ -351,7 +353,7 @@ pub async fn check_replicate_delete(
         existing_object: true,
     };
 
-    let tgt_arns = rcfg.filter_target_arns(&opts);
+    let tgt_arns = shard_target_arns(bucket, rcfg.filter_target_arns(&opts), &dobj.object_name).await;
     dsc.targets_map = HashMap::with_capacity(tgt_arns.len());
 
     if tgt_arns.is_empty() {
@@ -973,6 +975,7 @@ pub async fn init_bucket_replication_pool() {
         let stat = Arc::new(stats);
         warn!("init bucket replication pool");
         ReplicationPool::init_bucket_replication_pool(store, opts, stat).await;
+        tokio::spawn(bucket_targets::probe_targets_routine());
     } else {
         // TODO: to be added
     }
@@ -1647,18 +1650,12 @@ impl ConfigProcess for s3s::dto::ReplicationConfiguration {
     }
 }
 
-fn replication_statuses_map(s: &str) -> HashMap<String, ReplicationStatusType> {
-    let mut targets = HashMap::new();
-    let repl_status_regex = Regex::new(r"(\w+):([\w-]+)").unwrap();
-
-    for cap in repl_status_regex.captures_iter(s) {
-        if let (Some(target), Some(status)) = (cap.get(1), cap.get(2)) {
-            let tp = ReplicationStatusType::from(status.as_str());
-            targets.insert(target.as_str().to_string(), tp);
-        }
-    }
-
-    targets
+// Parses "arn1=COMPLETED;arn2=FAILED;" as written by replication_status_internal, ARNs contain ':'
+pub(crate) fn replication_statuses_map(s: &str) -> HashMap<String, ReplicationStatusType> {
+    s.split(';')
+        .filter_map(|entry| entry.split_once('='))
+        .map(|(arn, status)| (arn.to_string(), ReplicationStatusType::from(status)))
+        .collect()
 }
 
 fn version_purge_statuses_map(s: &str) -> HashMap<String, VersionPurgeStatusType> {
@@ -1792,6 +1789,85 @@ pub async fn schedule_replication(oi: ObjectInfo, o: Arc<store::ECStore>, dsc: R
     }
 }
 
+/// Keeps one target per region for the object when several targets share a region, which
+/// shards the bucket over them. Targets without a region are all kept.
+async fn shard_target_arns(bucket: &str, arns: Vec<String>, object: &str) -> Vec<String> {
+    if arns.len() < 2 {
+        return arns;
+    }
+
+    match get_bucket_targets_config(bucket).await {
+        Ok(targets) => shard_arns_by_region(&targets, arns, object, &bucket_targets::get_target_rtts(bucket).await),
+        Err(_) => arns,
+    }
+}
+
+fn shard_arns_by_region(
+    targets: &BucketTargets,
+    arns: Vec<String>,
+    object: &str,
+    rtts: &HashMap<String, bucket_targets::TargetRtt>,
+) -> Vec<String> {
+    let mut regions: HashMap<String, BucketTargets> = HashMap::new();
+    for target in targets.targets.iter() {
+        let Some(region) = target.region.as_ref().filter(|r| !r.is_empty()) else {
+            continue;
+        };
+        if target.arn.as_ref().is_some_and(|arn| arns.contains(arn)) {
+            regions.entry(region.clone()).or_default().targets.push(target.clone());
+        }
+    }
+
+    let now = OffsetDateTime::now_utc();
+    let mut dropped = HashSet::new();
+    for group in regions.values().filter(|g| g.targets.len() > 1) {
+        let chosen = select_target(&fastest_per_bucket(group, rtts, now), object).and_then(|t| t.arn.clone());
+        for target in group.targets.iter() {
+            if target.arn != chosen {
+                dropped.extend(target.arn.clone());
+            }
+        }
+    }
+
+    arns.into_iter().filter(|arn| !dropped.contains(arn)).collect()
+}
+
+/// Of the probed targets replicating to the same remote bucket, keeps only the one with the lowest
+/// RTT. Targets without a fresh RTT are always kept, so a target never probed yet, or one that
+/// stopped answering, isn't ranked on a number that says nothing about it.
+fn fastest_per_bucket(
+    group: &BucketTargets,
+    rtts: &HashMap<String, bucket_targets::TargetRtt>,
+    now: OffsetDateTime,
+) -> BucketTargets {
+    let rtt_of = |t: &BucketTarget| {
+        t.arn
+            .as_ref()
+            .and_then(|arn| rtts.get(arn))
+            .filter(|r| r.is_fresh(now))
+            .map(|r| r.rtt_ms)
+    };
+
+    let mut fastest: HashMap<&str, u64> = HashMap::new();
+    for target in group.targets.iter() {
+        if let Some(rtt) = rtt_of(target) {
+            let best = fastest.entry(target.target_bucket.as_str()).or_insert(rtt);
+            *best = (*best).min(rtt);
+        }
+    }
+
+    let targets = group
+        .targets
+        .iter()
+        .filter(|t| match (rtt_of(t), fastest.get(t.target_bucket.as_str())) {
+            (Some(rtt), Some(best)) => rtt == *best,
+            _ => true,
+        })
+        .cloned()
+        .collect();
+    BucketTargets { targets }
+}
+
 pub async fn must_replicate(bucket: &str, object: &str, mopts: &MustReplicateOptions) -> ReplicateDecision {
     let mut decision = ReplicateDecision::default();
 
@@ -1841,7 +1917,7 @@ pub async fn must_replicate(bucket: &str, object: &str, mopts: &MustReplicateOpt
     }
 
     // let rules = cfg.filter_actionable_rules(&opts);
-    let tgt_arns = cfg.filter_target_arns(&opts);
+    let tgt_arns = shard_target_arns(bucket, cfg.filter_target_arns(&opts), object).await;
     info!("arn lens:{}", tgt_arns.len());
     for tgt_arn in tgt_arns {
         let tgt = bucket_targets::get_bucket_target_client(bucket, &tgt_arn.clone()).await;
@@ -2014,6 +2090,7 @@ impl ReplicateObjectInfo {
             version_id: Uuid::try_parse(&self.version_id).ok(),
             delete_marker: self.delete_marker,
             transitioned_object: TransitionedObject::default(),
+            restore_status: None,
             user_tags: self.user_tags.clone(),
             parts: Vec::new(),
             is_latest: true,
@@ -2031,6 +2108,8 @@ impl ReplicateObjectInfo {
             version_purge_status_internal: self.version_purge_status_internal.clone(),
             version_purge_status: self.version_purge_status.clone(),
             checksum: self.checksum.clone(),
+            expiration: None,
+            owner: None,
         }
     }
 }
@@ -2144,6 +2223,7 @@ async fn replicate_object_with_multipart(
                 let task = Arc::clone(&task);
                 let bucket = local_obj_info.bucket.clone();
                 let name = local_obj_info.name.clone();
+                let throttle = GLOBAL_BANDWIDTH_MONITOR.throttle(&tgt_cli.arn);
 
                 upload_futures.push(tokio::spawn(async move {
                     let get_opts = ObjectOptions {
@@ -2156,7 +2236,7 @@ async fn replicate_object_with_multipart(
 
                     let h = HeaderMap::new();
                     match store.get_object_reader(&bucket, &name, None, h, &get_opts).await {
-                        Ok(mut reader) => match reader.read_all().await {
+                        Ok(mut reader) => match read_all_throttled(&mut reader.stream, &throttle).await.map_err(Error::from) {
                             Ok(ret) => {
                                 debug!("readall suc:");
                                 let body = Bytes::from(ret);
@@ -2345,7 +2425,8 @@ impl ReplicateObjectInfo {
                 Ok(mut reader) => {
                     warn!("endpoint is: {}", rinfo.endpoint);
                     let provider = StaticProvider::new(&target.ak, &target.sk, None);
-                    let res = reader.read_all().await;
+                    let throttle = GLOBAL_BANDWIDTH_MONITOR.throttle(&target.arn);
+                    let res = read_all_throttled(&mut reader.stream, &throttle).await;
                     match res {
                         Ok(ret) => {
                             let body = rustfs_rsc::Data::from(ret);
@@ -2732,3 +2813,59 @@ pub async fn replicate_object(ri: ReplicateObjectInfo, object_api: Arc<store::EC
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::bucket_targets::TargetRtt;
+
+    fn target(arn: &str, bucket: &str) -> BucketTarget {
+        BucketTarget {
+            arn: Some(arn.to_string()),
+            target_bucket: bucket.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn arns(targets: &BucketTargets) -> Vec<&str> {
+        targets.targets.iter().filter_map(|t| t.arn.as_deref()).collect()
+    }
+
+    #[test]
+    fn test_fastest_per_bucket() {
+        let now = OffsetDateTime::now_utc();
+        let rtt = |rtt_ms, age: u64| TargetRtt {
+            rtt_ms,
+            last_checked: now - std::time::Duration::from_secs(age),
+        };
+        let group = BucketTargets {
+            targets: vec![
+                target("arn:slow", "dst"),
+                target("arn:fast", "dst"),
+                target("arn:new", "dst"),
+                target("arn:other", "other-dst"),
+            ],
+        };
+
+        // nothing probed yet, everything stays a candidate
+        assert_eq!(
+            arns(&fastest_per_bucket(&group, &HashMap::new(), now)),
+            ["arn:slow", "arn:fast", "arn:new", "arn:other"]
+        );
+
+        // the slower probed target is dropped, the unprobed one and other buckets are kept
+        let rtts = HashMap::from([
+            ("arn:slow".to_string(), rtt(80, 10)),
+            ("arn:fast".to_string(), rtt(12, 10)),
+            ("arn:other".to_string(), rtt(300, 10)),
+        ]);
+        assert_eq!(arns(&fastest_per_bucket(&group, &rtts, now)), ["arn:fast", "arn:new", "arn:other"]);
+
+        // a target that stopped answering keeps its old low RTT, which no longer counts
+        let rtts = HashMap::from([("arn:slow".to_string(), rtt(80, 10)), ("arn:fast".to_string(), rtt(12, 3600))]);
+        assert_eq!(
+            arns(&fastest_per_bucket(&group, &rtts, now)),
+            ["arn:slow", "arn:fast", "arn:new", "arn:other"]
+        );
+    }
+}
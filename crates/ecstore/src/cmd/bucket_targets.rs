@@ -20,19 +20,25 @@ use crate::{
     rpc::{PeerS3Client, RemotePeerS3Client},
 };
 use crate::{
-    bucket::{self, target::BucketTargets},
+    bucket::{self, bandwidth::GLOBAL_BANDWIDTH_MONITOR, metadata::BUCKET_TARGETS_META_FILE, target::BucketTargets},
     new_object_layer_fn, store_api,
 };
 //use tokio::sync::RwLock;
+use crate::global::get_global_endpoints;
 use aws_sdk_s3::Client as S3Client;
 use chrono::Utc;
+use futures::future::join_all;
 use lazy_static::lazy_static;
+use rustfs_rsc::Minio;
+use rustfs_rsc::provider::StaticProvider;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::{
     collections::HashMap,
-    time::{Duration, SystemTime},
+    time::{Duration, Instant, SystemTime},
 };
 use thiserror::Error;
+use time::OffsetDateTime;
 use tokio::sync::RwLock;
 
 pub struct TClient {
@@ -188,6 +194,115 @@ pub async fn list_bucket_targets(bucket: &str) -> Result<BucketTargets, BucketRe
     }
 }
 
+pub const TARGET_RTT_PROBE_INTERVAL: Duration = Duration::from_secs(60);
+const TARGET_RTT_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+/// RTTs older than this are ignored and dropped, so a target that stops answering loses its place.
+pub const TARGET_RTT_MAX_AGE: Duration = Duration::from_secs(5 * 60);
+// an unchanged RTT is still re-saved at this age, so a live target never ages out
+const TARGET_RTT_REFRESH_AGE: Duration = Duration::from_secs(2 * 60);
+
+/// Round-trip time last measured to a bucket target, stored per ARN in the bucket's targets meta.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TargetRtt {
+    pub rtt_ms: u64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub last_checked: OffsetDateTime,
+}
+
+impl TargetRtt {
+    pub fn is_fresh(&self, now: OffsetDateTime) -> bool {
+        now - self.last_checked <= TARGET_RTT_MAX_AGE
+    }
+
+    // a quarter off and at least 5ms, so jitter between probes doesn't rewrite the bucket metadata
+    fn differs_from(&self, other: &TargetRtt) -> bool {
+        let diff = self.rtt_ms.abs_diff(other.rtt_ms);
+        diff > 5 && diff * 4 > other.rtt_ms
+    }
+}
+
+/// Probed RTTs of the targets of `bucket`, keyed by ARN.
+pub async fn get_target_rtts(bucket: &str) -> HashMap<String, TargetRtt> {
+    match metadata_sys::get(bucket).await {
+        Ok(meta) => target_rtts(&meta),
+        Err(_) => HashMap::new(),
+    }
+}
+
+pub fn target_rtts(meta: &bucket::metadata::BucketMetadata) -> HashMap<String, TargetRtt> {
+    meta.bucket_target_config_meta
+        .iter()
+        .flatten()
+        .filter_map(|(arn, value)| serde_json::from_str(value).ok().map(|rtt| (arn.clone(), rtt)))
+        .collect()
+}
+
+/// Merges a probe round into the stored RTTs, dropping stale entries. Returns `None` when nothing
+/// changed enough to be worth a save.
+pub fn merge_target_rtts(
+    current: &HashMap<String, TargetRtt>,
+    probed: &HashMap<String, TargetRtt>,
+    now: OffsetDateTime,
+) -> Option<HashMap<String, TargetRtt>> {
+    let mut merged: HashMap<String, TargetRtt> = current
+        .iter()
+        .filter(|(_, rtt)| rtt.is_fresh(now))
+        .map(|(arn, rtt)| (arn.clone(), *rtt))
+        .collect();
+    let mut changed = merged.len() != current.len();
+
+    for (arn, rtt) in probed {
+        let update = match merged.get(arn) {
+            Some(old) => rtt.differs_from(old) || now - old.last_checked >= TARGET_RTT_REFRESH_AGE,
+            None => true,
+        };
+        if update {
+            merged.insert(arn.clone(), *rtt);
+            changed = true;
+        }
+    }
+
+    changed.then_some(merged)
+}
+
+/// Saves a probe round into the targets meta of `bucket`. The bucket metadata is only rewritten when
+/// an RTT moved materially, a stored one is due for a refresh or a stale one is dropped.
+pub async fn save_target_rtts(bucket: &str, probed: &HashMap<String, TargetRtt>) -> crate::error::Result<()> {
+    let current = get_target_rtts(bucket).await;
+    let Some(merged) = merge_target_rtts(&current, probed, OffsetDateTime::now_utc()) else {
+        return Ok(());
+    };
+
+    let mut meta = HashMap::with_capacity(merged.len());
+    for (arn, rtt) in merged {
+        meta.insert(arn, serde_json::to_string(&rtt)?);
+    }
+    metadata_sys::update(bucket, BUCKET_TARGETS_META_FILE, serde_json::to_vec(&meta)?).await?;
+    Ok(())
+}
+
+/// Re-probes the targets of every bucket each [`TARGET_RTT_PROBE_INTERVAL`]. Only the node serving the
+/// first endpoint probes, the others read the RTTs from the bucket metadata.
+pub async fn probe_targets_routine() {
+    if !get_global_endpoints().first_local() {
+        return;
+    }
+    let Some(sys) = GLOBAL_Bucket_Target_Sys.get() else {
+        return;
+    };
+
+    let mut interval = tokio::time::interval(TARGET_RTT_PROBE_INTERVAL);
+    loop {
+        interval.tick().await;
+        let buckets: Vec<String> = sys.targets_map.read().await.keys().cloned().collect();
+        for bucket in buckets {
+            if let Err(err) = sys.probe_rtt(&bucket).await {
+                warn!("save target rtt of bucket {} failed: {}", bucket, err);
+            }
+        }
+    }
+}
+
 impl Default for BucketTargetSys {
     fn default() -> Self {
         Self::new()
@@ -266,6 +381,45 @@ impl BucketTargetSys {
         targets
     }
 
+    /// Times a HEAD of the remote bucket through each target's client, probing all targets of `bucket`
+    /// at once, and saves the RTTs of those that answered.
+    pub async fn probe_rtt(&self, bucket: &str) -> crate::error::Result<()> {
+        let Ok(targets) = self.list_bucket_targets(bucket).await else {
+            return Ok(());
+        };
+
+        let clients: Vec<TargetClient> = {
+            let arn_remotes_map = self.arn_remote_map.read().await;
+            targets
+                .targets
+                .iter()
+                .filter_map(|tgt| {
+                    let arn = tgt.arn.as_ref()?;
+                    let mut client = arn_remotes_map.get(arn)?.client.clone();
+                    client.arn = arn.clone();
+                    Some(client)
+                })
+                .collect()
+        };
+
+        let probes = clients.into_iter().map(|client| async move {
+            let start = Instant::now();
+            match tokio::time::timeout(TARGET_RTT_PROBE_TIMEOUT, client.head_bucket()).await {
+                Ok(Ok(true)) => Some((
+                    client.arn,
+                    TargetRtt {
+                        rtt_ms: start.elapsed().as_millis() as u64,
+                        last_checked: OffsetDateTime::now_utc(),
+                    },
+                )),
+                _ => None,
+            }
+        });
+        let rtts: HashMap<String, TargetRtt> = join_all(probes).await.into_iter().flatten().collect();
+
+        save_target_rtts(bucket, &rtts).await
+    }
+
     pub async fn remove_target(&self, bucket: &str, arn_str: &str) -> Result<(), SetTargetError> {
         //to do need lock;
         let mut targets_map = self.targets_map.write().await;
@@ -297,6 +451,7 @@ impl BucketTargetSys {
         // 更新 targets_map
         targets_map.insert(bucket.to_string(), targets);
         arn_remotes_map.remove(arn_str);
+        GLOBAL_BANDWIDTH_MONITOR.remove(arn_str);
 
         let targets = self.list_targets(Some(bucket), None).await;
         println!("targets is {}", targets.len());
@@ -527,15 +682,16 @@ impl BucketTargetSys {
             println!("437 exist:{}", tgt.arn.clone().unwrap());
             targets.push(tgt.clone());
         }
-        let arntgt: ArnTarget = ArnTarget::new(
+        let mut arntgt: ArnTarget = ArnTarget::new(
             tgt.target_bucket.clone(),
             tgt.endpoint.clone(),
             tgt.credentials.clone().unwrap().access_key.clone(),
             tgt.credentials.clone().unwrap().secret_key,
         );
+        arntgt.client.secure = tgt.secure;
 
         arn_remotes_map.insert(tgt.arn.clone().unwrap().clone(), arntgt);
-        //self.update_bandwidth_limit(bucket, &tgt.arn, tgt.bandwidth_limit).await;
+        GLOBAL_BANDWIDTH_MONITOR.set_limit(tgt.arn.as_deref().unwrap(), tgt.bandwidth_limit_bytes_per_sec);
 
         Ok(())
     }
@@ -592,6 +748,20 @@ impl TargetClient {
     pub async fn bucket_exists(&self, _bucket: &str) -> Result<bool, SetTargetError> {
         Ok(true) // Mocked implementation
     }
+
+    /// HEADs the remote bucket with the target's credentials, over TLS when the target is secure.
+    pub async fn head_bucket(&self) -> Result<bool, SetTargetError> {
+        let cli = Minio::builder()
+            .endpoint(self.endpoint.clone())
+            .provider(StaticProvider::new(&self.ak, &self.sk, None))
+            .secure(self.secure)
+            .build()
+            .map_err(|_| SetTargetError::HealthCheckFailed(self.bucket.clone()))?;
+
+        cli.bucket_exists(self.bucket.clone())
+            .await
+            .map_err(|_| SetTargetError::HealthCheckFailed(self.bucket.clone()))
+    }
 }
 use tracing::{error, info, warn};
 use uuid::Uuid;
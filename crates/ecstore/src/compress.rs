@@ -12,9 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::bucket::metadata_sys;
+use crate::bucket::utils::is_meta_bucketname;
+use crate::error::{Error, Result};
+use crate::kms::is_kms_encrypted;
+use crate::store_api::{ObjectOptions, PutObjReader};
+use rustfs_filemeta::headers::RESERVED_METADATA_PREFIX_LOWER;
+use rustfs_rio::{CompressReader, HashReader, WarpReader};
+use rustfs_utils::CompressionAlgorithm;
 use rustfs_utils::string::has_pattern;
 use rustfs_utils::string::has_string_suffix_in_slice;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::env;
+use std::io::Cursor;
+use std::str::FromStr;
 use tracing::error;
 
 pub const MIN_COMPRESSIBLE_SIZE: usize = 4096;
@@ -54,18 +65,122 @@ pub fn is_compressible(headers: &http::HeaderMap, object_name: &str) -> bool {
 
     // TODO: crypto request return false
 
+    !is_excluded(object_name, content_type)
+
+    // TODO: check from config
+}
+
+// already compressed formats gain nothing from another pass
+fn is_excluded(object_name: &str, content_type: &str) -> bool {
     if has_string_suffix_in_slice(object_name, STANDARD_EXCLUDE_COMPRESS_EXTENSIONS) {
         error!("object_name: {} is not compressible", object_name);
-        return false;
+        return true;
     }
 
     if !content_type.is_empty() && has_pattern(STANDARD_EXCLUDE_COMPRESS_CONTENT_TYPES, content_type) {
         error!("content_type: {} is not compressible", content_type);
-        return false;
+        return true;
     }
-    true
+    false
+}
 
-    // TODO: check from config
+/// Transparent compression for a put, from `ObjectOptions::compression` or the bucket default.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompressionOpts {
+    #[serde(serialize_with = "serialize_algorithm", deserialize_with = "deserialize_algorithm")]
+    pub algorithm: CompressionAlgorithm,
+    /// Object name suffixes to compress, e.g. ".txt"; empty allows every name.
+    pub extensions: Vec<String>,
+    /// Content-type patterns to compress, e.g. "text/*"; empty allows every type.
+    pub mime_types: Vec<String>,
+}
+
+impl CompressionOpts {
+    pub fn new(algorithm: CompressionAlgorithm) -> Self {
+        Self {
+            algorithm,
+            ..Default::default()
+        }
+    }
+
+    /// True when an object with this name and content type should be compressed.
+    pub fn allows(&self, object_name: &str, content_type: &str) -> bool {
+        if self.algorithm == CompressionAlgorithm::None || is_excluded(object_name, content_type) {
+            return false;
+        }
+
+        let extensions: Vec<&str> = self.extensions.iter().map(String::as_str).collect();
+        let mime_types: Vec<&str> = self.mime_types.iter().map(String::as_str).collect();
+        if extensions.is_empty() && mime_types.is_empty() {
+            return true;
+        }
+
+        (!extensions.is_empty() && has_string_suffix_in_slice(object_name, &extensions))
+            || (!mime_types.is_empty() && !content_type.is_empty() && has_pattern(&mime_types, content_type))
+    }
+}
+
+fn serialize_algorithm<S: Serializer>(algorithm: &CompressionAlgorithm, s: S) -> std::result::Result<S::Ok, S::Error> {
+    s.serialize_str(algorithm.as_str())
+}
+
+fn deserialize_algorithm<'de, D: Deserializer<'de>>(d: D) -> std::result::Result<CompressionAlgorithm, D::Error> {
+    let s = String::deserialize(d)?;
+    CompressionAlgorithm::from_str(&s).map_err(serde::de::Error::custom)
+}
+
+/// Picks the algorithm to compress a put with, `None` to store it as is.
+///
+/// Puts with a client checksum are left alone since the checksum covers the stored bytes, as are
+/// encrypted objects and ones that are already compressed, of unknown length or too small to be
+/// worth it.
+pub async fn put_compression(
+    bucket: &str,
+    object: &str,
+    data: &PutObjReader,
+    opts: &ObjectOptions,
+) -> Option<CompressionAlgorithm> {
+    if opts.checksum_algorithm.is_some()
+        || opts
+            .user_defined
+            .contains_key(&format!("{RESERVED_METADATA_PREFIX_LOWER}compression"))
+        || is_kms_encrypted(&opts.user_defined)
+        || data.actual_size() <= MIN_COMPRESSIBLE_SIZE as i64
+    {
+        return None;
+    }
+
+    let copts = match &opts.compression {
+        Some(copts) => copts.clone(),
+        // bucket metadata itself is written under the metadata sys lock
+        None if is_meta_bucketname(bucket) => return None,
+        None => metadata_sys::get_compression_config(bucket).await.ok()?.0,
+    };
+    let content_type = opts.user_defined.get("content-type").map(String::as_str).unwrap_or_default();
+
+    copts.allows(object, content_type).then_some(copts.algorithm)
+}
+
+/// Swaps the stream of `data` for a compressing one and returns `opts` with the algorithm and
+/// original size recorded, so the read path knows to decompress.
+pub fn compress_put_reader(
+    data: &mut PutObjReader,
+    opts: &ObjectOptions,
+    algorithm: CompressionAlgorithm,
+) -> Result<ObjectOptions> {
+    let actual_size = data.actual_size();
+    let empty = HashReader::new(Box::new(WarpReader::new(Cursor::new(Vec::new()))), 0, 0, None, false)?;
+    let inner = std::mem::replace(&mut data.stream, empty);
+    data.stream =
+        HashReader::new(Box::new(CompressReader::new(inner, algorithm)), -1, actual_size, None, false).map_err(Error::other)?;
+
+    let mut opts = opts.clone();
+    opts.user_defined
+        .insert(format!("{RESERVED_METADATA_PREFIX_LOWER}compression"), algorithm.to_string());
+    opts.user_defined
+        .insert(format!("{RESERVED_METADATA_PREFIX_LOWER}actual-size"), actual_size.to_string());
+    Ok(opts)
 }
 
 #[cfg(test)]
@@ -73,6 +188,30 @@ mod tests {
     use super::*;
     use temp_env;
 
+    #[test]
+    fn test_compression_opts_allows() {
+        let opts = CompressionOpts::new(CompressionAlgorithm::Zstd);
+        assert!(opts.allows("file.txt", ""));
+        assert!(opts.allows("file.bin", "application/octet-stream"));
+        assert!(!opts.allows("file.zip", ""));
+        assert!(!opts.allows("file.txt", "video/mp4"));
+        assert!(!CompressionOpts::new(CompressionAlgorithm::None).allows("file.txt", ""));
+
+        let opts = CompressionOpts {
+            extensions: vec![".log".to_string()],
+            mime_types: vec!["text/*".to_string()],
+            ..CompressionOpts::new(CompressionAlgorithm::Lz4)
+        };
+        assert!(opts.allows("app.log", ""));
+        assert!(opts.allows("page.html", "text/html"));
+        assert!(!opts.allows("data.bin", "application/octet-stream"));
+        assert!(!opts.allows("data.bin", ""));
+
+        let json = serde_json::to_string(&opts).unwrap();
+        assert!(json.contains("\"lz4\""));
+        assert_eq!(serde_json::from_str::<CompressionOpts>(&json).unwrap(), opts);
+    }
+
     #[test]
     fn test_is_compressible() {
         use http::HeaderMap;
@@ -265,11 +265,7 @@ pub fn validate_parity(ss_parity: usize, set_drive_count: usize) -> Result<()> {
     // }
 
     if ss_parity > set_drive_count / 2 {
-        return Err(Error::other(format!(
-            "parity {} should be less than or equal to {}",
-            ss_parity,
-            set_drive_count / 2
-        )));
+        return Err(Error::InvalidParity(ss_parity, set_drive_count / 2));
     }
 
     Ok(())
@@ -293,23 +289,9 @@ pub fn validate_parity_inner(ss_parity: usize, rrs_parity: usize, set_drive_coun
     //     )));
     // }
 
-    if set_drive_count > 2 {
-        if ss_parity > set_drive_count / 2 {
-            return Err(Error::other(format!(
-                "Standard storage class parity {} should be less than or equal to {}",
-                ss_parity,
-                set_drive_count / 2
-            )));
-        }
-
-        if rrs_parity > set_drive_count / 2 {
-            return Err(Error::other(format!(
-                "Reduced redundancy storage class parity {} should be less than or equal to {}",
-                rrs_parity,
-                set_drive_count / 2
-            )));
-        }
-    }
+    // more parity than data would leave a write quorum larger than the set
+    validate_parity(ss_parity, set_drive_count)?;
+    validate_parity(rrs_parity, set_drive_count)?;
 
     if ss_parity > 0 && rrs_parity > 0 && ss_parity < rrs_parity {
         return Err(Error::other(format!(
@@ -318,3 +300,35 @@ pub fn validate_parity_inner(ss_parity: usize, rrs_parity: usize, set_drive_coun
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_parity_against_set_size() {
+        for (drives, max) in [(1, 0), (2, 1), (4, 2), (5, 2), (8, 4), (16, 8)] {
+            assert!(validate_parity(max, drives).is_ok(), "{drives} drives");
+            assert!(validate_parity_inner(max, max, drives).is_ok(), "{drives} drives");
+            assert!(matches!(
+                validate_parity(max + 1, drives),
+                Err(Error::InvalidParity(p, m)) if p == max + 1 && m == max
+            ));
+            assert!(matches!(validate_parity_inner(max + 1, 0, drives), Err(Error::InvalidParity(..))));
+            assert!(matches!(validate_parity_inner(max, max + 1, drives), Err(Error::InvalidParity(..))));
+        }
+
+        // rrs may not be more protected than standard
+        assert!(validate_parity_inner(2, 4, 16).is_err());
+        assert!(validate_parity_inner(4, 2, 16).is_ok());
+    }
+
+    #[test]
+    fn test_parse_storage_class() {
+        assert_eq!(parse_storage_class("EC:4").unwrap().parity, 4);
+        assert_eq!(parse_storage_class("EC:0").unwrap().parity, 0);
+        for bad in ["EC", "EC:", "EC:x", "RS:2", "EC:2:1", ""] {
+            assert!(parse_storage_class(bad).is_err(), "{bad}");
+        }
+    }
+}
@@ -0,0 +1,434 @@
+// Copyright 2024 RustFS Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Direct IO (`O_DIRECT`) readers and writers for large object files.
+//!
+//! `O_DIRECT` requires the user buffer, the file offset and the transfer length
+//! to be aligned to the logical block size of the device. Buffers are aligned to
+//! [`DIRECT_IO_ALIGN`], unaligned tails are written after clearing `O_DIRECT` on
+//! the descriptor, and filesystems that reject `O_DIRECT` (tmpfs, some overlay
+//! setups) transparently fall back to buffered IO.
+
+use std::fs::File;
+use std::future::Future;
+use std::io::{self, Write};
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// Alignment used for buffers, offsets and lengths when `O_DIRECT` is active.
+pub const DIRECT_IO_ALIGN: usize = 4096;
+
+/// Size of the staging buffer used by the direct reader and writer.
+pub const DIRECT_IO_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Heap buffer whose usable region starts at a [`DIRECT_IO_ALIGN`] boundary.
+///
+/// The backing `Vec` is over-allocated by one alignment unit and the usable
+/// window is shifted to the first aligned address, which keeps the type free
+/// of unsafe allocation code. Moving the buffer never moves the heap data, so
+/// the alignment is stable for its whole lifetime.
+struct AlignedBuf {
+    raw: Vec<u8>,
+    start: usize,
+    cap: usize,
+    len: usize,
+}
+
+impl AlignedBuf {
+    fn new(cap: usize) -> Self {
+        let raw = vec![0u8; cap + DIRECT_IO_ALIGN];
+        let misalign = raw.as_ptr() as usize % DIRECT_IO_ALIGN;
+        let start = if misalign == 0 { 0 } else { DIRECT_IO_ALIGN - misalign };
+        Self { raw, start, cap, len: 0 }
+    }
+
+    fn filled(&self) -> &[u8] {
+        &self.raw[self.start..self.start + self.len]
+    }
+
+    fn spare(&mut self) -> &mut [u8] {
+        &mut self.raw[self.start + self.len..self.start + self.cap]
+    }
+
+    fn whole(&mut self) -> &mut [u8] {
+        &mut self.raw[self.start..self.start + self.cap]
+    }
+
+    fn is_full(&self) -> bool {
+        self.len == self.cap
+    }
+}
+
+fn is_direct_unsupported(err: &io::Error) -> bool {
+    err.raw_os_error() == Some(nix::libc::EINVAL)
+}
+
+/// Opens `path` with `O_DIRECT` when the platform supports it, returning the
+/// file and whether direct IO is actually in effect.
+fn open_direct(path: &Path, write: bool) -> io::Result<(File, bool)> {
+    let mut opts = std::fs::OpenOptions::new();
+    if write {
+        opts.write(true).create(true).truncate(true);
+    } else {
+        opts.read(true);
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let mut direct = opts.clone();
+        direct.custom_flags(nix::libc::O_DIRECT);
+        match direct.open(path) {
+            Ok(f) => return Ok((f, true)),
+            Err(e) if is_direct_unsupported(&e) => {
+                warn!("O_DIRECT not supported for {:?}, falling back to buffered io", path);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok((opts.open(path)?, false))
+}
+
+/// Clears `O_DIRECT` on an open descriptor so unaligned IO can be issued.
+#[cfg(target_os = "linux")]
+fn disable_direct(file: &File) -> io::Result<()> {
+    use nix::fcntl::{FcntlArg, OFlag, fcntl};
+
+    let flags = fcntl(file, FcntlArg::F_GETFL).map_err(io::Error::from)?;
+    let flags = OFlag::from_bits_truncate(flags) & !OFlag::O_DIRECT;
+    fcntl(file, FcntlArg::F_SETFL(flags)).map_err(io::Error::from)?;
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn disable_direct(_file: &File) -> io::Result<()> {
+    Ok(())
+}
+
+struct WriteInner {
+    file: File,
+    buf: AlignedBuf,
+    direct: bool,
+}
+
+impl WriteInner {
+    fn write_direct(&mut self, data_len: usize) -> io::Result<()> {
+        let start = self.buf.start;
+        let data = &self.buf.raw[start..start + data_len];
+        match self.file.write_all(data) {
+            Err(e) if self.direct && is_direct_unsupported(&e) => {
+                warn!("O_DIRECT write rejected, falling back to buffered io");
+                disable_direct(&self.file)?;
+                self.direct = false;
+                self.file.write_all(data)
+            }
+            res => res,
+        }
+    }
+
+    /// Writes every aligned block currently buffered and keeps the remainder.
+    fn flush_aligned(&mut self) -> io::Result<()> {
+        let aligned = if self.direct {
+            self.buf.len - self.buf.len % DIRECT_IO_ALIGN
+        } else {
+            self.buf.len
+        };
+        if aligned == 0 {
+            return Ok(());
+        }
+
+        self.write_direct(aligned)?;
+
+        let start = self.buf.start;
+        self.buf.raw.copy_within(start + aligned..start + self.buf.len, start);
+        self.buf.len -= aligned;
+        Ok(())
+    }
+
+    /// Writes out everything, dropping `O_DIRECT` for the unaligned tail, and syncs the data.
+    fn finish(&mut self) -> io::Result<()> {
+        self.flush_aligned()?;
+        if self.buf.len > 0 {
+            if self.direct {
+                disable_direct(&self.file)?;
+                self.direct = false;
+            }
+            self.file.write_all(self.buf.filled())?;
+            self.buf.len = 0;
+        }
+        self.file.sync_data()
+    }
+}
+
+enum WriteState {
+    Idle(Option<Box<WriteInner>>),
+    Busy(JoinHandle<io::Result<Box<WriteInner>>>),
+}
+
+/// Async writer staging data in aligned buffers and writing them with `O_DIRECT`.
+///
+/// The unaligned tail is only written on shutdown, which also syncs the file. A writer
+/// dropped without shutting it down loses whatever it still buffers.
+pub struct DirectWriter {
+    state: WriteState,
+    // Set once shutdown has started finishing the file.
+    finished: bool,
+}
+
+impl DirectWriter {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let (file, direct) = open_direct(path.as_ref(), true)?;
+        Ok(Self {
+            state: WriteState::Idle(Some(Box::new(WriteInner {
+                file,
+                buf: AlignedBuf::new(DIRECT_IO_BUFFER_SIZE),
+                direct,
+            }))),
+            finished: false,
+        })
+    }
+
+    fn poll_idle(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if let WriteState::Busy(handle) = &mut self.state {
+            let res = ready!(Pin::new(handle).poll(cx));
+            self.state = WriteState::Idle(None);
+            self.state = WriteState::Idle(Some(res.map_err(io::Error::other)??));
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn spawn(&mut self, f: fn(&mut WriteInner) -> io::Result<()>) {
+        if let WriteState::Idle(inner) = &mut self.state {
+            if let Some(mut inner) = inner.take() {
+                self.state = WriteState::Busy(tokio::task::spawn_blocking(move || {
+                    f(&mut inner)?;
+                    Ok(inner)
+                }));
+            }
+        }
+    }
+
+    fn inner_mut(&mut self) -> io::Result<&mut WriteInner> {
+        match &mut self.state {
+            WriteState::Idle(Some(inner)) => Ok(inner),
+            _ => Err(io::Error::other("direct io writer is closed")),
+        }
+    }
+}
+
+impl AsyncWrite for DirectWriter {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, data: &[u8]) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        ready!(this.poll_idle(cx))?;
+
+        this.finished = false;
+        let inner = this.inner_mut()?;
+        let spare = inner.buf.spare();
+        let n = spare.len().min(data.len());
+        spare[..n].copy_from_slice(&data[..n]);
+        inner.buf.len += n;
+
+        if inner.buf.is_full() {
+            this.spawn(WriteInner::flush_aligned);
+        }
+
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // Partial blocks cannot be written with O_DIRECT, they are kept until shutdown.
+        self.get_mut().poll_idle(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            ready!(this.poll_idle(cx))?;
+            if this.finished {
+                return Poll::Ready(Ok(()));
+            }
+            this.inner_mut()?;
+            this.finished = true;
+            this.spawn(WriteInner::finish);
+        }
+    }
+}
+
+struct ReadInner {
+    file: File,
+    buf: AlignedBuf,
+    direct: bool,
+    // Next aligned file offset to read from.
+    pos: u64,
+    // Bytes to drop from the start of the next chunk.
+    skip: usize,
+    // Bytes of the buffered chunk already handed out.
+    consumed: usize,
+    // Bytes still owed to the caller.
+    remaining: usize,
+    eof: bool,
+}
+
+impl ReadInner {
+    fn fill(&mut self) -> io::Result<()> {
+        use std::os::unix::fs::FileExt;
+
+        let pos = self.pos;
+        let n = match self.file.read_at(self.buf.whole(), pos) {
+            Err(e) if self.direct && is_direct_unsupported(&e) => {
+                warn!("O_DIRECT read rejected, falling back to buffered io");
+                disable_direct(&self.file)?;
+                self.direct = false;
+                self.file.read_at(self.buf.whole(), pos)?
+            }
+            res => res?,
+        };
+
+        self.pos += n as u64;
+        self.buf.len = n;
+        self.consumed = self.skip.min(n);
+        self.skip -= self.consumed;
+        if n == 0 {
+            self.eof = true;
+        }
+        Ok(())
+    }
+}
+
+enum ReadState {
+    Idle(Option<Box<ReadInner>>),
+    Busy(JoinHandle<io::Result<Box<ReadInner>>>),
+}
+
+/// Async reader for a byte range using aligned `O_DIRECT` reads.
+pub struct DirectReader {
+    state: ReadState,
+}
+
+impl DirectReader {
+    pub fn open(path: impl AsRef<Path>, offset: usize, length: usize) -> io::Result<Self> {
+        let (file, direct) = open_direct(path.as_ref(), false)?;
+        let aligned = offset - offset % DIRECT_IO_ALIGN;
+        Ok(Self {
+            state: ReadState::Idle(Some(Box::new(ReadInner {
+                file,
+                buf: AlignedBuf::new(DIRECT_IO_BUFFER_SIZE),
+                direct,
+                pos: aligned as u64,
+                skip: offset - aligned,
+                consumed: 0,
+                remaining: length,
+                eof: false,
+            }))),
+        })
+    }
+}
+
+impl AsyncRead for DirectReader {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, out: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            match &mut this.state {
+                ReadState::Busy(handle) => {
+                    let res = ready!(Pin::new(handle).poll(cx));
+                    this.state = ReadState::Idle(None);
+                    this.state = ReadState::Idle(Some(res.map_err(io::Error::other)??));
+                }
+                ReadState::Idle(slot) => {
+                    let Some(inner) = slot.as_mut() else {
+                        return Poll::Ready(Err(io::Error::other("direct io reader is closed")));
+                    };
+
+                    if inner.remaining == 0 || inner.eof {
+                        return Poll::Ready(Ok(()));
+                    }
+
+                    if inner.consumed < inner.buf.len {
+                        let avail = &inner.buf.filled()[inner.consumed..];
+                        let n = avail.len().min(out.remaining()).min(inner.remaining);
+                        out.put_slice(&avail[..n]);
+                        inner.consumed += n;
+                        inner.remaining -= n;
+                        return Poll::Ready(Ok(()));
+                    }
+
+                    if let Some(mut inner) = slot.take() {
+                        this.state = ReadState::Busy(tokio::task::spawn_blocking(move || {
+                            inner.fill()?;
+                            Ok(inner)
+                        }));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn test_aligned_buf_alignment() {
+        let buf = AlignedBuf::new(DIRECT_IO_ALIGN * 2);
+        assert_eq!(buf.filled().as_ptr() as usize % DIRECT_IO_ALIGN, 0);
+        assert_eq!(buf.cap, DIRECT_IO_ALIGN * 2);
+    }
+
+    #[tokio::test]
+    async fn test_direct_write_read_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("part.1");
+
+        // Not a multiple of the alignment, so the tail path is exercised too.
+        let data: Vec<u8> = (0..DIRECT_IO_BUFFER_SIZE * 2 + 1234).map(|i| (i % 251) as u8).collect();
+
+        let mut w = DirectWriter::open(&path).unwrap();
+        w.write_all(&data).await.unwrap();
+        w.shutdown().await.unwrap();
+        drop(w);
+
+        assert_eq!(std::fs::read(&path).unwrap(), data);
+
+        let offset = 5000;
+        let length = DIRECT_IO_BUFFER_SIZE + 77;
+        let mut r = DirectReader::open(&path, offset, length).unwrap();
+        let mut got = Vec::new();
+        r.read_to_end(&mut got).await.unwrap();
+        assert_eq!(got, &data[offset..offset + length]);
+    }
+
+    #[tokio::test]
+    async fn test_direct_writer_truncates_existing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("part.1");
+        std::fs::write(&path, vec![1u8; DIRECT_IO_ALIGN * 4]).unwrap();
+        let data = vec![7u8; DIRECT_IO_ALIGN + 10];
+
+        let mut w = DirectWriter::open(&path).unwrap();
+        w.write_all(&data).await.unwrap();
+        w.shutdown().await.unwrap();
+        drop(w);
+
+        assert_eq!(std::fs::read(&path).unwrap(), data);
+    }
+}
@@ -38,6 +38,9 @@ pub struct Endpoint {
     pub pool_idx: i32,
     pub set_idx: i32,
     pub disk_idx: i32,
+
+    /// internode address given with a `?grid=host:port` annotation, if any.
+    pub grid_host: Option<String>,
 }
 
 impl Display for Endpoint {
@@ -62,9 +65,13 @@ impl TryFrom<&str> for Endpoint {
         }
 
         let mut is_local = false;
+        let mut grid_host = None;
         let url = match Url::parse(value) {
             #[allow(unused_mut)]
             Ok(mut url) if url.has_host() => {
+                // An optional `?grid=host:port` names a separate interface for internode traffic.
+                grid_host = take_grid_annotation(&mut url)?;
+
                 // URL style of endpoint.
                 // Valid URL style endpoint is
                 // - Scheme field must contain "http" or "https"
@@ -128,6 +135,7 @@ impl TryFrom<&str> for Endpoint {
             pool_idx: -1,
             set_idx: -1,
             disk_idx: -1,
+            grid_host,
         })
     }
 }
@@ -169,8 +177,13 @@ impl Endpoint {
         Ok(())
     }
 
-    /// returns the host to be used for grid connections.
+    /// returns the host to be used for grid connections, the annotated grid
+    /// address when present and the endpoint's own host otherwise.
     pub fn grid_host(&self) -> String {
+        if let Some(grid_host) = &self.grid_host {
+            return format!("{}://{}", self.url.scheme(), grid_host);
+        }
+
         match (self.url.host(), self.url.port()) {
             (Some(host), Some(port)) => {
                 debug!("grid_host scheme={}: host={}, port={}", self.url.scheme(), host, port);
@@ -210,6 +223,30 @@ impl Endpoint {
     }
 }
 
+/// removes a `grid=host:port` query from the URL and returns its value.
+/// Any other query is left in place for the endpoint format check to reject.
+fn take_grid_annotation(url: &mut Url) -> Result<Option<String>> {
+    let pairs: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+    let [(key, value)] = pairs.as_slice() else {
+        return Ok(None);
+    };
+    if key != "grid" {
+        return Ok(None);
+    }
+
+    // the annotation must be a bare host with an optional port
+    let grid = Url::parse(&format!("{}://{value}", url.scheme()))
+        .ok()
+        .filter(|u| u.has_host() && u.path() == "/" && u.username().is_empty() && u.query().is_none())
+        .ok_or_else(|| Error::other(format!("invalid grid host annotation: {value}")))?;
+
+    url.set_query(None);
+    Ok(Some(match grid.port() {
+        Some(port) => format!("{}:{port}", grid.host_str().unwrap_or_default()),
+        None => grid.host_str().unwrap_or_default().to_string(),
+    }))
+}
+
 /// parse a file path into a URL.
 fn url_parse_from_file_path(value: &str) -> Result<Url> {
     // Only check if the arg is an ip address and ask for scheme since its absent.
@@ -259,6 +296,7 @@ mod test {
                     pool_idx: -1,
                     set_idx: -1,
                     disk_idx: -1,
+                    grid_host: None,
                 }),
                 expected_type: Some(EndpointType::Path),
                 expected_err: None,
@@ -271,6 +309,7 @@ mod test {
                     pool_idx: -1,
                     set_idx: -1,
                     disk_idx: -1,
+                    grid_host: None,
                 }),
                 expected_type: Some(EndpointType::Url),
                 expected_err: None,
@@ -283,6 +322,7 @@ mod test {
                     pool_idx: -1,
                     set_idx: -1,
                     disk_idx: -1,
+                    grid_host: None,
                 }),
                 expected_type: Some(EndpointType::Url),
                 expected_err: None,
@@ -343,6 +383,7 @@ mod test {
                     pool_idx: -1,
                     set_idx: -1,
                     disk_idx: -1,
+                    grid_host: None,
                 }),
                 expected_type: Some(EndpointType::Url),
                 expected_err: None,
@@ -457,6 +498,24 @@ mod test {
         assert_eq!(file_endpoint.grid_host(), "");
     }
 
+    #[test]
+    fn test_endpoint_grid_annotation() {
+        let endpoint = Endpoint::try_from("http://example.com:9000/path?grid=10.0.0.5:9100").unwrap();
+        assert_eq!(endpoint.grid_host.as_deref(), Some("10.0.0.5:9100"));
+        assert_eq!(endpoint.grid_host(), "http://10.0.0.5:9100");
+        // the public address is unchanged and the annotation is not part of the disk identity
+        assert_eq!(endpoint.host_port(), "example.com:9000");
+        assert_eq!(endpoint.to_string(), "http://example.com:9000/path");
+
+        let endpoint = Endpoint::try_from("http://example.com:9000/path").unwrap();
+        assert_eq!(endpoint.grid_host, None);
+        assert_eq!(endpoint.grid_host(), "http://example.com:9000");
+
+        assert!(Endpoint::try_from("http://example.com:9000/path?grid=").is_err());
+        assert!(Endpoint::try_from("http://example.com:9000/path?grid=a:1/x").is_err());
+        assert!(Endpoint::try_from("http://example.com:9000/path?other=1").is_err());
+    }
+
     #[test]
     fn test_endpoint_host_port() {
         let endpoint = Endpoint::try_from("http://example.com:9000/path").unwrap();
@@ -16,7 +16,6 @@
 use std::hash::{Hash, Hasher};
 use std::io::{self};
 use std::path::PathBuf;
-use tracing::error;
 
 pub type Error = DiskError;
 pub type Result<T> = core::result::Result<T, Error>;
@@ -138,6 +137,24 @@ pub enum DiskError {
     #[error("erasure read quorum")]
     ErasureReadQuorum,
 
+    #[error("remote drive does not implement {op}, peer version {peer_version}")]
+    RemoteNotImplemented { op: &'static str, peer_version: String },
+
+    #[error("reconstructed data of part {0} does not match the surviving shards")]
+    ReconstructionFailed(usize),
+
+    #[error("request deadline exceeded")]
+    DeadlineExceeded,
+
+    #[error("data dir referenced by xl.meta is missing")]
+    DataDirMissing,
+
+    #[error("remote drive circuit open, failing fast")]
+    CircuitOpen,
+
+    #[error("surviving parity shard does not match the reconstructed data")]
+    ParityMismatch,
+
     #[error("io error {0}")]
     Io(io::Error),
 }
@@ -249,6 +266,9 @@ impl From<DiskError> for std::io::Error {
 
 impl From<tonic::Status> for DiskError {
     fn from(e: tonic::Status) -> Self {
+        if e.code() == tonic::Code::DeadlineExceeded {
+            return DiskError::DeadlineExceeded;
+        }
         DiskError::other(e.message().to_string())
     }
 }
@@ -360,6 +380,15 @@ impl Clone for DiskError {
             DiskError::ErasureWriteQuorum => DiskError::ErasureWriteQuorum,
             DiskError::ErasureReadQuorum => DiskError::ErasureReadQuorum,
             DiskError::ShortWrite => DiskError::ShortWrite,
+            DiskError::RemoteNotImplemented { op, peer_version } => DiskError::RemoteNotImplemented {
+                op,
+                peer_version: peer_version.clone(),
+            },
+            DiskError::ReconstructionFailed(part) => DiskError::ReconstructionFailed(*part),
+            DiskError::DeadlineExceeded => DiskError::DeadlineExceeded,
+            DiskError::DataDirMissing => DiskError::DataDirMissing,
+            DiskError::CircuitOpen => DiskError::CircuitOpen,
+            DiskError::ParityMismatch => DiskError::ParityMismatch,
         }
     }
 }
@@ -406,6 +435,12 @@ impl DiskError {
             DiskError::ErasureWriteQuorum => 0x25,
             DiskError::ErasureReadQuorum => 0x26,
             DiskError::ShortWrite => 0x27,
+            DiskError::RemoteNotImplemented { .. } => 0x28,
+            DiskError::ReconstructionFailed(_) => 0x29,
+            DiskError::DeadlineExceeded => 0x2A,
+            DiskError::DataDirMissing => 0x2B,
+            DiskError::CircuitOpen => 0x2C,
+            DiskError::ParityMismatch => 0x2D,
         }
     }
 
@@ -450,6 +485,15 @@ impl DiskError {
             0x25 => Some(DiskError::ErasureWriteQuorum),
             0x26 => Some(DiskError::ErasureReadQuorum),
             0x27 => Some(DiskError::ShortWrite),
+            0x28 => Some(DiskError::RemoteNotImplemented {
+                op: "unknown",
+                peer_version: String::new(),
+            }),
+            0x29 => Some(DiskError::ReconstructionFailed(0)),
+            0x2A => Some(DiskError::DeadlineExceeded),
+            0x2B => Some(DiskError::DataDirMissing),
+            0x2C => Some(DiskError::CircuitOpen),
+            0x2D => Some(DiskError::ParityMismatch),
             _ => None,
         }
     }
@@ -550,6 +594,10 @@ mod tests {
             DiskError::MethodNotAllowed,
             DiskError::ErasureWriteQuorum,
             DiskError::ErasureReadQuorum,
+            DiskError::DeadlineExceeded,
+            DiskError::DataDirMissing,
+            DiskError::CircuitOpen,
+            DiskError::ParityMismatch,
         ];
 
         for error in errors {
@@ -18,6 +18,7 @@ pub static OBJECT_OP_IGNORED_ERRS: &[Error] = &[
     Error::DiskNotFound,
     Error::FaultyDisk,
     Error::FaultyRemoteDisk,
+    Error::CircuitOpen,
     Error::DiskAccessDenied,
     Error::DiskOngoingReq,
     Error::UnformattedDisk,
@@ -27,11 +28,17 @@ pub static BUCKET_OP_IGNORED_ERRS: &[Error] = &[
     Error::DiskNotFound,
     Error::FaultyDisk,
     Error::FaultyRemoteDisk,
+    Error::CircuitOpen,
     Error::DiskAccessDenied,
     Error::UnformattedDisk,
 ];
 
-pub static BASE_IGNORED_ERRS: &[Error] = &[Error::DiskNotFound, Error::FaultyDisk, Error::FaultyRemoteDisk];
+pub static BASE_IGNORED_ERRS: &[Error] = &[
+    Error::DiskNotFound,
+    Error::FaultyDisk,
+    Error::FaultyRemoteDisk,
+    Error::CircuitOpen,
+];
 
 pub fn reduce_write_quorum_errs(errors: &[Option<Error>], ignored_errs: &[Error], quorun: usize) -> Option<Error> {
     reduce_quorum_errs(errors, ignored_errs, quorun, Error::ErasureWriteQuorum)
@@ -0,0 +1,84 @@
+// Copyright 2024 RustFS Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Counts consecutive faults per drive and notifies a handler once a drive keeps failing,
+//! so higher layers can take it offline or queue a heal.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+
+use super::endpoint::Endpoint;
+use super::error::DiskError;
+
+pub type FaultHandler = Arc<dyn Fn(&Endpoint, &DiskError) + Send + Sync>;
+
+pub const DEFAULT_FAULT_THRESHOLD: u32 = 3;
+
+static FAULT_THRESHOLD: AtomicU32 = AtomicU32::new(DEFAULT_FAULT_THRESHOLD);
+static FAULT_HANDLER: LazyLock<RwLock<Option<FaultHandler>>> = LazyLock::new(|| RwLock::new(None));
+static CONSECUTIVE_FAULTS: LazyLock<Mutex<HashMap<String, u32>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Calls `handler` when a drive reaches the fault threshold, once until it succeeds again
+pub fn set_fault_handler(handler: FaultHandler) {
+    *FAULT_HANDLER.write().unwrap() = Some(handler);
+}
+
+pub fn clear_fault_handler() {
+    *FAULT_HANDLER.write().unwrap() = None;
+    CONSECUTIVE_FAULTS.lock().unwrap().clear();
+}
+
+/// Number of consecutive faults before the handler runs, at least one
+pub fn set_fault_threshold(threshold: u32) {
+    FAULT_THRESHOLD.store(threshold.max(1), Ordering::Relaxed);
+}
+
+pub fn has_fault_handler() -> bool {
+    FAULT_HANDLER.read().unwrap().is_some()
+}
+
+// Errors that say something about the drive itself rather than the object asked for.
+fn is_fault(err: &DiskError) -> bool {
+    matches!(
+        err,
+        DiskError::DiskNotFound | DiskError::FaultyDisk | DiskError::FaultyRemoteDisk | DiskError::CircuitOpen | DiskError::Io(_)
+    )
+}
+
+pub(crate) fn record<T>(endpoint: &Endpoint, res: &Result<T, DiskError>) {
+    let key = endpoint.to_string();
+
+    let err = match res {
+        Err(err) if is_fault(err) => err,
+        _ => {
+            CONSECUTIVE_FAULTS.lock().unwrap().remove(&key);
+            return;
+        }
+    };
+
+    let count = {
+        let mut faults = CONSECUTIVE_FAULTS.lock().unwrap();
+        let count = faults.entry(key).or_default();
+        *count += 1;
+        *count
+    };
+
+    if count == FAULT_THRESHOLD.load(Ordering::Relaxed) {
+        let handler = FAULT_HANDLER.read().unwrap().clone();
+        if let Some(handler) = handler {
+            handler(endpoint, err);
+        }
+    }
+}
@@ -87,6 +87,9 @@ pub enum DistributionAlgoVersion {
     V3,
 }
 
+/// Distribution algorithm newly formatted deployments are written with.
+pub const DISTRIBUTION_ALGO_CURRENT: DistributionAlgoVersion = DistributionAlgoVersion::V3;
+
 /// format.json currently has the format:
 ///
 /// ```json
@@ -121,6 +124,32 @@ pub struct FormatV3 {
     pub disk_info: Option<DiskInfo>,
 }
 
+/// format.json as written by any erasure format version, older versions
+/// list the set of a single set deployment as `jbod` with the drive id in `disk`,
+/// and leave out the distribution algorithm.
+#[derive(Debug, Deserialize)]
+struct FormatLegacy {
+    version: FormatMetaVersion,
+    format: FormatBackend,
+    id: Uuid,
+    xl: FormatErasureLegacy,
+}
+
+#[derive(Debug, Deserialize)]
+struct FormatErasureLegacy {
+    version: FormatErasureVersion,
+    #[serde(default)]
+    this: Option<Uuid>,
+    #[serde(default)]
+    disk: Option<Uuid>,
+    #[serde(default)]
+    sets: Vec<Vec<Uuid>>,
+    #[serde(default)]
+    jbod: Vec<Uuid>,
+    #[serde(rename = "distributionAlgo", default)]
+    distribution_algo: Option<DistributionAlgoVersion>,
+}
+
 impl TryFrom<&[u8]> for FormatV3 {
     type Error = JsonError;
 
@@ -152,7 +181,7 @@ impl FormatV3 {
             sets: (0..num_sets)
                 .map(|_| (0..set_len).map(|_| Uuid::new_v4()).collect())
                 .collect(),
-            distribution_algo: DistributionAlgoVersion::V3,
+            distribution_algo: DISTRIBUTION_ALGO_CURRENT,
         };
 
         Self {
@@ -164,6 +193,40 @@ impl FormatV3 {
         }
     }
 
+    /// Reads a format.json written by an older erasure format version and returns it in the current one,
+    /// with the deployment id, drive ids, set layout and distribution algorithm unchanged.
+    /// Returns `None` when `data` is already current.
+    pub fn upgrade(data: &[u8]) -> Result<Option<Self>> {
+        let legacy: FormatLegacy = serde_json::from_slice(data)?;
+        if legacy.version != FormatMetaVersion::V1 {
+            return Err(Error::other("invalid FormatMetaVersion"));
+        }
+
+        let xl = legacy.xl;
+        let (this, sets) = match xl.version {
+            FormatErasureVersion::V3 => return Ok(None),
+            FormatErasureVersion::V2 => (xl.this, xl.sets),
+            FormatErasureVersion::V1 => (xl.disk, vec![xl.jbod]),
+            FormatErasureVersion::Unknown => return Err(Error::other("invalid FormatErasureVersion")),
+        };
+        if sets.iter().all(|set| set.is_empty()) {
+            return Err(Error::other("format has no drives"));
+        }
+
+        Ok(Some(Self {
+            version: FormatMetaVersion::V1,
+            format: legacy.format,
+            id: legacy.id,
+            erasure: FormatErasureV3 {
+                version: FormatErasureVersion::V3,
+                this: this.unwrap_or_default(),
+                sets,
+                distribution_algo: xl.distribution_algo.unwrap_or(DistributionAlgoVersion::V1),
+            },
+            disk_info: None,
+        }))
+    }
+
     /// Returns the number of drives in the erasure set.
     pub fn drives(&self) -> usize {
         self.erasure.sets.iter().map(|v| v.len()).sum()
@@ -543,4 +606,38 @@ mod test {
         assert_eq!(original.erasure.sets.len(), deserialized.erasure.sets.len());
         assert_eq!(original.erasure.distribution_algo, deserialized.erasure.distribution_algo);
     }
+
+    #[test]
+    fn test_format_upgrade() {
+        let v1 = r#"{
+            "version": "1",
+            "format": "xl",
+            "id": "321b3874-987d-4c15-8fa5-757c956b1243",
+            "xl": {
+                "version": "1",
+                "disk": "c26315da-05cf-4778-a9ea-b44ea09f58c5",
+                "jbod": [
+                    "8ab9a908-f869-4f1f-8e42-eb067ffa7eb5",
+                    "c26315da-05cf-4778-a9ea-b44ea09f58c5"
+                ]
+            }
+        }"#;
+
+        let format = FormatV3::upgrade(v1.as_bytes()).unwrap().unwrap();
+        assert_eq!(format.id, Uuid::parse_str("321b3874-987d-4c15-8fa5-757c956b1243").unwrap());
+        assert_eq!(format.erasure.version, FormatErasureVersion::V3);
+        assert_eq!(format.erasure.this, Uuid::parse_str("c26315da-05cf-4778-a9ea-b44ea09f58c5").unwrap());
+        assert_eq!(format.erasure.sets.len(), 1);
+        assert_eq!(format.erasure.sets[0].len(), 2);
+        assert_eq!(format.erasure.distribution_algo, DistributionAlgoVersion::V1);
+        assert_eq!(format.find_disk_index_by_disk_id(format.erasure.this).unwrap(), (0, 1));
+
+        // the upgraded format is current
+        let json = format.to_json().unwrap();
+        assert!(FormatV3::upgrade(json.as_bytes()).unwrap().is_none());
+
+        // a version newer than this build knows is not touched
+        let unknown = r#"{"version":"1","format":"xl","id":"321b3874-987d-4c15-8fa5-757c956b1243","xl":{"version":"9"}}"#;
+        assert!(FormatV3::upgrade(unknown.as_bytes()).is_err());
+    }
 }
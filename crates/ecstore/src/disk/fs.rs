@@ -115,6 +115,40 @@ pub async fn open_file(path: impl AsRef<Path>, mode: FileMode) -> io::Result<Fil
     opts.open(path.as_ref()).await
 }
 
+/// Reserves `size` bytes of disk space for the file at `path`, creating it if needed, without
+/// changing its length. Filesystems that can't preallocate are left to allocate as data arrives.
+#[cfg(target_os = "linux")]
+pub async fn preallocate(path: impl AsRef<Path>, size: u64) -> io::Result<()> {
+    use nix::errno::Errno;
+    use nix::fcntl::{FallocateFlags, fallocate};
+
+    let path = path.as_ref().to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let existed = path.exists();
+        let file = std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        match fallocate(&file, FallocateFlags::FALLOC_FL_KEEP_SIZE, 0, size as i64) {
+            Ok(()) | Err(Errno::EOPNOTSUPP) => Ok(()),
+            Err(err) => {
+                // a failed fallocate can keep the blocks it did get, don't leave them reserved
+                if !existed {
+                    let _ = std::fs::remove_file(&path);
+                }
+                Err(err.into())
+            }
+        }
+    })
+    .await?
+}
+
+#[cfg(not(target_os = "linux"))]
+pub async fn preallocate(_path: impl AsRef<Path>, _size: u64) -> io::Result<()> {
+    Ok(())
+}
+
 pub async fn access(path: impl AsRef<Path>) -> io::Result<()> {
     fs::metadata(path).await?;
     Ok(())
@@ -16,8 +16,8 @@ use super::error::{Error, Result};
 use super::os::{is_root_disk, rename_all};
 use super::{
     BUCKET_META_PREFIX, CheckPartsResp, DeleteOptions, DiskAPI, DiskInfo, DiskInfoOptions, DiskLocation, DiskMetrics,
-    FileInfoVersions, RUSTFS_META_BUCKET, ReadMultipleReq, ReadMultipleResp, ReadOptions, RenameDataResp,
-    STORAGE_FORMAT_FILE_BACKUP, UpdateMetadataOpts, VolumeInfo, WalkDirOptions, os,
+    FileInfoVersions, MountInfo, RUSTFS_META_BUCKET, ReadMultipleReq, ReadMultipleResp, ReadOptions, RenameDataResp,
+    RenamePartResult, STORAGE_FORMAT_FILE_BACKUP, StatInfo, UpdateMetadataOpts, VolumeInfo, WalkDirOptions, os,
 };
 use super::{endpoint::Endpoint, error::DiskError, format::FormatV3};
 
@@ -30,9 +30,10 @@ use crate::disk::fs::{
     O_APPEND, O_CREATE, O_RDONLY, O_TRUNC, O_WRONLY, access, lstat, lstat_std, remove, remove_all_std, remove_std, rename,
 };
 use crate::disk::os::{check_path_length, is_empty_dir};
+use crate::disk::read_ahead::{ReadAheadReader, SequentialReads};
 use crate::disk::{
     CHECK_PART_FILE_CORRUPT, CHECK_PART_FILE_NOT_FOUND, CHECK_PART_SUCCESS, CHECK_PART_UNKNOWN, CHECK_PART_VOLUME_NOT_FOUND,
-    FileReader, RUSTFS_META_TMP_DELETED_BUCKET, conv_part_err_to_int,
+    FileReader, RUSTFS_META_TMP_BUCKET, RUSTFS_META_TMP_DELETED_BUCKET, conv_part_err_to_int,
 };
 use crate::disk::{FileWriter, STORAGE_FORMAT_FILE};
 use crate::global::{GLOBAL_IsErasureSD, GLOBAL_RootDiskThreshold};
@@ -55,7 +56,6 @@ use tokio::time::interval;
 use crate::erasure_coding::bitrot_verify;
 use bytes::Bytes;
 use path_absolutize::Absolutize;
-use rustfs_common::defer;
 use rustfs_filemeta::{
     Cache, FileInfo, FileInfoOpts, FileMeta, MetaCacheEntry, MetacacheWriter, Opts, RawFileInfo, UpdateFn, get_file_info,
     read_xl_meta_no_data,
@@ -65,8 +65,10 @@ use rustfs_utils::os::get_info;
 use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::io::SeekFrom;
+use std::pin::Pin;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU32, Ordering};
+use std::task::{Context, Poll};
 use std::time::{Duration, SystemTime};
 use std::{
     fs::Metadata,
@@ -111,11 +113,26 @@ pub struct LocalDisk {
     pub endpoint: Endpoint,
     pub disk_info_cache: Arc<Cache<DiskInfo>>,
     pub scanning: AtomicU32,
+    pub healing: AtomicU32,
     pub rotational: bool,
     pub fstype: String,
     pub major: u64,
     pub minor: u64,
     pub nrrequests: u64,
+    // Use O_DIRECT for data files at least this large, see DiskOption::direct_io_threshold.
+    pub direct_io_threshold: Option<usize>,
+    // Read sequential file streams ahead in chunks of this size, see DiskOption::read_ahead.
+    pub read_ahead: Option<usize>,
+    // Keep xl.meta.bkp next to xl.meta, see DiskOption::meta_backup.
+    pub meta_backup: bool,
+    // Free space writes must leave behind, see DiskOption::min_free_bytes and min_free_pct.
+    pub min_free_bytes: u64,
+    pub min_free_pct: u64,
+    sequential_reads: SequentialReads,
+    // Top level entries under RUSTFS_META_TMP_BUCKET still being written, gc_tmp leaves them alone.
+    tmp_inflight: TmpInflight,
+    // Held per part path while a part is committed, so a part's data and metadata always come from the same upload.
+    part_commit: PartCommitLocks,
     // pub id: Mutex<Option<Uuid>>,
     // pub format_data: Mutex<Vec<u8>>,
     // pub format_file_info: Mutex<Option<Metadata>>,
@@ -123,6 +140,86 @@ pub struct LocalDisk {
     exit_signal: Option<tokio::sync::broadcast::Sender<()>>,
 }
 
+type PartCommitLocks = std::sync::Mutex<HashMap<PathBuf, Arc<tokio::sync::Mutex<()>>>>;
+
+// Writers of each temp entry, an entry may take several files at once.
+type TmpInflight = Arc<std::sync::Mutex<HashMap<String, usize>>>;
+
+// Marks a temp entry as being written until dropped, so a write that fails or is abandoned
+// part-way leaves it to gc_tmp.
+struct TmpGuard {
+    inflight: TmpInflight,
+    entry: String,
+}
+
+impl Drop for TmpGuard {
+    fn drop(&mut self) {
+        let mut inflight = self.inflight.lock().unwrap();
+        if let Some(writers) = inflight.get_mut(&self.entry) {
+            *writers -= 1;
+            if *writers == 0 {
+                inflight.remove(&self.entry);
+            }
+        }
+    }
+}
+
+// A temp file writer, its entry is in flight while the writer is alive.
+struct TmpFileWriter {
+    inner: FileWriter,
+    _tmp: TmpGuard,
+}
+
+impl AsyncWrite for TmpFileWriter {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+struct PartCommitGuard<'a> {
+    locks: &'a PartCommitLocks,
+    path: PathBuf,
+    _guard: tokio::sync::OwnedMutexGuard<()>,
+}
+
+impl Drop for PartCommitGuard<'_> {
+    fn drop(&mut self) {
+        let mut locks = self.locks.lock().unwrap();
+        // only the map and this guard still hold the lock, nobody is waiting on the part
+        if locks.get(&self.path).is_some_and(|lock| Arc::strong_count(lock) == 2) {
+            locks.remove(&self.path);
+        }
+    }
+}
+
+/// Outcome of a passed [`LocalDisk::preflight`].
+#[derive(Debug, Clone)]
+pub struct PreflightReport {
+    pub disk_id: Uuid,
+    /// Time taken to write, read back and remove the probe file, a baseline for the drive.
+    pub latency: Duration,
+}
+
+/// Keeps a [`LocalDisk`] reported as scanning or healing until dropped,
+/// so the state is cleared on early returns and panics alike.
+pub struct DiskStateGuard<'a> {
+    counter: &'a AtomicU32,
+}
+
+impl Drop for DiskStateGuard<'_> {
+    fn drop(&mut self) {
+        self.counter.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
 impl Drop for LocalDisk {
     fn drop(&mut self) {
         if let Some(exit_signal) = self.exit_signal.take() {
@@ -233,11 +330,20 @@ impl LocalDisk {
             format_info: RwLock::new(format_info),
             disk_info_cache: Arc::new(cache),
             scanning: AtomicU32::new(0),
+            healing: AtomicU32::new(0),
             rotational: Default::default(),
             fstype: Default::default(),
             minor: Default::default(),
             major: Default::default(),
             nrrequests: Default::default(),
+            direct_io_threshold: None,
+            read_ahead: None,
+            meta_backup: false,
+            min_free_bytes: 0,
+            min_free_pct: 0,
+            sequential_reads: SequentialReads::default(),
+            tmp_inflight: Arc::default(),
+            part_commit: std::sync::Mutex::new(HashMap::new()),
             // // format_legacy,
             // format_file_info: Mutex::new(format_meta),
             // format_data: Mutex::new(format_data),
@@ -263,6 +369,17 @@ impl LocalDisk {
 
         disk.make_meta_volumes().await?;
 
+        if let Ok(mount) = disk.fs_mount_info().await {
+            if mount.durability_at_risk {
+                warn!(
+                    "drive {} is on a {} mount with options {}, acknowledged writes may be lost on power failure",
+                    disk.endpoint,
+                    mount.fs_type,
+                    mount.options.join(",")
+                );
+            }
+        }
+
         let (exit_tx, exit_rx) = tokio::sync::broadcast::channel(1);
         disk.exit_signal = Some(exit_tx);
 
@@ -318,6 +435,147 @@ impl LocalDisk {
         Ok(())
     }
 
+    fn tmp_entry(volume: &str, path: &str) -> Option<String> {
+        if volume != RUSTFS_META_TMP_BUCKET {
+            return None;
+        }
+
+        path.split(SLASH_SEPARATOR)
+            .find(|p| !p.is_empty())
+            .filter(|p| *p != ".trash")
+            .map(|p| p.to_string())
+    }
+
+    fn track_tmp(&self, volume: &str, path: &str) -> Option<TmpGuard> {
+        let entry = Self::tmp_entry(volume, path)?;
+        *self.tmp_inflight.lock().unwrap().entry(entry.clone()).or_default() += 1;
+        Some(TmpGuard {
+            inflight: self.tmp_inflight.clone(),
+            entry,
+        })
+    }
+
+    // Keeps the temp entry of the guard in flight for as long as the writer is alive.
+    fn tmp_writer(tmp: Option<TmpGuard>, writer: FileWriter) -> FileWriter {
+        match tmp {
+            Some(tmp) => Box::new(TmpFileWriter {
+                inner: writer,
+                _tmp: tmp,
+            }),
+            None => writer,
+        }
+    }
+
+    // Commits of the same part wait for each other, different parts commit in parallel.
+    async fn lock_part_commit(&self, part_path: &Path) -> PartCommitGuard<'_> {
+        let lock = self
+            .part_commit
+            .lock()
+            .unwrap()
+            .entry(part_path.to_path_buf())
+            .or_default()
+            .clone();
+        PartCommitGuard {
+            locks: &self.part_commit,
+            path: part_path.to_path_buf(),
+            _guard: lock.lock_owned().await,
+        }
+    }
+
+    /// Removes entries under `.rustfs.sys/tmp` and its `.trash` that were not modified
+    /// for `older_than`, skipping temp files of writes still in flight.
+    /// Returns the number of bytes reclaimed.
+    pub async fn gc_tmp(&self, older_than: Duration) -> Result<u64> {
+        let cutoff = SystemTime::now().checked_sub(older_than).unwrap_or(SystemTime::UNIX_EPOCH);
+        let tmp = self.get_bucket_path(RUSTFS_META_TMP_BUCKET)?;
+        let trash = self.get_bucket_path(RUSTFS_META_TMP_DELETED_BUCKET)?;
+
+        let mut reclaimed = 0;
+        for dir in [&tmp, &trash] {
+            let mut entries = match fs::read_dir(dir).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(to_file_error(e).into()),
+            };
+
+            while let Some(entry) = entries.next_entry().await.map_err(to_file_error)? {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if dir == &tmp && (name == ".trash" || self.tmp_inflight.lock().unwrap().contains_key(&name)) {
+                    continue;
+                }
+
+                let path = entry.path();
+                let (size, modified) = tree_size_and_mtime(&path).await;
+                if modified.is_some_and(|m| m > cutoff) {
+                    continue;
+                }
+
+                let res = if entry.file_type().await.map_err(to_file_error)?.is_dir() {
+                    fs::remove_dir_all(&path).await
+                } else {
+                    fs::remove_file(&path).await
+                };
+                match res {
+                    Ok(_) => reclaimed += size,
+                    Err(e) if e.kind() == ErrorKind::NotFound => {}
+                    Err(e) => warn!("gc_tmp remove {:?} failed: {:?}", path, e),
+                }
+            }
+        }
+
+        Ok(reclaimed)
+    }
+
+    /// Reports the drive as scanning in `disk_info` until the guard is dropped.
+    pub fn begin_scan(&self) -> DiskStateGuard<'_> {
+        self.scanning.fetch_add(1, Ordering::SeqCst);
+        DiskStateGuard { counter: &self.scanning }
+    }
+
+    /// Reports the drive as healing in `disk_info` until the guard is dropped.
+    pub fn begin_heal(&self) -> DiskStateGuard<'_> {
+        self.healing.fetch_add(1, Ordering::SeqCst);
+        DiskStateGuard { counter: &self.healing }
+    }
+
+    /// Checks a drive before it serves traffic: it has to take writes, carry a format.json and
+    /// hold the position in its set its endpoint names. Unformatted and inconsistent drives fail
+    /// with their usual errors.
+    pub async fn preflight(&self) -> Result<PreflightReport> {
+        let probe = format!("preflight-{}", Uuid::new_v4());
+        let data = Bytes::from_static(b"rustfs preflight probe");
+
+        let start = std::time::Instant::now();
+        let round_trip = async {
+            self.write_all(RUSTFS_META_TMP_BUCKET, &probe, data.clone()).await?;
+            let read = self.read_all(RUSTFS_META_TMP_BUCKET, &probe).await;
+            let deleted = self
+                .delete(
+                    RUSTFS_META_TMP_BUCKET,
+                    &probe,
+                    DeleteOptions {
+                        immediate: true,
+                        ..Default::default()
+                    },
+                )
+                .await;
+            if read? != data {
+                return Err(DiskError::FileCorrupt);
+            }
+            deleted
+        };
+        round_trip
+            .await
+            .map_err(|err| DiskError::other(format!("preflight: drive {} is not writable: {err}", self.endpoint)))?;
+        let latency = start.elapsed();
+
+        let Some(disk_id) = self.get_disk_id().await? else {
+            return Err(DiskError::UnformattedDisk);
+        };
+
+        Ok(PreflightReport { disk_id, latency })
+    }
+
     fn is_valid_volname(volname: &str) -> bool {
         if volname.len() < 3 {
             return false;
@@ -530,29 +788,26 @@ impl LocalDisk {
 
         let meta_path = file_path.as_ref().join(Path::new(STORAGE_FORMAT_FILE));
 
-        let res = {
-            if read_data {
-                self.read_all_data_with_dmtime(bucket, volume_dir, meta_path).await
-            } else {
-                match self.read_metadata_with_dmtime(meta_path).await {
-                    Ok(res) => Ok(res),
-                    Err(err) => {
-                        if err == Error::FileNotFound
-                            && !skip_access_checks(volume_dir.as_ref().to_string_lossy().to_string().as_str())
-                        {
-                            if let Err(e) = access(volume_dir.as_ref()).await {
-                                if e.kind() == ErrorKind::NotFound {
-                                    // warn!("read_metadata_with_dmtime os err {:?}", &aerr);
-                                    return Err(DiskError::VolumeNotFound);
-                                }
-                            }
-                        }
+        let res = self.read_raw_meta(bucket, volume_dir.as_ref(), &meta_path, read_data).await;
+
+        // a missing xl.meta means no object, only a damaged one is worth the backup
+        let corrupt = match &res {
+            Ok((buf, _)) => !FileMeta::is_xl2_v1_format(buf),
+            Err(err) => !matches!(
+                err,
+                DiskError::FileNotFound
+                    | DiskError::VolumeNotFound
+                    | DiskError::FileNameTooLong
+                    | DiskError::DiskNotFound
+                    | DiskError::FaultyDisk
+            ),
+        };
 
-                        Err(err)
-                    }
-                }
+        if corrupt && self.meta_backup {
+            if let Some(res) = self.read_raw_backup(bucket, volume_dir.as_ref(), file_path.as_ref()).await {
+                return Ok(res);
             }
-        };
+        }
 
         let (buf, mtime) = res?;
         if buf.is_empty() {
@@ -562,6 +817,57 @@ impl LocalDisk {
         Ok((buf, mtime))
     }
 
+    async fn read_raw_meta(
+        &self,
+        bucket: &str,
+        volume_dir: &Path,
+        meta_path: &Path,
+        read_data: bool,
+    ) -> Result<(Vec<u8>, Option<OffsetDateTime>)> {
+        if read_data {
+            return self.read_all_data_with_dmtime(bucket, volume_dir, meta_path).await;
+        }
+
+        match self.read_metadata_with_dmtime(meta_path).await {
+            Ok(res) => Ok(res),
+            Err(err) => {
+                if err == Error::FileNotFound && !skip_access_checks(volume_dir.to_string_lossy().to_string().as_str()) {
+                    if let Err(e) = access(volume_dir).await {
+                        if e.kind() == ErrorKind::NotFound {
+                            // warn!("read_metadata_with_dmtime os err {:?}", &aerr);
+                            return Err(DiskError::VolumeNotFound);
+                        }
+                    }
+                }
+
+                Err(err)
+            }
+        }
+    }
+
+    /// Reads `xl.meta.bkp` in place of a corrupt `xl.meta`. Only the corruption is reported,
+    /// the primary is left for heal to rewrite from the other disks.
+    async fn read_raw_backup(
+        &self,
+        bucket: &str,
+        volume_dir: &Path,
+        file_path: &Path,
+    ) -> Option<(Vec<u8>, Option<OffsetDateTime>)> {
+        let backup_path = file_path.join(Path::new(STORAGE_FORMAT_FILE_BACKUP));
+        let (buf, mtime) = self.read_all_data_with_dmtime(bucket, volume_dir, &backup_path).await.ok()?;
+        if FileMeta::load(&buf).is_err() {
+            return None;
+        }
+
+        let path = file_path.strip_prefix(volume_dir).ok()?.to_string_lossy().to_string();
+        warn!(
+            "{STORAGE_FORMAT_FILE} of {bucket}/{path} on {} is corrupt, reading {STORAGE_FORMAT_FILE_BACKUP}",
+            self.endpoint
+        );
+
+        Some((buf, mtime))
+    }
+
     async fn read_metadata(&self, file_path: impl AsRef<Path>) -> Result<Vec<u8>> {
         // TODO: suport timeout
         let (data, _) = self.read_metadata_with_dmtime(file_path.as_ref()).await?;
@@ -643,6 +949,32 @@ impl LocalDisk {
         Ok((bytes, modtime))
     }
 
+    // Whether the part already at `dst_path` makes the upload described by `meta` moot: it is
+    // the same part by etag and size, or a later upload of it going by mod time, with the etag
+    // breaking ties so every drive keeps the same one. Requires the part data to be present.
+    async fn committed_part_wins(
+        &self,
+        dst_volume: &str,
+        dst_path: &str,
+        dst_file_path: &Path,
+        meta: &[u8],
+    ) -> Option<RenamePartResult> {
+        let existing = self.read_all(dst_volume, format!("{dst_path}.meta").as_str()).await.ok()?;
+
+        let part = |buf: &[u8]| FileInfo::unmarshal(buf).ok().and_then(|fi| fi.parts.first().cloned());
+        let (old, new) = (part(&existing)?, part(meta)?);
+
+        let outcome = if !new.etag.is_empty() && (&old.etag, old.size) == (&new.etag, new.size) {
+            RenamePartResult::Unchanged
+        } else if (old.mod_time, &old.etag) > (new.mod_time, &new.etag) {
+            RenamePartResult::Superseded
+        } else {
+            return None;
+        };
+
+        lstat_std(dst_file_path).is_ok().then_some(outcome)
+    }
+
     async fn delete_versions_internal(&self, volume: &str, path: &str, fis: &Vec<FileInfo>) -> Result<()> {
         let volume_dir = self.get_bucket_path(volume)?;
         let xlpath = self.get_object_path(volume, format!("{path}/{STORAGE_FORMAT_FILE}").as_str())?;
@@ -685,6 +1017,7 @@ impl LocalDisk {
 
         // 没有版本了，删除 xl.meta
         if fm.versions.is_empty() {
+            self.delete_meta_backup(&volume_dir, path).await;
             self.delete_file(&volume_dir, &xlpath, true, false).await?;
             return Ok(());
         }
@@ -692,14 +1025,50 @@ impl LocalDisk {
         // 更新 xl.meta
         let buf = fm.marshal_msg()?;
 
-        let volume_dir = self.get_bucket_path(volume)?;
+        self.write_xl_meta(volume, path, &buf, true).await
+    }
 
-        self.write_all_private(volume, format!("{path}/{STORAGE_FORMAT_FILE}").as_str(), buf.into(), true, &volume_dir)
+    // Writes the xl.meta of `path` along with its backup, so the backup never lags behind it.
+    async fn write_xl_meta(&self, volume: &str, path: &str, buf: &[u8], sync: bool) -> Result<()> {
+        self.write_all_meta(volume, format!("{path}/{STORAGE_FORMAT_FILE}").as_str(), buf, sync)
             .await?;
-
+        self.write_meta_backup(volume, path, buf, sync).await;
         Ok(())
     }
 
+    // A backup that failed to update is removed, reading an outdated one would lose the change.
+    async fn write_meta_backup(&self, volume: &str, path: &str, buf: &[u8], sync: bool) {
+        if !self.meta_backup {
+            return;
+        }
+        let Ok(volume_dir) = self.get_bucket_path(volume) else {
+            return;
+        };
+        if let Err(err) = self
+            .write_all_private(
+                volume,
+                format!("{path}/{STORAGE_FORMAT_FILE_BACKUP}").as_str(),
+                Bytes::copy_from_slice(buf),
+                sync,
+                &volume_dir,
+            )
+            .await
+        {
+            warn!("write {STORAGE_FORMAT_FILE_BACKUP} of {volume}/{path} failed: {:?}", err);
+            self.delete_meta_backup(&volume_dir, path).await;
+        }
+    }
+
+    // The backup has to go with the last version, or it would keep the object dir around.
+    async fn delete_meta_backup(&self, volume_dir: &Path, path: &str) {
+        let backup_path = volume_dir.join(Path::new(format!("{path}/{STORAGE_FORMAT_FILE_BACKUP}").as_str()));
+        if let Err(err) = self.delete_file(&volume_dir.to_path_buf(), &backup_path, false, false).await {
+            if err != DiskError::FileNotFound {
+                warn!("delete {STORAGE_FORMAT_FILE_BACKUP} of {path} failed: {:?}", err);
+            }
+        }
+    }
+
     async fn write_all_meta(&self, volume: &str, path: &str, buf: &[u8], sync: bool) -> Result<()> {
         let volume_dir = self.get_bucket_path(volume)?;
         let file_path = volume_dir.join(Path::new(&path));
@@ -791,6 +1160,49 @@ impl LocalDisk {
         Ok(f)
     }
 
+    fn use_direct_io(&self, size: usize) -> bool {
+        cfg!(not(windows)) && self.direct_io_threshold.is_some_and(|threshold| size >= threshold)
+    }
+
+    // Fails with DataDirMissing when `fi` keeps its parts in a data dir that is not on this
+    // disk, as an interrupted rename_data leaves behind. Only the version being served is
+    // checked, raw reads of the whole xl.meta are left to heal.
+    fn check_data_dir(&self, volume: &str, path: &str, fi: &FileInfo) -> Result<()> {
+        if fi.deleted || fi.is_remote() || fi.inline_data() || fi.size == 0 {
+            return Ok(());
+        }
+
+        let Some(data_dir) = fi.data_dir else {
+            return Ok(());
+        };
+
+        let dir = self.get_object_path(volume, format!("{path}/{data_dir}").as_str())?;
+        if lstat_std(&dir).is_err() {
+            return Err(DiskError::DataDirMissing);
+        }
+
+        Ok(())
+    }
+
+    // Bytes kept free on a disk of `total` bytes, the larger of the two reservations.
+    fn reserved_space(&self, total: u64) -> u64 {
+        self.min_free_bytes.max(total / 100 * self.min_free_pct)
+    }
+
+    // Fails with DiskFull when writing `size` more bytes would eat into the reservation.
+    async fn check_free_space(&self, size: u64) -> Result<()> {
+        if self.min_free_bytes == 0 && self.min_free_pct == 0 {
+            return Ok(());
+        }
+
+        let info = Cache::get(self.disk_info_cache.clone()).await?;
+        if info.free.saturating_sub(size) < self.reserved_space(info.total) {
+            return Err(DiskError::DiskFull);
+        }
+
+        Ok(())
+    }
+
     #[allow(dead_code)]
     fn get_metrics(&self) -> DiskMetrics {
         DiskMetrics::default()
@@ -804,9 +1216,7 @@ impl LocalDisk {
         sum: &[u8],
         shard_size: usize,
     ) -> Result<()> {
-        let file = super::fs::open_file(part_path, O_CREATE | O_WRONLY)
-            .await
-            .map_err(to_file_error)?;
+        let file = super::fs::open_file(part_path, O_RDONLY).await.map_err(to_file_error)?;
 
         let meta = file.metadata().await.map_err(to_file_error)?;
         let file_size = meta.len() as usize;
@@ -825,32 +1235,6 @@ impl LocalDisk {
         out: &mut MetacacheWriter<W>,
         objs_returned: &mut i32,
     ) -> Result<()> {
-        let forward = {
-            opts.forward_to.as_ref().filter(|v| v.starts_with(&*current)).map(|v| {
-                let forward = v.trim_start_matches(&*current);
-                if let Some(idx) = forward.find('/') {
-                    forward[..idx].to_owned()
-                } else {
-                    forward.to_owned()
-                }
-            })
-            // if let Some(forward_to) = &opts.forward_to {
-
-            // } else {
-            //     None
-            // }
-            // if !opts.forward_to.is_empty() && opts.forward_to.starts_with(&*current) {
-            //     let forward = opts.forward_to.trim_start_matches(&*current);
-            //     if let Some(idx) = forward.find('/') {
-            //         &forward[..idx]
-            //     } else {
-            //         forward
-            //     }
-            // } else {
-            //     ""
-            // }
-        };
-
         if opts.limit > 0 && *objs_returned >= opts.limit {
             return Ok(());
         }
@@ -877,6 +1261,13 @@ impl LocalDisk {
         let s = SLASH_SEPARATOR.chars().next().unwrap_or_default();
         *current = current.trim_matches(s).to_owned();
 
+        // Full path prefix of the entries in this directory, used to compare against forward_to.
+        let dir_prefix = if current.is_empty() {
+            String::new()
+        } else {
+            format!("{current}{SLASH_SEPARATOR}")
+        };
+
         let bucket = opts.bucket.as_str();
 
         let mut dir_objes = HashSet::new();
@@ -896,11 +1287,10 @@ impl LocalDisk {
                 }
             }
 
-            if let Some(forward) = &forward {
-                if &entry < forward {
-                    *item = "".to_owned();
-                    continue;
-                }
+            // Skip subtrees that sort entirely before forward_to, but keep the one containing it.
+            if !entry.ends_with(STORAGE_FORMAT_FILE) && is_before_forward(opts, &format!("{dir_prefix}{entry}"), true) {
+                *item = "".to_owned();
+                continue;
             }
 
             if entry.ends_with(SLASH_SEPARATOR) {
@@ -923,11 +1313,19 @@ impl LocalDisk {
                     .read_metadata(self.get_object_path(bucket, format!("{}/{}", &current, &entry).as_str())?)
                     .await?;
 
+                if !opts.size_in_range(&metadata) || !opts.keeps_versions(&metadata) {
+                    return Ok(());
+                }
+
                 // 用 strip_suffix 只删除一次
                 let entry = entry.strip_suffix(STORAGE_FORMAT_FILE).unwrap_or_default().to_owned();
                 let name = entry.trim_end_matches(SLASH_SEPARATOR);
                 let name = decode_dir_object(format!("{}/{}", &current, &name).as_str());
 
+                if is_before_forward(opts, &name, false) {
+                    return Ok(());
+                }
+
                 out.write_obj(&MetaCacheEntry {
                     name: name.clone(),
                     metadata,
@@ -943,16 +1341,6 @@ impl LocalDisk {
 
         entries.sort();
 
-        let mut entries = entries.as_slice();
-        if let Some(forward) = &forward {
-            for (i, entry) in entries.iter().enumerate() {
-                if entry >= forward || forward.starts_with(entry.as_str()) {
-                    entries = &entries[i..];
-                    break;
-                }
-            }
-        }
-
         let mut dir_stack: Vec<String> = Vec::with_capacity(5);
 
         for entry in entries.iter() {
@@ -967,23 +1355,24 @@ impl LocalDisk {
 
             let name = path_join_buf(&[current, entry]);
 
-            if !dir_stack.is_empty() {
-                if let Some(pop) = dir_stack.pop() {
-                    if pop < name {
-                        //
-                        out.write_obj(&MetaCacheEntry {
-                            name: pop.clone(),
-                            ..Default::default()
-                        })
-                        .await?;
-
-                        if opts.recursive {
-                            let mut opts = opts.clone();
-                            opts.filter_prefix = None;
-                            if let Err(er) = Box::pin(self.scan_dir(&mut pop.clone(), &opts, out, objs_returned)).await {
-                                error!("scan_dir err {:?}", er);
-                            }
-                        }
+            // Directories are pushed with a trailing slash, so emit every pending one that sorts
+            // before this entry. Stopping at the first one would drop the rest of the stack.
+            while dir_stack.last().is_some_and(|top| top < &name) {
+                let Some(pop) = dir_stack.pop() else { break };
+
+                if !is_before_forward(opts, &pop, false) {
+                    out.write_obj(&MetaCacheEntry {
+                        name: pop.clone(),
+                        ..Default::default()
+                    })
+                    .await?;
+                }
+
+                if opts.recursive {
+                    let mut opts = opts.clone();
+                    opts.filter_prefix = None;
+                    if let Err(er) = Box::pin(self.scan_dir(&mut pop.clone(), &opts, out, objs_returned)).await {
+                        error!("scan_dir err {:?}", er);
                     }
                 }
             }
@@ -1006,11 +1395,19 @@ impl LocalDisk {
 
             match self.read_metadata(self.get_object_path(&opts.bucket, fname.as_str())?).await {
                 Ok(res) => {
+                    if !opts.size_in_range(&res) || !opts.keeps_versions(&res) {
+                        continue;
+                    }
+
                     if is_dir_obj {
                         meta.name = meta.name.trim_end_matches(GLOBAL_DIR_SUFFIX_WITH_SLASH).to_owned();
                         meta.name.push_str(SLASH_SEPARATOR);
                     }
 
+                    if is_before_forward(opts, &meta.name, false) {
+                        continue;
+                    }
+
                     meta.metadata = res;
 
                     out.write_obj(&meta).await?;
@@ -1037,12 +1434,14 @@ impl LocalDisk {
                 return Ok(());
             }
 
-            out.write_obj(&MetaCacheEntry {
-                name: dir.clone(),
-                ..Default::default()
-            })
-            .await?;
-            *objs_returned += 1;
+            if !is_before_forward(opts, &dir, false) {
+                out.write_obj(&MetaCacheEntry {
+                    name: dir.clone(),
+                    ..Default::default()
+                })
+                .await?;
+                *objs_returned += 1;
+            }
 
             if opts.recursive {
                 let mut dir = dir;
@@ -1059,6 +1458,14 @@ impl LocalDisk {
     }
 }
 
+// Reports whether `path` sorts before `opts.forward_to` and must not be emitted.
+// With `may_contain` the path is a directory that is still walked when forward_to lies inside it.
+fn is_before_forward(opts: &WalkDirOptions, path: &str, may_contain: bool) -> bool {
+    opts.forward_to
+        .as_deref()
+        .is_some_and(|forward_to| path < forward_to && !(may_contain && forward_to.starts_with(path)))
+}
+
 fn is_root_path(path: impl AsRef<Path>) -> bool {
     path.as_ref().components().count() == 1 && path.as_ref().has_root()
 }
@@ -1100,6 +1507,56 @@ pub async fn read_file_metadata(p: impl AsRef<Path>) -> Result<Metadata> {
     Ok(meta)
 }
 
+// Unreferenced data dirs younger than this may belong to a write that has
+// renamed its data into place but not yet committed xl.meta.
+const ABANDONED_DATA_DIR_MIN_AGE: Duration = Duration::from_secs(60 * 60);
+
+// Total size of the regular files below `path`.
+async fn dir_size(path: impl AsRef<Path>) -> Result<u64> {
+    let mut size = 0;
+    let mut stack = vec![path.as_ref().to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let mut entries = fs::read_dir(&dir).await.map_err(to_file_error)?;
+        while let Some(entry) = entries.next_entry().await.map_err(to_file_error)? {
+            let meta = entry.metadata().await.map_err(to_file_error)?;
+            if meta.is_dir() {
+                stack.push(entry.path());
+            } else {
+                size += meta.len();
+            }
+        }
+    }
+
+    Ok(size)
+}
+
+// Total size of the regular files at or below `path` and the newest modification time
+// seen, entries that vanish while walking are ignored.
+async fn tree_size_and_mtime(path: &Path) -> (u64, Option<SystemTime>) {
+    let mut size = 0;
+    let mut newest: Option<SystemTime> = None;
+    let mut stack = vec![path.to_path_buf()];
+    while let Some(p) = stack.pop() {
+        let Ok(meta) = fs::symlink_metadata(&p).await else {
+            continue;
+        };
+        if let Ok(m) = meta.modified() {
+            newest = Some(newest.map_or(m, |n| n.max(m)));
+        }
+        if !meta.is_dir() {
+            size += meta.len();
+            continue;
+        }
+        if let Ok(mut entries) = fs::read_dir(&p).await {
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                stack.push(entry.path());
+            }
+        }
+    }
+
+    (size, newest)
+}
+
 fn skip_access_checks(p: impl AsRef<str>) -> bool {
     let vols = [
         super::RUSTFS_META_TMP_DELETED_BUCKET,
@@ -1247,9 +1704,53 @@ impl DiskAPI for LocalDisk {
 
     #[tracing::instrument(level = "debug", skip_all)]
     async fn write_all(&self, volume: &str, path: &str, data: Bytes) -> Result<()> {
+        // system metadata stays writable within the reservation so format, heal and MRF state can still be saved
+        if volume != RUSTFS_META_BUCKET {
+            self.check_free_space(data.len() as u64).await?;
+        }
+        let _tmp = self.track_tmp(volume, path);
         self.write_all_public(volume, path, data).await
     }
 
+    #[tracing::instrument(level = "debug", skip_all)]
+    async fn write_all_multi(&self, volume: &str, items: &[(String, Bytes)]) -> Result<Vec<Option<Error>>> {
+        let volume_dir = self.get_bucket_path(volume)?;
+
+        let mut errs = Vec::with_capacity(items.len());
+        let mut files = Vec::with_capacity(items.len());
+        let mut dirs = HashSet::new();
+        for (path, data) in items {
+            let res = async {
+                if volume != RUSTFS_META_BUCKET {
+                    self.check_free_space(data.len() as u64).await?;
+                }
+                let _tmp = self.track_tmp(volume, path);
+                self.write_all_private(volume, path, data.clone(), false, &volume_dir).await
+            }
+            .await;
+            if res.is_ok() {
+                let file_path = volume_dir.join(path);
+                if let Some(parent) = file_path.parent() {
+                    dirs.insert(parent.to_path_buf());
+                }
+                files.push(file_path);
+            }
+            errs.push(res.err());
+        }
+
+        // the files and then the directories they were created in are synced after the whole
+        // batch, so the writes reach the drive together
+        tokio::task::spawn_blocking(move || {
+            for path in files.iter().chain(dirs.iter()) {
+                std::fs::File::open(path).and_then(|f| f.sync_all()).map_err(to_file_error)?;
+            }
+            Ok::<_, Error>(())
+        })
+        .await??;
+
+        Ok(errs)
+    }
+
     #[tracing::instrument(skip(self))]
     async fn delete(&self, volume: &str, path: &str, opt: DeleteOptions) -> Result<()> {
         let volume_dir = self.get_bucket_path(volume)?;
@@ -1362,7 +1863,14 @@ impl DiskAPI for LocalDisk {
     }
 
     #[tracing::instrument(level = "debug", skip(self))]
-    async fn rename_part(&self, src_volume: &str, src_path: &str, dst_volume: &str, dst_path: &str, meta: Bytes) -> Result<()> {
+    async fn rename_part(
+        &self,
+        src_volume: &str,
+        src_path: &str,
+        dst_volume: &str,
+        dst_path: &str,
+        meta: Bytes,
+    ) -> Result<RenamePartResult> {
         let src_volume_dir = self.get_bucket_path(src_volume)?;
         let dst_volume_dir = self.get_bucket_path(dst_volume)?;
         if !skip_access_checks(src_volume) {
@@ -1413,15 +1921,38 @@ impl DiskAPI for LocalDisk {
             remove_std(&dst_file_path).map_err(to_file_error)?;
         }
 
-        rename_all(&src_file_path, &dst_file_path, &dst_volume_dir).await?;
-
+        let _commit = self.lock_part_commit(&dst_file_path).await;
+        let outcome = if src_is_dir {
+            None
+        } else {
+            self.committed_part_wins(dst_volume, dst_path, &dst_file_path, &meta).await
+        };
+
+        if let Some(outcome) = outcome {
+            // a retry of the part in place or an older upload racing a newer one, keep the part and drop the new copy
+            if let Err(err) = remove_std(&src_file_path).map_err(|e| DiskError::from(to_file_error(e))) {
+                if err != DiskError::FileNotFound {
+                    return Err(err);
+                }
+            }
+
+            if let Some(parent) = src_file_path.parent() {
+                self.delete_file(&src_volume_dir, &parent.to_path_buf(), false, false).await?;
+            }
+
+            return Ok(outcome);
+        }
+
+        // the rename replaces an older upload of this part, so its data is not left behind
+        rename_all(&src_file_path, &dst_file_path, &dst_volume_dir).await?;
+
         self.write_all(dst_volume, format!("{dst_path}.meta").as_str(), meta).await?;
 
         if let Some(parent) = src_file_path.parent() {
             self.delete_file(&src_volume_dir, &parent.to_path_buf(), false, false).await?;
         }
 
-        Ok(())
+        Ok(RenamePartResult::Written)
     }
 
     #[tracing::instrument(skip(self))]
@@ -1475,6 +2006,11 @@ impl DiskAPI for LocalDisk {
 
         rename_all(&src_file_path, &dst_file_path, &dst_volume_dir).await?;
 
+        if dst_volume == RUSTFS_META_BUCKET && dst_path == super::FORMAT_CONFIG_FILE {
+            // the cached format.json was replaced, read the new one from disk
+            self.format_info.write().await.data.clear();
+        }
+
         if let Some(parent) = src_file_path.parent() {
             let _ = self.delete_file(&src_volume_dir, &parent.to_path_buf(), false, false).await;
         }
@@ -1483,7 +2019,7 @@ impl DiskAPI for LocalDisk {
     }
 
     #[tracing::instrument(level = "debug", skip(self))]
-    async fn create_file(&self, origvolume: &str, volume: &str, path: &str, _file_size: i64) -> Result<FileWriter> {
+    async fn create_file(&self, origvolume: &str, volume: &str, path: &str, file_size: i64) -> Result<FileWriter> {
         if !origvolume.is_empty() {
             let origvolume_dir = self.get_bucket_path(origvolume)?;
             if !skip_access_checks(origvolume) {
@@ -1493,6 +2029,11 @@ impl DiskAPI for LocalDisk {
             }
         }
 
+        // like write_all, system metadata is exempt from the reservation
+        if volume != RUSTFS_META_BUCKET {
+            self.check_free_space(file_size.max(0) as u64).await?;
+        }
+        let tmp = self.track_tmp(volume, path);
         let volume_dir = self.get_bucket_path(volume)?;
         let file_path = volume_dir.join(Path::new(&path));
         check_path_length(file_path.to_string_lossy().to_string().as_str())?;
@@ -1502,11 +2043,35 @@ impl DiskAPI for LocalDisk {
         if let Some(parent) = file_path.parent() {
             os::make_dir_all(parent, &volume_dir).await?;
         }
+
+        // opened before the space is reserved, it truncates the file and would drop the reservation
+        #[cfg(not(windows))]
+        let direct = match file_size > 0 && self.use_direct_io(file_size as usize) {
+            true => Some(super::direct_io::DirectWriter::open(&file_path).map_err(to_file_error)?),
+            false => None,
+        };
+
+        // reserve the space up front, an upload that can't fit fails before any data is streamed
+        if file_size > 0 {
+            if let Err(err) = super::fs::preallocate(&file_path, file_size as u64).await {
+                #[cfg(not(windows))]
+                if direct.is_some() {
+                    let _ = super::fs::remove(&file_path).await;
+                }
+                return Err(to_file_error(err).into());
+            }
+        }
+
+        #[cfg(not(windows))]
+        if let Some(w) = direct {
+            return Ok(Self::tmp_writer(tmp, Box::new(w)));
+        }
+
         let f = super::fs::open_file(&file_path, O_CREATE | O_WRONLY)
             .await
             .map_err(to_file_error)?;
 
-        Ok(Box::new(f))
+        Ok(Self::tmp_writer(tmp, Box::new(f)))
 
         // Ok(())
     }
@@ -1514,6 +2079,7 @@ impl DiskAPI for LocalDisk {
     #[tracing::instrument(level = "debug", skip(self))]
     // async fn append_file(&self, volume: &str, path: &str, mut r: DuplexStream) -> Result<File> {
     async fn append_file(&self, volume: &str, path: &str) -> Result<FileWriter> {
+        let tmp = self.track_tmp(volume, path);
         let volume_dir = self.get_bucket_path(volume)?;
         if !skip_access_checks(volume) {
             access(&volume_dir)
@@ -1526,7 +2092,7 @@ impl DiskAPI for LocalDisk {
 
         let f = self.open_file(file_path, O_CREATE | O_APPEND | O_WRONLY, volume_dir).await?;
 
-        Ok(Box::new(f))
+        Ok(Self::tmp_writer(tmp, Box::new(f)))
     }
 
     // TODO: io verifier
@@ -1565,7 +2131,7 @@ impl DiskAPI for LocalDisk {
         let file_path = volume_dir.join(Path::new(&path));
         check_path_length(file_path.to_string_lossy().to_string().as_str())?;
 
-        let mut f = self.open_file(file_path, O_RDONLY, volume_dir).await?;
+        let mut f = self.open_file(&file_path, O_RDONLY, volume_dir).await?;
 
         let meta = f.metadata().await?;
         if meta.len() < (offset + length) as u64 {
@@ -1578,10 +2144,23 @@ impl DiskAPI for LocalDisk {
             return Err(DiskError::FileCorrupt);
         }
 
+        #[cfg(not(windows))]
+        if self.use_direct_io(length) {
+            drop(f);
+            let r = super::direct_io::DirectReader::open(&file_path, offset, length).map_err(to_file_error)?;
+            return Ok(Box::new(r));
+        }
+
         if offset > 0 {
             f.seek(SeekFrom::Start(offset as u64)).await?;
         }
 
+        if let Some(chunk_size) = self.read_ahead {
+            if self.sequential_reads.observe(&file_path, offset, length) && length > chunk_size {
+                return Ok(Box::new(ReadAheadReader::new(f, length, chunk_size)));
+            }
+        }
+
         Ok(Box::new(f))
     }
     #[tracing::instrument(level = "debug", skip(self))]
@@ -1614,6 +2193,34 @@ impl DiskAPI for LocalDisk {
         Ok(entries)
     }
 
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn list_dir_paged(
+        &self,
+        origvolume: &str,
+        volume: &str,
+        dir_path: &str,
+        after: Option<&str>,
+        count: i32,
+    ) -> Result<(Vec<String>, Option<String>)> {
+        // read_dir order is unspecified, the whole directory has to be sorted for a stable marker
+        let mut entries = self.list_dir(origvolume, volume, dir_path, -1).await?;
+        entries.sort_unstable();
+
+        let start = after.map_or(0, |after| entries.partition_point(|e| e.as_str() <= after));
+        let end = if count > 0 {
+            start.saturating_add(count as usize).min(entries.len())
+        } else {
+            entries.len()
+        };
+
+        let more = end < entries.len();
+        entries.truncate(end);
+        let page = entries.split_off(start);
+        let next_marker = if more { page.last().cloned() } else { None };
+
+        Ok((page, next_marker))
+    }
+
     // FIXME: TODO: io.writer TODO cancel
     #[tracing::instrument(level = "debug", skip(self, wr))]
     async fn walk_dir<W: AsyncWrite + Unpin + Send>(&self, opts: WalkDirOptions, wr: &mut W) -> Result<()> {
@@ -1642,13 +2249,15 @@ impl DiskAPI for LocalDisk {
             )?;
 
             if let Ok(data) = self.read_metadata(fpath).await {
-                let meta = MetaCacheEntry {
-                    name: opts.base_dir.clone(),
-                    metadata: data,
-                    ..Default::default()
-                };
-                out.write_obj(&meta).await?;
-                objs_returned += 1;
+                if opts.size_in_range(&data) {
+                    let meta = MetaCacheEntry {
+                        name: opts.base_dir.clone(),
+                        metadata: data,
+                        ..Default::default()
+                    };
+                    out.write_obj(&meta).await?;
+                    objs_returned += 1;
+                }
             }
         }
 
@@ -1738,6 +2347,16 @@ impl DiskAPI for LocalDisk {
                     xlmeta = nmeta
                 }
             }
+
+            // a corrupt xl.meta would otherwise drop every older version
+            if xlmeta.versions.is_empty() && self.meta_backup {
+                let backup_path = dst_volume_dir.join(Path::new(format!("{}/{}", dst_path, STORAGE_FORMAT_FILE_BACKUP).as_str()));
+                if let Ok(buf) = super::fs::read_file(&backup_path).await {
+                    if let Ok(nmeta) = FileMeta::load(&buf) {
+                        xlmeta = nmeta
+                    }
+                }
+            }
         }
 
         let mut skip_parent = dst_volume_dir.clone();
@@ -1774,13 +2393,17 @@ impl DiskAPI for LocalDisk {
             // TODO: Sign
         }
 
-        let new_dst_buf = xlmeta.marshal_msg()?;
+        let new_dst_buf = Bytes::from(xlmeta.marshal_msg()?);
 
-        self.write_all(src_volume, format!("{}/{}", &src_path, STORAGE_FORMAT_FILE).as_str(), new_dst_buf.into())
+        self.write_all(src_volume, format!("{}/{}", src_path, STORAGE_FORMAT_FILE).as_str(), new_dst_buf.clone())
             .await?;
         if let Some((src_data_path, dst_data_path)) = has_data_dir_path.as_ref() {
             let no_inline = fi.data.is_none() && fi.size > 0;
             if no_inline {
+                if fi.healing() {
+                    // A heal rewrites the data dir in place, move the stale copy out of the way.
+                    self.move_to_trash(dst_data_path, true, false).await?;
+                }
                 if let Err(err) = rename_all(&src_data_path, &dst_data_path, &skip_parent).await {
                     let _ = self.delete_file(&dst_volume_dir, dst_data_path, false, false).await;
                     info!(
@@ -1819,6 +2442,9 @@ impl DiskAPI for LocalDisk {
             return Err(err);
         }
 
+        // reads fall back to the backup when xl.meta is corrupt
+        self.write_meta_backup(dst_volume, dst_path, &new_dst_buf, true).await;
+
         if let Some(src_file_path_parent) = src_file_path.parent() {
             if src_volume != super::RUSTFS_META_MULTIPART_BUCKET {
                 let _ = remove_std(src_file_path_parent);
@@ -1952,9 +2578,7 @@ impl DiskAPI for LocalDisk {
 
             let wbuf = xl_meta.marshal_msg()?;
 
-            return self
-                .write_all_meta(volume, format!("{path}/{STORAGE_FORMAT_FILE}").as_str(), &wbuf, !opts.no_persistence)
-                .await;
+            return self.write_xl_meta(volume, path, &wbuf, !opts.no_persistence).await;
         }
 
         Err(Error::other("Invalid Argument"))
@@ -1978,8 +2602,9 @@ impl DiskAPI for LocalDisk {
 
         let fm_data = meta.marshal_msg()?;
 
-        self.write_all(volume, format!("{path}/{STORAGE_FORMAT_FILE}").as_str(), fm_data.into())
+        self.write_all(volume, format!("{path}/{STORAGE_FORMAT_FILE}").as_str(), fm_data.clone().into())
             .await?;
+        self.write_meta_backup(volume, path, &fm_data, true).await;
 
         Ok(())
     }
@@ -2001,6 +2626,7 @@ impl DiskAPI for LocalDisk {
         let (data, _) = self.read_raw(volume, file_dir, file_path, read_data).await?;
 
         let fi = get_file_info(&data, volume, path, version_id, FileInfoOpts { data: read_data }).await?;
+        self.check_data_dir(volume, path, &fi)?;
 
         Ok(fi)
     }
@@ -2084,9 +2710,7 @@ impl DiskAPI for LocalDisk {
 
         if !meta.versions.is_empty() {
             let buf = meta.marshal_msg()?;
-            return self
-                .write_all_meta(volume, format!("{path}{SLASH_SEPARATOR}{STORAGE_FORMAT_FILE}").as_str(), &buf, true)
-                .await;
+            return self.write_xl_meta(volume, path, &buf, true).await;
         }
 
         // opts.undo_write && opts.old_data_dir.is_some_and(f)
@@ -2095,10 +2719,14 @@ impl DiskAPI for LocalDisk {
                 let src_path =
                     file_path.join(Path::new(format!("{old_data_dir}{SLASH_SEPARATOR}{STORAGE_FORMAT_FILE_BACKUP}").as_str()));
                 let dst_path = file_path.join(Path::new(format!("{path}{SLASH_SEPARATOR}{STORAGE_FORMAT_FILE}").as_str()));
-                return rename_all(src_path, dst_path, file_path).await;
+                rename_all(src_path, dst_path, file_path).await?;
+                // the backup holds the write being undone
+                self.delete_meta_backup(&volume_dir, path).await;
+                return Ok(());
             }
         }
 
+        self.delete_meta_backup(&volume_dir, path).await;
         self.delete_file(&volume_dir, &xl_path, true, false).await
     }
     #[tracing::instrument(level = "debug", skip(self))]
@@ -2124,13 +2752,92 @@ impl DiskAPI for LocalDisk {
         Ok(errs)
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn stat_info_file(&self, volume: &str, path: &str, glob: bool) -> Result<Vec<StatInfo>> {
+        let volume_dir = self.get_bucket_path(volume)?;
+        if !skip_access_checks(volume) {
+            access(&volume_dir)
+                .await
+                .map_err(|e| to_access_error(e, DiskError::VolumeAccessDenied))?;
+        }
+
+        let root = volume_dir.join(Path::new(path));
+        check_path_length(root.to_string_lossy().as_ref())?;
+
+        let mut files = Vec::new();
+        if glob {
+            // collect every xl.meta under the prefix
+            let mut stack = vec![root];
+            while let Some(dir) = stack.pop() {
+                let mut entries = match fs::read_dir(&dir).await {
+                    Ok(entries) => entries,
+                    Err(e) if e.kind() == ErrorKind::NotFound || e.kind() == ErrorKind::NotADirectory => continue,
+                    Err(e) => return Err(to_file_error(e).into()),
+                };
+
+                while let Some(entry) = entries.next_entry().await.map_err(to_file_error)? {
+                    let file_type = entry.file_type().await.map_err(to_file_error)?;
+                    if file_type.is_dir() {
+                        stack.push(entry.path());
+                    } else if entry.file_name() == STORAGE_FORMAT_FILE {
+                        files.push(entry.path());
+                    }
+                }
+            }
+            files.sort();
+        } else {
+            files.push(root);
+        }
+
+        let mut stats = Vec::with_capacity(files.len());
+        for file in files {
+            let st = lstat(&file).await.map_err(to_file_error)?;
+            let name = file
+                .strip_prefix(&volume_dir)
+                .unwrap_or(&file)
+                .to_string_lossy()
+                .replace(std::path::MAIN_SEPARATOR, SLASH_SEPARATOR);
+
+            stats.push(StatInfo {
+                name,
+                size: st.len(),
+                mod_time: st.modified().ok().map(OffsetDateTime::from),
+                dir: st.is_dir(),
+            });
+        }
+
+        Ok(stats)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn read_parts(&self, volume: &str, paths: &[String]) -> Result<Vec<Result<RawFileInfo>>> {
+        let volume_dir = self.get_bucket_path(volume)?;
+        if !skip_access_checks(volume) {
+            access(&volume_dir)
+                .await
+                .map_err(|e| to_access_error(e, DiskError::VolumeAccessDenied))?;
+        }
+
+        let mut results = Vec::with_capacity(paths.len());
+        for path in paths.iter() {
+            let meta_path = volume_dir.join(Path::new(path)).join(STORAGE_FORMAT_FILE);
+            results.push(self.read_metadata(meta_path).await.map(|buf| RawFileInfo { buf }));
+        }
+
+        Ok(results)
+    }
+
     #[tracing::instrument(skip(self))]
     async fn read_multiple(&self, req: ReadMultipleReq) -> Result<Vec<ReadMultipleResp>> {
         let mut results = Vec::new();
         let mut found = 0;
 
         for v in req.files.iter() {
-            let fpath = self.get_object_path(&req.bucket, format!("{}/{}", &req.prefix, v).as_str())?;
+            let fpath = if req.prefix.is_empty() {
+                self.get_object_path(&req.bucket, v)?
+            } else {
+                self.get_object_path(&req.bucket, format!("{}/{}", req.prefix, v).as_str())?
+            };
             let mut res = ReadMultipleResp {
                 bucket: req.bucket.clone(),
                 prefix: req.prefix.clone(),
@@ -2184,6 +2891,56 @@ impl DiskAPI for LocalDisk {
         Ok(results)
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn clean_abandoned_data(&self, volume: &str, path: &str) -> Result<u64> {
+        let file_path = self.get_object_path(volume, path)?;
+        let meta_path = file_path.join(STORAGE_FORMAT_FILE);
+
+        let referenced_dirs = |buf: Vec<u8>| -> Result<HashSet<Uuid>> {
+            let fm = FileMeta::load(&buf)?;
+            Ok(fm.get_data_dirs()?.into_iter().flatten().collect())
+        };
+
+        let referenced = referenced_dirs(self.read_metadata(&meta_path).await?)?;
+
+        let entries = os::read_dir(&file_path, 0).await.map_err(to_file_error)?;
+
+        let mut reclaimed = 0;
+        for entry in entries.iter() {
+            let Some(name) = entry.strip_suffix(SLASH_SEPARATOR) else {
+                continue;
+            };
+            let Ok(data_dir) = Uuid::parse_str(name) else {
+                continue;
+            };
+            if referenced.contains(&data_dir) {
+                continue;
+            }
+
+            let dir_path = file_path.join(name);
+            let recent = lstat(&dir_path)
+                .await
+                .ok()
+                .and_then(|st| st.modified().ok())
+                .and_then(|mtime| mtime.elapsed().ok())
+                .is_none_or(|age| age < ABANDONED_DATA_DIR_MIN_AGE);
+            if recent {
+                continue;
+            }
+
+            // xl.meta may have been rewritten while we were scanning.
+            if referenced_dirs(self.read_metadata(&meta_path).await?)?.contains(&data_dir) {
+                continue;
+            }
+
+            let size = dir_size(&dir_path).await?;
+            self.move_to_trash(&dir_path, true, false).await?;
+            reclaimed += size;
+        }
+
+        Ok(reclaimed)
+    }
+
     #[tracing::instrument(skip(self))]
     async fn delete_volume(&self, volume: &str) -> Result<()> {
         let p = self.get_bucket_path(volume)?;
@@ -2200,6 +2957,17 @@ impl DiskAPI for LocalDisk {
         Ok(())
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn fs_mount_info(&self) -> Result<MountInfo> {
+        if !cfg!(target_os = "linux") {
+            return Err(DiskError::other("mount info is only available on linux"));
+        }
+
+        let mounts = fs::read_to_string("/proc/mounts").await.map_err(to_file_error)?;
+        let root = fs::canonicalize(&self.root).await.map_err(to_file_error)?;
+        MountInfo::find(&mounts, &root).ok_or_else(|| DiskError::other(format!("no mount found for {}", root.display())))
+    }
+
     #[tracing::instrument(skip(self))]
     async fn disk_info(&self, _: &DiskInfoOptions) -> Result<DiskInfo> {
         let mut info = Cache::get(self.disk_info_cache.clone()).await?;
@@ -2208,7 +2976,10 @@ impl DiskAPI for LocalDisk {
         info.rotational = self.rotational;
         info.mount_path = self.path().to_str().unwrap().to_string();
         info.endpoint = self.endpoint.to_string();
-        info.scanning = self.scanning.load(Ordering::SeqCst) == 1;
+        info.scanning = self.scanning.load(Ordering::SeqCst) > 0;
+        info.healing = self.healing.load(Ordering::SeqCst) > 0;
+        // report what writes can still use
+        info.free = info.free.saturating_sub(self.reserved_space(info.total));
 
         Ok(info)
     }
@@ -2221,8 +2992,7 @@ impl DiskAPI for LocalDisk {
         scan_mode: HealScanMode,
         we_sleep: ShouldSleepFn,
     ) -> Result<DataUsageCache> {
-        self.scanning.fetch_add(1, Ordering::SeqCst);
-        defer!(|| { self.scanning.fetch_sub(1, Ordering::SeqCst) });
+        let _scanning = self.begin_scan();
 
         // must before metadata_sys
         let Some(store) = new_object_layer_fn() else {
@@ -2558,6 +3328,219 @@ mod test {
         let _ = fs::remove_dir_all(&test_dir).await;
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_rename_part_retry() {
+        let test_dir = "./test_local_disk_rename_part";
+        let _ = fs::remove_dir_all(&test_dir).await;
+        fs::create_dir_all(&test_dir).await.unwrap();
+
+        let endpoint = Endpoint::try_from(test_dir).unwrap();
+        let disk = LocalDisk::new(&endpoint, false).await.unwrap();
+        disk.make_volume("tmp-volume").await.unwrap();
+        disk.make_volume("part-volume").await.unwrap();
+
+        let meta = |etag: &str, size: usize| -> Bytes {
+            let fi = FileInfo {
+                parts: vec![rustfs_filemeta::ObjectPartInfo {
+                    etag: etag.to_string(),
+                    number: 1,
+                    size,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            };
+            fi.marshal_msg().unwrap().into()
+        };
+        let upload = |data: &'static [u8]| {
+            let disk = &disk;
+            async move { disk.write_all("tmp-volume", "u1/part.1", data.into()).await.unwrap() }
+        };
+
+        upload(b"first").await;
+        let res = disk
+            .rename_part("tmp-volume", "u1/part.1", "part-volume", "up/part.1", meta("e1", 5))
+            .await
+            .unwrap();
+        assert_eq!(res, RenamePartResult::Written);
+
+        // The same call again finds the part in place and its source already gone.
+        let res = disk
+            .rename_part("tmp-volume", "u1/part.1", "part-volume", "up/part.1", meta("e1", 5))
+            .await
+            .unwrap();
+        assert_eq!(res, RenamePartResult::Unchanged);
+
+        // A re-upload with the same etag keeps the existing data and drops the temp copy.
+        upload(b"first").await;
+        let res = disk
+            .rename_part("tmp-volume", "u1/part.1", "part-volume", "up/part.1", meta("e1", 5))
+            .await
+            .unwrap();
+        assert_eq!(res, RenamePartResult::Unchanged);
+        assert!(disk.read_all("tmp-volume", "u1/part.1").await.is_err());
+        assert_eq!(disk.read_all("part-volume", "up/part.1").await.unwrap(), b"first".as_slice());
+
+        // Different content replaces the part and its metadata.
+        upload(b"second!").await;
+        let res = disk
+            .rename_part("tmp-volume", "u1/part.1", "part-volume", "up/part.1", meta("e2", 7))
+            .await
+            .unwrap();
+        assert_eq!(res, RenamePartResult::Written);
+        assert_eq!(disk.read_all("part-volume", "up/part.1").await.unwrap(), b"second!".as_slice());
+        let stored = FileInfo::unmarshal(&disk.read_all("part-volume", "up/part.1.meta").await.unwrap()).unwrap();
+        assert_eq!(stored.parts[0].etag, "e2");
+
+        let _ = fs::remove_dir_all(&test_dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_part_commit_locks_per_part() {
+        let test_dir = "./test_local_disk_part_commit";
+        let _ = fs::remove_dir_all(&test_dir).await;
+        fs::create_dir_all(&test_dir).await.unwrap();
+
+        let endpoint = Endpoint::try_from(test_dir).unwrap();
+        let disk = LocalDisk::new(&endpoint, false).await.unwrap();
+        let part1 = Path::new("bucket/object/part.1");
+        let part2 = Path::new("bucket/object/part.2");
+
+        let guard = disk.lock_part_commit(part1).await;
+        // another part commits while part.1 is held, the same part waits
+        let other = tokio::time::timeout(Duration::from_millis(100), disk.lock_part_commit(part2)).await;
+        assert!(other.is_ok());
+        drop(other);
+        let same = tokio::time::timeout(Duration::from_millis(100), disk.lock_part_commit(part1)).await;
+        assert!(same.is_err());
+
+        drop(guard);
+        assert!(disk.part_commit.lock().unwrap().is_empty());
+
+        let _ = fs::remove_dir_all(&test_dir).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_rename_part_last_writer_wins() {
+        let test_dir = "./test_local_disk_rename_part_race";
+        let _ = fs::remove_dir_all(&test_dir).await;
+        fs::create_dir_all(&test_dir).await.unwrap();
+
+        let endpoint = Endpoint::try_from(test_dir).unwrap();
+        let disk = LocalDisk::new(&endpoint, false).await.unwrap();
+        disk.make_volume("tmp-volume").await.unwrap();
+        disk.make_volume("part-volume").await.unwrap();
+
+        let now = OffsetDateTime::now_utc();
+        let meta = |etag: &str, size: usize, mod_time: OffsetDateTime| -> Bytes {
+            let fi = FileInfo {
+                parts: vec![rustfs_filemeta::ObjectPartInfo {
+                    etag: etag.to_string(),
+                    number: 1,
+                    size,
+                    mod_time: Some(mod_time),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            };
+            fi.marshal_msg().unwrap().into()
+        };
+
+        // The later upload lands first, the earlier one arriving after it is discarded.
+        disk.write_all("tmp-volume", "u2/part.1", b"newer".as_slice().into())
+            .await
+            .unwrap();
+        let res = disk
+            .rename_part("tmp-volume", "u2/part.1", "part-volume", "up/part.1", meta("new", 5, now))
+            .await
+            .unwrap();
+        assert_eq!(res, RenamePartResult::Written);
+
+        disk.write_all("tmp-volume", "u1/part.1", b"older!".as_slice().into())
+            .await
+            .unwrap();
+        let res = disk
+            .rename_part(
+                "tmp-volume",
+                "u1/part.1",
+                "part-volume",
+                "up/part.1",
+                meta("old", 6, now - Duration::from_secs(1)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res, RenamePartResult::Superseded);
+        assert!(disk.read_all("tmp-volume", "u1/part.1").await.is_err());
+        assert_eq!(disk.read_all("part-volume", "up/part.1").await.unwrap(), b"newer".as_slice());
+        let stored = FileInfo::unmarshal(&disk.read_all("part-volume", "up/part.1.meta").await.unwrap()).unwrap();
+        assert_eq!(stored.parts[0].etag, "new");
+
+        let _ = fs::remove_dir_all(&test_dir).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_gc_tmp() {
+        let test_dir = "./test_local_disk_gc_tmp";
+        let _ = fs::remove_dir_all(&test_dir).await;
+        fs::create_dir_all(&test_dir).await.unwrap();
+
+        let endpoint = Endpoint::try_from(test_dir).unwrap();
+        let disk = LocalDisk::new(&endpoint, false).await.unwrap();
+
+        let tmp = disk.get_bucket_path(RUSTFS_META_TMP_BUCKET).unwrap();
+        let trash = disk.get_bucket_path(RUSTFS_META_TMP_DELETED_BUCKET).unwrap();
+        let old = SystemTime::now() - Duration::from_secs(2 * 60 * 60);
+        let backdate = |p: PathBuf| {
+            std::fs::File::open(&p).unwrap().set_modified(old).unwrap();
+            std::fs::File::open(p.parent().unwrap()).unwrap().set_modified(old).unwrap();
+        };
+
+        disk.write_all_public(RUSTFS_META_TMP_BUCKET, "stale/part.1", Bytes::from_static(b"stale data"))
+            .await
+            .unwrap();
+        backdate(tmp.join("stale/part.1"));
+        disk.write_all_public(RUSTFS_META_TMP_BUCKET, "fresh/part.1", Bytes::from_static(b"fresh"))
+            .await
+            .unwrap();
+        // still being written through the DiskAPI
+        let mut writer = disk
+            .create_file("", RUSTFS_META_TMP_BUCKET, "inflight/part.1", 0)
+            .await
+            .unwrap();
+        writer.write_all(b"inflight").await.unwrap();
+        writer.flush().await.unwrap();
+        backdate(tmp.join("inflight/part.1"));
+        // a write that failed part-way leaves its temp entry behind
+        disk.write_all_public(RUSTFS_META_TMP_BUCKET, "failed/x", Bytes::from_static(b"failed"))
+            .await
+            .unwrap();
+        backdate(tmp.join("failed/x"));
+        assert!(
+            disk.write_all(RUSTFS_META_TMP_BUCKET, "failed/x/part.1", Bytes::from_static(b"data"))
+                .await
+                .is_err()
+        );
+        disk.write_all_public(RUSTFS_META_TMP_DELETED_BUCKET, "gone/xl.meta", Bytes::from_static(b"trash"))
+            .await
+            .unwrap();
+        backdate(trash.join("gone/xl.meta"));
+
+        let reclaimed = disk.gc_tmp(Duration::from_secs(60 * 60)).await.unwrap();
+        assert!(reclaimed >= b"stale data".len() as u64, "{reclaimed}");
+        assert!(!tmp.join("stale").exists());
+        assert!(!trash.join("gone").exists());
+        assert!(!tmp.join("failed").exists());
+        assert!(tmp.join("fresh/part.1").exists());
+        assert!(tmp.join("inflight/part.1").exists());
+
+        // once the writer is dropped its temp file is fair game
+        drop(writer);
+        assert_eq!(disk.gc_tmp(Duration::from_secs(60 * 60)).await.unwrap(), b"inflight".len() as u64);
+        assert!(!tmp.join("inflight").exists());
+        assert!(tmp.join("fresh/part.1").exists());
+
+        let _ = fs::remove_dir_all(&test_dir).await;
+    }
+
     #[tokio::test]
     async fn test_local_disk_volume_operations() {
         let test_dir = "./test_local_disk_volumes";
@@ -2613,6 +3596,632 @@ mod test {
         let _ = fs::remove_dir_all(&test_dir).await;
     }
 
+    #[tokio::test]
+    async fn test_local_disk_scan_state() {
+        let test_dir = "./test_local_disk_scan_state";
+        fs::create_dir_all(&test_dir).await.unwrap();
+
+        let endpoint = Endpoint::try_from(test_dir).unwrap();
+        let disk = Arc::new(LocalDisk::new(&endpoint, false).await.unwrap());
+        let opts = DiskInfoOptions::default();
+
+        let scan = |fail: bool| {
+            let disk = disk.clone();
+            async move {
+                let _scanning = disk.begin_scan();
+                let info = disk.disk_info(&DiskInfoOptions::default()).await?;
+                assert!(info.scanning);
+                if fail {
+                    return Err(DiskError::FaultyDisk);
+                }
+                Ok(())
+            }
+        };
+
+        scan(false).await.unwrap();
+        assert!(!disk.disk_info(&opts).await.unwrap().scanning);
+        assert!(scan(true).await.is_err());
+        assert!(!disk.disk_info(&opts).await.unwrap().scanning);
+
+        // a panicking scan still clears the flag
+        let panicking = disk.clone();
+        let res = tokio::spawn(async move {
+            let _scanning = panicking.begin_scan();
+            panic!("scan failed");
+        })
+        .await;
+        assert!(res.is_err());
+        assert!(!disk.disk_info(&opts).await.unwrap().scanning);
+
+        {
+            let _healing = disk.begin_heal();
+            assert!(disk.disk_info(&opts).await.unwrap().healing);
+        }
+        assert!(!disk.disk_info(&opts).await.unwrap().healing);
+
+        let _ = fs::remove_dir_all(&test_dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_local_disk_direct_io_threshold() {
+        let test_dir = "./test_local_disk_direct_io";
+        let _ = fs::remove_dir_all(&test_dir).await;
+        fs::create_dir_all(&test_dir).await.unwrap();
+
+        let endpoint = Endpoint::try_from(test_dir).unwrap();
+        let mut disk = LocalDisk::new(&endpoint, false).await.unwrap();
+        disk.direct_io_threshold = Some(64 * 1024);
+        disk.make_volume("test-volume").await.unwrap();
+
+        let small: Vec<u8> = (0..1000).map(|i| (i % 7) as u8).collect();
+        let large: Vec<u8> = (0..3 * 1024 * 1024 + 333).map(|i| (i % 251) as u8).collect();
+
+        for (name, data) in [("small/part.1", &small), ("large/part.1", &large)] {
+            let mut w = disk.create_file("", "test-volume", name, data.len() as i64).await.unwrap();
+            w.write_all(data).await.unwrap();
+            w.shutdown().await.unwrap();
+            drop(w);
+
+            let offset = data.len() / 3;
+            let length = data.len() - offset;
+            let mut r = disk.read_file_stream("test-volume", name, offset, length).await.unwrap();
+            let mut got = vec![0u8; length];
+            r.read_exact(&mut got).await.unwrap();
+            assert_eq!(got, &data[offset..]);
+        }
+
+        let _ = fs::remove_dir_all(&test_dir).await;
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_local_disk_create_file_preallocate_disk_full() {
+        let test_dir = "./test_local_disk_preallocate";
+        let _ = fs::remove_dir_all(&test_dir).await;
+        fs::create_dir_all(&test_dir).await.unwrap();
+
+        let endpoint = Endpoint::try_from(test_dir).unwrap();
+        let disk = LocalDisk::new(&endpoint, false).await.unwrap();
+        disk.make_volume("test-volume").await.unwrap();
+
+        // larger than the whole disk, reserved blocks included
+        let total = disk.disk_info(&DiskInfoOptions::default()).await.unwrap().total;
+        let err = disk
+            .create_file("", "test-volume", "big/part.1", (total + (1 << 30)) as i64)
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err, DiskError::DiskFull);
+        assert!(!disk.get_bucket_path("test-volume").unwrap().join("big/part.1").exists());
+
+        // a declared size that fits is reserved without changing the file length
+        let mut w = disk.create_file("", "test-volume", "small/part.1", 1 << 20).await.unwrap();
+        w.write_all(b"data").await.unwrap();
+        w.shutdown().await.unwrap();
+        drop(w);
+        let meta = std::fs::metadata(disk.get_bucket_path("test-volume").unwrap().join("small/part.1")).unwrap();
+        assert_eq!(meta.len(), 4);
+
+        let _ = fs::remove_dir_all(&test_dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_local_disk_min_free_reservation() {
+        let test_dir = "./test_local_disk_min_free";
+        let _ = fs::remove_dir_all(&test_dir).await;
+        fs::create_dir_all(&test_dir).await.unwrap();
+
+        let endpoint = Endpoint::try_from(test_dir).unwrap();
+        let mut disk = LocalDisk::new(&endpoint, false).await.unwrap();
+        disk.make_volume("test-volume").await.unwrap();
+        let raw = disk.disk_info(&DiskInfoOptions::default()).await.unwrap();
+
+        // leave some headroom above the reservation
+        disk.min_free_bytes = raw.free - (64 << 20);
+        let info = disk.disk_info(&DiskInfoOptions::default()).await.unwrap();
+        assert!(info.free <= 64 << 20, "reported free space ignores the reservation");
+        disk.write_all("test-volume", "small", Bytes::from_static(b"data"))
+            .await
+            .unwrap();
+        let err = disk
+            .create_file("", "test-volume", "big/part.1", 128 << 20)
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err, DiskError::DiskFull);
+
+        // at the boundary any write fails while raw space remains
+        disk.min_free_bytes = raw.free;
+        let err = disk
+            .write_all("test-volume", "small", Bytes::from_static(b"data"))
+            .await
+            .unwrap_err();
+        assert_eq!(err, DiskError::DiskFull);
+        assert_eq!(disk.disk_info(&DiskInfoOptions::default()).await.unwrap().free, 0);
+
+        disk.min_free_bytes = 0;
+        disk.min_free_pct = 100;
+        let err = disk.create_file("", "test-volume", "pct/part.1", 1).await.err().unwrap();
+        assert_eq!(err, DiskError::DiskFull);
+
+        // system metadata can still be written
+        disk.write_all(RUSTFS_META_BUCKET, "mrf/list.bin", Bytes::from_static(b"data"))
+            .await
+            .unwrap();
+        let mut writer = disk.create_file("", RUSTFS_META_BUCKET, "pct/part.1", 4).await.unwrap();
+        writer.write_all(b"data").await.unwrap();
+        writer.shutdown().await.unwrap();
+
+        let _ = fs::remove_dir_all(&test_dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_local_disk_write_all_multi() {
+        let test_dir = "./test_local_disk_write_all_multi";
+        let _ = fs::remove_dir_all(&test_dir).await;
+        fs::create_dir_all(&test_dir).await.unwrap();
+
+        let endpoint = Endpoint::try_from(test_dir).unwrap();
+        let disk = LocalDisk::new(&endpoint, false).await.unwrap();
+        disk.make_volume("test-volume").await.unwrap();
+
+        let items = vec![
+            ("buckets/a/.metadata.bin".to_string(), Bytes::from_static(b"meta-a")),
+            (format!("buckets/{}/.metadata.bin", "x".repeat(300)), Bytes::from_static(b"too-long")),
+            ("buckets/b/.metadata.bin".to_string(), Bytes::from_static(b"meta-b")),
+        ];
+        let errs = disk.write_all_multi("test-volume", &items).await.unwrap();
+        assert_eq!(errs.len(), 3);
+        assert!(errs[0].is_none());
+        assert_eq!(errs[1], Some(DiskError::FileNameTooLong));
+        assert!(errs[2].is_none());
+
+        // the failed item does not stop the rest of the batch
+        let data = disk.read_all("test-volume", "buckets/a/.metadata.bin").await.unwrap();
+        assert_eq!(&data[..], b"meta-a");
+        let data = disk.read_all("test-volume", "buckets/b/.metadata.bin").await.unwrap();
+        assert_eq!(&data[..], b"meta-b");
+
+        let _ = fs::remove_dir_all(&test_dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_local_disk_preflight() {
+        let test_dir = "./test_local_disk_preflight";
+        fs::create_dir_all(&test_dir).await.unwrap();
+
+        let mut endpoint = Endpoint::try_from(test_dir).unwrap();
+        endpoint.set_set_index(0);
+        endpoint.set_disk_index(1);
+        let disk = LocalDisk::new(&endpoint, false).await.unwrap();
+
+        assert_eq!(disk.preflight().await.unwrap_err(), DiskError::UnformattedDisk);
+
+        let mut format = FormatV3::new(1, 4);
+        format.erasure.this = format.erasure.sets[0][1];
+        std::fs::write(&disk.format_path, format.to_json().unwrap()).unwrap();
+
+        let report = disk.preflight().await.unwrap();
+        assert_eq!(report.disk_id, format.erasure.this);
+        let tmp = disk.get_bucket_path(RUSTFS_META_TMP_BUCKET).unwrap();
+        let probes = std::fs::read_dir(&tmp)
+            .unwrap()
+            .filter(|entry| {
+                entry
+                    .as_ref()
+                    .unwrap()
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with("preflight-")
+            })
+            .count();
+        assert_eq!(probes, 0);
+
+        // a read-only drive, simulated with a file where the tmp volume should be
+        // since file modes don't bind root
+        std::fs::remove_dir_all(&tmp).unwrap();
+        std::fs::write(&tmp, b"").unwrap();
+        let err = disk.preflight().await.unwrap_err();
+        assert!(err.to_string().contains("is not writable"), "{err}");
+
+        let _ = fs::remove_dir_all(&test_dir).await;
+    }
+
+    async fn plant_object(disk: &LocalDisk, volume: &str, name: &str, size: i64) -> FileInfo {
+        let mut fi = FileInfo::new(name, 2, 2);
+        fi.volume = volume.to_string();
+        fi.name = name.to_string();
+        fi.version_id = Some(Uuid::new_v4());
+        fi.data_dir = Some(Uuid::new_v4());
+        fi.mod_time = Some(OffsetDateTime::now_utc());
+        fi.size = size;
+        disk.write_metadata("", volume, name, fi.clone()).await.unwrap();
+        fi
+    }
+
+    async fn walk_names(disk: &LocalDisk, opts: WalkDirOptions) -> Vec<String> {
+        let mut buf = Vec::new();
+        disk.walk_dir(opts, &mut buf).await.unwrap();
+        if buf.is_empty() {
+            return Vec::new();
+        }
+
+        MetacacheWriter::new(&mut buf).close().await.unwrap();
+        let mut reader = rustfs_filemeta::MetacacheReader::new(std::io::Cursor::new(buf));
+        reader
+            .read_all()
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|e| e.is_object())
+            .map(|e| e.name)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_walk_dir_size_filter() {
+        let test_dir = "./test_local_disk_walk_size";
+        fs::create_dir_all(&test_dir).await.unwrap();
+
+        let endpoint = Endpoint::try_from(test_dir).unwrap();
+        let disk = LocalDisk::new(&endpoint, false).await.unwrap();
+        disk.make_volume("test-volume").await.unwrap();
+
+        for (name, size) in [("a", 10), ("b", 100), ("c", 1000), ("d/e", 5000)] {
+            plant_object(&disk, "test-volume", name, size).await;
+        }
+
+        let opts = |min_size, max_size| WalkDirOptions {
+            bucket: "test-volume".to_string(),
+            recursive: true,
+            min_size,
+            max_size,
+            ..Default::default()
+        };
+
+        assert_eq!(walk_names(&disk, opts(None, None)).await, vec!["a", "b", "c", "d/e"]);
+        assert_eq!(walk_names(&disk, opts(Some(100), None)).await, vec!["b", "c", "d/e"]);
+        assert_eq!(walk_names(&disk, opts(None, Some(1000))).await, vec!["a", "b", "c"]);
+        assert_eq!(walk_names(&disk, opts(Some(50), Some(2000))).await, vec!["b", "c"]);
+        assert!(walk_names(&disk, opts(Some(10_000), None)).await.is_empty());
+
+        let _ = fs::remove_dir_all(&test_dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_walk_dir_include_tombstones() {
+        let test_dir = "./test_local_disk_walk_tombstones";
+        let _ = fs::remove_dir_all(&test_dir).await;
+        fs::create_dir_all(&test_dir).await.unwrap();
+
+        let endpoint = Endpoint::try_from(test_dir).unwrap();
+        let disk = LocalDisk::new(&endpoint, false).await.unwrap();
+        disk.make_volume("test-volume").await.unwrap();
+
+        for name in ["a", "b"] {
+            plant_object(&disk, "test-volume", name, 10).await;
+        }
+        let mut marker = FileInfo::new("a", 2, 2);
+        marker.volume = "test-volume".to_string();
+        marker.name = "a".to_string();
+        marker.version_id = Some(Uuid::new_v4());
+        marker.mod_time = Some(OffsetDateTime::now_utc() + time::Duration::seconds(1));
+        marker.deleted = true;
+        disk.write_metadata("", "test-volume", "a", marker).await.unwrap();
+
+        let opts = |include_tombstones| WalkDirOptions {
+            bucket: "test-volume".to_string(),
+            recursive: true,
+            include_tombstones,
+            include_free_versions: true,
+            ..Default::default()
+        };
+
+        assert_eq!(walk_names(&disk, opts(true)).await, vec!["a", "b"]);
+        assert_eq!(walk_names(&disk, opts(false)).await, vec!["b"]);
+        // unset, the walk emits everything as before
+        let unset = WalkDirOptions {
+            bucket: "test-volume".to_string(),
+            recursive: true,
+            ..Default::default()
+        };
+        assert_eq!(walk_names(&disk, unset).await, vec!["a", "b"]);
+
+        let _ = fs::remove_dir_all(&test_dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_walk_dir_forward_to() {
+        let test_dir = "./test_local_disk_walk_forward";
+        fs::create_dir_all(&test_dir).await.unwrap();
+
+        let endpoint = Endpoint::try_from(test_dir).unwrap();
+        let disk = LocalDisk::new(&endpoint, false).await.unwrap();
+        disk.make_volume("test-volume").await.unwrap();
+
+        let keys = ["a", "b-x/1", "b/c", "b/d/1", "b/d/2", "b/e", "c"];
+        for key in keys {
+            plant_object(&disk, "test-volume", key, 1).await;
+        }
+
+        let walk = |forward_to: &str| WalkDirOptions {
+            bucket: "test-volume".to_string(),
+            recursive: true,
+            forward_to: Some(forward_to.to_string()),
+            ..Default::default()
+        };
+
+        let all = walk_names(
+            &disk,
+            WalkDirOptions {
+                forward_to: None,
+                ..walk("")
+            },
+        )
+        .await;
+        assert_eq!(all, keys);
+
+        // Resuming at every existing key returns it and everything after it.
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(walk_names(&disk, walk(key)).await, &keys[i..], "forward_to {key}");
+        }
+
+        // Synthetic keys between existing ones lose nothing at the boundary.
+        assert_eq!(walk_names(&disk, walk("b/cc")).await, &keys[3..]);
+        assert_eq!(walk_names(&disk, walk("b/d/15")).await, &keys[4..]);
+        assert_eq!(walk_names(&disk, walk("b/")).await, &keys[2..]);
+        assert_eq!(walk_names(&disk, walk("b")).await, &keys[1..]);
+        assert!(walk_names(&disk, walk("d")).await.is_empty());
+
+        let _ = fs::remove_dir_all(&test_dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_list_dir_paged() {
+        let test_dir = "./test_local_disk_list_dir_paged";
+        let _ = fs::remove_dir_all(&test_dir).await;
+        fs::create_dir_all(&test_dir).await.unwrap();
+
+        let endpoint = Endpoint::try_from(test_dir).unwrap();
+        let disk = LocalDisk::new(&endpoint, false).await.unwrap();
+        disk.make_volume("test-volume").await.unwrap();
+
+        let dir = format!("{test_dir}/test-volume/dir");
+        fs::create_dir_all(&dir).await.unwrap();
+        for i in 0..5000 {
+            fs::write(format!("{dir}/entry-{i}"), b"").await.unwrap();
+        }
+
+        let mut seen = Vec::new();
+        let mut after: Option<String> = None;
+        let mut pages = 0;
+        loop {
+            let (page, next) = disk
+                .list_dir_paged("", "test-volume", "dir", after.as_deref(), 500)
+                .await
+                .unwrap();
+            assert!(page.len() <= 500);
+            if let Some(last) = seen.last() {
+                assert!(page.first().is_some_and(|first| first > last), "pages overlap");
+            }
+            seen.extend(page);
+            pages += 1;
+            match next {
+                Some(marker) => after = Some(marker),
+                None => break,
+            }
+        }
+
+        assert_eq!(pages, 10);
+        assert_eq!(seen.len(), 5000);
+        let mut all = disk.list_dir("", "test-volume", "dir", -1).await.unwrap();
+        all.sort();
+        assert_eq!(seen, all);
+
+        let (rest, next) = disk
+            .list_dir_paged("", "test-volume", "dir", Some(all[4997].as_str()), 500)
+            .await
+            .unwrap();
+        assert_eq!(rest, &all[4998..]);
+        assert!(next.is_none());
+
+        let _ = fs::remove_dir_all(&test_dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_stat_info_file() {
+        let test_dir = "./test_local_disk_stat_info";
+        fs::create_dir_all(&test_dir).await.unwrap();
+
+        let endpoint = Endpoint::try_from(test_dir).unwrap();
+        let disk = LocalDisk::new(&endpoint, false).await.unwrap();
+        disk.make_volume("test-volume").await.unwrap();
+
+        plant_object(&disk, "test-volume", "dir/obj1", 1).await;
+        plant_object(&disk, "test-volume", "dir/sub/obj2", 1).await;
+        plant_object(&disk, "test-volume", "other", 1).await;
+        disk.write_all("test-volume", "dir/plain.txt", Bytes::from_static(b"hello"))
+            .await
+            .unwrap();
+
+        // Without globbing only the path itself is stat'ed.
+        let stats = disk.stat_info_file("test-volume", "dir/plain.txt", false).await.unwrap();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].name, "dir/plain.txt");
+        assert_eq!(stats[0].size, 5);
+        assert!(!stats[0].dir);
+        assert!(stats[0].mod_time.is_some());
+
+        let stats = disk.stat_info_file("test-volume", "dir/sub", false).await.unwrap();
+        assert_eq!(stats.len(), 1);
+        assert!(stats[0].dir);
+
+        let res = disk.stat_info_file("test-volume", "dir/missing", false).await;
+        assert!(matches!(res, Err(DiskError::FileNotFound)));
+
+        // Globbing returns every xl.meta under the prefix.
+        let stats = disk.stat_info_file("test-volume", "dir", true).await.unwrap();
+        let names: Vec<_> = stats.iter().map(|st| st.name.as_str()).collect();
+        assert_eq!(names, ["dir/obj1/xl.meta", "dir/sub/obj2/xl.meta"]);
+        assert!(stats.iter().all(|st| !st.dir && st.size > 0));
+
+        assert!(
+            disk.stat_info_file("test-volume", "dir/missing", true)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let _ = fs::remove_dir_all(&test_dir).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_clean_abandoned_data() {
+        let test_dir = "./test_local_disk_clean_abandoned";
+        fs::create_dir_all(&test_dir).await.unwrap();
+
+        let endpoint = Endpoint::try_from(test_dir).unwrap();
+        let disk = LocalDisk::new(&endpoint, false).await.unwrap();
+        disk.make_volume("test-volume").await.unwrap();
+
+        let fi = plant_object(&disk, "test-volume", "obj", 4).await;
+        let live_dir = fi.data_dir.unwrap().to_string();
+        let abandoned_dir = Uuid::new_v4().to_string();
+        let in_flight_dir = Uuid::new_v4().to_string();
+
+        let obj_path = disk.get_object_path("test-volume", "obj").unwrap();
+        for (dir, len) in [(&live_dir, 4), (&abandoned_dir, 100), (&in_flight_dir, 50)] {
+            disk.write_all("test-volume", &format!("obj/{dir}/part.1"), Bytes::from(vec![0u8; len]))
+                .await
+                .unwrap();
+        }
+
+        // Only dirs that have been around for a while are candidates for removal.
+        let old = SystemTime::now() - ABANDONED_DATA_DIR_MIN_AGE * 2;
+        for dir in [&live_dir, &abandoned_dir] {
+            std::fs::File::open(obj_path.join(dir)).unwrap().set_modified(old).unwrap();
+        }
+
+        let reclaimed = disk.clean_abandoned_data("test-volume", "obj").await.unwrap();
+        assert_eq!(reclaimed, 100);
+        assert!(obj_path.join(&live_dir).exists());
+        assert!(!obj_path.join(&abandoned_dir).exists());
+        assert!(obj_path.join(&in_flight_dir).exists());
+        assert!(obj_path.join(STORAGE_FORMAT_FILE).exists());
+
+        assert_eq!(disk.clean_abandoned_data("test-volume", "obj").await.unwrap(), 0);
+
+        let _ = fs::remove_dir_all(&test_dir).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_read_falls_back_to_meta_backup() {
+        let test_dir = "./test_local_disk_meta_backup";
+        let _ = fs::remove_dir_all(&test_dir).await;
+        fs::create_dir_all(&test_dir).await.unwrap();
+
+        let endpoint = Endpoint::try_from(test_dir).unwrap();
+        let mut disk = LocalDisk::new(&endpoint, false).await.unwrap();
+        disk.make_volume("tmp-volume").await.unwrap();
+        disk.make_volume("test-volume").await.unwrap();
+
+        let mut fi = FileInfo::new("obj", 2, 2);
+        fi.volume = "test-volume".to_string();
+        fi.name = "obj".to_string();
+        fi.version_id = Some(Uuid::new_v4());
+        fi.mod_time = Some(OffsetDateTime::now_utc());
+        fi.size = 5;
+        fi.data = Some(Bytes::from_static(b"hello"));
+
+        // the backup is opt-in
+        disk.rename_data("tmp-volume", "u0", fi.clone(), "test-volume", "plain")
+            .await
+            .unwrap();
+        let plain_path = disk.get_object_path("test-volume", "plain").unwrap();
+        assert!(!plain_path.join(STORAGE_FORMAT_FILE_BACKUP).exists());
+
+        disk.meta_backup = true;
+        disk.rename_data("tmp-volume", "u1", fi.clone(), "test-volume", "obj")
+            .await
+            .unwrap();
+
+        let obj_path = disk.get_object_path("test-volume", "obj").unwrap();
+        assert!(obj_path.join(STORAGE_FORMAT_FILE_BACKUP).exists());
+
+        // later metadata updates reach the backup too
+        let mut updated = fi.clone();
+        updated.metadata.insert("x-amz-meta-color".to_string(), "blue".to_string());
+        disk.update_metadata("test-volume", "obj", updated, &UpdateMetadataOpts::default())
+            .await
+            .unwrap();
+
+        std::fs::write(obj_path.join(STORAGE_FORMAT_FILE), b"not an xl.meta").unwrap();
+
+        let opts = ReadOptions {
+            read_data: true,
+            ..Default::default()
+        };
+        let read = disk.read_version("", "test-volume", "obj", "", &opts).await.unwrap();
+        assert_eq!(read.version_id, fi.version_id);
+        assert_eq!(read.data.as_deref(), Some(b"hello".as_slice()));
+        assert_eq!(read.metadata.get("x-amz-meta-color").map(String::as_str), Some("blue"));
+
+        // reading leaves the primary for heal to rewrite
+        let primary = std::fs::read(obj_path.join(STORAGE_FORMAT_FILE)).unwrap();
+        assert_eq!(primary, b"not an xl.meta");
+
+        // as heal would
+        std::fs::copy(obj_path.join(STORAGE_FORMAT_FILE_BACKUP), obj_path.join(STORAGE_FORMAT_FILE)).unwrap();
+
+        // a missing xl.meta is a deleted object, the backup must not bring it back
+        disk.delete_version("test-volume", "obj", fi, false, DeleteOptions::default())
+            .await
+            .unwrap();
+        assert!(!obj_path.join(STORAGE_FORMAT_FILE_BACKUP).exists());
+        assert_eq!(
+            disk.read_version("", "test-volume", "obj", "", &opts).await.unwrap_err(),
+            DiskError::FileNotFound
+        );
+
+        let _ = fs::remove_dir_all(&test_dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_read_parts() {
+        let test_dir = "./test_local_disk_read_parts";
+        fs::create_dir_all(&test_dir).await.unwrap();
+
+        let endpoint = Endpoint::try_from(test_dir).unwrap();
+        let disk = LocalDisk::new(&endpoint, false).await.unwrap();
+        disk.make_volume("test-volume").await.unwrap();
+
+        plant_object(&disk, "test-volume", "part-a", 10).await;
+        plant_object(&disk, "test-volume", "part-c", 30).await;
+
+        let paths = vec!["part-a".to_string(), "part-b".to_string(), "part-c".to_string()];
+        let results = disk.read_parts("test-volume", &paths).await.unwrap();
+        assert_eq!(results.len(), 3);
+
+        let size_of = |res: &Result<RawFileInfo>| match res {
+            Ok(raw) => {
+                FileMeta::load(&raw.buf)
+                    .unwrap()
+                    .into_fileinfo("test-volume", "", "", false, false)
+                    .unwrap()
+                    .size
+            }
+            Err(err) => panic!("unexpected error: {err}"),
+        };
+        assert_eq!(size_of(&results[0]), 10);
+        assert!(matches!(results[1], Err(DiskError::FileNotFound)));
+        assert_eq!(size_of(&results[2]), 30);
+
+        let res = disk.read_parts("missing-volume", &paths).await;
+        assert!(matches!(res, Err(DiskError::VolumeNotFound)));
+
+        let _ = fs::remove_dir_all(&test_dir).await;
+    }
+
     #[test]
     fn test_is_valid_volname() {
         // Valid volume names (length >= 3)
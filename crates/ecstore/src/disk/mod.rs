@@ -12,14 +12,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(not(windows))]
+pub mod direct_io;
 pub mod endpoint;
 pub mod error;
 pub mod error_conv;
 pub mod error_reduce;
+pub mod fault;
 pub mod format;
 pub mod fs;
 pub mod local;
 pub mod os;
+pub mod read_ahead;
 
 pub const RUSTFS_META_BUCKET: &str = ".rustfs.sys";
 pub const RUSTFS_META_MULTIPART_BUCKET: &str = ".rustfs.sys/multipart";
@@ -35,25 +39,99 @@ use crate::heal::{
     data_usage_cache::{DataUsageCache, DataUsageEntry},
     heal_commands::{HealScanMode, HealingTracker},
 };
-use crate::rpc::RemoteDisk;
+use crate::rpc::{CircuitBreakerConfig, RemoteDisk};
 use bytes::Bytes;
 use endpoint::Endpoint;
 use error::DiskError;
 use error::{Error, Result};
 use local::LocalDisk;
-use rustfs_filemeta::{FileInfo, RawFileInfo};
+use rustfs_filemeta::{FileInfo, FileMeta, RawFileInfo, VersionType};
 use rustfs_madmin::info_commands::DiskMetrics;
 use serde::{Deserialize, Serialize};
-use std::{fmt::Debug, path::PathBuf, sync::Arc};
+use std::{
+    fmt::Debug,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Instant,
+};
 use time::OffsetDateTime;
 use tokio::{
-    io::{AsyncRead, AsyncWrite},
+    io::{AsyncRead, AsyncWrite, ReadBuf},
     sync::mpsc::Sender,
 };
 use uuid::Uuid;
 
 pub type DiskStore = Arc<Disk>;
 
+tokio::task_local! {
+    static REQUEST_ID: String;
+    static DEADLINE: Instant;
+}
+
+/// Runs `fut` with `request_id` recorded on the spans of the disk calls it makes.
+pub async fn with_request_id<F: Future>(request_id: String, fut: F) -> F::Output {
+    REQUEST_ID.scope(request_id, fut).await
+}
+
+fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Runs `fut` with the disk reads it makes bounded by `deadline`, they fail with
+/// `DiskError::DeadlineExceeded` once it has passed.
+pub async fn with_deadline<F: Future>(deadline: Option<Instant>, fut: F) -> F::Output {
+    match deadline {
+        Some(deadline) => DEADLINE.scope(deadline, fut).await,
+        None => fut.await,
+    }
+}
+
+pub(crate) fn current_deadline() -> Option<Instant> {
+    DEADLINE.try_with(|deadline| *deadline).ok()
+}
+
+// Gives up on `fut` when the caller's deadline passes, dropping it frees the request.
+async fn until_deadline<T>(fut: impl Future<Output = Result<T>>) -> Result<T> {
+    let Some(deadline) = current_deadline() else {
+        return fut.await;
+    };
+    if Instant::now() >= deadline {
+        return Err(DiskError::DeadlineExceeded);
+    }
+    tokio::time::timeout_at(deadline.into(), fut)
+        .await
+        .unwrap_or(Err(DiskError::DeadlineExceeded))
+}
+
+// Fails reads from a stream opened under a deadline once it has passed, so a request does not
+// hang on a disk that stalls after the open went through.
+struct DeadlineReader {
+    inner: FileReader,
+    deadline: Pin<Box<tokio::time::Sleep>>,
+}
+
+impl AsyncRead for DeadlineReader {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        if self.deadline.as_mut().poll(cx).is_ready() {
+            return Poll::Ready(Err(DiskError::DeadlineExceeded.into()));
+        }
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+// Bounds the reads of a stream by the caller's deadline, if there is one.
+fn until_deadline_stream(reader: FileReader) -> FileReader {
+    match current_deadline() {
+        Some(deadline) => Box::new(DeadlineReader {
+            inner: reader,
+            deadline: Box::pin(tokio::time::sleep_until(deadline.into())),
+        }),
+        None => reader,
+    }
+}
+
 pub type FileReader = Box<dyn AsyncRead + Send + Sync + Unpin>;
 pub type FileWriter = Box<dyn AsyncWrite + Send + Sync + Unpin>;
 
@@ -63,6 +141,16 @@ pub enum Disk {
     Remote(Box<RemoteDisk>),
 }
 
+impl Disk {
+    // Feeds the result to the fault counter when a handler is registered.
+    fn track_fault<T>(&self, res: Result<T>) -> Result<T> {
+        if fault::has_fault_handler() {
+            fault::record(&self.endpoint(), &res);
+        }
+        res
+    }
+}
+
 #[async_trait::async_trait]
 impl DiskAPI for Disk {
     #[tracing::instrument(skip(self))]
@@ -107,26 +195,29 @@ impl DiskAPI for Disk {
 
     #[tracing::instrument(skip(self))]
     async fn close(&self) -> Result<()> {
-        match self {
+        let res = match self {
             Disk::Local(local_disk) => local_disk.close().await,
             Disk::Remote(remote_disk) => remote_disk.close().await,
-        }
+        };
+        self.track_fault(res)
     }
 
     #[tracing::instrument(skip(self))]
     async fn get_disk_id(&self) -> Result<Option<Uuid>> {
-        match self {
+        let res = match self {
             Disk::Local(local_disk) => local_disk.get_disk_id().await,
             Disk::Remote(remote_disk) => remote_disk.get_disk_id().await,
-        }
+        };
+        self.track_fault(res)
     }
 
     #[tracing::instrument(skip(self))]
     async fn set_disk_id(&self, id: Option<Uuid>) -> Result<()> {
-        match self {
+        let res = match self {
             Disk::Local(local_disk) => local_disk.set_disk_id(id).await,
             Disk::Remote(remote_disk) => remote_disk.set_disk_id(id).await,
-        }
+        };
+        self.track_fault(res)
     }
 
     #[tracing::instrument(skip(self))]
@@ -145,55 +236,61 @@ impl DiskAPI for Disk {
         }
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(skip(self), fields(request_id = current_request_id().as_deref()))]
     async fn make_volume(&self, volume: &str) -> Result<()> {
-        match self {
+        let res = match self {
             Disk::Local(local_disk) => local_disk.make_volume(volume).await,
             Disk::Remote(remote_disk) => remote_disk.make_volume(volume).await,
-        }
+        };
+        self.track_fault(res)
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(skip(self), fields(request_id = current_request_id().as_deref()))]
     async fn make_volumes(&self, volumes: Vec<&str>) -> Result<()> {
-        match self {
+        let res = match self {
             Disk::Local(local_disk) => local_disk.make_volumes(volumes).await,
             Disk::Remote(remote_disk) => remote_disk.make_volumes(volumes).await,
-        }
+        };
+        self.track_fault(res)
     }
 
     #[tracing::instrument(skip(self))]
     async fn list_volumes(&self) -> Result<Vec<VolumeInfo>> {
-        match self {
+        let res = match self {
             Disk::Local(local_disk) => local_disk.list_volumes().await,
             Disk::Remote(remote_disk) => remote_disk.list_volumes().await,
-        }
+        };
+        self.track_fault(res)
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(skip(self), fields(request_id = current_request_id().as_deref()))]
     async fn stat_volume(&self, volume: &str) -> Result<VolumeInfo> {
-        match self {
+        let res = match self {
             Disk::Local(local_disk) => local_disk.stat_volume(volume).await,
             Disk::Remote(remote_disk) => remote_disk.stat_volume(volume).await,
-        }
+        };
+        self.track_fault(res)
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(skip(self), fields(request_id = current_request_id().as_deref()))]
     async fn delete_volume(&self, volume: &str) -> Result<()> {
-        match self {
+        let res = match self {
             Disk::Local(local_disk) => local_disk.delete_volume(volume).await,
             Disk::Remote(remote_disk) => remote_disk.delete_volume(volume).await,
-        }
+        };
+        self.track_fault(res)
     }
 
     #[tracing::instrument(skip(self, wr))]
     async fn walk_dir<W: AsyncWrite + Unpin + Send>(&self, opts: WalkDirOptions, wr: &mut W) -> Result<()> {
-        match self {
+        let res = match self {
             Disk::Local(local_disk) => local_disk.walk_dir(opts, wr).await,
             Disk::Remote(remote_disk) => remote_disk.walk_dir(opts, wr).await,
-        }
+        };
+        self.track_fault(res)
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(skip(self, fi), fields(request_id = current_request_id().as_deref()))]
     async fn delete_version(
         &self,
         volume: &str,
@@ -202,50 +299,55 @@ impl DiskAPI for Disk {
         force_del_marker: bool,
         opts: DeleteOptions,
     ) -> Result<()> {
-        match self {
+        let res = match self {
             Disk::Local(local_disk) => local_disk.delete_version(volume, path, fi, force_del_marker, opts).await,
             Disk::Remote(remote_disk) => remote_disk.delete_version(volume, path, fi, force_del_marker, opts).await,
-        }
+        };
+        self.track_fault(res)
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(skip(self, versions), fields(request_id = current_request_id().as_deref()))]
     async fn delete_versions(
         &self,
         volume: &str,
         versions: Vec<FileInfoVersions>,
         opts: DeleteOptions,
     ) -> Result<Vec<Option<Error>>> {
-        match self {
+        let res = match self {
             Disk::Local(local_disk) => local_disk.delete_versions(volume, versions, opts).await,
             Disk::Remote(remote_disk) => remote_disk.delete_versions(volume, versions, opts).await,
-        }
+        };
+        self.track_fault(res)
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(skip(self), fields(request_id = current_request_id().as_deref()))]
     async fn delete_paths(&self, volume: &str, paths: &[String]) -> Result<()> {
-        match self {
+        let res = match self {
             Disk::Local(local_disk) => local_disk.delete_paths(volume, paths).await,
             Disk::Remote(remote_disk) => remote_disk.delete_paths(volume, paths).await,
-        }
+        };
+        self.track_fault(res)
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(skip(self, fi), fields(request_id = current_request_id().as_deref()))]
     async fn write_metadata(&self, _org_volume: &str, volume: &str, path: &str, fi: FileInfo) -> Result<()> {
-        match self {
+        let res = match self {
             Disk::Local(local_disk) => local_disk.write_metadata(_org_volume, volume, path, fi).await,
             Disk::Remote(remote_disk) => remote_disk.write_metadata(_org_volume, volume, path, fi).await,
-        }
+        };
+        self.track_fault(res)
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(skip(self, fi), fields(request_id = current_request_id().as_deref()))]
     async fn update_metadata(&self, volume: &str, path: &str, fi: FileInfo, opts: &UpdateMetadataOpts) -> Result<()> {
-        match self {
+        let res = match self {
             Disk::Local(local_disk) => local_disk.update_metadata(volume, path, fi, opts).await,
             Disk::Remote(remote_disk) => remote_disk.update_metadata(volume, path, fi, opts).await,
-        }
+        };
+        self.track_fault(res)
     }
 
-    #[tracing::instrument(level = "debug", skip(self))]
+    #[tracing::instrument(level = "debug", skip(self), fields(request_id = current_request_id().as_deref()))]
     async fn read_version(
         &self,
         _org_volume: &str,
@@ -254,21 +356,29 @@ impl DiskAPI for Disk {
         version_id: &str,
         opts: &ReadOptions,
     ) -> Result<FileInfo> {
-        match self {
-            Disk::Local(local_disk) => local_disk.read_version(_org_volume, volume, path, version_id, opts).await,
-            Disk::Remote(remote_disk) => remote_disk.read_version(_org_volume, volume, path, version_id, opts).await,
-        }
+        let res = until_deadline(async {
+            match self {
+                Disk::Local(local_disk) => local_disk.read_version(_org_volume, volume, path, version_id, opts).await,
+                Disk::Remote(remote_disk) => remote_disk.read_version(_org_volume, volume, path, version_id, opts).await,
+            }
+        })
+        .await;
+        self.track_fault(res)
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(skip(self), fields(request_id = current_request_id().as_deref()))]
     async fn read_xl(&self, volume: &str, path: &str, read_data: bool) -> Result<RawFileInfo> {
-        match self {
-            Disk::Local(local_disk) => local_disk.read_xl(volume, path, read_data).await,
-            Disk::Remote(remote_disk) => remote_disk.read_xl(volume, path, read_data).await,
-        }
+        let res = until_deadline(async {
+            match self {
+                Disk::Local(local_disk) => local_disk.read_xl(volume, path, read_data).await,
+                Disk::Remote(remote_disk) => remote_disk.read_xl(volume, path, read_data).await,
+            }
+        })
+        .await;
+        self.track_fault(res)
     }
 
-    #[tracing::instrument(skip(self, fi))]
+    #[tracing::instrument(skip(self, fi), fields(request_id = current_request_id().as_deref()))]
     async fn rename_data(
         &self,
         src_volume: &str,
@@ -277,126 +387,226 @@ impl DiskAPI for Disk {
         dst_volume: &str,
         dst_path: &str,
     ) -> Result<RenameDataResp> {
-        match self {
+        let res = match self {
             Disk::Local(local_disk) => local_disk.rename_data(src_volume, src_path, fi, dst_volume, dst_path).await,
             Disk::Remote(remote_disk) => remote_disk.rename_data(src_volume, src_path, fi, dst_volume, dst_path).await,
-        }
+        };
+        self.track_fault(res)
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(skip(self), fields(request_id = current_request_id().as_deref()))]
     async fn list_dir(&self, _origvolume: &str, volume: &str, _dir_path: &str, _count: i32) -> Result<Vec<String>> {
-        match self {
+        let res = match self {
             Disk::Local(local_disk) => local_disk.list_dir(_origvolume, volume, _dir_path, _count).await,
             Disk::Remote(remote_disk) => remote_disk.list_dir(_origvolume, volume, _dir_path, _count).await,
-        }
+        };
+        self.track_fault(res)
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(skip(self), fields(request_id = current_request_id().as_deref()))]
+    async fn list_dir_paged(
+        &self,
+        origvolume: &str,
+        volume: &str,
+        dir_path: &str,
+        after: Option<&str>,
+        count: i32,
+    ) -> Result<(Vec<String>, Option<String>)> {
+        let res = match self {
+            Disk::Local(local_disk) => local_disk.list_dir_paged(origvolume, volume, dir_path, after, count).await,
+            Disk::Remote(remote_disk) => remote_disk.list_dir_paged(origvolume, volume, dir_path, after, count).await,
+        };
+        self.track_fault(res)
+    }
+
+    #[tracing::instrument(skip(self), fields(request_id = current_request_id().as_deref()))]
     async fn read_file(&self, volume: &str, path: &str) -> Result<FileReader> {
-        match self {
-            Disk::Local(local_disk) => local_disk.read_file(volume, path).await,
-            Disk::Remote(remote_disk) => remote_disk.read_file(volume, path).await,
-        }
+        let res = until_deadline(async {
+            match self {
+                Disk::Local(local_disk) => local_disk.read_file(volume, path).await,
+                Disk::Remote(remote_disk) => remote_disk.read_file(volume, path).await,
+            }
+        })
+        .await
+        .map(until_deadline_stream);
+        self.track_fault(res)
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(skip(self), fields(request_id = current_request_id().as_deref()))]
     async fn read_file_stream(&self, volume: &str, path: &str, offset: usize, length: usize) -> Result<FileReader> {
-        match self {
-            Disk::Local(local_disk) => local_disk.read_file_stream(volume, path, offset, length).await,
-            Disk::Remote(remote_disk) => remote_disk.read_file_stream(volume, path, offset, length).await,
-        }
+        let res = until_deadline(async {
+            match self {
+                Disk::Local(local_disk) => local_disk.read_file_stream(volume, path, offset, length).await,
+                Disk::Remote(remote_disk) => remote_disk.read_file_stream(volume, path, offset, length).await,
+            }
+        })
+        .await
+        .map(until_deadline_stream);
+        self.track_fault(res)
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(skip(self), fields(request_id = current_request_id().as_deref()))]
     async fn append_file(&self, volume: &str, path: &str) -> Result<FileWriter> {
-        match self {
+        let res = match self {
             Disk::Local(local_disk) => local_disk.append_file(volume, path).await,
             Disk::Remote(remote_disk) => remote_disk.append_file(volume, path).await,
-        }
+        };
+        self.track_fault(res)
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(skip(self), fields(request_id = current_request_id().as_deref()))]
     async fn create_file(&self, _origvolume: &str, volume: &str, path: &str, _file_size: i64) -> Result<FileWriter> {
-        match self {
+        let res = match self {
             Disk::Local(local_disk) => local_disk.create_file(_origvolume, volume, path, _file_size).await,
             Disk::Remote(remote_disk) => remote_disk.create_file(_origvolume, volume, path, _file_size).await,
-        }
+        };
+        self.track_fault(res)
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(skip(self), fields(request_id = current_request_id().as_deref()))]
     async fn rename_file(&self, src_volume: &str, src_path: &str, dst_volume: &str, dst_path: &str) -> Result<()> {
-        match self {
+        let res = match self {
             Disk::Local(local_disk) => local_disk.rename_file(src_volume, src_path, dst_volume, dst_path).await,
             Disk::Remote(remote_disk) => remote_disk.rename_file(src_volume, src_path, dst_volume, dst_path).await,
-        }
+        };
+        self.track_fault(res)
     }
 
-    #[tracing::instrument(skip(self))]
-    async fn rename_part(&self, src_volume: &str, src_path: &str, dst_volume: &str, dst_path: &str, meta: Bytes) -> Result<()> {
-        match self {
+    #[tracing::instrument(skip(self, meta), fields(request_id = current_request_id().as_deref()))]
+    async fn rename_part(
+        &self,
+        src_volume: &str,
+        src_path: &str,
+        dst_volume: &str,
+        dst_path: &str,
+        meta: Bytes,
+    ) -> Result<RenamePartResult> {
+        let res = match self {
             Disk::Local(local_disk) => local_disk.rename_part(src_volume, src_path, dst_volume, dst_path, meta).await,
             Disk::Remote(remote_disk) => {
                 remote_disk
                     .rename_part(src_volume, src_path, dst_volume, dst_path, meta)
                     .await
             }
-        }
+        };
+        self.track_fault(res)
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(skip(self), fields(request_id = current_request_id().as_deref()))]
     async fn delete(&self, volume: &str, path: &str, opt: DeleteOptions) -> Result<()> {
-        match self {
+        let res = match self {
             Disk::Local(local_disk) => local_disk.delete(volume, path, opt).await,
             Disk::Remote(remote_disk) => remote_disk.delete(volume, path, opt).await,
-        }
+        };
+        self.track_fault(res)
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(skip(self, fi), fields(request_id = current_request_id().as_deref()))]
     async fn verify_file(&self, volume: &str, path: &str, fi: &FileInfo) -> Result<CheckPartsResp> {
-        match self {
+        let res = match self {
             Disk::Local(local_disk) => local_disk.verify_file(volume, path, fi).await,
             Disk::Remote(remote_disk) => remote_disk.verify_file(volume, path, fi).await,
-        }
+        };
+        self.track_fault(res)
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(skip(self, fi), fields(request_id = current_request_id().as_deref()))]
     async fn check_parts(&self, volume: &str, path: &str, fi: &FileInfo) -> Result<CheckPartsResp> {
-        match self {
+        let res = match self {
             Disk::Local(local_disk) => local_disk.check_parts(volume, path, fi).await,
             Disk::Remote(remote_disk) => remote_disk.check_parts(volume, path, fi).await,
-        }
+        };
+        self.track_fault(res)
+    }
+
+    #[tracing::instrument(skip(self), fields(request_id = current_request_id().as_deref()))]
+    async fn stat_info_file(&self, volume: &str, path: &str, glob: bool) -> Result<Vec<StatInfo>> {
+        let res = match self {
+            Disk::Local(local_disk) => local_disk.stat_info_file(volume, path, glob).await,
+            Disk::Remote(remote_disk) => remote_disk.stat_info_file(volume, path, glob).await,
+        };
+        self.track_fault(res)
+    }
+
+    #[tracing::instrument(skip(self), fields(request_id = current_request_id().as_deref()))]
+    async fn read_parts(&self, volume: &str, paths: &[String]) -> Result<Vec<Result<RawFileInfo>>> {
+        let res = until_deadline(async {
+            match self {
+                Disk::Local(local_disk) => local_disk.read_parts(volume, paths).await,
+                Disk::Remote(remote_disk) => remote_disk.read_parts(volume, paths).await,
+            }
+        })
+        .await;
+        self.track_fault(res)
     }
 
     #[tracing::instrument(skip(self))]
     async fn read_multiple(&self, req: ReadMultipleReq) -> Result<Vec<ReadMultipleResp>> {
-        match self {
-            Disk::Local(local_disk) => local_disk.read_multiple(req).await,
-            Disk::Remote(remote_disk) => remote_disk.read_multiple(req).await,
-        }
+        let res = until_deadline(async {
+            match self {
+                Disk::Local(local_disk) => local_disk.read_multiple(req).await,
+                Disk::Remote(remote_disk) => remote_disk.read_multiple(req).await,
+            }
+        })
+        .await;
+        self.track_fault(res)
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(skip(self), fields(request_id = current_request_id().as_deref()))]
+    async fn clean_abandoned_data(&self, volume: &str, path: &str) -> Result<u64> {
+        let res = match self {
+            Disk::Local(local_disk) => local_disk.clean_abandoned_data(volume, path).await,
+            Disk::Remote(remote_disk) => remote_disk.clean_abandoned_data(volume, path).await,
+        };
+        self.track_fault(res)
+    }
+
+    #[tracing::instrument(skip(self, data), fields(request_id = current_request_id().as_deref()))]
     async fn write_all(&self, volume: &str, path: &str, data: Bytes) -> Result<()> {
-        match self {
+        let res = match self {
             Disk::Local(local_disk) => local_disk.write_all(volume, path, data).await,
             Disk::Remote(remote_disk) => remote_disk.write_all(volume, path, data).await,
-        }
+        };
+        self.track_fault(res)
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(skip(self, items), fields(request_id = current_request_id().as_deref()))]
+    async fn write_all_multi(&self, volume: &str, items: &[(String, Bytes)]) -> Result<Vec<Option<Error>>> {
+        let res = match self {
+            Disk::Local(local_disk) => local_disk.write_all_multi(volume, items).await,
+            Disk::Remote(remote_disk) => remote_disk.write_all_multi(volume, items).await,
+        };
+        self.track_fault(res)
+    }
+
+    #[tracing::instrument(skip(self), fields(request_id = current_request_id().as_deref()))]
     async fn read_all(&self, volume: &str, path: &str) -> Result<Bytes> {
-        match self {
-            Disk::Local(local_disk) => local_disk.read_all(volume, path).await,
-            Disk::Remote(remote_disk) => remote_disk.read_all(volume, path).await,
-        }
+        let res = until_deadline(async {
+            match self {
+                Disk::Local(local_disk) => local_disk.read_all(volume, path).await,
+                Disk::Remote(remote_disk) => remote_disk.read_all(volume, path).await,
+            }
+        })
+        .await;
+        self.track_fault(res)
     }
 
     #[tracing::instrument(skip(self))]
     async fn disk_info(&self, opts: &DiskInfoOptions) -> Result<DiskInfo> {
-        match self {
+        let res = match self {
             Disk::Local(local_disk) => local_disk.disk_info(opts).await,
             Disk::Remote(remote_disk) => remote_disk.disk_info(opts).await,
-        }
+        };
+        self.track_fault(res)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn fs_mount_info(&self) -> Result<MountInfo> {
+        let res = match self {
+            Disk::Local(local_disk) => local_disk.fs_mount_info().await,
+            Disk::Remote(remote_disk) => remote_disk.fs_mount_info().await,
+        };
+        self.track_fault(res)
     }
 
     #[tracing::instrument(skip(self, cache, we_sleep, scan_mode))]
@@ -407,10 +617,11 @@ impl DiskAPI for Disk {
         scan_mode: HealScanMode,
         we_sleep: ShouldSleepFn,
     ) -> Result<DataUsageCache> {
-        match self {
+        let res = match self {
             Disk::Local(local_disk) => local_disk.ns_scanner(cache, updates, scan_mode, we_sleep).await,
             Disk::Remote(remote_disk) => remote_disk.ns_scanner(cache, updates, scan_mode, we_sleep).await,
-        }
+        };
+        self.track_fault(res)
     }
 
     #[tracing::instrument(skip(self))]
@@ -424,7 +635,12 @@ impl DiskAPI for Disk {
 
 pub async fn new_disk(ep: &Endpoint, opt: &DiskOption) -> Result<DiskStore> {
     if ep.is_local {
-        let s = LocalDisk::new(ep, opt.cleanup).await?;
+        let mut s = LocalDisk::new(ep, opt.cleanup).await?;
+        s.direct_io_threshold = opt.direct_io_threshold;
+        s.read_ahead = opt.read_ahead;
+        s.meta_backup = opt.meta_backup;
+        s.min_free_bytes = opt.min_free_bytes;
+        s.min_free_pct = opt.min_free_pct;
         Ok(Arc::new(Disk::Local(Box::new(s))))
     } else {
         let remote_disk = RemoteDisk::new(ep, opt).await?;
@@ -500,25 +716,46 @@ pub trait DiskAPI: Debug + Send + Sync + 'static {
     // File operations.
     // 读目录下的所有文件、目录
     async fn list_dir(&self, origvolume: &str, volume: &str, dir_path: &str, count: i32) -> Result<Vec<String>>;
+    /// Lists up to `count` entries sorted by name and strictly after `after`,
+    /// along with the marker to pass back for the next page, `None` once the directory is exhausted.
+    async fn list_dir_paged(
+        &self,
+        origvolume: &str,
+        volume: &str,
+        dir_path: &str,
+        after: Option<&str>,
+        count: i32,
+    ) -> Result<(Vec<String>, Option<String>)>;
     async fn read_file(&self, volume: &str, path: &str) -> Result<FileReader>;
     async fn read_file_stream(&self, volume: &str, path: &str, offset: usize, length: usize) -> Result<FileReader>;
     async fn append_file(&self, volume: &str, path: &str) -> Result<FileWriter>;
     async fn create_file(&self, origvolume: &str, volume: &str, path: &str, file_size: i64) -> Result<FileWriter>;
     // ReadFileStream
     async fn rename_file(&self, src_volume: &str, src_path: &str, dst_volume: &str, dst_path: &str) -> Result<()>;
-    async fn rename_part(&self, src_volume: &str, src_path: &str, dst_volume: &str, dst_path: &str, meta: Bytes) -> Result<()>;
+    async fn rename_part(
+        &self,
+        src_volume: &str,
+        src_path: &str,
+        dst_volume: &str,
+        dst_path: &str,
+        meta: Bytes,
+    ) -> Result<RenamePartResult>;
     async fn delete(&self, volume: &str, path: &str, opt: DeleteOptions) -> Result<()>;
     // VerifyFile
     async fn verify_file(&self, volume: &str, path: &str, fi: &FileInfo) -> Result<CheckPartsResp>;
     // CheckParts
     async fn check_parts(&self, volume: &str, path: &str, fi: &FileInfo) -> Result<CheckPartsResp>;
-    // StatInfoFile
-    // ReadParts
+    async fn stat_info_file(&self, volume: &str, path: &str, glob: bool) -> Result<Vec<StatInfo>>;
+    async fn read_parts(&self, volume: &str, paths: &[String]) -> Result<Vec<Result<RawFileInfo>>>;
     async fn read_multiple(&self, req: ReadMultipleReq) -> Result<Vec<ReadMultipleResp>>;
-    // CleanAbandonedData
+    async fn clean_abandoned_data(&self, volume: &str, path: &str) -> Result<u64>;
     async fn write_all(&self, volume: &str, path: &str, data: Bytes) -> Result<()>;
+    /// Writes a batch of small files under `volume`, syncing the files and their directories once at the end.
+    /// Each item gets its own result, in order.
+    async fn write_all_multi(&self, volume: &str, items: &[(String, Bytes)]) -> Result<Vec<Option<Error>>>;
     async fn read_all(&self, volume: &str, path: &str) -> Result<Bytes>;
     async fn disk_info(&self, opts: &DiskInfoOptions) -> Result<DiskInfo>;
+    async fn fs_mount_info(&self) -> Result<MountInfo>;
     async fn ns_scanner(
         &self,
         cache: &DataUsageCache,
@@ -580,6 +817,76 @@ pub struct DiskInfo {
     pub error: String,
 }
 
+/// The mount a drive lives on, as listed in `/proc/mounts`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MountInfo {
+    pub device: String,
+    pub mount_point: String,
+    pub fs_type: String,
+    pub options: Vec<String>,
+    pub write_barriers: bool,
+    // writes acknowledged to clients may be lost on a crash or power loss
+    pub durability_at_risk: bool,
+}
+
+impl MountInfo {
+    /// Parses one `/proc/mounts` line.
+    pub fn parse(line: &str) -> Option<MountInfo> {
+        let mut fields = line.split_whitespace();
+        let device = unescape_mount_field(fields.next()?);
+        let mount_point = unescape_mount_field(fields.next()?);
+        let fs_type = fields.next()?.to_string();
+        let options: Vec<String> = fields.next()?.split(',').map(str::to_string).collect();
+
+        let write_barriers = !options.iter().any(|o| o == "nobarrier" || o == "barrier=0");
+        let durability_at_risk =
+            !write_barriers || matches!(fs_type.as_str(), "tmpfs" | "ramfs") || options.iter().any(|o| o == "data=writeback");
+
+        Some(MountInfo {
+            device,
+            mount_point,
+            fs_type,
+            options,
+            write_barriers,
+            durability_at_risk,
+        })
+    }
+
+    /// Finds the mount holding `path` in the contents of `/proc/mounts`.
+    pub fn find(mounts: &str, path: &Path) -> Option<MountInfo> {
+        // the deepest mount point wins, and of the same one the last mounted
+        mounts
+            .lines()
+            .filter_map(MountInfo::parse)
+            .filter(|m| path.starts_with(&m.mount_point))
+            .max_by_key(|m| m.mount_point.len())
+    }
+}
+
+// `/proc/mounts` writes spaces, tabs, newlines and backslashes as octal escapes.
+fn unescape_mount_field(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let code = field
+            .get(i + 1..i + 4)
+            .filter(|_| bytes[i] == b'\\')
+            .and_then(|oct| u8::from_str_radix(oct, 8).ok());
+        match code {
+            Some(code) => {
+                out.push(code);
+                i += 4;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct Info {
     pub total: u64,
@@ -623,7 +930,44 @@ impl FileInfoVersions {
     }
 }
 
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// Merges entries for the same object and orders their versions newest first, dropping
+/// repeated version ids. Of the repeats the most complete `FileInfo` is kept, so a client
+/// naming a version twice does not delete it, or reclaim its data dir, twice.
+pub fn normalize_versions(versions: &mut Vec<FileInfoVersions>) {
+    let mut merged: Vec<FileInfoVersions> = Vec::with_capacity(versions.len());
+    for entry in versions.drain(..) {
+        match merged.iter_mut().find(|m| m.volume == entry.volume && m.name == entry.name) {
+            Some(m) => {
+                m.latest_mod_time = m.latest_mod_time.max(entry.latest_mod_time);
+                m.versions.extend(entry.versions);
+                m.free_versions.extend(entry.free_versions);
+            }
+            None => merged.push(entry),
+        }
+    }
+
+    for entry in merged.iter_mut() {
+        let mut unique: Vec<FileInfo> = Vec::with_capacity(entry.versions.len());
+        for fi in entry.versions.drain(..) {
+            match unique.iter_mut().find(|u| u.version_id == fi.version_id) {
+                Some(u) if completeness(&fi) > completeness(u) => *u = fi,
+                Some(_) => {}
+                None => unique.push(fi),
+            }
+        }
+        // stable, so versions without a mod time keep their order at the end
+        unique.sort_by_key(|fi| std::cmp::Reverse(fi.mod_time));
+        entry.versions = unique;
+    }
+
+    *versions = merged;
+}
+
+fn completeness(fi: &FileInfo) -> (bool, bool, usize, usize) {
+    (fi.mod_time.is_some(), fi.data_dir.is_some(), fi.parts.len(), fi.metadata.len())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WalkDirOptions {
     // Bucket to scanner
     pub bucket: String,
@@ -640,6 +984,7 @@ pub struct WalkDirOptions {
     pub filter_prefix: Option<String>,
 
     // ForwardTo will forward to the given object path.
+    // Entries sorting before it are skipped, the path itself is returned if it exists.
     pub forward_to: Option<String>,
 
     // Limit the number of returned objects if > 0.
@@ -648,12 +993,117 @@ pub struct WalkDirOptions {
     // DiskID contains the disk ID of the disk.
     // Leave empty to not check disk ID.
     pub disk_id: String,
+
+    // MinSize skips objects whose latest version is smaller than this many bytes.
+    pub min_size: Option<u64>,
+
+    // MaxSize skips objects whose latest version is larger than this many bytes.
+    pub max_size: Option<u64>,
+
+    // IncludeTombstones returns objects whose latest version is a delete marker.
+    // Peers that predate the option always walk everything.
+    #[serde(default = "walk_includes_all")]
+    pub include_tombstones: bool,
+
+    // IncludeFreeVersions returns objects left with nothing but free versions of tiered data.
+    #[serde(default = "walk_includes_all")]
+    pub include_free_versions: bool,
+}
+
+fn walk_includes_all() -> bool {
+    true
+}
+
+// Walks emit tombstones and free versions unless a caller opts out.
+impl Default for WalkDirOptions {
+    fn default() -> Self {
+        Self {
+            bucket: String::new(),
+            base_dir: String::new(),
+            recursive: false,
+            report_notfound: false,
+            filter_prefix: None,
+            forward_to: None,
+            limit: 0,
+            disk_id: String::new(),
+            min_size: None,
+            max_size: None,
+            include_tombstones: walk_includes_all(),
+            include_free_versions: walk_includes_all(),
+        }
+    }
+}
+
+impl WalkDirOptions {
+    /// Reports whether the latest version recorded in `xl_meta` satisfies
+    /// `min_size`/`max_size`. Only the metadata is decoded, never object data.
+    /// Entries that cannot be decoded are kept so the caller can surface the error.
+    pub fn size_in_range(&self, xl_meta: &[u8]) -> bool {
+        if self.min_size.is_none() && self.max_size.is_none() {
+            return true;
+        }
+
+        let size = match FileMeta::load(xl_meta).and_then(|fm| fm.into_fileinfo("", "", "", false, false)) {
+            Ok(fi) => fi.size.max(0) as u64,
+            Err(_) => return true,
+        };
+
+        self.min_size.is_none_or(|min| size >= min) && self.max_size.is_none_or(|max| size <= max)
+    }
+
+    /// Reports whether an object is kept by `include_tombstones`/`include_free_versions`.
+    /// Like [`WalkDirOptions::size_in_range`] undecodable entries are kept.
+    pub fn keeps_versions(&self, xl_meta: &[u8]) -> bool {
+        if self.include_tombstones && self.include_free_versions {
+            return true;
+        }
+
+        let Ok(fm) = FileMeta::load(xl_meta) else {
+            return true;
+        };
+        if fm.versions.is_empty() {
+            return true;
+        }
+
+        match fm.versions.iter().find(|v| !v.header.free_version()) {
+            Some(latest) => self.include_tombstones || latest.header.version_type != VersionType::Delete,
+            None => self.include_free_versions,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default)]
 pub struct DiskOption {
     pub cleanup: bool,
     pub health_check: bool,
+    // Files at least this large are read and written with O_DIRECT on local disks.
+    // None keeps buffered IO for everything.
+    pub direct_io_threshold: Option<usize>,
+    // Chunk size sequential file streams are read ahead by on local disks.
+    // None reads only what the caller asks for.
+    pub read_ahead: Option<usize>,
+    // Local disks write an xl.meta.bkp along with every xl.meta and read it when xl.meta
+    // is corrupt, at the cost of a second synced write per metadata update.
+    pub meta_backup: bool,
+    // Space local disks keep free, writes that would cut into it fail with DiskFull.
+    // The larger of the two applies, zero for both writes until the disk is full.
+    pub min_free_bytes: u64,
+    pub min_free_pct: u64,
+    // Fails calls to a remote disk fast after repeated failures, None never does.
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+}
+
+/// Outcome of moving an uploaded part into place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenamePartResult {
+    /// The part was moved into place, replacing any earlier upload of it.
+    Written,
+    /// An identical part was already in place, so the call was a retry and the
+    /// new copy was discarded.
+    Unchanged,
+    /// A later upload of the part was already in place, so the new copy was
+    /// discarded.
+    Superseded,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -692,6 +1142,15 @@ pub struct ReadMultipleResp {
     pub mod_time: Option<OffsetDateTime>,
 }
 
+/// Stat result of a file or directory on a disk, `name` is relative to the volume.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatInfo {
+    pub name: String,
+    pub size: u64,
+    pub mod_time: Option<OffsetDateTime>,
+    pub dir: bool,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct VolumeInfo {
     pub name: String,
@@ -734,10 +1193,111 @@ mod tests {
     use super::*;
     use endpoint::Endpoint;
     use local::LocalDisk;
+    use std::collections::HashMap;
     use std::path::PathBuf;
+    use std::sync::Mutex;
     use tokio::fs;
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id};
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+    use tracing_subscriber::{Layer, Registry};
     use uuid::Uuid;
 
+    /// Keeps the fields recorded when each span is created, keyed by span name.
+    #[derive(Clone, Default)]
+    struct SpanFields(Arc<Mutex<Vec<(String, HashMap<String, String>)>>>);
+
+    struct FieldVisitor<'a>(&'a mut HashMap<String, String>);
+
+    impl Visit for FieldVisitor<'_> {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0
+                .insert(field.name().to_string(), format!("{value:?}").trim_matches('"').to_string());
+        }
+    }
+
+    impl<S: tracing::Subscriber> Layer<S> for SpanFields {
+        fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+            let mut fields = HashMap::new();
+            attrs.record(&mut FieldVisitor(&mut fields));
+            self.0.lock().unwrap().push((attrs.metadata().name().to_string(), fields));
+        }
+    }
+
+    #[test]
+    fn test_mount_info_parse() {
+        let mounts = "\
+/dev/sda1 / ext4 rw,relatime 0 0
+tmpfs /tmp tmpfs rw,nosuid,nodev 0 0
+/dev/sdb1 /mnt/data\\040disk xfs rw,noatime,nobarrier 0 0
+/dev/sdc1 /mnt/data\\040disk/sub ext4 rw,noatime,data=ordered 0 0
+";
+
+        let root = MountInfo::parse(mounts.lines().next().unwrap()).unwrap();
+        assert_eq!(root.device, "/dev/sda1");
+        assert_eq!(root.mount_point, "/");
+        assert_eq!(root.fs_type, "ext4");
+        assert_eq!(root.options, vec!["rw", "relatime"]);
+        assert!(root.write_barriers);
+        assert!(!root.durability_at_risk);
+
+        let data = MountInfo::find(mounts, Path::new("/mnt/data disk/drive1")).unwrap();
+        assert_eq!(data.mount_point, "/mnt/data disk");
+        assert_eq!(data.fs_type, "xfs");
+        assert!(!data.write_barriers);
+        assert!(data.durability_at_risk);
+
+        let sub = MountInfo::find(mounts, Path::new("/mnt/data disk/sub/drive2")).unwrap();
+        assert_eq!(sub.device, "/dev/sdc1");
+        assert!(!sub.durability_at_risk);
+
+        assert!(MountInfo::find(mounts, Path::new("/tmp/drive")).unwrap().durability_at_risk);
+        assert_eq!(MountInfo::find(mounts, Path::new("/home")).unwrap().mount_point, "/");
+        assert!(MountInfo::parse("/dev/sda1 /").is_none());
+    }
+
+    #[test]
+    fn test_normalize_versions() {
+        let t = |secs| Some(OffsetDateTime::from_unix_timestamp(secs).unwrap());
+        let (v1, v2, v3) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let fi = |name: &str, vid, mod_time, data_dir| FileInfo {
+            name: name.to_string(),
+            version_id: Some(vid),
+            mod_time,
+            data_dir,
+            ..Default::default()
+        };
+        let entry = |name: &str, versions| FileInfoVersions {
+            name: name.to_string(),
+            versions,
+            ..Default::default()
+        };
+
+        let data_dir = Some(Uuid::new_v4());
+        let mut versions = vec![
+            entry("a", vec![fi("a", v1, t(10), None), fi("a", v2, t(30), None)]),
+            entry("b", vec![fi("b", v3, None, None)]),
+            // the same version again, this time with its data dir
+            entry("a", vec![fi("a", v1, t(10), data_dir), fi("a", v3, t(20), None)]),
+            entry("b", vec![fi("b", v3, None, None)]),
+        ];
+        normalize_versions(&mut versions);
+
+        assert_eq!(versions.len(), 2);
+        let a = &versions[0];
+        assert_eq!(a.name, "a");
+        let ids: Vec<_> = a.versions.iter().map(|v| v.version_id.unwrap()).collect();
+        assert_eq!(ids, vec![v2, v3, v1]);
+        assert_eq!(a.versions[2].data_dir, data_dir);
+
+        assert_eq!(versions[1].name, "b");
+        assert_eq!(versions[1].versions.len(), 1);
+    }
+
     /// Test DiskLocation validation
     #[test]
     fn test_disk_location_valid() {
@@ -838,6 +1398,10 @@ mod tests {
             forward_to: Some("object/path".to_string()),
             limit: 100,
             disk_id: "disk-123".to_string(),
+            min_size: Some(10),
+            max_size: None,
+            include_tombstones: false,
+            include_free_versions: false,
         };
 
         assert_eq!(opts.bucket, "test-bucket");
@@ -848,6 +1412,8 @@ mod tests {
         assert_eq!(opts.forward_to, Some("object/path".to_string()));
         assert_eq!(opts.limit, 100);
         assert_eq!(opts.disk_id, "disk-123");
+        assert_eq!(opts.min_size, Some(10));
+        assert_eq!(opts.max_size, None);
     }
 
     /// Test DeleteOptions structure
@@ -894,10 +1460,17 @@ mod tests {
         let opt = DiskOption {
             cleanup: true,
             health_check: false,
+            direct_io_threshold: Some(1024 * 1024),
+            read_ahead: None,
+            meta_backup: false,
+            min_free_bytes: 0,
+            min_free_pct: 0,
+            circuit_breaker: None,
         };
 
         assert!(opt.cleanup);
         assert!(!opt.health_check);
+        assert_eq!(opt.direct_io_threshold, Some(1024 * 1024));
     }
 
     /// Test DiskInfoOptions structure
@@ -1029,6 +1602,12 @@ mod tests {
         let opt = DiskOption {
             cleanup: false,
             health_check: true,
+            direct_io_threshold: None,
+            read_ahead: None,
+            meta_backup: false,
+            min_free_bytes: 0,
+            min_free_pct: 0,
+            circuit_breaker: None,
         };
 
         let disk = new_disk(&endpoint, &opt).await;
@@ -1074,4 +1653,154 @@ mod tests {
         // 清理测试目录
         let _ = fs::remove_dir_all(&test_dir).await;
     }
+
+    #[tokio::test]
+    async fn test_fault_handler_threshold() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let test_dir = "./test_disk_fault_handler";
+        let _ = fs::remove_dir_all(&test_dir).await;
+        fs::create_dir_all(&test_dir).await.unwrap();
+
+        let endpoint = Endpoint::try_from(test_dir).unwrap();
+        let disk = Disk::Local(Box::new(LocalDisk::new(&endpoint, false).await.unwrap()));
+
+        let fired = Arc::new(AtomicUsize::new(0));
+        let counter = fired.clone();
+        let key = endpoint.to_string();
+        fault::set_fault_handler(Arc::new(move |ep, err| {
+            if ep.to_string() == key {
+                assert_eq!(*err, DiskError::FaultyDisk);
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        }));
+
+        let fail = || disk.track_fault::<()>(Err(DiskError::FaultyDisk));
+        for _ in 1..fault::DEFAULT_FAULT_THRESHOLD {
+            let _ = fail();
+        }
+        assert_eq!(fired.load(Ordering::SeqCst), 0);
+
+        // a successful operation starts the count over
+        disk.make_volume("bucket").await.unwrap();
+        for _ in 1..fault::DEFAULT_FAULT_THRESHOLD {
+            let _ = fail();
+        }
+        assert_eq!(fired.load(Ordering::SeqCst), 0);
+
+        let _ = fail();
+        assert_eq!(fired.load(Ordering::SeqCst), 1);
+        // not repeated while the drive keeps failing
+        let _ = fail();
+        assert_eq!(fired.load(Ordering::SeqCst), 1);
+
+        fault::clear_fault_handler();
+        let _ = fs::remove_dir_all(&test_dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_read_all_span_records_context() {
+        let test_dir = "./test_disk_span_context";
+        let _ = fs::remove_dir_all(&test_dir).await;
+        fs::create_dir_all(&test_dir).await.unwrap();
+
+        let endpoint = Endpoint::try_from(test_dir).unwrap();
+        let disk = Disk::Local(Box::new(LocalDisk::new(&endpoint, false).await.unwrap()));
+        disk.make_volume("bucket").await.unwrap();
+        disk.write_all("bucket", "object/xl.meta", Bytes::from_static(b"secret-bytes"))
+            .await
+            .unwrap();
+
+        let spans = SpanFields::default();
+        let _guard = tracing::subscriber::set_default(Registry::default().with(spans.clone()));
+
+        let data = with_request_id("req-1".to_string(), disk.read_all("bucket", "object/xl.meta"))
+            .await
+            .unwrap();
+        assert_eq!(data, Bytes::from_static(b"secret-bytes"));
+
+        let recorded = spans.0.lock().unwrap().clone();
+        let (_, fields) = recorded.iter().find(|(name, _)| name == "read_all").expect("read_all span");
+        assert_eq!(fields.get("volume").map(String::as_str), Some("bucket"));
+        assert_eq!(fields.get("path").map(String::as_str), Some("object/xl.meta"));
+        assert_eq!(fields.get("request_id").map(String::as_str), Some("req-1"));
+        assert!(fields.values().all(|v| !v.contains("secret-bytes")));
+
+        let _ = fs::remove_dir_all(&test_dir).await;
+    }
+    #[tokio::test]
+    async fn test_slow_read_aborts_at_deadline() {
+        // stands in for a disk that takes far longer than the request allows
+        let slow_read = async {
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+            Ok(Bytes::from_static(b"late"))
+        };
+
+        let start = Instant::now();
+        let deadline = start + std::time::Duration::from_millis(50);
+        let res = with_deadline(Some(deadline), until_deadline(slow_read)).await;
+        assert!(matches!(res, Err(DiskError::DeadlineExceeded)));
+        assert!(start.elapsed() < std::time::Duration::from_secs(1));
+
+        // without a deadline the read runs to completion
+        let res = with_deadline(None, until_deadline(async { Ok(Bytes::from_static(b"done")) })).await;
+        assert_eq!(res.unwrap(), Bytes::from_static(b"done"));
+    }
+
+    #[tokio::test]
+    async fn test_read_all_past_deadline() {
+        let test_dir = "./test_disk_deadline";
+        let _ = fs::remove_dir_all(&test_dir).await;
+        fs::create_dir_all(&test_dir).await.unwrap();
+
+        let endpoint = Endpoint::try_from(test_dir).unwrap();
+        let disk = Disk::Local(Box::new(LocalDisk::new(&endpoint, false).await.unwrap()));
+        disk.make_volume("bucket").await.unwrap();
+        disk.write_all("bucket", "object/xl.meta", Bytes::from_static(b"data"))
+            .await
+            .unwrap();
+
+        let passed = Instant::now() - std::time::Duration::from_millis(1);
+        let res = with_deadline(Some(passed), disk.read_all("bucket", "object/xl.meta")).await;
+        assert!(matches!(res, Err(DiskError::DeadlineExceeded)));
+
+        let data = with_deadline(None, disk.read_all("bucket", "object/xl.meta")).await.unwrap();
+        assert_eq!(data, Bytes::from_static(b"data"));
+
+        let _ = fs::remove_dir_all(&test_dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_stream_read_past_deadline() {
+        use tokio::io::AsyncReadExt;
+
+        let test_dir = "./test_disk_stream_deadline";
+        let _ = fs::remove_dir_all(&test_dir).await;
+        fs::create_dir_all(&test_dir).await.unwrap();
+
+        let endpoint = Endpoint::try_from(test_dir).unwrap();
+        let disk = Disk::Local(Box::new(LocalDisk::new(&endpoint, false).await.unwrap()));
+        disk.make_volume("bucket").await.unwrap();
+        disk.write_all("bucket", "object/part.1", Bytes::from_static(b"data"))
+            .await
+            .unwrap();
+
+        // the open goes through, the stream then stalls past the deadline
+        let deadline = Instant::now() + std::time::Duration::from_millis(50);
+        let mut reader = with_deadline(Some(deadline), disk.read_file_stream("bucket", "object/part.1", 0, 4))
+            .await
+            .unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        let mut buf = Vec::new();
+        assert!(reader.read_to_end(&mut buf).await.is_err());
+
+        let mut reader = with_deadline(None, disk.read_file_stream("bucket", "object/part.1", 0, 4))
+            .await
+            .unwrap();
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf, b"data");
+
+        let _ = fs::remove_dir_all(&test_dir).await;
+    }
 }
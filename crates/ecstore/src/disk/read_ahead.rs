@@ -0,0 +1,233 @@
+// Copyright 2024 RustFS Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Read-ahead for sequential file streams.
+//!
+//! A [`ReadAheadReader`] reads the file in fixed size chunks on a background task,
+//! so the next chunk is already in memory while the caller works on the current one.
+//! It never reads past the requested range. [`SequentialReads`] decides which streams
+//! get one: streams from the start of a file or continuing the previous stream of the
+//! same file, random range reads are served straight from the file.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::{Context, Poll, ready};
+
+use bytes::{Bytes, BytesMut};
+use tokio::io::{AsyncRead, AsyncReadExt, ReadBuf};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Files whose last read end is remembered, the table is reset when it grows past this.
+const MAX_TRACKED_FILES: usize = 1024;
+
+/// End offset of the last stream opened on each file.
+#[derive(Debug, Default)]
+pub struct SequentialReads {
+    ends: Mutex<HashMap<PathBuf, usize>>,
+}
+
+impl SequentialReads {
+    /// Records a stream of `length` bytes at `offset` of `path` and reports whether it reads the
+    /// file sequentially, that is from its start or from where the previous stream ended.
+    pub fn observe(&self, path: &Path, offset: usize, length: usize) -> bool {
+        let mut ends = self.ends.lock().unwrap();
+        let sequential = offset == 0 || ends.get(path) == Some(&offset);
+
+        if ends.len() >= MAX_TRACKED_FILES && !ends.contains_key(path) {
+            ends.clear();
+        }
+        ends.insert(path.to_path_buf(), offset + length);
+
+        sequential
+    }
+}
+
+/// Reads up to `length` bytes of `inner` a chunk ahead of the caller.
+pub struct ReadAheadReader {
+    rx: mpsc::Receiver<io::Result<Bytes>>,
+    buf: Bytes,
+    task: JoinHandle<()>,
+}
+
+impl ReadAheadReader {
+    pub fn new<R>(inner: R, length: usize, chunk_size: usize) -> Self
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+    {
+        // one chunk waits in the channel while the task reads the following one
+        let (tx, rx) = mpsc::channel(1);
+        let task = tokio::spawn(fill(inner, length, chunk_size.max(1), tx));
+        Self {
+            rx,
+            buf: Bytes::new(),
+            task,
+        }
+    }
+}
+
+async fn fill<R>(mut inner: R, mut remaining: usize, chunk_size: usize, tx: mpsc::Sender<io::Result<Bytes>>)
+where
+    R: AsyncRead + Unpin,
+{
+    while remaining > 0 {
+        let mut chunk = BytesMut::zeroed(chunk_size.min(remaining));
+        let mut filled = 0;
+        while filled < chunk.len() {
+            match inner.read(&mut chunk[filled..]).await {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(err) => {
+                    let _ = tx.send(Err(err)).await;
+                    return;
+                }
+            }
+        }
+
+        let eof = filled < chunk.len();
+        chunk.truncate(filled);
+        remaining -= filled;
+        if filled > 0 && tx.send(Ok(chunk.freeze())).await.is_err() {
+            return;
+        }
+        if eof {
+            return;
+        }
+    }
+}
+
+impl AsyncRead for ReadAheadReader {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        while this.buf.is_empty() {
+            match ready!(this.rx.poll_recv(cx)) {
+                Some(Ok(chunk)) => this.buf = chunk,
+                Some(Err(err)) => return Poll::Ready(Err(err)),
+                None => return Poll::Ready(Ok(())),
+            }
+        }
+
+        let n = buf.remaining().min(this.buf.len());
+        buf.put_slice(&this.buf.split_to(n));
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Drop for ReadAheadReader {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Counts the reads and bytes served by the wrapped reader.
+    struct CountingReader {
+        inner: Cursor<Vec<u8>>,
+        reads: Arc<AtomicUsize>,
+        bytes: Arc<AtomicUsize>,
+    }
+
+    impl AsyncRead for CountingReader {
+        fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+            let before = buf.filled().len();
+            ready!(Pin::new(&mut self.inner).poll_read(cx, buf))?;
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            self.bytes.fetch_add(buf.filled().len() - before, Ordering::SeqCst);
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    const CHUNK: usize = 64 * 1024;
+    const READ_BUF: usize = 4 * 1024;
+
+    /// Opens `length` bytes at `offset` of `data` the way `read_file_stream` does, then drains it
+    /// in small reads, returning the data, the reads and the bytes that hit the underlying reader.
+    async fn stream(seq: &SequentialReads, path: &Path, data: &[u8], offset: usize, length: usize) -> (Vec<u8>, usize, usize) {
+        let reads = Arc::new(AtomicUsize::new(0));
+        let bytes = Arc::new(AtomicUsize::new(0));
+        let mut cursor = Cursor::new(data.to_vec());
+        cursor.set_position(offset as u64);
+        let inner = CountingReader {
+            inner: cursor,
+            reads: reads.clone(),
+            bytes: bytes.clone(),
+        };
+
+        let mut r: Box<dyn AsyncRead + Send + Unpin> = if seq.observe(path, offset, length) && length > CHUNK {
+            Box::new(ReadAheadReader::new(inner, length, CHUNK))
+        } else {
+            Box::new(inner.take(length as u64))
+        };
+
+        let mut got = Vec::with_capacity(length);
+        let mut buf = vec![0u8; READ_BUF];
+        loop {
+            let n = r.read(&mut buf).await.unwrap();
+            if n == 0 {
+                break;
+            }
+            got.extend_from_slice(&buf[..n]);
+        }
+        drop(r);
+
+        (got, reads.load(Ordering::SeqCst), bytes.load(Ordering::SeqCst))
+    }
+
+    #[tokio::test]
+    async fn test_read_ahead_sequential_vs_random() {
+        let data: Vec<u8> = (0..1024 * 1024).map(|i| (i % 251) as u8).collect();
+        let seq = SequentialReads::default();
+        let range = 256 * 1024;
+
+        // sequential: consecutive ranges are served in whole chunks
+        let mut sequential_reads = 0;
+        for offset in (0..data.len()).step_by(range) {
+            let (got, reads, bytes) = stream(&seq, Path::new("seq/part.1"), &data, offset, range).await;
+            assert_eq!(got, &data[offset..offset + range]);
+            assert_eq!(bytes, range);
+            sequential_reads += reads;
+        }
+        assert_eq!(sequential_reads, data.len() / CHUNK);
+
+        // random: the same ranges out of order are read as asked, with no prefetch past them
+        let mut random_reads = 0;
+        for offset in [3 * range, range, 3 * range] {
+            let (got, reads, bytes) = stream(&seq, Path::new("random/part.1"), &data, offset, range).await;
+            assert_eq!(got, &data[offset..offset + range]);
+            assert_eq!(bytes, range);
+            random_reads += reads;
+        }
+        assert_eq!(random_reads, 3 * range / READ_BUF);
+        assert!(random_reads / 3 > sequential_reads / 4);
+    }
+
+    #[tokio::test]
+    async fn test_read_ahead_stops_at_length() {
+        let data: Vec<u8> = (0..300 * 1024).map(|i| (i % 13) as u8).collect();
+        let seq = SequentialReads::default();
+
+        let (got, _, bytes) = stream(&seq, Path::new("part.1"), &data, 0, 200 * 1024).await;
+        assert_eq!(got, &data[..200 * 1024]);
+        assert_eq!(bytes, 200 * 1024);
+    }
+}
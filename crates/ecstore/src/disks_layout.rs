@@ -31,7 +31,7 @@ pub struct PoolDisksLayout {
 }
 
 impl PoolDisksLayout {
-    fn new(args: impl Into<String>, layout: Vec<Vec<String>>) -> Self {
+    pub(crate) fn new(args: impl Into<String>, layout: Vec<Vec<String>>) -> Self {
         PoolDisksLayout {
             cmd_line: args.into(),
             layout,
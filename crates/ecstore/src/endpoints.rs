@@ -222,8 +222,6 @@ impl PoolEndpointList {
             pool_endpoints.push(endpoints);
         }
 
-        // setup type
-        let mut unique_args = HashSet::new();
         let mut pool_endpoint_list = Self {
             inner: pool_endpoints,
             setup_type: SetupType::Unknown,
@@ -304,7 +302,6 @@ impl PoolEndpointList {
             // Add missing port in all endpoints.
             for ep in endpoints.as_mut() {
                 if !ep.url.has_host() {
-                    unique_args.insert(format!("localhost:{}", server_addr.port()));
                     continue;
                 }
                 match ep.url.port() {
@@ -318,19 +315,15 @@ impl PoolEndpointList {
                         }
                     }
                 }
-                unique_args.insert(ep.host_port());
             }
         }
 
-        let setup_type = match pool_endpoint_list.as_ref()[0].as_ref()[0].get_type() {
-            EndpointType::Path => SetupType::Erasure,
-            EndpointType::Url => match unique_args.len() {
-                1 => SetupType::Erasure,
-                _ => SetupType::DistErasure,
-            },
-        };
-
-        pool_endpoint_list.setup_type = setup_type;
+        pool_endpoint_list.setup_type = setup_type_of(
+            pool_endpoint_list
+                .inner
+                .iter()
+                .flat_map(|endpoints| endpoints.as_ref().iter()),
+        );
 
         Ok(pool_endpoint_list)
     }
@@ -406,6 +399,30 @@ impl PoolEndpointList {
     }
 }
 
+/// returns the setup type served by the given pools: a single local path is
+/// ErasureSD, any remote endpoint makes it DistErasure, otherwise Erasure.
+pub fn determine_setup_type(pools: &[PoolEndpoints]) -> SetupType {
+    setup_type_of(pools.iter().flat_map(|pool| pool.endpoints.as_ref().iter()))
+}
+
+fn setup_type_of<'a>(endpoints: impl Iterator<Item = &'a Endpoint>) -> SetupType {
+    let mut count = 0;
+    let mut first_type = None;
+    for ep in endpoints {
+        if !ep.is_local {
+            return SetupType::DistErasure;
+        }
+        first_type.get_or_insert(ep.get_type());
+        count += 1;
+    }
+
+    match (count, first_type) {
+        (0, _) => SetupType::Unknown,
+        (1, Some(EndpointType::Path)) => SetupType::ErasureSD,
+        _ => SetupType::Erasure,
+    }
+}
+
 /// represent endpoints in a given pool
 /// along with its setCount and setDriveCount.
 #[derive(Debug, Clone)]
@@ -423,6 +440,26 @@ pub struct PoolEndpoints {
 #[derive(Debug, Clone, Default)]
 pub struct EndpointServerPools(pub Vec<PoolEndpoints>);
 
+/// Changes between two endpoint layouts, endpoints are matched by URL.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EndpointsDiff {
+    pub added: Vec<Endpoint>,
+    pub removed: Vec<Endpoint>,
+    /// (old, new) for endpoints kept at a different pool/set/disk position
+    pub moved: Vec<(Endpoint, Endpoint)>,
+}
+
+impl EndpointsDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.moved.is_empty()
+    }
+
+    /// Same drives in a different order, e.g. pools listed the other way around.
+    pub fn is_reorder_only(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && !self.moved.is_empty()
+    }
+}
+
 impl From<Vec<PoolEndpoints>> for EndpointServerPools {
     fn from(v: Vec<PoolEndpoints>) -> Self {
         Self(v)
@@ -519,6 +556,34 @@ impl EndpointServerPools {
             .is_some_and(|v| v.is_local)
     }
 
+    /// returns all endpoints served by this node.
+    pub fn local_endpoints(&self) -> Vec<Endpoint> {
+        self.0
+            .iter()
+            .flat_map(|pool| pool.endpoints.as_ref().iter())
+            .filter(|ep| ep.is_local)
+            .cloned()
+            .collect()
+    }
+
+    /// returns all endpoints served by other nodes.
+    pub fn remote_endpoints(&self) -> Vec<Endpoint> {
+        self.0
+            .iter()
+            .flat_map(|pool| pool.endpoints.as_ref().iter())
+            .filter(|ep| !ep.is_local)
+            .cloned()
+            .collect()
+    }
+
+    /// returns the number of disks this node has to initialize.
+    pub fn local_disk_count(&self) -> usize {
+        self.0
+            .iter()
+            .map(|pool| pool.endpoints.as_ref().iter().filter(|ep| ep.is_local).count())
+            .sum()
+    }
+
     /// returns a sorted list of nodes in this cluster
     pub fn get_nodes(&self) -> Vec<Node> {
         let mut node_map = HashMap::new();
@@ -593,6 +658,40 @@ impl EndpointServerPools {
         (hosts, local.unwrap_or_default())
     }
 
+    /// Lists what changed going from `self` to `other`, so a reload only touches the affected drives.
+    pub fn diff(&self, other: &EndpointServerPools) -> EndpointsDiff {
+        let position = |ep: &Endpoint| (ep.pool_idx, ep.set_idx, ep.disk_idx);
+        let index = |pools: &EndpointServerPools| -> HashMap<String, Endpoint> {
+            pools
+                .0
+                .iter()
+                .flat_map(|pool| pool.endpoints.as_ref().iter())
+                .map(|ep| (ep.to_string(), ep.clone()))
+                .collect()
+        };
+        let old = index(self);
+        let new = index(other);
+
+        let mut diff = EndpointsDiff::default();
+        for (key, ep) in new.iter() {
+            match old.get(key) {
+                None => diff.added.push(ep.clone()),
+                Some(old_ep) if position(old_ep) != position(ep) => diff.moved.push((old_ep.clone(), ep.clone())),
+                Some(_) => {}
+            }
+        }
+        for (key, ep) in old.iter() {
+            if !new.contains_key(key) {
+                diff.removed.push(ep.clone());
+            }
+        }
+
+        diff.added.sort_by_key(position);
+        diff.removed.sort_by_key(position);
+        diff.moved.sort_by_key(|(_, ep)| position(ep));
+        diff
+    }
+
     pub fn find_grid_hosts_from_peer(&self, host: &XHost) -> Option<String> {
         for ep in self.0.iter() {
             for endpoint in ep.endpoints.0.iter() {
@@ -850,6 +949,7 @@ mod test {
                     pool_idx: 0,
                     set_idx: 0,
                     disk_idx: 0,
+                    grid_host: None,
                 }])),
                 expected_setup_type: Some(SetupType::ErasureSD),
                 ..Default::default()
@@ -864,6 +964,7 @@ mod test {
                     pool_idx: 0,
                     set_idx: 0,
                     disk_idx: 0,
+                    grid_host: None,
                 }])),
                 expected_setup_type: Some(SetupType::ErasureSD),
                 ..Default::default()
@@ -892,6 +993,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                     Endpoint {
                         url: must_file_path("/d2"),
@@ -899,6 +1001,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                     Endpoint {
                         url: must_file_path("/d3"),
@@ -906,6 +1009,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                     Endpoint {
                         url: must_file_path("/d4"),
@@ -913,6 +1017,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                 ])),
                 expected_setup_type: Some(SetupType::Erasure),
@@ -935,6 +1040,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                     Endpoint {
                         url: must_url("http://localhost:9000/d2"),
@@ -942,6 +1048,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                     Endpoint {
                         url: must_url("http://localhost:9000/d3"),
@@ -949,6 +1056,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                     Endpoint {
                         url: must_url("http://localhost:9000/d4"),
@@ -956,6 +1064,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                 ])),
                 expected_setup_type: Some(SetupType::Erasure),
@@ -1015,6 +1124,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                     Endpoint {
                         url: case1_ur_ls[1].clone(),
@@ -1022,6 +1132,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                     Endpoint {
                         url: case1_ur_ls[2].clone(),
@@ -1029,6 +1140,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                     Endpoint {
                         url: case1_ur_ls[3].clone(),
@@ -1036,6 +1148,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                 ])),
                 expected_setup_type: Some(SetupType::DistErasure),
@@ -1057,6 +1170,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                     Endpoint {
                         url: case2_ur_ls[1].clone(),
@@ -1064,6 +1178,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                     Endpoint {
                         url: case2_ur_ls[2].clone(),
@@ -1071,6 +1186,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                     Endpoint {
                         url: case2_ur_ls[3].clone(),
@@ -1078,6 +1194,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                 ])),
                 expected_setup_type: Some(SetupType::DistErasure),
@@ -1099,6 +1216,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                     Endpoint {
                         url: case3_ur_ls[1].clone(),
@@ -1106,6 +1224,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                     Endpoint {
                         url: case3_ur_ls[2].clone(),
@@ -1113,6 +1232,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                     Endpoint {
                         url: case3_ur_ls[3].clone(),
@@ -1120,6 +1240,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                 ])),
                 expected_setup_type: Some(SetupType::DistErasure),
@@ -1141,6 +1262,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                     Endpoint {
                         url: case4_ur_ls[1].clone(),
@@ -1148,6 +1270,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                     Endpoint {
                         url: case4_ur_ls[2].clone(),
@@ -1155,6 +1278,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                     Endpoint {
                         url: case4_ur_ls[3].clone(),
@@ -1162,6 +1286,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                 ])),
                 expected_setup_type: Some(SetupType::DistErasure),
@@ -1183,6 +1308,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                     Endpoint {
                         url: case5_ur_ls[1].clone(),
@@ -1190,6 +1316,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                     Endpoint {
                         url: case5_ur_ls[2].clone(),
@@ -1197,6 +1324,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                     Endpoint {
                         url: case5_ur_ls[3].clone(),
@@ -1204,6 +1332,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                 ])),
                 expected_setup_type: Some(SetupType::DistErasure),
@@ -1225,6 +1354,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                     Endpoint {
                         url: case6_ur_ls[1].clone(),
@@ -1232,6 +1362,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                     Endpoint {
                         url: case6_ur_ls[2].clone(),
@@ -1239,6 +1370,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                     Endpoint {
                         url: case6_ur_ls[3].clone(),
@@ -1246,6 +1378,7 @@ mod test {
                         pool_idx: 0,
                         set_idx: 0,
                         disk_idx: 0,
+                        grid_host: None,
                     },
                 ])),
                 expected_setup_type: Some(SetupType::DistErasure),
@@ -1380,4 +1513,146 @@ mod test {
             }
         }
     }
+
+    fn new_pool(urls: &[&str], local: &[bool], pool_idx: i32) -> PoolEndpoints {
+        let mut eps = Endpoints::try_from(urls).unwrap();
+        for (i, ep) in eps.as_mut().iter_mut().enumerate() {
+            ep.is_local = local[i];
+            ep.pool_idx = pool_idx;
+            ep.disk_idx = i as i32;
+        }
+        PoolEndpoints {
+            legacy: false,
+            set_count: 1,
+            drives_per_set: urls.len(),
+            endpoints: eps,
+            cmd_line: String::new(),
+            platform: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_local_remote_endpoints() {
+        let pools: EndpointServerPools = vec![
+            new_pool(
+                &[
+                    "http://node1:9000/d1",
+                    "http://node1:9000/d2",
+                    "http://node2:9000/d1",
+                    "http://node2:9000/d2",
+                ],
+                &[true, true, false, false],
+                0,
+            ),
+            new_pool(
+                &[
+                    "http://node1:9000/d3",
+                    "http://node2:9000/d3",
+                    "http://node3:9000/d1",
+                    "http://node3:9000/d2",
+                ],
+                &[true, false, false, false],
+                1,
+            ),
+        ]
+        .into();
+
+        let local: Vec<String> = pools.local_endpoints().iter().map(|ep| ep.to_string()).collect();
+        assert_eq!(local, ["http://node1:9000/d1", "http://node1:9000/d2", "http://node1:9000/d3"]);
+        assert_eq!(pools.local_disk_count(), 3);
+
+        let remote = pools.remote_endpoints();
+        assert_eq!(remote.len(), 5);
+        assert!(remote.iter().all(|ep| !ep.is_local && ep.host_port() != "node1:9000"));
+        assert_eq!(remote[0].pool_idx, 0);
+        assert_eq!(remote[4].pool_idx, 1);
+
+        assert!(EndpointServerPools::default().local_endpoints().is_empty());
+        assert_eq!(EndpointServerPools::default().local_disk_count(), 0);
+    }
+
+    #[test]
+    fn test_determine_setup_type() {
+        assert_eq!(determine_setup_type(&[]), SetupType::Unknown);
+
+        // Single local path.
+        let pools = [new_pool(&["/d1"], &[true], 0)];
+        assert_eq!(determine_setup_type(&pools), SetupType::ErasureSD);
+
+        // Multiple local paths on one node.
+        let pools = [new_pool(&["/d1", "/d2", "/d3", "/d4"], &[true; 4], 0)];
+        assert_eq!(determine_setup_type(&pools), SetupType::Erasure);
+
+        // Single node with multiple drives given as URLs, across two pools.
+        let pools = [
+            new_pool(&["http://node1:9000/d1", "http://node1:9000/d2"], &[true; 2], 0),
+            new_pool(&["http://node1:9000/d3", "http://node1:9000/d4"], &[true; 2], 1),
+        ];
+        assert_eq!(determine_setup_type(&pools), SetupType::Erasure);
+
+        // Any remote endpoint makes it distributed, even in a later pool.
+        let pools = [
+            new_pool(&["http://node1:9000/d1", "http://node1:9000/d2"], &[true; 2], 0),
+            new_pool(&["http://node1:9000/d3", "http://node2:9000/d3"], &[true, false], 1),
+        ];
+        assert_eq!(determine_setup_type(&pools), SetupType::DistErasure);
+    }
+
+    fn test_pools(layout: &[&[&str]]) -> EndpointServerPools {
+        let pools = layout
+            .iter()
+            .enumerate()
+            .map(|(pool_idx, paths)| {
+                let endpoints = paths
+                    .iter()
+                    .enumerate()
+                    .map(|(disk_idx, path)| {
+                        let mut ep = Endpoint::try_from(*path).unwrap();
+                        ep.set_pool_index(pool_idx);
+                        ep.set_set_index(0);
+                        ep.set_disk_index(disk_idx);
+                        ep
+                    })
+                    .collect::<Vec<_>>();
+                PoolEndpoints {
+                    legacy: false,
+                    set_count: 1,
+                    drives_per_set: paths.len(),
+                    endpoints: Endpoints::from(endpoints),
+                    cmd_line: paths.join(" "),
+                    platform: String::new(),
+                }
+            })
+            .collect::<Vec<_>>();
+        EndpointServerPools::from(pools)
+    }
+
+    #[test]
+    fn test_endpoint_server_pools_diff() {
+        let old = test_pools(&[&["/d1", "/d2", "/d3"], &["/e1", "/e2"]]);
+        assert!(old.diff(&old).is_empty());
+
+        let grown = test_pools(&[&["/d1", "/d2", "/d3", "/d4"], &["/e1", "/e2"]]);
+        let diff = old.diff(&grown);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].to_string(), "/d4");
+        assert_eq!((diff.added[0].pool_idx, diff.added[0].disk_idx), (0, 3));
+        assert!(diff.removed.is_empty());
+        assert!(diff.moved.is_empty());
+        assert!(!diff.is_reorder_only());
+
+        let reverse = grown.diff(&old);
+        assert_eq!(reverse.removed.len(), 1);
+        assert_eq!(reverse.removed[0].to_string(), "/d4");
+        assert!(reverse.added.is_empty());
+
+        // the same drives with the pools swapped only moves them
+        let swapped = test_pools(&[&["/e1", "/e2"], &["/d1", "/d2", "/d3"]]);
+        let diff = old.diff(&swapped);
+        assert!(diff.is_reorder_only());
+        assert_eq!(diff.moved.len(), 5);
+        let (from, to) = &diff.moved[0];
+        assert_eq!(from.to_string(), "/e1");
+        assert_eq!((from.pool_idx, to.pool_idx), (1, 0));
+    }
 }
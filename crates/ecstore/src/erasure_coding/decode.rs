@@ -177,7 +177,7 @@ where
         let block_slice = &block[offset..];
         offset = 0;
 
-        if write_left < block.len() {
+        if write_left < block_slice.len() {
             writer.write_all(&block_slice[..write_left]).await.map_err(|e| {
                 error!("write_data_blocks write_all err: {}", e);
                 e
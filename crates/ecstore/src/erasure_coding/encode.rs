@@ -79,6 +79,36 @@ impl<'a> MultiWriter<'a> {
             while let Some(()) = futures.next().await {}
         }
 
+        self.check_quorum()
+    }
+
+    /// Finishes every writer still in use, one that fails to finish counts against the quorum
+    /// like a failed write.
+    pub async fn shutdown(&mut self) -> std::io::Result<()> {
+        {
+            let mut futures = FuturesUnordered::new();
+            for (writer_opt, err) in self.writers.iter_mut().zip(self.errs.iter_mut()) {
+                if err.is_some() {
+                    continue;
+                }
+                futures.push(async move {
+                    match writer_opt {
+                        Some(writer) => {
+                            if let Err(e) = writer.shutdown().await {
+                                *err = Some(Error::from(e));
+                            }
+                        }
+                        None => *err = Some(Error::DiskNotFound),
+                    }
+                });
+            }
+            while let Some(()) = futures.next().await {}
+        }
+
+        self.check_quorum()
+    }
+
+    fn check_quorum(&self) -> std::io::Result<()> {
         let nil_count = self.errs.iter().filter(|&e| e.is_none()).count();
         if nil_count >= self.write_quorum {
             return Ok(());
@@ -111,13 +141,6 @@ impl<'a> MultiWriter<'a> {
                 .join(", ")
         )))
     }
-
-    pub async fn _shutdown(&mut self) -> std::io::Result<()> {
-        for writer in self.writers.iter_mut().flatten() {
-            writer.shutdown().await?;
-        }
-        Ok(())
-    }
 }
 
 impl Erasure {
@@ -168,7 +191,7 @@ impl Erasure {
         }
 
         let (reader, total) = task.await??;
-        // writers.shutdown().await?;
+        writers.shutdown().await?;
         Ok((reader, total))
     }
 }
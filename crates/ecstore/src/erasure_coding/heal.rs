@@ -58,6 +58,7 @@ impl super::Erasure {
 
             if self.parity_shards > 0 {
                 self.decode_data(&mut shards)?;
+                self.verify_parity(&mut shards)?;
             }
 
             let shards = shards
@@ -65,10 +66,106 @@ impl super::Erasure {
                 .map(|s| Bytes::from(s.unwrap_or_default()))
                 .collect::<Vec<_>>();
 
-            let mut writers = MultiWriter::new(writers, self.data_shards);
+            // Any single outdated drive that takes the shard is progress.
+            let mut writers = MultiWriter::new(writers, 1);
             writers.write(shards).await?;
         }
 
+        MultiWriter::new(writers, 1).shutdown().await?;
+
         Ok(())
     }
+
+    /// Re-encodes parity from the decoded data shards. A surviving parity shard that
+    /// disagrees means some surviving shard was corrupt even though it passed its bitrot
+    /// check, so the block must not be written and `ParityMismatch` is returned. Missing
+    /// parity shards are filled in.
+    fn verify_parity(&self, shards: &mut [Option<Vec<u8>>]) -> Result<()> {
+        let mut data = Vec::new();
+        for shard in shards.iter().take(self.data_shards) {
+            match shard {
+                Some(shard) => data.extend_from_slice(shard),
+                None => return Err(Error::other("can not reconstruct data: data shard missing after decode")),
+            }
+        }
+
+        let encoded = self.encode_data(&data)?;
+        for (shard, expected) in shards.iter_mut().zip(encoded).skip(self.data_shards) {
+            match shard {
+                Some(shard) if shard[..] != expected[..] => return Err(Error::ParityMismatch),
+                Some(_) => {}
+                None => *shard = Some(expected.to_vec()),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{BitrotWriter, CustomWriter, Erasure};
+    use super::*;
+    use rustfs_utils::HashAlgorithm;
+    use std::io::Cursor;
+
+    const ALGO: HashAlgorithm = HashAlgorithm::HighwayHash256;
+
+    // Bitrot framed shard files for `data`, `corrupt` flips a byte of that shard before
+    // it is hashed so the file still passes its own bitrot check.
+    async fn shard_files(erasure: &Erasure, data: &[u8], corrupt: Option<usize>) -> Vec<Vec<u8>> {
+        let mut writers: Vec<_> = (0..erasure.total_shard_count())
+            .map(|_| BitrotWriter::new(Cursor::new(Vec::new()), erasure.shard_size(), ALGO))
+            .collect();
+        for block in data.chunks(erasure.block_size) {
+            let shards = erasure.encode_data(block).unwrap();
+            for (index, (writer, shard)) in writers.iter_mut().zip(shards).enumerate() {
+                let mut shard = shard.to_vec();
+                if corrupt == Some(index) {
+                    shard[0] ^= 0x01;
+                }
+                writer.write(&shard).await.unwrap();
+            }
+        }
+        writers.into_iter().map(|w| w.into_inner().into_inner()).collect()
+    }
+
+    async fn heal_shard(erasure: &Erasure, files: &[Vec<u8>], total_length: usize, missing: usize) -> Result<Vec<u8>> {
+        let readers = files
+            .iter()
+            .enumerate()
+            .map(|(index, file)| {
+                (index != missing).then(|| BitrotReader::new(Cursor::new(file.clone()), erasure.shard_size(), ALGO))
+            })
+            .collect();
+        let mut writers: Vec<_> = (0..files.len())
+            .map(|index| {
+                (index == missing)
+                    .then(|| BitrotWriterWrapper::new(CustomWriter::new_inline_buffer(), erasure.shard_size(), ALGO))
+            })
+            .collect();
+
+        erasure
+            .heal(&mut writers, readers, total_length, &vec![false; files.len()])
+            .await?;
+        Ok(writers[missing].take().unwrap().into_inline_data().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_heal_verifies_reconstruction() {
+        let erasure = Erasure::new(2, 2, 64);
+        let data: Vec<u8> = (0..150u32).map(|i| (i * 7) as u8).collect();
+
+        let files = shard_files(&erasure, &data, None).await;
+        // a data shard and a parity shard are rebuilt byte for byte
+        for missing in [0, 3] {
+            let healed = heal_shard(&erasure, &files, data.len(), missing).await.unwrap();
+            assert_eq!(healed, files[missing], "shard {missing}");
+        }
+
+        // shard 1 hashes fine but its content is wrong
+        let files = shard_files(&erasure, &data, Some(1)).await;
+        let err = heal_shard(&erasure, &files, data.len(), 0).await.unwrap_err();
+        assert_eq!(err, Error::ParityMismatch);
+    }
 }
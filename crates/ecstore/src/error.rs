@@ -181,6 +181,54 @@ pub enum StorageError {
     #[error("first disk wait")]
     FirstDiskWait,
 
+    #[error("Server is in read-only mode")]
+    ReadOnlyMode,
+
+    #[error("Your proposed upload exceeds the maximum allowed object size")]
+    EntityTooLarge,
+
+    #[error("Your metadata headers exceed the maximum allowed metadata size")]
+    MetadataTooLarge,
+
+    #[error("At least one of the pre-conditions you specified did not hold")]
+    PreconditionFailed,
+
+    #[error("The checksum you specified did not match what we received")]
+    BadDigest,
+
+    #[error("Bucket metadata timestamp is too far ahead of the local clock")]
+    ClockSkew,
+
+    #[error("Your proposed upload is smaller than the minimum allowed object size")]
+    EntityTooSmall,
+
+    #[error("The list of parts was not in ascending order")]
+    InvalidPartOrder,
+
+    #[error("The requested range is not satisfiable")]
+    InvalidRange,
+
+    #[error("Reconstructed data of part {0} does not match the surviving shards")]
+    ReconstructionFailed(usize),
+
+    #[error("Object is WORM protected and cannot be overwritten or deleted: {0}/{1}")]
+    ObjectLocked(String, String),
+
+    #[error("Retention in COMPLIANCE mode can not be removed before it expires: {0}/{1}")]
+    ComplianceModeLocked(String, String),
+
+    #[error("Parity {0} exceeds the maximum of {1} for the erasure set")]
+    InvalidParity(usize, usize),
+
+    #[error("Object has reached its version limit and the oldest version is locked: {0}/{1}")]
+    TooManyVersions(String, String),
+
+    #[error("Version id already exists at the destination: {0}/{1}({2})")]
+    VersionIdConflict(String, String, String),
+
+    #[error("Request deadline exceeded")]
+    DeadlineExceeded,
+
     #[error("Io error: {0}")]
     Io(std::io::Error),
 }
@@ -211,6 +259,7 @@ impl From<DiskError> for StorageError {
             // DiskError::DiskOngoingReq => StorageError::DiskOngoingReq,
             DiskError::DriveIsRoot => StorageError::DriveIsRoot,
             DiskError::FaultyRemoteDisk => StorageError::FaultyRemoteDisk,
+            DiskError::CircuitOpen => StorageError::FaultyRemoteDisk,
             DiskError::FaultyDisk => StorageError::FaultyDisk,
             DiskError::DiskAccessDenied => StorageError::DiskAccessDenied,
             DiskError::FileNotFound => StorageError::FileNotFound,
@@ -235,6 +284,8 @@ impl From<DiskError> for StorageError {
             DiskError::MethodNotAllowed => StorageError::MethodNotAllowed,
             DiskError::ErasureReadQuorum => StorageError::ErasureReadQuorum,
             DiskError::ErasureWriteQuorum => StorageError::ErasureWriteQuorum,
+            DiskError::ReconstructionFailed(part) => StorageError::ReconstructionFailed(part),
+            DiskError::DeadlineExceeded => StorageError::DeadlineExceeded,
             _ => StorageError::Io(std::io::Error::other(e)),
         }
     }
@@ -264,6 +315,7 @@ impl From<StorageError> for DiskError {
             StorageError::VolumeNotFound => DiskError::VolumeNotFound,
             StorageError::VolumeExists => DiskError::VolumeExists,
             StorageError::FileNameTooLong => DiskError::FileNameTooLong,
+            StorageError::DeadlineExceeded => DiskError::DeadlineExceeded,
             _ => DiskError::other(val),
         }
     }
@@ -409,6 +461,26 @@ impl Clone for StorageError {
             StorageError::FirstDiskWait => StorageError::FirstDiskWait,
             StorageError::TooManyOpenFiles => StorageError::TooManyOpenFiles,
             StorageError::NoHealRequired => StorageError::NoHealRequired,
+            StorageError::ReadOnlyMode => StorageError::ReadOnlyMode,
+            StorageError::EntityTooLarge => StorageError::EntityTooLarge,
+            StorageError::MetadataTooLarge => StorageError::MetadataTooLarge,
+            StorageError::PreconditionFailed => StorageError::PreconditionFailed,
+            StorageError::BadDigest => StorageError::BadDigest,
+            StorageError::ClockSkew => StorageError::ClockSkew,
+            StorageError::EntityTooSmall => StorageError::EntityTooSmall,
+            StorageError::InvalidPartOrder => StorageError::InvalidPartOrder,
+            StorageError::InvalidRange => StorageError::InvalidRange,
+            StorageError::ReconstructionFailed(part) => StorageError::ReconstructionFailed(*part),
+            StorageError::ObjectLocked(bucket, object) => StorageError::ObjectLocked(bucket.clone(), object.clone()),
+            StorageError::ComplianceModeLocked(bucket, object) => {
+                StorageError::ComplianceModeLocked(bucket.clone(), object.clone())
+            }
+            StorageError::InvalidParity(parity, max) => StorageError::InvalidParity(*parity, *max),
+            StorageError::TooManyVersions(bucket, object) => StorageError::TooManyVersions(bucket.clone(), object.clone()),
+            StorageError::VersionIdConflict(bucket, object, version_id) => {
+                StorageError::VersionIdConflict(bucket.clone(), object.clone(), version_id.clone())
+            }
+            StorageError::DeadlineExceeded => StorageError::DeadlineExceeded,
         }
     }
 }
@@ -471,6 +543,22 @@ impl StorageError {
             StorageError::ConfigNotFound => 0x35,
             StorageError::TooManyOpenFiles => 0x36,
             StorageError::NoHealRequired => 0x37,
+            StorageError::ReadOnlyMode => 0x38,
+            StorageError::EntityTooLarge => 0x39,
+            StorageError::MetadataTooLarge => 0x3A,
+            StorageError::PreconditionFailed => 0x3B,
+            StorageError::BadDigest => 0x3C,
+            StorageError::ClockSkew => 0x3D,
+            StorageError::EntityTooSmall => 0x3E,
+            StorageError::InvalidPartOrder => 0x3F,
+            StorageError::InvalidRange => 0x40,
+            StorageError::ReconstructionFailed(_) => 0x41,
+            StorageError::ObjectLocked(..) => 0x42,
+            StorageError::ComplianceModeLocked(..) => 0x43,
+            StorageError::InvalidParity(..) => 0x44,
+            StorageError::TooManyVersions(..) => 0x45,
+            StorageError::VersionIdConflict(..) => 0x46,
+            StorageError::DeadlineExceeded => 0x47,
         }
     }
 
@@ -535,6 +623,26 @@ impl StorageError {
             0x35 => Some(StorageError::ConfigNotFound),
             0x36 => Some(StorageError::TooManyOpenFiles),
             0x37 => Some(StorageError::NoHealRequired),
+            0x38 => Some(StorageError::ReadOnlyMode),
+            0x39 => Some(StorageError::EntityTooLarge),
+            0x3A => Some(StorageError::MetadataTooLarge),
+            0x3B => Some(StorageError::PreconditionFailed),
+            0x3C => Some(StorageError::BadDigest),
+            0x3D => Some(StorageError::ClockSkew),
+            0x3E => Some(StorageError::EntityTooSmall),
+            0x3F => Some(StorageError::InvalidPartOrder),
+            0x40 => Some(StorageError::InvalidRange),
+            0x41 => Some(StorageError::ReconstructionFailed(0)),
+            0x42 => Some(StorageError::ObjectLocked(Default::default(), Default::default())),
+            0x43 => Some(StorageError::ComplianceModeLocked(Default::default(), Default::default())),
+            0x44 => Some(StorageError::InvalidParity(0, 0)),
+            0x45 => Some(StorageError::TooManyVersions(Default::default(), Default::default())),
+            0x46 => Some(StorageError::VersionIdConflict(
+                Default::default(),
+                Default::default(),
+                Default::default(),
+            )),
+            0x47 => Some(StorageError::DeadlineExceeded),
             _ => None,
         }
     }
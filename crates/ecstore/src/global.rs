@@ -26,8 +26,11 @@ use lazy_static::lazy_static;
 use rustfs_policy::auth::Credentials;
 use std::{
     collections::HashMap,
-    sync::{Arc, OnceLock},
-    time::SystemTime,
+    sync::{
+        Arc, OnceLock,
+        atomic::{AtomicBool, AtomicI32, AtomicU64, AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant, SystemTime},
 };
 use tokio::sync::{OnceCell, RwLock};
 use uuid::Uuid;
@@ -46,7 +49,6 @@ pub static ref GLOBAL_LOCAL_DISK: Arc<RwLock<Vec<Option<DiskStore>>>> = Arc::new
 pub static ref GLOBAL_IsErasure: RwLock<bool> = RwLock::new(false);
 pub static ref GLOBAL_IsDistErasure: RwLock<bool> = RwLock::new(false);
 pub static ref GLOBAL_IsErasureSD: RwLock<bool> = RwLock::new(false);
-pub static ref GLOBAL_LOCAL_DISK_MAP: Arc<RwLock<HashMap<String, Option<DiskStore>>>> = Arc::new(RwLock::new(HashMap::new()));
 pub static ref GLOBAL_LOCAL_DISK_SET_DRIVES: Arc<RwLock<TypeLocalDiskSetDrives>> = Arc::new(RwLock::new(Vec::new()));
 pub static ref GLOBAL_Endpoints: OnceLock<EndpointServerPools> = OnceLock::new();
 pub static ref GLOBAL_RootDiskThreshold: RwLock<u64> = RwLock::new(0);
@@ -66,6 +68,42 @@ pub static ref GLOBAL_NodeNamesHex: HashMap<String, ()> = HashMap::new();}
 
 static GLOBAL_ACTIVE_CRED: OnceLock<Credentials> = OnceLock::new();
 
+static GLOBAL_TARGETS_SECRET_KEY: OnceLock<String> = OnceLock::new();
+
+static GLOBAL_READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+// 0 means unlimited
+static GLOBAL_MAX_OBJECT_SIZE: AtomicU64 = AtomicU64::new(0);
+
+/// S3 caps the user metadata of an object at 2 KiB
+pub const DEFAULT_MAX_USER_METADATA_SIZE: u64 = 2 * 1024;
+
+static GLOBAL_MAX_USER_METADATA_SIZE: AtomicU64 = AtomicU64::new(DEFAULT_MAX_USER_METADATA_SIZE);
+
+/// S3 returns at most 1000 keys per listing page
+pub const DEFAULT_MAX_LIST_KEYS: i32 = 1000;
+
+static GLOBAL_MAX_LIST_KEYS: AtomicI32 = AtomicI32::new(DEFAULT_MAX_LIST_KEYS);
+
+// 0 means unlimited
+static GLOBAL_MAX_VERSIONS_PER_OBJECT: AtomicUsize = AtomicUsize::new(0);
+
+// bytes, 0 means local disks always use buffered IO
+static GLOBAL_DISK_DIRECT_IO_THRESHOLD: AtomicUsize = AtomicUsize::new(0);
+
+// bytes, 0 means local disks read only what the caller asks for
+static GLOBAL_DISK_READ_AHEAD: AtomicUsize = AtomicUsize::new(0);
+
+// local disks keep an xl.meta.bkp next to every xl.meta
+static GLOBAL_DISK_META_BACKUP: AtomicBool = AtomicBool::new(false);
+
+// space local disks keep free for writes, the larger of the two applies
+static GLOBAL_DISK_MIN_FREE_BYTES: AtomicU64 = AtomicU64::new(0);
+static GLOBAL_DISK_MIN_FREE_PCT: AtomicU64 = AtomicU64::new(0);
+
+// milliseconds, 0 means requests have no deadline
+static GLOBAL_REQUEST_TIMEOUT_MS: AtomicU64 = AtomicU64::new(0);
+
 pub fn init_global_action_cred(ak: Option<String>, sk: Option<String>) {
     let ak = {
         if let Some(k) = ak {
@@ -96,6 +134,16 @@ pub fn get_global_action_cred() -> Option<Credentials> {
     GLOBAL_ACTIVE_CRED.get().cloned()
 }
 
+/// Set the key remote bucket target secrets are encrypted with at rest, only the first call takes effect
+pub fn set_global_targets_secret_key(key: String) {
+    let _ = GLOBAL_TARGETS_SECRET_KEY.set(key);
+}
+
+/// Get the key remote bucket target secrets are encrypted with at rest, if one is configured
+pub fn get_global_targets_secret_key() -> Option<&'static str> {
+    GLOBAL_TARGETS_SECRET_KEY.get().map(String::as_str)
+}
+
 /// Get the global rustfs port
 pub fn global_rustfs_port() -> u16 {
     if let Some(p) = GLOBAL_RUSTFS_PORT.get() {
@@ -135,14 +183,70 @@ pub fn get_global_endpoints() -> EndpointServerPools {
     }
 }
 
+#[cfg(not(test))]
+lazy_static! {
+    pub static ref GLOBAL_LOCAL_DISK_MAP: TypeLocalDiskMap = Arc::new(RwLock::new(HashMap::new()));
+}
+
+#[cfg(not(test))]
 pub fn new_object_layer_fn() -> Option<Arc<ECStore>> {
     GLOBAL_OBJECT_API.get().cloned()
 }
 
+#[cfg(not(test))]
 pub async fn set_object_layer(o: Arc<ECStore>) {
     GLOBAL_OBJECT_API.set(o).expect("set_object_layer fail ")
 }
 
+// Tests run on threads of their own and share one binary, so in tests the object layer and the local
+// drives belong to the thread that set them up instead of the whole process.
+#[cfg(test)]
+thread_local! {
+    static TEST_OBJECT_API: std::cell::RefCell<Option<Arc<ECStore>>> = const { std::cell::RefCell::new(None) };
+    static TEST_LOCAL_DISK_MAP: &'static TypeLocalDiskMap = Box::leak(Box::default());
+}
+
+#[cfg(test)]
+pub fn new_object_layer_fn() -> Option<Arc<ECStore>> {
+    TEST_OBJECT_API.with_borrow(|o| o.clone())
+}
+
+#[cfg(test)]
+pub async fn set_object_layer(o: Arc<ECStore>) {
+    TEST_OBJECT_API.set(Some(o));
+}
+
+/// Makes `o` the object layer of the calling test thread, along with its local drives.
+#[cfg(test)]
+pub(crate) fn set_test_object_layer(o: Arc<ECStore>) {
+    use crate::disk::DiskAPI;
+
+    let mut local_disks = GLOBAL_LOCAL_DISK_MAP
+        .try_write()
+        .expect("local drives of the test thread in use");
+    for disk in o.disk_map.values().flatten().flatten() {
+        if disk.is_local() {
+            local_disks.insert(disk.endpoint().to_string(), Some(disk.clone()));
+        }
+    }
+    TEST_OBJECT_API.set(Some(o));
+}
+
+#[cfg(test)]
+pub struct TestLocalDiskMap;
+
+#[cfg(test)]
+pub static GLOBAL_LOCAL_DISK_MAP: TestLocalDiskMap = TestLocalDiskMap;
+
+#[cfg(test)]
+impl std::ops::Deref for TestLocalDiskMap {
+    type Target = TypeLocalDiskMap;
+
+    fn deref(&self) -> &TypeLocalDiskMap {
+        TEST_LOCAL_DISK_MAP.with(|map| *map)
+    }
+}
+
 pub async fn is_dist_erasure() -> bool {
     let lock = GLOBAL_IsDistErasure.read().await;
     *lock
@@ -158,6 +262,128 @@ pub async fn is_erasure() -> bool {
     *lock
 }
 
+/// Switch the server in or out of read-only mode, writes fail with `ReadOnlyMode` while it is on
+pub fn set_global_read_only(read_only: bool) {
+    GLOBAL_READ_ONLY.store(read_only, Ordering::SeqCst);
+}
+
+/// Get whether the server is in read-only mode
+pub fn is_global_read_only() -> bool {
+    GLOBAL_READ_ONLY.load(Ordering::SeqCst)
+}
+
+/// Set the largest object or part accepted by put_object / put_object_part, `None` disables the limit
+pub fn set_global_max_object_size(size: Option<u64>) {
+    GLOBAL_MAX_OBJECT_SIZE.store(size.unwrap_or_default(), Ordering::SeqCst);
+}
+
+/// Get the configured max object size, if any
+pub fn get_global_max_object_size() -> Option<u64> {
+    match GLOBAL_MAX_OBJECT_SIZE.load(Ordering::SeqCst) {
+        0 => None,
+        size => Some(size),
+    }
+}
+
+/// Set the largest combined size of user metadata keys and values accepted on writes
+pub fn set_global_max_user_metadata_size(size: u64) {
+    GLOBAL_MAX_USER_METADATA_SIZE.store(size, Ordering::SeqCst);
+}
+
+/// Get the configured user metadata size limit
+pub fn get_global_max_user_metadata_size() -> u64 {
+    GLOBAL_MAX_USER_METADATA_SIZE.load(Ordering::SeqCst)
+}
+
+/// Set the hard ceiling on keys returned by one listing page, values below 1 are raised to 1
+pub fn set_global_max_list_keys(max_keys: i32) {
+    GLOBAL_MAX_LIST_KEYS.store(max_keys.max(1), Ordering::SeqCst);
+}
+
+/// Get the hard ceiling on keys returned by one listing page
+pub fn get_global_max_list_keys() -> i32 {
+    GLOBAL_MAX_LIST_KEYS.load(Ordering::SeqCst)
+}
+
+/// Set how many versions an object may keep, a put beyond it prunes the oldest noncurrent one, `None` disables the limit
+pub fn set_global_max_versions_per_object(max_versions: Option<usize>) {
+    GLOBAL_MAX_VERSIONS_PER_OBJECT.store(max_versions.unwrap_or_default(), Ordering::SeqCst);
+}
+
+/// Get the configured version limit per object, if any
+pub fn get_global_max_versions_per_object() -> Option<usize> {
+    match GLOBAL_MAX_VERSIONS_PER_OBJECT.load(Ordering::SeqCst) {
+        0 => None,
+        max_versions => Some(max_versions),
+    }
+}
+
+/// Set the file size from which local disks read and write with O_DIRECT, `None` keeps buffered IO for everything
+pub fn set_global_disk_direct_io_threshold(threshold: Option<usize>) {
+    GLOBAL_DISK_DIRECT_IO_THRESHOLD.store(threshold.unwrap_or_default(), Ordering::SeqCst);
+}
+
+/// Get the file size from which local disks read and write with O_DIRECT, if any
+pub fn get_global_disk_direct_io_threshold() -> Option<usize> {
+    match GLOBAL_DISK_DIRECT_IO_THRESHOLD.load(Ordering::SeqCst) {
+        0 => None,
+        threshold => Some(threshold),
+    }
+}
+
+/// Set the chunk size local disks read sequential file streams ahead by, `None` disables read-ahead
+pub fn set_global_disk_read_ahead(chunk_size: Option<usize>) {
+    GLOBAL_DISK_READ_AHEAD.store(chunk_size.unwrap_or_default(), Ordering::SeqCst);
+}
+
+/// Get the chunk size local disks read sequential file streams ahead by, if any
+pub fn get_global_disk_read_ahead() -> Option<usize> {
+    match GLOBAL_DISK_READ_AHEAD.load(Ordering::SeqCst) {
+        0 => None,
+        chunk_size => Some(chunk_size),
+    }
+}
+
+/// Set whether local disks keep a backup of every xl.meta to read when the primary is corrupt
+pub fn set_global_disk_meta_backup(enabled: bool) {
+    GLOBAL_DISK_META_BACKUP.store(enabled, Ordering::SeqCst);
+}
+
+/// Get whether local disks keep a backup of every xl.meta
+pub fn get_global_disk_meta_backup() -> bool {
+    GLOBAL_DISK_META_BACKUP.load(Ordering::SeqCst)
+}
+
+/// Set the space local disks keep free, as bytes and as a percentage of the disk, writes that would cut into it fail with `DiskFull`
+pub fn set_global_disk_min_free(min_free_bytes: u64, min_free_pct: u64) {
+    GLOBAL_DISK_MIN_FREE_BYTES.store(min_free_bytes, Ordering::SeqCst);
+    GLOBAL_DISK_MIN_FREE_PCT.store(min_free_pct.min(100), Ordering::SeqCst);
+}
+
+/// Get the bytes local disks keep free
+pub fn get_global_disk_min_free_bytes() -> u64 {
+    GLOBAL_DISK_MIN_FREE_BYTES.load(Ordering::SeqCst)
+}
+
+/// Get the percentage of each local disk kept free
+pub fn get_global_disk_min_free_pct() -> u64 {
+    GLOBAL_DISK_MIN_FREE_PCT.load(Ordering::SeqCst)
+}
+
+/// Set how long a request may spend on disk reads before they fail with `DeadlineExceeded`, `None` disables the limit
+pub fn set_global_request_timeout(timeout: Option<Duration>) {
+    let ms = timeout.map_or(0, |t| t.as_millis().clamp(1, u64::MAX as u128) as u64);
+    GLOBAL_REQUEST_TIMEOUT_MS.store(ms, Ordering::SeqCst);
+}
+
+/// Get the deadline of a request starting now, if requests are bounded
+pub fn get_global_request_deadline() -> Option<Instant> {
+    match GLOBAL_REQUEST_TIMEOUT_MS.load(Ordering::SeqCst) {
+        0 => None,
+        ms => Some(Instant::now() + Duration::from_millis(ms)),
+    }
+}
+
 pub async fn update_erasure_type(setup_type: SetupType) {
     let mut is_erasure = GLOBAL_IsErasure.write().await;
     *is_erasure = setup_type == SetupType::Erasure;
@@ -182,3 +408,4 @@ pub async fn update_erasure_type(setup_type: SetupType) {
 // }
 
 type TypeLocalDiskSetDrives = Vec<Vec<Vec<Option<DiskStore>>>>;
+pub type TypeLocalDiskMap = Arc<RwLock<HashMap<String, Option<DiskStore>>>>;
@@ -24,7 +24,7 @@ use tokio::{
     },
     time::interval,
 };
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
 use super::{
@@ -38,7 +38,7 @@ use crate::heal::heal_commands::{HEAL_ITEM_BUCKET, HealScanMode};
 use crate::heal::heal_ops::{BG_HEALING_UUID, HealSource};
 use crate::{
     config::RUSTFS_CONFIG_PREFIX,
-    disk::{BUCKET_META_PREFIX, DiskAPI, DiskInfoOptions, RUSTFS_META_BUCKET, endpoint::Endpoint, error::DiskError},
+    disk::{BUCKET_META_PREFIX, Disk, DiskAPI, DiskInfoOptions, RUSTFS_META_BUCKET, endpoint::Endpoint, error::DiskError, fault},
     global::{GLOBAL_BackgroundHealRoutine, GLOBAL_BackgroundHealState, GLOBAL_LOCAL_DISK_MAP},
     heal::{
         data_usage::{DATA_USAGE_CACHE_NAME, DATA_USAGE_ROOT},
@@ -47,7 +47,7 @@ use crate::{
         heal_ops::NOP_HEAL,
     },
     new_object_layer_fn,
-    store::get_disk_via_endpoint,
+    store::{all_local_disk, get_disk_via_endpoint},
     store_api::{BucketInfo, BucketOptions, StorageAPI},
 };
 
@@ -65,11 +65,46 @@ pub async fn init_auto_heal() {
             monitor_local_disks_and_heal().await;
         });
     }
+    let requeued = GLOBAL_MRFState.load(&all_local_disk().await).await;
+    if requeued > 0 {
+        info!("requeued {} mrf operations saved at last shutdown", requeued);
+    }
     spawn(async {
         GLOBAL_MRFState.heal_routine().await;
     });
 }
 
+/// Takes a drive that keeps failing out of its set and queues it for healing, the set monitor
+/// brings it back once it connects again.
+pub fn init_disk_fault_handler() {
+    fault::set_fault_handler(Arc::new(|ep, err| {
+        warn!("disk {} keeps failing, taking it offline: {}", ep, err);
+        let ep = ep.clone();
+        spawn(async move {
+            take_faulty_disk_offline(&ep).await;
+        });
+    }));
+}
+
+async fn take_faulty_disk_offline(ep: &Endpoint) {
+    if let Some(store) = new_object_layer_fn() {
+        let set = usize::try_from(ep.pool_idx)
+            .ok()
+            .and_then(|pool_idx| store.pools.get(pool_idx))
+            .zip(usize::try_from(ep.set_idx).ok())
+            .and_then(|(pool, set_idx)| pool.disk_set.get(set_idx));
+        if let Some(set) = set {
+            set.take_disk_offline(ep).await;
+        }
+    }
+
+    if ep.is_local {
+        GLOBAL_BackgroundHealState
+            .push_heal_local_disks(std::slice::from_ref(ep))
+            .await;
+    }
+}
+
 async fn init_background_healing() {
     let bg_seq = Arc::new(new_bg_heal_sequence());
     for _ in 0..GLOBAL_BackgroundHealRoutine.workers {
@@ -201,6 +236,10 @@ async fn heal_fresh_disk(endpoint: &Endpoint) -> Result<()> {
         "Healing drive '{}' - 'mc admin heal alias/ --verbose' to check the current status.",
         endpoint.to_string()
     );
+    let _healing = match disk.as_ref() {
+        Disk::Local(local_disk) => Some(local_disk.begin_heal()),
+        Disk::Remote(_) => None,
+    };
 
     let Some(store) = new_object_layer_fn() else {
         return Err(Error::other("errServerNotInitialized"));
@@ -350,6 +389,14 @@ pub struct HealRoutine {
     workers: usize,
 }
 
+impl std::fmt::Debug for HealRoutine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HealRoutine")
+            .field("workers", &self.workers)
+            .finish_non_exhaustive()
+    }
+}
+
 impl HealRoutine {
     pub fn new() -> Arc<Self> {
         let mut workers = num_cpus::get() / 2;
@@ -371,6 +418,18 @@ impl HealRoutine {
         })
     }
 
+    /// Waits until every queued heal task has been picked up by a worker, or `timeout` elapses.
+    pub async fn drain(&self, timeout: Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        while self.tasks_tx.capacity() < self.tasks_tx.max_capacity() {
+            if tokio::time::Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        true
+    }
+
     pub async fn add_worker(&self, bgseq: Arc<HealSequence>) {
         loop {
             let mut d_res = HealResultItem::default();
@@ -482,3 +541,31 @@ pub(crate) async fn heal_object(bucket: &str, object: &str, version_id: &str, sc
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::test_util;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_faulty_disk_taken_offline() {
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let set = store.pools[0].disk_set[0].clone();
+        let saved = set.disks.read().await.clone();
+        let ep = set.set_endpoints[1].clone();
+
+        take_faulty_disk_offline(&ep).await;
+        let offline = set.disks.read().await.iter().map(Option::is_none).collect::<Vec<_>>();
+        let queued = GLOBAL_BackgroundHealState.get_heal_local_disk_endpoints().await;
+
+        // put the disk back before checking so other tests keep a full set
+        *set.disks.write().await = saved;
+        GLOBAL_BackgroundHealState
+            .pop_heal_local_disks(std::slice::from_ref(&ep))
+            .await;
+
+        assert_eq!(offline, [false, true, false, false]);
+        assert!(queued.as_ref().contains(&ep));
+    }
+}
@@ -19,11 +19,12 @@ use std::{
     io::{Cursor, Read},
     path::{Path, PathBuf},
     pin::Pin,
+    str::FromStr,
     sync::{
-        Arc,
+        Arc, Mutex,
         atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
     },
-    time::{Duration, SystemTime},
+    time::{Duration, Instant, SystemTime},
 };
 
 use time::{self, OffsetDateTime};
@@ -65,7 +66,7 @@ use crate::{
         com::{read_config, save_config},
         heal::Config,
     },
-    disk::{DiskInfoOptions, DiskStore},
+    disk::{Disk, DiskInfoOptions, DiskStore},
     global::{GLOBAL_BackgroundHealState, GLOBAL_IsErasure, GLOBAL_IsErasureSD},
     heal::{
         data_usage::BACKGROUND_HEAL_INFO_PATH,
@@ -75,7 +76,7 @@ use crate::{
         heal_ops::{BG_HEALING_UUID, HealSource},
     },
     new_object_layer_fn,
-    store::ECStore,
+    store::{ECStore, all_local_disk},
     store_utils::is_reserved_or_invalid_bucket,
 };
 use crate::{disk::DiskAPI, store_api::ObjectInfo};
@@ -103,7 +104,8 @@ use tokio::{
     },
     time::sleep,
 };
-use tracing::{debug, error, info};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
 
 const DATA_SCANNER_SLEEP_PER_FOLDER: Duration = Duration::from_millis(1); // Time to wait between folders.
 const DATA_USAGE_UPDATE_DIR_CYCLES: u32 = 16; // Visit all folders every n cycles.
@@ -121,8 +123,12 @@ static _SCANNER_IDLE_MODE: AtomicU32 = AtomicU32::new(0); // default is throttle
 static SCANNER_EXCESS_OBJECT_VERSIONS: AtomicU64 = AtomicU64::new(100);
 static SCANNER_EXCESS_OBJECT_VERSIONS_TOTAL_SIZE: AtomicU64 = AtomicU64::new(1024 * 1024 * 1024 * 1024); // 1 TB
 static SCANNER_EXCESS_FOLDERS: AtomicU64 = AtomicU64::new(50_000);
+static SCANNER_THROTTLE: std::sync::RwLock<ScannerThrottle> = std::sync::RwLock::new(ScannerThrottle::DEFAULT);
+static TMP_GC_AGE_SECS: AtomicU64 = AtomicU64::new(24 * 60 * 60); // Temp files untouched this long are removed after each cycle.
+static SCANNER_COLD_BUCKET_PCT: AtomicU64 = AtomicU64::new(0); // Buckets that changed less than this since their last scan skip a cycle.
 
 lazy_static! {
+    static ref SCANNER_STOP: CancellationToken = CancellationToken::new();
     static ref SCANNER_SLEEPER: RwLock<DynamicSleeper> = RwLock::new(new_dynamic_sleeper(2.0, Duration::from_secs(1), true));
     pub static ref globalHealConfig: Arc<RwLock<Config>> = Arc::new(RwLock::new(Config::default()));
 }
@@ -163,7 +169,7 @@ impl DynamicSleeper {
         sleep(want_sleep).await;
     }
 
-    fn _update(&mut self, factor: f64, max_wait: Duration) -> Result<()> {
+    fn update(&mut self, factor: f64, max_wait: Duration) -> Result<()> {
         if (self.factor - factor).abs() < 1e-10 && self.max_sleep == max_wait {
             return Ok(());
         }
@@ -184,6 +190,141 @@ fn new_dynamic_sleeper(factor: f64, max_wait: Duration, is_scanner: bool) -> Dyn
     }
 }
 
+/// Scanner pacing presets selectable by operators.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ScannerSpeed {
+    Slow,
+    #[default]
+    Default,
+    Fast,
+}
+
+impl FromStr for ScannerSpeed {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "slow" => Ok(ScannerSpeed::Slow),
+            "default" => Ok(ScannerSpeed::Default),
+            "fast" => Ok(ScannerSpeed::Fast),
+            _ => Err(format!("invalid scanner speed {s:?}, expected one of: slow, default, fast")),
+        }
+    }
+}
+
+/// Limits how hard the background scanner works a drive.
+///
+/// `max_io_per_sec` caps scanned folders and objects per second on each drive
+/// (0 disables the cap), and `cpu_target_pct` is the share of wall time the
+/// scanner may spend working once it is being throttled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScannerThrottle {
+    pub max_io_per_sec: u32,
+    pub cpu_target_pct: u8,
+}
+
+impl ScannerThrottle {
+    pub const SLOW: Self = Self {
+        max_io_per_sec: 100,
+        cpu_target_pct: 10,
+    };
+    pub const DEFAULT: Self = Self {
+        max_io_per_sec: 500,
+        cpu_target_pct: 33,
+    };
+    pub const FAST: Self = Self {
+        max_io_per_sec: 2000,
+        cpu_target_pct: 50,
+    };
+
+    /// Sleep time per unit of work, as used by the scanner's dynamic sleeper.
+    pub fn sleep_factor(&self) -> f64 {
+        let pct = self.cpu_target_pct.clamp(1, 100) as f64;
+        (100.0 - pct) / pct
+    }
+
+    /// Returns a closure that asks for a sleep on every operation past the
+    /// per-second budget, so the number of sleeps grows with the excess IO.
+    pub fn should_sleep_fn(&self) -> ShouldSleepFn {
+        if self.max_io_per_sec == 0 {
+            return None;
+        }
+
+        let max_io = self.max_io_per_sec;
+        let window = Mutex::new((Instant::now(), 0u32));
+        Some(Arc::new(move || {
+            let mut window = window.lock().unwrap_or_else(|e| e.into_inner());
+            if window.0.elapsed() >= Duration::from_secs(1) {
+                *window = (Instant::now(), 0);
+            }
+            window.1 = window.1.saturating_add(1);
+            window.1 > max_io
+        }))
+    }
+}
+
+impl Default for ScannerThrottle {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl From<ScannerSpeed> for ScannerThrottle {
+    fn from(speed: ScannerSpeed) -> Self {
+        match speed {
+            ScannerSpeed::Slow => Self::SLOW,
+            ScannerSpeed::Default => Self::DEFAULT,
+            ScannerSpeed::Fast => Self::FAST,
+        }
+    }
+}
+
+/// Set the throttle applied from the next scanner cycle on.
+pub fn set_scanner_throttle(throttle: ScannerThrottle) {
+    *SCANNER_THROTTLE.write().unwrap_or_else(|e| e.into_inner()) = throttle;
+}
+
+pub fn get_scanner_throttle() -> ScannerThrottle {
+    *SCANNER_THROTTLE.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Set how long temp and trash files may sit on local drives before the scanner removes them, zero disables it
+pub fn set_tmp_gc_age(age: Duration) {
+    TMP_GC_AGE_SECS.store(age.as_secs(), Ordering::SeqCst);
+}
+
+pub fn get_tmp_gc_age() -> Duration {
+    Duration::from_secs(TMP_GC_AGE_SECS.load(Ordering::SeqCst))
+}
+
+/// Set how much, in percent of objects and of size, a bucket must change between two scans to be
+/// scanned in full on the next cycle, zero scans every bucket every cycle
+pub fn set_scanner_cold_bucket_threshold(pct: u64) {
+    SCANNER_COLD_BUCKET_PCT.store(pct, Ordering::SeqCst);
+}
+
+pub fn get_scanner_cold_bucket_threshold() -> u64 {
+    SCANNER_COLD_BUCKET_PCT.load(Ordering::SeqCst)
+}
+
+// Removes stale temp and trash files from every local drive.
+async fn gc_local_tmp() {
+    let age = get_tmp_gc_age();
+    if age.is_zero() {
+        return;
+    }
+
+    for disk in all_local_disk().await {
+        if let Disk::Local(local) = disk.as_ref() {
+            match local.gc_tmp(age).await {
+                Ok(0) => {}
+                Ok(n) => info!(disk = %local.to_string(), bytes = n, "Removed stale temp files"),
+                Err(err) => warn!(disk = %local.to_string(), error = %err, "Failed to remove stale temp files"),
+            }
+        }
+    }
+}
+
 /// Initialize and start the data scanner in the background
 ///
 /// This function starts a background task that continuously runs the data scanner
@@ -205,8 +346,11 @@ pub async fn init_data_scanner() {
 
     tokio::spawn(async move {
         loop {
-            // Run the data scanner
-            run_data_scanner().await;
+            // Run the data scanner, abandoning the cycle if a shutdown is requested
+            tokio::select! {
+                _ = run_data_scanner() => {}
+                _ = SCANNER_STOP.cancelled() => break,
+            }
 
             // Calculate randomized sleep duration
             // Use random factor (0.0 to 1.0) multiplied by the scanner cycle duration
@@ -222,11 +366,22 @@ pub async fn init_data_scanner() {
             info!(duration_secs = sleep_duration.as_secs(), "Data scanner sleeping before next cycle");
 
             // Sleep with the calculated duration
-            sleep(sleep_duration).await;
+            tokio::select! {
+                _ = sleep(sleep_duration) => {}
+                _ = SCANNER_STOP.cancelled() => break,
+            }
         }
+        info!("Data scanner stopped");
     });
 }
 
+/// Stop the background data scanner started by [`init_data_scanner`].
+///
+/// A cycle in progress is abandoned; the next start resumes from the last saved cycle.
+pub fn stop_data_scanner() {
+    SCANNER_STOP.cancel();
+}
+
 /// Run a single data scanner cycle
 ///
 /// This function performs one complete scan cycle, including:
@@ -308,8 +463,19 @@ async fn run_data_scanner() {
         "Starting namespace scanner"
     );
 
+    let throttle = get_scanner_throttle();
+    {
+        let mut sleeper = SCANNER_SLEEPER.write().await;
+        let max_sleep = sleeper.max_sleep;
+        let _ = sleeper.update(throttle.sleep_factor(), max_sleep);
+    }
+
     // Run the namespace scanner
-    match store.clone().ns_scanner(tx, cycle_info.current as usize, scan_mode).await {
+    match store
+        .clone()
+        .ns_scanner(tx, cycle_info.current as usize, scan_mode, throttle)
+        .await
+    {
         Ok(_) => {
             info!(cycle = cycle_info.current, "Namespace scanner completed successfully");
 
@@ -345,6 +511,8 @@ async fn run_data_scanner() {
         }
     }
 
+    gc_local_tmp().await;
+
     // Complete metrics collection for this cycle
     stop_fn(&scan_result);
 }
@@ -677,19 +845,18 @@ impl ScannerItem {
             return Ok(object_infos);
         }
 
-        let overflow_versions = &fivs[lim + 1..];
-        for fi in fivs[..lim + 1].iter() {
-            object_infos.push(ObjectInfo::from_file_info(
-                fi,
-                &self.bucket,
-                &self.object_path().to_string_lossy(),
-                versioned,
-            ));
-        }
+        // xl.meta order is not trusted here, the helper sorts by mod time
+        let mod_times: Vec<_> = fivs.iter().map(|fi| fi.mod_time).collect();
+        let overflow = lifecycle::newer_noncurrent_overflow(&mod_times, lim, event.noncurrent_days, OffsetDateTime::now_utc());
 
-        let mut to_del = Vec::<ObjectToDelete>::with_capacity(overflow_versions.len());
-        for fi in overflow_versions.iter() {
+        let mut to_del = Vec::<ObjectToDelete>::with_capacity(overflow.len());
+        for (i, fi) in fivs.iter().enumerate() {
             let obj = ObjectInfo::from_file_info(fi, &self.bucket, &self.object_path().to_string_lossy(), versioned);
+            if !overflow.contains(&i) {
+                object_infos.push(obj);
+                continue;
+            }
+
             if lock_enabled && enforce_retention_for_deletion(&obj) {
                 //if enforce_retention_for_deletion(&obj) {
                 if self.debug {
@@ -703,14 +870,6 @@ impl ScannerItem {
                 continue;
             }
 
-            if OffsetDateTime::now_utc().unix_timestamp()
-                < lifecycle::expected_expiry_time(obj.successor_mod_time.expect("err"), event.noncurrent_days as i32)
-                    .unix_timestamp()
-            {
-                object_infos.push(obj);
-                continue;
-            }
-
             to_del.push(ObjectToDelete {
                 object_name: obj.name,
                 version_id: obj.version_id,
@@ -1748,7 +1907,7 @@ mod tests {
     use rmp_serde::{Deserializer, Serializer};
     use serde::{Deserialize, Serialize};
 
-    use super::CurrentScannerCycle;
+    use super::{CurrentScannerCycle, ScannerSpeed, ScannerThrottle};
 
     #[test]
     fn test_current_cycle() {
@@ -1769,4 +1928,36 @@ mod tests {
 
         println!("{c:?}");
     }
+
+    #[test]
+    fn test_scanner_throttle() {
+        let throttle = ScannerThrottle {
+            max_io_per_sec: 100,
+            cpu_target_pct: 50,
+        };
+        let sleeps = |ops: usize| {
+            let should_sleep = throttle.should_sleep_fn().unwrap();
+            (0..ops).filter(|_| should_sleep()).count()
+        };
+
+        // Within budget nothing sleeps, past it every extra operation does.
+        assert_eq!(sleeps(100), 0);
+        assert_eq!(sleeps(200), 100);
+        assert_eq!(sleeps(400), 300);
+
+        let unlimited = ScannerThrottle {
+            max_io_per_sec: 0,
+            ..throttle
+        };
+        assert!(unlimited.should_sleep_fn().is_none());
+
+        assert_eq!(throttle.sleep_factor(), 1.0);
+        assert_eq!(ScannerThrottle::DEFAULT.sleep_factor().round(), 2.0);
+        assert!(ScannerThrottle::SLOW.sleep_factor() > ScannerThrottle::DEFAULT.sleep_factor());
+        assert!(ScannerThrottle::DEFAULT.sleep_factor() > ScannerThrottle::FAST.sleep_factor());
+
+        assert_eq!(ScannerThrottle::from("Slow".parse::<ScannerSpeed>().unwrap()), ScannerThrottle::SLOW);
+        assert_eq!(ScannerThrottle::default(), ScannerThrottle::DEFAULT);
+        assert!("turbo".parse::<ScannerSpeed>().is_err());
+    }
 }
@@ -151,6 +151,14 @@ pub async fn store_data_usage_in_backend(mut rx: Receiver<DataUsageInfo>) {
     }
 }
 
+/// Object count and size of `bucket` from the usage the scanner last persisted, along with when it was taken.
+pub async fn load_bucket_usage_from_backend(store: Arc<ECStore>, bucket: &str) -> Option<(u64, u64, Option<SystemTime>)> {
+    let buf = read_config(store, &DATA_USAGE_OBJ_NAME_PATH).await.ok()?;
+    let usage: DataUsageInfo = serde_json::from_slice(&buf).ok()?;
+    let bui = usage.buckets_usage.get(bucket)?;
+    Some((bui.objects_count, bui.size, usage.last_update))
+}
+
 // TODO: cancel ctx
 pub async fn load_data_usage_from_backend(store: Arc<ECStore>) -> Result<DataUsageInfo> {
     let buf = match read_config(store, &DATA_USAGE_OBJ_NAME_PATH).await {
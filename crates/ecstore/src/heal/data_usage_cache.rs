@@ -39,6 +39,10 @@ use super::data_usage::{BucketTargetUsageInfo, BucketUsageInfo, DataUsageInfo};
 pub const DATA_USAGE_BUCKET_LEN: usize = 11;
 pub const DATA_USAGE_VERSION_LEN: usize = 7;
 
+// Format version of the encoded DataUsageCache. Caches written before the
+// version field existed decode as version 0.
+pub const DATA_USAGE_CACHE_VERSION: u16 = 1;
+
 pub type DataUsageHashMap = HashSet<String>;
 
 struct ObjectHistogramInterval {
@@ -405,7 +409,7 @@ pub struct DataUsageEntryInfo {
     pub entry: DataUsageEntry,
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DataUsageCacheInfo {
     pub name: String,
     pub next_cycle: u32,
@@ -417,20 +421,25 @@ pub struct DataUsageCacheInfo {
     pub updates: Option<Sender<DataUsageEntry>>,
     #[serde(skip)]
     pub replication: Option<ReplicationConfiguration>,
+    // missing in caches written before versioning, which decode as 0
+    #[serde(default)]
+    pub version: u16,
 }
 
-// impl Default for DataUsageCacheInfo {
-//     fn default() -> Self {
-//         Self {
-//             name: Default::default(),
-//             next_cycle: Default::default(),
-//             last_update: SystemTime::now(),
-//             skip_healing: Default::default(),
-//             updates: Default::default(),
-//             replication: Default::default(),
-//         }
-//     }
-// }
+impl Default for DataUsageCacheInfo {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            next_cycle: Default::default(),
+            last_update: Default::default(),
+            skip_healing: Default::default(),
+            lifecycle: Default::default(),
+            updates: Default::default(),
+            replication: Default::default(),
+            version: DATA_USAGE_CACHE_VERSION,
+        }
+    }
+}
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct DataUsageCache {
@@ -438,7 +447,33 @@ pub struct DataUsageCache {
     pub cache: HashMap<String, DataUsageEntry>,
 }
 
+impl DataUsageCacheInfo {
+    /// Whether the full scan of the bucket is put off on cycle `want_cycle`.
+    pub fn scan_deferred(&self, want_cycle: u32) -> bool {
+        want_cycle < self.next_cycle
+    }
+}
+
 impl DataUsageCache {
+    /// Puts off the next full scan of a bucket whose object count and size both changed by less
+    /// than `threshold_pct` percent from `before` to `after`, returns whether it was put off.
+    pub fn defer_if_cold(
+        &mut self,
+        before: &DataUsageEntry,
+        after: &DataUsageEntry,
+        want_cycle: u32,
+        threshold_pct: u64,
+    ) -> bool {
+        let changed = |old: usize, new: usize| old.abs_diff(new) as u128 * 100 >= old.max(1) as u128 * threshold_pct as u128;
+        if threshold_pct == 0 || changed(before.objects, after.objects) || changed(before.size, after.size) {
+            return false;
+        }
+
+        // the next cycle reuses these totals, the one after scans the bucket again
+        self.info.next_cycle = want_cycle.saturating_add(2);
+        true
+    }
+
     pub async fn load(store: &SetDisks, name: &str) -> Result<Self> {
         let mut d = DataUsageCache::default();
         let mut retries = 0;
@@ -459,7 +494,7 @@ impl DataUsageCache {
                 .await
             {
                 Ok(mut reader) => {
-                    if let Ok(info) = Self::unmarshal(&reader.read_all().await?) {
+                    if let Ok(info) = Self::load_compat(&reader.read_all().await?) {
                         d = info
                     }
                     break;
@@ -482,7 +517,7 @@ impl DataUsageCache {
                                 .await
                             {
                                 Ok(mut reader) => {
-                                    if let Ok(info) = Self::unmarshal(&reader.read_all().await?) {
+                                    if let Ok(info) = Self::load_compat(&reader.read_all().await?) {
                                         d = info
                                     }
                                     break;
@@ -619,6 +654,52 @@ impl DataUsageCache {
         }
     }
 
+    /// Returns the `n` largest immediate children of `bucket` as
+    /// `(prefix, total size)`, largest first and ties ordered by prefix.
+    pub fn top_prefixes(&self, bucket: &str, n: usize) -> Vec<(String, u64)> {
+        let root_key = hash_path(bucket).key();
+        let Some(root) = self.cache.get(&root_key) else {
+            return Vec::new();
+        };
+
+        let parent = format!("{root_key}/");
+        let mut sizes: Vec<(String, u64)> = root
+            .children
+            .iter()
+            .filter(|child| self.cache.contains_key(*child))
+            .map(|child| {
+                let name = child.strip_prefix(&parent).unwrap_or(child).to_string();
+                (name, self.total_size(child))
+            })
+            .collect();
+
+        let order = |a: &(String, u64), b: &(String, u64)| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0));
+        if n < sizes.len() {
+            sizes.select_nth_unstable_by(n, order);
+            sizes.truncate(n);
+        }
+        sizes.sort_unstable_by(order);
+        sizes
+    }
+
+    // Size of an entry and everything below it. Walks the tree with an explicit
+    // stack over borrowed keys, so deep trees neither recurse nor clone entries.
+    fn total_size(&self, key: &str) -> u64 {
+        let mut total = 0;
+        let mut seen = HashSet::new();
+        let mut stack = vec![key];
+        while let Some(key) = stack.pop() {
+            if !seen.insert(key) {
+                continue;
+            }
+            if let Some(e) = self.cache.get(key) {
+                total += e.size as u64;
+                stack.extend(e.children.iter().map(String::as_str));
+            }
+        }
+        total
+    }
+
     pub fn search_parent(&self, hash: &DataUsageHash) -> Option<DataUsageHash> {
         let want = hash.key();
         if let Some(last_index) = want.rfind('/') {
@@ -765,12 +846,30 @@ impl DataUsageCache {
         for key in other_root.as_ref().unwrap().children.iter() {
             let entry = &o.cache[key];
             let flat = o.flatten(entry);
-            let mut existing = self.cache[key].clone();
+            let mut existing = self.cache.get(key).cloned().unwrap_or_default();
             existing.merge(&flat);
             self.replace_hashed(&DataUsageHash(key.clone()), &Some(e_hash.clone()), &existing);
         }
     }
 
+    /// Merges a single bucket cache, as returned by a drive scan, in as a child of this cache's root.
+    pub fn merge_bucket(&mut self, bucket: &DataUsageCache) {
+        let Some(root) = bucket.root() else {
+            return;
+        };
+        let mut other = DataUsageCache {
+            info: DataUsageCacheInfo {
+                name: self.info.name.clone(),
+                last_update: bucket.info.last_update,
+                ..Default::default()
+            },
+            cache: HashMap::new(),
+        };
+        other.replace(&self.info.name, "", DataUsageEntry::default());
+        other.replace(&bucket.info.name, &self.info.name, bucket.flatten(&root));
+        self.merge(&other);
+    }
+
     pub fn root_hash(&self) -> DataUsageHash {
         hash_path(&self.info.name)
     }
@@ -850,6 +949,85 @@ impl DataUsageCache {
         let t: Self = rmp_serde::from_slice(buf)?;
         Ok(t)
     }
+
+    /// Decodes a cache written by this or an earlier format version, upgrading
+    /// it to the current one. Newer versions are rejected so the caller can
+    /// drop the cache and rebuild it with a fresh scan.
+    pub fn load_compat(buf: &[u8]) -> Result<Self> {
+        let CacheVersionProbe(version) = rmp_serde::from_slice(buf)?;
+        match version {
+            DATA_USAGE_CACHE_VERSION => Self::unmarshal(buf),
+            // v0 only lacks the version field itself, serde fills in the defaults
+            0 => {
+                let mut cache = Self::unmarshal(buf)?;
+                cache.info.version = DATA_USAGE_CACHE_VERSION;
+                Ok(cache)
+            }
+            v => Err(Error::other(format!(
+                "data usage cache version {v} is newer than supported version {DATA_USAGE_CACHE_VERSION}, rebuild it by rescanning"
+            ))),
+        }
+    }
+}
+
+// Reads only `info.version` of an encoded DataUsageCache, ignoring everything
+// else so that layouts from newer versions can still be identified.
+struct CacheVersionProbe(u16);
+
+struct InfoVersionProbe(u16);
+
+// Fields of DataUsageCacheInfo encoded ahead of `version`.
+const INFO_FIELDS_BEFORE_VERSION: usize = 4;
+
+impl<'de> Deserialize<'de> for CacheVersionProbe {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = CacheVersionProbe;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("an encoded data usage cache")
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<Self::Value, A::Error> {
+                let InfoVersionProbe(version) = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                while seq.next_element::<serde::de::IgnoredAny>()?.is_some() {}
+                Ok(CacheVersionProbe(version))
+            }
+        }
+
+        deserializer.deserialize_seq(Visitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for InfoVersionProbe {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = InfoVersionProbe;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("encoded data usage cache info")
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<Self::Value, A::Error> {
+                for i in 0..INFO_FIELDS_BEFORE_VERSION {
+                    if seq.next_element::<serde::de::IgnoredAny>()?.is_none() {
+                        return Err(serde::de::Error::invalid_length(i, &self));
+                    }
+                }
+                let version = seq.next_element()?.unwrap_or_default();
+                while seq.next_element::<serde::de::IgnoredAny>()?.is_some() {}
+                Ok(InfoVersionProbe(version))
+            }
+        }
+
+        deserializer.deserialize_seq(Visitor)
+    }
 }
 
 #[derive(Default, Clone)]
@@ -926,3 +1104,177 @@ impl DataUsageHash {
 pub fn hash_path(data: &str) -> DataUsageHash {
     DataUsageHash(Path::new(&data).clean().to_string_lossy().to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_cache() -> DataUsageCache {
+        let mut cache = DataUsageCache {
+            info: DataUsageCacheInfo {
+                name: "bucket".to_string(),
+                next_cycle: 7,
+                last_update: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        cache.cache.insert(
+            hash_path("bucket").key(),
+            DataUsageEntry {
+                size: 1024,
+                objects: 3,
+                ..Default::default()
+            },
+        );
+        cache
+    }
+
+    #[test]
+    fn test_defer_cold_bucket() {
+        let before = DataUsageEntry {
+            size: 10 * 1024 * 1024,
+            objects: 1000,
+            ..Default::default()
+        };
+        let want_cycle = 5;
+
+        // a handful of small writes leave the bucket cold
+        let mut cold = sample_cache();
+        let unchanged = DataUsageEntry {
+            size: before.size + 1024,
+            objects: before.objects + 2,
+            ..Default::default()
+        };
+        assert!(cold.defer_if_cold(&before, &unchanged, want_cycle, 1));
+        assert!(cold.info.scan_deferred(want_cycle + 1));
+        assert!(!cold.info.scan_deferred(want_cycle + 2));
+
+        // a bucket that grew past the threshold is scanned on the next cycle
+        let mut hot = sample_cache();
+        hot.info.next_cycle = want_cycle;
+        let grown = DataUsageEntry {
+            size: before.size * 2,
+            objects: before.objects + 500,
+            ..Default::default()
+        };
+        assert!(!hot.defer_if_cold(&before, &grown, want_cycle, 1));
+        assert!(!hot.info.scan_deferred(want_cycle + 1));
+
+        // a threshold of zero turns deferral off
+        let mut cache = sample_cache();
+        cache.info.next_cycle = want_cycle;
+        assert!(!cache.defer_if_cold(&before, &before, want_cycle, 0));
+        assert!(!cache.info.scan_deferred(want_cycle + 1));
+    }
+
+    #[test]
+    fn test_load_compat_round_trip() {
+        let cache = sample_cache();
+        assert_eq!(cache.info.version, DATA_USAGE_CACHE_VERSION);
+
+        let loaded = DataUsageCache::load_compat(&cache.marshal_msg().unwrap()).unwrap();
+        assert_eq!(loaded.info.version, DATA_USAGE_CACHE_VERSION);
+        assert_eq!(loaded.info.name, "bucket");
+        assert_eq!(loaded.info.next_cycle, 7);
+        assert_eq!(loaded.info.last_update, cache.info.last_update);
+        let entry = &loaded.cache[&hash_path("bucket").key()];
+        assert_eq!((entry.size, entry.objects), (1024, 3));
+    }
+
+    #[test]
+    fn test_load_compat_versions() {
+        // Layout written before DataUsageCacheInfo carried a version.
+        #[derive(Serialize)]
+        struct InfoV0 {
+            name: String,
+            next_cycle: u32,
+            last_update: Option<SystemTime>,
+            skip_healing: bool,
+        }
+        #[derive(Serialize)]
+        struct CacheV0 {
+            info: InfoV0,
+            cache: HashMap<String, DataUsageEntry>,
+        }
+
+        let current = sample_cache();
+        let old = CacheV0 {
+            info: InfoV0 {
+                name: current.info.name.clone(),
+                next_cycle: current.info.next_cycle,
+                last_update: current.info.last_update,
+                skip_healing: true,
+            },
+            cache: current.cache.clone(),
+        };
+        let buf = rmp_serde::to_vec(&old).unwrap();
+
+        let loaded = DataUsageCache::load_compat(&buf).unwrap();
+        assert_eq!(loaded.info.version, DATA_USAGE_CACHE_VERSION);
+        assert_eq!(loaded.info.name, "bucket");
+        assert!(loaded.info.skip_healing);
+        assert_eq!(loaded.cache[&hash_path("bucket").key()].size, 1024);
+
+        // A newer writer may append fields; the version is still found and rejected.
+        #[derive(Serialize)]
+        struct InfoNext {
+            name: String,
+            next_cycle: u32,
+            last_update: Option<SystemTime>,
+            skip_healing: bool,
+            version: u16,
+            extra: String,
+        }
+        #[derive(Serialize)]
+        struct CacheNext {
+            info: InfoNext,
+            cache: HashMap<String, DataUsageEntry>,
+            extra: u64,
+        }
+        let next = CacheNext {
+            info: InfoNext {
+                name: "bucket".to_string(),
+                next_cycle: 1,
+                last_update: None,
+                skip_healing: false,
+                version: DATA_USAGE_CACHE_VERSION + 1,
+                extra: "new".to_string(),
+            },
+            cache: HashMap::new(),
+            extra: 1,
+        };
+        let err = DataUsageCache::load_compat(&rmp_serde::to_vec(&next).unwrap()).unwrap_err();
+        assert!(err.to_string().contains("rebuild it by rescanning"), "{err}");
+    }
+
+    #[test]
+    fn test_top_prefixes() {
+        let mut cache = DataUsageCache::default();
+        let mut add = |path: &str, parent: Option<&str>, size: usize| {
+            let e = DataUsageEntry {
+                size,
+                ..Default::default()
+            };
+            cache.replace_hashed(&hash_path(path), &parent.map(hash_path), &e);
+        };
+        add("bucket", None, 0);
+        add("bucket/a", Some("bucket"), 10);
+        add("bucket/b", Some("bucket"), 5);
+        add("bucket/b/x", Some("bucket/b"), 20);
+        add("bucket/b/x/y", Some("bucket/b/x"), 5);
+        add("bucket/c", Some("bucket"), 30);
+        add("bucket/d", Some("bucket"), 1);
+        add("other", None, 0);
+        add("other/z", Some("other"), 100);
+
+        // b is 5 + 20 + 5 = 30 and ties with c, so the name decides.
+        assert_eq!(
+            cache.top_prefixes("bucket", 3),
+            [("b".to_string(), 30), ("c".to_string(), 30), ("a".to_string(), 10)]
+        );
+        assert_eq!(cache.top_prefixes("bucket", 10).len(), 4);
+        assert!(cache.top_prefixes("bucket", 0).is_empty());
+        assert!(cache.top_prefixes("missing", 3).is_empty());
+    }
+}
@@ -15,7 +15,7 @@
 use std::{
     collections::{HashMap, HashSet},
     path::Path,
-    time::SystemTime,
+    time::{Duration, SystemTime},
 };
 
 use crate::{
@@ -99,6 +99,15 @@ impl Default for HealStartSuccess {
 
 pub type HealStopSuccess = HealStartSuccess;
 
+/// Throughput of a drive heal, all zero until the heal has started and reported progress.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HealRate {
+    pub objects_per_sec: f64,
+    pub bytes_per_sec: f64,
+    /// Time left to visit `objects_total_count` objects at the current rate, `None` when unknown.
+    pub eta: Option<Duration>,
+}
+
 #[derive(Debug, Default, Deserialize, Serialize)]
 pub struct HealingTracker {
     #[serde(skip_serializing, skip_deserializing)]
@@ -316,6 +325,36 @@ impl HealingTracker {
         });
     }
 
+    /// Heal throughput so far, measured from `started` to `last_update`.
+    pub fn heal_rate(&self) -> HealRate {
+        let (Some(started), Some(last_update)) = (self.started, self.last_update) else {
+            return HealRate::default();
+        };
+        let elapsed = (OffsetDateTime::from(last_update) - started).as_seconds_f64();
+        if elapsed <= 0.0 {
+            return HealRate::default();
+        }
+
+        let objects = self.items_healed + self.items_failed + self.item_skipped;
+        let bytes = self.bytes_done + self.bytes_failed + self.bytes_skipped;
+        let objects_per_sec = objects as f64 / elapsed;
+        let bytes_per_sec = bytes as f64 / elapsed;
+
+        let eta = if self.finished || (self.objects_total_count > 0 && objects >= self.objects_total_count) {
+            Some(Duration::ZERO)
+        } else if self.objects_total_count > 0 && objects_per_sec > 0.0 {
+            Some(Duration::from_secs_f64((self.objects_total_count - objects) as f64 / objects_per_sec))
+        } else {
+            None
+        };
+
+        HealRate {
+            objects_per_sec,
+            bytes_per_sec,
+            eta,
+        }
+    }
+
     pub async fn to_healing_disk(&self) -> rustfs_madmin::HealingDisk {
         let _ = self.mu.read().await;
 
@@ -542,3 +581,41 @@ pub async fn get_local_background_heal_status() -> (BgHealState, bool) {
 
     (status, true)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heal_rate() {
+        let started = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        let mut tracker = HealingTracker {
+            objects_total_count: 1000,
+            items_healed: 180,
+            items_failed: 10,
+            item_skipped: 10,
+            bytes_done: 3_000_000,
+            bytes_failed: 1_000_000,
+            ..Default::default()
+        };
+
+        // not yet begun
+        assert_eq!(tracker.heal_rate(), HealRate::default());
+        tracker.started = Some(started);
+        assert_eq!(tracker.heal_rate(), HealRate::default());
+
+        // 200 objects and 4 MB in 100s leaves 800 objects, 400s at 2 objects/sec
+        tracker.last_update = Some((started + time::Duration::seconds(100)).into());
+        let rate = tracker.heal_rate();
+        assert_eq!(rate.objects_per_sec, 2.0);
+        assert_eq!(rate.bytes_per_sec, 40_000.0);
+        assert_eq!(rate.eta, Some(Duration::from_secs(400)));
+
+        // total not known yet
+        tracker.objects_total_count = 0;
+        assert_eq!(tracker.heal_rate().eta, None);
+
+        tracker.objects_total_count = 200;
+        assert_eq!(tracker.heal_rate().eta, Some(Duration::ZERO));
+    }
+}
@@ -13,7 +13,7 @@
 // limitations under the License.
 
 use super::{
-    background_heal_ops::HealTask,
+    background_heal_ops::{HealRoutine, HealTask},
     data_scanner::HEAL_DELETE_DANGLING,
     error::ERR_SKIP_FILE,
     heal_commands::{HEAL_ITEM_BUCKET_METADATA, HealOpts, HealScanMode, HealStopSuccess, HealingTracker},
@@ -59,13 +59,14 @@ use tokio::{
     },
     time::{interval, sleep},
 };
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
 type HealStatusSummary = String;
 type ItemsMap = HashMap<HealItemType, usize>;
 pub type HealEntryFn =
     Arc<dyn Fn(String, MetaCacheEntry, HealScanMode) -> Pin<Box<dyn Future<Output = Result<()>> + Send>> + Send + Sync + 'static>;
+pub type HealProgressFn = Arc<dyn Fn(HealResultItem) + Send + Sync + 'static>;
 
 pub const BG_HEALING_UUID: &str = "0000-0000-0000-0000";
 pub const HEALING_TRACKER_FILENAME: &str = ".healing.bin";
@@ -81,6 +82,7 @@ pub const LOGIN_PATH_PREFIX: &str = "/login";
 
 const MAX_UNCONSUMED_HEAL_RESULT_ITEMS: usize = 1000;
 const HEAL_UNCONSUMED_TIMEOUT: Duration = Duration::from_secs(24 * 60 * 60);
+const HEAL_PROGRESS_BUFFER: usize = 1024;
 pub const NOP_HEAL: &str = "";
 
 lazy_static! {}
@@ -103,6 +105,37 @@ pub struct HealSource {
     pub opts: Option<HealOpts>,
 }
 
+/// Hands heal results to a progress callback running on its own task, so a slow
+/// callback never holds up the heal. Results are dropped while the buffer is full.
+#[derive(Clone)]
+pub struct HealProgress {
+    tx: M_Sender<HealResultItem>,
+}
+
+impl HealProgress {
+    pub fn new(progress: HealProgressFn) -> Self {
+        let (tx, mut rx) = mpsc::channel::<HealResultItem>(HEAL_PROGRESS_BUFFER);
+        spawn(async move {
+            while let Some(item) = rx.recv().await {
+                progress(item);
+            }
+        });
+        Self { tx }
+    }
+
+    fn report(&self, item: &HealResultItem) {
+        if self.tx.try_send(item.clone()).is_err() {
+            warn!("heal progress is falling behind, dropped result for {}/{}", item.bucket, item.object);
+        }
+    }
+}
+
+impl std::fmt::Debug for HealProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HealProgress").finish_non_exhaustive()
+    }
+}
+
 #[derive(Debug)]
 pub struct HealSequence {
     pub bucket: String,
@@ -120,6 +153,9 @@ pub struct HealSequence {
     pub healed_items_map: RwLock<ItemsMap>,
     pub heal_failed_items_map: RwLock<ItemsMap>,
     pub last_heal_activity: RwLock<SystemTime>,
+    pub progress: Option<HealProgress>,
+    // heal tasks go to the global background heal routine when unset
+    pub routine: Option<Arc<HealRoutine>>,
 
     traverse_and_heal_done_tx: Arc<RwLock<M_Sender<Option<Error>>>>,
     traverse_and_heal_done_rx: Arc<RwLock<M_Receiver<Option<Error>>>>,
@@ -198,6 +234,8 @@ impl Default for HealSequence {
             healed_items_map: Default::default(),
             heal_failed_items_map: Default::default(),
             last_heal_activity: RwLock::new(SystemTime::now()),
+            progress: None,
+            routine: None,
             traverse_and_heal_done_tx: Arc::new(RwLock::new(h_tx)),
             traverse_and_heal_done_rx: Arc::new(RwLock::new(h_rx)),
             tx,
@@ -226,6 +264,22 @@ impl HealSequence {
             ..Default::default()
         }
     }
+
+    /// Reports the result of every item the sequence heals to `progress` as it completes.
+    pub fn with_progress(mut self, progress: HealProgressFn) -> Self {
+        self.progress = Some(HealProgress::new(progress));
+        self
+    }
+
+    /// Queues the heal tasks of the sequence on `routine` instead of the global background heal routine.
+    pub fn with_routine(mut self, routine: Arc<HealRoutine>) -> Self {
+        self.routine = Some(routine);
+        self
+    }
+
+    fn routine(&self) -> &HealRoutine {
+        self.routine.as_deref().unwrap_or(&GLOBAL_BackgroundHealRoutine)
+    }
 }
 
 impl HealSequence {
@@ -333,7 +387,7 @@ impl HealSequence {
 
         if source.no_wait {
             let task_str = format!("{task:?}");
-            if GLOBAL_BackgroundHealRoutine.tasks_tx.try_send(task).is_ok() {
+            if self.routine().tasks_tx.try_send(task).is_ok() {
                 info!("Task in the queue: {:?}", task_str);
             }
             return Ok(());
@@ -343,7 +397,7 @@ impl HealSequence {
         task.resp_tx = Some(resp_tx);
 
         let task_str = format!("{task:?}");
-        if GLOBAL_BackgroundHealRoutine.tasks_tx.try_send(task).is_ok() {
+        if self.routine().tasks_tx.try_send(task).is_ok() {
             info!("Task in the queue: {:?}", task_str);
         } else {
             error!("push task to queue failed");
@@ -365,16 +419,6 @@ impl HealSequence {
                 } else {
                     self.count_failed(heal_type.clone()).await;
                 }
-                if !self.report_progress {
-                    return if let Some(err) = res.err {
-                        if err.to_string() == ERR_SKIP_FILE {
-                            return Ok(());
-                        }
-                        Err(err)
-                    } else {
-                        Ok(())
-                    };
-                }
                 res.result.heal_item_type = heal_type.clone();
                 if let Some(err) = res.err.as_ref() {
                     res.result.detail = err.to_string();
@@ -389,6 +433,19 @@ impl HealSequence {
                         );
                     }
                 }
+                if let Some(progress) = &self.progress {
+                    progress.report(&res.result);
+                }
+                if !self.report_progress {
+                    return if let Some(err) = res.err {
+                        if err.to_string() == ERR_SKIP_FILE {
+                            return Ok(());
+                        }
+                        Err(err)
+                    } else {
+                        Ok(())
+                    };
+                }
 
                 info!("queue_heal_task, HealResult: {:?}", res);
                 self.push_heal_result_item(&res.result).await
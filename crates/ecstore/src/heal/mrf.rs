@@ -12,26 +12,30 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::disk::{BUCKET_META_PREFIX, RUSTFS_META_BUCKET};
+use crate::disk::{BUCKET_META_PREFIX, DiskAPI, DiskStore, RUSTFS_META_BUCKET};
+use crate::error::{Error, Result};
 use crate::heal::background_heal_ops::{heal_bucket, heal_object};
 use crate::heal::heal_commands::{HEAL_DEEP_SCAN, HEAL_NORMAL_SCAN};
 use chrono::{DateTime, Utc};
 use lazy_static::lazy_static;
 use regex::Regex;
-use rustfs_utils::path::SLASH_SEPARATOR;
+use rustfs_utils::path::{SLASH_SEPARATOR, path_join_buf};
+use serde::{Deserialize, Serialize};
 use std::ops::Sub;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 use tokio::sync::RwLock;
 use tokio::sync::mpsc::{Receiver, Sender};
 use tokio::time::sleep;
-use tracing::error;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
 pub const MRF_OPS_QUEUE_SIZE: u64 = 100000;
 pub const HEAL_DIR: &str = ".heal";
 pub const HEAL_MRFMETA_FORMAT: u64 = 1;
 pub const HEAL_MRFMETA_VERSION_V1: u64 = 1;
+pub const HEAL_MRF_LIST_FILE: &str = "list.bin";
 
 lazy_static! {
     pub static ref HEAL_MRF_DIR: String =
@@ -44,7 +48,7 @@ lazy_static! {
     ];
 }
 
-#[derive(Default)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct PartialOperation {
     pub bucket: String,
     pub object: String,
@@ -61,6 +65,7 @@ pub struct MRFState {
     rx: RwLock<Receiver<PartialOperation>>,
     closed: AtomicBool,
     closing: AtomicBool,
+    stop: CancellationToken,
 }
 
 impl Default for MRFState {
@@ -77,6 +82,7 @@ impl MRFState {
             rx: RwLock::new(rx),
             closed: Default::default(),
             closing: Default::default(),
+            stop: CancellationToken::new(),
         }
     }
 
@@ -89,8 +95,15 @@ impl MRFState {
 
     pub async fn heal_routine(&self) {
         loop {
-            // rx used only there,
-            if let Some(op) = self.rx.write().await.recv().await {
+            // rx used only there and by shutdown once this loop has returned
+            let op = {
+                let mut rx = self.rx.write().await;
+                tokio::select! {
+                    op = rx.recv() => op,
+                    _ = self.stop.cancelled() => None,
+                }
+            };
+            if let Some(op) = op {
                 if op.bucket == RUSTFS_META_BUCKET {
                     for pattern in &*PATTERNS {
                         if pattern.is_match(&op.object) {
@@ -139,4 +152,90 @@ impl MRFState {
             }
         }
     }
+
+    /// Stops accepting new operations and ends [`MRFState::heal_routine`] after its current heal.
+    ///
+    /// Unless `force` is set, the operations still queued are written to `disks` so that
+    /// [`MRFState::load`] can requeue them on the next start.
+    pub async fn shutdown(&self, disks: &[DiskStore], force: bool) -> Result<usize> {
+        self.closing.store(true, Ordering::SeqCst);
+        self.stop.cancel();
+
+        let mut rx = self.rx.write().await;
+        let mut ops = Vec::new();
+        while let Ok(op) = rx.try_recv() {
+            ops.push(op);
+        }
+        self.closed.store(true, Ordering::SeqCst);
+        self.closing.store(false, Ordering::SeqCst);
+
+        if force || ops.is_empty() {
+            return Ok(0);
+        }
+
+        let buf = encode_mrf_list(&ops)?;
+        let path = mrf_list_path();
+        let mut saved = false;
+        for disk in disks {
+            match disk.write_all(RUSTFS_META_BUCKET, &path, buf.clone().into()).await {
+                Ok(_) => saved = true,
+                Err(err) => warn!("save mrf list to {} failed, err: {:?}", disk.to_string(), err),
+            }
+        }
+        if !saved {
+            return Err(Error::other("mrf list could not be saved to any local drive"));
+        }
+
+        info!("saved {} pending mrf operations", ops.len());
+        Ok(ops.len())
+    }
+
+    /// Requeues operations saved by a previous [`MRFState::shutdown`] and removes the saved list.
+    pub async fn load(&self, disks: &[DiskStore]) -> usize {
+        let path = mrf_list_path();
+        let mut ops = None;
+        for disk in disks {
+            let Ok(buf) = disk.read_all(RUSTFS_META_BUCKET, &path).await else {
+                continue;
+            };
+            if ops.is_none() {
+                match decode_mrf_list(&buf) {
+                    Ok(v) => ops = Some(v),
+                    Err(err) => warn!("load mrf list from {} failed, err: {:?}", disk.to_string(), err),
+                }
+            }
+            let _ = disk.delete(RUSTFS_META_BUCKET, &path, Default::default()).await;
+        }
+
+        let ops = ops.unwrap_or_default();
+        let n = ops.len();
+        for op in ops {
+            self.add_partial(op).await;
+        }
+        n
+    }
+}
+
+pub(crate) fn mrf_list_path() -> String {
+    path_join_buf(&[HEAL_MRF_DIR.as_str(), HEAL_MRF_LIST_FILE])
+}
+
+fn encode_mrf_list(ops: &[PartialOperation]) -> Result<Vec<u8>> {
+    let mut buf = Vec::with_capacity(4);
+    buf.extend_from_slice(&(HEAL_MRFMETA_FORMAT as u16).to_le_bytes());
+    buf.extend_from_slice(&(HEAL_MRFMETA_VERSION_V1 as u16).to_le_bytes());
+    buf.extend(rmp_serde::to_vec(ops).map_err(Error::other)?);
+    Ok(buf)
+}
+
+pub(crate) fn decode_mrf_list(buf: &[u8]) -> Result<Vec<PartialOperation>> {
+    if buf.len() < 4 {
+        return Err(Error::other("mrf list too short"));
+    }
+    let format = u16::from_le_bytes([buf[0], buf[1]]) as u64;
+    let version = u16::from_le_bytes([buf[2], buf[3]]) as u64;
+    if format != HEAL_MRFMETA_FORMAT || version != HEAL_MRFMETA_VERSION_V1 {
+        return Err(Error::other(format!("unknown mrf list format {format} version {version}")));
+    }
+    rmp_serde::from_slice(&buf[4..]).map_err(Error::other)
 }
@@ -16,6 +16,7 @@
 extern crate core;
 
 pub mod admin_server_info;
+pub mod audit;
 pub mod bitrot;
 pub mod bucket;
 pub mod cache_value;
@@ -30,6 +31,7 @@ pub mod erasure_coding;
 pub mod error;
 pub mod global;
 pub mod heal;
+pub mod kms;
 pub mod metrics_realtime;
 pub mod notification_sys;
 pub mod pools;
@@ -49,8 +51,15 @@ pub mod event;
 pub mod event_notification;
 pub mod tier;
 
+pub use global::get_global_max_object_size;
+pub use global::is_global_read_only;
 pub use global::new_object_layer_fn;
 pub use global::set_global_endpoints;
+pub use global::set_global_max_list_keys;
+pub use global::set_global_max_object_size;
+pub use global::set_global_max_user_metadata_size;
+pub use global::set_global_max_versions_per_object;
+pub use global::set_global_read_only;
 pub use global::update_erasure_type;
 
 pub use global::GLOBAL_Endpoints;
@@ -242,6 +242,17 @@ impl PoolMeta {
             false
         }
     }
+    // Forgets the decommission of a pool, which takes new objects again.
+    pub fn decommission_clear(&mut self, idx: usize) -> bool {
+        match self.pools.get_mut(idx) {
+            Some(pool) if pool.decommission.is_some() => {
+                pool.last_update = OffsetDateTime::now_utc();
+                pool.decommission = None;
+                true
+            }
+            _ => false,
+        }
+    }
     pub fn decommission(&mut self, idx: usize, pi: PoolSpaceInfo) -> Result<()> {
         if let Some(pool) = self.pools.get_mut(idx) {
             if let Some(ref info) = pool.decommission {
@@ -637,6 +648,34 @@ impl ECStore {
 
         Ok(())
     }
+
+    /// Marks a pool as decommissioning in the pool meta every node reads: new objects are placed on
+    /// the remaining pools while objects already on it are still read from it, until
+    /// [`ECStore::decommission`] has moved them off.
+    #[tracing::instrument(skip(self))]
+    pub async fn set_pool_decommissioning(&self, idx: usize, decommissioning: bool) -> Result<()> {
+        if decommissioning {
+            if self.pool_meta.read().await.is_suspended(idx) {
+                return Ok(());
+            }
+
+            return self.start_decommission(vec![idx]).await;
+        }
+
+        let mut lock = self.pool_meta.write().await;
+        if lock.decommission_clear(idx) {
+            lock.save(self.pools.clone()).await?;
+
+            drop(lock);
+
+            if let Some(notification_sys) = get_global_notification_sys() {
+                notification_sys.reload_pool_meta().await;
+            }
+        }
+
+        Ok(())
+    }
+
     pub async fn is_decommission_running(&self) -> bool {
         let pool_meta = self.pool_meta.read().await;
         for pool in pool_meta.pools.iter() {
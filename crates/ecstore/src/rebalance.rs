@@ -16,9 +16,11 @@ use crate::StorageAPI;
 use crate::cache_value::metacache_set::{ListPathRawOptions, list_path_raw};
 use crate::config::com::{read_config_with_metadata, save_config_with_opts};
 use crate::disk::error::DiskError;
+use crate::endpoints::EndpointServerPools;
 use crate::error::{Error, Result};
 use crate::error::{is_err_data_movement_overwrite, is_err_object_not_found, is_err_version_not_found};
 use crate::global::get_global_endpoints;
+use crate::heal::data_usage_cache::DataUsageCache;
 use crate::pools::ListCallback;
 use crate::set_disk::SetDisks;
 use crate::store::ECStore;
@@ -141,13 +143,130 @@ pub struct RebalanceInfo {
     pub status: RebalStatus, // Current state of rebalance operation
 }
 
-#[allow(dead_code)]
 #[derive(Debug, Clone, Default)]
 pub struct DiskStat {
     pub total_space: u64,
     pub available_space: u64,
 }
 
+/// One step of a rebalance plan, a bucket or one prefix of it moving to another pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RebalanceMove {
+    pub bucket: String,
+    pub prefix: String, // empty when the whole bucket moves
+    pub from_pool: usize,
+    pub to_pool: usize,
+    pub estimated_bytes: u64, // object bytes to copy, parity not included
+}
+
+/// Moves that even out pool utilization, in the order they should run. Nothing is moved
+/// until an operator starts the rebalance.
+#[derive(Debug, Clone, Default)]
+pub struct RebalancePlan {
+    pub percent_free_goal: f64,
+    pub moves: Vec<RebalanceMove>,
+}
+
+impl RebalancePlan {
+    pub fn total_bytes(&self) -> u64 {
+        self.moves.iter().map(|m| m.estimated_bytes).sum()
+    }
+}
+
+/// Plans which buckets, or prefixes of buckets too large to move whole, leave the pools that are
+/// fuller than the cluster average for the emptiest pools. `usage` and `disk_stats` are indexed by pool.
+pub fn plan_rebalance(usage: &[DataUsageCache], pools: &EndpointServerPools, disk_stats: &[DiskStat]) -> RebalancePlan {
+    let stats: Vec<DiskStat> = (0..pools.as_ref().len())
+        .map(|idx| disk_stats.get(idx).cloned().unwrap_or_default())
+        .collect();
+    let total_cap: u64 = stats.iter().map(|s| s.total_space).sum();
+    let total_free: u64 = stats.iter().map(|s| s.available_space).sum();
+    if total_cap == 0 {
+        return RebalancePlan::default();
+    }
+    let percent_free_goal = total_free as f64 / total_cap as f64;
+
+    // bytes each pool holds above (positive) or below (negative) its share at the goal
+    let total_used = total_cap.saturating_sub(total_free) as u128;
+    let mut excess: Vec<i64> = stats
+        .iter()
+        .map(|s| {
+            let used = s.total_space.saturating_sub(s.available_space) as i64;
+            let share = (s.total_space as u128 * total_used / total_cap as u128) as i64;
+            used - share
+        })
+        .collect();
+
+    let mut sources: Vec<usize> = (0..stats.len()).filter(|&idx| excess[idx] > 0).collect();
+    sources.sort_by_key(|&idx| std::cmp::Reverse(excess[idx]));
+
+    let mut moves = Vec::new();
+    for from in sources {
+        let Some(cache) = usage.get(from) else {
+            continue;
+        };
+        let Some(root) = cache.root() else {
+            continue;
+        };
+
+        // usage counts object bytes, the disk stats count them with parity
+        let logical = cache.flatten(&root).size as u64;
+        let used = stats[from].total_space.saturating_sub(stats[from].available_space);
+        let ratio = if logical > 0 {
+            (used as f64 / logical as f64).max(1.0)
+        } else {
+            1.0
+        };
+
+        let mut buckets: Vec<(String, u64)> = root
+            .children
+            .iter()
+            .filter_map(|bucket| cache.size_recursive(bucket).map(|e| (bucket.clone(), e.size as u64)))
+            .collect();
+        buckets.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        for (bucket, size) in buckets {
+            if excess[from] <= 0 {
+                break;
+            }
+            // a bucket larger than what has to go is split into its top level prefixes
+            let candidates = if (size as f64 * ratio) as i64 <= excess[from] {
+                vec![(String::new(), size)]
+            } else {
+                cache.top_prefixes(&bucket, usize::MAX)
+            };
+
+            for (prefix, size) in candidates {
+                let bytes = (size as f64 * ratio) as i64;
+                if bytes == 0 || bytes > excess[from] {
+                    continue;
+                }
+                let Some(to) = (0..excess.len())
+                    .filter(|&idx| excess[idx] < 0)
+                    .min_by_key(|&idx| excess[idx])
+                else {
+                    break;
+                };
+
+                excess[from] -= bytes;
+                excess[to] += bytes;
+                moves.push(RebalanceMove {
+                    bucket: bucket.clone(),
+                    prefix,
+                    from_pool: from,
+                    to_pool: to,
+                    estimated_bytes: size,
+                });
+            }
+        }
+    }
+
+    RebalancePlan {
+        percent_free_goal,
+        moves,
+    }
+}
+
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct RebalanceMeta {
     #[serde(skip)]
@@ -1203,3 +1322,108 @@ impl SetDisks {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endpoints::{Endpoints, PoolEndpoints};
+    use crate::heal::data_usage::DATA_USAGE_ROOT;
+    use crate::heal::data_usage_cache::DataUsageEntry;
+
+    const GIB: u64 = 1 << 30;
+
+    fn pools(count: usize) -> EndpointServerPools {
+        EndpointServerPools(
+            (0..count)
+                .map(|idx| PoolEndpoints {
+                    legacy: false,
+                    set_count: 1,
+                    drives_per_set: 4,
+                    endpoints: Endpoints::default(),
+                    cmd_line: format!("pool{idx}"),
+                    platform: String::new(),
+                })
+                .collect(),
+        )
+    }
+
+    fn usage(entries: &[(&str, &str, u64)]) -> DataUsageCache {
+        let mut cache = DataUsageCache::default();
+        cache.info.name = DATA_USAGE_ROOT.to_string();
+        cache.replace(DATA_USAGE_ROOT, "", DataUsageEntry::default());
+        for (path, parent, size) in entries {
+            let entry = DataUsageEntry {
+                size: *size as usize,
+                ..Default::default()
+            };
+            cache.replace(path, parent, entry);
+        }
+        cache
+    }
+
+    #[test]
+    fn test_plan_rebalance_toward_empty_pool() {
+        // pool 0 is 90% full, pool 1 was just added
+        let full = usage(&[
+            ("photos", DATA_USAGE_ROOT, 0),
+            ("photos/2023", "photos", 400 * GIB),
+            ("photos/2024", "photos", 300 * GIB),
+            ("tmp", DATA_USAGE_ROOT, 150 * GIB),
+            ("logs", DATA_USAGE_ROOT, 50 * GIB),
+        ]);
+        let stats = [
+            DiskStat {
+                total_space: 1000 * GIB,
+                available_space: 100 * GIB,
+            },
+            DiskStat {
+                total_space: 1000 * GIB,
+                available_space: 1000 * GIB,
+            },
+        ];
+
+        let plan = plan_rebalance(&[full, DataUsageCache::default()], &pools(2), &stats);
+        assert!((plan.percent_free_goal - 0.55).abs() < 1e-9);
+
+        // 450GiB has to go: photos is too large to move whole so its largest prefix goes,
+        // tmp no longer fits after that and logs makes up the rest
+        assert_eq!(
+            plan.moves,
+            [
+                RebalanceMove {
+                    bucket: "photos".to_string(),
+                    prefix: "2023".to_string(),
+                    from_pool: 0,
+                    to_pool: 1,
+                    estimated_bytes: 400 * GIB,
+                },
+                RebalanceMove {
+                    bucket: "logs".to_string(),
+                    prefix: String::new(),
+                    from_pool: 0,
+                    to_pool: 1,
+                    estimated_bytes: 50 * GIB,
+                },
+            ]
+        );
+        assert_eq!(plan.total_bytes(), 450 * GIB);
+    }
+
+    #[test]
+    fn test_plan_rebalance_balanced_pools() {
+        let half = usage(&[("data", DATA_USAGE_ROOT, 500 * GIB)]);
+        let stats = [
+            DiskStat {
+                total_space: 1000 * GIB,
+                available_space: 500 * GIB,
+            },
+            DiskStat {
+                total_space: 2000 * GIB,
+                available_space: 1000 * GIB,
+            },
+        ];
+
+        let plan = plan_rebalance(&[half.clone(), half], &pools(2), &stats);
+        assert!(plan.moves.is_empty());
+    }
+}
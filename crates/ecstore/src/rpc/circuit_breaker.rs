@@ -0,0 +1,135 @@
+// Copyright 2024 RustFS Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Circuit breaker for calls to a remote disk. After enough consecutive failures of one class
+//! of calls the breaker opens and those calls fail fast; once the cooldown has passed a single
+//! probe goes out, and its outcome closes the breaker or opens it again.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures that open the breaker.
+    pub failure_threshold: u32,
+    /// How long an open breaker fails calls before letting a probe through.
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Calls share a breaker with the others of their class, so failing scans do not stop reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OpClass {
+    Read,
+    Write,
+    Scan,
+}
+
+impl OpClass {
+    pub fn of(op: &str) -> Self {
+        match op {
+            "ns_scanner" | "clean_abandoned_data" => OpClass::Scan,
+            "verify_file" | "check_parts" | "disk_info" | "fs_mount_info" => OpClass::Read,
+            op if op.starts_with("read_") || op.starts_with("stat_") || op.starts_with("list_") => OpClass::Read,
+            _ => OpClass::Write,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct Breaker {
+    state: BreakerState,
+    failures: u32,
+    // when the breaker last opened, or when the probe of a half-open breaker went out
+    since: Instant,
+}
+
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    breakers: Mutex<HashMap<OpClass, Breaker>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            breakers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns whether a call of `class` may go out. An open breaker past its cooldown turns
+    /// half-open and lets this call through as the probe; further calls wait for its outcome.
+    pub fn allow(&self, class: OpClass) -> bool {
+        let mut breakers = self.breakers.lock().unwrap();
+        let Some(breaker) = breakers.get_mut(&class) else {
+            return true;
+        };
+
+        match breaker.state {
+            BreakerState::Closed => true,
+            // a probe that never reported back, e.g. a dropped call, does not hold the breaker forever
+            BreakerState::Open | BreakerState::HalfOpen if breaker.since.elapsed() >= self.config.cooldown => {
+                breaker.state = BreakerState::HalfOpen;
+                breaker.since = Instant::now();
+                true
+            }
+            BreakerState::Open | BreakerState::HalfOpen => false,
+        }
+    }
+
+    /// Records the outcome of a call of `class` that [`CircuitBreaker::allow`] let through.
+    pub fn record(&self, class: OpClass, success: bool) {
+        let mut breakers = self.breakers.lock().unwrap();
+        if success {
+            breakers.remove(&class);
+            return;
+        }
+
+        let breaker = breakers.entry(class).or_insert(Breaker {
+            state: BreakerState::Closed,
+            failures: 0,
+            since: Instant::now(),
+        });
+        breaker.failures += 1;
+        if breaker.state == BreakerState::HalfOpen || breaker.failures >= self.config.failure_threshold {
+            breaker.state = BreakerState::Open;
+            breaker.since = Instant::now();
+        }
+    }
+
+    pub fn state(&self, class: OpClass) -> BreakerState {
+        self.breakers
+            .lock()
+            .unwrap()
+            .get(&class)
+            .map_or(BreakerState::Closed, |b| b.state)
+    }
+}
@@ -12,12 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod circuit_breaker;
 mod http_auth;
 mod peer_rest_client;
 mod peer_s3_client;
 mod remote_disk;
 mod tonic_service;
 
+pub use circuit_breaker::{BreakerState, CircuitBreakerConfig, OpClass};
 pub use http_auth::{build_auth_headers, verify_rpc_signature};
 pub use peer_rest_client::PeerRestClient;
 pub use peer_s3_client::{LocalPeerS3Client, PeerS3Client, RemotePeerS3Client, S3PeerSys};
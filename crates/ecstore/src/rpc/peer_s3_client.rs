@@ -180,15 +180,16 @@ impl S3PeerSys {
                 let pools = cli.get_pools();
                 let idx = i;
                 if pools.unwrap_or_default().contains(&idx) {
-                    per_pool_errs.push(errors[j].as_ref());
+                    per_pool_errs.push(errors[j].clone());
                 }
+            }
 
-                // TODO: reduceWriteQuorumErrs
+            if let Some(pool_err) = reduce_write_quorum_errs(&per_pool_errs, BUCKET_OP_IGNORED_ERRS, per_pool_errs.len() / 2 + 1)
+            {
+                return Err(pool_err);
             }
         }
 
-        // TODO:
-
         Ok(())
     }
     pub async fn list_bucket(&self, opts: &BucketOptions) -> Result<Vec<BucketInfo>> {
@@ -408,7 +409,13 @@ impl PeerS3Client for LocalPeerS3Client {
             }
         }
 
-        // TODO: reduceWriteQuorumErrs
+        if errs.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(err) = reduce_write_quorum_errs(&errs, BUCKET_OP_IGNORED_ERRS, local_disks.len() / 2 + 1) {
+            return Err(err);
+        }
 
         Ok(())
     }
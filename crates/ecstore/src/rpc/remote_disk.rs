@@ -13,28 +13,35 @@
 // limitations under the License.
 
 use std::path::PathBuf;
+use std::time::Instant;
 
 use bytes::Bytes;
 use futures::lock::Mutex;
 use http::{HeaderMap, HeaderValue, Method, header::CONTENT_TYPE};
 use rustfs_protos::{
+    NODE_SERVICE_VERSION,
+    models::PingBodyBuilder,
     node_service_time_out_client,
     proto_gen::node_service::{
-        CheckPartsRequest, DeletePathsRequest, DeleteRequest, DeleteVersionRequest, DeleteVersionsRequest, DeleteVolumeRequest,
-        DiskInfoRequest, ListDirRequest, ListVolumesRequest, MakeVolumeRequest, MakeVolumesRequest, NsScannerRequest,
-        ReadAllRequest, ReadMultipleRequest, ReadVersionRequest, ReadXlRequest, RenameDataRequest, RenameFileRequest,
-        StatVolumeRequest, UpdateMetadataRequest, VerifyFileRequest, WriteAllRequest, WriteMetadataRequest,
+        CheckPartsRequest, CleanAbandonedDataRequest, DeletePathsRequest, DeleteRequest, DeleteVersionRequest,
+        DeleteVersionsRequest, DeleteVolumeRequest, DiskInfoRequest, FsMountInfoRequest, ListDirRequest, ListVolumesRequest,
+        MakeVolumeRequest, MakeVolumesRequest, NsScannerRequest, PingRequest, ReadAllRequest, ReadMultipleRequest,
+        ReadPartsRequest, ReadVersionRequest, ReadXlRequest, RenameDataRequest, RenameFileRequest, StatInfoFileRequest,
+        StatVolumeRequest, UpdateMetadataRequest, VerifyFileRequest, WriteAllItem, WriteAllMultiRequest, WriteAllRequest,
+        WriteMetadataRequest,
     },
 };
 
 use crate::disk::{
-    CheckPartsResp, DeleteOptions, DiskAPI, DiskInfo, DiskInfoOptions, DiskLocation, DiskOption, FileInfoVersions,
-    ReadMultipleReq, ReadMultipleResp, ReadOptions, RenameDataResp, UpdateMetadataOpts, VolumeInfo, WalkDirOptions,
-    endpoint::Endpoint,
+    CheckPartsResp, DeleteOptions, DiskAPI, DiskInfo, DiskInfoOptions, DiskLocation, DiskOption, FileInfoVersions, MountInfo,
+    ReadMultipleReq, ReadMultipleResp, ReadOptions, RenameDataResp, RenamePartResult, StatInfo, UpdateMetadataOpts, VolumeInfo,
+    WalkDirOptions, endpoint::Endpoint,
 };
 use crate::{
+    disk::current_deadline,
     disk::error::{Error, Result},
     rpc::build_auth_headers,
+    rpc::circuit_breaker::{CircuitBreaker, OpClass},
 };
 use crate::{
     disk::{FileReader, FileWriter},
@@ -46,6 +53,7 @@ use crate::{
 };
 use rustfs_filemeta::{FileInfo, RawFileInfo};
 use rustfs_protos::proto_gen::node_service::RenamePartRequest;
+use rustfs_protos::proto_gen::node_service::node_service_client::NodeServiceClient;
 use rustfs_rio::{HttpReader, HttpWriter};
 use tokio::{
     io::AsyncWrite,
@@ -53,6 +61,8 @@ use tokio::{
 };
 use tokio_stream::{StreamExt, wrappers::ReceiverStream};
 use tonic::Request;
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::Channel;
 use tracing::info;
 use uuid::Uuid;
 
@@ -63,25 +73,105 @@ pub struct RemoteDisk {
     pub url: url::Url,
     pub root: PathBuf,
     endpoint: Endpoint,
+    breaker: Option<CircuitBreaker>,
+}
+
+type NodeClient = NodeServiceClient<
+    InterceptedService<Channel, Box<dyn Fn(Request<()>) -> std::result::Result<Request<()>, tonic::Status> + Send + Sync>>,
+>;
+
+/// Passes the time left until the caller's deadline to the peer as the gRPC timeout,
+/// so it stops working on a read the caller has already given up on.
+fn with_timeout<T>(mut request: Request<T>) -> Request<T> {
+    if let Some(deadline) = current_deadline() {
+        request.set_timeout(deadline.saturating_duration_since(Instant::now()));
+    }
+    request
 }
 
 impl RemoteDisk {
-    pub async fn new(ep: &Endpoint, _opt: &DiskOption) -> Result<Self> {
+    pub async fn new(ep: &Endpoint, opt: &DiskOption) -> Result<Self> {
         // let root = fs::canonicalize(ep.url.path()).await?;
         let root = PathBuf::from(ep.get_file_path());
-        let addr = if let Some(port) = ep.url.port() {
-            format!("{}://{}:{}", ep.url.scheme(), ep.url.host_str().unwrap(), port)
-        } else {
-            format!("{}://{}", ep.url.scheme(), ep.url.host_str().unwrap())
-        };
+        let addr = ep.grid_host();
         Ok(Self {
             id: Mutex::new(None),
             addr,
             url: ep.url.clone(),
             root,
             endpoint: ep.clone(),
+            breaker: opt.circuit_breaker.map(CircuitBreaker::new),
         })
     }
+
+    /// Connects for `op`, failing fast with `CircuitOpen` while the breaker of its class is open.
+    async fn client(&self, op: &'static str) -> Result<NodeClient> {
+        if let Some(breaker) = &self.breaker {
+            if !breaker.allow(OpClass::of(op)) {
+                return Err(Error::CircuitOpen);
+            }
+        }
+
+        node_service_time_out_client(&self.addr).await.map_err(|err| {
+            self.record(op, false);
+            Error::other(format!("can not get client, err: {err}"))
+        })
+    }
+
+    fn record(&self, op: &'static str, success: bool) {
+        if let Some(breaker) = &self.breaker {
+            breaker.record(OpClass::of(op), success);
+        }
+    }
+
+    /// Awaits an RPC, reporting a peer that does not know it as `RemoteNotImplemented`.
+    async fn call<T>(
+        &self,
+        op: &'static str,
+        rpc: impl Future<Output = std::result::Result<tonic::Response<T>, tonic::Status>>,
+    ) -> Result<T> {
+        let res = rpc.await;
+        match &res {
+            // the caller gave up on the call: that says nothing about the peer either way
+            Err(status) if matches!(status.code(), tonic::Code::DeadlineExceeded | tonic::Code::Cancelled) => {}
+            // a peer that answers, even with Unimplemented, is reachable
+            Err(status) => self.record(op, status.code() == tonic::Code::Unimplemented),
+            Ok(_) => self.record(op, true),
+        }
+        match res {
+            Ok(response) => Ok(response.into_inner()),
+            Err(status) if status.code() == tonic::Code::Unimplemented => Err(Error::RemoteNotImplemented {
+                op,
+                peer_version: self.peer_version().await,
+            }),
+            Err(status) => Err(status.into()),
+        }
+    }
+
+    /// Protocol version the peer advertises in its ping handshake.
+    pub async fn peer_version(&self) -> String {
+        let mut client = match node_service_time_out_client(&self.addr).await {
+            Ok(client) => client,
+            Err(_) => return "unknown".to_string(),
+        };
+
+        let mut fbb = flatbuffers::FlatBufferBuilder::new();
+        let payload = fbb.create_vector(b"version");
+        let mut builder = PingBodyBuilder::new(&mut fbb);
+        builder.add_payload(payload);
+        let root = builder.finish();
+        fbb.finish(root, None);
+
+        let request = Request::new(PingRequest {
+            version: NODE_SERVICE_VERSION,
+            body: Bytes::copy_from_slice(fbb.finished_data()),
+        });
+
+        match client.ping(request).await {
+            Ok(response) => response.into_inner().version.to_string(),
+            Err(_) => "unknown".to_string(),
+        }
+    }
 }
 
 // TODO: all api need to handle errors
@@ -165,15 +255,13 @@ impl DiskAPI for RemoteDisk {
     #[tracing::instrument(skip(self))]
     async fn make_volume(&self, volume: &str) -> Result<()> {
         info!("make_volume");
-        let mut client = node_service_time_out_client(&self.addr)
-            .await
-            .map_err(|err| Error::other(format!("can not get client, err: {err}")))?;
+        let mut client = self.client("make_volume").await?;
         let request = Request::new(MakeVolumeRequest {
             disk: self.endpoint.to_string(),
             volume: volume.to_string(),
         });
 
-        let response = client.make_volume(request).await?.into_inner();
+        let response = self.call("make_volume", client.make_volume(request)).await?;
 
         if !response.success {
             return Err(response.error.unwrap_or_default().into());
@@ -185,15 +273,13 @@ impl DiskAPI for RemoteDisk {
     #[tracing::instrument(skip(self))]
     async fn make_volumes(&self, volumes: Vec<&str>) -> Result<()> {
         info!("make_volumes");
-        let mut client = node_service_time_out_client(&self.addr)
-            .await
-            .map_err(|err| Error::other(format!("can not get client, err: {err}")))?;
+        let mut client = self.client("make_volumes").await?;
         let request = Request::new(MakeVolumesRequest {
             disk: self.endpoint.to_string(),
             volumes: volumes.iter().map(|s| (*s).to_string()).collect(),
         });
 
-        let response = client.make_volumes(request).await?.into_inner();
+        let response = self.call("make_volumes", client.make_volumes(request)).await?;
 
         if !response.success {
             return Err(response.error.unwrap_or_default().into());
@@ -205,14 +291,12 @@ impl DiskAPI for RemoteDisk {
     #[tracing::instrument(skip(self))]
     async fn list_volumes(&self) -> Result<Vec<VolumeInfo>> {
         info!("list_volumes");
-        let mut client = node_service_time_out_client(&self.addr)
-            .await
-            .map_err(|err| Error::other(format!("can not get client, err: {err}")))?;
+        let mut client = self.client("list_volumes").await?;
         let request = Request::new(ListVolumesRequest {
             disk: self.endpoint.to_string(),
         });
 
-        let response = client.list_volumes(request).await?.into_inner();
+        let response = self.call("list_volumes", client.list_volumes(request)).await?;
 
         if !response.success {
             return Err(response.error.unwrap_or_default().into());
@@ -230,15 +314,13 @@ impl DiskAPI for RemoteDisk {
     #[tracing::instrument(skip(self))]
     async fn stat_volume(&self, volume: &str) -> Result<VolumeInfo> {
         info!("stat_volume");
-        let mut client = node_service_time_out_client(&self.addr)
-            .await
-            .map_err(|err| Error::other(format!("can not get client, err: {err}")))?;
+        let mut client = self.client("stat_volume").await?;
         let request = Request::new(StatVolumeRequest {
             disk: self.endpoint.to_string(),
             volume: volume.to_string(),
         });
 
-        let response = client.stat_volume(request).await?.into_inner();
+        let response = self.call("stat_volume", client.stat_volume(request)).await?;
 
         if !response.success {
             return Err(response.error.unwrap_or_default().into());
@@ -252,15 +334,13 @@ impl DiskAPI for RemoteDisk {
     #[tracing::instrument(skip(self))]
     async fn delete_volume(&self, volume: &str) -> Result<()> {
         info!("delete_volume {}/{}", self.endpoint.to_string(), volume);
-        let mut client = node_service_time_out_client(&self.addr)
-            .await
-            .map_err(|err| Error::other(format!("can not get client, err: {err}")))?;
+        let mut client = self.client("delete_volume").await?;
         let request = Request::new(DeleteVolumeRequest {
             disk: self.endpoint.to_string(),
             volume: volume.to_string(),
         });
 
-        let response = client.delete_volume(request).await?.into_inner();
+        let response = self.call("delete_volume", client.delete_volume(request)).await?;
 
         if !response.success {
             return Err(response.error.unwrap_or_default().into());
@@ -332,9 +412,7 @@ impl DiskAPI for RemoteDisk {
         let file_info = serde_json::to_string(&fi)?;
         let opts = serde_json::to_string(&opts)?;
 
-        let mut client = node_service_time_out_client(&self.addr)
-            .await
-            .map_err(|err| Error::other(format!("can not get client, err: {err}")))?;
+        let mut client = self.client("delete_version").await?;
         let request = Request::new(DeleteVersionRequest {
             disk: self.endpoint.to_string(),
             volume: volume.to_string(),
@@ -344,7 +422,7 @@ impl DiskAPI for RemoteDisk {
             opts,
         });
 
-        let response = client.delete_version(request).await?.into_inner();
+        let response = self.call("delete_version", client.delete_version(request)).await?;
 
         if !response.success {
             return Err(response.error.unwrap_or_default().into());
@@ -368,9 +446,7 @@ impl DiskAPI for RemoteDisk {
         for file_info_versions in versions.iter() {
             versions_str.push(serde_json::to_string(file_info_versions)?);
         }
-        let mut client = node_service_time_out_client(&self.addr)
-            .await
-            .map_err(|err| Error::other(format!("can not get client, err: {err}")))?;
+        let mut client = self.client("delete_versions").await?;
         let request = Request::new(DeleteVersionsRequest {
             disk: self.endpoint.to_string(),
             volume: volume.to_string(),
@@ -379,7 +455,7 @@ impl DiskAPI for RemoteDisk {
         });
 
         // TODO: use Error not string
-        let response = client.delete_versions(request).await?.into_inner();
+        let response = self.call("delete_versions", client.delete_versions(request)).await?;
         if !response.success {
             return Err(response.error.unwrap_or_default().into());
         }
@@ -402,16 +478,14 @@ impl DiskAPI for RemoteDisk {
     async fn delete_paths(&self, volume: &str, paths: &[String]) -> Result<()> {
         info!("delete_paths");
         let paths = paths.to_owned();
-        let mut client = node_service_time_out_client(&self.addr)
-            .await
-            .map_err(|err| Error::other(format!("can not get client, err: {err}")))?;
+        let mut client = self.client("delete_paths").await?;
         let request = Request::new(DeletePathsRequest {
             disk: self.endpoint.to_string(),
             volume: volume.to_string(),
             paths,
         });
 
-        let response = client.delete_paths(request).await?.into_inner();
+        let response = self.call("delete_paths", client.delete_paths(request)).await?;
 
         if !response.success {
             return Err(response.error.unwrap_or_default().into());
@@ -424,9 +498,7 @@ impl DiskAPI for RemoteDisk {
     async fn write_metadata(&self, _org_volume: &str, volume: &str, path: &str, fi: FileInfo) -> Result<()> {
         info!("write_metadata {}/{}", volume, path);
         let file_info = serde_json::to_string(&fi)?;
-        let mut client = node_service_time_out_client(&self.addr)
-            .await
-            .map_err(|err| Error::other(format!("can not get client, err: {err}")))?;
+        let mut client = self.client("write_metadata").await?;
         let request = Request::new(WriteMetadataRequest {
             disk: self.endpoint.to_string(),
             volume: volume.to_string(),
@@ -434,7 +506,7 @@ impl DiskAPI for RemoteDisk {
             file_info,
         });
 
-        let response = client.write_metadata(request).await?.into_inner();
+        let response = self.call("write_metadata", client.write_metadata(request)).await?;
 
         if !response.success {
             return Err(response.error.unwrap_or_default().into());
@@ -449,9 +521,7 @@ impl DiskAPI for RemoteDisk {
         let file_info = serde_json::to_string(&fi)?;
         let opts = serde_json::to_string(&opts)?;
 
-        let mut client = node_service_time_out_client(&self.addr)
-            .await
-            .map_err(|err| Error::other(format!("can not get client, err: {err}")))?;
+        let mut client = self.client("update_metadata").await?;
         let request = Request::new(UpdateMetadataRequest {
             disk: self.endpoint.to_string(),
             volume: volume.to_string(),
@@ -460,7 +530,7 @@ impl DiskAPI for RemoteDisk {
             opts,
         });
 
-        let response = client.update_metadata(request).await?.into_inner();
+        let response = self.call("update_metadata", client.update_metadata(request)).await?;
 
         if !response.success {
             return Err(response.error.unwrap_or_default().into());
@@ -480,18 +550,16 @@ impl DiskAPI for RemoteDisk {
     ) -> Result<FileInfo> {
         info!("read_version");
         let opts = serde_json::to_string(opts)?;
-        let mut client = node_service_time_out_client(&self.addr)
-            .await
-            .map_err(|err| Error::other(format!("can not get client, err: {err}")))?;
-        let request = Request::new(ReadVersionRequest {
+        let mut client = self.client("read_version").await?;
+        let request = with_timeout(Request::new(ReadVersionRequest {
             disk: self.endpoint.to_string(),
             volume: volume.to_string(),
             path: path.to_string(),
             version_id: version_id.to_string(),
             opts,
-        });
+        }));
 
-        let response = client.read_version(request).await?.into_inner();
+        let response = self.call("read_version", client.read_version(request)).await?;
 
         if !response.success {
             return Err(response.error.unwrap_or_default().into());
@@ -505,17 +573,15 @@ impl DiskAPI for RemoteDisk {
     #[tracing::instrument(level = "debug", skip(self))]
     async fn read_xl(&self, volume: &str, path: &str, read_data: bool) -> Result<RawFileInfo> {
         info!("read_xl {}/{}/{}", self.endpoint.to_string(), volume, path);
-        let mut client = node_service_time_out_client(&self.addr)
-            .await
-            .map_err(|err| Error::other(format!("can not get client, err: {err}")))?;
-        let request = Request::new(ReadXlRequest {
+        let mut client = self.client("read_xl").await?;
+        let request = with_timeout(Request::new(ReadXlRequest {
             disk: self.endpoint.to_string(),
             volume: volume.to_string(),
             path: path.to_string(),
             read_data,
-        });
+        }));
 
-        let response = client.read_xl(request).await?.into_inner();
+        let response = self.call("read_xl", client.read_xl(request)).await?;
 
         if !response.success {
             return Err(response.error.unwrap_or_default().into());
@@ -537,9 +603,7 @@ impl DiskAPI for RemoteDisk {
     ) -> Result<RenameDataResp> {
         info!("rename_data {}/{}/{}/{}", self.addr, self.endpoint.to_string(), dst_volume, dst_path);
         let file_info = serde_json::to_string(&fi)?;
-        let mut client = node_service_time_out_client(&self.addr)
-            .await
-            .map_err(|err| Error::other(format!("can not get client, err: {err}")))?;
+        let mut client = self.client("rename_data").await?;
         let request = Request::new(RenameDataRequest {
             disk: self.endpoint.to_string(),
             src_volume: src_volume.to_string(),
@@ -549,7 +613,7 @@ impl DiskAPI for RemoteDisk {
             dst_path: dst_path.to_string(),
         });
 
-        let response = client.rename_data(request).await?.into_inner();
+        let response = self.call("rename_data", client.rename_data(request)).await?;
 
         if !response.success {
             return Err(response.error.unwrap_or_default().into());
@@ -563,15 +627,14 @@ impl DiskAPI for RemoteDisk {
     #[tracing::instrument(skip(self))]
     async fn list_dir(&self, _origvolume: &str, volume: &str, _dir_path: &str, _count: i32) -> Result<Vec<String>> {
         info!("list_dir {}/{}", volume, _dir_path);
-        let mut client = node_service_time_out_client(&self.addr)
-            .await
-            .map_err(|err| Error::other(format!("can not get client, err: {err}")))?;
+        let mut client = self.client("list_dir").await?;
         let request = Request::new(ListDirRequest {
             disk: self.endpoint.to_string(),
             volume: volume.to_string(),
+            ..Default::default()
         });
 
-        let response = client.list_dir(request).await?.into_inner();
+        let response = self.call("list_dir", client.list_dir(request)).await?;
 
         if !response.success {
             return Err(response.error.unwrap_or_default().into());
@@ -580,6 +643,36 @@ impl DiskAPI for RemoteDisk {
         Ok(response.volumes)
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn list_dir_paged(
+        &self,
+        origvolume: &str,
+        volume: &str,
+        dir_path: &str,
+        after: Option<&str>,
+        count: i32,
+    ) -> Result<(Vec<String>, Option<String>)> {
+        info!("list_dir_paged {}/{}", volume, dir_path);
+        let mut client = self.client("list_dir").await?;
+        let request = Request::new(ListDirRequest {
+            disk: self.endpoint.to_string(),
+            volume: volume.to_string(),
+            paged: true,
+            origvolume: origvolume.to_string(),
+            dir_path: dir_path.to_string(),
+            after: after.map(|s| s.to_string()),
+            count,
+        });
+
+        let response = self.call("list_dir", client.list_dir(request)).await?;
+
+        if !response.success {
+            return Err(response.error.unwrap_or_default().into());
+        }
+
+        Ok((response.volumes, response.next_marker))
+    }
+
     #[tracing::instrument(skip(self, wr))]
     async fn walk_dir<W: AsyncWrite + Unpin + Send>(&self, opts: WalkDirOptions, wr: &mut W) -> Result<()> {
         info!("walk_dir {}", self.endpoint.to_string());
@@ -698,9 +791,7 @@ impl DiskAPI for RemoteDisk {
     #[tracing::instrument(level = "debug", skip(self))]
     async fn rename_file(&self, src_volume: &str, src_path: &str, dst_volume: &str, dst_path: &str) -> Result<()> {
         info!("rename_file");
-        let mut client = node_service_time_out_client(&self.addr)
-            .await
-            .map_err(|err| Error::other(format!("can not get client, err: {err}")))?;
+        let mut client = self.client("rename_file").await?;
         let request = Request::new(RenameFileRequest {
             disk: self.endpoint.to_string(),
             src_volume: src_volume.to_string(),
@@ -709,7 +800,7 @@ impl DiskAPI for RemoteDisk {
             dst_path: dst_path.to_string(),
         });
 
-        let response = client.rename_file(request).await?.into_inner();
+        let response = self.call("rename_file", client.rename_file(request)).await?;
 
         if !response.success {
             return Err(response.error.unwrap_or_default().into());
@@ -719,11 +810,16 @@ impl DiskAPI for RemoteDisk {
     }
 
     #[tracing::instrument(skip(self))]
-    async fn rename_part(&self, src_volume: &str, src_path: &str, dst_volume: &str, dst_path: &str, meta: Bytes) -> Result<()> {
+    async fn rename_part(
+        &self,
+        src_volume: &str,
+        src_path: &str,
+        dst_volume: &str,
+        dst_path: &str,
+        meta: Bytes,
+    ) -> Result<RenamePartResult> {
         info!("rename_part {}/{}", src_volume, src_path);
-        let mut client = node_service_time_out_client(&self.addr)
-            .await
-            .map_err(|err| Error::other(format!("can not get client, err: {err}")))?;
+        let mut client = self.client("rename_part").await?;
         let request = Request::new(RenamePartRequest {
             disk: self.endpoint.to_string(),
             src_volume: src_volume.to_string(),
@@ -733,22 +829,26 @@ impl DiskAPI for RemoteDisk {
             meta,
         });
 
-        let response = client.rename_part(request).await?.into_inner();
+        let response = self.call("rename_part", client.rename_part(request)).await?;
 
         if !response.success {
             return Err(response.error.unwrap_or_default().into());
         }
 
-        Ok(())
+        if response.unchanged {
+            Ok(RenamePartResult::Unchanged)
+        } else if response.superseded {
+            Ok(RenamePartResult::Superseded)
+        } else {
+            Ok(RenamePartResult::Written)
+        }
     }
 
     #[tracing::instrument(skip(self))]
     async fn delete(&self, volume: &str, path: &str, opt: DeleteOptions) -> Result<()> {
         info!("delete {}/{}/{}", self.endpoint.to_string(), volume, path);
         let options = serde_json::to_string(&opt)?;
-        let mut client = node_service_time_out_client(&self.addr)
-            .await
-            .map_err(|err| Error::other(format!("can not get client, err: {err}")))?;
+        let mut client = self.client("delete").await?;
         let request = Request::new(DeleteRequest {
             disk: self.endpoint.to_string(),
             volume: volume.to_string(),
@@ -756,7 +856,7 @@ impl DiskAPI for RemoteDisk {
             options,
         });
 
-        let response = client.delete(request).await?.into_inner();
+        let response = self.call("delete", client.delete(request)).await?;
 
         if !response.success {
             return Err(response.error.unwrap_or_default().into());
@@ -769,9 +869,7 @@ impl DiskAPI for RemoteDisk {
     async fn verify_file(&self, volume: &str, path: &str, fi: &FileInfo) -> Result<CheckPartsResp> {
         info!("verify_file");
         let file_info = serde_json::to_string(&fi)?;
-        let mut client = node_service_time_out_client(&self.addr)
-            .await
-            .map_err(|err| Error::other(format!("can not get client, err: {err}")))?;
+        let mut client = self.client("verify_file").await?;
         let request = Request::new(VerifyFileRequest {
             disk: self.endpoint.to_string(),
             volume: volume.to_string(),
@@ -779,7 +877,7 @@ impl DiskAPI for RemoteDisk {
             file_info,
         });
 
-        let response = client.verify_file(request).await?.into_inner();
+        let response = self.call("verify_file", client.verify_file(request)).await?;
 
         if !response.success {
             return Err(response.error.unwrap_or_default().into());
@@ -794,9 +892,7 @@ impl DiskAPI for RemoteDisk {
     async fn check_parts(&self, volume: &str, path: &str, fi: &FileInfo) -> Result<CheckPartsResp> {
         info!("check_parts");
         let file_info = serde_json::to_string(&fi)?;
-        let mut client = node_service_time_out_client(&self.addr)
-            .await
-            .map_err(|err| Error::other(format!("can not get client, err: {err}")))?;
+        let mut client = self.client("check_parts").await?;
         let request = Request::new(CheckPartsRequest {
             disk: self.endpoint.to_string(),
             volume: volume.to_string(),
@@ -804,7 +900,7 @@ impl DiskAPI for RemoteDisk {
             file_info,
         });
 
-        let response = client.check_parts(request).await?.into_inner();
+        let response = self.call("check_parts", client.check_parts(request)).await?;
 
         if !response.success {
             return Err(response.error.unwrap_or_default().into());
@@ -815,19 +911,78 @@ impl DiskAPI for RemoteDisk {
         Ok(check_parts_resp)
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn stat_info_file(&self, volume: &str, path: &str, glob: bool) -> Result<Vec<StatInfo>> {
+        info!("stat_info_file {}/{}/{}", self.endpoint.to_string(), volume, path);
+        let mut client = self.client("stat_info_file").await?;
+        let request = Request::new(StatInfoFileRequest {
+            disk: self.endpoint.to_string(),
+            volume: volume.to_string(),
+            path: path.to_string(),
+            glob,
+        });
+
+        let response = self.call("stat_info_file", client.stat_info_file(request)).await?;
+
+        if !response.success {
+            return Err(response.error.unwrap_or_default().into());
+        }
+
+        let mut stat_infos = Vec::with_capacity(response.stat_infos.len());
+        for json_str in response.stat_infos.iter() {
+            stat_infos.push(serde_json::from_str::<StatInfo>(json_str)?);
+        }
+
+        Ok(stat_infos)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn read_parts(&self, volume: &str, paths: &[String]) -> Result<Vec<Result<RawFileInfo>>> {
+        info!("read_parts {}/{}", self.endpoint.to_string(), volume);
+        let mut client = self.client("read_parts").await?;
+        let request = with_timeout(Request::new(ReadPartsRequest {
+            disk: self.endpoint.to_string(),
+            volume: volume.to_string(),
+            paths: paths.to_vec(),
+        }));
+
+        let response = self.call("read_parts", client.read_parts(request)).await?;
+
+        if !response.success {
+            return Err(response.error.unwrap_or_default().into());
+        }
+
+        if response.results.len() != paths.len() {
+            return Err(Error::other(format!(
+                "read_parts: expected {} results, got {}",
+                paths.len(),
+                response.results.len()
+            )));
+        }
+
+        let results = response
+            .results
+            .into_iter()
+            .map(|res| match res.error {
+                Some(err) => Err(err.into()),
+                None => Ok(serde_json::from_str::<RawFileInfo>(&res.raw_file_info)?),
+            })
+            .collect();
+
+        Ok(results)
+    }
+
     #[tracing::instrument(skip(self))]
     async fn read_multiple(&self, req: ReadMultipleReq) -> Result<Vec<ReadMultipleResp>> {
         info!("read_multiple {}/{}/{}", self.endpoint.to_string(), req.bucket, req.prefix);
         let read_multiple_req = serde_json::to_string(&req)?;
-        let mut client = node_service_time_out_client(&self.addr)
-            .await
-            .map_err(|err| Error::other(format!("can not get client, err: {err}")))?;
-        let request = Request::new(ReadMultipleRequest {
+        let mut client = self.client("read_multiple").await?;
+        let request = with_timeout(Request::new(ReadMultipleRequest {
             disk: self.endpoint.to_string(),
             read_multiple_req,
-        });
+        }));
 
-        let response = client.read_multiple(request).await?.into_inner();
+        let response = self.call("read_multiple", client.read_multiple(request)).await?;
 
         if !response.success {
             return Err(response.error.unwrap_or_default().into());
@@ -842,12 +997,31 @@ impl DiskAPI for RemoteDisk {
         Ok(read_multiple_resps)
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn clean_abandoned_data(&self, volume: &str, path: &str) -> Result<u64> {
+        info!("clean_abandoned_data {}/{}/{}", self.endpoint.to_string(), volume, path);
+        let mut client = self.client("clean_abandoned_data").await?;
+        let request = Request::new(CleanAbandonedDataRequest {
+            disk: self.endpoint.to_string(),
+            volume: volume.to_string(),
+            path: path.to_string(),
+        });
+
+        let response = self
+            .call("clean_abandoned_data", client.clean_abandoned_data(request))
+            .await?;
+
+        if !response.success {
+            return Err(response.error.unwrap_or_default().into());
+        }
+
+        Ok(response.reclaimed)
+    }
+
     #[tracing::instrument(skip(self))]
     async fn write_all(&self, volume: &str, path: &str, data: Bytes) -> Result<()> {
         info!("write_all");
-        let mut client = node_service_time_out_client(&self.addr)
-            .await
-            .map_err(|err| Error::other(format!("can not get client, err: {err}")))?;
+        let mut client = self.client("write_all").await?;
         let request = Request::new(WriteAllRequest {
             disk: self.endpoint.to_string(),
             volume: volume.to_string(),
@@ -855,7 +1029,7 @@ impl DiskAPI for RemoteDisk {
             data,
         });
 
-        let response = client.write_all(request).await?.into_inner();
+        let response = self.call("write_all", client.write_all(request)).await?;
 
         if !response.success {
             return Err(response.error.unwrap_or_default().into());
@@ -864,19 +1038,42 @@ impl DiskAPI for RemoteDisk {
         Ok(())
     }
 
+    #[tracing::instrument(skip(self, items))]
+    async fn write_all_multi(&self, volume: &str, items: &[(String, Bytes)]) -> Result<Vec<Option<Error>>> {
+        info!("write_all_multi {} items", items.len());
+        let mut client = self.client("write_all_multi").await?;
+        let request = Request::new(WriteAllMultiRequest {
+            disk: self.endpoint.to_string(),
+            volume: volume.to_string(),
+            items: items
+                .iter()
+                .map(|(path, data)| WriteAllItem {
+                    path: path.clone(),
+                    data: data.clone(),
+                })
+                .collect(),
+        });
+
+        let response = self.call("write_all_multi", client.write_all_multi(request)).await?;
+
+        if !response.success {
+            return Err(response.error.unwrap_or_default().into());
+        }
+
+        Ok(response.results.into_iter().map(|r| r.error.map(Error::from)).collect())
+    }
+
     #[tracing::instrument(skip(self))]
     async fn read_all(&self, volume: &str, path: &str) -> Result<Bytes> {
         info!("read_all {}/{}", volume, path);
-        let mut client = node_service_time_out_client(&self.addr)
-            .await
-            .map_err(|err| Error::other(format!("can not get client, err: {err}")))?;
-        let request = Request::new(ReadAllRequest {
+        let mut client = self.client("read_all").await?;
+        let request = with_timeout(Request::new(ReadAllRequest {
             disk: self.endpoint.to_string(),
             volume: volume.to_string(),
             path: path.to_string(),
-        });
+        }));
 
-        let response = client.read_all(request).await?.into_inner();
+        let response = self.call("read_all", client.read_all(request)).await?;
 
         if !response.success {
             return Err(response.error.unwrap_or_default().into());
@@ -888,15 +1085,13 @@ impl DiskAPI for RemoteDisk {
     #[tracing::instrument(skip(self))]
     async fn disk_info(&self, opts: &DiskInfoOptions) -> Result<DiskInfo> {
         let opts = serde_json::to_string(&opts)?;
-        let mut client = node_service_time_out_client(&self.addr)
-            .await
-            .map_err(|err| Error::other(format!("can not get client, err: {err}")))?;
+        let mut client = self.client("disk_info").await?;
         let request = Request::new(DiskInfoRequest {
             disk: self.endpoint.to_string(),
             opts,
         });
 
-        let response = client.disk_info(request).await?.into_inner();
+        let response = self.call("disk_info", client.disk_info(request)).await?;
 
         if !response.success {
             return Err(response.error.unwrap_or_default().into());
@@ -907,6 +1102,22 @@ impl DiskAPI for RemoteDisk {
         Ok(disk_info)
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn fs_mount_info(&self) -> Result<MountInfo> {
+        let mut client = self.client("fs_mount_info").await?;
+        let request = Request::new(FsMountInfoRequest {
+            disk: self.endpoint.to_string(),
+        });
+
+        let response = self.call("fs_mount_info", client.fs_mount_info(request)).await?;
+
+        if !response.success {
+            return Err(response.error.unwrap_or_default().into());
+        }
+
+        Ok(serde_json::from_str::<MountInfo>(&response.mount_info)?)
+    }
+
     #[tracing::instrument(skip(self, cache, scan_mode, _we_sleep))]
     async fn ns_scanner(
         &self,
@@ -917,13 +1128,11 @@ impl DiskAPI for RemoteDisk {
     ) -> Result<DataUsageCache> {
         info!("ns_scanner");
         let cache = serde_json::to_string(cache)?;
-        let mut client = node_service_time_out_client(&self.addr)
-            .await
-            .map_err(|err| Error::other(format!("can not get client, err: {err}")))?;
+        let mut client = self.client("ns_scanner").await?;
 
         let (tx, rx) = mpsc::channel(10);
         let in_stream = ReceiverStream::new(rx);
-        let mut response = client.ns_scanner(in_stream).await?.into_inner();
+        let mut response = self.call("ns_scanner", client.ns_scanner(in_stream)).await?;
         let request = NsScannerRequest {
             disk: self.endpoint.to_string(),
             cache,
@@ -971,11 +1180,18 @@ mod tests {
             pool_idx: 0,
             set_idx: 1,
             disk_idx: 2,
+            grid_host: None,
         };
 
         let disk_option = DiskOption {
             cleanup: false,
             health_check: false,
+            direct_io_threshold: None,
+            read_ahead: None,
+            meta_backup: false,
+            min_free_bytes: 0,
+            min_free_pct: 0,
+            circuit_breaker: None,
         };
 
         let remote_disk = RemoteDisk::new(&endpoint, &disk_option).await.unwrap();
@@ -997,11 +1213,18 @@ mod tests {
             pool_idx: -1,
             set_idx: -1,
             disk_idx: -1,
+            grid_host: None,
         };
 
         let disk_option = DiskOption {
             cleanup: false,
             health_check: false,
+            direct_io_threshold: None,
+            read_ahead: None,
+            meta_backup: false,
+            min_free_bytes: 0,
+            min_free_pct: 0,
+            circuit_breaker: None,
         };
 
         let remote_disk = RemoteDisk::new(&endpoint, &disk_option).await.unwrap();
@@ -1029,11 +1252,18 @@ mod tests {
             pool_idx: 0,
             set_idx: 0,
             disk_idx: 0,
+            grid_host: None,
         };
 
         let disk_option = DiskOption {
             cleanup: false,
             health_check: false,
+            direct_io_threshold: None,
+            read_ahead: None,
+            meta_backup: false,
+            min_free_bytes: 0,
+            min_free_pct: 0,
+            circuit_breaker: None,
         };
 
         let remote_disk = RemoteDisk::new(&endpoint, &disk_option).await.unwrap();
@@ -1052,11 +1282,18 @@ mod tests {
             pool_idx: 0,
             set_idx: 0,
             disk_idx: 0,
+            grid_host: None,
         };
 
         let disk_option = DiskOption {
             cleanup: false,
             health_check: false,
+            direct_io_threshold: None,
+            read_ahead: None,
+            meta_backup: false,
+            min_free_bytes: 0,
+            min_free_pct: 0,
+            circuit_breaker: None,
         };
 
         let remote_disk = RemoteDisk::new(&endpoint, &disk_option).await.unwrap();
@@ -1096,11 +1333,18 @@ mod tests {
                 pool_idx: 0,
                 set_idx: 0,
                 disk_idx: 0,
+                grid_host: None,
             };
 
             let disk_option = DiskOption {
                 cleanup: false,
                 health_check: false,
+                direct_io_threshold: None,
+                read_ahead: None,
+                meta_backup: false,
+                min_free_bytes: 0,
+                min_free_pct: 0,
+                circuit_breaker: None,
             };
 
             let remote_disk = RemoteDisk::new(&endpoint, &disk_option).await.unwrap();
@@ -1122,11 +1366,18 @@ mod tests {
             pool_idx: 0,
             set_idx: 1,
             disk_idx: 2,
+            grid_host: None,
         };
 
         let disk_option = DiskOption {
             cleanup: false,
             health_check: false,
+            direct_io_threshold: None,
+            read_ahead: None,
+            meta_backup: false,
+            min_free_bytes: 0,
+            min_free_pct: 0,
+            circuit_breaker: None,
         };
 
         let remote_disk = RemoteDisk::new(&valid_endpoint, &disk_option).await.unwrap();
@@ -1143,6 +1394,7 @@ mod tests {
             pool_idx: -1,
             set_idx: -1,
             disk_idx: -1,
+            grid_host: None,
         };
 
         let remote_disk_invalid = RemoteDisk::new(&invalid_endpoint, &disk_option).await.unwrap();
@@ -1162,11 +1414,18 @@ mod tests {
             pool_idx: 0,
             set_idx: 0,
             disk_idx: 0,
+            grid_host: None,
         };
 
         let disk_option = DiskOption {
             cleanup: false,
             health_check: false,
+            direct_io_threshold: None,
+            read_ahead: None,
+            meta_backup: false,
+            min_free_bytes: 0,
+            min_free_pct: 0,
+            circuit_breaker: None,
         };
 
         let remote_disk = RemoteDisk::new(&endpoint, &disk_option).await.unwrap();
@@ -1185,6 +1444,7 @@ mod tests {
             pool_idx: 1,
             set_idx: 2,
             disk_idx: 3,
+            grid_host: None,
         };
 
         // Test endpoint method - we can't test this without creating RemoteDisk instance
@@ -1195,4 +1455,161 @@ mod tests {
         assert_eq!(endpoint.set_idx, 2);
         assert_eq!(endpoint.disk_idx, 3);
     }
+
+    /// Serves the real node service but behaves like a peer from before `ReadParts` existed.
+    #[derive(Clone)]
+    struct OlderPeer<S>(S);
+
+    impl<S: tonic::server::NamedService> tonic::server::NamedService for OlderPeer<S> {
+        const NAME: &'static str = S::NAME;
+    }
+
+    struct OlderPing;
+
+    impl tonic::server::UnaryService<PingRequest> for OlderPing {
+        type Response = rustfs_protos::proto_gen::node_service::PingResponse;
+        type Future = std::future::Ready<std::result::Result<tonic::Response<Self::Response>, tonic::Status>>;
+
+        fn call(&mut self, _request: tonic::Request<PingRequest>) -> Self::Future {
+            std::future::ready(Ok(tonic::Response::new(Self::Response {
+                version: 1,
+                body: Bytes::new(),
+            })))
+        }
+    }
+
+    impl<S> tower::Service<http::Request<tonic::body::Body>> for OlderPeer<S>
+    where
+        S: tower::Service<
+                http::Request<tonic::body::Body>,
+                Response = http::Response<tonic::body::Body>,
+                Error = std::convert::Infallible,
+            >,
+        S::Future: Send + 'static,
+    {
+        type Response = http::Response<tonic::body::Body>;
+        type Error = std::convert::Infallible;
+        type Future = std::pin::Pin<Box<dyn Future<Output = std::result::Result<Self::Response, Self::Error>> + Send>>;
+
+        fn poll_ready(&mut self, cx: &mut std::task::Context<'_>) -> std::task::Poll<std::result::Result<(), Self::Error>> {
+            self.0.poll_ready(cx)
+        }
+
+        fn call(&mut self, req: http::Request<tonic::body::Body>) -> Self::Future {
+            match req.uri().path() {
+                "/node_service.NodeService/ReadParts" => {
+                    Box::pin(async { Ok(tonic::Status::unimplemented("unknown method ReadParts").into_http()) })
+                }
+                "/node_service.NodeService/Ping" => Box::pin(async move {
+                    let mut grpc = tonic::server::Grpc::new(tonic::codec::ProstCodec::default());
+                    Ok(grpc.unary(OlderPing, req).await)
+                }),
+                _ => Box::pin(self.0.call(req)),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remote_not_implemented() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let service =
+            rustfs_protos::proto_gen::node_service::node_service_server::NodeServiceServer::new(crate::rpc::make_server());
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(OlderPeer(service))
+                .serve_with_incoming(tonic::transport::server::TcpIncoming::from(listener)),
+        );
+
+        let endpoint = Endpoint {
+            url: url::Url::parse(&format!("http://127.0.0.1:{port}/data")).unwrap(),
+            is_local: false,
+            pool_idx: 0,
+            set_idx: 0,
+            disk_idx: 0,
+            grid_host: None,
+        };
+        let disk_option = DiskOption {
+            cleanup: false,
+            health_check: false,
+            direct_io_threshold: None,
+            read_ahead: None,
+            meta_backup: false,
+            min_free_bytes: 0,
+            min_free_pct: 0,
+            circuit_breaker: None,
+        };
+        let remote_disk = RemoteDisk::new(&endpoint, &disk_option).await.unwrap();
+
+        assert_eq!(remote_disk.peer_version().await, "1");
+
+        match remote_disk.read_parts("bucket", &["object".to_string()]).await {
+            Err(Error::RemoteNotImplemented { op, peer_version }) => {
+                assert_eq!(op, "read_parts");
+                assert_eq!(peer_version, "1");
+            }
+            Err(err) => panic!("expected RemoteNotImplemented, got {err:?}"),
+            Ok(_) => panic!("expected RemoteNotImplemented"),
+        }
+
+        // Ops the peer knows still go through and fail with the peer's own error.
+        let err = remote_disk.read_xl("bucket", "object", false).await.err().unwrap();
+        assert!(!matches!(err, Error::RemoteNotImplemented { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_remote_disk_circuit_breaker() {
+        use crate::rpc::{BreakerState, CircuitBreakerConfig, OpClass};
+        use std::time::Duration;
+
+        let url = url::Url::parse("http://remote-server:9000").unwrap();
+        let endpoint = Endpoint {
+            url,
+            is_local: false,
+            pool_idx: 0,
+            set_idx: 0,
+            disk_idx: 0,
+            grid_host: None,
+        };
+        let disk_option = DiskOption {
+            cleanup: false,
+            health_check: false,
+            direct_io_threshold: None,
+            read_ahead: None,
+            meta_backup: false,
+            min_free_bytes: 0,
+            min_free_pct: 0,
+            circuit_breaker: Some(CircuitBreakerConfig {
+                failure_threshold: 2,
+                cooldown: Duration::from_millis(100),
+            }),
+        };
+        let remote_disk = RemoteDisk::new(&endpoint, &disk_option).await.unwrap();
+        let breaker = remote_disk.breaker.as_ref().unwrap();
+
+        for _ in 0..2 {
+            let res = remote_disk
+                .call("read_all", async { Err::<tonic::Response<()>, _>(tonic::Status::unavailable("down")) })
+                .await;
+            assert!(res.is_err());
+        }
+        assert_eq!(breaker.state(OpClass::Read), BreakerState::Open);
+        assert_eq!(breaker.state(OpClass::Write), BreakerState::Closed);
+
+        // An open breaker fails fast without dialing the peer.
+        assert!(matches!(remote_disk.client("read_xl").await, Err(Error::CircuitOpen)));
+
+        // After the cooldown a single probe is let through.
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert!(breaker.allow(OpClass::Read));
+        assert_eq!(breaker.state(OpClass::Read), BreakerState::HalfOpen);
+        assert!(!breaker.allow(OpClass::Read));
+
+        remote_disk
+            .call("read_all", async { Ok(tonic::Response::new(())) })
+            .await
+            .unwrap();
+        assert_eq!(breaker.state(OpClass::Read), BreakerState::Closed);
+        assert!(breaker.allow(OpClass::Read));
+    }
 }
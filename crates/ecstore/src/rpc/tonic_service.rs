@@ -19,10 +19,11 @@ use crate::{
     admin_server_info::get_local_server_property,
     bucket::{metadata::load_bucket_metadata, metadata_sys},
     disk::{
-        DeleteOptions, DiskAPI, DiskInfoOptions, DiskStore, FileInfoVersions, ReadMultipleReq, ReadOptions, UpdateMetadataOpts,
-        error::DiskError,
+        DeleteOptions, DiskAPI, DiskInfoOptions, DiskStore, FileInfoVersions, ReadMultipleReq, ReadOptions, RenamePartResult,
+        UpdateMetadataOpts, error::DiskError,
     },
     heal::{
+        data_scanner::get_scanner_throttle,
         data_usage_cache::DataUsageCache,
         heal_commands::{HealOpts, get_local_background_heal_status},
     },
@@ -46,6 +47,7 @@ use rustfs_madmin::health::{
 };
 use rustfs_madmin::net::get_net_info;
 use rustfs_protos::{
+    NODE_SERVICE_VERSION,
     models::{PingBody, PingBodyBuilder},
     proto_gen::node_service::{node_service_server::NodeService as Node, *},
 };
@@ -122,7 +124,7 @@ impl Node for NodeService {
         let finished_data = fbb.finished_data();
 
         Ok(tonic::Response::new(PingResponse {
-            version: 1,
+            version: NODE_SERVICE_VERSION,
             body: Bytes::copy_from_slice(finished_data),
         }))
     }
@@ -329,6 +331,36 @@ impl Node for NodeService {
         }
     }
 
+    async fn write_all_multi(&self, request: Request<WriteAllMultiRequest>) -> Result<Response<WriteAllMultiResponse>, Status> {
+        let request = request.into_inner();
+        if let Some(disk) = self.find_disk(&request.disk).await {
+            let items: Vec<(String, Bytes)> = request.items.into_iter().map(|item| (item.path, item.data)).collect();
+            match disk.write_all_multi(&request.volume, &items).await {
+                Ok(errs) => Ok(tonic::Response::new(WriteAllMultiResponse {
+                    success: true,
+                    results: errs
+                        .into_iter()
+                        .map(|err| WriteAllResult {
+                            error: err.map(Into::into),
+                        })
+                        .collect(),
+                    error: None,
+                })),
+                Err(err) => Ok(tonic::Response::new(WriteAllMultiResponse {
+                    success: false,
+                    results: Vec::new(),
+                    error: Some(err.into()),
+                })),
+            }
+        } else {
+            Ok(tonic::Response::new(WriteAllMultiResponse {
+                success: false,
+                results: Vec::new(),
+                error: Some(DiskError::other("can not find disk".to_string()).into()),
+            }))
+        }
+    }
+
     async fn delete(&self, request: Request<DeleteRequest>) -> Result<Response<DeleteResponse>, Status> {
         let request = request.into_inner();
         if let Some(disk) = self.find_disk(&request.disk).await {
@@ -464,19 +496,25 @@ impl Node for NodeService {
                 )
                 .await
             {
-                Ok(_) => Ok(tonic::Response::new(RenamePartResponse {
+                Ok(result) => Ok(tonic::Response::new(RenamePartResponse {
                     success: true,
                     error: None,
+                    unchanged: result == RenamePartResult::Unchanged,
+                    superseded: result == RenamePartResult::Superseded,
                 })),
                 Err(err) => Ok(tonic::Response::new(RenamePartResponse {
                     success: false,
                     error: Some(err.into()),
+                    unchanged: false,
+                    superseded: false,
                 })),
             }
         } else {
             Ok(tonic::Response::new(RenamePartResponse {
                 success: false,
                 error: Some(DiskError::other("can not find disk".to_string()).into()),
+                unchanged: false,
+                superseded: false,
             }))
         }
     }
@@ -749,16 +787,30 @@ impl Node for NodeService {
     async fn list_dir(&self, request: Request<ListDirRequest>) -> Result<Response<ListDirResponse>, Status> {
         let request = request.into_inner();
         if let Some(disk) = self.find_disk(&request.disk).await {
-            match disk.list_dir("", &request.volume, "", 0).await {
-                Ok(volumes) => Ok(tonic::Response::new(ListDirResponse {
+            let res = if request.paged {
+                disk.list_dir_paged(
+                    &request.origvolume,
+                    &request.volume,
+                    &request.dir_path,
+                    request.after.as_deref(),
+                    request.count,
+                )
+                .await
+            } else {
+                disk.list_dir("", &request.volume, "", 0).await.map(|volumes| (volumes, None))
+            };
+            match res {
+                Ok((volumes, next_marker)) => Ok(tonic::Response::new(ListDirResponse {
                     success: true,
                     volumes,
                     error: None,
+                    next_marker,
                 })),
                 Err(err) => Ok(tonic::Response::new(ListDirResponse {
                     success: false,
                     volumes: Vec::new(),
                     error: Some(err.into()),
+                    next_marker: None,
                 })),
             }
         } else {
@@ -766,6 +818,7 @@ impl Node for NodeService {
                 success: false,
                 volumes: Vec::new(),
                 error: Some(DiskError::other("can not find disk".to_string()).into()),
+                next_marker: None,
             }))
         }
     }
@@ -1185,6 +1238,84 @@ impl Node for NodeService {
         }
     }
 
+    async fn stat_info_file(&self, request: Request<StatInfoFileRequest>) -> Result<Response<StatInfoFileResponse>, Status> {
+        let request = request.into_inner();
+        if let Some(disk) = self.find_disk(&request.disk).await {
+            match disk.stat_info_file(&request.volume, &request.path, request.glob).await {
+                Ok(stat_infos) => {
+                    let mut encoded = Vec::with_capacity(stat_infos.len());
+                    for stat_info in stat_infos.iter() {
+                        match serde_json::to_string(stat_info) {
+                            Ok(json_str) => encoded.push(json_str),
+                            Err(err) => {
+                                return Ok(tonic::Response::new(StatInfoFileResponse {
+                                    success: false,
+                                    stat_infos: Vec::new(),
+                                    error: Some(DiskError::other(format!("encode data failed: {err}")).into()),
+                                }));
+                            }
+                        }
+                    }
+                    Ok(tonic::Response::new(StatInfoFileResponse {
+                        success: true,
+                        stat_infos: encoded,
+                        error: None,
+                    }))
+                }
+                Err(err) => Ok(tonic::Response::new(StatInfoFileResponse {
+                    success: false,
+                    stat_infos: Vec::new(),
+                    error: Some(err.into()),
+                })),
+            }
+        } else {
+            Ok(tonic::Response::new(StatInfoFileResponse {
+                success: false,
+                stat_infos: Vec::new(),
+                error: Some(DiskError::other("can not find disk".to_string()).into()),
+            }))
+        }
+    }
+
+    async fn read_parts(&self, request: Request<ReadPartsRequest>) -> Result<Response<ReadPartsResponse>, Status> {
+        let request = request.into_inner();
+        if let Some(disk) = self.find_disk(&request.disk).await {
+            match disk.read_parts(&request.volume, &request.paths).await {
+                Ok(parts) => {
+                    let results = parts
+                        .into_iter()
+                        .map(|part| match part.and_then(|raw_file_info| Ok(serde_json::to_string(&raw_file_info)?)) {
+                            Ok(raw_file_info) => ReadPartsResult {
+                                raw_file_info,
+                                error: None,
+                            },
+                            Err(err) => ReadPartsResult {
+                                raw_file_info: String::new(),
+                                error: Some(err.into()),
+                            },
+                        })
+                        .collect();
+                    Ok(tonic::Response::new(ReadPartsResponse {
+                        success: true,
+                        results,
+                        error: None,
+                    }))
+                }
+                Err(err) => Ok(tonic::Response::new(ReadPartsResponse {
+                    success: false,
+                    results: Vec::new(),
+                    error: Some(err.into()),
+                })),
+            }
+        } else {
+            Ok(tonic::Response::new(ReadPartsResponse {
+                success: false,
+                results: Vec::new(),
+                error: Some(DiskError::other("can not find disk".to_string()).into()),
+            }))
+        }
+    }
+
     async fn delete_version(&self, request: Request<DeleteVersionRequest>) -> Result<Response<DeleteVersionResponse>, Status> {
         let request = request.into_inner();
         if let Some(disk) = self.find_disk(&request.disk).await {
@@ -1337,6 +1468,33 @@ impl Node for NodeService {
         }
     }
 
+    async fn clean_abandoned_data(
+        &self,
+        request: Request<CleanAbandonedDataRequest>,
+    ) -> Result<Response<CleanAbandonedDataResponse>, Status> {
+        let request = request.into_inner();
+        if let Some(disk) = self.find_disk(&request.disk).await {
+            match disk.clean_abandoned_data(&request.volume, &request.path).await {
+                Ok(reclaimed) => Ok(tonic::Response::new(CleanAbandonedDataResponse {
+                    success: true,
+                    reclaimed,
+                    error: None,
+                })),
+                Err(err) => Ok(tonic::Response::new(CleanAbandonedDataResponse {
+                    success: false,
+                    reclaimed: 0,
+                    error: Some(err.into()),
+                })),
+            }
+        } else {
+            Ok(tonic::Response::new(CleanAbandonedDataResponse {
+                success: false,
+                reclaimed: 0,
+                error: Some(DiskError::other("can not find disk".to_string()).into()),
+            }))
+        }
+    }
+
     async fn delete_volume(&self, request: Request<DeleteVolumeRequest>) -> Result<Response<DeleteVolumeResponse>, Status> {
         let request = request.into_inner();
         if let Some(disk) = self.find_disk(&request.disk).await {
@@ -1399,6 +1557,37 @@ impl Node for NodeService {
         }
     }
 
+    async fn fs_mount_info(&self, request: Request<FsMountInfoRequest>) -> Result<Response<FsMountInfoResponse>, Status> {
+        let request = request.into_inner();
+        if let Some(disk) = self.find_disk(&request.disk).await {
+            match disk.fs_mount_info().await {
+                Ok(mount_info) => match serde_json::to_string(&mount_info) {
+                    Ok(mount_info) => Ok(tonic::Response::new(FsMountInfoResponse {
+                        success: true,
+                        mount_info,
+                        error: None,
+                    })),
+                    Err(err) => Ok(tonic::Response::new(FsMountInfoResponse {
+                        success: false,
+                        mount_info: "".to_string(),
+                        error: Some(DiskError::other(format!("encode data failed: {err}")).into()),
+                    })),
+                },
+                Err(err) => Ok(tonic::Response::new(FsMountInfoResponse {
+                    success: false,
+                    mount_info: "".to_string(),
+                    error: Some(err.into()),
+                })),
+            }
+        } else {
+            Ok(tonic::Response::new(FsMountInfoResponse {
+                success: false,
+                mount_info: "".to_string(),
+                error: Some(DiskError::other("can not find disk".to_string()).into()),
+            }))
+        }
+    }
+
     type NsScannerStream = ResponseStream<NsScannerResponse>;
     async fn ns_scanner(&self, request: Request<Streaming<NsScannerRequest>>) -> Result<Response<Self::NsScannerStream>, Status> {
         info!("ns_scanner");
@@ -1445,7 +1634,9 @@ impl Node for NodeService {
                                 }
                             }
                         });
-                        let data_usage_cache = disk.ns_scanner(&cache, updates_tx, request.scan_mode as usize, None).await;
+                        let data_usage_cache = disk
+                            .ns_scanner(&cache, updates_tx, request.scan_mode as usize, get_scanner_throttle().should_sleep_fn())
+                            .await;
                         let _ = task.await;
                         match data_usage_cache {
                             Ok(data_usage_cache) => {
@@ -2293,7 +2484,7 @@ mod tests {
         assert!(response.is_ok());
 
         let ping_response = response.unwrap().into_inner();
-        assert_eq!(ping_response.version, 1);
+        assert_eq!(ping_response.version, NODE_SERVICE_VERSION);
         assert!(!ping_response.body.is_empty());
     }
 
@@ -2310,7 +2501,7 @@ mod tests {
         assert!(response.is_ok()); // Should still succeed but log error
 
         let ping_response = response.unwrap().into_inner();
-        assert_eq!(ping_response.version, 1);
+        assert_eq!(ping_response.version, NODE_SERVICE_VERSION);
         assert!(!ping_response.body.is_empty());
     }
 
@@ -2581,6 +2772,7 @@ mod tests {
         let request = Request::new(ListDirRequest {
             disk: "invalid-disk-path".to_string(),
             volume: "test-volume".to_string(),
+            ..Default::default()
         });
 
         let response = service.list_dir(request).await;
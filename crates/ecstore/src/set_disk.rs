@@ -16,31 +16,39 @@
 
 use crate::bitrot::{create_bitrot_reader, create_bitrot_writer};
 use crate::bucket::lifecycle::lifecycle::TRANSITION_COMPLETE;
+use crate::bucket::metadata_sys;
+use crate::bucket::object_lock::objectlock_sys::{check_put_against_lock, clear_retention_meta};
+use crate::bucket::utils::is_meta_bucketname;
+use crate::checksum::{ChecksumHasher, ChecksumMode};
 use crate::client::{object_api_utils::extract_etag, transition_api::ReaderImpl};
 use crate::disk::error_reduce::{OBJECT_OP_IGNORED_ERRS, reduce_read_quorum_errs, reduce_write_quorum_errs};
 use crate::disk::{
     self, CHECK_PART_DISK_NOT_FOUND, CHECK_PART_FILE_CORRUPT, CHECK_PART_FILE_NOT_FOUND, CHECK_PART_SUCCESS,
-    conv_part_err_to_int, has_part_err,
+    conv_part_err_to_int, has_part_err, normalize_versions, with_deadline,
 };
 use crate::erasure_coding;
 use crate::erasure_coding::bitrot_verify;
 use crate::error::ObjectApiError;
 use crate::error::{Error, Result};
 use crate::global::GLOBAL_MRFState;
+use crate::global::get_global_max_object_size;
 use crate::global::{GLOBAL_LocalNodeName, GLOBAL_TierConfigMgr};
 use crate::heal::data_usage_cache::DataUsageCache;
 use crate::heal::heal_ops::{HealEntryFn, HealSequence};
+use crate::kms;
+use crate::sets::close_storage_disks;
+use crate::store::delete_prefix_batch;
 use crate::store_api::ObjectToDelete;
 use crate::{
     bucket::lifecycle::bucket_lifecycle_ops::{gen_transition_objname, get_transitioned_object_reader, put_restore_opts},
     cache_value::metacache_set::{ListPathRawOptions, list_path_raw},
     config::{GLOBAL_StorageClass, storageclass},
     disk::{
-        CheckPartsResp, DeleteOptions, DiskAPI, DiskInfo, DiskInfoOptions, DiskOption, DiskStore, FileInfoVersions,
+        CheckPartsResp, DeleteOptions, Disk, DiskAPI, DiskInfo, DiskInfoOptions, DiskOption, DiskStore, FileInfoVersions,
         RUSTFS_META_BUCKET, RUSTFS_META_MULTIPART_BUCKET, RUSTFS_META_TMP_BUCKET, ReadMultipleReq, ReadMultipleResp, ReadOptions,
-        UpdateMetadataOpts, endpoint::Endpoint, error::DiskError, format::FormatV3, new_disk,
+        RenamePartResult, UpdateMetadataOpts, endpoint::Endpoint, error::DiskError, format::FormatV3, new_disk,
     },
-    error::{StorageError, to_object_err},
+    error::{StorageError, is_err_object_not_found, is_err_version_not_found, to_object_err},
     event::name::EventName,
     event_notification::{EventArgs, send_event},
     global::{
@@ -57,15 +65,18 @@ use crate::{
         heal_ops::BG_HEALING_UUID,
     },
     store_api::{
-        BucketInfo, BucketOptions, CompletePart, DeleteBucketOptions, DeletedObject, GetObjectReader, HTTPRangeSpec,
-        ListMultipartsInfo, ListObjectsV2Info, MakeBucketOptions, MultipartInfo, MultipartUploadResult, ObjectIO, ObjectInfo,
-        ObjectOptions, PartInfo, PutObjReader, StorageAPI,
+        BucketInfo, BucketOptions, ClusterHealth, CompletePart, DeleteBucketOptions, DeletePrefixReport, DeletedObject,
+        DiskInspectInfo, DiskMetaView, GetObjectReader, HTTPRangeSpec, ListMultipartsInfo, ListObjectsV2Info, MakeBucketOptions,
+        MetaConsistencyReport, MultipartInfo, MultipartUploadResult, ObjectIO, ObjectInfo, ObjectInspectReport, ObjectOptions,
+        PartInfo, PoolHealth, PutObjReader, ReplicationStats, StorageAPI, check_if_match, copy_object_range,
     },
     store_init::load_format_erasure,
 };
 use crate::{disk::STORAGE_FORMAT_FILE, heal::mrf::PartialOperation};
 use crate::{
-    heal::data_scanner::{HEAL_DELETE_DANGLING, globalHealConfig},
+    heal::data_scanner::{
+        HEAL_DELETE_DANGLING, ScannerThrottle, get_scanner_cold_bucket_threshold, get_scanner_throttle, globalHealConfig,
+    },
     store_api::ListObjectVersionsInfo,
 };
 use bytes::Bytes;
@@ -78,12 +89,15 @@ use md5::{Digest as Md5Digest, Md5};
 use rand::{Rng, seq::SliceRandom};
 use rustfs_filemeta::headers::RESERVED_METADATA_PREFIX_LOWER;
 use rustfs_filemeta::{
-    FileInfo, FileMeta, FileMetaShallowVersion, MetaCacheEntries, MetaCacheEntry, MetadataResolutionParams, ObjectPartInfo,
-    RawFileInfo, file_info_from_raw,
+    FileInfo, FileInfoOpts, FileMeta, FileMetaShallowVersion, MetaCacheEntries, MetaCacheEntry, MetadataResolutionParams,
+    ObjectPartInfo, RawFileInfo, file_info_from_raw, get_file_info,
     headers::{AMZ_OBJECT_TAGGING, AMZ_STORAGE_CLASS},
     merge_file_meta_versions,
 };
-use rustfs_lock::{LockApi, namespace_lock::NsLockMap};
+use rustfs_lock::{
+    LockApi,
+    namespace_lock::{LockGuard, NamespaceLock, NsLockMap},
+};
 use rustfs_madmin::heal_commands::{HealDriveInfo, HealResultItem};
 use rustfs_rio::{EtagResolvable, HashReader, TryGetIndex as _, WarpReader};
 use rustfs_utils::{
@@ -92,13 +106,15 @@ use rustfs_utils::{
     path::{SLASH_SEPARATOR, encode_dir_object, has_suffix, path_join_buf},
 };
 use rustfs_workers::workers::Workers;
-use s3s::header::X_AMZ_RESTORE;
+use s3s::header::{X_AMZ_OBJECT_LOCK_MODE, X_AMZ_OBJECT_LOCK_RETAIN_UNTIL_DATE, X_AMZ_RESTORE};
 use sha2::{Digest, Sha256};
 use std::hash::Hash;
 use std::mem::{self};
+use std::pin::Pin;
+use std::task::{Context, Poll};
 use std::time::SystemTime;
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeSet, HashMap, HashSet},
     io::{Cursor, Write},
     path::Path,
     sync::Arc,
@@ -106,7 +122,7 @@ use std::{
 };
 use time::OffsetDateTime;
 use tokio::{
-    io::AsyncWrite,
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt, ReadBuf},
     sync::{RwLock, broadcast},
 };
 use tokio::{
@@ -120,11 +136,15 @@ use uuid::Uuid;
 
 pub const DEFAULT_READ_BUFFER_SIZE: usize = 1024 * 1024;
 
+// How long a reader or a writer waits for the data lock of an object, see lock_object_data.
+const DATA_LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+
 #[derive(Debug, Clone)]
 pub struct SetDisks {
     pub lockers: Vec<LockApi>,
     pub locker_owner: String,
     pub ns_mutex: Arc<RwLock<NsLockMap>>,
+    pub ns_lock: Arc<dyn NamespaceLock>,
     pub disks: Arc<RwLock<Vec<Option<DiskStore>>>>,
     pub set_endpoints: Vec<Endpoint>,
     pub set_drive_count: usize,
@@ -140,6 +160,7 @@ impl SetDisks {
         lockers: Vec<LockApi>,
         locker_owner: String,
         ns_mutex: Arc<RwLock<NsLockMap>>,
+        ns_lock: Arc<dyn NamespaceLock>,
         disks: Arc<RwLock<Vec<Option<DiskStore>>>>,
         set_drive_count: usize,
         default_parity_count: usize,
@@ -152,6 +173,7 @@ impl SetDisks {
             lockers,
             locker_owner,
             ns_mutex,
+            ns_lock,
             disks,
             set_drive_count,
             default_parity_count,
@@ -161,6 +183,20 @@ impl SetDisks {
             set_endpoints,
         })
     }
+    // Readers hold the data lock of an object shared while they stream its parts, writers hold it
+    // exclusive while they swap its xl.meta and remove the data dir it replaced, so no read is cut
+    // off mid-stream. It is scoped to the set and kept apart from the object lock ECStore takes
+    // around whole writes, which a write holding that must still be able to take.
+    async fn lock_object_data(&self, bucket: &str, object: &str, shared: bool) -> Result<LockGuard> {
+        let resource = format!("{}#{}.{}", path_join_buf(&[bucket, object]), self.pool_index, self.set_index);
+        let guard = if shared {
+            self.ns_lock.rlock(&[resource], DATA_LOCK_TIMEOUT).await?
+        } else {
+            self.ns_lock.lock(&[resource], DATA_LOCK_TIMEOUT).await?
+        };
+        Ok(guard)
+    }
+
     async fn get_disks_internal(&self) -> Vec<Option<DiskStore>> {
         let rl = self.disks.read().await;
 
@@ -204,6 +240,11 @@ impl SetDisks {
 
         new_disk
     }
+
+    pub async fn online_disk_count(&self) -> usize {
+        self.get_online_disks().await.len()
+    }
+
     async fn get_online_local_disks(&self) -> Vec<Option<DiskStore>> {
         let mut disks = self.get_online_disks().await;
 
@@ -316,6 +357,54 @@ impl SetDisks {
             .filter(|v| v.as_ref().is_some_and(|d| d.is_local()))
             .collect()
     }
+    // Reads the part.N.meta files of an upload, sorted by part number.
+    async fn list_uploaded_parts(&self, bucket: &str, object: &str, upload_id: &str, fi: &FileInfo) -> Vec<PartInfo> {
+        let upload_id_path = Self::get_upload_id_dir(bucket, object, upload_id);
+        let part_path = format!("{}/{}/", upload_id_path, fi.data_dir.unwrap_or(Uuid::nil()));
+        let disks = self.disks.read().await.clone();
+
+        let mut part_nums = BTreeSet::new();
+        for disk in disks.iter().flatten() {
+            if let Ok(entries) = disk.list_dir("", RUSTFS_META_MULTIPART_BUCKET, &part_path, -1).await {
+                part_nums.extend(
+                    entries
+                        .iter()
+                        .filter_map(|name| name.strip_prefix("part.")?.strip_suffix(".meta")?.parse::<usize>().ok()),
+                );
+            }
+        }
+
+        if part_nums.is_empty() {
+            return Vec::new();
+        }
+
+        let req = ReadMultipleReq {
+            bucket: RUSTFS_META_MULTIPART_BUCKET.to_string(),
+            prefix: part_path,
+            files: part_nums.iter().map(|n| format!("part.{n}.meta")).collect(),
+            max_size: 1 << 20,
+            metadata_only: true,
+            abort404: false,
+            max_results: 0,
+        };
+
+        let read_quorum = fi.read_quorum(self.set_drive_count - self.default_parity_count);
+        Self::read_multiple_files(&disks, req, read_quorum)
+            .await
+            .iter()
+            .filter(|res| res.error.is_empty() && res.exists)
+            .filter_map(|res| FileInfo::unmarshal(&res.data).ok())
+            .filter_map(|part_fi| part_fi.parts.first().cloned())
+            .map(|part| PartInfo {
+                part_num: part.number,
+                last_mod: part.mod_time,
+                size: part.size,
+                etag: Some(part.etag),
+                actual_size: part.actual_size,
+            })
+            .collect()
+    }
+
     fn default_write_quorum(&self) -> usize {
         let mut data_count = self.set_drive_count - self.default_parity_count;
         if data_count == self.default_parity_count {
@@ -470,6 +559,8 @@ impl SetDisks {
         if max >= write_quorum { data_dir } else { None }
     }
 
+    // Removes the data dir an overwrite replaced, the caller holds the data lock of the object so
+    // no reader is still streaming from it.
     #[allow(dead_code)]
     #[tracing::instrument(level = "debug", skip(self, disks))]
     async fn commit_rename_data_dir(
@@ -559,7 +650,7 @@ impl SetDisks {
         dst_object: &str,
         meta: Bytes,
         write_quorum: usize,
-    ) -> disk::error::Result<Vec<Option<DiskStore>>> {
+    ) -> disk::error::Result<(Vec<Option<DiskStore>>, bool)> {
         let src_bucket = Arc::new(src_bucket.to_string());
         let src_object = Arc::new(src_object.to_string());
         let dst_bucket = Arc::new(dst_bucket.to_string());
@@ -584,10 +675,15 @@ impl SetDisks {
             })
         });
 
+        // Drives settle concurrent uploads of a part the same way, so one of them keeping
+        // a later upload means that upload is the part on every drive.
+        let mut superseded = false;
+
         let results = join_all(futures).await;
         for result in results {
             match result? {
-                Ok(_) => {
+                Ok(res) => {
+                    superseded |= res == RenamePartResult::Superseded;
                     errs.push(None);
                 }
                 Err(e) => {
@@ -603,7 +699,7 @@ impl SetDisks {
         }
 
         let disks = Self::eval_disks(disks, &errs);
-        Ok(disks)
+        Ok((disks, superseded))
     }
 
     fn eval_disks(disks: &[Option<DiskStore>], errs: &[Option<DiskError>]) -> Vec<Option<DiskStore>> {
@@ -1126,7 +1222,7 @@ impl SetDisks {
                         }
 
                         let props = ObjProps {
-                            mod_time: metas[i].mod_time,
+                            successor_mod_time: metas[i].successor_mod_time,
                             num_versions: metas[i].num_versions,
                         };
 
@@ -1141,9 +1237,9 @@ impl SetDisks {
 
             for (val, &count) in &valid_obj_map {
                 if count > quorum {
-                    fi.mod_time = val.mod_time;
+                    fi.successor_mod_time = val.successor_mod_time;
                     fi.num_versions = val.num_versions;
-                    fi.is_latest = val.mod_time.is_none();
+                    fi.is_latest = val.successor_mod_time.is_none();
 
                     break;
                 }
@@ -1467,6 +1563,18 @@ impl SetDisks {
         }
     }
 
+    /// Drops the drive at `ep` from the set, connect_disks renews it once it is reachable again.
+    pub async fn take_disk_offline(&self, ep: &Endpoint) {
+        let Some(idx) = self.set_endpoints.iter().position(|e| e == ep) else {
+            return;
+        };
+
+        let disk = self.disks.write().await[idx].take();
+        if let Some(disk) = disk {
+            let _ = disk.close().await;
+        }
+    }
+
     pub async fn renew_disk(&self, ep: &Endpoint) {
         debug!("renew_disk start {:?}", ep);
 
@@ -1482,6 +1590,17 @@ impl SetDisks {
             }
         };
 
+        // a drive that can't take writes or sits in the wrong slot stays out of the set
+        if let Disk::Local(local_disk) = new_disk.as_ref() {
+            if let Err(err) = local_disk.preflight().await {
+                warn!("preflight of {:?} failed, not adding it to the set: {}", ep, err);
+                GLOBAL_BackgroundHealState
+                    .push_heal_local_disks(std::slice::from_ref(ep))
+                    .await;
+                return;
+            }
+        }
+
         if new_disk.is_local() {
             if let Some(h) = new_disk.healing().await {
                 if !h.finished {
@@ -1834,9 +1953,24 @@ impl SetDisks {
         // warn!("get_object_fileinfo parts_metadata {:?}", &parts_metadata);
         // warn!("get_object_fileinfo {}/{} errs {:?}", bucket, object, &errs);
 
+        let (fi, op_online_disks) = self.quorum_fileinfo(&disks, bucket, object, &parts_metadata, &errs).await?;
+
+        Ok((fi, parts_metadata, op_online_disks))
+    }
+
+    // Settles one object's metadata read from every drive, queuing it for heal when
+    // some drives disagree.
+    async fn quorum_fileinfo(
+        &self,
+        disks: &[Option<DiskStore>],
+        bucket: &str,
+        object: &str,
+        parts_metadata: &[FileInfo],
+        errs: &[Option<DiskError>],
+    ) -> Result<(FileInfo, Vec<Option<DiskStore>>)> {
         let _min_disks = self.set_drive_count - self.default_parity_count;
 
-        let (read_quorum, _) = match Self::object_quorum_from_meta(&parts_metadata, &errs, self.default_parity_count)
+        let (read_quorum, _) = match Self::object_quorum_from_meta(parts_metadata, errs, self.default_parity_count)
             .map_err(|err| to_object_err(err.into(), vec![bucket, object]))
         {
             Ok(v) => v,
@@ -1846,14 +1980,14 @@ impl SetDisks {
             }
         };
 
-        if let Some(err) = reduce_read_quorum_errs(&errs, OBJECT_OP_IGNORED_ERRS, read_quorum as usize) {
+        if let Some(err) = reduce_read_quorum_errs(errs, OBJECT_OP_IGNORED_ERRS, read_quorum as usize) {
             error!("reduce_read_quorum_errs: {:?}, bucket: {}, object: {}", &err, bucket, object);
             return Err(to_object_err(err.into(), vec![bucket, object]));
         }
 
-        let (op_online_disks, mot_time, etag) = Self::list_online_disks(&disks, &parts_metadata, &errs, read_quorum as usize);
+        let (op_online_disks, mot_time, etag) = Self::list_online_disks(disks, parts_metadata, errs, read_quorum as usize);
 
-        let fi = Self::pick_valid_fileinfo(&parts_metadata, mot_time, etag, read_quorum as usize)?;
+        let fi = Self::pick_valid_fileinfo(parts_metadata, mot_time, etag, read_quorum as usize)?;
         if errs.iter().any(|err| err.is_some()) {
             GLOBAL_MRFState
                 .add_partial(PartialOperation {
@@ -1871,7 +2005,74 @@ impl SetDisks {
 
         // let online_disks: Vec<Option<DiskStore>> = op_online_disks.iter().filter(|v| v.is_some()).cloned().collect();
 
-        Ok((fi, parts_metadata, op_online_disks))
+        Ok((fi, op_online_disks))
+    }
+
+    // Reads the xl.meta of every object with a single read_multiple per drive, giving for
+    // each object what read_all_fileinfo would have.
+    async fn read_all_fileinfos(
+        disks: &[Option<DiskStore>],
+        bucket: &str,
+        objects: &[String],
+        version_id: &str,
+    ) -> Vec<(Vec<FileInfo>, Vec<Option<DiskError>>)> {
+        let req = ReadMultipleReq {
+            bucket: bucket.to_string(),
+            prefix: String::new(),
+            files: objects
+                .iter()
+                .map(|object| format!("{object}{SLASH_SEPARATOR}{STORAGE_FORMAT_FILE}"))
+                .collect(),
+            max_size: 0,
+            metadata_only: true,
+            abort404: false,
+            max_results: 0,
+        };
+
+        let futures = disks.iter().map(|disk| {
+            let req = req.clone();
+            async move {
+                match disk {
+                    Some(disk) => disk.read_multiple(req).await,
+                    None => Err(DiskError::DiskNotFound),
+                }
+            }
+        });
+        let results = join_all(futures).await;
+
+        let mut metas: Vec<(Vec<FileInfo>, Vec<Option<DiskError>>)> = objects
+            .iter()
+            .map(|_| (Vec::with_capacity(disks.len()), Vec::with_capacity(disks.len())))
+            .collect();
+        for result in results {
+            let mut resps = result.map(|resps| resps.into_iter());
+            for (object, (parts_metadata, errs)) in objects.iter().zip(metas.iter_mut()) {
+                let fi = match resps.as_mut() {
+                    Ok(resps) => match resps.next() {
+                        Some(resp) if !resp.exists => Err(DiskError::FileNotFound),
+                        Some(resp) if !resp.error.is_empty() => Err(DiskError::other(resp.error)),
+                        Some(resp) => get_file_info(&resp.data, bucket, object, version_id, FileInfoOpts { data: false })
+                            .await
+                            .map_err(DiskError::from),
+                        None => Err(DiskError::FileNotFound),
+                    },
+                    Err(err) => Err(err.clone()),
+                };
+
+                match fi {
+                    Ok(fi) => {
+                        parts_metadata.push(fi);
+                        errs.push(None);
+                    }
+                    Err(err) => {
+                        parts_metadata.push(FileInfo::default());
+                        errs.push(Some(err));
+                    }
+                }
+            }
+        }
+
+        metas
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -1941,6 +2142,7 @@ impl SetDisks {
             }
 
             let till_offset = erasure.shard_file_offset(part_offset, part_length, part_size);
+            let shard_offset = part_offset / erasure.block_size * erasure.shard_size();
 
             let mut readers = Vec::with_capacity(disks.len());
             let mut errors = Vec::with_capacity(disks.len());
@@ -1950,8 +2152,8 @@ impl SetDisks {
                     disk_op.as_ref(),
                     bucket,
                     &format!("{}/{}/part.{}", object, files[idx].data_dir.unwrap_or_default(), part_number),
-                    part_offset,
-                    till_offset,
+                    shard_offset,
+                    till_offset - shard_offset,
                     erasure.shard_size(),
                     HashAlgorithm::HighwayHash256,
                 )
@@ -2327,6 +2529,11 @@ impl SetDisks {
 
                         result.object_size =
                             ObjectInfo::from_file_info(&lastest_meta, bucket, object, true).get_actual_size()? as usize;
+                        // report the layout the object was written with, the storage class may have changed since
+                        if lastest_meta.erasure.data_blocks > 0 {
+                            result.data_blocks = lastest_meta.erasure.data_blocks;
+                            result.parity_blocks = lastest_meta.erasure.parity_blocks;
+                        }
                         // Loop to find number of disks with valid data, per-drive
                         // data state and a list of outdated disks on which data needs
                         // to be healed.
@@ -2356,6 +2563,7 @@ impl SetDisks {
                                     | DiskError::FileVersionNotFound
                                     | DiskError::VolumeNotFound
                                     | DiskError::PartMissingOrCorrupt
+                                    | DiskError::DataDirMissing
                                     | DiskError::OutdatedXLMeta => DRIVE_STATE_MISSING,
                                     _ => DRIVE_STATE_CORRUPT,
                                 },
@@ -2392,6 +2600,26 @@ impl SetDisks {
                             ));
                         }
 
+                        if opts.update_parity && !opts.dry_run && !lastest_meta.deleted && !lastest_meta.is_remote() {
+                            let parity_blocks = self.storage_class_parity(&lastest_meta);
+                            if parity_blocks != lastest_meta.erasure.parity_blocks {
+                                // every drive is rewritten, which heals the outdated ones as well
+                                let (fi, online_disks) = self
+                                    .reencode_object(bucket, object, &lastest_meta, &parts_metadata, &disks, parity_blocks)
+                                    .await?;
+                                result.data_blocks = fi.erasure.data_blocks;
+                                result.parity_blocks = fi.erasure.parity_blocks;
+                                for disk in online_disks.iter().flatten() {
+                                    for (i, v) in result.before.drives.iter().enumerate() {
+                                        if v.endpoint == disk.endpoint().to_string() {
+                                            result.after.drives[i].state = DRIVE_STATE_OK.to_string();
+                                        }
+                                    }
+                                }
+                                return Ok((result, None));
+                            }
+                        }
+
                         if disks_to_heal_count == 0 {
                             return Ok((result, None));
                         }
@@ -2583,6 +2811,11 @@ impl SetDisks {
                                 };
 
                                 for disk in out_dated_disks.iter() {
+                                    // only the outdated drives are written
+                                    if disk.is_none() {
+                                        writers.push(None);
+                                        continue;
+                                    }
                                     let writer = create_bitrot_writer(
                                         is_inline_buffer,
                                         disk.as_ref(),
@@ -2649,7 +2882,14 @@ impl SetDisks {
                                 // Heal each part. erasure.Heal() will write the healed
                                 // part to .rustfs/tmp/uuid/ which needs to be renamed
                                 // later to the final location.
-                                erasure.heal(&mut writers, readers, part.size, &prefer).await?;
+                                if let Err(err) = erasure.heal(&mut writers, readers, part.size, &prefer).await {
+                                    let _ = self.delete_all(RUSTFS_META_TMP_BUCKET, &tmp_id).await;
+                                    if err == DiskError::ParityMismatch {
+                                        error!("heal_object {}/{} part {} reconstruction mismatch", bucket, object, part.number);
+                                        return Err(DiskError::ReconstructionFailed(part.number));
+                                    }
+                                    return Err(err);
+                                }
                                 // close_bitrot_writers(&mut writers).await?;
 
                                 for (index, disk) in out_dated_disks.iter().enumerate() {
@@ -2953,6 +3193,141 @@ impl SetDisks {
         result
     }
 
+    // Parity the storage class of `fi` calls for under the current config.
+    fn storage_class_parity(&self, fi: &FileInfo) -> usize {
+        GLOBAL_StorageClass
+            .get()
+            .and_then(|sc| sc.get_parity_for_sc(fi.metadata.get(AMZ_STORAGE_CLASS).map(String::as_str).unwrap_or_default()))
+            .unwrap_or(self.default_parity_count)
+    }
+
+    // Rewrites all shards of `fi` with `parity_blocks` parity, keeping its version, parts and
+    // metadata. Returns the new file info and the drives that took it.
+    async fn reencode_object(
+        &self,
+        bucket: &str,
+        object: &str,
+        fi: &FileInfo,
+        files: &[FileInfo],
+        disks: &[Option<DiskStore>],
+        parity_blocks: usize,
+    ) -> disk::error::Result<(FileInfo, Vec<Option<DiskStore>>)> {
+        let data_blocks = disks.len() - parity_blocks;
+        let write_quorum = if data_blocks == parity_blocks {
+            data_blocks + 1
+        } else {
+            data_blocks
+        };
+
+        let mut nfi = fi.clone();
+        nfi.erasure = FileInfo::new([bucket, object].join("/").as_str(), data_blocks, parity_blocks).erasure;
+        nfi.data_dir = Some(Uuid::new_v4());
+        nfi.data = None;
+        nfi.parts = Vec::new();
+
+        // every drive gets the same fresh layout, so only the drives need to follow the new distribution
+        let shuffle_disks = Self::shuffle_disks(disks, &nfi.erasure.distribution);
+        let mut parts_metadatas = vec![nfi.clone(); disks.len()];
+
+        let is_inline_buffer = fi.inline_data();
+        let tmp_dir = Uuid::new_v4().to_string();
+        let mut offset = 0;
+        for part in fi.parts.iter() {
+            let erasure = erasure_coding::Erasure::new(data_blocks, parity_blocks, nfi.erasure.block_size);
+            let mut writers = Vec::with_capacity(shuffle_disks.len());
+            for disk in shuffle_disks.iter() {
+                if disk.is_none() {
+                    writers.push(None);
+                    continue;
+                }
+                let writer = create_bitrot_writer(
+                    is_inline_buffer,
+                    disk.as_ref(),
+                    RUSTFS_META_TMP_BUCKET,
+                    &format!("{}/{}/part.{}", tmp_dir, nfi.data_dir.unwrap_or_default(), part.number),
+                    erasure.shard_file_size(part.size as i64),
+                    erasure.shard_size(),
+                    HashAlgorithm::HighwayHash256,
+                )
+                .await?;
+                writers.push(Some(writer));
+            }
+
+            // decode the part straight into the encoder instead of holding it in memory
+            let (pr, mut pw) = tokio::io::duplex(fi.erasure.block_size);
+            let decode = async move {
+                let res = Self::get_object_with_fileinfo(
+                    bucket,
+                    object,
+                    offset,
+                    part.size as i64,
+                    &mut pw,
+                    fi.clone(),
+                    files.to_vec(),
+                    disks,
+                    self.set_index,
+                    self.pool_index,
+                )
+                .await;
+                let _ = pw.shutdown().await;
+                res
+            };
+            let (decoded, encoded) = tokio::join!(decode, Arc::new(erasure).encode(pr, &mut writers, write_quorum));
+            let res = match (decoded, encoded) {
+                (Err(err), _) => Err(err.into()),
+                (_, Err(err)) => Err(err.into()),
+                (Ok(()), Ok((_, n))) if n != part.size => Err(DiskError::LessData),
+                _ => Ok(()),
+            };
+            if let Err(err) = res {
+                let _ = self.delete_all(RUSTFS_META_TMP_BUCKET, &tmp_dir).await;
+                return Err(err);
+            }
+            offset += part.size;
+
+            for (index, pfi) in parts_metadatas.iter_mut().enumerate() {
+                pfi.add_object_part(
+                    part.number,
+                    part.etag.clone(),
+                    part.size,
+                    part.mod_time,
+                    part.actual_size,
+                    part.index.clone(),
+                );
+                if is_inline_buffer {
+                    if let Some(writer) = writers[index].take() {
+                        pfi.data = Some(writer.into_inline_data().map(bytes::Bytes::from).unwrap_or_default());
+                    }
+                    pfi.set_inline_data();
+                }
+            }
+        }
+
+        let _data_lock = self.lock_object_data(bucket, object, false).await?;
+        let (online_disks, _, old_dir) = Self::rename_data(
+            &shuffle_disks,
+            RUSTFS_META_TMP_BUCKET,
+            &tmp_dir,
+            &parts_metadatas,
+            bucket,
+            object,
+            write_quorum,
+        )
+        .await?;
+
+        if let Some(old_dir) = old_dir {
+            self.commit_rename_data_dir(&shuffle_disks, bucket, object, &old_dir.to_string(), write_quorum)
+                .await?;
+        }
+
+        self.delete_all(RUSTFS_META_TMP_BUCKET, &tmp_dir)
+            .await
+            .map_err(DiskError::other)?;
+
+        nfi.parts = fi.parts.clone();
+        Ok((nfi, online_disks))
+    }
+
     async fn delete_if_dang_ling(
         &self,
         bucket: &str,
@@ -3034,6 +3409,7 @@ impl SetDisks {
         want_cycle: u32,
         updates: Sender<DataUsageCache>,
         heal_scan_mode: HealScanMode,
+        throttle: ScannerThrottle,
     ) -> Result<()> {
         info!("ns_scanner");
         if buckets.is_empty() {
@@ -3153,6 +3529,8 @@ impl SetDisks {
             let self_clone = Arc::clone(&self);
             let bucket_rx_clone = bucket_rx.clone();
             let buckets_results_tx_clone = buckets_results_tx.clone();
+            // each drive gets its own IO budget
+            let we_sleep = throttle.should_sleep_fn();
             futures.push(async move {
                 loop {
                     match bucket_rx_clone.write().await.try_recv() {
@@ -3166,6 +3544,32 @@ impl SetDisks {
                             if cache.info.name.is_empty() {
                                 cache.info.name = bucket_info.name.clone();
                             }
+                            let previous = match cache.root() {
+                                Some(root) if cache.info.name == bucket_info.name => Some(cache.flatten(&root)),
+                                _ => None,
+                            };
+                            // a cold bucket only gets a light metadata pass this cycle: unless entries were added
+                            // to or removed from its top level since its last full scan, the totals of that scan stand
+                            if previous.is_some() && cache.info.scan_deferred(want_cycle) {
+                                let touched = match (disk.stat_volume(&bucket_info.name).await, cache.info.last_update) {
+                                    (Ok(vol), Some(last)) => vol.created.is_none_or(|mtime| SystemTime::from(mtime) > last),
+                                    _ => true,
+                                };
+                                if touched {
+                                    info!("data-scanner: cold bucket {} changed at its top level, scanning it", bucket_info.name);
+                                    cache.info.next_cycle = want_cycle;
+                                }
+                            }
+                            if let Some(root) = previous.as_ref().filter(|_| cache.info.scan_deferred(want_cycle)) {
+                                let _ = buckets_results_tx_clone
+                                    .send(DataUsageEntryInfo {
+                                        name: bucket_info.name.clone(),
+                                        parent: DATA_USAGE_ROOT.to_string(),
+                                        entry: root.clone(),
+                                    })
+                                    .await;
+                                continue;
+                            }
                             cache.info.skip_healing = healing;
                             cache.info.next_cycle = want_cycle;
                             if cache.info.name != bucket_info.name {
@@ -3200,7 +3604,7 @@ impl SetDisks {
 
                             // Calc usage
                             let before = cache.info.last_update;
-                            let mut cache = match disk.ns_scanner(&cache, tx, heal_scan_mode, None).await {
+                            let mut cache = match disk.ns_scanner(&cache, tx, heal_scan_mode, we_sleep.clone()).await {
                                 Ok(cache) => cache,
                                 Err(_) => {
                                     if cache.info.last_update > before {
@@ -3226,9 +3630,14 @@ impl SetDisks {
                                 .send(DataUsageEntryInfo {
                                     name: cache.info.name.clone(),
                                     parent: DATA_USAGE_ROOT.to_string(),
-                                    entry: root,
+                                    entry: root.clone(),
                                 })
                                 .await;
+                            if let Some(before) = &previous {
+                                if cache.defer_if_cold(before, &root, want_cycle, get_scanner_cold_bucket_threshold()) {
+                                    info!("data-scanner: bucket {} barely changed, deferring its next scan", cache.info.name);
+                                }
+                            }
                             let _ = cache.save(&cache_name.to_string_lossy()).await;
                         }
                     }
@@ -3246,6 +3655,26 @@ impl SetDisks {
         Ok(())
     }
 
+    /// Scans every bucket once on one of the set's online drives and merges the results into a
+    /// cache rooted at [`DATA_USAGE_ROOT`]. Each drive gets its own scanner throttle budget.
+    pub async fn scan_usage(
+        &self,
+        buckets: &[BucketInfo],
+        updates: Sender<DataUsageEntry>,
+        scan_mode: HealScanMode,
+    ) -> Result<DataUsageCache> {
+        let (disks, healing) = self.get_online_disk_with_healing(false).await?;
+        let disks: Vec<DiskStore> = disks.into_iter().flatten().take(num_cpus::get()).collect();
+        let throttle = get_scanner_throttle();
+
+        Ok(scan_buckets_on(disks, buckets, healing, |disk, cache| {
+            let updates = updates.clone();
+            let we_sleep = throttle.should_sleep_fn();
+            async move { Ok(disk.ns_scanner(&cache, updates, scan_mode, we_sleep).await?) }
+        })
+        .await)
+    }
+
     pub async fn heal_erasure_set(self: Arc<Self>, buckets: &[String], tracker: Arc<RwLock<HealingTracker>>) -> Result<()> {
         let (bg_seq, found) = GLOBAL_BackgroundHealState.get_heal_sequence_by_token(BG_HEALING_UUID).await;
         if !found {
@@ -3814,6 +4243,8 @@ impl ObjectIO for SetDisks {
         h: HeaderMap,
         opts: &ObjectOptions,
     ) -> Result<GetObjectReader> {
+        // held until the parts are streamed, an overwrite keeps the data dir until then
+        let data_lock = self.lock_object_data(bucket, object, true).await?;
         let (fi, files, disks) = self
             .get_object_fileinfo(bucket, object, opts, true)
             .await
@@ -3852,30 +4283,37 @@ impl ObjectIO for SetDisks {
 
         let (rd, wd) = tokio::io::duplex(DEFAULT_READ_BUFFER_SIZE);
 
-        let (reader, offset, length) = GetObjectReader::new(Box::new(rd), range, &object_info, opts, &h)?;
+        let object_key = kms::object_key(&object_info).await?;
+        let (reader, offset, length) = GetObjectReader::new(Box::new(rd), range, &object_info, opts, &h, object_key)?;
 
         // let disks = disks.clone();
         let bucket = bucket.to_owned();
         let object = object.to_owned();
         let set_index = self.set_index;
         let pool_index = self.pool_index;
+        // the task does not inherit the caller's task-local deadline
+        let deadline = opts.deadline;
         tokio::spawn(async move {
-            if let Err(e) = Self::get_object_with_fileinfo(
-                &bucket,
-                &object,
-                offset,
-                length,
-                &mut Box::new(wd),
-                fi,
-                files,
-                &disks,
-                set_index,
-                pool_index,
+            if let Err(e) = with_deadline(
+                deadline,
+                Self::get_object_with_fileinfo(
+                    &bucket,
+                    &object,
+                    offset,
+                    length,
+                    &mut Box::new(wd),
+                    fi,
+                    files,
+                    &disks,
+                    set_index,
+                    pool_index,
+                ),
             )
             .await
             {
                 error!("get_object_with_fileinfo err {:?}", e);
             };
+            drop(data_lock);
 
             // error!("get_object_with_fileinfo end {}/{}", bucket, object);
         });
@@ -3885,6 +4323,8 @@ impl ObjectIO for SetDisks {
 
     #[tracing::instrument(level = "debug", skip(self, data,))]
     async fn put_object(&self, bucket: &str, object: &str, data: &mut PutObjReader, opts: &ObjectOptions) -> Result<ObjectInfo> {
+        check_max_object_size(data.size())?;
+
         let disks = self.disks.read().await;
 
         // let mut _ns = None;
@@ -3935,6 +4375,11 @@ impl ObjectIO for SetDisks {
         if data_drives == parity_drives {
             write_quorum += 1
         }
+        if !is_meta_bucketname(bucket) {
+            if let Ok((quorum, _)) = metadata_sys::get_write_quorum_config(bucket).await {
+                write_quorum = write_quorum.max(quorum);
+            }
+        }
 
         let mut fi = FileInfo::new([bucket, object].join("/").as_str(), data_drives, parity_drives);
 
@@ -3962,12 +4407,12 @@ impl ObjectIO for SetDisks {
 
         let erasure = erasure_coding::Erasure::new(fi.erasure.data_blocks, fi.erasure.parity_blocks, fi.erasure.block_size);
 
-        let is_inline_buffer = {
-            if let Some(sc) = GLOBAL_StorageClass.get() {
-                sc.should_inline(erasure.shard_file_size(data.size()), opts.versioned)
-            } else {
-                false
-            }
+        let is_inline_buffer = if let Some(threshold) = opts.inline_data_threshold {
+            data.size() >= 0 && (data.size() as usize) < threshold
+        } else if let Some(sc) = GLOBAL_StorageClass.get() {
+            sc.should_inline(erasure.shard_file_size(data.size()), opts.versioned)
+        } else {
+            false
         };
 
         let mut writers = Vec::with_capacity(shuffle_disks.len());
@@ -4018,6 +4463,7 @@ impl ObjectIO for SetDisks {
         let nil_count = errors.iter().filter(|&e| e.is_none()).count();
         if nil_count < write_quorum {
             error!("not enough disks to write: {:?}", errors);
+            let _ = self.delete_all(RUSTFS_META_TMP_BUCKET, &tmp_dir).await;
             if let Some(write_err) = reduce_write_quorum_errs(&errors, OBJECT_OP_IGNORED_ERRS, write_quorum) {
                 return Err(to_object_err(write_err.into(), vec![bucket, object]));
             }
@@ -4030,15 +4476,30 @@ impl ObjectIO for SetDisks {
             HashReader::new(Box::new(WarpReader::new(Cursor::new(Vec::new()))), 0, 0, None, false)?,
         );
 
+        let stream = SizeLimitReader::new(stream, get_global_max_object_size());
+        let stream = ChecksumReader::new(stream, opts.checksum_algorithm);
+
         let (reader, w_size) = match Arc::new(erasure).encode(stream, &mut writers, write_quorum).await {
             Ok((r, w)) => (r, w),
             Err(e) => {
                 error!("encode err {:?}", e);
+                let _ = self.delete_all(RUSTFS_META_TMP_BUCKET, &tmp_dir).await;
                 return Err(e.into());
             }
-        }; // TODO: 出错，删除临时目录
+        };
+
+        let (reader, checksum) = reader.into_parts();
+        let _ = mem::replace(&mut data.stream, reader.into_inner());
 
-        let _ = mem::replace(&mut data.stream, reader);
+        if let (Some(mode), Some(computed)) = (opts.checksum_algorithm, checksum) {
+            if opts.checksum_value.as_ref().is_some_and(|expected| *expected != computed) {
+                let _ = self.delete_all(RUSTFS_META_TMP_BUCKET, &tmp_dir).await;
+                return Err(StorageError::BadDigest);
+            }
+
+            user_defined.insert(format!("{RESERVED_METADATA_PREFIX_LOWER}checksum-algorithm"), mode.to_string());
+            user_defined.insert(format!("{RESERVED_METADATA_PREFIX_LOWER}checksum"), computed);
+        }
         // if let Err(err) = close_bitrot_writers(&mut writers).await {
         //     error!("close_bitrot_writers err {:?}", err);
         // }
@@ -4099,6 +4560,7 @@ impl ObjectIO for SetDisks {
             }
         }
 
+        let data_lock = self.lock_object_data(bucket, object, false).await?;
         let (online_disks, _, op_old_dir) = Self::rename_data(
             &shuffle_disks,
             RUSTFS_META_TMP_BUCKET,
@@ -4114,6 +4576,7 @@ impl ObjectIO for SetDisks {
             self.commit_rename_data_dir(&shuffle_disks, bucket, object, &old_dir.to_string(), write_quorum)
                 .await?;
         }
+        drop(data_lock);
 
         self.delete_all(RUSTFS_META_TMP_BUCKET, &tmp_dir).await?;
 
@@ -4139,6 +4602,12 @@ impl ObjectIO for SetDisks {
 
 #[async_trait::async_trait]
 impl StorageAPI for SetDisks {
+    #[tracing::instrument(skip(self))]
+    async fn shutdown(&self, _force: bool) -> Result<()> {
+        let disks = self.get_disks_internal().await;
+        close_storage_disks(&disks).await;
+        Ok(())
+    }
     #[tracing::instrument(skip(self))]
     async fn backend_info(&self) -> rustfs_madmin::BackendInfo {
         unimplemented!()
@@ -4386,9 +4855,10 @@ impl StorageAPI for SetDisks {
             };
 
             if vr.deleted {
+                // a marker written while versioning is suspended is the null version
                 del_objects[i] = DeletedObject {
                     delete_marker: vr.deleted,
-                    delete_marker_version_id: vr.version_id.map(|v| v.to_string()),
+                    delete_marker_version_id: Some(vr.version_id.map_or_else(|| "null".to_string(), |v| v.to_string())),
                     delete_marker_mtime: vr.mod_time,
                     object_name: vr.name.clone(),
                     ..Default::default()
@@ -4409,6 +4879,7 @@ impl StorageAPI for SetDisks {
         for (_, ver) in vers_map {
             vers.push(ver);
         }
+        normalize_versions(&mut vers);
 
         let disks = self.disks.read().await;
 
@@ -4441,6 +4912,29 @@ impl StorageAPI for SetDisks {
         Ok((del_objects, del_errs))
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn delete_prefix(self: Arc<Self>, bucket: &str, prefix: &str, opts: ObjectOptions) -> Result<DeletePrefixReport> {
+        let mut objects = Vec::new();
+        for object in self.walk_versions(bucket, prefix).await? {
+            if opts.all_versions {
+                objects.extend(object.versions.iter().map(|fi| ObjectToDelete {
+                    object_name: fi.name.clone(),
+                    version_id: fi.version_id,
+                }));
+            } else if object.versions.first().is_some_and(|fi| !fi.deleted) {
+                // like a listing, objects whose latest version is a delete marker are already gone
+                objects.push(ObjectToDelete {
+                    object_name: object.name,
+                    version_id: None,
+                });
+            }
+        }
+
+        let mut report = DeletePrefixReport::default();
+        delete_prefix_batch(&self, bucket, objects, &opts, &mut report).await?;
+        Ok(report)
+    }
+
     #[tracing::instrument(skip(self))]
     async fn delete_object(&self, bucket: &str, object: &str, opts: ObjectOptions) -> Result<ObjectInfo> {
         if opts.delete_prefix {
@@ -4450,7 +4944,51 @@ impl StorageAPI for SetDisks {
 
             return Ok(ObjectInfo::default());
         }
-        unimplemented!()
+
+        let version_id = match opts.version_id.as_deref() {
+            Some(vid) if vid != "null" => Some(
+                Uuid::parse_str(vid)
+                    .map_err(|_| StorageError::InvalidVersionID(bucket.to_owned(), object.to_owned(), vid.to_owned()))?,
+            ),
+            _ => None,
+        };
+
+        // holding the data lock keeps the version the If-Match check saw in place until it is deleted
+        let _data_lock = match &opts.if_match {
+            Some(if_match) => {
+                let data_lock = self.lock_object_data(bucket, object, false).await?;
+                check_if_match(if_match, &self.get_object_info(bucket, object, &opts).await?)?;
+                Some(data_lock)
+            }
+            None => None,
+        };
+
+        let (mut deleted, _) = self
+            .delete_objects(
+                bucket,
+                vec![ObjectToDelete {
+                    object_name: object.to_owned(),
+                    version_id,
+                }],
+                opts,
+            )
+            .await?;
+        let deleted = deleted.pop().unwrap_or_default();
+
+        let version_id = if deleted.delete_marker {
+            deleted.delete_marker_version_id
+        } else {
+            deleted.version_id
+        };
+
+        Ok(ObjectInfo {
+            bucket: bucket.to_owned(),
+            name: object.to_owned(),
+            version_id: version_id.and_then(|v| Uuid::parse_str(&v).ok()),
+            delete_marker: deleted.delete_marker,
+            mod_time: deleted.delete_marker_mtime,
+            ..Default::default()
+        })
     }
 
     #[tracing::instrument(skip(self))]
@@ -4480,6 +5018,55 @@ impl StorageAPI for SetDisks {
         unimplemented!()
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn inspect_object(&self, bucket: &str, object: &str, version_id: Option<&str>) -> Result<ObjectInspectReport> {
+        let disks = self.get_disks_internal().await;
+        let vid = version_id.unwrap_or_default();
+        let (metas, errs) = Self::read_all_fileinfo(&disks, "", bucket, object, vid, false, false).await?;
+
+        let futures = disks.iter().enumerate().map(|(i, disk)| {
+            let (fi, err) = (&metas[i], &errs[i]);
+            let endpoint = self.set_endpoints.get(i).map(|e| e.to_string()).unwrap_or_default();
+            async move {
+                let mut info = DiskInspectInfo {
+                    endpoint,
+                    ..Default::default()
+                };
+                let Some(disk) = disk else {
+                    info.err = Some(DiskError::DiskNotFound.to_string());
+                    return info;
+                };
+                info.online = disk.is_online().await;
+                if let Some(err) = err {
+                    info.err = Some(err.to_string());
+                    return info;
+                }
+
+                info.etag = fi.metadata.get("etag").cloned();
+                info.parts = fi.parts.len();
+                info.data_dir = fi.data_dir;
+                info.erasure_index = fi.erasure.index;
+                info.mod_time = fi.mod_time;
+                if !fi.deleted && !fi.is_remote() && !fi.inline_data() {
+                    match disk.verify_file(bucket, object, fi).await {
+                        Ok(resp) => info.bitrot = resp.results,
+                        Err(err) => info.err = Some(err.to_string()),
+                    }
+                }
+                info
+            }
+        });
+
+        Ok(ObjectInspectReport {
+            bucket: bucket.to_string(),
+            object: object.to_string(),
+            version_id: version_id.map(str::to_string),
+            pool_index: self.pool_index,
+            set_index: self.set_index,
+            disks: join_all(futures).await,
+        })
+    }
+
     #[tracing::instrument(skip(self))]
     async fn get_object_info(&self, bucket: &str, object: &str, opts: &ObjectOptions) -> Result<ObjectInfo> {
         // let mut _ns = None;
@@ -4522,6 +5109,26 @@ impl StorageAPI for SetDisks {
         Ok(oi)
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn get_object_infos(&self, bucket: &str, objects: &[String], opts: &ObjectOptions) -> Result<Vec<Result<ObjectInfo>>> {
+        let disks = self.disks.read().await.clone();
+        let vid = opts.version_id.clone().unwrap_or_default();
+
+        let metas = Self::read_all_fileinfos(&disks, bucket, objects, &vid).await;
+
+        let mut infos = Vec::with_capacity(objects.len());
+        for (object, (parts_metadata, errs)) in objects.iter().zip(metas) {
+            let info = self
+                .quorum_fileinfo(&disks, bucket, object, &parts_metadata, &errs)
+                .await
+                .map(|(fi, _)| ObjectInfo::from_file_info(&fi, bucket, object, opts.versioned || opts.version_suspended))
+                .map_err(|e| to_object_err(e, vec![bucket, object]));
+            infos.push(info);
+        }
+
+        Ok(infos)
+    }
+
     #[tracing::instrument(skip(self))]
     async fn add_partial(&self, bucket: &str, object: &str, version_id: &str) -> Result<()> {
         GLOBAL_MRFState
@@ -4852,22 +5459,84 @@ impl StorageAPI for SetDisks {
         self.put_object_tags(bucket, object, "", opts).await
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn clear_object_retention(&self, bucket: &str, object: &str, version_id: &str, bypass: bool) -> Result<()> {
+        let opts = ObjectOptions {
+            version_id: (!version_id.is_empty()).then(|| version_id.to_owned()),
+            ..Default::default()
+        };
+        let (mut fi, _, disks) = self.get_object_fileinfo(bucket, object, &opts, false).await?;
+        if fi.deleted {
+            return Err(to_object_err(Error::MethodNotAllowed, vec![bucket, object]));
+        }
+
+        if clear_retention_meta(bucket, object, &mut fi.metadata, bypass)? {
+            // every drive gets the new metadata in one update, like tagging
+            self.update_object_meta(bucket, object, fi, disks.as_slice()).await?;
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn touch_object(
+        &self,
+        bucket: &str,
+        object: &str,
+        version_id: Option<&str>,
+        new_mtime: OffsetDateTime,
+    ) -> Result<ObjectInfo> {
+        let opts = ObjectOptions {
+            version_id: version_id.map(str::to_owned),
+            ..Default::default()
+        };
+        let (mut fi, _, disks) = self.get_object_fileinfo(bucket, object, &opts, false).await?;
+        if fi.deleted {
+            return Err(to_object_err(Error::MethodNotAllowed, vec![bucket, object]));
+        }
+
+        let versioned = fi.version_id.is_some();
+        // a locked version keeps its metadata, mod time included
+        if let Ok((cfg, _)) = metadata_sys::get_object_lock_config(bucket).await {
+            check_put_against_lock(&ObjectInfo::from_file_info(&fi, bucket, object, versioned), &cfg)?;
+        }
+
+        // only the mod time changes, the etag and data dir stay as they are
+        fi.mod_time = Some(new_mtime);
+        self.update_object_meta(bucket, object, fi.clone(), disks.as_slice()).await?;
+
+        Ok(ObjectInfo::from_file_info(&fi, bucket, object, versioned))
+    }
+
     #[tracing::instrument(skip(self))]
     async fn copy_object_part(
         &self,
-        _src_bucket: &str,
-        _src_object: &str,
-        _dst_bucket: &str,
-        _dst_object: &str,
-        _upload_id: &str,
-        _part_id: usize,
-        _start_offset: i64,
-        _length: i64,
-        _src_info: &ObjectInfo,
-        _src_opts: &ObjectOptions,
-        _dst_opts: &ObjectOptions,
-    ) -> Result<()> {
-        unimplemented!()
+        src_bucket: &str,
+        src_object: &str,
+        dst_bucket: &str,
+        dst_object: &str,
+        upload_id: &str,
+        part_id: usize,
+        start_offset: i64,
+        length: i64,
+        src_info: &ObjectInfo,
+        src_opts: &ObjectOptions,
+        dst_opts: &ObjectOptions,
+    ) -> Result<PartInfo> {
+        copy_object_range(
+            self,
+            src_bucket,
+            src_object,
+            dst_bucket,
+            dst_object,
+            upload_id,
+            part_id,
+            start_offset,
+            length,
+            src_info,
+            src_opts,
+            dst_opts,
+        )
+        .await
     }
 
     #[tracing::instrument(level = "debug", skip(self, data, opts))]
@@ -4880,6 +5549,8 @@ impl StorageAPI for SetDisks {
         data: &mut PutObjReader,
         opts: &ObjectOptions,
     ) -> Result<PartInfo> {
+        check_max_object_size(data.size())?;
+
         let upload_id_path = Self::get_upload_id_dir(bucket, object, upload_id);
 
         let (mut fi, _) = self.check_upload_id_exists(bucket, object, upload_id, true).await?;
@@ -4999,9 +5670,17 @@ impl StorageAPI for SetDisks {
             HashReader::new(Box::new(WarpReader::new(Cursor::new(Vec::new()))), 0, 0, None, false)?,
         );
 
-        let (reader, w_size) = Arc::new(erasure).encode(stream, &mut writers, write_quorum).await?; // TODO: 出错，删除临时目录
+        let stream = SizeLimitReader::new(stream, get_global_max_object_size());
 
-        let _ = mem::replace(&mut data.stream, reader);
+        let (reader, w_size) = match Arc::new(erasure).encode(stream, &mut writers, write_quorum).await {
+            Ok((r, w)) => (r, w),
+            Err(e) => {
+                let _ = self.delete_all(RUSTFS_META_TMP_BUCKET, &tmp_part).await;
+                return Err(e.into());
+            }
+        };
+
+        let _ = mem::replace(&mut data.stream, reader.into_inner());
 
         if (w_size as i64) < data.size() {
             return Err(Error::other("put_object_part write size < data.size()"));
@@ -5023,11 +5702,12 @@ impl StorageAPI for SetDisks {
             }
         }
 
+        let now = OffsetDateTime::now_utc();
         let part_info = ObjectPartInfo {
             etag: etag.clone(),
             number: part_id,
             size: w_size,
-            mod_time: Some(OffsetDateTime::now_utc()),
+            mod_time: Some(now),
             actual_size,
             index: index_op,
             ..Default::default()
@@ -5040,7 +5720,7 @@ impl StorageAPI for SetDisks {
         let fi_buff = fi.marshal_msg()?;
 
         let part_path = format!("{}/{}/{}", upload_id_path, fi.data_dir.unwrap_or_default(), part_suffix);
-        let _ = Self::rename_part(
+        let (_, superseded) = Self::rename_part(
             &disks,
             RUSTFS_META_TMP_BUCKET,
             &tmp_part_path,
@@ -5051,10 +5731,20 @@ impl StorageAPI for SetDisks {
         )
         .await?;
 
+        if superseded {
+            // a concurrent upload of the same part won, report the part that was committed
+            return self
+                .list_uploaded_parts(bucket, object, upload_id, &fi)
+                .await
+                .into_iter()
+                .find(|part| part.part_num == part_id)
+                .ok_or_else(|| Error::InvalidPart(part_id, bucket.to_owned(), object.to_owned()));
+        }
+
         let ret: PartInfo = PartInfo {
             etag: Some(etag.clone()),
             part_num: part_id,
-            last_mod: Some(OffsetDateTime::now_utc()),
+            last_mod: Some(now),
             size: w_size,
             actual_size,
         };
@@ -5139,8 +5829,8 @@ impl StorageAPI for SetDisks {
 
                 let splits: Vec<&str> = upload_id.split("x").collect();
                 if splits.len() == 2 {
-                    if let Ok(unix) = splits[1].parse::<i64>() {
-                        OffsetDateTime::from_unix_timestamp(unix)?
+                    if let Ok(unix_nanos) = splits[1].parse::<i128>() {
+                        OffsetDateTime::from_unix_timestamp_nanos(unix_nanos)?
                     } else {
                         now
                     }
@@ -5313,7 +6003,7 @@ impl StorageAPI for SetDisks {
         bucket: &str,
         object: &str,
         upload_id: &str,
-        _opts: &ObjectOptions,
+        opts: &ObjectOptions,
     ) -> Result<MultipartInfo> {
         // TODO: nslock
         let (fi, _) = self
@@ -5321,11 +6011,18 @@ impl StorageAPI for SetDisks {
             .await
             .map_err(|e| to_object_err(e, vec![bucket, object, upload_id]))?;
 
+        let parts = if opts.skip_parts {
+            Vec::new()
+        } else {
+            self.list_uploaded_parts(bucket, object, upload_id, &fi).await
+        };
+
         Ok(MultipartInfo {
             bucket: bucket.to_owned(),
             object: object.to_owned(),
             upload_id: upload_id.to_owned(),
             user_defined: fi.metadata.clone(),
+            parts,
             ..Default::default()
         })
     }
@@ -5347,6 +6044,10 @@ impl StorageAPI for SetDisks {
         uploaded_parts: Vec<CompletePart>,
         opts: &ObjectOptions,
     ) -> Result<ObjectInfo> {
+        if uploaded_parts.windows(2).any(|w| w[0].part_num >= w[1].part_num) {
+            return Err(Error::InvalidPartOrder);
+        }
+
         let (mut fi, files_metas) = self.check_upload_id_exists(bucket, object, upload_id, true).await?;
         let upload_id_path = Self::get_upload_id_dir(bucket, object, upload_id);
 
@@ -5424,16 +6125,13 @@ impl StorageAPI for SetDisks {
         let mut object_actual_size: i64 = 0;
 
         for (i, p) in uploaded_parts.iter().enumerate() {
-            let has_part = curr_fi.parts.iter().find(|v| v.number == p.part_num);
-            if has_part.is_none() {
+            let Some(ext_part) = curr_fi.parts.iter().find(|v| v.number == p.part_num) else {
                 error!(
-                    "complete_multipart_upload has_part.is_none() {:?}, part_id={}, bucket={}, object={}",
-                    has_part, p.part_num, bucket, object
+                    "complete_multipart_upload part not found, part_id={}, bucket={}, object={}",
+                    p.part_num, bucket, object
                 );
                 return Err(Error::InvalidPart(p.part_num, "".to_owned(), p.etag.clone().unwrap_or_default()));
-            }
-
-            let ext_part = &curr_fi.parts[i];
+            };
 
             if p.etag != Some(ext_part.etag.clone()) {
                 error!(
@@ -5450,7 +6148,7 @@ impl StorageAPI for SetDisks {
                     "complete_multipart_upload is_min_allowed_part_size err {:?}, part_id={}, bucket={}, object={}",
                     ext_part.actual_size, p.part_num, bucket, object
                 );
-                return Err(Error::InvalidPart(p.part_num, ext_part.etag.clone(), p.etag.clone().unwrap_or_default()));
+                return Err(Error::EntityTooSmall);
             }
 
             object_size += ext_part.size;
@@ -5484,6 +6182,15 @@ impl StorageAPI for SetDisks {
 
         fi.metadata.insert("etag".to_owned(), etag);
 
+        // the bucket default retention, unless the upload was started with a retention of its own
+        if !fi.metadata.contains_key(X_AMZ_OBJECT_LOCK_MODE.as_str()) {
+            for key in [X_AMZ_OBJECT_LOCK_MODE.as_str(), X_AMZ_OBJECT_LOCK_RETAIN_UNTIL_DATE.as_str()] {
+                if let Some(value) = opts.user_defined.get(key) {
+                    fi.metadata.insert(key.to_owned(), value.clone());
+                }
+            }
+        }
+
         fi.metadata
             .insert(format!("{RESERVED_METADATA_PREFIX_LOWER}actual-size"), object_actual_size.to_string());
 
@@ -5556,6 +6263,7 @@ impl StorageAPI for SetDisks {
             Self::cleanup_multipart_path(&disks, &parts).await;
         }
 
+        let data_lock = self.lock_object_data(bucket, object, false).await?;
         let (online_disks, versions, op_old_dir) = Self::rename_data(
             &shuffle_disks,
             RUSTFS_META_MULTIPART_BUCKET,
@@ -5574,6 +6282,7 @@ impl StorageAPI for SetDisks {
             self.commit_rename_data_dir(&shuffle_disks, bucket, object, &old_dir.to_string(), write_quorum)
                 .await?;
         }
+        drop(data_lock);
         if let Some(versions) = versions {
             GLOBAL_MRFState
                 .add_partial(PartialOperation {
@@ -5618,6 +6327,12 @@ impl StorageAPI for SetDisks {
         unimplemented!()
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn health(&self) -> ClusterHealth {
+        let online = self.online_disk_count().await;
+        ClusterHealth::from_pools(vec![PoolHealth::new(self.set_drive_count, self.default_parity_count, &[online])])
+    }
+
     #[tracing::instrument(skip(self))]
     async fn delete_bucket(&self, _bucket: &str, _opts: &DeleteBucketOptions) -> Result<()> {
         unimplemented!()
@@ -5685,6 +6400,101 @@ impl StorageAPI for SetDisks {
         Ok((result, err.map(|e| e.into())))
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn scrub_object(&self, bucket: &str, object: &str, opts: &HealOpts) -> Result<Vec<(String, HealResultItem)>> {
+        let disks = self.disks.read().await.clone();
+
+        // Collect the versions known to any drive, including free versions,
+        // so versions missing from some drives get healed too.
+        let (raws, errs) = Self::read_all_raw_file_info(&disks, bucket, object, false).await;
+        let mut versions: Vec<(Option<Uuid>, Option<OffsetDateTime>)> = Vec::new();
+        for raw in raws.iter().flatten() {
+            let Ok(meta) = FileMeta::load(&raw.buf) else {
+                continue;
+            };
+            for ver in meta.versions.iter() {
+                if !versions.iter().any(|(vid, _)| *vid == ver.header.version_id) {
+                    versions.push((ver.header.version_id, ver.header.mod_time));
+                }
+            }
+        }
+        if versions.is_empty() {
+            if DiskError::is_all_not_found(&errs) {
+                return Err(to_object_err(Error::FileNotFound, vec![bucket, object]));
+            }
+            return Err(Error::ErasureReadQuorum);
+        }
+        versions.sort_by_key(|v| std::cmp::Reverse(v.1));
+
+        let mut opts = *opts;
+        opts.scan_mode = HEAL_DEEP_SCAN;
+
+        let mut results = Vec::with_capacity(versions.len());
+        for (vid, _) in versions {
+            let version_id = vid.map(|v| v.to_string()).unwrap_or_default();
+            let (mut result, err) = match StorageAPI::heal_object(self, bucket, object, &version_id, &opts).await {
+                Ok(res) => res,
+                Err(err) => (HealResultItem::default(), Some(err)),
+            };
+            if let Some(err) = err {
+                if is_err_object_not_found(&err) || is_err_version_not_found(&err) {
+                    // removed while we were scrubbing
+                    continue;
+                }
+                result.detail = format!("unrecoverable: {err}");
+            }
+            results.push((vid.map_or_else(|| "null".to_string(), |v| v.to_string()), result));
+        }
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn verify_metadata_consistency(&self, bucket: &str, object: &str) -> Result<MetaConsistencyReport> {
+        let disks = self.disks.read().await.clone();
+
+        let (fis, errs) = Self::read_all_fileinfo(&disks, "", bucket, object, "", false, false).await?;
+        if DiskError::is_all_not_found(&errs) {
+            return Err(to_object_err(Error::FileNotFound, vec![bucket, object]));
+        }
+
+        let views = disks
+            .iter()
+            .zip(fis.iter().zip(errs.iter()))
+            .map(|(disk, (fi, err))| {
+                let endpoint = disk.as_ref().map(|d| d.endpoint().to_string()).unwrap_or_default();
+                match err {
+                    Some(err) => DiskMetaView {
+                        endpoint,
+                        err: Some(err.to_string()),
+                        ..Default::default()
+                    },
+                    None => DiskMetaView {
+                        endpoint,
+                        version_id: fi.version_id,
+                        etag: fi.metadata.get("etag").cloned(),
+                        mod_time: fi.mod_time,
+                        parts: fi.parts.iter().map(|p| (p.number, p.size)).collect(),
+                        err: None,
+                    },
+                }
+            })
+            .collect();
+
+        Ok(MetaConsistencyReport::new(bucket, object, views))
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn replication_stats(self: Arc<Self>, bucket: &str) -> Result<ReplicationStats> {
+        let mut stats = ReplicationStats::default();
+        for object in self.walk_versions(bucket, "").await? {
+            for fi in object.versions.iter().filter(|fi| !fi.deleted) {
+                stats.add_object(&ObjectInfo::from_file_info(fi, bucket, &fi.name, false));
+            }
+        }
+        Ok(stats)
+    }
+
     #[tracing::instrument(skip(self))]
     async fn heal_objects(
         &self,
@@ -5708,15 +6518,64 @@ impl StorageAPI for SetDisks {
     }
 }
 
+/// Hands `buckets` out to `disks` so each bucket is scanned exactly once, and merges the bucket
+/// caches returned by `scan` into one cache rooted at [`DATA_USAGE_ROOT`].
+///
+/// Lifecycle rules are applied by the drive scanner while it walks each bucket. A bucket whose
+/// scan fails is left out of the result and picked up again on the next cycle.
+async fn scan_buckets_on<D, F, Fut>(disks: Vec<D>, buckets: &[BucketInfo], skip_healing: bool, scan: F) -> DataUsageCache
+where
+    D: Clone,
+    F: Fn(D, DataUsageCache) -> Fut,
+    Fut: Future<Output = Result<DataUsageCache>>,
+{
+    let queue = std::sync::Mutex::new(buckets.iter().collect::<std::collections::VecDeque<_>>());
+    let merged = std::sync::Mutex::new(DataUsageCache {
+        info: DataUsageCacheInfo {
+            name: DATA_USAGE_ROOT.to_string(),
+            ..Default::default()
+        },
+        cache: HashMap::new(),
+    });
+
+    join_all(disks.into_iter().map(|disk| {
+        let (queue, merged, scan) = (&queue, &merged, &scan);
+        async move {
+            loop {
+                let Some(bucket) = queue.lock().unwrap().pop_front() else {
+                    return;
+                };
+                let cache = DataUsageCache {
+                    info: DataUsageCacheInfo {
+                        name: bucket.name.clone(),
+                        skip_healing,
+                        ..Default::default()
+                    },
+                    cache: HashMap::new(),
+                };
+                match scan(disk.clone(), cache).await {
+                    Ok(cache) => merged.lock().unwrap().merge_bucket(&cache),
+                    Err(err) => warn!("data-scanner: scan bucket {} failed, err: {:?}", bucket.name, err),
+                }
+            }
+        }
+    }))
+    .await;
+
+    let mut merged = merged.into_inner().unwrap();
+    merged.info.last_update = Some(SystemTime::now());
+    merged
+}
+
 #[derive(Debug, PartialEq, Eq)]
 struct ObjProps {
-    mod_time: Option<OffsetDateTime>,
+    successor_mod_time: Option<OffsetDateTime>,
     num_versions: usize,
 }
 
 impl Hash for ObjProps {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.mod_time.hash(state);
+        self.successor_mod_time.hash(state);
         self.num_versions.hash(state);
     }
 }
@@ -6048,7 +6907,11 @@ pub fn should_heal_object_on_disk(
     lastest_meta: &FileInfo,
 ) -> (bool, Option<DiskError>) {
     if let Some(err) = err {
-        if err == &DiskError::FileNotFound || err == &DiskError::FileVersionNotFound || err == &DiskError::FileCorrupt {
+        if err == &DiskError::FileNotFound
+            || err == &DiskError::FileVersionNotFound
+            || err == &DiskError::FileCorrupt
+            || err == &DiskError::DataDirMissing
+        {
             return (true, Some(err.clone()));
         }
     }
@@ -6179,22 +7042,97 @@ fn is_min_allowed_part_size(size: i64) -> bool {
     size >= GLOBAL_MIN_PART_SIZE.as_u64() as i64
 }
 
+fn check_max_object_size(size: i64) -> Result<()> {
+    if let Some(limit) = get_global_max_object_size() {
+        if size > 0 && size as u64 > limit {
+            return Err(StorageError::EntityTooLarge);
+        }
+    }
+    Ok(())
+}
+
+/// Fails the read with `EntityTooLarge` as soon as more than `limit` bytes
+/// have come through, so oversized streams are cut off while being encoded.
+struct SizeLimitReader<R> {
+    inner: R,
+    remaining: Option<u64>,
+}
+
+impl<R> SizeLimitReader<R> {
+    fn new(inner: R, limit: Option<u64>) -> Self {
+        Self { inner, remaining: limit }
+    }
+
+    fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for SizeLimitReader<R> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        let before = buf.filled().len();
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+
+        if let (Poll::Ready(Ok(())), Some(remaining)) = (&poll, self.remaining) {
+            let read = (buf.filled().len() - before) as u64;
+            if read > remaining {
+                return Poll::Ready(Err(std::io::Error::other(StorageError::EntityTooLarge)));
+            }
+            self.remaining = Some(remaining - read);
+        }
+
+        poll
+    }
+}
+
+// Hashes the upload as it is read, for the checksum the client asked to be verified and stored.
+struct ChecksumReader<R> {
+    inner: R,
+    hasher: Option<ChecksumHasher>,
+}
+
+impl<R> ChecksumReader<R> {
+    fn new(inner: R, mode: Option<ChecksumMode>) -> Self {
+        Self {
+            inner,
+            hasher: mode.and_then(ChecksumHasher::new),
+        }
+    }
+
+    fn into_parts(self) -> (R, Option<String>) {
+        (self.inner, self.hasher.map(ChecksumHasher::finalize))
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for ChecksumReader<R> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        let before = buf.filled().len();
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+
+        if let (Poll::Ready(Ok(())), Some(hasher)) = (&poll, self.hasher.as_mut()) {
+            hasher.update(&buf.filled()[before..]);
+        }
+
+        poll
+    }
+}
+
+// S3 multipart ETag: the MD5 of the concatenated binary part MD5s, followed by `-<part count>`.
+// A completed upload carries the suffix even with a single part, only plain puts get a bare MD5.
 fn get_complete_multipart_md5(parts: &[CompletePart]) -> String {
-    let mut buf = Vec::new();
+    let mut hasher = Md5::new();
 
     for part in parts.iter() {
         if let Some(etag) = &part.etag {
+            let etag = etag.trim_matches('"');
             if let Ok(etag_bytes) = hex_simd::decode_to_vec(etag.as_bytes()) {
-                buf.extend(etag_bytes);
+                hasher.update(&etag_bytes);
             } else {
-                buf.extend(etag.bytes());
+                hasher.update(etag.as_bytes());
             }
         }
     }
 
-    let mut hasher = Md5::new();
-    let _ = hasher.write(&buf);
-
     format!("{:x}-{}", hasher.finalize(), parts.len())
 }
 
@@ -6209,6 +7147,73 @@ mod tests {
     use std::collections::HashMap;
     use time::OffsetDateTime;
 
+    #[tokio::test]
+    async fn test_scan_buckets_on_merges_disk_caches() {
+        let buckets: Vec<BucketInfo> = ["alpha", "beta", "gamma"]
+            .iter()
+            .map(|name| BucketInfo {
+                name: name.to_string(),
+                ..Default::default()
+            })
+            .collect();
+        // (bucket, objects, size) of every bucket cache a mock drive returned
+        let scanned = std::sync::Mutex::new(Vec::new());
+
+        // mock drive `id` reports a top-level entry plus one prefix for each bucket it scans;
+        // drive 3 fails on "gamma"
+        let scan = |id: usize, mut cache: DataUsageCache| {
+            let scanned = &scanned;
+            async move {
+                let bucket = cache.info.name.clone();
+                if bucket == "gamma" && id == 3 {
+                    return Err(Error::other("drive offline"));
+                }
+                let top = DataUsageEntry {
+                    objects: id + 1,
+                    size: 100 * (id + 1),
+                    ..Default::default()
+                };
+                let dir = DataUsageEntry {
+                    objects: 2,
+                    size: 10 * bucket.len(),
+                    ..Default::default()
+                };
+                scanned
+                    .lock()
+                    .unwrap()
+                    .push((bucket.clone(), top.objects + dir.objects, top.size + dir.size));
+                cache.replace(&bucket, "", top);
+                cache.replace(&format!("{bucket}/dir"), &bucket, dir);
+                Ok(cache)
+            }
+        };
+
+        // two sets, merged the way the store merges its sets
+        let mut cluster = DataUsageCache::default();
+        cluster.info.name = DATA_USAGE_ROOT.to_string();
+        for disks in [vec![0, 1], vec![2, 3]] {
+            let set = scan_buckets_on(disks, &buckets, false, scan).await;
+            assert_eq!(set.info.name, DATA_USAGE_ROOT);
+            assert!(set.info.last_update.is_some());
+            cluster.merge(&set);
+        }
+
+        let scanned = scanned.into_inner().unwrap();
+        assert!(scanned.len() >= 2 * buckets.len() - 1);
+
+        for bucket in buckets.iter() {
+            let flat = cluster.flatten(&cluster.find(&bucket.name).unwrap());
+            let want = scanned.iter().filter(|(b, _, _)| *b == bucket.name);
+            assert_eq!(flat.objects, want.clone().map(|(_, o, _)| o).sum::<usize>(), "{}", bucket.name);
+            assert_eq!(flat.size, want.map(|(_, _, s)| s).sum::<usize>(), "{}", bucket.name);
+        }
+
+        let total = cluster.dui(DATA_USAGE_ROOT, &buckets);
+        assert_eq!(total.buckets_count, buckets.len() as u64);
+        assert_eq!(total.objects_total_count, scanned.iter().map(|(_, o, _)| *o as u64).sum::<u64>());
+        assert_eq!(total.objects_total_size, scanned.iter().map(|(_, _, s)| *s as u64).sum::<u64>());
+    }
+
     #[test]
     fn test_check_part_constants() {
         // Test that all CHECK_PART constants have expected values
@@ -6260,6 +7265,13 @@ mod tests {
         }];
         let single_result = get_complete_multipart_md5(&single_part);
         assert!(single_result.ends_with("-1"));
+
+        // quoted etags hash the same as bare ones
+        let quoted = vec![CompletePart {
+            part_num: 1,
+            etag: Some("\"d41d8cd98f00b204e9800998ecf8427e\"".to_string()),
+        }];
+        assert_eq!(get_complete_multipart_md5(&quoted), single_result);
     }
 
     #[test]
@@ -6629,4 +7641,128 @@ mod tests {
         assert_eq!(result2.len(), 3);
         assert!(result2.iter().all(|d| d.is_none()));
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_heal_object_keeps_parity() {
+        use tokio::io::AsyncReadExt;
+
+        let _guard = crate::store::test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = crate::store::test_util::test_store();
+        let bucket = "heal-keep-parity";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+        let body: Vec<u8> = (0..(2 << 20)).map(|i| (i % 251) as u8).collect();
+        let mut data = PutObjReader::from_vec(body.clone());
+        store
+            .put_object(bucket, "obj", &mut data, &ObjectOptions::default())
+            .await
+            .unwrap();
+
+        let set = store.pools[0].disk_set[0].clone();
+        let disks = set.disks.read().await.clone();
+        let parity_of = |metas: &[FileInfo]| metas.iter().map(|fi| fi.erasure.parity_blocks).collect::<Vec<_>>();
+
+        // Rewrite the object as if an older storage class had given it different parity.
+        let (metas, _) = SetDisks::read_all_fileinfo(&disks, "", bucket, "obj", "", true, true)
+            .await
+            .unwrap();
+        let current = set.storage_class_parity(&metas[0]);
+        let old_parity = if current == 1 { 2 } else { 1 };
+        set.reencode_object(bucket, "obj", &metas[0], &metas, &disks, old_parity)
+            .await
+            .unwrap();
+
+        // A drive losing the object is healed back to the layout the object has.
+        disks[1]
+            .as_ref()
+            .unwrap()
+            .delete(
+                bucket,
+                "obj",
+                DeleteOptions {
+                    recursive: true,
+                    immediate: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let (result, err) = set.heal_object(bucket, "obj", "", &HealOpts::default()).await.unwrap();
+        assert!(err.is_none(), "{err:?}");
+        assert_eq!((result.data_blocks, result.parity_blocks), (disks.len() - old_parity, old_parity));
+        let (metas, _) = SetDisks::read_all_fileinfo(&disks, "", bucket, "obj", "", true, true)
+            .await
+            .unwrap();
+        assert_eq!(parity_of(&metas), vec![old_parity; disks.len()]);
+
+        // Asking for the current parity migrates it.
+        let opts = HealOpts {
+            update_parity: true,
+            ..Default::default()
+        };
+        let (result, err) = set.heal_object(bucket, "obj", "", &opts).await.unwrap();
+        assert!(err.is_none(), "{err:?}");
+        assert_eq!(result.parity_blocks, current);
+        let (metas, _) = SetDisks::read_all_fileinfo(&disks, "", bucket, "obj", "", true, true)
+            .await
+            .unwrap();
+        assert_eq!(parity_of(&metas), vec![current; disks.len()]);
+
+        let mut reader = store
+            .get_object_reader(bucket, "obj", None, HeaderMap::new(), &ObjectOptions::default())
+            .await
+            .unwrap();
+        let mut read = Vec::new();
+        reader.stream.read_to_end(&mut read).await.unwrap();
+        assert!(read == body, "object data changed across the parity migration");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_heal_missing_data_dir() {
+        use tokio::io::AsyncReadExt;
+
+        let _guard = crate::store::test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = crate::store::test_util::test_store();
+        let bucket = "heal-missing-data-dir";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+        let body: Vec<u8> = (0..(2 << 20)).map(|i| (i % 241) as u8).collect();
+        let mut data = PutObjReader::from_vec(body.clone());
+        store
+            .put_object(bucket, "obj", &mut data, &ObjectOptions::default())
+            .await
+            .unwrap();
+
+        let set = store.pools[0].disk_set[0].clone();
+        let disk = set.disks.read().await[0].clone().unwrap();
+        let fi = disk
+            .read_version("", bucket, "obj", "", &ReadOptions::default())
+            .await
+            .unwrap();
+        let data_dir = disk.path().join(bucket).join("obj").join(fi.data_dir.unwrap().to_string());
+
+        // xl.meta survives an interrupted rename while the data dir it names does not
+        std::fs::remove_dir_all(&data_dir).unwrap();
+        let err = disk
+            .read_version("", bucket, "obj", "", &ReadOptions::default())
+            .await
+            .unwrap_err();
+        assert_eq!(err, DiskError::DataDirMissing);
+        // the raw xl.meta stays readable for the other versions it holds
+        assert!(disk.read_xl(bucket, "obj", false).await.is_ok());
+
+        let (result, err) = set.heal_object(bucket, "obj", "", &HealOpts::default()).await.unwrap();
+        assert!(err.is_none(), "{err:?}");
+        assert!(result.after.drives.iter().all(|d| d.state == DRIVE_STATE_OK), "{result:?}");
+        assert!(data_dir.is_dir());
+        disk.read_version("", bucket, "obj", "", &ReadOptions::default())
+            .await
+            .unwrap();
+
+        let mut reader = store
+            .get_object_reader(bucket, "obj", None, HeaderMap::new(), &ObjectOptions::default())
+            .await
+            .unwrap();
+        let mut read = Vec::new();
+        reader.stream.read_to_end(&mut read).await.unwrap();
+        assert!(read == body, "healed object differs from what was written");
+    }
 }
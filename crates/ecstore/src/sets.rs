@@ -17,34 +17,46 @@ use std::{collections::HashMap, sync::Arc};
 
 use crate::disk::error_reduce::count_errs;
 use crate::error::{Error, Result};
+use crate::rpc::CircuitBreakerConfig;
 use crate::{
     disk::{
         DiskAPI, DiskInfo, DiskOption, DiskStore,
         error::DiskError,
-        format::{DistributionAlgoVersion, FormatV3},
+        format::{DISTRIBUTION_ALGO_CURRENT, DistributionAlgoVersion, FormatV3},
         new_disk,
     },
-    endpoints::{Endpoints, PoolEndpoints},
+    endpoints::{EndpointServerPools, Endpoints, PoolEndpoints},
     error::StorageError,
-    global::{GLOBAL_LOCAL_DISK_SET_DRIVES, is_dist_erasure},
+    global::{
+        GLOBAL_LOCAL_DISK_SET_DRIVES, get_global_disk_direct_io_threshold, get_global_disk_meta_backup,
+        get_global_disk_min_free_bytes, get_global_disk_min_free_pct, get_global_disk_read_ahead, is_dist_erasure,
+    },
     heal::heal_commands::{
         DRIVE_STATE_CORRUPT, DRIVE_STATE_MISSING, DRIVE_STATE_OFFLINE, DRIVE_STATE_OK, HEAL_ITEM_METADATA, HealOpts,
     },
     set_disk::SetDisks,
     store_api::{
-        BucketInfo, BucketOptions, CompletePart, DeleteBucketOptions, DeletedObject, GetObjectReader, HTTPRangeSpec,
-        ListMultipartsInfo, ListObjectVersionsInfo, ListObjectsV2Info, MakeBucketOptions, MultipartInfo, MultipartUploadResult,
-        ObjectIO, ObjectInfo, ObjectOptions, ObjectToDelete, PartInfo, PutObjReader, StorageAPI,
+        BucketInfo, BucketOptions, ClusterHealth, CompletePart, DeleteBucketOptions, DeletePrefixReport, DeletedObject,
+        GetObjectReader, HTTPRangeSpec, ListMultipartsInfo, ListObjectVersionsInfo, ListObjectsV2Info, MakeBucketOptions,
+        MetaConsistencyReport, MultipartInfo, MultipartUploadResult, ObjectIO, ObjectInfo, ObjectInspectReport, ObjectOptions,
+        ObjectToDelete, PartInfo, PoolHealth, PutObjReader, ReplicationStats, StorageAPI, copy_object_range,
+    },
+    store_init::{
+        check_format_erasure_values, get_format_erasure_in_quorum, load_format_erasure_all, read_disk_ids, save_format_file,
     },
-    store_init::{check_format_erasure_values, get_format_erasure_in_quorum, load_format_erasure_all, save_format_file},
 };
 use futures::future::join_all;
 use http::HeaderMap;
 use rustfs_common::globals::GLOBAL_Local_Node_Name;
 use rustfs_filemeta::FileInfo;
-use rustfs_lock::{LockApi, namespace_lock::NsLockMap, new_lock_api};
+use rustfs_lock::{
+    LockApi,
+    namespace_lock::{DistNamespaceLock, LocalNamespaceLock, NamespaceLock, NsLockMap},
+    new_lock_api,
+};
 use rustfs_madmin::heal_commands::{HealDriveInfo, HealResultItem};
 use rustfs_utils::{crc_hash, path::path_join_buf, sip_hash};
+use time::OffsetDateTime;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
@@ -146,7 +158,19 @@ impl Sets {
                     disk = local_disk;
                 }
 
-                let has_disk_id = disk.as_ref().unwrap().get_disk_id().await.unwrap_or_else(|err| {
+                set_drive.push(disk);
+            }
+
+            // read the disk ids of the whole set at once instead of one drive at a time
+            let present: Vec<(usize, DiskStore)> = set_drive
+                .iter()
+                .enumerate()
+                .filter_map(|(j, d)| d.clone().map(|d| (j, d)))
+                .collect();
+            let stores: Vec<DiskStore> = present.iter().map(|(_, d)| d.clone()).collect();
+            let ids = read_disk_ids(&stores).await;
+            for ((j, _), id) in present.iter().zip(ids) {
+                let has_disk_id = id.unwrap_or_else(|err| {
                     if err == DiskError::UnformattedDisk {
                         error!("get_disk_id err {:?}", err);
                     } else {
@@ -156,20 +180,24 @@ impl Sets {
                     None
                 });
 
-                if let Some(_disk_id) = has_disk_id {
-                    set_drive.push(disk);
-                } else {
+                if has_disk_id.is_none() {
                     error!("sets new set_drive {}-{} get_disk_id is none", i, j);
-                    set_drive.push(None);
+                    set_drive[*j] = None;
                 }
             }
 
             // warn!("sets new set_drive {:?}", &set_drive);
 
+            let ns_lock: Arc<dyn NamespaceLock> = if is_dist_erasure().await {
+                Arc::new(DistNamespaceLock::new(GLOBAL_Local_Node_Name.read().await.to_string(), locker.clone()))
+            } else {
+                Arc::new(LocalNamespaceLock::new())
+            };
             let set_disks = SetDisks::new(
                 locker.clone(),
                 GLOBAL_Local_Node_Name.read().await.to_string(),
                 Arc::new(RwLock::new(NsLockMap::new(is_dist_erasure().await))),
+                ns_lock,
                 Arc::new(RwLock::new(set_drive)),
                 set_drive_count,
                 partiy_count,
@@ -217,6 +245,11 @@ impl Sets {
         self.set_drive_count
     }
 
+    /// Number of online disks in each erasure set, in set order.
+    pub async fn online_disk_counts(&self) -> Vec<usize> {
+        join_all(self.disk_set.iter().map(|set| set.online_disk_count())).await
+    }
+
     // pub async fn cleanup_deleted_objects_loop(self: Arc<Self>, mut rx: Receiver<()>) {
     //     tokio::time::sleep(Duration::from_secs(5)).await;
 
@@ -292,11 +325,7 @@ impl Sets {
     }
 
     fn get_hashed_set_index(&self, input: &str) -> usize {
-        match self.distribution_algo {
-            DistributionAlgoVersion::V1 => crc_hash(input, self.disk_set.len()),
-
-            DistributionAlgoVersion::V2 | DistributionAlgoVersion::V3 => sip_hash(input, self.disk_set.len(), self.id.as_bytes()),
-        }
+        hash_set_index(&self.distribution_algo, &self.id, self.disk_set.len(), input)
     }
 
     // async fn commit_rename_data_dir(
@@ -366,6 +395,12 @@ impl ObjectIO for Sets {
 
 #[async_trait::async_trait]
 impl StorageAPI for Sets {
+    #[tracing::instrument(skip(self))]
+    async fn shutdown(&self, force: bool) -> Result<()> {
+        let results = join_all(self.disk_set.iter().map(|set| set.shutdown(force))).await;
+        results.into_iter().find(|r| r.is_err()).unwrap_or(Ok(()))
+    }
+
     #[tracing::instrument(skip(self))]
     async fn backend_info(&self) -> rustfs_madmin::BackendInfo {
         unimplemented!()
@@ -457,11 +492,41 @@ impl StorageAPI for Sets {
         unimplemented!()
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn inspect_object(&self, bucket: &str, object: &str, version_id: Option<&str>) -> Result<ObjectInspectReport> {
+        self.get_disks_by_key(object).inspect_object(bucket, object, version_id).await
+    }
+
     #[tracing::instrument(skip(self))]
     async fn get_object_info(&self, bucket: &str, object: &str, opts: &ObjectOptions) -> Result<ObjectInfo> {
         self.get_disks_by_key(object).get_object_info(bucket, object, opts).await
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn get_object_infos(&self, bucket: &str, objects: &[String], opts: &ObjectOptions) -> Result<Vec<Result<ObjectInfo>>> {
+        let mut by_set: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (i, object) in objects.iter().enumerate() {
+            by_set.entry(self.get_hashed_set_index(object)).or_default().push(i);
+        }
+
+        let futures = by_set.into_iter().map(|(set_idx, idxs)| async move {
+            let names: Vec<String> = idxs.iter().map(|&i| objects[i].clone()).collect();
+            (idxs, self.get_disks(set_idx).get_object_infos(bucket, &names, opts).await)
+        });
+
+        let mut infos: Vec<Option<Result<ObjectInfo>>> = objects.iter().map(|_| None).collect();
+        for (idxs, res) in join_all(futures).await {
+            for (i, info) in idxs.into_iter().zip(res?) {
+                infos[i] = Some(info);
+            }
+        }
+
+        Ok(infos
+            .into_iter()
+            .map(|info| info.unwrap_or_else(|| Err(Error::other("no result"))))
+            .collect())
+    }
+
     #[tracing::instrument(skip(self))]
     async fn copy_object(
         &self,
@@ -619,6 +684,24 @@ impl StorageAPI for Sets {
         Ok((del_objects, del_errs))
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn delete_prefix(self: Arc<Self>, bucket: &str, prefix: &str, opts: ObjectOptions) -> Result<DeletePrefixReport> {
+        let results = join_all(
+            self.disk_set
+                .iter()
+                .map(|set| set.clone().delete_prefix(bucket, prefix, opts.clone())),
+        )
+        .await;
+
+        let mut report = DeletePrefixReport::default();
+        for result in results {
+            let set_report = result?;
+            report.deleted += set_report.deleted;
+            report.skipped.extend(set_report.skipped);
+        }
+        Ok(report)
+    }
+
     #[tracing::instrument(skip(self))]
     async fn list_multipart_uploads(
         &self,
@@ -658,19 +741,33 @@ impl StorageAPI for Sets {
     #[tracing::instrument(skip(self))]
     async fn copy_object_part(
         &self,
-        _src_bucket: &str,
-        _src_object: &str,
-        _dst_bucket: &str,
-        _dst_object: &str,
-        _upload_id: &str,
-        _part_id: usize,
-        _start_offset: i64,
-        _length: i64,
-        _src_info: &ObjectInfo,
-        _src_opts: &ObjectOptions,
-        _dst_opts: &ObjectOptions,
-    ) -> Result<()> {
-        unimplemented!()
+        src_bucket: &str,
+        src_object: &str,
+        dst_bucket: &str,
+        dst_object: &str,
+        upload_id: &str,
+        part_id: usize,
+        start_offset: i64,
+        length: i64,
+        src_info: &ObjectInfo,
+        src_opts: &ObjectOptions,
+        dst_opts: &ObjectOptions,
+    ) -> Result<PartInfo> {
+        copy_object_range(
+            self,
+            src_bucket,
+            src_object,
+            dst_bucket,
+            dst_object,
+            upload_id,
+            part_id,
+            start_offset,
+            length,
+            src_info,
+            src_opts,
+            dst_opts,
+        )
+        .await
     }
 
     #[tracing::instrument(skip(self))]
@@ -732,6 +829,12 @@ impl StorageAPI for Sets {
         unimplemented!()
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn health(&self) -> ClusterHealth {
+        let online = self.online_disk_counts().await;
+        ClusterHealth::from_pools(vec![PoolHealth::new(self.set_drive_count, self.default_parity_count, &online)])
+    }
+
     #[tracing::instrument(skip(self))]
     async fn put_object_metadata(&self, bucket: &str, object: &str, opts: &ObjectOptions) -> Result<ObjectInfo> {
         self.get_disks_by_key(object).put_object_metadata(bucket, object, opts).await
@@ -754,6 +857,26 @@ impl StorageAPI for Sets {
         self.get_disks_by_key(object).delete_object_tags(bucket, object, opts).await
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn clear_object_retention(&self, bucket: &str, object: &str, version_id: &str, bypass: bool) -> Result<()> {
+        self.get_disks_by_key(object)
+            .clear_object_retention(bucket, object, version_id, bypass)
+            .await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn touch_object(
+        &self,
+        bucket: &str,
+        object: &str,
+        version_id: Option<&str>,
+        new_mtime: OffsetDateTime,
+    ) -> Result<ObjectInfo> {
+        self.get_disks_by_key(object)
+            .touch_object(bucket, object, version_id, new_mtime)
+            .await
+    }
+
     #[tracing::instrument(skip(self))]
     async fn heal_format(&self, dry_run: bool) -> Result<(HealResultItem, Option<Error>)> {
         let (disks, _) = init_storage_disks_with_errors(
@@ -761,6 +884,12 @@ impl StorageAPI for Sets {
             &DiskOption {
                 cleanup: false,
                 health_check: false,
+                direct_io_threshold: get_global_disk_direct_io_threshold(),
+                read_ahead: get_global_disk_read_ahead(),
+                meta_backup: get_global_disk_meta_backup(),
+                min_free_bytes: get_global_disk_min_free_bytes(),
+                min_free_pct: get_global_disk_min_free_pct(),
+                circuit_breaker: Some(CircuitBreakerConfig::default()),
             },
         )
         .await;
@@ -856,6 +985,27 @@ impl StorageAPI for Sets {
             .await
     }
     #[tracing::instrument(skip(self))]
+    async fn scrub_object(&self, bucket: &str, object: &str, opts: &HealOpts) -> Result<Vec<(String, HealResultItem)>> {
+        self.get_disks_by_key(object).scrub_object(bucket, object, opts).await
+    }
+    #[tracing::instrument(skip(self))]
+    async fn verify_metadata_consistency(&self, bucket: &str, object: &str) -> Result<MetaConsistencyReport> {
+        self.get_disks_by_key(object)
+            .verify_metadata_consistency(bucket, object)
+            .await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn replication_stats(self: Arc<Self>, bucket: &str) -> Result<ReplicationStats> {
+        let results = join_all(self.disk_set.iter().map(|set| set.clone().replication_stats(bucket))).await;
+
+        let mut stats = ReplicationStats::default();
+        for result in results {
+            stats.merge(result?);
+        }
+        Ok(stats)
+    }
+    #[tracing::instrument(skip(self))]
     async fn heal_objects(
         &self,
         _bucket: &str,
@@ -876,7 +1026,7 @@ impl StorageAPI for Sets {
     }
 }
 
-async fn _close_storage_disks(disks: &[Option<DiskStore>]) {
+pub(crate) async fn close_storage_disks(disks: &[Option<DiskStore>]) {
     let mut futures = Vec::with_capacity(disks.len());
     for disk in disks.iter().flatten() {
         let disk = disk.clone();
@@ -1007,3 +1157,32 @@ fn new_heal_format_sets(
 
     (new_formats, current_disks_info)
 }
+
+/// Set an object key hashes to among `set_count` sets of the deployment.
+pub fn hash_set_index(algo: &DistributionAlgoVersion, deployment_id: &Uuid, set_count: usize, key: &str) -> usize {
+    match algo {
+        DistributionAlgoVersion::V1 => crc_hash(key, set_count),
+
+        DistributionAlgoVersion::V2 | DistributionAlgoVersion::V3 => sip_hash(key, set_count, deployment_id.as_bytes()),
+    }
+}
+
+/// Computes where an object is placed without a running store, one `(pool, set)` per pool.
+///
+/// Within a pool the set only depends on the object name and the deployment id, but which pool
+/// a new object goes to is picked by free space at write time, so every pool is a candidate.
+/// Assumes [`DISTRIBUTION_ALGO_CURRENT`], a deployment upgraded from a format without a distribution
+/// algorithm keeps CRC hashing, see [`hash_set_index`].
+pub fn object_set_index(pools: &EndpointServerPools, deployment_id: &Uuid, _bucket: &str, object: &str) -> Vec<(usize, usize)> {
+    pools
+        .as_ref()
+        .iter()
+        .enumerate()
+        .map(|(pool_idx, pool)| {
+            (
+                pool_idx,
+                hash_set_index(&DISTRIBUTION_ALGO_CURRENT, deployment_id, pool.set_count, object),
+            )
+        })
+        .collect()
+}
@@ -13,13 +13,26 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::bucket::lifecycle::bucket_lifecycle_ops::init_background_expiry;
+use crate::audit::{self, AuditOperation};
+use crate::bucket::encryption::{apply_default_encryption, is_encryption_requested};
+use crate::bucket::lifecycle::bucket_lifecycle_ops::{init_background_expiry, set_object_expiration};
+use crate::bucket::metadata::{BUCKET_COMPRESSION_CONFIG, BUCKET_WRITE_QUORUM_CONFIG};
 use crate::bucket::metadata_sys::{self, set_bucket_metadata};
-use crate::bucket::utils::{check_valid_bucket_name, check_valid_bucket_name_strict, is_meta_bucketname};
+use crate::bucket::object_lock::ObjectLockApi;
+use crate::bucket::object_lock::objectlock;
+use crate::bucket::object_lock::objectlock_sys::{
+    apply_default_retention, apply_default_retention_meta, check_put_against_lock, enforce_retention_bypass_for_delete,
+    enforce_retention_for_deletion,
+};
+use crate::bucket::utils::{check_valid_bucket_name, check_valid_bucket_name_strict, is_meta_bucketname, validate_bucket_name};
+use crate::cache_value::list_cache::{GLOBAL_LIST_CACHE, ListKey};
+use crate::cache_value::negative_cache::GLOBAL_NEGATIVE_CACHE;
+use crate::cache_value::upload_idempotency::GLOBAL_UPLOAD_IDEMPOTENCY;
+use crate::compress::{CompressionOpts, compress_put_reader, put_compression};
 use crate::config::GLOBAL_StorageClass;
 use crate::config::storageclass;
 use crate::disk::endpoint::{Endpoint, EndpointType};
-use crate::disk::{DiskAPI, DiskInfo, DiskInfoOptions};
+use crate::disk::{DiskAPI, DiskInfo, DiskInfoOptions, with_deadline};
 use crate::error::{Error, Result};
 use crate::error::{
     StorageError, is_err_bucket_exists, is_err_invalid_upload_id, is_err_object_not_found, is_err_read_quorum,
@@ -27,19 +40,29 @@ use crate::error::{
 };
 use crate::global::{
     DISK_ASSUME_UNKNOWN_SIZE, DISK_FILL_FRACTION, DISK_MIN_INODES, DISK_RESERVE_FRACTION, GLOBAL_BOOT_TIME,
-    GLOBAL_LOCAL_DISK_MAP, GLOBAL_LOCAL_DISK_SET_DRIVES, GLOBAL_TierConfigMgr, get_global_endpoints, is_dist_erasure,
-    is_erasure_sd, set_global_deployment_id, set_object_layer,
+    GLOBAL_BackgroundHealRoutine, GLOBAL_LOCAL_DISK_MAP, GLOBAL_LOCAL_DISK_SET_DRIVES, GLOBAL_MRFState, GLOBAL_TierConfigMgr,
+    get_global_disk_direct_io_threshold, get_global_disk_meta_backup, get_global_disk_min_free_bytes,
+    get_global_disk_min_free_pct, get_global_disk_read_ahead, get_global_endpoints, get_global_max_list_keys,
+    get_global_max_user_metadata_size, get_global_max_versions_per_object, is_dist_erasure, is_erasure_sd, is_global_read_only,
+    set_global_deployment_id, set_object_layer,
 };
-use crate::heal::data_usage::{DATA_USAGE_ROOT, DataUsageInfo};
-use crate::heal::data_usage_cache::{DataUsageCache, DataUsageCacheInfo};
-use crate::heal::heal_commands::{HEAL_ITEM_METADATA, HealOpts, HealScanMode};
+use crate::heal::data_scanner::{ScannerThrottle, stop_data_scanner};
+use crate::heal::data_usage::{DATA_USAGE_ROOT, DataUsageInfo, load_bucket_usage_from_backend};
+use crate::heal::data_usage_cache::{DataUsageCache, DataUsageCacheInfo, DataUsageEntry};
+use crate::heal::heal_commands::{HEAL_ITEM_METADATA, HEAL_ITEM_OBJECT, HealOpts, HealScanMode};
 use crate::heal::heal_ops::{HealEntryFn, HealSequence};
+use crate::kms::{GLOBAL_KMS, encrypt_put_reader};
 use crate::new_object_layer_fn;
 use crate::notification_sys::get_global_notification_sys;
 use crate::pools::PoolMeta;
 use crate::rebalance::RebalanceMeta;
-use crate::store_api::{ListMultipartsInfo, ListObjectVersionsInfo, MultipartInfo, ObjectIO};
+use crate::rpc::CircuitBreakerConfig;
+use crate::store_api::{
+    ClusterHealth, DeletePrefixReport, ListMultipartsInfo, ListObjectVersionsInfo, MetaConsistencyReport, MultipartInfo,
+    ObjectIO, ObjectInspectReport, PoolHealth, ReplicationStats, check_if_match, copy_object_range,
+};
 use crate::store_init::{check_disk_fatal_errs, ec_drives_no_config};
+use crate::store_list_objects::clamp_max_keys;
 use crate::{
     bucket::{lifecycle::bucket_lifecycle_ops::TransitionState, metadata::BucketMetadata},
     disk::{BUCKET_META_PREFIX, DiskOption, DiskStore, RUSTFS_META_BUCKET, new_disk},
@@ -54,6 +77,7 @@ use crate::{
     store_init,
 };
 use futures::future::join_all;
+use futures::{StreamExt as _, stream};
 use glob::Pattern;
 use http::HeaderMap;
 use lazy_static::lazy_static;
@@ -61,6 +85,8 @@ use rand::Rng as _;
 use rustfs_common::globals::{GLOBAL_Local_Node_Name, GLOBAL_Rustfs_Host, GLOBAL_Rustfs_Port};
 use rustfs_filemeta::FileInfo;
 use rustfs_filemeta::MetaCacheEntry;
+use rustfs_filemeta::headers::RESERVED_METADATA_PREFIX_LOWER;
+use rustfs_lock::namespace_lock::{DistNamespaceLock, LocalNamespaceLock, LockGuard, NamespaceLock};
 use rustfs_madmin::heal_commands::HealResultItem;
 use rustfs_utils::crypto::base64_decode;
 use rustfs_utils::path::{SLASH_SEPARATOR, decode_dir_object, encode_dir_object, path_join_buf};
@@ -69,8 +95,12 @@ use std::cmp::Ordering;
 use std::net::SocketAddr;
 use std::process::exit;
 use std::slice::Iter;
-use std::time::SystemTime;
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::time::{Instant, SystemTime};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
 use time::OffsetDateTime;
 use tokio::select;
 use tokio::sync::mpsc::Sender;
@@ -81,6 +111,16 @@ use tracing::{error, warn};
 use uuid::Uuid;
 
 const MAX_UPLOADS_LIST: usize = 10000;
+/// How long a non-forced shutdown waits for queued heal tasks to be picked up.
+const SHUTDOWN_HEAL_DRAIN_TIMEOUT: Duration = Duration::from_secs(60);
+
+// delete_prefix lists this many keys at a time and deletes them in batches, a few batches at once
+const DELETE_PREFIX_PAGE_SIZE: i32 = 1000;
+const DELETE_PREFIX_BATCH_SIZE: usize = 100;
+const DELETE_PREFIX_CONCURRENCY: usize = 4;
+
+// How long a writer waits for another writer of the same object to finish.
+const OBJECT_LOCK_TIMEOUT: Duration = Duration::from_secs(30);
 
 #[derive(Debug)]
 pub struct ECStore {
@@ -93,6 +133,8 @@ pub struct ECStore {
     pub pool_meta: RwLock<PoolMeta>,
     pub rebalance_meta: RwLock<Option<RebalanceMeta>>,
     pub decommission_cancelers: Vec<Option<usize>>,
+    // serializes read-modify-write of an object's versions across the cluster
+    pub ns_lock: Arc<dyn NamespaceLock>,
 }
 
 // impl Clone for ECStore {
@@ -159,6 +201,12 @@ impl ECStore {
                 &DiskOption {
                     cleanup: true,
                     health_check: true,
+                    direct_io_threshold: get_global_disk_direct_io_threshold(),
+                    read_ahead: get_global_disk_read_ahead(),
+                    meta_backup: get_global_disk_meta_backup(),
+                    min_free_bytes: get_global_disk_min_free_bytes(),
+                    min_free_pct: get_global_disk_min_free_pct(),
+                    circuit_breaker: Some(CircuitBreakerConfig::default()),
                 },
             )
             .await;
@@ -237,6 +285,16 @@ impl ECStore {
         pool_meta.dont_save = true;
 
         let decommission_cancelers = vec![None; pools.len()];
+        // like NewNSLock, object locks are always taken on the lock servers of the first pool
+        let ns_lock: Arc<dyn NamespaceLock> = if is_dist_erasure().await {
+            Arc::new(DistNamespaceLock::new(
+                GLOBAL_Local_Node_Name.read().await.to_string(),
+                pools[0].lockers[0].clone(),
+            ))
+        } else {
+            Arc::new(LocalNamespaceLock::new())
+        };
+
         let ec = Arc::new(ECStore {
             id: deployment_id.unwrap(),
             disk_map,
@@ -245,6 +303,7 @@ impl ECStore {
             pool_meta: RwLock::new(pool_meta),
             rebalance_meta: RwLock::new(None),
             decommission_cancelers,
+            ns_lock,
         });
 
         if let Some(dep_id) = deployment_id {
@@ -518,6 +577,20 @@ impl ECStore {
         Ok(())
     }
 
+    // Removes a bucket whose creation did not complete, errors are ignored.
+    async fn rollback_make_bucket(&self, bucket: &str) {
+        let _ = self
+            .delete_bucket(
+                bucket,
+                &DeleteBucketOptions {
+                    no_lock: true,
+                    no_recreate: true,
+                    ..Default::default()
+                },
+            )
+            .await;
+    }
+
     async fn get_available_pool_idx(&self, bucket: &str, object: &str, size: i64) -> Option<usize> {
         // // 先随机返回一个
 
@@ -811,11 +884,41 @@ impl ECStore {
         errs
     }
 
+    /// Runs one usage scan over every drive and returns the merged cluster cache.
+    ///
+    /// Entries are streamed to `updates` as the drives walk their buckets; lifecycle actions are
+    /// applied along the way and the scanner throttle is respected per drive.
+    #[tracing::instrument(skip(self, updates))]
+    pub async fn scan_usage(&self, updates: Sender<DataUsageEntry>, scan_mode: HealScanMode) -> Result<DataUsageCache> {
+        let buckets = self.list_bucket(&BucketOptions::default()).await?;
+
+        let futures = self
+            .pools
+            .iter()
+            .flat_map(|pool| pool.disk_set.iter())
+            .map(|set| set.scan_usage(&buckets, updates.clone(), scan_mode));
+        let results = join_all(futures).await;
+
+        let mut cache = DataUsageCache {
+            info: DataUsageCacheInfo {
+                name: DATA_USAGE_ROOT.to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        for result in results {
+            cache.merge(&result?);
+        }
+        cache.info.last_update = Some(SystemTime::now());
+        Ok(cache)
+    }
+
     pub async fn ns_scanner(
         &self,
         updates: Sender<DataUsageInfo>,
         want_cycle: usize,
         heal_scan_mode: HealScanMode,
+        throttle: ScannerThrottle,
     ) -> Result<()> {
         info!("ns_scanner updates - {}", want_cycle);
         let all_buckets = self.list_bucket(&BucketOptions::default()).await?;
@@ -857,7 +960,7 @@ impl ECStore {
                     });
                     if let Err(err) = set
                         .clone()
-                        .ns_scanner(&all_buckets_clone, want_cycle as u32, tx, heal_scan_mode)
+                        .ns_scanner(&all_buckets_clone, want_cycle as u32, tx, heal_scan_mode, throttle)
                         .await
                     {
                         let mut f_w = first_err_clone.write().await;
@@ -928,6 +1031,25 @@ impl ECStore {
         Ok(())
     }
 
+    // `object` must already be encoded
+    async fn get_object_info_uncached(&self, bucket: &str, object: &str, opts: &ObjectOptions) -> Result<ObjectInfo> {
+        let res = with_deadline(opts.deadline, async {
+            if self.single_pool() {
+                self.pools[0].get_object_info(bucket, object, opts).await
+            } else {
+                // TODO: nslock
+
+                let (info, _) = self.get_latest_object_info_with_idx(bucket, object, opts).await?;
+                Ok(info)
+            }
+        })
+        .await;
+        let mut info = past_deadline(opts.deadline, res)?;
+
+        set_object_expiration(&mut info).await;
+        Ok(info)
+    }
+
     async fn get_latest_object_info_with_idx(
         &self,
         bucket: &str,
@@ -1066,6 +1188,119 @@ impl ECStore {
         // *self.pool_meta.write().unwrap() = meta;
         Ok(())
     }
+
+    async fn object_lock_config(bucket: &str) -> Option<ObjectLockConfiguration> {
+        // bucket metadata itself is written under the metadata sys lock
+        if is_meta_bucketname(bucket) {
+            return None;
+        }
+
+        metadata_sys::get_object_lock_config(bucket)
+            .await
+            .ok()
+            .map(|(cfg, _)| cfg)
+            .filter(|cfg| cfg.enabled())
+    }
+
+    // `opts` with the bucket default retention added, moved data keeps the retention it was written with.
+    async fn with_default_retention(bucket: &str, opts: &ObjectOptions) -> Option<ObjectOptions> {
+        if opts.data_movement {
+            return None;
+        }
+        let cfg = Self::object_lock_config(bucket).await?;
+        let mut opts = opts.clone();
+        apply_default_retention(&cfg, &mut opts, objectlock::utc_now_ntp());
+        Some(opts)
+    }
+
+    // Only a put that targets an existing version replaces it, any other put adds a new
+    // version next to the locked one.
+    async fn check_put_lock(&self, bucket: &str, object: &str, opts: &ObjectOptions) -> Result<()> {
+        if opts.data_movement || (opts.versioned && opts.version_id.is_none()) {
+            return Ok(());
+        }
+
+        let Some(cfg) = Self::object_lock_config(bucket).await else {
+            return Ok(());
+        };
+
+        let get_opts = ObjectOptions {
+            version_id: opts.version_id.clone(),
+            versioned: opts.versioned,
+            version_suspended: opts.version_suspended,
+            ..Default::default()
+        };
+        match self.get_object_info(bucket, object, &get_opts).await {
+            Ok(info) => check_put_against_lock(&info, &cfg),
+            Err(err) if is_err_object_not_found(&err) || is_err_version_not_found(&err) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn start_multipart_upload(&self, bucket: &str, object: &str, opts: &ObjectOptions) -> Result<MultipartUploadResult> {
+        if self.single_pool() {
+            return self.pools[0].new_multipart_upload(bucket, object, opts).await;
+        }
+
+        for (idx, pool) in self.pools.iter().enumerate() {
+            if self.is_suspended(idx).await || self.is_pool_rebalancing(idx).await {
+                continue;
+            }
+            let res = pool
+                .list_multipart_uploads(bucket, object, None, None, None, MAX_UPLOADS_LIST)
+                .await?;
+
+            if !res.uploads.is_empty() {
+                return self.pools[idx].new_multipart_upload(bucket, object, opts).await;
+            }
+        }
+        let idx = self.get_pool_idx(bucket, object, -1).await?;
+        if opts.data_movement && idx == opts.src_pool_idx {
+            return Err(StorageError::DataMovementOverwriteErr(
+                bucket.to_owned(),
+                object.to_owned(),
+                "".to_owned(),
+            ));
+        }
+
+        self.pools[idx].new_multipart_upload(bucket, object, opts).await
+    }
+
+    /// Compresses puts into the bucket that don't set `ObjectOptions::compression`, `None` turns it off.
+    pub async fn set_bucket_compression(&self, bucket: &str, opts: Option<CompressionOpts>) -> Result<()> {
+        self.get_bucket_info(bucket, &BucketOptions::default()).await?;
+
+        let data = match opts {
+            Some(opts) => serde_json::to_vec(&opts)?,
+            None => Vec::new(),
+        };
+
+        metadata_sys::update(bucket, BUCKET_COMPRESSION_CONFIG, data).await?;
+        Ok(())
+    }
+
+    /// Requires `quorum` drives to commit every put into the bucket, `None` restores the pool default.
+    pub async fn set_bucket_write_quorum(&self, bucket: &str, quorum: Option<usize>) -> Result<()> {
+        self.get_bucket_info(bucket, &BucketOptions::default()).await?;
+
+        let data = match quorum {
+            Some(quorum) => {
+                let max = self.pools.iter().map(|pool| pool.set_drive_count).min().unwrap_or_default();
+                if quorum == 0 || quorum > max {
+                    return Err(StorageError::InvalidArgument(
+                        bucket.to_string(),
+                        String::new(),
+                        format!("write quorum {quorum} is not achievable with {max} drives per set"),
+                    ));
+                }
+                serde_json::to_vec(&quorum)?
+            }
+            None => Vec::new(),
+        };
+
+        metadata_sys::update(bucket, BUCKET_WRITE_QUORUM_CONFIG, data).await?;
+        Ok(())
+    }
 }
 
 #[tracing::instrument(level = "info", skip(all_buckets, updates))]
@@ -1133,6 +1368,12 @@ pub async fn init_local_disks(endpoint_pools: EndpointServerPools) -> Result<()>
     let opt = &DiskOption {
         cleanup: true,
         health_check: true,
+        direct_io_threshold: get_global_disk_direct_io_threshold(),
+        read_ahead: get_global_disk_read_ahead(),
+        meta_backup: get_global_disk_meta_backup(),
+        min_free_bytes: get_global_disk_min_free_bytes(),
+        min_free_pct: get_global_disk_min_free_pct(),
+        circuit_breaker: Some(CircuitBreakerConfig::default()),
     };
 
     let mut global_set_drives = GLOBAL_LOCAL_DISK_SET_DRIVES.write().await;
@@ -1146,24 +1387,16 @@ pub async fn init_local_disks(endpoint_pools: EndpointServerPools) -> Result<()>
     }
 
     let mut global_local_disk_map = GLOBAL_LOCAL_DISK_MAP.write().await;
+    global_local_disk_map.reserve(endpoint_pools.local_disk_count());
 
-    for pool_eps in endpoint_pools.as_ref().iter() {
-        let mut set_drives = HashMap::new();
-        for ep in pool_eps.endpoints.as_ref().iter() {
-            if !ep.is_local {
-                continue;
-            }
-
-            let disk = new_disk(ep, opt).await?;
-
-            let path = disk.endpoint().to_string();
+    for ep in endpoint_pools.local_endpoints().iter() {
+        let disk = new_disk(ep, opt).await?;
 
-            global_local_disk_map.insert(path, Some(disk.clone()));
+        let path = disk.endpoint().to_string();
 
-            set_drives.insert(ep.disk_idx, Some(disk.clone()));
+        global_local_disk_map.insert(path, Some(disk.clone()));
 
-            global_set_drives[ep.pool_idx as usize][ep.set_idx as usize][ep.disk_idx as usize] = Some(disk.clone());
-        }
+        global_set_drives[ep.pool_idx as usize][ep.set_idx as usize][ep.disk_idx as usize] = Some(disk.clone());
     }
 
     Ok(())
@@ -1219,6 +1452,147 @@ impl Clone for PoolObjInfo {
 //     pub limit: i32,
 // }
 
+impl ECStore {
+    // Every version of `object`, newest first.
+    async fn object_versions(&self, bucket: &str, object: &str) -> Result<Vec<ObjectInfo>> {
+        let Some(store) = new_object_layer_fn() else {
+            return Err(Error::other("errServerNotInitialized"));
+        };
+
+        // the versions of a key are listed newest first, ahead of the longer keys it prefixes
+        let mut versions = Vec::new();
+        let (mut marker, mut version_marker) = (None, None);
+        loop {
+            let page = store
+                .clone()
+                .list_object_versions(bucket, object, marker, version_marker, None, DELETE_PREFIX_PAGE_SIZE)
+                .await?;
+            let done = !page.is_truncated || page.objects.iter().any(|oi| oi.name != object);
+            versions.extend(page.objects.into_iter().filter(|oi| oi.name == object));
+            if done {
+                break;
+            }
+            (marker, version_marker) = (page.next_marker, page.next_version_idmarker);
+        }
+
+        Ok(versions)
+    }
+
+    // The oldest noncurrent versions of `object` that have to go for `incoming` more versions to fit
+    // under `max_versions`, fails with TooManyVersions when one of them is locked.
+    async fn versions_over_limit(
+        &self,
+        bucket: &str,
+        object: &str,
+        max_versions: usize,
+        incoming: usize,
+    ) -> Result<Vec<ObjectInfo>> {
+        let versions = self.object_versions(bucket, &decode_dir_object(object)).await?;
+        let Some(excess) = (versions.len() + incoming).checked_sub(max_versions).filter(|&n| n > 0) else {
+            return Ok(Vec::new());
+        };
+
+        let over: Vec<ObjectInfo> = versions.into_iter().rev().filter(|oi| !oi.is_latest).take(excess).collect();
+        if over.iter().any(enforce_retention_for_deletion) && Self::object_lock_config(bucket).await.is_some() {
+            return Err(StorageError::TooManyVersions(bucket.to_owned(), decode_dir_object(object)));
+        }
+
+        Ok(over)
+    }
+
+    // Takes the object lock `with_version_limit` has to run under, when a version limit applies.
+    async fn lock_for_version_limit(&self, bucket: &str, object: &str, versioned: bool) -> Result<Option<LockGuard>> {
+        if !versioned || get_global_max_versions_per_object().is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some(self.lock_object(bucket, object).await?))
+    }
+
+    // Runs `write`, which adds a version of `object`, under the max versions per object limit: it is
+    // rejected up front with TooManyVersions when the versions making room for it are locked, and the
+    // oldest noncurrent versions over the limit are pruned once it is committed. The caller holds the
+    // object lock.
+    async fn with_version_limit<F>(&self, bucket: &str, object: &str, versioned: bool, write: F) -> Result<ObjectInfo>
+    where
+        F: Future<Output = Result<ObjectInfo>>,
+    {
+        let Some(max_versions) = get_global_max_versions_per_object().filter(|_| versioned) else {
+            return write.await;
+        };
+
+        self.versions_over_limit(bucket, object, max_versions, 1).await?;
+        let info = write.await?;
+
+        for oldest in self.versions_over_limit(bucket, object, max_versions, 0).await? {
+            let version_id = oldest.version_id.map(|v| v.to_string());
+            let opts = ObjectOptions {
+                version_id: version_id.clone(),
+                versioned: true,
+                ..Default::default()
+            };
+            self.delete_object(bucket, &decode_dir_object(object), opts).await?;
+            let note = HealResultItem {
+                heal_item_type: HEAL_ITEM_OBJECT.to_string(),
+                bucket: bucket.to_owned(),
+                object: decode_dir_object(object),
+                version_id: version_id.unwrap_or_default(),
+                detail: format!("pruned, over the limit of {max_versions} versions per object"),
+                ..Default::default()
+            };
+            info!("{:?}", note);
+        }
+
+        Ok(info)
+    }
+
+    // Write locks an object so checks on its versions and the write depending on them are not
+    // interleaved with another writer.
+    pub(crate) async fn lock_object(&self, bucket: &str, object: &str) -> Result<LockGuard> {
+        Ok(self
+            .ns_lock
+            .lock(&[path_join_buf(&[bucket, object])], OBJECT_LOCK_TIMEOUT)
+            .await?)
+    }
+
+    // The source version id for a copy that keeps it, the destination has to be versioned and must
+    // not hold a version with that id already.
+    async fn preserved_version_id(
+        &self,
+        dst_bucket: &str,
+        dst_object: &str,
+        src_info: &ObjectInfo,
+        dst_opts: &ObjectOptions,
+    ) -> Result<String> {
+        let Some(version_id) = src_info.version_id.filter(|vid| !vid.is_nil()) else {
+            return Err(StorageError::InvalidArgument(
+                dst_bucket.to_owned(),
+                dst_object.to_owned(),
+                "source object has no version id to preserve".to_owned(),
+            ));
+        };
+        if !dst_opts.versioned {
+            return Err(StorageError::InvalidArgument(
+                dst_bucket.to_owned(),
+                dst_object.to_owned(),
+                "preserving the version id needs a versioned destination".to_owned(),
+            ));
+        }
+
+        let version_id = version_id.to_string();
+        let get_opts = ObjectOptions {
+            version_id: Some(version_id.clone()),
+            versioned: true,
+            ..Default::default()
+        };
+        match self.get_object_info(dst_bucket, dst_object, &get_opts).await {
+            Ok(_) => Err(StorageError::VersionIdConflict(dst_bucket.to_owned(), dst_object.to_owned(), version_id)),
+            Err(err) if is_err_object_not_found(&err) || is_err_version_not_found(&err) => Ok(version_id),
+            Err(err) => Err(err),
+        }
+    }
+}
+
 #[async_trait::async_trait]
 impl ObjectIO for ECStore {
     #[tracing::instrument(level = "debug", skip(self))]
@@ -1230,48 +1604,113 @@ impl ObjectIO for ECStore {
         h: HeaderMap,
         opts: &ObjectOptions,
     ) -> Result<GetObjectReader> {
-        check_get_obj_args(bucket, object)?;
+        audit::record_get(bucket, object, async move {
+            check_get_obj_args(bucket, object)?;
 
-        let object = encode_dir_object(object);
+            let object = encode_dir_object(object);
 
-        if self.single_pool() {
-            return self.pools[0].get_object_reader(bucket, object.as_str(), range, h, opts).await;
-        }
+            let res = with_deadline(opts.deadline, async {
+                if self.single_pool() {
+                    self.pools[0].get_object_reader(bucket, object.as_str(), range, h, opts).await
+                } else {
+                    // TODO: nslock
 
-        // TODO: nslock
+                    let mut opts = opts.clone();
 
-        let mut opts = opts.clone();
+                    opts.no_lock = true;
 
-        opts.no_lock = true;
+                    // TODO: check if DeleteMarker
+                    let (_oi, idx) = self.get_latest_object_info_with_idx(bucket, &object, &opts).await?;
 
-        // TODO: check if DeleteMarker
-        let (_oi, idx) = self.get_latest_object_info_with_idx(bucket, &object, &opts).await?;
+                    self.pools[idx]
+                        .get_object_reader(bucket, object.as_str(), range, h, &opts)
+                        .await
+                }
+            })
+            .await;
+            let mut reader = past_deadline(opts.deadline, res)?;
 
-        self.pools[idx]
-            .get_object_reader(bucket, object.as_str(), range, h, &opts)
-            .await
+            set_object_expiration(&mut reader.object_info).await;
+            Ok(reader)
+        })
+        .await
     }
     #[tracing::instrument(level = "debug", skip(self, data))]
     async fn put_object(&self, bucket: &str, object: &str, data: &mut PutObjReader, opts: &ObjectOptions) -> Result<ObjectInfo> {
-        check_put_object_args(bucket, object)?;
+        audit::record(AuditOperation::PutObject, bucket, object, audit::written_size, async move {
+            check_read_only(bucket)?;
 
-        let object = encode_dir_object(object);
+            check_put_object_args(bucket, object)?;
+            validate_user_metadata(&opts.user_defined)?;
+            self.check_put_lock(bucket, object, opts).await?;
 
-        if self.single_pool() {
-            return self.pools[0].put_object(bucket, object.as_str(), data, opts).await;
-        }
+            let retained_opts = Self::with_default_retention(bucket, opts).await;
+            let opts = retained_opts.as_ref().unwrap_or(opts);
 
-        let idx = self.get_pool_idx(bucket, &object, data.size()).await?;
+            let object = encode_dir_object(object);
+            let _invalidate = GLOBAL_NEGATIVE_CACHE.invalidate_on_drop(bucket, [object.as_str()]);
+            let _invalidate_list = GLOBAL_LIST_CACHE.invalidate_on_drop(bucket, [object.as_str()]);
 
-        if opts.data_movement && idx == opts.src_pool_idx {
-            return Err(StorageError::DataMovementOverwriteErr(
-                bucket.to_owned(),
-                object.to_owned(),
-                opts.version_id.clone().unwrap_or_default(),
-            ));
-        }
+            let encrypted_opts;
+            // bucket metadata itself is written under the metadata sys lock
+            let bucket_meta = if is_meta_bucketname(bucket) {
+                None
+            } else {
+                metadata_sys::get(bucket).await.ok()
+            };
+            let opts = match bucket_meta {
+                Some(meta) if meta.sse_config.is_some() && !is_encryption_requested(opts) => {
+                    let mut o = opts.clone();
+                    apply_default_encryption(&meta, &mut o);
+                    encrypted_opts = o;
+                    &encrypted_opts
+                }
+                _ => opts,
+            };
+
+            let sealed_opts;
+            let opts = if GLOBAL_KMS.encrypts_put(opts) {
+                sealed_opts = encrypt_put_reader(bucket, &object, data, opts).await?;
+                &sealed_opts
+            } else {
+                opts
+            };
+
+            let compressed_opts;
+            let opts = match put_compression(bucket, &object, data, opts).await {
+                Some(algorithm) => {
+                    compressed_opts = compress_put_reader(data, opts, algorithm)?;
+                    &compressed_opts
+                }
+                None => opts,
+            };
+
+            let versioned = opts.versioned && !opts.data_movement;
+            let _lock = self.lock_for_version_limit(bucket, &object, versioned).await?;
+            let mut info = self
+                .with_version_limit(bucket, &object, versioned, async {
+                    if self.single_pool() {
+                        return self.pools[0].put_object(bucket, object.as_str(), data, opts).await;
+                    }
+
+                    let idx = self.get_pool_idx(bucket, &object, data.size()).await?;
+
+                    if opts.data_movement && idx == opts.src_pool_idx {
+                        return Err(StorageError::DataMovementOverwriteErr(
+                            bucket.to_owned(),
+                            object.to_owned(),
+                            opts.version_id.clone().unwrap_or_default(),
+                        ));
+                    }
+
+                    self.pools[idx].put_object(bucket, &object, data, opts).await
+                })
+                .await?;
 
-        self.pools[idx].put_object(bucket, &object, data, opts).await
+            set_object_expiration(&mut info).await;
+            Ok(info)
+        })
+        .await
     }
 }
 
@@ -1288,6 +1727,21 @@ lazy_static! {
 
 #[async_trait::async_trait]
 impl StorageAPI for ECStore {
+    #[tracing::instrument(skip(self))]
+    async fn shutdown(&self, force: bool) -> Result<()> {
+        stop_data_scanner();
+
+        if !force && !GLOBAL_BackgroundHealRoutine.drain(SHUTDOWN_HEAL_DRAIN_TIMEOUT).await {
+            warn!("shutdown: heal queue not drained within {:?}", SHUTDOWN_HEAL_DRAIN_TIMEOUT);
+        }
+
+        let mrf = GLOBAL_MRFState.shutdown(&all_local_disk().await, force).await;
+
+        let results = join_all(self.pools.iter().map(|pool| pool.shutdown(force))).await;
+        mrf?;
+        results.into_iter().find(|r| r.is_err()).unwrap_or(Ok(()))
+    }
+
     #[tracing::instrument(skip(self))]
     async fn backend_info(&self) -> rustfs_madmin::BackendInfo {
         let (standard_sc_parity, rr_sc_parity) = {
@@ -1363,43 +1817,42 @@ impl StorageAPI for ECStore {
 
     #[tracing::instrument(skip(self))]
     async fn make_bucket(&self, bucket: &str, opts: &MakeBucketOptions) -> Result<()> {
+        check_read_only(bucket)?;
+
+        // the meta buckets are created by the server itself
         if !is_meta_bucketname(bucket) {
-            if let Err(err) = check_valid_bucket_name_strict(bucket) {
-                return Err(StorageError::BucketNameInvalid(err.to_string()));
-            }
+            validate_bucket_name(bucket)?;
 
             // TODO: nslock
         }
 
         if let Err(err) = self.peer_sys.make_bucket(bucket, opts).await {
-            if !is_err_bucket_exists(&err.into()) {
-                let _ = self
-                    .delete_bucket(
-                        bucket,
-                        &DeleteBucketOptions {
-                            no_lock: true,
-                            no_recreate: true,
-                            ..Default::default()
-                        },
-                    )
-                    .await;
+            let err = to_object_err(err.into(), vec![bucket]);
+            if !is_err_bucket_exists(&err) {
+                self.rollback_make_bucket(bucket).await;
             }
+            return Err(err);
         };
 
         let mut meta = BucketMetadata::new(bucket);
 
         meta.set_created(opts.created_at);
 
+        // Object lock requires versioning, so a lock enabled bucket is always created versioned.
         if opts.lock_enabled {
+            meta.lock_enabled = true;
             meta.object_lock_config_xml = crate::bucket::utils::serialize::<ObjectLockConfiguration>(&enableObjcetLockConfig)?;
-            meta.versioning_config_xml = crate::bucket::utils::serialize::<VersioningConfiguration>(&enableVersioningConfig)?;
         }
 
-        if opts.versioning_enabled {
+        if opts.lock_enabled || opts.versioning_enabled {
             meta.versioning_config_xml = crate::bucket::utils::serialize::<VersioningConfiguration>(&enableVersioningConfig)?;
         }
 
-        meta.save().await?;
+        // The bucket only counts as created once its metadata is on disk.
+        if let Err(err) = meta.save().await {
+            self.rollback_make_bucket(bucket).await;
+            return Err(err);
+        }
 
         set_bucket_metadata(bucket.to_string(), meta).await?;
 
@@ -1416,6 +1869,17 @@ impl StorageAPI for ECStore {
             info.object_locking = sys.object_locking();
         }
 
+        // approximate figures from the last persisted scan, stale by however long ago it ran
+        let usage = match new_object_layer_fn() {
+            Some(store) => load_bucket_usage_from_backend(store, bucket).await,
+            None => None,
+        };
+        if let Some((objects, size, updated)) = usage {
+            info.objects_count = Some(objects);
+            info.size = Some(size);
+            info.usage_updated = updated.map(OffsetDateTime::from);
+        }
+
         Ok(info)
     }
     #[tracing::instrument(skip(self))]
@@ -1435,6 +1899,8 @@ impl StorageAPI for ECStore {
     }
     #[tracing::instrument(skip(self))]
     async fn delete_bucket(&self, bucket: &str, opts: &DeleteBucketOptions) -> Result<()> {
+        check_read_only(bucket)?;
+
         if is_meta_bucketname(bucket) {
             return Err(StorageError::BucketNameInvalid(bucket.to_string()));
         }
@@ -1458,6 +1924,8 @@ impl StorageAPI for ECStore {
 
         // TODO: replication opts.srdelete_op
 
+        GLOBAL_LIST_CACHE.invalidate(bucket, None);
+
         // 删除 meta
         self.delete_all(RUSTFS_META_BUCKET, format!("{BUCKET_META_PREFIX}/{bucket}").as_str())
             .await?;
@@ -1479,8 +1947,33 @@ impl StorageAPI for ECStore {
         fetch_owner: bool,
         start_after: Option<String>,
     ) -> Result<ListObjectsV2Info> {
-        self.inner_list_objects_v2(bucket, prefix, continuation_token, delimiter, max_keys, fetch_owner, start_after)
-            .await
+        // clamp before keying the cache so every oversized request shares one page
+        let max_keys = clamp_max_keys(max_keys, get_global_max_list_keys());
+        if !GLOBAL_LIST_CACHE.is_enabled() || is_meta_bucketname(bucket) {
+            return self
+                .inner_list_objects_v2(bucket, prefix, continuation_token, delimiter, max_keys, fetch_owner, start_after)
+                .await;
+        }
+
+        let key = ListKey {
+            bucket: bucket.to_string(),
+            prefix: prefix.to_string(),
+            delimiter: delimiter.clone(),
+            max_keys,
+            continuation_token: continuation_token.clone(),
+            start_after: start_after.clone(),
+            fetch_owner,
+        };
+        if let Some(info) = GLOBAL_LIST_CACHE.get(&key) {
+            return Ok(info);
+        }
+
+        let generation = GLOBAL_LIST_CACHE.generation(bucket);
+        let info = self
+            .inner_list_objects_v2(bucket, prefix, continuation_token, delimiter, max_keys, fetch_owner, start_after)
+            .await?;
+        GLOBAL_LIST_CACHE.insert(key, &info, generation);
+        Ok(info)
     }
 
     #[tracing::instrument(skip(self))]
@@ -1498,159 +1991,267 @@ impl StorageAPI for ECStore {
     }
 
     #[tracing::instrument(skip(self))]
-    async fn get_object_info(&self, bucket: &str, object: &str, opts: &ObjectOptions) -> Result<ObjectInfo> {
+    async fn inspect_object(&self, bucket: &str, object: &str, version_id: Option<&str>) -> Result<ObjectInspectReport> {
         check_object_args(bucket, object)?;
 
         let object = encode_dir_object(object);
 
         if self.single_pool() {
-            return self.pools[0].get_object_info(bucket, object.as_str(), opts).await;
+            return self.pools[0].inspect_object(bucket, &object, version_id).await;
         }
 
-        // TODO: nslock
-
-        let (info, _) = self.get_latest_object_info_with_idx(bucket, object.as_str(), opts).await?;
+        let opts = ObjectOptions {
+            version_id: version_id.map(str::to_string),
+            ..Default::default()
+        };
+        let idx = self.get_pool_idx_existing_with_opts(bucket, &object, &opts).await?;
 
-        Ok(info)
+        self.pools[idx].inspect_object(bucket, &object, version_id).await
     }
 
-    // TODO: review
     #[tracing::instrument(skip(self))]
-    async fn copy_object(
-        &self,
-        src_bucket: &str,
-        src_object: &str,
-        dst_bucket: &str,
-        dst_object: &str,
-        src_info: &mut ObjectInfo,
-        src_opts: &ObjectOptions,
-        dst_opts: &ObjectOptions,
-    ) -> Result<ObjectInfo> {
-        check_copy_obj_args(src_bucket, src_object)?;
-        check_copy_obj_args(dst_bucket, dst_object)?;
+    async fn get_object_info(&self, bucket: &str, object: &str, opts: &ObjectOptions) -> Result<ObjectInfo> {
+        check_object_args(bucket, object)?;
 
-        let src_object = encode_dir_object(src_object);
-        let dst_object = encode_dir_object(dst_object);
+        let object = encode_dir_object(object);
 
-        let cp_src_dst_same = path_join_buf(&[src_bucket, &src_object]) == path_join_buf(&[dst_bucket, &dst_object]);
+        if !GLOBAL_NEGATIVE_CACHE.is_enabled() || is_meta_bucketname(bucket) {
+            return self.get_object_info_uncached(bucket, &object, opts).await;
+        }
 
-        // TODO: nslock
+        let version_id = opts.version_id.as_deref().unwrap_or_default();
+        if let Some(err) = GLOBAL_NEGATIVE_CACHE.get(bucket, &object, version_id) {
+            return Err(err);
+        }
+
+        let generation = GLOBAL_NEGATIVE_CACHE.generation();
+        let res = self.get_object_info_uncached(bucket, &object, opts).await;
+        if let Err(err) = &res {
+            if is_err_object_not_found(err) || is_err_version_not_found(err) {
+                GLOBAL_NEGATIVE_CACHE.insert(bucket, &object, version_id, err.clone(), generation);
+            }
+        }
 
-        let pool_idx = self.get_pool_idx_no_lock(src_bucket, &src_object, src_info.size).await?;
+        res
+    }
 
-        if cp_src_dst_same {
-            if let (Some(src_vid), Some(dst_vid)) = (&src_opts.version_id, &dst_opts.version_id) {
-                if src_vid == dst_vid {
-                    return self.pools[pool_idx]
-                        .copy_object(src_bucket, &src_object, dst_bucket, &dst_object, src_info, src_opts, dst_opts)
-                        .await;
+    #[tracing::instrument(skip(self))]
+    async fn get_object_infos(&self, bucket: &str, objects: &[String], opts: &ObjectOptions) -> Result<Vec<Result<ObjectInfo>>> {
+        if !self.single_pool() {
+            return Ok(join_all(objects.iter().map(|object| self.get_object_info(bucket, object, opts))).await);
+        }
+
+        let mut infos: Vec<Option<Result<ObjectInfo>>> = Vec::with_capacity(objects.len());
+        let mut names = Vec::with_capacity(objects.len());
+        for object in objects.iter() {
+            match check_object_args(bucket, object) {
+                Ok(()) => {
+                    infos.push(None);
+                    names.push(encode_dir_object(object));
                 }
+                Err(err) => infos.push(Some(Err(err))),
             }
+        }
 
-            if !dst_opts.versioned && src_opts.version_id.is_none() {
-                return self.pools[pool_idx]
-                    .copy_object(src_bucket, &src_object, dst_bucket, &dst_object, src_info, src_opts, dst_opts)
-                    .await;
-            }
+        let mut read = self.pools[0].get_object_infos(bucket, &names, opts).await?.into_iter();
+        Ok(infos
+            .into_iter()
+            .map(|info| info.unwrap_or_else(|| read.next().unwrap_or_else(|| Err(Error::other("no result")))))
+            .collect())
+    }
 
-            if dst_opts.versioned && src_opts.version_id != dst_opts.version_id {
-                src_info.version_only = true;
-                return self.pools[pool_idx]
-                    .copy_object(src_bucket, &src_object, dst_bucket, &dst_object, src_info, src_opts, dst_opts)
-                    .await;
+    // TODO: review
+    #[tracing::instrument(skip(self))]
+    async fn copy_object(
+        &self,
+        src_bucket: &str,
+        src_object: &str,
+        dst_bucket: &str,
+        dst_object: &str,
+        src_info: &mut ObjectInfo,
+        src_opts: &ObjectOptions,
+        dst_opts: &ObjectOptions,
+    ) -> Result<ObjectInfo> {
+        audit::record(AuditOperation::CopyObject, dst_bucket, dst_object, audit::written_size, async move {
+            check_read_only(dst_bucket)?;
+
+            check_copy_obj_args(src_bucket, src_object)?;
+            check_copy_obj_args(dst_bucket, dst_object)?;
+            // the metadata written to the destination, replaced or copied from the source
+            validate_user_metadata(&src_info.user_defined)?;
+            if !dst_opts.data_movement {
+                if let Some(cfg) = Self::object_lock_config(dst_bucket).await {
+                    apply_default_retention_meta(&cfg, &mut src_info.user_defined, objectlock::utc_now_ntp());
+                }
             }
-        }
 
-        let put_opts = ObjectOptions {
-            user_defined: src_info.user_defined.clone(),
-            versioned: dst_opts.versioned,
-            version_id: dst_opts.version_id.clone(),
-            no_lock: true,
-            mod_time: dst_opts.mod_time,
-            ..Default::default()
-        };
+            let src_object = encode_dir_object(src_object);
+            let dst_object = encode_dir_object(dst_object);
+            let _invalidate = GLOBAL_NEGATIVE_CACHE.invalidate_on_drop(dst_bucket, [dst_object.as_str()]);
+            let _invalidate_list = GLOBAL_LIST_CACHE.invalidate_on_drop(dst_bucket, [dst_object.as_str()]);
 
-        if let Some(put_object_reader) = src_info.put_object_reader.as_mut() {
-            return self.pools[pool_idx]
-                .put_object(dst_bucket, &dst_object, put_object_reader, &put_opts)
-                .await;
-        }
+            let cp_src_dst_same = path_join_buf(&[src_bucket, &src_object]) == path_join_buf(&[dst_bucket, &dst_object]);
+
+            // held until the copy is written, a concurrent writer can not take the preserved id meanwhile
+            let _lock = self.lock_object(dst_bucket, &dst_object).await?;
+
+            // rewriting the metadata of the source version in place adds no version
+            let adds_version = dst_opts.versioned
+                && !(cp_src_dst_same && src_opts.version_id.is_some() && src_opts.version_id == dst_opts.version_id);
+            self.with_version_limit(dst_bucket, &dst_object, adds_version, async {
+                let version_id = if dst_opts.preserve_version_id {
+                    Some(self.preserved_version_id(dst_bucket, &dst_object, src_info, dst_opts).await?)
+                } else {
+                    dst_opts.version_id.clone()
+                };
+
+                let pool_idx = self.get_pool_idx_no_lock(src_bucket, &src_object, src_info.size).await?;
+
+                if cp_src_dst_same {
+                    if let (Some(src_vid), Some(dst_vid)) = (&src_opts.version_id, &dst_opts.version_id) {
+                        if src_vid == dst_vid {
+                            return self.pools[pool_idx]
+                                .copy_object(src_bucket, &src_object, dst_bucket, &dst_object, src_info, src_opts, dst_opts)
+                                .await;
+                        }
+                    }
+
+                    if !dst_opts.versioned && src_opts.version_id.is_none() {
+                        return self.pools[pool_idx]
+                            .copy_object(src_bucket, &src_object, dst_bucket, &dst_object, src_info, src_opts, dst_opts)
+                            .await;
+                    }
+
+                    if dst_opts.versioned && src_opts.version_id != dst_opts.version_id {
+                        src_info.version_only = true;
+                        return self.pools[pool_idx]
+                            .copy_object(src_bucket, &src_object, dst_bucket, &dst_object, src_info, src_opts, dst_opts)
+                            .await;
+                    }
+                }
+
+                let put_opts = ObjectOptions {
+                    user_defined: src_info.user_defined.clone(),
+                    versioned: dst_opts.versioned,
+                    version_id,
+                    no_lock: true,
+                    mod_time: dst_opts.mod_time,
+                    ..Default::default()
+                };
+
+                if let Some(put_object_reader) = src_info.put_object_reader.as_mut() {
+                    return self.pools[pool_idx]
+                        .put_object(dst_bucket, &dst_object, put_object_reader, &put_opts)
+                        .await;
+                }
 
-        Err(StorageError::InvalidArgument(
-            src_bucket.to_owned(),
-            src_object.to_owned(),
-            "put_object_reader is none".to_owned(),
-        ))
+                Err(StorageError::InvalidArgument(
+                    src_bucket.to_owned(),
+                    src_object.to_owned(),
+                    "put_object_reader is none".to_owned(),
+                ))
+            })
+            .await
+        })
+        .await
     }
     #[tracing::instrument(skip(self))]
     async fn delete_object(&self, bucket: &str, object: &str, opts: ObjectOptions) -> Result<ObjectInfo> {
-        check_del_obj_args(bucket, object)?;
+        audit::record(AuditOperation::DeleteObject, bucket, object, |_| 0, async move {
+            check_read_only(bucket)?;
 
-        if opts.delete_prefix {
-            self.delete_prefix(bucket, object).await?;
-            return Ok(ObjectInfo::default());
-        }
+            check_del_obj_args(bucket, object)?;
 
-        // TODO: nslock
+            // removing a delete marker brings the object back
+            let _invalidate = GLOBAL_NEGATIVE_CACHE.invalidate_on_drop(bucket, [encode_dir_object(object)]);
+            let _invalidate_list = GLOBAL_LIST_CACHE.invalidate_on_drop(bucket, [object]);
 
-        let object = encode_dir_object(object);
-        let object = object.as_str();
+            if opts.delete_prefix {
+                self.delete_prefix(bucket, object).await?;
+                return Ok(ObjectInfo::default());
+            }
 
-        // 查询在哪个 pool
-        let (mut pinfo, errs) = self
-            .get_pool_info_existing_with_opts(bucket, object, &opts)
-            .await
-            .map_err(|e| {
-                if is_err_read_quorum(&e) {
-                    StorageError::ErasureWriteQuorum
-                } else {
-                    e
-                }
-            })?;
+            // TODO: nslock
 
-        if pinfo.object_info.delete_marker && opts.version_id.is_none() {
-            pinfo.object_info.name = decode_dir_object(object);
-            return Ok(pinfo.object_info);
-        }
+            let object = encode_dir_object(object);
+            let object = object.as_str();
 
-        if opts.data_movement && opts.src_pool_idx == pinfo.index {
-            return Err(StorageError::DataMovementOverwriteErr(
-                bucket.to_owned(),
-                object.to_owned(),
-                opts.version_id.unwrap_or_default(),
-            ));
-        }
+            // If-Match is checked and the object deleted under the object lock. Puts do not take it, so the
+            // set checks again under the data lock they commit with.
+            let _lock = match opts.if_match {
+                Some(_) => Some(self.lock_object(bucket, object).await?),
+                None => None,
+            };
 
-        if opts.data_movement {
-            let mut obj = self.pools[pinfo.index].delete_object(bucket, object, opts).await?;
-            obj.name = decode_dir_object(obj.name.as_str());
-            return Ok(obj);
-        }
+            // 查询在哪个 pool
+            let (mut pinfo, errs) = self
+                .get_pool_info_existing_with_opts(bucket, object, &opts)
+                .await
+                .map_err(|e| {
+                    if is_err_read_quorum(&e) {
+                        StorageError::ErasureWriteQuorum
+                    } else {
+                        e
+                    }
+                })?;
 
-        if !errs.is_empty() && !opts.versioned && !opts.version_suspended {
-            return self.delete_object_from_all_pools(bucket, object, &opts, errs).await;
-        }
+            if let Some(if_match) = &opts.if_match {
+                check_if_match(if_match, &pinfo.object_info)?;
+            }
 
-        for pool in self.pools.iter() {
-            match pool.delete_object(bucket, object, opts.clone()).await {
-                Ok(res) => {
-                    let mut obj = res;
-                    obj.name = decode_dir_object(object);
-                    return Ok(obj);
-                }
-                Err(err) => {
-                    if !is_err_object_not_found(&err) && !is_err_version_not_found(&err) {
-                        return Err(err);
+            if pinfo.object_info.delete_marker && opts.version_id.is_none() {
+                pinfo.object_info.name = decode_dir_object(object);
+                return Ok(pinfo.object_info);
+            }
+
+            if opts.data_movement && opts.src_pool_idx == pinfo.index {
+                return Err(StorageError::DataMovementOverwriteErr(
+                    bucket.to_owned(),
+                    object.to_owned(),
+                    opts.version_id.unwrap_or_default(),
+                ));
+            }
+
+            if opts.data_movement {
+                let mut obj = self.pools[pinfo.index].delete_object(bucket, object, opts).await?;
+                obj.name = decode_dir_object(obj.name.as_str());
+                return Ok(obj);
+            }
+
+            // a delete marker on top is fine, removing the locked version is not
+            if (opts.version_id.is_some() || !opts.versioned)
+                && enforce_retention_bypass_for_delete(&pinfo.object_info, opts.bypass_governance)
+                && Self::object_lock_config(bucket).await.is_some()
+            {
+                return Err(StorageError::ObjectLocked(bucket.to_owned(), decode_dir_object(object)));
+            }
+
+            if !errs.is_empty() && !opts.versioned && !opts.version_suspended {
+                return self.delete_object_from_all_pools(bucket, object, &opts, errs).await;
+            }
+
+            for pool in self.pools.iter() {
+                match pool.delete_object(bucket, object, opts.clone()).await {
+                    Ok(res) => {
+                        let mut obj = res;
+                        obj.name = decode_dir_object(object);
+                        return Ok(obj);
+                    }
+                    Err(err) => {
+                        if !is_err_object_not_found(&err) && !is_err_version_not_found(&err) {
+                            return Err(err);
+                        }
                     }
                 }
             }
-        }
 
-        if let Some(ver) = opts.version_id {
-            return Err(StorageError::VersionNotFound(bucket.to_owned(), object.to_owned(), ver));
-        }
+            if let Some(ver) = opts.version_id {
+                return Err(StorageError::VersionNotFound(bucket.to_owned(), object.to_owned(), ver));
+            }
 
-        Err(StorageError::ObjectNotFound(bucket.to_owned(), object.to_owned()))
+            Err(StorageError::ObjectNotFound(bucket.to_owned(), object.to_owned()))
+        })
+        .await
     }
     // TODO: review
     #[tracing::instrument(skip(self))]
@@ -1660,151 +2261,246 @@ impl StorageAPI for ECStore {
         objects: Vec<ObjectToDelete>,
         opts: ObjectOptions,
     ) -> Result<(Vec<DeletedObject>, Vec<Option<Error>>)> {
-        // encode object name
-        let objects: Vec<ObjectToDelete> = objects
-            .iter()
-            .map(|v| {
-                let mut v = v.clone();
-                v.object_name = encode_dir_object(v.object_name.as_str());
-                v
-            })
-            .collect();
-
-        // 默认返回值
-        let mut del_objects = vec![DeletedObject::default(); objects.len()];
+        let names: Vec<String> = objects.iter().map(|v| v.object_name.clone()).collect();
+        let res: Result<_> = async move {
+            check_read_only(bucket)?;
+
+            // encode object name
+            let objects: Vec<ObjectToDelete> = objects
+                .iter()
+                .map(|v| {
+                    let mut v = v.clone();
+                    v.object_name = encode_dir_object(v.object_name.as_str());
+                    v
+                })
+                .collect();
+            let _invalidate = GLOBAL_NEGATIVE_CACHE.invalidate_on_drop(bucket, objects.iter().map(|v| v.object_name.as_str()));
+            let _invalidate_list = GLOBAL_LIST_CACHE.invalidate_on_drop(bucket, objects.iter().map(|v| v.object_name.as_str()));
+
+            // 默认返回值
+            let mut del_objects = vec![DeletedObject::default(); objects.len()];
+
+            let mut del_errs = Vec::with_capacity(objects.len());
+            for _ in 0..objects.len() {
+                del_errs.push(None)
+            }
 
-        let mut del_errs = Vec::with_capacity(objects.len());
-        for _ in 0..objects.len() {
-            del_errs.push(None)
-        }
+            // TODO: nslock
 
-        // TODO: nslock
+            let mut futures = Vec::with_capacity(objects.len());
 
-        let mut futures = Vec::with_capacity(objects.len());
+            for obj in objects.iter() {
+                futures.push(async move {
+                    self.internal_get_pool_info_existing_with_opts(
+                        bucket,
+                        &obj.object_name,
+                        &ObjectOptions {
+                            no_lock: true,
+                            version_id: obj.version_id.map(|v| v.to_string()),
+                            ..Default::default()
+                        },
+                    )
+                    .await
+                });
+            }
 
-        for obj in objects.iter() {
-            futures.push(async move {
-                self.internal_get_pool_info_existing_with_opts(
-                    bucket,
-                    &obj.object_name,
-                    &ObjectOptions {
-                        no_lock: true,
-                        ..Default::default()
-                    },
-                )
-                .await
-            });
-        }
+            let results = join_all(futures).await;
+            let lock_enabled = Self::object_lock_config(bucket).await.is_some();
+
+            // let mut jhs = Vec::new();
+            // let semaphore = Arc::new(Semaphore::new(num_cpus::get()));
+            // let pools = Arc::new(self.pools.clone());
+
+            // for obj in objects.iter() {
+            //     let (semaphore, pools, bucket, object_name, opt) = (
+            //         semaphore.clone(),
+            //         pools.clone(),
+            //         bucket.to_string(),
+            //         obj.object_name.to_string(),
+            //         ObjectOptions::default(),
+            //     );
+
+            //     let jh = tokio::spawn(async move {
+            //         let _permit = semaphore.acquire().await.unwrap();
+            //         self.internal_get_pool_info_existing_with_opts(pools.as_ref(), &bucket, &object_name, &opt)
+            //             .await
+            //     });
+            //     jhs.push(jh);
+            // }
+            // let mut results = Vec::new();
+            // for jh in jhs {
+            //     results.push(jh.await.unwrap());
+            // }
 
-        let results = join_all(futures).await;
+            // 记录 pool Index 对应的 objects pool_idx -> objects idx
+            let mut pool_obj_idx_map = HashMap::new();
+            let mut orig_index_map = HashMap::new();
+            // objects whose requested version is itself a delete marker
+            let mut marker_versions = HashSet::new();
+
+            for (i, res) in results.into_iter().enumerate() {
+                match res {
+                    Ok((pinfo, _)) => {
+                        if let Some(obj) = objects.get(i) {
+                            if pinfo.object_info.delete_marker && obj.version_id.is_none() {
+                                del_objects[i] = DeletedObject {
+                                    delete_marker: pinfo.object_info.delete_marker,
+                                    delete_marker_version_id: pinfo.object_info.version_id.map(|v| v.to_string()),
+                                    object_name: decode_dir_object(&pinfo.object_info.name),
+                                    delete_marker_mtime: pinfo.object_info.mod_time,
+                                    ..Default::default()
+                                };
+                                continue;
+                            }
 
-        // let mut jhs = Vec::new();
-        // let semaphore = Arc::new(Semaphore::new(num_cpus::get()));
-        // let pools = Arc::new(self.pools.clone());
-
-        // for obj in objects.iter() {
-        //     let (semaphore, pools, bucket, object_name, opt) = (
-        //         semaphore.clone(),
-        //         pools.clone(),
-        //         bucket.to_string(),
-        //         obj.object_name.to_string(),
-        //         ObjectOptions::default(),
-        //     );
-
-        //     let jh = tokio::spawn(async move {
-        //         let _permit = semaphore.acquire().await.unwrap();
-        //         self.internal_get_pool_info_existing_with_opts(pools.as_ref(), &bucket, &object_name, &opt)
-        //             .await
-        //     });
-        //     jhs.push(jh);
-        // }
-        // let mut results = Vec::new();
-        // for jh in jhs {
-        //     results.push(jh.await.unwrap());
-        // }
-
-        // 记录 pool Index 对应的 objects pool_idx -> objects idx
-        let mut pool_obj_idx_map = HashMap::new();
-        let mut orig_index_map = HashMap::new();
+                            if lock_enabled
+                                && (obj.version_id.is_some() || !opts.versioned)
+                                && enforce_retention_bypass_for_delete(&pinfo.object_info, opts.bypass_governance)
+                            {
+                                del_errs[i] =
+                                    Some(StorageError::ObjectLocked(bucket.to_owned(), decode_dir_object(&obj.object_name)));
+                                del_objects[i] = DeletedObject {
+                                    object_name: decode_dir_object(&obj.object_name),
+                                    version_id: obj.version_id.map(|v| v.to_string()),
+                                    ..Default::default()
+                                };
+                                continue;
+                            }
 
-        for (i, res) in results.into_iter().enumerate() {
-            match res {
-                Ok((pinfo, _)) => {
-                    if let Some(obj) = objects.get(i) {
-                        if pinfo.object_info.delete_marker && obj.version_id.is_none() {
-                            del_objects[i] = DeletedObject {
-                                delete_marker: pinfo.object_info.delete_marker,
-                                delete_marker_version_id: pinfo.object_info.version_id.map(|v| v.to_string()),
-                                object_name: decode_dir_object(&pinfo.object_info.name),
-                                delete_marker_mtime: pinfo.object_info.mod_time,
-                                ..Default::default()
-                            };
-                            continue;
-                        }
+                            if pinfo.object_info.delete_marker {
+                                marker_versions.insert(i);
+                            }
 
-                        if !pool_obj_idx_map.contains_key(&pinfo.index) {
-                            pool_obj_idx_map.insert(pinfo.index, vec![obj.clone()]);
-                        } else if let Some(val) = pool_obj_idx_map.get_mut(&pinfo.index) {
-                            val.push(obj.clone());
-                        }
+                            if !pool_obj_idx_map.contains_key(&pinfo.index) {
+                                pool_obj_idx_map.insert(pinfo.index, vec![obj.clone()]);
+                            } else if let Some(val) = pool_obj_idx_map.get_mut(&pinfo.index) {
+                                val.push(obj.clone());
+                            }
 
-                        if !orig_index_map.contains_key(&pinfo.index) {
-                            orig_index_map.insert(pinfo.index, vec![i]);
-                        } else if let Some(val) = orig_index_map.get_mut(&pinfo.index) {
-                            val.push(i);
+                            if !orig_index_map.contains_key(&pinfo.index) {
+                                orig_index_map.insert(pinfo.index, vec![i]);
+                            } else if let Some(val) = orig_index_map.get_mut(&pinfo.index) {
+                                val.push(i);
+                            }
                         }
                     }
-                }
-                Err(e) => {
-                    if !is_err_object_not_found(&e) && is_err_version_not_found(&e) {
-                        del_errs[i] = Some(e)
-                    }
+                    Err(e) => {
+                        if !is_err_object_not_found(&e) && !is_err_version_not_found(&e) {
+                            del_errs[i] = Some(e)
+                        }
 
-                    if let Some(obj) = objects.get(i) {
-                        del_objects[i] = DeletedObject {
-                            object_name: decode_dir_object(&obj.object_name),
-                            version_id: obj.version_id.map(|v| v.to_string()),
-                            ..Default::default()
+                        if let Some(obj) = objects.get(i) {
+                            del_objects[i] = DeletedObject {
+                                object_name: decode_dir_object(&obj.object_name),
+                                version_id: obj.version_id.map(|v| v.to_string()),
+                                ..Default::default()
+                            }
                         }
                     }
                 }
             }
-        }
 
-        if !pool_obj_idx_map.is_empty() {
-            for (i, sets) in self.pools.iter().enumerate() {
-                //  取 pool idx 对应的 objects index
-                if let Some(objs) = pool_obj_idx_map.get(&i) {
-                    //  取对应 obj，理论上不会 none
-                    // let objs: Vec<ObjectToDelete> = obj_idxs.iter().filter_map(|&idx| objects.get(idx).cloned()).collect();
+            if !pool_obj_idx_map.is_empty() {
+                for (i, sets) in self.pools.iter().enumerate() {
+                    //  取 pool idx 对应的 objects index
+                    if let Some(objs) = pool_obj_idx_map.get(&i) {
+                        //  取对应 obj，理论上不会 none
+                        // let objs: Vec<ObjectToDelete> = obj_idxs.iter().filter_map(|&idx| objects.get(idx).cloned()).collect();
 
-                    if objs.is_empty() {
-                        continue;
-                    }
+                        if objs.is_empty() {
+                            continue;
+                        }
 
-                    let (pdel_objs, perrs) = sets.delete_objects(bucket, objs.clone(), opts.clone()).await?;
+                        let (pdel_objs, perrs) = sets.delete_objects(bucket, objs.clone(), opts.clone()).await?;
 
-                    // 同时存入不可能为 none
-                    let org_indexes = orig_index_map.get(&i).unwrap();
+                        // 同时存入不可能为 none
+                        let org_indexes = orig_index_map.get(&i).unwrap();
 
-                    // perrs 的顺序理论上跟 obj_idxs 顺序一致
-                    for (i, err) in perrs.into_iter().enumerate() {
-                        let obj_idx = org_indexes[i];
+                        // perrs 的顺序理论上跟 obj_idxs 顺序一致
+                        for (i, err) in perrs.into_iter().enumerate() {
+                            let obj_idx = org_indexes[i];
 
-                        if err.is_some() {
-                            del_errs[obj_idx] = err;
-                        }
+                            if err.is_some() {
+                                del_errs[obj_idx] = err;
+                            }
+
+                            let mut dobj = pdel_objs.get(i).unwrap().clone();
+                            dobj.object_name = decode_dir_object(&dobj.object_name);
 
-                        let mut dobj = pdel_objs.get(i).unwrap().clone();
-                        dobj.object_name = decode_dir_object(&dobj.object_name);
+                            // Permanently removing a delete marker still reports it as one.
+                            if marker_versions.contains(&obj_idx) {
+                                dobj.delete_marker = true;
+                                dobj.delete_marker_version_id.clone_from(&dobj.version_id);
+                            }
 
-                        del_objects[obj_idx] = dobj;
+                            del_objects[obj_idx] = dobj;
+                        }
                     }
                 }
             }
+
+            Ok((del_objects, del_errs))
+        }
+        .await;
+        for (i, object) in names.iter().enumerate() {
+            let res = match &res {
+                Ok((_, errs)) => match errs.get(i) {
+                    Some(Some(err)) => Err(err.clone()),
+                    _ => Ok(()),
+                },
+                Err(err) => Err(err.clone()),
+            };
+            audit::emit_result(AuditOperation::DeleteObject, bucket, object, &res, |_| 0).await;
         }
+        res
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn delete_prefix(self: Arc<Self>, bucket: &str, prefix: &str, opts: ObjectOptions) -> Result<DeletePrefixReport> {
+        check_read_only(bucket)?;
+
+        let mut report = DeletePrefixReport::default();
+        let (mut marker, mut version_marker) = (None, None);
+        loop {
+            // the marker is the last key listed, deleting up to it doesn't shift the next page
+            let (batch, is_truncated) = if opts.all_versions {
+                let page = self
+                    .clone()
+                    .list_object_versions(bucket, prefix, marker, version_marker, None, DELETE_PREFIX_PAGE_SIZE)
+                    .await?;
+                (marker, version_marker) = (page.next_marker, page.next_version_idmarker);
+                let batch: Vec<ObjectToDelete> = page
+                    .objects
+                    .iter()
+                    .map(|oi| ObjectToDelete {
+                        object_name: oi.name.clone(),
+                        version_id: oi.version_id,
+                    })
+                    .collect();
+                (batch, page.is_truncated)
+            } else {
+                let page = self
+                    .clone()
+                    .list_objects_v2(bucket, prefix, marker, None, DELETE_PREFIX_PAGE_SIZE, false, None)
+                    .await?;
+                marker = page.next_continuation_token;
+                let batch: Vec<ObjectToDelete> = page
+                    .objects
+                    .iter()
+                    .map(|oi| ObjectToDelete {
+                        object_name: oi.name.clone(),
+                        version_id: None,
+                    })
+                    .collect();
+                (batch, page.is_truncated)
+            };
+
+            delete_prefix_batch(&self, bucket, batch, &opts, &mut report).await?;
 
-        Ok((del_objects, del_errs))
+            if !is_truncated || marker.is_none() {
+                return Ok(report);
+            }
+        }
     }
 
     #[tracing::instrument(skip(self))]
@@ -1861,34 +2557,18 @@ impl StorageAPI for ECStore {
 
     #[tracing::instrument(skip(self))]
     async fn new_multipart_upload(&self, bucket: &str, object: &str, opts: &ObjectOptions) -> Result<MultipartUploadResult> {
-        check_new_multipart_args(bucket, object)?;
-
-        if self.single_pool() {
-            return self.pools[0].new_multipart_upload(bucket, object, opts).await;
-        }
+        check_read_only(bucket)?;
 
-        for (idx, pool) in self.pools.iter().enumerate() {
-            if self.is_suspended(idx).await || self.is_pool_rebalancing(idx).await {
-                continue;
-            }
-            let res = pool
-                .list_multipart_uploads(bucket, object, None, None, None, MAX_UPLOADS_LIST)
-                .await?;
+        check_new_multipart_args(bucket, object)?;
+        validate_user_metadata(&opts.user_defined)?;
 
-            if !res.uploads.is_empty() {
-                return self.pools[idx].new_multipart_upload(bucket, object, opts).await;
-            }
-        }
-        let idx = self.get_pool_idx(bucket, object, -1).await?;
-        if opts.data_movement && idx == opts.src_pool_idx {
-            return Err(StorageError::DataMovementOverwriteErr(
-                bucket.to_owned(),
-                object.to_owned(),
-                "".to_owned(),
-            ));
+        if let Some(key) = opts.idempotency_key.as_deref() {
+            return GLOBAL_UPLOAD_IDEMPOTENCY
+                .get_or_start(bucket, object, key, || self.start_multipart_upload(bucket, object, opts))
+                .await;
         }
 
-        self.pools[idx].new_multipart_upload(bucket, object, opts).await
+        self.start_multipart_upload(bucket, object, opts).await
     }
 
     #[tracing::instrument(skip(self))]
@@ -1908,6 +2588,8 @@ impl StorageAPI for ECStore {
     }
     #[tracing::instrument(skip(self))]
     async fn transition_object(&self, bucket: &str, object: &str, opts: &ObjectOptions) -> Result<()> {
+        check_read_only(bucket)?;
+
         let object = encode_dir_object(object);
         if self.single_pool() {
             return self.pools[0].transition_object(bucket, &object, opts).await;
@@ -1922,6 +2604,8 @@ impl StorageAPI for ECStore {
 
     #[tracing::instrument(skip(self))]
     async fn restore_transitioned_object(&self, bucket: &str, object: &str, opts: &ObjectOptions) -> Result<()> {
+        check_read_only(bucket)?;
+
         let object = encode_dir_object(object);
         if self.single_pool() {
             return self.pools[0].restore_transitioned_object(bucket, &object, opts).await;
@@ -1939,22 +2623,35 @@ impl StorageAPI for ECStore {
         &self,
         src_bucket: &str,
         src_object: &str,
-        _dst_bucket: &str,
-        _dst_object: &str,
-        _upload_id: &str,
-        _part_id: usize,
-        _start_offset: i64,
-        _length: i64,
-        _src_info: &ObjectInfo,
-        _src_opts: &ObjectOptions,
-        _dst_opts: &ObjectOptions,
-    ) -> Result<()> {
-        check_new_multipart_args(src_bucket, src_object)?;
+        dst_bucket: &str,
+        dst_object: &str,
+        upload_id: &str,
+        part_id: usize,
+        start_offset: i64,
+        length: i64,
+        src_info: &ObjectInfo,
+        src_opts: &ObjectOptions,
+        dst_opts: &ObjectOptions,
+    ) -> Result<PartInfo> {
+        check_read_only(dst_bucket)?;
 
-        // TODO: PutObjectReader
-        // self.put_object_part(dst_bucket, dst_object, upload_id, part_id, data, opts)
+        check_new_multipart_args(src_bucket, src_object)?;
 
-        unimplemented!()
+        copy_object_range(
+            self,
+            src_bucket,
+            src_object,
+            dst_bucket,
+            dst_object,
+            upload_id,
+            part_id,
+            start_offset,
+            length,
+            src_info,
+            src_opts,
+            dst_opts,
+        )
+        .await
     }
     #[tracing::instrument(skip(self, data))]
     async fn put_object_part(
@@ -1966,6 +2663,8 @@ impl StorageAPI for ECStore {
         data: &mut PutObjReader,
         opts: &ObjectOptions,
     ) -> Result<PartInfo> {
+        check_read_only(bucket)?;
+
         check_put_object_part_args(bucket, object, upload_id)?;
 
         if self.single_pool() {
@@ -2032,6 +2731,8 @@ impl StorageAPI for ECStore {
     }
     #[tracing::instrument(skip(self))]
     async fn abort_multipart_upload(&self, bucket: &str, object: &str, upload_id: &str, opts: &ObjectOptions) -> Result<()> {
+        check_read_only(bucket)?;
+
         check_abort_multipart_args(bucket, object, upload_id)?;
 
         // TODO: defer DeleteUploadID
@@ -2070,38 +2771,52 @@ impl StorageAPI for ECStore {
         uploaded_parts: Vec<CompletePart>,
         opts: &ObjectOptions,
     ) -> Result<ObjectInfo> {
-        check_complete_multipart_args(bucket, object, upload_id)?;
+        audit::record(AuditOperation::CompleteMultipartUpload, bucket, object, audit::written_size, async move {
+            check_read_only(bucket)?;
+
+            check_complete_multipart_args(bucket, object, upload_id)?;
+            let retained_opts = Self::with_default_retention(bucket, opts).await;
+            let opts = retained_opts.as_ref().unwrap_or(opts);
+            let _invalidate = GLOBAL_NEGATIVE_CACHE.invalidate_on_drop(bucket, [encode_dir_object(object)]);
+            let _invalidate_list = GLOBAL_LIST_CACHE.invalidate_on_drop(bucket, [object]);
+
+            let encoded = encode_dir_object(object);
+            let _lock = self.lock_for_version_limit(bucket, &encoded, opts.versioned).await?;
+            self.with_version_limit(bucket, &encoded, opts.versioned, async {
+                if self.single_pool() {
+                    return self.pools[0]
+                        .clone()
+                        .complete_multipart_upload(bucket, object, upload_id, uploaded_parts, opts)
+                        .await;
+                }
 
-        if self.single_pool() {
-            return self.pools[0]
-                .clone()
-                .complete_multipart_upload(bucket, object, upload_id, uploaded_parts, opts)
-                .await;
-        }
+                for pool in self.pools.iter() {
+                    if self.is_suspended(pool.pool_idx).await {
+                        continue;
+                    }
 
-        for pool in self.pools.iter() {
-            if self.is_suspended(pool.pool_idx).await {
-                continue;
-            }
+                    let pool = pool.clone();
+                    let err = match pool
+                        .complete_multipart_upload(bucket, object, upload_id, uploaded_parts.clone(), opts)
+                        .await
+                    {
+                        Ok(res) => return Ok(res),
+                        Err(err) => {
+                            //
+                            if is_err_invalid_upload_id(&err) { None } else { Some(err) }
+                        }
+                    };
 
-            let pool = pool.clone();
-            let err = match pool
-                .complete_multipart_upload(bucket, object, upload_id, uploaded_parts.clone(), opts)
-                .await
-            {
-                Ok(res) => return Ok(res),
-                Err(err) => {
-                    //
-                    if is_err_invalid_upload_id(&err) { None } else { Some(err) }
+                    if let Some(er) = err {
+                        return Err(er);
+                    }
                 }
-            };
-
-            if let Some(er) = err {
-                return Err(er);
-            }
-        }
 
-        Err(StorageError::InvalidUploadID(bucket.to_owned(), object.to_owned(), upload_id.to_owned()))
+                Err(StorageError::InvalidUploadID(bucket.to_owned(), object.to_owned(), upload_id.to_owned()))
+            })
+            .await
+        })
+        .await
     }
 
     #[tracing::instrument(skip(self))]
@@ -2122,8 +2837,25 @@ impl StorageAPI for ECStore {
         }
         counts
     }
+
+    #[tracing::instrument(skip(self))]
+    async fn health(&self) -> ClusterHealth {
+        let online = join_all(self.pools.iter().map(|pool| pool.online_disk_counts())).await;
+
+        let pools = self
+            .pools
+            .iter()
+            .zip(self.set_drive_counts())
+            .zip(online)
+            .map(|((pool, set_drive_count), online)| PoolHealth::new(set_drive_count, pool.default_parity_count, &online))
+            .collect();
+
+        ClusterHealth::from_pools(pools)
+    }
     #[tracing::instrument(skip(self))]
     async fn put_object_metadata(&self, bucket: &str, object: &str, opts: &ObjectOptions) -> Result<ObjectInfo> {
+        check_read_only(bucket)?;
+
         let object = encode_dir_object(object);
         if self.single_pool() {
             return self.pools[0].put_object_metadata(bucket, object.as_str(), opts).await;
@@ -2151,6 +2883,8 @@ impl StorageAPI for ECStore {
 
     #[tracing::instrument(level = "debug", skip(self))]
     async fn put_object_tags(&self, bucket: &str, object: &str, tags: &str, opts: &ObjectOptions) -> Result<ObjectInfo> {
+        check_read_only(bucket)?;
+
         let object = encode_dir_object(object);
 
         if self.single_pool() {
@@ -2178,6 +2912,8 @@ impl StorageAPI for ECStore {
 
     #[tracing::instrument(skip(self))]
     async fn delete_object_tags(&self, bucket: &str, object: &str, opts: &ObjectOptions) -> Result<ObjectInfo> {
+        check_read_only(bucket)?;
+
         let object = encode_dir_object(object);
 
         if self.single_pool() {
@@ -2189,6 +2925,79 @@ impl StorageAPI for ECStore {
         self.pools[idx].delete_object_tags(bucket, object.as_str(), opts).await
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn clear_object_retention(&self, bucket: &str, object: &str, version_id: &str, bypass: bool) -> Result<()> {
+        check_read_only(bucket)?;
+
+        let object = encode_dir_object(object);
+
+        if self.single_pool() {
+            return self.pools[0]
+                .clear_object_retention(bucket, object.as_str(), version_id, bypass)
+                .await;
+        }
+
+        let opts = ObjectOptions {
+            version_id: (!version_id.is_empty()).then(|| version_id.to_owned()),
+            ..Default::default()
+        };
+        let idx = self.get_pool_idx_existing_with_opts(bucket, object.as_str(), &opts).await?;
+
+        self.pools[idx]
+            .clear_object_retention(bucket, object.as_str(), version_id, bypass)
+            .await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn touch_object(
+        &self,
+        bucket: &str,
+        object: &str,
+        version_id: Option<&str>,
+        new_mtime: OffsetDateTime,
+    ) -> Result<ObjectInfo> {
+        check_read_only(bucket)?;
+
+        let _lock = self.lock_object(bucket, &encode_dir_object(object)).await?;
+
+        // versions are kept newest first, a new mod time must not move the version past its
+        // neighbours or a noncurrent version could become the latest
+        let versions = self.object_versions(bucket, object).await?;
+        let touched = versions.iter().position(|oi| match version_id {
+            Some(vid) => oi.version_id.is_some_and(|v| v.to_string() == vid),
+            None => oi.is_latest,
+        });
+        if let Some(idx) = touched {
+            let newer = idx.checked_sub(1).and_then(|i| versions[i].mod_time);
+            let older = versions.get(idx + 1).and_then(|oi| oi.mod_time);
+            if newer.is_some_and(|t| new_mtime >= t) || older.is_some_and(|t| new_mtime <= t) {
+                return Err(StorageError::InvalidArgument(
+                    bucket.to_owned(),
+                    object.to_owned(),
+                    "the new mod time would reorder the versions of the object".to_owned(),
+                ));
+            }
+        }
+
+        let object = encode_dir_object(object);
+
+        if self.single_pool() {
+            return self.pools[0]
+                .touch_object(bucket, object.as_str(), version_id, new_mtime)
+                .await;
+        }
+
+        let opts = ObjectOptions {
+            version_id: version_id.map(str::to_owned),
+            ..Default::default()
+        };
+        let idx = self.get_pool_idx_existing_with_opts(bucket, object.as_str(), &opts).await?;
+
+        self.pools[idx]
+            .touch_object(bucket, object.as_str(), version_id, new_mtime)
+            .await
+    }
+
     #[tracing::instrument(skip(self))]
     async fn heal_format(&self, dry_run: bool) -> Result<(HealResultItem, Option<Error>)> {
         info!("heal_format");
@@ -2307,6 +3116,84 @@ impl StorageAPI for ECStore {
         Ok((HealResultItem::default(), Some(Error::FileNotFound)))
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn scrub_object(&self, bucket: &str, object: &str, opts: &HealOpts) -> Result<Vec<(String, HealResultItem)>> {
+        let object = encode_dir_object(object);
+
+        let futures = self.pools.iter().map(|pool| pool.scrub_object(bucket, &object, opts));
+        let results = join_all(futures).await;
+
+        let mut first_err = None;
+        for res in results {
+            match res {
+                Ok(mut versions) => {
+                    for (_, result) in versions.iter_mut() {
+                        result.object = decode_dir_object(&result.object);
+                    }
+                    return Ok(versions);
+                }
+                Err(err) => {
+                    if first_err.is_none() && !is_err_object_not_found(&err) {
+                        first_err = Some(err);
+                    }
+                }
+            }
+        }
+
+        Err(first_err.unwrap_or_else(|| to_object_err(Error::FileNotFound, vec![bucket, &object])))
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn verify_metadata_consistency(&self, bucket: &str, object: &str) -> Result<MetaConsistencyReport> {
+        let object = encode_dir_object(object);
+
+        let futures = self
+            .pools
+            .iter()
+            .map(|pool| pool.verify_metadata_consistency(bucket, &object));
+        let results = join_all(futures).await;
+
+        let mut first_err = None;
+        for res in results {
+            match res {
+                Ok(mut report) => {
+                    report.object = decode_dir_object(&report.object);
+                    return Ok(report);
+                }
+                Err(err) => {
+                    if first_err.is_none() && !is_err_object_not_found(&err) {
+                        first_err = Some(err);
+                    }
+                }
+            }
+        }
+
+        Err(first_err.unwrap_or_else(|| to_object_err(Error::FileNotFound, vec![bucket, &object])))
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn replication_stats(self: Arc<Self>, bucket: &str) -> Result<ReplicationStats> {
+        let mut stats = ReplicationStats::default();
+
+        let (mut marker, mut version_marker) = (None, None);
+        loop {
+            let page = self
+                .clone()
+                .list_object_versions(bucket, "", marker, version_marker, None, 1000)
+                .await?;
+
+            for oi in page.objects.iter().filter(|oi| !oi.delete_marker) {
+                stats.add_object(oi);
+            }
+
+            if !page.is_truncated {
+                return Ok(stats);
+            }
+            marker = page.next_marker;
+            version_marker = page.next_version_idmarker;
+        }
+    }
+
     #[tracing::instrument(skip(self))]
     async fn heal_objects(
         &self,
@@ -2459,6 +3346,42 @@ impl StorageAPI for ECStore {
     }
 }
 
+/// Deletes `objects` a batch at a time, a few batches in parallel, and records each outcome in
+/// `report`. Objects that object lock keeps in place are reported as skipped.
+pub(crate) async fn delete_prefix_batch<S: StorageAPI>(
+    store: &Arc<S>,
+    bucket: &str,
+    objects: Vec<ObjectToDelete>,
+    opts: &ObjectOptions,
+    report: &mut DeletePrefixReport,
+) -> Result<()> {
+    // owned chunks keep the futures free of borrows, which the instrumented async fns need to be Send
+    let chunks: Vec<Vec<ObjectToDelete>> = objects.chunks(DELETE_PREFIX_BATCH_SIZE).map(<[_]>::to_vec).collect();
+    let results: Vec<_> = stream::iter(chunks.into_iter().map(|chunk| {
+        let (store, bucket, opts) = (store.clone(), bucket.to_string(), opts.clone());
+        async move {
+            let res = store.delete_objects(&bucket, chunk.clone(), opts).await;
+            (chunk, res)
+        }
+    }))
+    .buffer_unordered(DELETE_PREFIX_CONCURRENCY)
+    .collect()
+    .await;
+
+    for (chunk, res) in results {
+        let (_, errs) = res?;
+        for (obj, err) in chunk.iter().zip(errs) {
+            match err {
+                None => report.deleted += 1,
+                Some(StorageError::ObjectLocked(..)) => report.skipped.push(obj.clone()),
+                Some(err) => return Err(err),
+            }
+        }
+    }
+
+    Ok(())
+}
+
 async fn init_local_peer(endpoint_pools: &EndpointServerPools, host: &String, port: &String) {
     let mut peer_set = Vec::new();
     endpoint_pools.as_ref().iter().for_each(|endpoints| {
@@ -2633,6 +3556,39 @@ fn check_abort_multipart_args(bucket: &str, object: &str, upload_id: &str) -> Re
     check_multipart_object_args(bucket, object, upload_id)
 }
 
+// Rejects writes while the server is read-only, the system bucket stays writable for internal state.
+fn check_read_only(bucket: &str) -> Result<()> {
+    if is_global_read_only() && !is_meta_bucketname(bucket) {
+        return Err(StorageError::ReadOnlyMode);
+    }
+
+    Ok(())
+}
+
+// A read that failed after its deadline passed timed out, whatever quorum error the disks added up to
+fn past_deadline<T>(deadline: Option<Instant>, res: Result<T>) -> Result<T> {
+    match (res, deadline) {
+        (Err(_), Some(deadline)) if Instant::now() >= deadline => Err(StorageError::DeadlineExceeded),
+        (res, _) => res,
+    }
+}
+
+/// Fails with `MetadataTooLarge` when the UTF-8 length of all user metadata keys and values
+/// exceeds the configured limit, internal `x-rustfs-internal-` entries are not counted.
+pub fn validate_user_metadata(meta: &HashMap<String, String>) -> Result<()> {
+    let size: usize = meta
+        .iter()
+        .filter(|(k, _)| !k.to_lowercase().starts_with(RESERVED_METADATA_PREFIX_LOWER))
+        .map(|(k, v)| k.len() + v.len())
+        .sum();
+
+    if size as u64 > get_global_max_user_metadata_size() {
+        return Err(StorageError::MetadataTooLarge);
+    }
+
+    Ok(())
+}
+
 #[tracing::instrument(level = "debug")]
 fn check_put_object_args(bucket: &str, object: &str) -> Result<()> {
     if !is_meta_bucketname(bucket) && check_valid_bucket_name_strict(bucket).is_err() {
@@ -2759,9 +3715,77 @@ pub async fn has_space_for(dis: &[Option<DiskInfo>], size: i64) -> Result<bool>
     Ok(available > want as u64)
 }
 
+#[cfg(test)]
+pub(crate) mod test_util {
+    use super::*;
+    use crate::bucket::metadata_sys::init_bucket_metadata_sys;
+    use crate::global::{set_global_endpoints, update_erasure_type};
+    use std::sync::OnceLock;
+
+    static TEST_STORE: OnceLock<Arc<ECStore>> = OnceLock::new();
+
+    /// Held by tests that flip process-wide switches such as read-only mode or the max object size.
+    pub(crate) static GLOBAL_SWITCH_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+    /// Directory holding the drives of [`test_store`].
+    pub(crate) fn test_store_root() -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("rustfs-ecstore-test-{}", std::process::id()))
+    }
+
+    /// Returns a store over four local drives in a temp dir, shared by every test in the binary and
+    /// made the object layer of the calling test's thread.
+    ///
+    /// The endpoints and the bucket metadata can only be set up once per process, and the store is
+    /// built on a runtime that outlives the individual tests so its background tasks keep running.
+    pub(crate) fn test_store() -> Arc<ECStore> {
+        let store = TEST_STORE.get_or_init(|| {
+            let (tx, rx) = std::sync::mpsc::channel();
+            std::thread::spawn(move || {
+                let rt = tokio::runtime::Builder::new_multi_thread().enable_all().build().unwrap();
+                rt.block_on(async move {
+                    let root = test_store_root();
+                    let volumes: Vec<String> = (1..=4)
+                        .map(|i| root.join(format!("d{i}")).to_string_lossy().to_string())
+                        .collect();
+                    for volume in volumes.iter() {
+                        std::fs::create_dir_all(volume).unwrap();
+                    }
+
+                    tx.send(new_test_store(volumes).await).unwrap();
+                    std::future::pending::<()>().await
+                });
+            });
+            rx.recv().unwrap()
+        });
+        crate::global::set_test_object_layer(store.clone());
+        store.clone()
+    }
+
+    /// Builds the object layer of the calling thread over `volumes`, one pool per ellipses pattern if they have any.
+    pub(crate) async fn new_test_store(volumes: Vec<String>) -> Arc<ECStore> {
+        let address: SocketAddr = "127.0.0.1:9000".parse().unwrap();
+
+        let (endpoint_pools, setup_type) = EndpointServerPools::from_volumes(&address.to_string(), volumes).unwrap();
+        set_global_endpoints(endpoint_pools.as_ref().clone());
+        update_erasure_type(setup_type).await;
+        init_local_disks(endpoint_pools.clone()).await.unwrap();
+
+        let store = ECStore::new(address, endpoint_pools).await.unwrap();
+        init_bucket_metadata_sys(store.clone(), Vec::new()).await;
+        store
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cmd::bucket_replication::REPLICATION_STATUS;
+    use crate::disk::RUSTFS_META_TMP_BUCKET;
+    use crate::global::{set_global_max_object_size, set_global_read_only};
+    use crate::heal::heal_commands::DRIVE_STATE_OK;
+    use crate::store_api::{ObjectToDelete, PutObjReader};
+    use rustfs_rio::{HashReader, WarpReader};
+    use std::io::Cursor;
 
     // Test validation functions
     #[test]
@@ -2948,4 +3972,2653 @@ mod tests {
         assert!(check_put_object_args("", "test-object").is_err());
         assert!(check_put_object_args("test-bucket", "").is_err());
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_read_only_mode() {
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        store
+            .make_bucket("read-only-test", &MakeBucketOptions::default())
+            .await
+            .unwrap();
+
+        let opts = ObjectOptions::default();
+        let mut data = PutObjReader::from_vec(b"hello".to_vec());
+        store.put_object("read-only-test", "obj", &mut data, &opts).await.unwrap();
+
+        set_global_read_only(true);
+
+        let mut data = PutObjReader::from_vec(b"world".to_vec());
+        let put = store.put_object("read-only-test", "obj2", &mut data, &opts).await;
+        let del = store.delete_object("read-only-test", "obj", opts.clone()).await;
+        let mk = store.make_bucket("read-only-test2", &MakeBucketOptions::default()).await;
+        let info = store.get_object_info("read-only-test", "obj", &opts).await;
+        let list = store
+            .clone()
+            .list_objects_v2("read-only-test", "", None, None, 10, false, None)
+            .await;
+
+        set_global_read_only(false);
+
+        assert_eq!(put.unwrap_err(), StorageError::ReadOnlyMode);
+        assert_eq!(del.unwrap_err(), StorageError::ReadOnlyMode);
+        assert_eq!(mk.unwrap_err(), StorageError::ReadOnlyMode);
+        assert_eq!(info.unwrap().size, 5);
+        assert_eq!(list.unwrap().objects.len(), 1);
+
+        let mut data = PutObjReader::from_vec(b"world".to_vec());
+        store.put_object("read-only-test", "obj2", &mut data, &opts).await.unwrap();
+    }
+
+    fn tmp_entries() -> Vec<String> {
+        let mut entries = Vec::new();
+        for i in 1..=4 {
+            let tmp = test_util::test_store_root()
+                .join(format!("d{i}"))
+                .join(RUSTFS_META_TMP_BUCKET);
+            for entry in std::fs::read_dir(tmp).unwrap() {
+                let name = entry.unwrap().file_name().to_string_lossy().to_string();
+                if name != ".trash" {
+                    entries.push(name);
+                }
+            }
+        }
+        entries
+    }
+
+    // A stream of unknown length, so the limit can only be enforced while encoding.
+    fn unsized_reader(len: usize) -> PutObjReader {
+        let data = vec![7u8; len];
+        PutObjReader::new(HashReader::new(Box::new(WarpReader::new(Cursor::new(data))), -1, -1, None, false).unwrap())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_max_object_size() {
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        store
+            .make_bucket("max-size-test", &MakeBucketOptions::default())
+            .await
+            .unwrap();
+        let opts = ObjectOptions::default();
+        let upload = store.new_multipart_upload("max-size-test", "multipart", &opts).await.unwrap();
+
+        set_global_max_object_size(Some(1024));
+
+        let mut data = PutObjReader::from_vec(vec![7u8; 2048]);
+        let sized = store.put_object("max-size-test", "sized", &mut data, &opts).await;
+        let mut data = unsized_reader(256 * 1024);
+        let put = store.put_object("max-size-test", "unsized", &mut data, &opts).await;
+        let mut data = unsized_reader(256 * 1024);
+        let part = store
+            .put_object_part("max-size-test", "multipart", &upload.upload_id, 1, &mut data, &opts)
+            .await;
+        let mut data = unsized_reader(1024);
+        let small = store.put_object("max-size-test", "small", &mut data, &opts).await;
+        let leftovers = tmp_entries();
+
+        set_global_max_object_size(None);
+
+        assert_eq!(sized.unwrap_err(), StorageError::EntityTooLarge);
+        assert_eq!(put.unwrap_err(), StorageError::EntityTooLarge);
+        assert_eq!(part.unwrap_err(), StorageError::EntityTooLarge);
+        assert_eq!(small.unwrap().size, 1024);
+        assert!(leftovers.is_empty(), "temp data left behind: {leftovers:?}");
+        assert!(store.get_object_info("max-size-test", "unsized", &opts).await.is_err());
+    }
+
+    async fn list_versions_from(
+        store: &Arc<ECStore>,
+        bucket: &str,
+        max_keys: i32,
+        mut marker: Option<String>,
+        mut version_marker: Option<String>,
+    ) -> Vec<(String, Option<Uuid>)> {
+        let mut listed = Vec::new();
+        loop {
+            let page = store
+                .clone()
+                .list_object_versions(bucket, "", marker, version_marker, None, max_keys)
+                .await
+                .unwrap();
+            listed.extend(page.objects.iter().map(|o| (o.name.clone(), o.version_id)));
+            if !page.is_truncated {
+                return listed;
+            }
+            marker = page.next_marker;
+            version_marker = page.next_version_idmarker;
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_list_object_versions_pagination() {
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "versions-test";
+        store
+            .make_bucket(
+                bucket,
+                &MakeBucketOptions {
+                    versioning_enabled: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let opts = ObjectOptions {
+            versioned: true,
+            ..Default::default()
+        };
+        for (name, count) in [("a", 3), ("b", 2), ("c", 1)] {
+            for _ in 0..count {
+                let mut data = PutObjReader::from_vec(name.as_bytes().to_vec());
+                store.put_object(bucket, name, &mut data, &opts).await.unwrap();
+            }
+        }
+
+        // Newest version first within each key, and every page size yields the same listing.
+        let all = list_versions_from(&store, bucket, 1000, None, None).await;
+        assert_eq!(
+            all.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(),
+            ["a", "a", "a", "b", "b", "c"]
+        );
+        for max_keys in 1..=4 {
+            assert_eq!(list_versions_from(&store, bucket, max_keys, None, None).await, all, "max_keys {max_keys}");
+        }
+
+        // Delete the version the first page ended on before asking for the next one.
+        let first = store
+            .clone()
+            .list_object_versions(bucket, "", None, None, None, 2)
+            .await
+            .unwrap();
+        assert!(first.is_truncated);
+        assert_eq!(first.next_marker.as_deref(), Some("a"));
+        let marker_version = first.objects.last().unwrap().version_id;
+        assert_eq!(marker_version, all[1].1);
+
+        let delete = ObjectToDelete {
+            object_name: "a".to_string(),
+            version_id: marker_version,
+        };
+        let (_, errs) = store.delete_objects(bucket, vec![delete], opts.clone()).await.unwrap();
+        assert!(errs.iter().all(|e| e.is_none()));
+
+        let rest = list_versions_from(&store, bucket, 2, first.next_marker, first.next_version_idmarker).await;
+        assert_eq!(rest, all[2..]);
+
+        // A plain version id marker resumes right after that version.
+        let rest = list_versions_from(&store, bucket, 2, Some("b".to_string()), all[3].1.map(|v| v.to_string())).await;
+        assert_eq!(rest, all[4..]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_make_bucket_lock_enabled() {
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+
+        store
+            .make_bucket(
+                "lock-enabled-test",
+                &MakeBucketOptions {
+                    lock_enabled: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        store
+            .make_bucket("lock-disabled-test", &MakeBucketOptions::default())
+            .await
+            .unwrap();
+
+        let meta = metadata_sys::get("lock-enabled-test").await.unwrap();
+        assert!(meta.object_locking());
+        assert!(meta.versioning());
+        let info = store
+            .get_bucket_info("lock-enabled-test", &BucketOptions::default())
+            .await
+            .unwrap();
+        assert!(info.object_locking);
+        assert!(info.versionning);
+
+        let meta = metadata_sys::get("lock-disabled-test").await.unwrap();
+        assert!(!meta.object_locking());
+        assert!(!meta.versioning());
+
+        // A second create fails without touching the existing bucket's metadata.
+        let err = store
+            .make_bucket("lock-enabled-test", &MakeBucketOptions::default())
+            .await
+            .unwrap_err();
+        assert!(is_err_bucket_exists(&err));
+        assert!(metadata_sys::get("lock-enabled-test").await.unwrap().object_locking());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_delete_objects_reports_markers() {
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+
+        let delete_one = |bucket: &'static str, version_id: Option<Uuid>, opts: ObjectOptions| {
+            let store = store.clone();
+            async move {
+                let delete = ObjectToDelete {
+                    object_name: "obj".to_string(),
+                    version_id,
+                };
+                let (mut deleted, errs) = store.delete_objects(bucket, vec![delete], opts).await.unwrap();
+                assert!(errs.iter().all(|e| e.is_none()), "{errs:?}");
+                deleted.remove(0)
+            }
+        };
+
+        // Unversioned bucket: the object is simply gone.
+        let bucket = "delete-unversioned";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+        let mut data = PutObjReader::from_vec(b"data".to_vec());
+        store
+            .put_object(bucket, "obj", &mut data, &ObjectOptions::default())
+            .await
+            .unwrap();
+        let dobj = delete_one(bucket, None, ObjectOptions::default()).await;
+        assert_eq!(dobj.object_name, "obj");
+        assert!(!dobj.delete_marker);
+        assert_eq!(dobj.delete_marker_version_id, None);
+        assert_eq!(dobj.version_id, None);
+
+        let bucket = "delete-versioned";
+        store
+            .make_bucket(
+                bucket,
+                &MakeBucketOptions {
+                    versioning_enabled: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let opts = ObjectOptions {
+            versioned: true,
+            ..Default::default()
+        };
+        let mut data = PutObjReader::from_vec(b"data".to_vec());
+        let put = store.put_object(bucket, "obj", &mut data, &opts).await.unwrap();
+        let put_version = put.version_id.unwrap();
+
+        // Deleting the current version writes a delete marker.
+        let dobj = delete_one(bucket, None, opts.clone()).await;
+        assert!(dobj.delete_marker);
+        assert_eq!(dobj.version_id, None);
+        let marker_version = dobj.delete_marker_version_id.clone().unwrap();
+        assert_ne!(marker_version, put_version.to_string());
+
+        // Deleting a specific version removes it permanently.
+        let dobj = delete_one(bucket, Some(put_version), opts.clone()).await;
+        assert!(!dobj.delete_marker);
+        assert_eq!(dobj.delete_marker_version_id, None);
+        assert_eq!(dobj.version_id, Some(put_version.to_string()));
+
+        // Removing the marker itself reports the marker's version.
+        let marker_uuid = Uuid::parse_str(&marker_version).unwrap();
+        let dobj = delete_one(bucket, Some(marker_uuid), opts.clone()).await;
+        assert!(dobj.delete_marker);
+        assert_eq!(dobj.delete_marker_version_id.as_deref(), Some(marker_version.as_str()));
+        assert_eq!(dobj.version_id.as_deref(), Some(marker_version.as_str()));
+
+        let versions = store
+            .clone()
+            .list_object_versions(bucket, "", None, None, None, 10)
+            .await
+            .unwrap();
+        assert!(versions.objects.is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_health_write_quorum() {
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+
+        let health = store.health().await;
+        assert_eq!(health.pools.len(), 1);
+        let pool = &health.pools[0];
+        assert_eq!((pool.set_drive_count, pool.read_quorum, pool.write_quorum), (4, 2, 3));
+        assert_eq!(health.disks_online, 4);
+        assert!(health.read_quorum && health.write_quorum);
+
+        let set = store.pools[0].disk_set[0].clone();
+        let saved = set.disks.read().await.clone();
+
+        // One missing disk leaves write quorum intact, the second one takes it away.
+        // Results are checked after the disks are put back so other tests keep a full set.
+        let mut seen = Vec::new();
+        for i in 0..3 {
+            set.disks.write().await[i] = None;
+            let health = store.health().await;
+            seen.push((health.disks_online, health.write_quorum, health.read_quorum));
+            // the pool and the set report the same view of their own disks
+            for layer in [store.pools[0].health().await, set.health().await] {
+                assert_eq!(
+                    (layer.disks_online, layer.write_quorum, layer.read_quorum),
+                    (health.disks_online, health.write_quorum, health.read_quorum)
+                );
+            }
+        }
+        *set.disks.write().await = saved;
+
+        assert_eq!(seen, [(3, true, true), (2, false, true), (1, false, false)]);
+        assert!(store.health().await.write_quorum);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_bucket_write_quorum() {
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "write-quorum";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+
+        for quorum in [0, 5] {
+            let err = store.set_bucket_write_quorum(bucket, Some(quorum)).await.unwrap_err();
+            assert!(matches!(err, StorageError::InvalidArgument(..)), "{err:?}");
+        }
+        store.set_bucket_write_quorum(bucket, Some(4)).await.unwrap();
+
+        let put = |object: &'static str| {
+            let store = store.clone();
+            async move {
+                let mut data = PutObjReader::from_vec(b"data".to_vec());
+                store.put_object(bucket, object, &mut data, &ObjectOptions::default()).await
+            }
+        };
+        put("all-online").await.unwrap();
+
+        // One drive down still meets the default quorum of 3 but not the bucket's 4.
+        let set = store.pools[0].disk_set[0].clone();
+        let saved = set.disks.read().await.clone();
+        set.disks.write().await[3] = None;
+        let strict = put("one-offline").await;
+        store.set_bucket_write_quorum(bucket, None).await.unwrap();
+        let relaxed = put("one-offline").await;
+        *set.disks.write().await = saved;
+
+        assert_eq!(strict.unwrap_err(), StorageError::ErasureWriteQuorum);
+        relaxed.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_complete_multipart_part_validation() {
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "complete-parts";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+        let opts = ObjectOptions::default();
+
+        let upload = store.new_multipart_upload(bucket, "obj", &opts).await.unwrap();
+        for (part_id, len) in [(1, 5 << 20), (2, 1024), (3, 1024)] {
+            let mut data = PutObjReader::from_vec(vec![7u8; len]);
+            store
+                .put_object_part(bucket, "obj", &upload.upload_id, part_id, &mut data, &opts)
+                .await
+                .unwrap();
+        }
+
+        let info = store
+            .get_multipart_info(bucket, "obj", &upload.upload_id, &opts)
+            .await
+            .unwrap();
+        let manifest: Vec<_> = info.parts.iter().map(|p| (p.part_num, p.size)).collect();
+        assert_eq!(manifest, vec![(1, 5 << 20), (2, 1024), (3, 1024)]);
+        assert!(info.parts.iter().all(|p| p.etag.as_ref().is_some_and(|e| !e.is_empty())));
+
+        let skip_opts = ObjectOptions {
+            skip_parts: true,
+            ..Default::default()
+        };
+        let brief = store
+            .get_multipart_info(bucket, "obj", &upload.upload_id, &skip_opts)
+            .await
+            .unwrap();
+        assert!(brief.parts.is_empty());
+        assert_eq!(brief.user_defined, info.user_defined);
+
+        let complete = |nums: &[usize]| {
+            nums.iter()
+                .map(|n| CompletePart {
+                    part_num: *n,
+                    etag: info.parts[n - 1].etag.clone(),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let err = store
+            .clone()
+            .complete_multipart_upload(bucket, "obj", &upload.upload_id, complete(&[2, 1, 3]), &opts)
+            .await
+            .unwrap_err();
+        assert_eq!(err, StorageError::InvalidPartOrder);
+
+        let err = store
+            .clone()
+            .complete_multipart_upload(bucket, "obj", &upload.upload_id, complete(&[1, 2, 3]), &opts)
+            .await
+            .unwrap_err();
+        assert_eq!(err, StorageError::EntityTooSmall);
+
+        // Only the last part may be small.
+        let obj = store
+            .clone()
+            .complete_multipart_upload(bucket, "obj", &upload.upload_id, complete(&[1, 3]), &opts)
+            .await
+            .unwrap();
+        assert_eq!(obj.size, (5 << 20) + 1024);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_complete_multipart_etag() {
+        use md5::{Digest, Md5};
+
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "multipart-etag";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+        let opts = ObjectOptions::default();
+
+        let parts: Vec<Vec<u8>> = vec![vec![1u8; 5 << 20], vec![2u8; 5 << 20], vec![3u8; 1024]];
+        let upload = store.new_multipart_upload(bucket, "obj", &opts).await.unwrap();
+        let mut uploaded = Vec::new();
+        for (i, part) in parts.iter().enumerate() {
+            let mut data = PutObjReader::from_vec(part.clone());
+            let info = store
+                .put_object_part(bucket, "obj", &upload.upload_id, i + 1, &mut data, &opts)
+                .await
+                .unwrap();
+            assert_eq!(info.etag.as_deref(), Some(format!("{:x}", Md5::digest(part)).as_str()));
+            uploaded.push(CompletePart {
+                part_num: i + 1,
+                etag: info.etag,
+            });
+        }
+
+        let obj = store
+            .clone()
+            .complete_multipart_upload(bucket, "obj", &upload.upload_id, uploaded, &opts)
+            .await
+            .unwrap();
+
+        let mut concat = Vec::new();
+        for part in parts.iter() {
+            concat.extend_from_slice(&Md5::digest(part));
+        }
+        let expected = format!("{:x}-3", Md5::digest(&concat));
+        assert_eq!(obj.etag.as_deref(), Some(expected.as_str()));
+
+        let info = store.get_object_info(bucket, "obj", &opts).await.unwrap();
+        assert_eq!(info.etag.as_deref(), Some(expected.as_str()));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_copy_object_part_range() {
+        use tokio::io::AsyncReadExt;
+
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "copy-part-range";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+        let opts = ObjectOptions::default();
+
+        // Spans several erasure blocks so the range crosses block boundaries.
+        let src: Vec<u8> = (0..3_500_000u32).map(|i| (i % 251) as u8).collect();
+        let mut data = PutObjReader::from_vec(src.clone());
+        let src_info = store.put_object(bucket, "src", &mut data, &opts).await.unwrap();
+
+        let upload = store.new_multipart_upload(bucket, "dst", &opts).await.unwrap();
+        let (start, length) = (1_000_000usize, 1_500_000usize);
+        let part = store
+            .copy_object_part(
+                bucket,
+                "src",
+                bucket,
+                "dst",
+                &upload.upload_id,
+                1,
+                start as i64,
+                length as i64,
+                &src_info,
+                &opts,
+                &opts,
+            )
+            .await
+            .unwrap();
+        assert_eq!(part.size, length);
+
+        for (offset, len) in [(-1, 10), (0, src.len() as i64 + 1), (src.len() as i64, 1)] {
+            let err = store
+                .copy_object_part(bucket, "src", bucket, "dst", &upload.upload_id, 2, offset, len, &src_info, &opts, &opts)
+                .await
+                .unwrap_err();
+            assert_eq!(err, StorageError::InvalidRange);
+        }
+
+        let parts = vec![CompletePart {
+            part_num: 1,
+            etag: part.etag.clone(),
+        }];
+        store
+            .clone()
+            .complete_multipart_upload(bucket, "dst", &upload.upload_id, parts, &opts)
+            .await
+            .unwrap();
+
+        let mut reader = store
+            .get_object_reader(bucket, "dst", None, HeaderMap::new(), &opts)
+            .await
+            .unwrap();
+        let mut copied = Vec::new();
+        reader.stream.read_to_end(&mut copied).await.unwrap();
+        assert!(copied == src[start..start + length], "copied range differs from the source");
+
+        // Small objects are stored inline in xl.meta.
+        let small = &src[..100_000];
+        let mut data = PutObjReader::from_vec(small.to_vec());
+        store.put_object(bucket, "small", &mut data, &opts).await.unwrap();
+        let range = HTTPRangeSpec {
+            is_suffix_length: false,
+            start: 5_000,
+            end: 24_999,
+        };
+        let mut reader = store
+            .get_object_reader(bucket, "small", Some(range), HeaderMap::new(), &opts)
+            .await
+            .unwrap();
+        let mut ranged = Vec::new();
+        reader.stream.read_to_end(&mut ranged).await.unwrap();
+        assert!(ranged == small[5_000..25_000], "inline range differs from the source");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_new_multipart_upload_idempotency_key() {
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "upload-idempotency";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+
+        let keyed = ObjectOptions {
+            idempotency_key: Some("retry-1".to_string()),
+            ..Default::default()
+        };
+        let first = store.new_multipart_upload(bucket, "obj", &keyed).await.unwrap();
+        let retry = store.new_multipart_upload(bucket, "obj", &keyed).await.unwrap();
+        assert_eq!(first.upload_id, retry.upload_id);
+
+        let uploads = store
+            .list_multipart_uploads(bucket, "obj", None, None, None, 100)
+            .await
+            .unwrap();
+        assert_eq!(uploads.uploads.len(), 1);
+
+        // the key is scoped to the object, and no key means a new upload every time
+        let other = store.new_multipart_upload(bucket, "other", &keyed).await.unwrap();
+        assert_ne!(other.upload_id, first.upload_id);
+        let unkeyed = store
+            .new_multipart_upload(bucket, "obj", &ObjectOptions::default())
+            .await
+            .unwrap();
+        assert_ne!(unkeyed.upload_id, first.upload_id);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_object_lock_put_and_delete() {
+        use time::format_description::well_known::Rfc3339;
+
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "worm-put-delete";
+        store
+            .make_bucket(
+                bucket,
+                &MakeBucketOptions {
+                    lock_enabled: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let versioned = ObjectOptions {
+            versioned: true,
+            ..Default::default()
+        };
+        let put = |object: &'static str, user_defined: HashMap<String, String>| {
+            let store = store.clone();
+            let opts = ObjectOptions {
+                user_defined,
+                ..versioned.clone()
+            };
+            async move {
+                let mut data = PutObjReader::from_vec(b"worm".to_vec());
+                store.put_object(bucket, object, &mut data, &opts).await
+            }
+        };
+        let at_version = |version: Uuid| ObjectOptions {
+            version_id: Some(version.to_string()),
+            ..versioned.clone()
+        };
+
+        // a legal hold does not stop new versions, only the held one is immutable
+        let held = put("held", HashMap::from([("x-amz-object-lock-legal-hold".to_string(), "ON".to_string())]))
+            .await
+            .unwrap();
+        let held_version = held.version_id.unwrap();
+        let next = put("held", HashMap::new()).await.unwrap();
+        assert_ne!(next.version_id, Some(held_version));
+
+        let mut data = PutObjReader::from_vec(b"overwrite".to_vec());
+        let err = store
+            .put_object(bucket, "held", &mut data, &at_version(held_version))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, StorageError::ObjectLocked(..)), "{err:?}");
+        let err = store
+            .delete_object(bucket, "held", at_version(held_version))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, StorageError::ObjectLocked(..)), "{err:?}");
+
+        // compliance retention
+        let until = (OffsetDateTime::now_utc() + Duration::from_secs(3600))
+            .format(&Rfc3339)
+            .unwrap();
+        let retained = put(
+            "retained",
+            HashMap::from([
+                ("x-amz-object-lock-mode".to_string(), "COMPLIANCE".to_string()),
+                ("x-amz-object-lock-retain-until-date".to_string(), until),
+            ]),
+        )
+        .await
+        .unwrap();
+        let retained_version = retained.version_id.unwrap();
+        put("retained", HashMap::new()).await.unwrap();
+
+        let err = store
+            .delete_object(bucket, "retained", at_version(retained_version))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, StorageError::ObjectLocked(..)), "{err:?}");
+        let (_, errs) = store
+            .delete_objects(
+                bucket,
+                vec![ObjectToDelete {
+                    object_name: "retained".to_string(),
+                    version_id: Some(retained_version),
+                }],
+                versioned.clone(),
+            )
+            .await
+            .unwrap();
+        assert!(matches!(errs[0], Some(StorageError::ObjectLocked(..))), "{errs:?}");
+
+        // a delete marker leaves the locked version in place
+        let marker = store.delete_object(bucket, "retained", versioned.clone()).await.unwrap();
+        assert!(marker.delete_marker);
+        store
+            .get_object_info(bucket, "retained", &at_version(retained_version))
+            .await
+            .unwrap();
+
+        // retention in the past no longer protects the version
+        let expired = put(
+            "expired",
+            HashMap::from([
+                ("x-amz-object-lock-mode".to_string(), "COMPLIANCE".to_string()),
+                ("x-amz-object-lock-retain-until-date".to_string(), "2000-01-01T00:00:00Z".to_string()),
+            ]),
+        )
+        .await
+        .unwrap();
+        store
+            .delete_object(bucket, "expired", at_version(expired.version_id.unwrap()))
+            .await
+            .unwrap();
+
+        // governance retention gives way to a permitted bypass, compliance does not
+        let until = (OffsetDateTime::now_utc() + Duration::from_secs(3600))
+            .format(&Rfc3339)
+            .unwrap();
+        let governed = put(
+            "governed",
+            HashMap::from([
+                ("x-amz-object-lock-mode".to_string(), "GOVERNANCE".to_string()),
+                ("x-amz-object-lock-retain-until-date".to_string(), until),
+            ]),
+        )
+        .await
+        .unwrap();
+        let governed_version = governed.version_id.unwrap();
+        let err = store
+            .delete_object(bucket, "governed", at_version(governed_version))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, StorageError::ObjectLocked(..)), "{err:?}");
+        let bypass = |version: Uuid| ObjectOptions {
+            bypass_governance: true,
+            ..at_version(version)
+        };
+        store
+            .delete_object(bucket, "governed", bypass(governed_version))
+            .await
+            .unwrap();
+        let err = store
+            .delete_object(bucket, "retained", bypass(retained_version))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, StorageError::ObjectLocked(..)), "{err:?}");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_clear_object_retention() {
+        use crate::bucket::object_lock::objectlock;
+        use time::format_description::well_known::Rfc3339;
+
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "worm-clear-retention";
+        store
+            .make_bucket(
+                bucket,
+                &MakeBucketOptions {
+                    lock_enabled: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let until = (OffsetDateTime::now_utc() + Duration::from_secs(3600))
+            .format(&Rfc3339)
+            .unwrap();
+        let put = |object: &'static str, mode: &'static str| {
+            let store = store.clone();
+            let opts = ObjectOptions {
+                versioned: true,
+                user_defined: HashMap::from([
+                    ("x-amz-object-lock-mode".to_string(), mode.to_string()),
+                    ("x-amz-object-lock-retain-until-date".to_string(), until.clone()),
+                ]),
+                ..Default::default()
+            };
+            async move {
+                let mut data = PutObjReader::from_vec(b"worm".to_vec());
+                let info = store.put_object(bucket, object, &mut data, &opts).await.unwrap();
+                info.version_id.unwrap().to_string()
+            }
+        };
+        let at_version = |version: &str| ObjectOptions {
+            versioned: true,
+            version_id: Some(version.to_string()),
+            ..Default::default()
+        };
+
+        let governed = put("governed", "GOVERNANCE").await;
+        let err = store
+            .clear_object_retention(bucket, "governed", &governed, false)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, StorageError::ObjectLocked(..)), "{err:?}");
+
+        store
+            .clear_object_retention(bucket, "governed", &governed, true)
+            .await
+            .unwrap();
+        let info = store
+            .get_object_info(bucket, "governed", &at_version(&governed))
+            .await
+            .unwrap();
+        let retention = objectlock::get_object_retention_meta(info.user_defined);
+        assert!(retention.mode.is_none());
+        store.delete_object(bucket, "governed", at_version(&governed)).await.unwrap();
+
+        // bypass does not apply to compliance mode
+        let compliant = put("compliant", "COMPLIANCE").await;
+        let err = store
+            .clear_object_retention(bucket, "compliant", &compliant, true)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, StorageError::ComplianceModeLocked(..)), "{err:?}");
+        let info = store
+            .get_object_info(bucket, "compliant", &at_version(&compliant))
+            .await
+            .unwrap();
+        assert_eq!(info.user_defined.get("x-amz-object-lock-mode").map(String::as_str), Some("COMPLIANCE"));
+        let err = store
+            .delete_object(bucket, "compliant", at_version(&compliant))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, StorageError::ObjectLocked(..)), "{err:?}");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_replication_stats() {
+        use crate::store_api::{ReplicationCount, TargetReplicationStats};
+
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "replication-stats";
+        store
+            .make_bucket(
+                bucket,
+                &MakeBucketOptions {
+                    versioning_enabled: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        const A: &str = "arn:rustfs:replication:us-east-1:a:dst";
+        const B: &str = "arn:rustfs:replication:eu-west-1:b:dst";
+        let status_key = format!("{RESERVED_METADATA_PREFIX_LOWER}{REPLICATION_STATUS}");
+        for (object, len, status) in [
+            ("one", 10, format!("{A}=COMPLETED;{B}=FAILED;")),
+            ("two", 20, format!("{A}=PENDING;")),
+            // an older version of "two" still counts
+            ("two", 40, format!("{A}=COMPLETED;{B}=COMPLETE;")),
+            ("three", 5, format!("{A}=FAILED;{B}=PENDING;")),
+            ("replica", 7, format!("{A}=REPLICA;")),
+            ("local", 9, String::new()),
+        ] {
+            let opts = ObjectOptions {
+                versioned: true,
+                user_defined: HashMap::from([(status_key.clone(), status)]),
+                ..Default::default()
+            };
+            let mut data = PutObjReader::from_vec(vec![1u8; len]);
+            store.put_object(bucket, object, &mut data, &opts).await.unwrap();
+        }
+        let versioned = ObjectOptions {
+            versioned: true,
+            ..Default::default()
+        };
+        store.delete_object(bucket, "one", versioned).await.unwrap();
+
+        let stats = store.clone().replication_stats(bucket).await.unwrap();
+        let count = |count, bytes| ReplicationCount { count, bytes };
+        assert_eq!(stats.targets.len(), 2);
+        assert_eq!(
+            stats.targets[A],
+            TargetReplicationStats {
+                pending: count(1, 20),
+                failed: count(1, 5),
+                completed: count(2, 50),
+            }
+        );
+        assert_eq!(
+            stats.targets[B],
+            TargetReplicationStats {
+                pending: count(1, 5),
+                failed: count(1, 10),
+                completed: count(1, 40),
+            }
+        );
+
+        // the pool and its sets walk their own drives and come to the same totals
+        assert_eq!(store.pools[0].clone().replication_stats(bucket).await.unwrap(), stats);
+        assert_eq!(store.pools[0].disk_set[0].clone().replication_stats(bucket).await.unwrap(), stats);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_get_object_infos() {
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "get-object-infos";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+
+        for (name, body) in [("a", "one"), ("dir/b", "three"), ("dir/", "")] {
+            let mut data = PutObjReader::from_vec(body.as_bytes().to_vec());
+            store
+                .put_object(bucket, name, &mut data, &ObjectOptions::default())
+                .await
+                .unwrap();
+        }
+
+        let objects: Vec<String> = ["a", "missing", "dir/b", "", "dir/"].iter().map(|s| s.to_string()).collect();
+        let infos = store
+            .get_object_infos(bucket, &objects, &ObjectOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(infos.len(), objects.len());
+
+        for i in [0, 2, 4] {
+            let info = infos[i].as_ref().unwrap();
+            let single = store
+                .get_object_info(bucket, &objects[i], &ObjectOptions::default())
+                .await
+                .unwrap();
+            assert_eq!(info.name, objects[i]);
+            assert_eq!((info.size, &info.etag, info.mod_time), (single.size, &single.etag, single.mod_time));
+        }
+        assert_eq!(infos[0].as_ref().unwrap().size, 3);
+        assert!(is_err_object_not_found(infos[1].as_ref().unwrap_err()), "{:?}", infos[1]);
+        assert!(matches!(infos[3], Err(StorageError::ObjectNameInvalid(..))), "{:?}", infos[3]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_list_cache_list_objects_v2() {
+        use crate::cache_value::list_cache::set_list_cache;
+
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "list-cache";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+
+        let put = |name: &'static str| {
+            let store = store.clone();
+            async move {
+                let mut data = PutObjReader::from_vec(b"data".to_vec());
+                store
+                    .put_object(bucket, name, &mut data, &ObjectOptions::default())
+                    .await
+                    .unwrap();
+            }
+        };
+        let list = || store.clone().list_objects_v2(bucket, "a/", None, None, 1000, false, None);
+
+        put("a/1").await;
+        set_list_cache(16, Duration::from_secs(60));
+        let first = list().await.unwrap();
+
+        // With no disks in the set only the cache can answer.
+        let set = store.pools[0].disk_set[0].clone();
+        let saved = set.disks.read().await.clone();
+        *set.disks.write().await = vec![None; saved.len()];
+        let cached = list().await;
+        *set.disks.write().await = saved;
+
+        put("a/2").await;
+        let after_put = list().await;
+        set_list_cache(0, Duration::ZERO);
+
+        let names = |info: &ListObjectsV2Info| info.objects.iter().map(|o| o.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&first), vec!["a/1"]);
+        assert_eq!(names(&cached.unwrap()), vec!["a/1"]);
+        assert_eq!(names(&after_put.unwrap()), vec!["a/1", "a/2"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_object_set_index() {
+        use crate::disk::format::{DISTRIBUTION_ALGO_CURRENT, DistributionAlgoVersion, FormatV3};
+        use crate::sets::{hash_set_index, object_set_index};
+
+        let store = test_util::test_store();
+        let pool = &store.pools[0];
+        let pools = EndpointServerPools(vec![pool.endpoints.clone()]);
+
+        // the live store places objects the same way
+        for object in ["a", "dir/b", "dir/c/d.txt"] {
+            let placement = object_set_index(&pools, &pool.id, "bucket", object);
+            assert_eq!(placement, object_set_index(&pools, &pool.id, "bucket", object));
+            assert_eq!(placement, vec![(0, pool.get_disks_by_key(object).set_index)]);
+        }
+
+        // with more sets, names spread over them and keep their place in every pool
+        let mut wide = pool.endpoints.clone();
+        wide.set_count = 16;
+        let pools = EndpointServerPools(vec![wide.clone(), wide]);
+        let mut seen = HashSet::new();
+        for i in 0..64 {
+            let object = format!("obj-{i}");
+            let placement = object_set_index(&pools, &pool.id, "bucket", &object);
+            assert_eq!(placement.len(), 2);
+            assert_eq!((placement[0].0, placement[1].0), (0, 1));
+            assert_eq!(placement[0].1, placement[1].1);
+            assert_eq!(placement[0].1, rustfs_utils::sip_hash(&object, 16, pool.id.as_bytes()));
+            seen.insert(placement[0].1);
+        }
+        assert!(seen.len() > 1);
+
+        // a new format hashes with the current algorithm, so does one upgraded from V2, while one
+        // upgraded from V1 keeps CRC and is not what object_set_index computes
+        assert_eq!(FormatV3::new(16, 4).erasure.distribution_algo, DISTRIBUTION_ALGO_CURRENT);
+        let sets = (0..16)
+            .map(|_| format!(r#"["{}"]"#, Uuid::new_v4()))
+            .collect::<Vec<_>>()
+            .join(",");
+        let v2 = format!(
+            r#"{{"version":"1","format":"xl","id":"{}","xl":{{"version":"2","this":"{}","sets":[{sets}],"distributionAlgo":"SIPMOD"}}}}"#,
+            pool.id,
+            Uuid::new_v4()
+        );
+        let v2 = FormatV3::upgrade(v2.as_bytes()).unwrap().unwrap();
+        assert_eq!(v2.erasure.distribution_algo, DistributionAlgoVersion::V2);
+        let v1 = format!(
+            r#"{{"version":"1","format":"xl","id":"{}","xl":{{"version":"1","disk":"{}","jbod":["{}"]}}}}"#,
+            pool.id,
+            Uuid::new_v4(),
+            Uuid::new_v4()
+        );
+        let v1 = FormatV3::upgrade(v1.as_bytes()).unwrap().unwrap();
+        assert_eq!(v1.erasure.distribution_algo, DistributionAlgoVersion::V1);
+
+        let mut moved = 0;
+        for i in 0..64 {
+            let object = format!("obj-{i}");
+            let placement = object_set_index(&pools, &v2.id, "bucket", &object)[0].1;
+            assert_eq!(
+                placement,
+                hash_set_index(&v2.erasure.distribution_algo, &v2.id, v2.erasure.sets.len(), &object)
+            );
+            let legacy = hash_set_index(&v1.erasure.distribution_algo, &v1.id, 16, &object);
+            assert_eq!(legacy, rustfs_utils::crc_hash(&object, 16));
+            if legacy != placement {
+                moved += 1;
+            }
+        }
+        assert!(moved > 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_shutdown_saves_pending_mrf() {
+        use crate::heal::mrf::{MRFState, PartialOperation, decode_mrf_list, mrf_list_path};
+
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let _store = test_util::test_store();
+        let disks = all_local_disk().await;
+        assert!(!disks.is_empty());
+
+        // a state of its own, shutting down the global one would break the other tests on the shared store;
+        // nothing runs its heal routine, so queued operations stay pending
+        let mrf = MRFState::new();
+        for object in ["mrf-a", "mrf-b"] {
+            mrf.add_partial(PartialOperation {
+                bucket: "mrf-bucket".to_string(),
+                object: object.to_string(),
+                version_id: Some(Uuid::new_v4().to_string()),
+                queued: chrono::Utc::now(),
+                ..Default::default()
+            })
+            .await;
+        }
+
+        assert_eq!(mrf.shutdown(&disks, false).await.unwrap(), 2);
+        for disk in disks.iter() {
+            let buf = disk.read_all(RUSTFS_META_BUCKET, &mrf_list_path()).await.unwrap();
+            let ops = decode_mrf_list(&buf).unwrap();
+            for object in ["mrf-a", "mrf-b"] {
+                assert!(ops.iter().any(|op| op.bucket == "mrf-bucket" && op.object == object));
+            }
+        }
+
+        // the state is closed now, later operations are dropped instead of queued
+        mrf.add_partial(PartialOperation {
+            bucket: "mrf-bucket".to_string(),
+            object: "mrf-late".to_string(),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(mrf.shutdown(&disks, false).await.unwrap(), 0);
+
+        // the next start requeues the saved operations and removes the list
+        assert_eq!(MRFState::new().load(&disks).await, 2);
+        for disk in disks.iter() {
+            assert!(disk.read_all(RUSTFS_META_BUCKET, &mrf_list_path()).await.is_err());
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_inspect_object_missing_shard() {
+        use crate::disk::{CHECK_PART_FILE_NOT_FOUND, CHECK_PART_SUCCESS};
+
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "inspect-object";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+
+        // large enough not to be inlined into xl.meta
+        let mut data = PutObjReader::from_vec(vec![3u8; 1 << 20]);
+        let info = store
+            .put_object(bucket, "obj", &mut data, &ObjectOptions::default())
+            .await
+            .unwrap();
+
+        let report = store.inspect_object(bucket, "obj", None).await.unwrap();
+        assert_eq!(report.disks.len(), 4);
+        assert!(report.disks.iter().all(|d| d.is_healthy()));
+        assert!(report.disks.iter().all(|d| d.etag == info.etag && d.parts == 1));
+        let data_dir = report.disks[0].data_dir.unwrap();
+        assert!(report.disks.iter().all(|d| d.data_dir == Some(data_dir)));
+
+        let root = test_util::test_store_root();
+        std::fs::remove_file(
+            root.join("d2")
+                .join(bucket)
+                .join("obj")
+                .join(data_dir.to_string())
+                .join("part.1"),
+        )
+        .unwrap();
+
+        let report = store.inspect_object(bucket, "obj", None).await.unwrap();
+        for disk in report.disks.iter() {
+            if disk.endpoint.ends_with("d2") {
+                assert!(!disk.is_healthy());
+                assert_eq!(disk.bitrot, vec![CHECK_PART_FILE_NOT_FOUND]);
+            } else {
+                assert_eq!(disk.bitrot, vec![CHECK_PART_SUCCESS]);
+            }
+        }
+        assert_eq!(report.disks.iter().filter(|d| !d.is_healthy()).count(), 1);
+
+        // a drive without xl.meta reports the read error instead of a layout
+        std::fs::remove_file(root.join("d3").join(bucket).join("obj").join("xl.meta")).unwrap();
+        let report = store.inspect_object(bucket, "obj", None).await.unwrap();
+        let d3 = report.disks.iter().find(|d| d.endpoint.ends_with("d3")).unwrap();
+        assert!(d3.err.is_some());
+        assert_eq!(d3.data_dir, None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_put_object_compression_round_trip() {
+        use crate::compress::CompressionOpts;
+        use rustfs_utils::CompressionAlgorithm;
+
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "compression";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+
+        let body: Vec<u8> = (0..20_000).flat_map(|i| format!("line {i}\n").into_bytes()).collect();
+        let read = |object: &'static str, range: Option<HTTPRangeSpec>| {
+            let store = store.clone();
+            async move {
+                let mut reader = store
+                    .get_object_reader(bucket, object, range, HeaderMap::new(), &ObjectOptions::default())
+                    .await
+                    .unwrap();
+                let info = reader.object_info.clone();
+                (info, reader.read_all().await.unwrap())
+            }
+        };
+
+        let opts = ObjectOptions {
+            compression: Some(CompressionOpts::new(CompressionAlgorithm::Zstd)),
+            ..Default::default()
+        };
+        let mut data = PutObjReader::from_vec(body.clone());
+        let info = store.put_object(bucket, "log.txt", &mut data, &opts).await.unwrap();
+        assert_eq!(info.is_compressed_ok().unwrap(), (CompressionAlgorithm::Zstd, true));
+        assert_eq!(info.get_actual_size().unwrap(), body.len() as i64);
+        assert!(info.size < body.len() as i64);
+
+        let (info, got) = read("log.txt", None).await;
+        assert_eq!(info.size, body.len() as i64);
+        assert!(got == body);
+
+        // ranges are taken against the uncompressed bytes
+        let range = HTTPRangeSpec {
+            is_suffix_length: false,
+            start: 70_000,
+            end: 79_999,
+        };
+        let (info, got) = read("log.txt", Some(range)).await;
+        assert_eq!(info.size, 10_000);
+        assert!(got == body[70_000..80_000]);
+
+        let suffix = HTTPRangeSpec {
+            is_suffix_length: true,
+            start: -100,
+            end: -1,
+        };
+        let (_, got) = read("log.txt", Some(suffix)).await;
+        assert!(got == body[body.len() - 100..]);
+
+        // already compressed types are stored as is
+        let mut data = PutObjReader::from_vec(body.clone());
+        let info = store.put_object(bucket, "log.zip", &mut data, &opts).await.unwrap();
+        assert!(!info.is_compressed());
+
+        // the bucket default applies when the put doesn't choose
+        store
+            .set_bucket_compression(bucket, Some(CompressionOpts::new(CompressionAlgorithm::Lz4)))
+            .await
+            .unwrap();
+        let mut data = PutObjReader::from_vec(body.clone());
+        let info = store
+            .put_object(bucket, "default.txt", &mut data, &ObjectOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(info.is_compressed_ok().unwrap(), (CompressionAlgorithm::Lz4, true));
+        assert!(read("default.txt", None).await.1 == body);
+
+        store.set_bucket_compression(bucket, None).await.unwrap();
+        let mut data = PutObjReader::from_vec(body.clone());
+        let info = store
+            .put_object(bucket, "plain.txt", &mut data, &ObjectOptions::default())
+            .await
+            .unwrap();
+        assert!(!info.is_compressed());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_put_object_default_encryption() {
+        use crate::bucket::metadata::BUCKET_SSECONFIG;
+        use crate::bucket::utils::serialize;
+        use crate::kms::{LocalKms, set_kms_provider};
+        use s3s::dto::{
+            ServerSideEncryption, ServerSideEncryptionByDefault, ServerSideEncryptionConfiguration, ServerSideEncryptionRule,
+        };
+
+        set_kms_provider(Arc::new(LocalKms::new("test-key", [42u8; 32])), "test-key");
+
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "sse-default";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+
+        let config = ServerSideEncryptionConfiguration {
+            rules: vec![ServerSideEncryptionRule {
+                apply_server_side_encryption_by_default: Some(ServerSideEncryptionByDefault {
+                    sse_algorithm: ServerSideEncryption::from_static(ServerSideEncryption::AES256),
+                    kms_master_key_id: None,
+                }),
+                bucket_key_enabled: None,
+            }],
+        };
+        metadata_sys::update(bucket, BUCKET_SSECONFIG, serialize(&config).unwrap())
+            .await
+            .unwrap();
+
+        let sse = "x-amz-server-side-encryption";
+        let ssec = "x-amz-server-side-encryption-customer-algorithm";
+
+        let body = b"plain".repeat(1000);
+        let mut data = PutObjReader::from_vec(body.clone());
+        let info = store
+            .put_object(bucket, "plain", &mut data, &ObjectOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(info.user_defined.get(sse).map(String::as_str), Some("AES256"));
+        // the default SSE-S3 is applied for real, under the default KMS key
+        let sealed_key = format!("{RESERVED_METADATA_PREFIX_LOWER}kms-sealed-key");
+        assert!(info.user_defined.contains_key(&sealed_key));
+        assert!(!info.user_defined.contains_key("x-amz-server-side-encryption-aws-kms-key-id"));
+        assert_ne!(info.size, body.len() as i64);
+
+        let mut reader = store
+            .get_object_reader(bucket, "plain", None, HeaderMap::new(), &ObjectOptions::default())
+            .await
+            .unwrap();
+        assert!(reader.read_all().await.unwrap() == body);
+
+        let mut opts = ObjectOptions::default();
+        opts.user_defined.insert(ssec.to_string(), "AES256".to_string());
+        let mut data = PutObjReader::from_vec(b"customer".to_vec());
+        let info = store.put_object(bucket, "customer", &mut data, &opts).await.unwrap();
+        assert!(!info.user_defined.contains_key(sse));
+        assert_eq!(info.user_defined.get(ssec).map(String::as_str), Some("AES256"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_put_object_kms_round_trip() {
+        use crate::kms::{LocalKms, set_kms_provider};
+
+        set_kms_provider(Arc::new(LocalKms::new("test-key", [42u8; 32])), "test-key");
+
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "sse-kms";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+
+        let body: Vec<u8> = (0..50_000u32).flat_map(|i| i.to_le_bytes()).collect();
+        let mut opts = ObjectOptions::default();
+        opts.user_defined
+            .insert("x-amz-server-side-encryption".to_string(), "aws:kms".to_string());
+        let mut data = PutObjReader::from_vec(body.clone());
+        let info = store.put_object(bucket, "secret", &mut data, &opts).await.unwrap();
+
+        // the sealed data key and its context are stored, the plain key is not
+        let meta = |name: &str| info.user_defined.get(&format!("{RESERVED_METADATA_PREFIX_LOWER}{name}"));
+        assert_eq!(meta("kms-key-id").map(String::as_str), Some("test-key"));
+        assert!(meta("kms-sealed-key").is_some());
+        assert_eq!(meta("kms-context").map(String::as_str), Some(r#"{"sse-kms":"sse-kms/secret"}"#));
+        assert_eq!(
+            info.user_defined
+                .get("x-amz-server-side-encryption-aws-kms-key-id")
+                .map(String::as_str),
+            Some("test-key")
+        );
+        assert_ne!(info.size, body.len() as i64);
+        assert!(!info.is_compressed());
+
+        let mut reader = store
+            .get_object_reader(bucket, "secret", None, HeaderMap::new(), &ObjectOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(reader.object_info.size, body.len() as i64);
+        assert!(reader.read_all().await.unwrap() == body);
+
+        let range = HTTPRangeSpec {
+            is_suffix_length: false,
+            start: 100_000,
+            end: 100_099,
+        };
+        let mut reader = store
+            .get_object_reader(bucket, "secret", Some(range), HeaderMap::new(), &ObjectOptions::default())
+            .await
+            .unwrap();
+        assert!(reader.read_all().await.unwrap() == body[100_000..100_100]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_audit_sink_put_object() {
+        use crate::audit::{AuditEvent, AuditSink, with_audit_sink};
+        use tokio::io::AsyncReadExt;
+
+        #[derive(Default)]
+        struct CapturingSink(std::sync::Mutex<Vec<AuditEvent>>);
+
+        #[async_trait::async_trait]
+        impl AuditSink for CapturingSink {
+            async fn emit(&self, event: AuditEvent) {
+                self.0.lock().unwrap().push(event);
+            }
+        }
+
+        let store = test_util::test_store();
+        store.make_bucket("audit", &MakeBucketOptions::default()).await.unwrap();
+
+        let sink = Arc::new(CapturingSink::default());
+        let take = || std::mem::take(&mut *sink.0.lock().unwrap());
+        with_audit_sink(sink.clone(), async {
+            let mut data = PutObjReader::from_vec(b"audited".to_vec());
+            store
+                .put_object("audit", "obj", &mut data, &ObjectOptions::default())
+                .await
+                .unwrap();
+
+            let events = take();
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].operation, AuditOperation::PutObject);
+            assert_eq!(events[0].operation.as_str(), "s3:PutObject");
+            assert_eq!(events[0].bucket, "audit");
+            assert_eq!(events[0].object, "obj");
+            assert_eq!(events[0].result, Ok(()));
+            assert_eq!(events[0].bytes, 7);
+
+            // failures are recorded too
+            assert!(
+                store
+                    .delete_object("audit", "missing", ObjectOptions::default())
+                    .await
+                    .is_err()
+            );
+            let events = take();
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].operation, AuditOperation::DeleteObject);
+            assert!(events[0].result.is_err());
+            assert_eq!(events[0].bytes, 0);
+
+            // a GetObject reports the bytes read before the stream was dropped
+            let mut reader = store
+                .get_object_reader("audit", "obj", None, HeaderMap::new(), &ObjectOptions::default())
+                .await
+                .unwrap();
+            let mut buf = [0u8; 3];
+            reader.stream.read_exact(&mut buf).await.unwrap();
+            assert!(take().is_empty());
+            drop(reader);
+        })
+        .await;
+
+        let mut events = take();
+        for _ in 0..100 {
+            if !events.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            events = take();
+        }
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].operation, AuditOperation::GetObject);
+        assert_eq!(events[0].result, Ok(()));
+        assert_eq!(events[0].bytes, 3);
+
+        // nothing is recorded outside the scope
+        let mut data = PutObjReader::from_vec(b"unaudited".to_vec());
+        store
+            .put_object("audit", "obj", &mut data, &ObjectOptions::default())
+            .await
+            .unwrap();
+        assert!(take().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_delete_prefix_skips_locked() {
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "delete-prefix";
+        store
+            .make_bucket(
+                bucket,
+                &MakeBucketOptions {
+                    lock_enabled: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let versioned = ObjectOptions {
+            versioned: true,
+            ..Default::default()
+        };
+        let put = |object: &'static str, user_defined: HashMap<String, String>| {
+            let store = store.clone();
+            let opts = ObjectOptions {
+                user_defined,
+                ..versioned.clone()
+            };
+            async move {
+                let mut data = PutObjReader::from_vec(object.as_bytes().to_vec());
+                store.put_object(bucket, object, &mut data, &opts).await.unwrap()
+            }
+        };
+
+        put("logs/a", HashMap::new()).await;
+        put("logs/a", HashMap::new()).await;
+        put("logs/sub/b", HashMap::new()).await;
+        let held = put(
+            "logs/held",
+            HashMap::from([("x-amz-object-lock-legal-hold".to_string(), "ON".to_string())]),
+        )
+        .await;
+        put("other", HashMap::new()).await;
+        put("tmp/c", HashMap::new()).await;
+        put("tmp/d/e", HashMap::new()).await;
+
+        let opts = ObjectOptions {
+            all_versions: true,
+            ..versioned.clone()
+        };
+        let report = store.clone().delete_prefix(bucket, "logs/", opts.clone()).await.unwrap();
+        assert_eq!(report.deleted, 3);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].object_name, "logs/held");
+        assert_eq!(report.skipped[0].version_id, held.version_id);
+
+        // a pool walks its sets' drives directly
+        let report = store.pools[0].clone().delete_prefix(bucket, "tmp/", opts).await.unwrap();
+        assert_eq!(report.deleted, 2);
+        assert!(report.skipped.is_empty());
+
+        let left = store
+            .clone()
+            .list_object_versions(bucket, "", None, None, None, 1000)
+            .await
+            .unwrap();
+        let mut names: Vec<&str> = left.objects.iter().map(|oi| oi.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, ["logs/held", "other"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_object_expiration_from_lifecycle() {
+        use crate::bucket::metadata::BUCKET_LIFECYCLE_CONFIG;
+        use crate::bucket::utils::serialize;
+        use s3s::dto::{BucketLifecycleConfiguration, ExpirationStatus, LifecycleExpiration, LifecycleRule};
+
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "lifecycle-expiration";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+
+        let config = BucketLifecycleConfiguration {
+            rules: vec![LifecycleRule {
+                abort_incomplete_multipart_upload: None,
+                expiration: Some(LifecycleExpiration {
+                    days: Some(30),
+                    ..Default::default()
+                }),
+                filter: None,
+                id: Some("expire-logs".to_string()),
+                noncurrent_version_expiration: None,
+                noncurrent_version_transitions: None,
+                prefix: Some("logs/".to_string()),
+                status: ExpirationStatus::from_static(ExpirationStatus::ENABLED),
+                transitions: None,
+            }],
+        };
+        metadata_sys::update(bucket, BUCKET_LIFECYCLE_CONFIG, serialize(&config).unwrap())
+            .await
+            .unwrap();
+
+        let mut data = PutObjReader::from_vec(b"log line".to_vec());
+        let info = store
+            .put_object(bucket, "logs/app.log", &mut data, &ObjectOptions::default())
+            .await
+            .unwrap();
+        // expired at the first midnight UTC after 30 days have passed
+        let expected = (info.mod_time.unwrap().to_offset(time::UtcOffset::UTC) + time::Duration::days(31))
+            .replace_time(time::Time::MIDNIGHT);
+        assert_eq!(info.expiration, Some((expected, "expire-logs".to_string())));
+
+        let info = store
+            .get_object_info(bucket, "logs/app.log", &ObjectOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(info.expiration, Some((expected, "expire-logs".to_string())));
+
+        let mut data = PutObjReader::from_vec(b"kept".to_vec());
+        let info = store
+            .put_object(bucket, "data/kept", &mut data, &ObjectOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(info.expiration, None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_max_versions_per_object() {
+        use crate::global::set_global_max_versions_per_object;
+
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "max-versions";
+        store
+            .make_bucket(
+                bucket,
+                &MakeBucketOptions {
+                    lock_enabled: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let put = |object: &'static str, user_defined: HashMap<String, String>| {
+            let store = store.clone();
+            let opts = ObjectOptions {
+                versioned: true,
+                user_defined,
+                ..Default::default()
+            };
+            async move {
+                let mut data = PutObjReader::from_vec(object.as_bytes().to_vec());
+                store.put_object(bucket, object, &mut data, &opts).await
+            }
+        };
+        let version_ids = |object: &'static str| {
+            let store = store.clone();
+            async move {
+                let page = store
+                    .list_object_versions(bucket, object, None, None, None, 1000)
+                    .await
+                    .unwrap();
+                page.objects.iter().map(|oi| oi.version_id).collect::<Vec<_>>()
+            }
+        };
+
+        set_global_max_versions_per_object(Some(3));
+
+        let mut puts = Vec::new();
+        for _ in 0..4 {
+            puts.push(put("pruned", HashMap::new()).await);
+        }
+        let hold = HashMap::from([("x-amz-object-lock-legal-hold".to_string(), "ON".to_string())]);
+        let held = put("held", hold).await;
+        put("held", HashMap::new()).await.unwrap();
+        put("held", HashMap::new()).await.unwrap();
+        let rejected = put("held", HashMap::new()).await;
+
+        // the first version went to make room for the fourth
+        let puts: Vec<_> = puts.into_iter().map(|res| res.unwrap().version_id).collect();
+        assert_eq!(version_ids("pruned").await, [puts[3], puts[2], puts[1]]);
+
+        // copies and completed multipart uploads prune the same way
+        let versioned = ObjectOptions {
+            versioned: true,
+            ..Default::default()
+        };
+        let mut src_info = store
+            .get_object_info(bucket, "pruned", &ObjectOptions::default())
+            .await
+            .unwrap();
+        src_info.put_object_reader = Some(PutObjReader::from_vec(b"pruned".to_vec()));
+        let copied = store
+            .copy_object(bucket, "pruned", bucket, "pruned", &mut src_info, &ObjectOptions::default(), &versioned)
+            .await
+            .unwrap();
+        let upload = store.new_multipart_upload(bucket, "pruned", &versioned).await.unwrap();
+        let mut data = PutObjReader::from_vec(b"part".to_vec());
+        let part = store
+            .put_object_part(bucket, "pruned", &upload.upload_id, 1, &mut data, &versioned)
+            .await
+            .unwrap();
+        let completed = store
+            .clone()
+            .complete_multipart_upload(
+                bucket,
+                "pruned",
+                &upload.upload_id,
+                vec![CompletePart {
+                    part_num: 1,
+                    etag: part.etag,
+                }],
+                &versioned,
+            )
+            .await
+            .unwrap();
+
+        set_global_max_versions_per_object(None);
+
+        assert_eq!(version_ids("pruned").await, [completed.version_id, copied.version_id, puts[3]]);
+
+        assert_eq!(
+            rejected.unwrap_err(),
+            StorageError::TooManyVersions(bucket.to_string(), "held".to_string())
+        );
+        let held_versions = version_ids("held").await;
+        assert_eq!(held_versions.len(), 3);
+        assert_eq!(held_versions.last(), Some(&held.unwrap().version_id));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_copy_object_preserve_version_id() {
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let (src_bucket, dst_bucket) = ("preserve-src", "preserve-dst");
+        let versioned = MakeBucketOptions {
+            versioning_enabled: true,
+            ..Default::default()
+        };
+        store.make_bucket(src_bucket, &versioned).await.unwrap();
+        store.make_bucket(dst_bucket, &versioned).await.unwrap();
+
+        let put_opts = ObjectOptions {
+            versioned: true,
+            ..Default::default()
+        };
+        let mut data = PutObjReader::from_vec(b"first".to_vec());
+        let first = store.put_object(src_bucket, "obj", &mut data, &put_opts).await.unwrap();
+        let mut data = PutObjReader::from_vec(b"second".to_vec());
+        store.put_object(src_bucket, "obj", &mut data, &put_opts).await.unwrap();
+
+        let src_opts = ObjectOptions {
+            version_id: first.version_id.map(|v| v.to_string()),
+            versioned: true,
+            ..Default::default()
+        };
+        let dst_opts = ObjectOptions {
+            versioned: true,
+            preserve_version_id: true,
+            ..Default::default()
+        };
+        let copy = || {
+            let store = store.clone();
+            let (src_opts, dst_opts) = (src_opts.clone(), dst_opts.clone());
+            async move {
+                let mut src_info = store.get_object_info(src_bucket, "obj", &src_opts).await.unwrap();
+                src_info.put_object_reader = Some(PutObjReader::from_vec(b"first".to_vec()));
+                store
+                    .copy_object(src_bucket, "obj", dst_bucket, "obj", &mut src_info, &src_opts, &dst_opts)
+                    .await
+            }
+        };
+
+        let copied = copy().await.unwrap();
+        assert_eq!(copied.version_id, first.version_id);
+
+        let dst_info = store
+            .get_object_info(
+                dst_bucket,
+                "obj",
+                &ObjectOptions {
+                    version_id: first.version_id.map(|v| v.to_string()),
+                    versioned: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(dst_info.version_id, first.version_id);
+        assert_eq!(dst_info.size, 5);
+
+        // the destination already holds that version now
+        assert_eq!(
+            copy().await.unwrap_err(),
+            StorageError::VersionIdConflict(dst_bucket.to_string(), "obj".to_string(), first.version_id.unwrap().to_string())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_list_objects_v2_fetch_owner() {
+        use crate::store_api::{BUCKET_OWNER_DISPLAY_NAME, BUCKET_OWNER_ID, ObjectOwner};
+
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "fetch-owner";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+
+        let owned = ObjectOptions {
+            user_defined: HashMap::from([
+                (format!("{RESERVED_METADATA_PREFIX_LOWER}owner-id"), "alice-id".to_string()),
+                (format!("{RESERVED_METADATA_PREFIX_LOWER}owner-name"), "alice".to_string()),
+            ]),
+            ..Default::default()
+        };
+        let mut data = PutObjReader::from_vec(b"a".to_vec());
+        store.put_object(bucket, "owned", &mut data, &owned).await.unwrap();
+        let mut data = PutObjReader::from_vec(b"b".to_vec());
+        store
+            .put_object(bucket, "unowned", &mut data, &ObjectOptions::default())
+            .await
+            .unwrap();
+
+        let list = |fetch_owner: bool| store.clone().list_objects_v2(bucket, "", None, None, 1000, fetch_owner, None);
+
+        let with_owner = list(true).await.unwrap();
+        let owners: Vec<_> = with_owner
+            .objects
+            .iter()
+            .map(|oi| (oi.name.as_str(), oi.owner.clone()))
+            .collect();
+        assert_eq!(
+            owners,
+            [
+                (
+                    "owned",
+                    Some(ObjectOwner {
+                        id: "alice-id".to_string(),
+                        display_name: "alice".to_string(),
+                    })
+                ),
+                (
+                    "unowned",
+                    Some(ObjectOwner {
+                        id: BUCKET_OWNER_ID.to_string(),
+                        display_name: BUCKET_OWNER_DISPLAY_NAME.to_string(),
+                    })
+                ),
+            ]
+        );
+
+        let without_owner = list(false).await.unwrap();
+        assert_eq!(without_owner.objects.len(), 2);
+        assert!(without_owner.objects.iter().all(|oi| oi.owner.is_none()));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_put_object_default_retention() {
+        use crate::bucket::metadata::OBJECT_LOCK_CONFIG;
+        use crate::bucket::utils::serialize;
+        use s3s::dto::{DefaultRetention, ObjectLockRetentionMode, ObjectLockRule};
+        use time::format_description::well_known::Rfc3339;
+
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "worm-default-retention";
+        store
+            .make_bucket(
+                bucket,
+                &MakeBucketOptions {
+                    lock_enabled: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let config = ObjectLockConfiguration {
+            object_lock_enabled: Some(ObjectLockEnabled::from_static(ObjectLockEnabled::ENABLED)),
+            rule: Some(ObjectLockRule {
+                default_retention: Some(DefaultRetention {
+                    days: Some(7),
+                    mode: Some(ObjectLockRetentionMode::from_static(ObjectLockRetentionMode::COMPLIANCE)),
+                    years: None,
+                }),
+            }),
+        };
+        metadata_sys::update(bucket, OBJECT_LOCK_CONFIG, serialize(&config).unwrap())
+            .await
+            .unwrap();
+
+        let put = |object: &'static str, user_defined: HashMap<String, String>| {
+            let store = store.clone();
+            async move {
+                let opts = ObjectOptions {
+                    versioned: true,
+                    user_defined,
+                    ..Default::default()
+                };
+                let mut data = PutObjReader::from_vec(b"worm".to_vec());
+                store.put_object(bucket, object, &mut data, &opts).await.unwrap()
+            }
+        };
+        let retention = |info: &ObjectInfo| {
+            let ret = objectlock::get_object_retention_meta(info.user_defined.clone());
+            (ret.mode.unwrap(), OffsetDateTime::from(ret.retain_until_date.unwrap()))
+        };
+
+        let before = OffsetDateTime::now_utc();
+        let info = put("inherits", HashMap::new()).await;
+        let after = OffsetDateTime::now_utc();
+        let (mode, until) = retention(&info);
+        assert_eq!(mode.as_str(), ObjectLockRetentionMode::COMPLIANCE);
+        let week = time::Duration::days(7);
+        assert!(until >= before.replace_nanosecond(0).unwrap() + week && until <= after + week, "{until}");
+
+        // retention sent with the put wins over the bucket default
+        let own_until = (OffsetDateTime::now_utc() + Duration::from_secs(3600))
+            .replace_nanosecond(0)
+            .unwrap();
+        let info = put(
+            "explicit",
+            HashMap::from([
+                ("x-amz-object-lock-mode".to_string(), "GOVERNANCE".to_string()),
+                ("x-amz-object-lock-retain-until-date".to_string(), own_until.format(&Rfc3339).unwrap()),
+            ]),
+        )
+        .await;
+        let (mode, until) = retention(&info);
+        assert_eq!(mode.as_str(), ObjectLockRetentionMode::GOVERNANCE);
+        assert_eq!(until, own_until);
+
+        // so do a completed multipart upload and a copy from a bucket without object lock
+        let versioned = ObjectOptions {
+            versioned: true,
+            ..Default::default()
+        };
+        let before = OffsetDateTime::now_utc();
+        let upload = store.new_multipart_upload(bucket, "multipart", &versioned).await.unwrap();
+        let mut data = PutObjReader::from_vec(b"part".to_vec());
+        let part = store
+            .put_object_part(bucket, "multipart", &upload.upload_id, 1, &mut data, &versioned)
+            .await
+            .unwrap();
+        let completed = store
+            .clone()
+            .complete_multipart_upload(
+                bucket,
+                "multipart",
+                &upload.upload_id,
+                vec![CompletePart {
+                    part_num: 1,
+                    etag: part.etag,
+                }],
+                &versioned,
+            )
+            .await
+            .unwrap();
+
+        let src_bucket = "worm-default-retention-src";
+        store.make_bucket(src_bucket, &MakeBucketOptions::default()).await.unwrap();
+        let mut data = PutObjReader::from_vec(b"worm".to_vec());
+        store
+            .put_object(src_bucket, "src", &mut data, &ObjectOptions::default())
+            .await
+            .unwrap();
+        let mut src_info = store
+            .get_object_info(src_bucket, "src", &ObjectOptions::default())
+            .await
+            .unwrap();
+        src_info.put_object_reader = Some(PutObjReader::from_vec(b"worm".to_vec()));
+        let copied = store
+            .copy_object(src_bucket, "src", bucket, "copied", &mut src_info, &ObjectOptions::default(), &versioned)
+            .await
+            .unwrap();
+        let after = OffsetDateTime::now_utc();
+
+        for info in [completed, copied] {
+            let (mode, until) = retention(&info);
+            assert_eq!(mode.as_str(), ObjectLockRetentionMode::COMPLIANCE, "{}", info.name);
+            assert!(until >= before.replace_nanosecond(0).unwrap() + week && until <= after + week, "{until}");
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_heal_objects_progress() {
+        use crate::heal::background_heal_ops::HealRoutine;
+        use crate::heal::heal_commands::{HEAL_ITEM_OBJECT, HealOpts};
+        use crate::heal::heal_ops::new_bg_heal_sequence;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "heal-progress";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+        for i in 0..3 {
+            let mut data = PutObjReader::from_vec(format!("object {i}").into_bytes());
+            store
+                .put_object(bucket, &format!("obj-{i}"), &mut data, &ObjectOptions::default())
+                .await
+                .unwrap();
+        }
+
+        // a routine of its own, a worker on the global one would outlive the test
+        let routine = HealRoutine::new();
+        let worker = routine.clone();
+
+        let reported = Arc::new(AtomicUsize::new(0));
+        let counter = reported.clone();
+        let hs = Arc::new(
+            HealSequence::new(bucket, "", "", HealOpts::default(), false)
+                .with_routine(routine)
+                .with_progress(Arc::new(move |item| {
+                    if item.heal_item_type == HEAL_ITEM_OBJECT {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                    }
+                })),
+        );
+        // the worker runs on this thread, the only one the test store is the object layer of
+        let opts = HealOpts::default();
+        tokio::select! {
+            res = store.heal_objects(bucket, "", &opts, hs.clone(), false) => res.unwrap(),
+            _ = worker.add_worker(Arc::new(new_bg_heal_sequence())) => panic!("heal worker stopped"),
+        }
+
+        let healed = hs
+            .healed_items_map
+            .read()
+            .await
+            .get(HEAL_ITEM_OBJECT)
+            .copied()
+            .unwrap_or_default();
+        assert_eq!(healed, 3);
+
+        // the callback runs on its own task and catches up shortly after
+        for _ in 0..50 {
+            if reported.load(Ordering::SeqCst) == healed {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(reported.load(Ordering::SeqCst), healed);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_put_object_part_concurrent() {
+        use md5::{Digest, Md5};
+        use tokio::io::AsyncReadExt;
+
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "multipart-part-race";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+        let opts = ObjectOptions::default();
+
+        let upload = store.new_multipart_upload(bucket, "obj", &opts).await.unwrap();
+        let first = vec![1u8; 1 << 20];
+        let second = vec![2u8; (1 << 20) + 7];
+        let put = |body: Vec<u8>| {
+            let (store, opts, upload_id) = (store.clone(), opts.clone(), upload.upload_id.clone());
+            async move {
+                let mut data = PutObjReader::from_vec(body);
+                store
+                    .put_object_part(bucket, "obj", &upload_id, 1, &mut data, &opts)
+                    .await
+                    .unwrap()
+            }
+        };
+        let (a, b) = tokio::join!(put(first.clone()), put(second.clone()));
+
+        // Only the committed upload of the part completes, whichever of the two won.
+        let mut completed = None;
+        for etag in [a.etag, b.etag] {
+            let parts = vec![CompletePart { part_num: 1, etag }];
+            if let Ok(obj) = store
+                .clone()
+                .complete_multipart_upload(bucket, "obj", &upload.upload_id, parts, &opts)
+                .await
+            {
+                completed = Some(obj);
+                break;
+            }
+        }
+        let obj = completed.expect("neither reported part completed the upload");
+
+        let mut reader = store
+            .get_object_reader(bucket, "obj", None, HeaderMap::new(), &opts)
+            .await
+            .unwrap();
+        let mut body = Vec::new();
+        reader.stream.read_to_end(&mut body).await.unwrap();
+        assert!(body == first || body == second, "completed object mixes both uploads");
+
+        let expected = format!("{:x}-1", Md5::digest(Md5::digest(&body)));
+        assert_eq!(obj.etag.as_deref(), Some(expected.as_str()));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_get_bucket_info_usage_estimate() {
+        use crate::config::com::save_config;
+        use crate::heal::data_usage::{BucketUsageInfo, DATA_USAGE_OBJ_NAME_PATH};
+
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "bucket-info-usage";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+
+        // what the scanner on whichever node ran last persisted
+        let scanned = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let usage = DataUsageInfo {
+            last_update: Some(scanned),
+            buckets_usage: HashMap::from([(
+                bucket.to_string(),
+                BucketUsageInfo {
+                    objects_count: 3,
+                    size: 4096,
+                    ..Default::default()
+                },
+            )]),
+            ..Default::default()
+        };
+        save_config(store.clone(), &DATA_USAGE_OBJ_NAME_PATH, serde_json::to_vec(&usage).unwrap())
+            .await
+            .unwrap();
+
+        let info = store.get_bucket_info(bucket, &BucketOptions::default()).await.unwrap();
+        assert_eq!(info.objects_count, Some(3));
+        assert_eq!(info.size, Some(4096));
+        assert_eq!(info.usage_updated, Some(OffsetDateTime::from(scanned)));
+
+        // buckets the scan has not reached yet report no estimate
+        store
+            .make_bucket("bucket-info-unscanned", &MakeBucketOptions::default())
+            .await
+            .unwrap();
+        let info = store
+            .get_bucket_info("bucket-info-unscanned", &BucketOptions::default())
+            .await
+            .unwrap();
+        assert_eq!((info.objects_count, info.size), (None, None));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_get_object_during_overwrite() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use tokio::io::AsyncReadExt;
+
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "overwrite-read";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+        let opts = ObjectOptions::default();
+
+        // Large enough to be stored in part files rather than inline in xl.meta.
+        let first = vec![1u8; 1024 * 1024];
+        let second = vec![2u8; 1024 * 1024 + 4096];
+        let mut data = PutObjReader::from_vec(first.clone());
+        store.put_object(bucket, "obj", &mut data, &opts).await.unwrap();
+
+        let done = Arc::new(AtomicBool::new(false));
+        let reader = {
+            let (store, done, opts) = (store.clone(), done.clone(), opts.clone());
+            let (first, second) = (first.clone(), second.clone());
+            tokio::spawn(async move {
+                let mut reads = 0;
+                while !done.load(Ordering::SeqCst) || reads == 0 {
+                    let mut reader = store
+                        .get_object_reader(bucket, "obj", None, HeaderMap::new(), &opts)
+                        .await
+                        .unwrap();
+                    let mut body = Vec::new();
+                    reader.stream.read_to_end(&mut body).await.unwrap();
+                    assert!(body == first || body == second, "read a partial object of {} bytes", body.len());
+                    reads += 1;
+                }
+            })
+        };
+
+        for i in 0..8 {
+            let body = if i % 2 == 0 { second.clone() } else { first.clone() };
+            let mut data = PutObjReader::from_vec(body);
+            store.put_object(bucket, "obj", &mut data, &opts).await.unwrap();
+        }
+        done.store(true, Ordering::SeqCst);
+        reader.await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_overwrite_waits_for_open_reader() {
+        use tokio::io::AsyncReadExt;
+
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "overwrite-open-reader";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+        let opts = ObjectOptions::default();
+
+        // more than the read pipe buffers, so the reader keeps streaming parts until drained
+        let first = vec![1u8; 4 * 1024 * 1024];
+        let second = vec![2u8; 1024 * 1024];
+        let mut data = PutObjReader::from_vec(first.clone());
+        store.put_object(bucket, "obj", &mut data, &opts).await.unwrap();
+
+        let mut reader = store
+            .get_object_reader(bucket, "obj", None, HeaderMap::new(), &opts)
+            .await
+            .unwrap();
+        let overwrite = {
+            let (store, opts, second) = (store.clone(), opts.clone(), second.clone());
+            tokio::spawn(async move {
+                let mut data = PutObjReader::from_vec(second);
+                store.put_object(bucket, "obj", &mut data, &opts).await.unwrap();
+            })
+        };
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert!(!overwrite.is_finished());
+
+        let mut body = Vec::new();
+        reader.stream.read_to_end(&mut body).await.unwrap();
+        assert!(body == first, "read a partial object of {} bytes", body.len());
+
+        overwrite.await.unwrap();
+        let mut reader = store
+            .get_object_reader(bucket, "obj", None, HeaderMap::new(), &opts)
+            .await
+            .unwrap();
+        let mut body = Vec::new();
+        reader.stream.read_to_end(&mut body).await.unwrap();
+        assert!(body == second);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_touch_object() {
+        use time::format_description::well_known::Rfc3339;
+
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "touch-object";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+
+        let mut data = PutObjReader::from_vec(b"keep me".to_vec());
+        let put = store
+            .put_object(bucket, "obj", &mut data, &ObjectOptions::default())
+            .await
+            .unwrap();
+        let disk = store.pools[0].disk_set[0].disks.read().await[0].clone().unwrap();
+        let before = disk
+            .read_version("", bucket, "obj", "", &crate::disk::ReadOptions::default())
+            .await
+            .unwrap();
+
+        let new_mtime = OffsetDateTime::from_unix_timestamp(1_600_000_000).unwrap();
+        let touched = store.touch_object(bucket, "obj", None, new_mtime).await.unwrap();
+        assert_eq!(touched.mod_time, Some(new_mtime));
+        assert_eq!(touched.etag, put.etag);
+
+        let info = store.get_object_info(bucket, "obj", &ObjectOptions::default()).await.unwrap();
+        assert_eq!(info.mod_time, Some(new_mtime));
+        assert_eq!(info.etag, put.etag);
+        let after = disk
+            .read_version("", bucket, "obj", "", &crate::disk::ReadOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(after.data_dir, before.data_dir);
+
+        let mut reader = store
+            .get_object_reader(bucket, "obj", None, HeaderMap::new(), &ObjectOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(reader.read_all().await.unwrap(), b"keep me");
+
+        // a version under retention is not touched
+        let locked = "touch-object-locked";
+        store
+            .make_bucket(
+                locked,
+                &MakeBucketOptions {
+                    lock_enabled: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let until = (OffsetDateTime::now_utc() + Duration::from_secs(3600))
+            .format(&Rfc3339)
+            .unwrap();
+        let opts = ObjectOptions {
+            versioned: true,
+            user_defined: HashMap::from([
+                ("x-amz-object-lock-mode".to_string(), "GOVERNANCE".to_string()),
+                ("x-amz-object-lock-retain-until-date".to_string(), until),
+            ]),
+            ..Default::default()
+        };
+        let mut data = PutObjReader::from_vec(b"worm".to_vec());
+        let info = store.put_object(locked, "obj", &mut data, &opts).await.unwrap();
+        let version = info.version_id.unwrap().to_string();
+        let err = store
+            .touch_object(locked, "obj", Some(&version), new_mtime)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, StorageError::ObjectLocked(..)), "{err:?}");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_touch_object_keeps_latest() {
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "touch-object-versions";
+        store
+            .make_bucket(
+                bucket,
+                &MakeBucketOptions {
+                    versioning_enabled: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let opts = ObjectOptions {
+            versioned: true,
+            ..Default::default()
+        };
+        let put = |body: &'static [u8]| {
+            let store = store.clone();
+            let opts = opts.clone();
+            async move {
+                let mut data = PutObjReader::from_vec(body.to_vec());
+                store.put_object(bucket, "obj", &mut data, &opts).await.unwrap()
+            }
+        };
+        let old = put(b"old").await;
+        let latest = put(b"latest").await;
+        let old_version = old.version_id.unwrap().to_string();
+        let latest_version = latest.version_id.unwrap().to_string();
+
+        // the noncurrent version can not overtake the latest, nor the latest fall behind it
+        let later = latest.mod_time.unwrap() + Duration::from_secs(3600);
+        let err = store
+            .touch_object(bucket, "obj", Some(&old_version), later)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, StorageError::InvalidArgument(..)), "{err:?}");
+        let earlier = old.mod_time.unwrap() - Duration::from_secs(3600);
+        let err = store
+            .touch_object(bucket, "obj", Some(&latest_version), earlier)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, StorageError::InvalidArgument(..)), "{err:?}");
+
+        // moves that keep the order are fine
+        store.touch_object(bucket, "obj", Some(&old_version), earlier).await.unwrap();
+        store.touch_object(bucket, "obj", None, later).await.unwrap();
+
+        let info = store.get_object_info(bucket, "obj", &ObjectOptions::default()).await.unwrap();
+        assert_eq!(info.version_id, latest.version_id);
+        assert_eq!(info.mod_time, Some(later));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_target_rtt_persisted() {
+        use crate::cmd::bucket_targets::{TargetRtt, get_target_rtts, save_target_rtts, target_rtts};
+
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "target-rtt";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+        assert!(get_target_rtts(bucket).await.is_empty());
+
+        let last_checked = OffsetDateTime::now_utc();
+        let first = TargetRtt {
+            rtt_ms: 42,
+            last_checked,
+        };
+        save_target_rtts(bucket, &HashMap::from([("arn:a".to_string(), first)]))
+            .await
+            .unwrap();
+
+        // a later round only probing another target keeps the earlier entry
+        let second = TargetRtt { rtt_ms: 7, last_checked };
+        save_target_rtts(bucket, &HashMap::from([("arn:b".to_string(), second)]))
+            .await
+            .unwrap();
+
+        let rtts = get_target_rtts(bucket).await;
+        assert_eq!(rtts.len(), 2);
+        assert_eq!(rtts["arn:a"], first);
+        assert_eq!(rtts["arn:b"], second);
+
+        let on_disk = metadata_sys::get_config_from_disk(bucket).await.unwrap();
+        assert_eq!(target_rtts(&on_disk), rtts);
+
+        // jitter doesn't rewrite the bucket metadata
+        let jitter = TargetRtt {
+            rtt_ms: 44,
+            last_checked: OffsetDateTime::now_utc(),
+        };
+        save_target_rtts(bucket, &HashMap::from([("arn:a".to_string(), jitter)]))
+            .await
+            .unwrap();
+        assert_eq!(get_target_rtts(bucket).await["arn:a"], first);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_negative_cache_get_object_info() {
+        use crate::cache_value::negative_cache::set_negative_cache;
+
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "negative-cache";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+
+        set_negative_cache(16, Duration::from_secs(60));
+        let err = store
+            .get_object_info(bucket, "obj", &ObjectOptions::default())
+            .await
+            .unwrap_err();
+        assert!(is_err_object_not_found(&err), "{err:?}");
+
+        // With no disks in the set only the cache can answer.
+        let set = store.pools[0].disk_set[0].clone();
+        let saved = set.disks.read().await.clone();
+        *set.disks.write().await = vec![None; saved.len()];
+        let cached = store.get_object_info(bucket, "obj", &ObjectOptions::default()).await;
+        *set.disks.write().await = saved;
+
+        let mut data = PutObjReader::from_vec(b"data".to_vec());
+        store
+            .put_object(bucket, "obj", &mut data, &ObjectOptions::default())
+            .await
+            .unwrap();
+        let found = store.get_object_info(bucket, "obj", &ObjectOptions::default()).await;
+        set_negative_cache(0, Duration::ZERO);
+
+        assert_eq!(cached.unwrap_err(), err);
+        assert_eq!(found.unwrap().size, 4);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_delete_object_if_match() {
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "delete-if-match";
+        store
+            .make_bucket(
+                bucket,
+                &MakeBucketOptions {
+                    versioning_enabled: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let opts = ObjectOptions {
+            versioned: true,
+            ..Default::default()
+        };
+
+        let mut data = PutObjReader::from_vec(b"old".to_vec());
+        let stale = store.put_object(bucket, "obj", &mut data, &opts).await.unwrap();
+        let mut data = PutObjReader::from_vec(b"new".to_vec());
+        let current = store.put_object(bucket, "obj", &mut data, &opts).await.unwrap();
+
+        let err = store
+            .delete_object(
+                bucket,
+                "obj",
+                ObjectOptions {
+                    if_match: stale.etag.clone(),
+                    ..opts.clone()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err, StorageError::PreconditionFailed);
+        let versions = store
+            .clone()
+            .list_object_versions(bucket, "", None, None, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(versions.objects.len(), 2);
+        assert!(versions.objects.iter().all(|o| !o.delete_marker));
+
+        let deleted = store
+            .delete_object(
+                bucket,
+                "obj",
+                ObjectOptions {
+                    if_match: Some(format!("\"{}\"", current.etag.unwrap())),
+                    ..opts.clone()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(deleted.delete_marker);
+        let versions = store
+            .clone()
+            .list_object_versions(bucket, "", None, None, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(versions.objects.iter().filter(|o| o.delete_marker).count(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_put_object_checksums() {
+        use crate::checksum::ChecksumMode;
+
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "checksums";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+
+        let cases = [
+            (ChecksumMode::ChecksumCRC32, "DUoRhQ=="),
+            (ChecksumMode::ChecksumCRC32C, "yZRlqg=="),
+            (ChecksumMode::ChecksumSHA1, "Kq5sNclPz7QV2+lfQIuc6R7oRu0="),
+            (ChecksumMode::ChecksumSHA256, "uU0nuZNNPgilLlLX2n2r+sSE7+N6U4DukIj3rOLvzek="),
+        ];
+        for (mode, value) in cases {
+            let object = mode.to_string();
+            let mut data = PutObjReader::from_vec(b"hello world".to_vec());
+            let opts = ObjectOptions {
+                checksum_algorithm: Some(mode),
+                checksum_value: Some(value.to_string()),
+                ..Default::default()
+            };
+            store.put_object(bucket, &object, &mut data, &opts).await.unwrap();
+
+            let info = store
+                .get_object_info(bucket, &object, &ObjectOptions::default())
+                .await
+                .unwrap();
+            assert_eq!(info.checksum(), Some((mode, value.to_string())), "{object}");
+        }
+
+        // The server computes the checksum when the client only names the algorithm.
+        let mut data = PutObjReader::from_vec(b"hello world".to_vec());
+        let opts = ObjectOptions {
+            checksum_algorithm: Some(ChecksumMode::ChecksumCRC32),
+            ..Default::default()
+        };
+        let info = store.put_object(bucket, "computed", &mut data, &opts).await.unwrap();
+        assert_eq!(info.checksum(), Some((ChecksumMode::ChecksumCRC32, "DUoRhQ==".to_string())));
+
+        let mut data = PutObjReader::from_vec(b"hello world!".to_vec());
+        let opts = ObjectOptions {
+            checksum_algorithm: Some(ChecksumMode::ChecksumSHA256),
+            checksum_value: Some(cases[3].1.to_string()),
+            ..Default::default()
+        };
+        let err = store.put_object(bucket, "mismatch", &mut data, &opts).await.unwrap_err();
+        assert_eq!(err, StorageError::BadDigest);
+        let err = store
+            .get_object_info(bucket, "mismatch", &ObjectOptions::default())
+            .await
+            .unwrap_err();
+        assert!(is_err_object_not_found(&err), "{err:?}");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_scrub_object_versions() {
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+
+        let bucket = "scrub-versions";
+        store
+            .make_bucket(
+                bucket,
+                &MakeBucketOptions {
+                    versioning_enabled: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let opts = ObjectOptions {
+            versioned: true,
+            ..Default::default()
+        };
+
+        // Large enough to be stored in part files rather than inline in xl.meta.
+        let obj_dir = test_util::test_store_root().join("d1").join(bucket).join("obj");
+        let data_dirs = || -> HashSet<std::path::PathBuf> {
+            std::fs::read_dir(&obj_dir)
+                .map(|rd| rd.flatten().map(|e| e.path()).filter(|p| p.is_dir()).collect())
+                .unwrap_or_default()
+        };
+        let mut versions = Vec::new();
+        let mut old_dir = None;
+        for i in 0..2u8 {
+            let before = data_dirs();
+            let mut data = PutObjReader::from_vec(vec![i; 1024 * 1024]);
+            let put = store.put_object(bucket, "obj", &mut data, &opts).await.unwrap();
+            versions.push(put.version_id.unwrap().to_string());
+            if i == 0 {
+                old_dir = data_dirs().difference(&before).next().cloned();
+            }
+        }
+
+        // Flip bytes in a shard of the non-current version.
+        let part = old_dir.unwrap().join("part.1");
+        let mut buf = std::fs::read(&part).unwrap();
+        for b in buf.iter_mut().skip(64).take(1024) {
+            *b = !*b;
+        }
+        std::fs::write(&part, &buf).unwrap();
+
+        let results = store.scrub_object(bucket, "obj", &HealOpts::default()).await.unwrap();
+        let ids: Vec<&str> = results.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, [versions[1].as_str(), versions[0].as_str()]);
+        for (_, result) in results.iter() {
+            assert!(result.detail.is_empty(), "{result:?}");
+        }
+        let (_, healed) = &results[1];
+        assert_eq!(healed.before.drives.iter().filter(|d| d.state != DRIVE_STATE_OK).count(), 1);
+        assert!(healed.after.drives.iter().all(|d| d.state == DRIVE_STATE_OK), "{healed:?}");
+        assert_ne!(std::fs::read(&part).unwrap(), buf);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_put_object_inline_data_threshold() {
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+
+        let bucket = "inline-threshold";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+        let has_data_dir = |object: &str| {
+            std::fs::read_dir(test_util::test_store_root().join("d1").join(bucket).join(object))
+                .unwrap()
+                .flatten()
+                .any(|e| e.path().is_dir())
+        };
+
+        let put = |object: &'static str, data: Vec<u8>, threshold: usize| {
+            let store = store.clone();
+            async move {
+                let opts = ObjectOptions {
+                    inline_data_threshold: Some(threshold),
+                    ..Default::default()
+                };
+                let mut reader = PutObjReader::from_vec(data);
+                store.put_object(bucket, object, &mut reader, &opts).await.unwrap();
+            }
+        };
+
+        put("tiny", b"tiny".to_vec(), 1024).await;
+        assert!(!has_data_dir("tiny"));
+        // larger than the storage class default but under the threshold
+        let big = vec![7u8; 512 * 1024];
+        put("big", big.clone(), 1024 * 1024).await;
+        assert!(!has_data_dir("big"));
+        // a zero threshold never inlines
+        put("not-inline", b"tiny".to_vec(), 0).await;
+        assert!(has_data_dir("not-inline"));
+
+        for (object, expected) in [("tiny", b"tiny".to_vec()), ("big", big), ("not-inline", b"tiny".to_vec())] {
+            let h = HeaderMap::new();
+            let mut reader = store
+                .get_object_reader(bucket, object, None, h, &ObjectOptions::default())
+                .await
+                .unwrap();
+            assert_eq!(reader.read_all().await.unwrap(), expected, "{object}");
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_verify_metadata_consistency() {
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+
+        let bucket = "meta-consistency";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+        let mut data = PutObjReader::from_vec(b"hello world".to_vec());
+        store
+            .put_object(bucket, "obj", &mut data, &ObjectOptions::default())
+            .await
+            .unwrap();
+
+        let report = store.verify_metadata_consistency(bucket, "obj").await.unwrap();
+        assert_eq!(report.disks.len(), 4);
+        assert!(report.is_consistent(), "{report:?}");
+        assert!(report.disks.iter().all(|d| d.err.is_none() && d.etag.is_some()));
+
+        // Rewrite the ETag on one drive only, as a write that reached just some drives would.
+        let disk = store.pools[0].disk_set[0].disks.read().await[2].clone().unwrap();
+        let mut fi = disk
+            .read_version("", bucket, "obj", "", &crate::disk::ReadOptions::default())
+            .await
+            .unwrap();
+        fi.metadata.insert("etag".to_string(), "deadbeef".to_string());
+        disk.update_metadata(bucket, "obj", fi, &crate::disk::UpdateMetadataOpts::default())
+            .await
+            .unwrap();
+
+        let report = store.verify_metadata_consistency(bucket, "obj").await.unwrap();
+        assert_eq!(report.divergent, [2], "{report:?}");
+        assert_eq!(report.disks[2].etag.as_deref(), Some("deadbeef"));
+        assert_eq!(report.disks[2].endpoint, disk.endpoint().to_string());
+
+        let err = store.verify_metadata_consistency(bucket, "missing").await.unwrap_err();
+        assert!(is_err_object_not_found(&err), "{err:?}");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_validate_user_metadata_boundary() {
+        assert_eq!(get_global_max_user_metadata_size(), 2048);
+
+        // 3 bytes of key, 2 bytes per 'é' and one more byte land exactly on the limit.
+        let mut meta = HashMap::from([("key".to_string(), format!("{}a", "é".repeat(1022)))]);
+        meta.insert(format!("{RESERVED_METADATA_PREFIX_LOWER}compression"), "x".repeat(4096));
+        validate_user_metadata(&meta).unwrap();
+
+        meta.get_mut("key").unwrap().push('a');
+        assert_eq!(validate_user_metadata(&meta), Err(StorageError::MetadataTooLarge));
+
+        let _guard = test_util::GLOBAL_SWITCH_LOCK.lock().await;
+        let store = test_util::test_store();
+        let bucket = "metadata-limit";
+        store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+        let opts = ObjectOptions {
+            user_defined: meta,
+            ..Default::default()
+        };
+        let mut data = PutObjReader::from_vec(b"data".to_vec());
+        let err = store.put_object(bucket, "obj", &mut data, &opts).await.unwrap_err();
+        assert_eq!(err, StorageError::MetadataTooLarge);
+        let err = store.new_multipart_upload(bucket, "obj", &opts).await.unwrap_err();
+        assert_eq!(err, StorageError::MetadataTooLarge);
+    }
 }
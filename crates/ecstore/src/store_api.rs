@@ -14,29 +14,40 @@
 
 use crate::bucket::metadata_sys::get_versioning_config;
 use crate::bucket::versioning::VersioningApi as _;
-use crate::cmd::bucket_replication::{ReplicationStatusType, VersionPurgeStatusType};
+use crate::checksum::ChecksumMode;
+use crate::cmd::bucket_replication::{
+    REPLICATION_STATUS, ReplicationStatusType, VersionPurgeStatusType, replication_statuses_map,
+};
+use crate::compress::CompressionOpts;
 use crate::error::{Error, Result};
 use crate::heal::heal_ops::HealSequence;
+use crate::kms::{ObjectKey, is_kms_encrypted};
+use crate::store_list_objects::VersionMarker;
 use crate::store_utils::clean_metadata;
 use crate::{
     bucket::lifecycle::bucket_lifecycle_audit::LcAuditEvent,
     bucket::lifecycle::lifecycle::ExpirationOptions,
-    bucket::lifecycle::{bucket_lifecycle_ops::TransitionedObject, lifecycle::TransitionOptions},
+    bucket::lifecycle::{
+        bucket_lifecycle_ops::TransitionedObject,
+        lifecycle::{RestoreStatus, TRANSITION_COMPLETE, TransitionOptions},
+    },
 };
 use crate::{disk::DiskStore, heal::heal_commands::HealOpts};
 use http::{HeaderMap, HeaderValue};
 use rustfs_filemeta::headers::RESERVED_METADATA_PREFIX_LOWER;
 use rustfs_filemeta::{FileInfo, MetaCacheEntriesSorted, ObjectPartInfo, headers::AMZ_OBJECT_TAGGING};
 use rustfs_madmin::heal_commands::HealResultItem;
-use rustfs_rio::{DecompressReader, HashReader, LimitReader, WarpReader};
+use rustfs_rio::{DecompressReader, DecryptReader, HashReader, LimitReader, WarpReader};
 use rustfs_utils::CompressionAlgorithm;
 use rustfs_utils::path::decode_dir_object;
+use s3s::header::X_AMZ_RESTORE;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::io::Cursor;
 use std::str::FromStr as _;
 use std::sync::Arc;
+use std::time::Instant;
 use time::OffsetDateTime;
 use tokio::io::{AsyncRead, AsyncReadExt};
 use tracing::warn;
@@ -45,6 +56,10 @@ use uuid::Uuid;
 pub const ERASURE_ALGORITHM: &str = "rs-vandermonde";
 pub const BLOCK_SIZE_V2: usize = 1024 * 1024; // 1M
 
+// the bucket owner, also reported for objects whose metadata doesn't name one
+pub const BUCKET_OWNER_ID: &str = "c19050dbcee97fda828689dda99097a6321af2248fa760517237346e5d9c8a66";
+pub const BUCKET_OWNER_DISPLAY_NAME: &str = "rustfs";
+
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct MakeBucketOptions {
     pub lock_enabled: bool,
@@ -108,13 +123,14 @@ pub struct GetObjectReader {
 }
 
 impl GetObjectReader {
-    #[tracing::instrument(level = "debug", skip(reader))]
+    #[tracing::instrument(level = "debug", skip(reader, object_key))]
     pub fn new(
         reader: Box<dyn AsyncRead + Unpin + Send + Sync>,
         rs: Option<HTTPRangeSpec>,
         oi: &ObjectInfo,
         opts: &ObjectOptions,
         _h: &HeaderMap<HeaderValue>,
+        object_key: Option<ObjectKey>,
     ) -> Result<(Self, usize, i64)> {
         let mut rs = rs;
 
@@ -124,31 +140,30 @@ impl GetObjectReader {
             }
         }
 
-        // TODO:Encrypted
-
         let (algo, is_compressed) = oi.is_compressed_ok()?;
 
         // TODO: check TRANSITION
 
-        if is_compressed {
+        if object_key.is_some() || is_compressed {
+            let plain_reader: Box<dyn AsyncRead + Unpin + Send + Sync> = match object_key {
+                Some(object_key) => Box::new(DecryptReader::new(WarpReader::new(reader), object_key.key, object_key.iv)),
+                None => Box::new(DecompressReader::new(reader, algo)),
+            };
+
             let actual_size = oi.get_actual_size()?;
-            let (off, length) = (0, oi.size);
-            let (_dec_off, dec_length) = (0, actual_size);
-            if let Some(_rs) = rs {
-                // TODO: range spec is not supported for compressed object
-                return Err(Error::other("The requested range is not satisfiable"));
-                // let (off, length) = rs.get_offset_length(actual_size)?;
+            if actual_size < 0 || (is_compressed && actual_size == 0) {
+                return Err(Error::other(format!("invalid decompressed size {actual_size}")));
             }
 
-            let dec_reader = DecompressReader::new(reader, algo);
-
-            let actual_size = if actual_size > 0 {
-                actual_size as usize
-            } else {
-                return Err(Error::other(format!("invalid decompressed size {actual_size}")));
+            // the whole stored stream is read, ranges apply to the decrypted or decompressed bytes
+            let (off, length) = (0, oi.size);
+            let (dec_off, dec_length) = match rs {
+                Some(rs) => rs.get_offset_length(actual_size)?,
+                None => (0, actual_size),
             };
 
-            let dec_reader = LimitReader::new(dec_reader, actual_size);
+            let dec_reader = SkipReader::new(plain_reader, dec_off);
+            let dec_reader = LimitReader::new(dec_reader, dec_length as usize);
 
             let mut oi = oi.clone();
             oi.size = dec_length;
@@ -201,6 +216,42 @@ impl GetObjectReader {
     }
 }
 
+pin_project_lite::pin_project! {
+    /// Discards the first `skip` bytes of the inner reader.
+    struct SkipReader<R> {
+        #[pin]
+        inner: R,
+        skip: usize,
+    }
+}
+
+impl<R> SkipReader<R> {
+    fn new(inner: R, skip: usize) -> Self {
+        Self { inner, skip }
+    }
+}
+
+impl<R: AsyncRead> AsyncRead for SkipReader<R> {
+    fn poll_read(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        let mut this = self.project();
+        let mut scratch = [0u8; 8192];
+        while *this.skip > 0 {
+            let n = (*this.skip).min(scratch.len());
+            let mut skip_buf = tokio::io::ReadBuf::new(&mut scratch[..n]);
+            std::task::ready!(this.inner.as_mut().poll_read(cx, &mut skip_buf))?;
+            if skip_buf.filled().is_empty() {
+                return std::task::Poll::Ready(Ok(()));
+            }
+            *this.skip -= skip_buf.filled().len();
+        }
+        this.inner.poll_read(cx, buf)
+    }
+}
+
 #[derive(Debug)]
 pub struct HTTPRangeSpec {
     pub is_suffix_length: bool,
@@ -312,6 +363,37 @@ pub struct ObjectOptions {
     pub lifecycle_audit_event: LcAuditEvent,
 
     pub eval_metadata: Option<HashMap<String, String>>,
+
+    // ETag the current object must have for a delete to go ahead, "*" matches any object
+    pub if_match: Option<String>,
+
+    // whole-object checksum sent by the client, the value is base64 and computed by the server when absent
+    pub checksum_algorithm: Option<ChecksumMode>,
+    pub checksum_value: Option<String>,
+
+    // objects smaller than this are kept inside xl.meta, overrides the storage class inline_block
+    pub inline_data_threshold: Option<usize>,
+
+    // retried new_multipart_upload calls with the same key get the upload the first one started
+    pub idempotency_key: Option<String>,
+
+    // compress put_object data before erasure coding, falls back to the bucket default when unset
+    pub compression: Option<CompressionOpts>,
+
+    // delete_prefix removes every version under the prefix rather than only the latest ones
+    pub all_versions: bool,
+
+    // copy_object writes the source version id at the destination, which must not hold that version yet
+    pub preserve_version_id: bool,
+
+    // disk reads made on behalf of the request fail with DeadlineExceeded once this passes
+    pub deadline: Option<Instant>,
+
+    // deletes may remove versions under unexpired GOVERNANCE retention, set only for callers allowed to bypass it
+    pub bypass_governance: bool,
+
+    // get_multipart_info returns only the upload metadata, without reading every part.N.meta
+    pub skip_parts: bool,
 }
 
 // impl Default for ObjectOptions {
@@ -338,6 +420,12 @@ pub struct BucketInfo {
     pub deleted: Option<OffsetDateTime>,
     pub versionning: bool,
     pub object_locking: bool,
+    /// Object count from the last usage scan, None until one has run.
+    pub objects_count: Option<u64>,
+    /// Total object size from the last usage scan, None until one has run.
+    pub size: Option<u64>,
+    /// When the usage scan behind `objects_count` and `size` was taken.
+    pub usage_updated: Option<OffsetDateTime>,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -369,6 +457,12 @@ impl From<s3s::dto::CompletedPart> for CompletePart {
     }
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ObjectOwner {
+    pub id: String,
+    pub display_name: String,
+}
+
 #[derive(Debug, Default)]
 pub struct ObjectInfo {
     pub bucket: String,
@@ -384,6 +478,8 @@ pub struct ObjectInfo {
     pub version_id: Option<Uuid>,
     pub delete_marker: bool,
     pub transitioned_object: TransitionedObject,
+    // set when a transitioned object has been asked to restore
+    pub restore_status: Option<RestoreStatus>,
     pub user_tags: String,
     pub parts: Vec<ObjectPartInfo>,
     pub is_latest: bool,
@@ -401,6 +497,10 @@ pub struct ObjectInfo {
     pub version_purge_status_internal: String,
     pub version_purge_status: VersionPurgeStatusType,
     pub checksum: Vec<u8>,
+    // the lifecycle expiry date and rule id, sent back as x-amz-expiration
+    pub expiration: Option<(OffsetDateTime, String)>,
+    // only filled in by listings that ask for the owner
+    pub owner: Option<ObjectOwner>,
 }
 
 impl Clone for ObjectInfo {
@@ -418,6 +518,7 @@ impl Clone for ObjectInfo {
             version_id: self.version_id,
             delete_marker: self.delete_marker,
             transitioned_object: self.transitioned_object.clone(),
+            restore_status: self.restore_status.clone(),
             user_tags: self.user_tags.clone(),
             parts: self.parts.clone(),
             is_latest: self.is_latest,
@@ -435,11 +536,38 @@ impl Clone for ObjectInfo {
             version_purge_status_internal: self.version_purge_status_internal.clone(),
             version_purge_status: self.version_purge_status.clone(),
             checksum: Default::default(),
+            expiration: self.expiration.clone(),
+            owner: self.owner.clone(),
         }
     }
 }
 
 impl ObjectInfo {
+    /// The whole-object checksum stored at upload, with its base64 value.
+    pub fn checksum(&self) -> Option<(ChecksumMode, String)> {
+        let algorithm = self
+            .user_defined
+            .get(&format!("{RESERVED_METADATA_PREFIX_LOWER}checksum-algorithm"))?;
+        let value = self.user_defined.get(&format!("{RESERVED_METADATA_PREFIX_LOWER}checksum"))?;
+        Some((ChecksumMode::from_algorithm(algorithm)?, value.clone()))
+    }
+
+    /// The owner recorded in the object metadata, the bucket owner when it has none.
+    pub fn stored_owner(&self) -> ObjectOwner {
+        let id = self.user_defined.get(&format!("{RESERVED_METADATA_PREFIX_LOWER}owner-id"));
+        let display_name = self.user_defined.get(&format!("{RESERVED_METADATA_PREFIX_LOWER}owner-name"));
+        match id.filter(|id| !id.is_empty()) {
+            Some(id) => ObjectOwner {
+                id: id.clone(),
+                display_name: display_name.cloned().unwrap_or_default(),
+            },
+            None => ObjectOwner {
+                id: BUCKET_OWNER_ID.to_owned(),
+                display_name: BUCKET_OWNER_DISPLAY_NAME.to_owned(),
+            },
+        }
+    }
+
     pub fn is_compressed(&self) -> bool {
         self.user_defined
             .contains_key(&format!("{RESERVED_METADATA_PREFIX_LOWER}compression"))
@@ -487,7 +615,12 @@ impl ObjectInfo {
             return Ok(actual_size);
         }
 
-        // TODO: IsEncrypted
+        if is_kms_encrypted(&self.user_defined) {
+            if let Some(size_str) = self.user_defined.get(&format!("{RESERVED_METADATA_PREFIX_LOWER}actual-size")) {
+                return size_str.parse::<i64>().map_err(|e| std::io::Error::other(e.to_string()));
+            }
+            return Err(std::io::Error::other("missing decrypted size"));
+        }
 
         Ok(self.size)
     }
@@ -530,6 +663,13 @@ impl ObjectInfo {
             tier: fi.transition_tier.clone(),
         };
 
+        // only an object that went to a tier can be restored
+        let restore_status = if transitioned_object.status == TRANSITION_COMPLETE {
+            fi.metadata.get(X_AMZ_RESTORE.as_str()).and_then(|v| RestoreStatus::parse(v))
+        } else {
+            None
+        };
+
         let metadata = {
             let mut v = fi.metadata.clone();
             clean_metadata(&mut v);
@@ -572,6 +712,7 @@ impl ObjectInfo {
             inlined,
             user_defined: metadata,
             transitioned_object,
+            restore_status,
             ..Default::default()
         }
     }
@@ -581,7 +722,7 @@ impl ObjectInfo {
         bucket: &str,
         prefix: &str,
         delimiter: Option<String>,
-        after_version_id: Option<Uuid>,
+        version_marker: Option<&VersionMarker>,
     ) -> Vec<ObjectInfo> {
         let vcfg = get_versioning_config(bucket).await.ok();
         let mut objects = Vec::with_capacity(entries.entries().len());
@@ -617,14 +758,9 @@ impl ObjectInfo {
                     }
                 };
 
-                let versions = if let Some(vid) = after_version_id {
-                    if let Some(idx) = file_infos.find_version_index(vid) {
-                        &file_infos.versions[idx + 1..]
-                    } else {
-                        &file_infos.versions
-                    }
-                } else {
-                    &file_infos.versions
+                let versions = match version_marker {
+                    Some(marker) if marker.key == entry.name => marker.skip_versions(&file_infos.versions),
+                    _ => &file_infos.versions,
                 };
 
                 for fi in versions.iter() {
@@ -755,7 +891,7 @@ pub struct ListObjectsInfo {
     pub prefixes: Vec<String>,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct ListObjectsV2Info {
     // Indicates whether the returned list objects response is truncated. A
     // value of true indicates that the list was truncated. The list can be truncated
@@ -772,6 +908,9 @@ pub struct ListObjectsV2Info {
     pub continuation_token: Option<String>,
     pub next_continuation_token: Option<String>,
 
+    // The max keys actually applied to this page after clamping.
+    pub max_keys: i32,
+
     // List of objects info for this request.
     pub objects: Vec<ObjectInfo>,
 
@@ -795,6 +934,9 @@ pub struct MultipartInfo {
 
     // Any metadata set during InitMultipartUpload, including encryption headers.
     pub user_defined: HashMap<String, String>,
+
+    // Parts uploaded so far, sorted by part number.
+    pub parts: Vec<PartInfo>,
 }
 
 // ListMultipartsInfo - represents bucket resources for incomplete multipart uploads.
@@ -849,6 +991,14 @@ pub struct ObjectToDelete {
     pub object_name: String,
     pub version_id: Option<Uuid>,
 }
+
+/// Outcome of [`StorageAPI::delete_prefix`].
+#[derive(Debug, Default, Clone)]
+pub struct DeletePrefixReport {
+    pub deleted: usize,
+    /// Objects left in place because object lock forbids removing them.
+    pub skipped: Vec<ObjectToDelete>,
+}
 #[derive(Debug, Default, Clone)]
 pub struct DeletedObject {
     pub delete_marker: bool,
@@ -861,6 +1011,124 @@ pub struct DeletedObject {
     // pub replication_state: ReplicationState,
 }
 
+/// What one drive's `xl.meta` says about the latest version of an object.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DiskMetaView {
+    pub endpoint: String,
+    pub version_id: Option<Uuid>,
+    pub etag: Option<String>,
+    pub mod_time: Option<OffsetDateTime>,
+    // (number, size) of each part
+    pub parts: Vec<(usize, usize)>,
+    // set when the metadata could not be read, the other fields are then empty
+    pub err: Option<String>,
+}
+
+impl DiskMetaView {
+    fn agrees_with(&self, other: &DiskMetaView) -> bool {
+        self.version_id == other.version_id
+            && self.etag == other.etag
+            && self.mod_time == other.mod_time
+            && self.parts == other.parts
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct MetaConsistencyReport {
+    pub bucket: String,
+    pub object: String,
+    pub disks: Vec<DiskMetaView>,
+    /// Indexes into `disks` whose readable metadata disagrees with the majority,
+    /// a sign of a split-brain write.
+    pub divergent: Vec<usize>,
+}
+
+impl MetaConsistencyReport {
+    pub fn new(bucket: &str, object: &str, disks: Vec<DiskMetaView>) -> Self {
+        let readable: Vec<usize> = (0..disks.len()).filter(|&i| disks[i].err.is_none()).collect();
+        let majority = readable
+            .iter()
+            .copied()
+            .max_by_key(|&i| readable.iter().filter(|&&j| disks[j].agrees_with(&disks[i])).count());
+        let divergent = match majority {
+            Some(m) => readable.into_iter().filter(|&i| !disks[i].agrees_with(&disks[m])).collect(),
+            None => Vec::new(),
+        };
+
+        Self {
+            bucket: bucket.to_string(),
+            object: object.to_string(),
+            disks,
+            divergent,
+        }
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.divergent.is_empty()
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReplicationCount {
+    pub count: u64,
+    pub bytes: u64,
+}
+
+/// Object versions of one replication target, by their replication status.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TargetReplicationStats {
+    pub pending: ReplicationCount,
+    pub failed: ReplicationCount,
+    pub completed: ReplicationCount,
+}
+
+impl TargetReplicationStats {
+    pub fn add(&mut self, status: &ReplicationStatusType, size: u64) {
+        let count = match status {
+            ReplicationStatusType::Pending => &mut self.pending,
+            ReplicationStatusType::Failed => &mut self.failed,
+            ReplicationStatusType::Completed | ReplicationStatusType::CompletedLegacy => &mut self.completed,
+            ReplicationStatusType::Replica | ReplicationStatusType::Unknown => return,
+        };
+        count.count += 1;
+        count.bytes += size;
+    }
+
+    pub fn merge(&mut self, other: &TargetReplicationStats) {
+        for (count, other) in [
+            (&mut self.pending, &other.pending),
+            (&mut self.failed, &other.failed),
+            (&mut self.completed, &other.completed),
+        ] {
+            count.count += other.count;
+            count.bytes += other.bytes;
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ReplicationStats {
+    /// Keyed by target ARN.
+    pub targets: HashMap<String, TargetReplicationStats>,
+}
+
+impl ReplicationStats {
+    /// Counts an object version towards every target its replication status names.
+    pub fn add_object(&mut self, oi: &ObjectInfo) {
+        let status_key = format!("{RESERVED_METADATA_PREFIX_LOWER}{REPLICATION_STATUS}");
+        let internal = oi.user_defined.get(&status_key).unwrap_or(&oi.replication_status_internal);
+        for (arn, status) in replication_statuses_map(internal) {
+            self.targets.entry(arn).or_default().add(&status, oi.size.max(0) as u64);
+        }
+    }
+
+    pub fn merge(&mut self, other: ReplicationStats) {
+        for (arn, target) in other.targets {
+            self.targets.entry(arn).or_default().merge(&target);
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct ListObjectVersionsInfo {
     pub is_truncated: bool,
@@ -870,6 +1138,90 @@ pub struct ListObjectVersionsInfo {
     pub prefixes: Vec<String>,
 }
 
+#[derive(Debug, Default, Clone)]
+pub struct PoolHealth {
+    pub set_drive_count: usize,
+    pub read_quorum: usize,
+    pub write_quorum: usize,
+    pub disks_online: usize,
+    pub disks_total: usize,
+    // every erasure set in the pool has at least read_quorum / write_quorum disks online
+    pub has_read_quorum: bool,
+    pub has_write_quorum: bool,
+}
+
+impl PoolHealth {
+    /// Health of a pool whose erasure sets have `online[i]` disks online each.
+    pub fn new(set_drive_count: usize, parity: usize, online: &[usize]) -> Self {
+        let read_quorum = set_drive_count - parity;
+        let write_quorum = if read_quorum == parity { read_quorum + 1 } else { read_quorum };
+
+        Self {
+            set_drive_count,
+            read_quorum,
+            write_quorum,
+            disks_online: online.iter().sum(),
+            disks_total: set_drive_count * online.len(),
+            has_read_quorum: online.iter().all(|&n| n >= read_quorum),
+            has_write_quorum: online.iter().all(|&n| n >= write_quorum),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct ClusterHealth {
+    pub pools: Vec<PoolHealth>,
+    pub disks_online: usize,
+    pub read_quorum: bool,
+    pub write_quorum: bool,
+}
+
+impl ClusterHealth {
+    pub fn from_pools(pools: Vec<PoolHealth>) -> Self {
+        Self {
+            disks_online: pools.iter().map(|p| p.disks_online).sum(),
+            read_quorum: pools.iter().all(|p| p.has_read_quorum),
+            write_quorum: pools.iter().all(|p| p.has_write_quorum),
+            pools,
+        }
+    }
+}
+
+/// One drive's view of an object version, as returned by [`StorageAPI::inspect_object`].
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct DiskInspectInfo {
+    pub endpoint: String,
+    pub online: bool,
+    pub etag: Option<String>,
+    pub parts: usize,
+    pub data_dir: Option<Uuid>,
+    pub erasure_index: usize,
+    pub mod_time: Option<OffsetDateTime>,
+    /// `CHECK_PART_*` bitrot result for each part; empty when the shards are not verified,
+    /// as for delete markers, transitioned and inline objects.
+    pub bitrot: Vec<usize>,
+    /// Why the drive could not report the version, e.g. a missing `xl.meta` or an offline drive.
+    pub err: Option<String>,
+}
+
+impl DiskInspectInfo {
+    /// True when the drive holds the version and every part verified.
+    pub fn is_healthy(&self) -> bool {
+        self.online && self.err.is_none() && self.bitrot.iter().all(|r| *r == crate::disk::CHECK_PART_SUCCESS)
+    }
+}
+
+/// Per-drive layout of an object version across its erasure set.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ObjectInspectReport {
+    pub bucket: String,
+    pub object: String,
+    pub version_id: Option<String>,
+    pub pool_index: usize,
+    pub set_index: usize,
+    pub disks: Vec<DiskInspectInfo>,
+}
+
 #[async_trait::async_trait]
 pub trait ObjectIO: Send + Sync + 'static {
     // GetObjectNInfo FIXME:
@@ -888,10 +1240,12 @@ pub trait ObjectIO: Send + Sync + 'static {
 #[async_trait::async_trait]
 #[allow(clippy::too_many_arguments)]
 pub trait StorageAPI: ObjectIO {
-    // NewNSLock TODO:
-    // Shutdown TODO:
-    // NSScanner TODO:
-
+    // NewNSLock: see rustfs_lock::namespace_lock::NamespaceLock
+    // NSScanner: see ECStore::scan_usage
+    /// Tears the layer down for process exit: stops background work and closes every drive.
+    ///
+    /// Unless `force` is set, queued heals are drained and pending MRF operations are saved first.
+    async fn shutdown(&self, force: bool) -> Result<()>;
     async fn backend_info(&self) -> rustfs_madmin::BackendInfo;
     async fn storage_info(&self) -> rustfs_madmin::StorageInfo;
     async fn local_storage_info(&self) -> rustfs_madmin::StorageInfo;
@@ -925,6 +1279,10 @@ pub trait StorageAPI: ObjectIO {
 
     // GetObjectNInfo ObjectIO
     async fn get_object_info(&self, bucket: &str, object: &str, opts: &ObjectOptions) -> Result<ObjectInfo>;
+    /// Reports each drive's view of an object version to diagnose split-brain or missing shards.
+    async fn inspect_object(&self, bucket: &str, object: &str, version_id: Option<&str>) -> Result<ObjectInspectReport>;
+    // Metadata of many objects in one go, with a result per object in the order asked
+    async fn get_object_infos(&self, bucket: &str, objects: &[String], opts: &ObjectOptions) -> Result<Vec<Result<ObjectInfo>>>;
     // PutObject ObjectIO
     // CopyObject
     async fn copy_object(
@@ -945,6 +1303,10 @@ pub trait StorageAPI: ObjectIO {
         objects: Vec<ObjectToDelete>,
         opts: ObjectOptions,
     ) -> Result<(Vec<DeletedObject>, Vec<Option<Error>>)>;
+    /// Deletes every object under `prefix`, or every version with `opts.all_versions`.
+    ///
+    /// Objects under retention or legal hold are skipped and listed in the report.
+    async fn delete_prefix(self: Arc<Self>, bucket: &str, prefix: &str, opts: ObjectOptions) -> Result<DeletePrefixReport>;
 
     // TransitionObject TODO:
     // RestoreTransitionedObject TODO:
@@ -974,7 +1336,7 @@ pub trait StorageAPI: ObjectIO {
         src_info: &ObjectInfo,
         src_opts: &ObjectOptions,
         dst_opts: &ObjectOptions,
-    ) -> Result<()>;
+    ) -> Result<PartInfo>;
     async fn put_object_part(
         &self,
         bucket: &str,
@@ -1007,7 +1369,8 @@ pub trait StorageAPI: ObjectIO {
     // SetDriveCounts
     fn set_drive_counts(&self) -> Vec<usize>;
 
-    // Health TODO:
+    // Health
+    async fn health(&self) -> ClusterHealth;
     // PutObjectMetadata
     async fn put_object_metadata(&self, bucket: &str, object: &str, opts: &ObjectOptions) -> Result<ObjectInfo>;
     // DecomTieredObject
@@ -1017,6 +1380,16 @@ pub trait StorageAPI: ObjectIO {
     async fn restore_transitioned_object(&self, bucket: &str, object: &str, opts: &ObjectOptions) -> Result<()>;
     async fn put_object_tags(&self, bucket: &str, object: &str, tags: &str, opts: &ObjectOptions) -> Result<ObjectInfo>;
     async fn delete_object_tags(&self, bucket: &str, object: &str, opts: &ObjectOptions) -> Result<ObjectInfo>;
+    // Remove a version's GOVERNANCE retention before it expires, `bypass` carries the caller's bypass permission
+    async fn clear_object_retention(&self, bucket: &str, object: &str, version_id: &str, bypass: bool) -> Result<()>;
+    // Set a version's mod time without rewriting its data, the latest version when `version_id` is None
+    async fn touch_object(
+        &self,
+        bucket: &str,
+        object: &str,
+        version_id: Option<&str>,
+        new_mtime: OffsetDateTime,
+    ) -> Result<ObjectInfo>;
 
     async fn heal_format(&self, dry_run: bool) -> Result<(HealResultItem, Option<Error>)>;
     async fn heal_bucket(&self, bucket: &str, opts: &HealOpts) -> Result<HealResultItem>;
@@ -1027,8 +1400,112 @@ pub trait StorageAPI: ObjectIO {
         version_id: &str,
         opts: &HealOpts,
     ) -> Result<(HealResultItem, Option<Error>)>;
+    // Verify and heal every version of an object, unrecoverable versions carry the error in `detail`
+    async fn scrub_object(&self, bucket: &str, object: &str, opts: &HealOpts) -> Result<Vec<(String, HealResultItem)>>;
+    // Compare the latest version's xl.meta across the object's drives, for diagnosing split-brain writes
+    async fn verify_metadata_consistency(&self, bucket: &str, object: &str) -> Result<MetaConsistencyReport>;
+    // Count the bucket's object versions per replication target and status, scanning every version
+    async fn replication_stats(self: Arc<Self>, bucket: &str) -> Result<ReplicationStats>;
     async fn heal_objects(&self, bucket: &str, prefix: &str, opts: &HealOpts, hs: Arc<HealSequence>, is_meta: bool)
     -> Result<()>;
     async fn get_pool_and_set(&self, id: &str) -> Result<(Option<usize>, Option<usize>, Option<usize>)>;
     async fn check_abandoned_parts(&self, bucket: &str, object: &str, opts: &HealOpts) -> Result<()>;
 }
+
+// If-Match holds when the ETag is equal ignoring quotes, or is "*" and the object exists
+pub(crate) fn check_if_match(if_match: &str, oi: &ObjectInfo) -> Result<()> {
+    let if_match = if_match.trim().trim_matches('"');
+    let matched =
+        !oi.delete_marker && (if_match == "*" || oi.etag.as_deref().is_some_and(|etag| etag.trim_matches('"') == if_match));
+    if !matched {
+        return Err(Error::PreconditionFailed);
+    }
+
+    Ok(())
+}
+
+/// Streams `[start_offset, start_offset + length)` of the source object into a part of the upload.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn copy_object_range<S: StorageAPI + ?Sized>(
+    api: &S,
+    src_bucket: &str,
+    src_object: &str,
+    dst_bucket: &str,
+    dst_object: &str,
+    upload_id: &str,
+    part_id: usize,
+    start_offset: i64,
+    length: i64,
+    src_info: &ObjectInfo,
+    src_opts: &ObjectOptions,
+    dst_opts: &ObjectOptions,
+) -> Result<PartInfo> {
+    let size = src_info.get_actual_size()?;
+    if start_offset < 0 || length < 0 || start_offset.checked_add(length).is_none_or(|end| end > size) {
+        return Err(Error::InvalidRange);
+    }
+
+    let mut data = if length == 0 {
+        PutObjReader::from_vec(Vec::new())
+    } else {
+        let range = HTTPRangeSpec {
+            is_suffix_length: false,
+            start: start_offset,
+            end: start_offset + length - 1,
+        };
+        let gr = api
+            .get_object_reader(src_bucket, src_object, Some(range), HeaderMap::new(), src_opts)
+            .await?;
+        PutObjReader::new(HashReader::new(Box::new(WarpReader::new(gr.stream)), length, length, None, false)?)
+    };
+
+    api.put_object_part(dst_bucket, dst_object, upload_id, part_id, &mut data, dst_opts)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transitioned(restore: Option<&str>) -> FileInfo {
+        let mut fi = FileInfo {
+            transition_status: TRANSITION_COMPLETE.to_string(),
+            ..Default::default()
+        };
+        if let Some(restore) = restore {
+            fi.metadata.insert(X_AMZ_RESTORE.as_str().to_string(), restore.to_string());
+        }
+        fi
+    }
+
+    #[test]
+    fn test_object_info_restore_status() {
+        let oi = ObjectInfo::from_file_info(&transitioned(Some("ongoing-request=\"true\"")), "bucket", "obj", false);
+        assert_eq!(oi.restore_status, Some(RestoreStatus::ongoing()));
+
+        let expiry = time::macros::datetime!(2012-12-21 00:00:00 UTC);
+        let completed = TransitionOptions {
+            restore_expiry: expiry,
+            ..Default::default()
+        }
+        .completed_restore();
+        let header = completed.to_string();
+        assert_eq!(header, "ongoing-request=\"false\", expiry-date=\"Fri, 21 Dec 2012 00:00:00 GMT\"");
+        let oi = ObjectInfo::from_file_info(&transitioned(Some(&header)), "bucket", "obj", false);
+        let status = oi.restore_status.unwrap();
+        assert!(!status.is_ongoing());
+        assert_eq!(status.expiry, Some(expiry));
+
+        // neither a transitioned object without a restore, nor a local one carrying the header
+        let oi = ObjectInfo::from_file_info(&transitioned(None), "bucket", "obj", false);
+        assert!(oi.restore_status.is_none());
+        let mut local = transitioned(Some("ongoing-request=\"true\""));
+        local.transition_status.clear();
+        assert!(
+            ObjectInfo::from_file_info(&local, "bucket", "obj", false)
+                .restore_status
+                .is_none()
+        );
+        assert!(RestoreStatus::parse("ongoing-request=\"maybe\"").is_none());
+    }
+}
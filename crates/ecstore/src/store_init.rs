@@ -15,6 +15,7 @@
 use crate::config::{KVS, storageclass};
 use crate::disk::error_reduce::{count_errs, reduce_write_quorum_errs};
 use crate::disk::{self, DiskAPI};
+use crate::disks_layout::DisksLayout;
 use crate::error::{Error, Result};
 use crate::{
     disk::{
@@ -153,6 +154,69 @@ async fn init_format_erasure(
     get_format_erasure_in_quorum(&fms)
 }
 
+/// Formats brand-new drives for every pool of `layout`, `disks` lists the drives in layout order.
+/// Each pool gets its own format, all sharing a fresh deployment id, and every drive is written
+/// concurrently. Drives that already hold a format or buckets are refused unless `force` is set.
+pub async fn format_fresh(disks: &[DiskStore], layout: &DisksLayout, force: bool) -> Result<Vec<FormatV3>> {
+    let pools: Vec<(usize, usize)> = layout
+        .pools
+        .iter()
+        .map(|pool| (pool.iter().count(), pool.iter().next().map_or(0, Vec::len)))
+        .collect();
+
+    let total: usize = pools
+        .iter()
+        .map(|(set_count, set_drive_count)| set_count * set_drive_count)
+        .sum();
+    if total != disks.len() {
+        return Err(Error::other(format!("format_fresh: layout has {} drives, got {}", total, disks.len())));
+    }
+
+    if !force {
+        let in_use = join_all(disks.iter().map(disk_in_use)).await;
+        let used: Vec<String> = disks
+            .iter()
+            .zip(in_use)
+            .filter(|(_, in_use)| *in_use)
+            .map(|(disk, _)| disk.to_string())
+            .collect();
+        if !used.is_empty() {
+            return Err(Error::other(format!("refusing to format drives holding data: {}", used.join(", "))));
+        }
+    }
+
+    let deployment_id = Uuid::new_v4();
+    let mut pool_formats = Vec::with_capacity(pools.len());
+    let mut formats = Vec::with_capacity(disks.len());
+    for (set_count, set_drive_count) in pools {
+        let mut fm = FormatV3::new(set_count, set_drive_count);
+        fm.id = deployment_id;
+        for set in fm.erasure.sets.iter() {
+            for this in set.iter() {
+                let mut disk_fm = fm.clone();
+                disk_fm.erasure.this = *this;
+                formats.push(Some(disk_fm));
+            }
+        }
+        pool_formats.push(fm);
+    }
+
+    let disks: Vec<Option<DiskStore>> = disks.iter().cloned().map(Some).collect();
+    save_format_file_all(&disks, &formats).await?;
+
+    Ok(pool_formats)
+}
+
+async fn disk_in_use(disk: &DiskStore) -> bool {
+    if disk.read_all(RUSTFS_META_BUCKET, FORMAT_CONFIG_FILE).await.is_ok() {
+        return true;
+    }
+
+    disk.list_volumes()
+        .await
+        .is_ok_and(|volumes| volumes.iter().any(|v| v.name != RUSTFS_META_BUCKET))
+}
+
 pub fn get_format_erasure_in_quorum(formats: &[Option<FormatV3>]) -> Result<FormatV3> {
     let mut countmap = HashMap::new();
 
@@ -222,6 +286,51 @@ fn check_format_erasure_value(format: &FormatV3) -> Result<()> {
     Ok(())
 }
 
+/// Reads the disk id of every drive concurrently, results are in the same order as `disks`.
+pub async fn read_disk_ids(disks: &[DiskStore]) -> Vec<disk::error::Result<Option<Uuid>>> {
+    join_all(disks.iter().map(|disk| disk.get_disk_id())).await
+}
+
+/// Checks that every reachable drive carries an id from `expected` and sits at the
+/// position `format.json` assigns to it. Offline and unformatted drives are skipped,
+/// they are accounted for by the quorum checks.
+pub async fn verify_format_consistency(disks: &[DiskStore], expected: &FormatV3) -> Result<()> {
+    let ids = read_disk_ids(disks).await;
+
+    let mut mismatched = Vec::new();
+    for (disk, id) in disks.iter().zip(ids) {
+        let ep = disk.endpoint();
+        match id {
+            Ok(Some(id)) => match expected.find_disk_index_by_disk_id(id) {
+                Ok((m, n)) => {
+                    if ep.set_idx >= 0 && (m as i32 != ep.set_idx || n as i32 != ep.disk_idx) {
+                        mismatched.push(format!(
+                            "{} holds drive {}:{} but is configured as {}:{}",
+                            disk.to_string(),
+                            m,
+                            n,
+                            ep.set_idx,
+                            ep.disk_idx
+                        ));
+                    }
+                }
+                Err(_) => mismatched.push(format!("{} has drive id {} not found in format.json", disk.to_string(), id)),
+            },
+            Err(DiskError::InconsistentDisk) => {
+                mismatched.push(format!("{} format.json does not match its drive position", disk.to_string()))
+            }
+            Ok(None) | Err(_) => {}
+        }
+    }
+
+    if mismatched.is_empty() {
+        return Ok(());
+    }
+
+    warn!("verify_format_consistency: {:?}", &mismatched);
+    Err(Error::other(format!("inconsistent drives: {}", mismatched.join("; "))))
+}
+
 // load_format_erasure_all 读取所有 foramt.json
 pub async fn load_format_erasure_all(disks: &[Option<DiskStore>], heal: bool) -> (Vec<Option<FormatV3>>, Vec<Option<DiskError>>) {
     let mut futures = Vec::with_capacity(disks.len());
@@ -231,6 +340,11 @@ pub async fn load_format_erasure_all(disks: &[Option<DiskStore>], heal: bool) ->
     for disk in disks.iter() {
         futures.push(async move {
             if let Some(disk) = disk {
+                if !heal {
+                    if let Err(err) = upgrade_format(disk).await {
+                        warn!("upgrade {} on {} failed: {:?}", FORMAT_CONFIG_FILE, disk.to_string(), err);
+                    }
+                }
                 load_format_erasure(disk, heal).await
             } else {
                 Err(DiskError::DiskNotFound)
@@ -284,6 +398,26 @@ pub async fn load_format_erasure(disk: &DiskStore, heal: bool) -> disk::error::R
     Ok(fm)
 }
 
+/// Rewrites a `format.json` left by an older erasure format version in the current one, keeping
+/// the deployment id, drive ids and set layout. Returns whether `disk` was upgraded, drives that
+/// are current or unformatted are left alone.
+pub async fn upgrade_format(disk: &DiskStore) -> disk::error::Result<bool> {
+    let data = match disk.read_all(RUSTFS_META_BUCKET, FORMAT_CONFIG_FILE).await {
+        Ok(data) => data,
+        Err(DiskError::FileNotFound | DiskError::VolumeNotFound) => return Ok(false),
+        Err(e) => return Err(e),
+    };
+
+    let Some(fm) = FormatV3::upgrade(data.as_ref())? else {
+        return Ok(false);
+    };
+
+    save_format_file(&Some(disk.clone()), &Some(fm), "").await?;
+    info!("upgraded {} on {} to the current format version", FORMAT_CONFIG_FILE, disk.to_string());
+
+    Ok(true)
+}
+
 async fn save_format_file_all(disks: &[Option<DiskStore>], formats: &[Option<FormatV3>]) -> disk::error::Result<()> {
     let mut futures = Vec::with_capacity(disks.len());
 
@@ -394,3 +528,136 @@ pub fn ec_drives_no_config(set_drive_count: usize) -> Result<usize> {
 //         }
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disk::endpoint::Endpoint;
+
+    async fn new_test_disk(dir: &str, disk_idx: usize) -> DiskStore {
+        let _ = tokio::fs::remove_dir_all(dir).await;
+        tokio::fs::create_dir_all(dir).await.unwrap();
+
+        let mut ep = Endpoint::try_from(dir).unwrap();
+        ep.set_pool_index(0);
+        ep.set_set_index(0);
+        ep.set_disk_index(disk_idx);
+
+        new_disk(&ep, &DiskOption::default()).await.unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_verify_format_consistency() {
+        let dirs = ["./test_verify_format_0", "./test_verify_format_1"];
+        let disks = vec![new_test_disk(dirs[0], 0).await, new_test_disk(dirs[1], 1).await];
+
+        let expected = FormatV3::new(1, 2);
+        for (j, disk) in disks.iter().enumerate() {
+            let mut fm = expected.clone();
+            fm.erasure.this = expected.erasure.sets[0][j];
+            save_format_file(&Some(disk.clone()), &Some(fm), "").await.unwrap();
+        }
+
+        let ids = read_disk_ids(&disks).await;
+        assert_eq!(ids[0].as_ref().unwrap(), &Some(expected.erasure.sets[0][0]));
+        assert_eq!(ids[1].as_ref().unwrap(), &Some(expected.erasure.sets[0][1]));
+        verify_format_consistency(&disks, &expected).await.unwrap();
+
+        // a drive from another deployment plugged into slot 1
+        let foreign = FormatV3::new(1, 2);
+        let mut fm = foreign.clone();
+        fm.erasure.this = foreign.erasure.sets[0][1];
+        let disks = vec![disks[0].clone(), new_test_disk(dirs[1], 1).await];
+        save_format_file(&Some(disks[1].clone()), &Some(fm), "").await.unwrap();
+
+        let err = verify_format_consistency(&disks, &expected).await.unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains(&foreign.erasure.sets[0][1].to_string()), "{msg}");
+        assert!(!msg.contains(&expected.erasure.sets[0][0].to_string()), "{msg}");
+
+        for dir in dirs {
+            let _ = tokio::fs::remove_dir_all(dir).await;
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_format_fresh() {
+        use crate::disks_layout::PoolDisksLayout;
+
+        let dirs: Vec<String> = (0..8).map(|i| format!("./test_format_fresh_{i}")).collect();
+        let mut disks = Vec::new();
+        for (i, dir) in dirs.iter().enumerate() {
+            disks.push(new_test_disk(dir, i % 4).await);
+        }
+
+        let sets = dirs.chunks(4).map(|set| set.to_vec()).collect();
+        let layout = DisksLayout {
+            legacy: false,
+            pools: vec![PoolDisksLayout::new(dirs.join(" "), sets)],
+        };
+
+        let formats = format_fresh(&disks, &layout, false).await.unwrap();
+        assert_eq!(formats.len(), 1);
+        let fm = &formats[0];
+        assert_eq!(fm.erasure.sets.len(), 2);
+        assert_eq!(fm.erasure.sets[0].len(), 4);
+
+        for (i, disk) in disks.iter().enumerate() {
+            let (set, drive) = (i / 4, i % 4);
+            let saved = load_format_erasure(disk, false).await.unwrap();
+            assert_eq!(saved.id, fm.id);
+            assert_eq!(saved.erasure.this, fm.erasure.sets[set][drive]);
+        }
+
+        // formatted drives are left alone unless forced
+        let err = format_fresh(&disks, &layout, false).await.unwrap_err();
+        assert!(err.to_string().contains("refusing"), "{err}");
+        let forced = format_fresh(&disks, &layout, true).await.unwrap();
+        assert_ne!(forced[0].id, fm.id);
+
+        for dir in dirs {
+            let _ = tokio::fs::remove_dir_all(dir).await;
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_upgrade_format() {
+        let dir = "./test_upgrade_format";
+        let disk = new_test_disk(dir, 0).await;
+
+        // unformatted drives are left alone
+        assert!(!upgrade_format(&disk).await.unwrap());
+
+        let expected = FormatV3::new(1, 2);
+        let this = expected.erasure.sets[0][1];
+        let old = serde_json::json!({
+            "version": "1",
+            "format": "xl",
+            "id": expected.id,
+            "xl": {
+                "version": "2",
+                "this": this,
+                "sets": expected.erasure.sets,
+            }
+        });
+        disk.write_all(RUSTFS_META_BUCKET, FORMAT_CONFIG_FILE, old.to_string().into_bytes().into())
+            .await
+            .unwrap();
+        assert!(load_format_erasure(&disk, false).await.is_err());
+
+        assert!(upgrade_format(&disk).await.unwrap());
+
+        let fm = load_format_erasure(&disk, false).await.unwrap();
+        assert_eq!(fm.id, expected.id);
+        assert_eq!(fm.erasure.this, this);
+        assert_eq!(fm.erasure.sets, expected.erasure.sets);
+        assert_eq!(fm.erasure.version, FormatErasureVersion::V3);
+        check_format_erasure_value(&fm).unwrap();
+
+        // running it again on the now current drive changes nothing
+        assert!(!upgrade_format(&disk).await.unwrap());
+        assert_eq!(load_format_erasure(&disk, false).await.unwrap(), fm);
+
+        let _ = tokio::fs::remove_dir_all(dir).await;
+    }
+}
@@ -19,8 +19,10 @@ use crate::cache_value::metacache_set::{ListPathRawOptions, list_path_raw};
 use crate::disk::error::DiskError;
 use crate::disk::{DiskInfo, DiskStore};
 use crate::error::{
-    Error, Result, StorageError, is_all_not_found, is_all_volume_not_found, is_err_bucket_not_found, to_object_err,
+    Error, Result, StorageError, is_all_not_found, is_all_volume_not_found, is_err_bucket_not_found, is_err_object_not_found,
+    to_object_err,
 };
+use crate::global::{DEFAULT_MAX_LIST_KEYS, get_global_max_list_keys};
 use crate::set_disk::SetDisks;
 use crate::store::check_list_objs_args;
 use crate::store_api::{ListObjectVersionsInfo, ListObjectsInfo, ObjectInfo, ObjectOptions};
@@ -29,12 +31,13 @@ use crate::{store::ECStore, store_api::ListObjectsV2Info};
 use futures::future::join_all;
 use rand::seq::SliceRandom;
 use rustfs_filemeta::{
-    FileInfo, MetaCacheEntries, MetaCacheEntriesSorted, MetaCacheEntriesSortedResult, MetaCacheEntry, MetadataResolutionParams,
-    merge_file_meta_versions,
+    FileInfo, FileInfoVersions, MetaCacheEntries, MetaCacheEntriesSorted, MetaCacheEntriesSortedResult, MetaCacheEntry,
+    MetadataResolutionParams, merge_file_meta_versions,
 };
 use rustfs_utils::path::{self, SLASH_SEPARATOR, base_dir_from_prefix};
 use std::collections::HashMap;
 use std::sync::Arc;
+use time::OffsetDateTime;
 use tokio::sync::broadcast::{self, Receiver as B_Receiver};
 use tokio::sync::mpsc::{self, Receiver, Sender};
 use tracing::{error, info};
@@ -47,6 +50,99 @@ const MAX_OBJECT_LIST: i32 = 1000;
 
 const METACACHE_SHARE_PREFIX: bool = false;
 
+const NULL_VERSION_ID: &str = "null";
+
+/// Position inside the versions of `key` that a ListObjectVersions page resumes after.
+///
+/// Next version markers are handed out as `<version id>[mt:<mod time nanos>]` so that a page can
+/// still resume at the following version when the marker version was deleted in between. Plain
+/// version ids (and `null`) are accepted as well.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionMarker {
+    pub key: String,
+    pub version_id: Option<Uuid>,
+    pub mod_time: Option<OffsetDateTime>,
+}
+
+impl VersionMarker {
+    pub fn parse(key: &str, marker: &str) -> Result<Self> {
+        let (id, tags) = match marker.split_once('[') {
+            Some((id, tags)) => (id, tags.trim_end_matches(']')),
+            None => (marker, ""),
+        };
+
+        let version_id = if id == NULL_VERSION_ID {
+            None
+        } else {
+            Some(Uuid::parse_str(id)?).filter(|v| !v.is_nil())
+        };
+
+        let mut mod_time = None;
+        for tag in tags.split(',') {
+            if let Some(("mt", nanos)) = tag.split_once(':') {
+                let nanos = nanos
+                    .parse::<i128>()
+                    .map_err(|_| Error::other(format!("invalid version marker {marker}")))?;
+                mod_time = Some(
+                    OffsetDateTime::from_unix_timestamp_nanos(nanos)
+                        .map_err(|_| Error::other(format!("invalid version marker {marker}")))?,
+                );
+            }
+        }
+
+        Ok(Self {
+            key: key.to_owned(),
+            version_id,
+            mod_time,
+        })
+    }
+
+    pub fn encode(obj: &ObjectInfo) -> String {
+        let id = obj
+            .version_id
+            .filter(|v| !v.is_nil())
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| NULL_VERSION_ID.to_owned());
+        match obj.mod_time {
+            Some(mod_time) => format!("{id}[mt:{}]", mod_time.unix_timestamp_nanos()),
+            None => id,
+        }
+    }
+
+    /// Returns the versions of the marker key that come after the marker, newest first.
+    pub fn skip_versions<'a>(&self, versions: &'a [FileInfo]) -> &'a [FileInfo] {
+        if let Some(idx) = versions
+            .iter()
+            .position(|v| v.version_id.filter(|v| !v.is_nil()) == self.version_id)
+        {
+            return &versions[idx + 1..];
+        }
+
+        // The marker version is gone, resume at the first version older than it.
+        match self.mod_time {
+            Some(mod_time) => {
+                let idx = versions
+                    .iter()
+                    .position(|v| v.mod_time.is_none_or(|t| t < mod_time))
+                    .unwrap_or(versions.len());
+                &versions[idx..]
+            }
+            None => versions,
+        }
+    }
+}
+
+/// The number of keys a `list_objects_v2` page actually returns for the requested `max_keys`.
+///
+/// Like S3 a missing, zero or negative value lists a full page, anything over the ceiling is capped.
+pub fn clamp_max_keys(max_keys: i32, ceiling: i32) -> i32 {
+    let ceiling = ceiling.max(1);
+    if max_keys <= 0 {
+        return DEFAULT_MAX_LIST_KEYS.min(ceiling);
+    }
+    max_keys.min(ceiling)
+}
+
 pub fn max_keys_plus_one(max_keys: i32, add_one: bool) -> i32 {
     let mut max_keys = max_keys;
     if !(0..=MAX_OBJECT_LIST).contains(&max_keys) {
@@ -220,9 +316,10 @@ impl ECStore {
         continuation_token: Option<String>,
         delimiter: Option<String>,
         max_keys: i32,
-        _fetch_owner: bool,
+        fetch_owner: bool,
         start_after: Option<String>,
     ) -> Result<ListObjectsV2Info> {
+        let max_keys = clamp_max_keys(max_keys, get_global_max_list_keys());
         let marker = {
             if continuation_token.is_none() {
                 start_after
@@ -231,11 +328,18 @@ impl ECStore {
             }
         };
 
-        let loi = self.list_objects_generic(bucket, prefix, marker, delimiter, max_keys).await?;
+        let mut loi = self.list_objects_generic(bucket, prefix, marker, delimiter, max_keys).await?;
+        if fetch_owner {
+            for oi in loi.objects.iter_mut() {
+                oi.owner = Some(oi.stored_owner());
+            }
+        }
+
         Ok(ListObjectsV2Info {
             is_truncated: loi.is_truncated,
             continuation_token,
             next_continuation_token: loi.next_marker,
+            max_keys,
             objects: loi.objects,
             prefixes: loi.prefixes,
         })
@@ -379,10 +483,9 @@ impl ECStore {
             return Err(StorageError::NotImplemented);
         }
 
-        let version_marker = if let Some(marker) = version_marker {
-            Some(Uuid::parse_str(&marker)?)
-        } else {
-            None
+        let version_marker = match (&marker, version_marker) {
+            (Some(key), Some(version_marker)) => Some(VersionMarker::parse(key, &version_marker)?),
+            _ => None,
         };
 
         // if marker set, limit +1
@@ -412,8 +515,13 @@ impl ECStore {
             }
         }
 
+        // With a version marker the marker key may still have versions left to list.
         if let Some(result) = list_result.entries.as_mut() {
-            result.forward_past(opts.marker);
+            if version_marker.is_some() {
+                result.forward_to(opts.marker);
+            } else {
+                result.forward_past(opts.marker);
+            }
         }
 
         let mut get_objects = ObjectInfo::from_meta_cache_entries_sorted_versions(
@@ -421,7 +529,7 @@ impl ECStore {
             bucket,
             prefix,
             delimiter.clone(),
-            version_marker,
+            version_marker.as_ref(),
         )
         .await;
 
@@ -438,7 +546,7 @@ impl ECStore {
             if is_truncated {
                 get_objects
                     .last()
-                    .map(|last| (Some(last.name.clone()), last.version_id.map(|v| v.to_string())))
+                    .map(|last| (Some(last.name.clone()), (!last.is_dir).then(|| VersionMarker::encode(last))))
                     .unwrap_or_default()
             } else {
                 (None, None)
@@ -1319,7 +1427,40 @@ impl SetDisks {
             },
         )
         .await
-        .map_err(Error::other)
+        .map_err(Error::from)
+    }
+
+    /// Collects every object under `prefix` in `bucket` on this set, with all of its versions.
+    pub(crate) async fn walk_versions(&self, bucket: &str, prefix: &str) -> Result<Vec<FileInfoVersions>> {
+        let mut opts = ListPathOptions {
+            bucket: bucket.to_owned(),
+            prefix: prefix.to_owned(),
+            base_dir: base_dir_from_prefix(prefix),
+            recursive: true,
+            ..Default::default()
+        };
+        opts.set_filter();
+
+        let (_cancel_tx, cancel_rx) = broadcast::channel(1);
+        let (sender, mut recv) = mpsc::channel::<MetaCacheEntry>(100);
+        let collect = async move {
+            let mut objects = Vec::new();
+            while let Some(entry) = recv.recv().await {
+                if entry.is_object() && entry.name.starts_with(prefix) {
+                    objects.push(entry.file_info_versions(bucket)?);
+                }
+            }
+            Ok::<_, Error>(objects)
+        };
+
+        let (listed, objects) = tokio::join!(self.list_path(cancel_rx, opts, sender), collect);
+        match listed {
+            // nothing was ever written under the prefix on this set
+            Err(err) if is_err_object_not_found(&err) => {}
+            Err(err) => return Err(err),
+            Ok(()) => {}
+        }
+        objects
     }
 }
 
@@ -1384,6 +1525,24 @@ fn calc_common_counter(infos: &[DiskInfo], read_quorum: usize) -> u64 {
 
 #[cfg(test)]
 mod test {
+    use super::*;
+
+    #[test]
+    fn test_clamp_max_keys() {
+        // missing, zero or negative lists a full page
+        assert_eq!(clamp_max_keys(-5, 1000), 1000);
+        assert_eq!(clamp_max_keys(0, 1000), 1000);
+        assert_eq!(clamp_max_keys(250, 1000), 250);
+        assert_eq!(clamp_max_keys(1000, 1000), 1000);
+        assert_eq!(clamp_max_keys(i32::MAX, 1000), 1000);
+
+        // a lower ceiling also bounds the default page
+        assert_eq!(clamp_max_keys(0, 100), 100);
+        assert_eq!(clamp_max_keys(500, 100), 100);
+        assert_eq!(clamp_max_keys(5000, 10_000), 5000);
+        assert_eq!(clamp_max_keys(10, 0), 1);
+    }
+
     // use std::sync::Arc;
 
     // use crate::cache_value::metacache_set::list_path_raw;
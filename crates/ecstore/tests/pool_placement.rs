@@ -0,0 +1,93 @@
+// Copyright 2024 RustFS Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// A store of two pools sets the object layer globals of the process, so it gets a test binary of its own.
+
+use rustfs_ecstore::bucket::metadata_sys::init_bucket_metadata_sys;
+use rustfs_ecstore::endpoints::EndpointServerPools;
+use rustfs_ecstore::global::{set_global_endpoints, update_erasure_type};
+use rustfs_ecstore::store::{ECStore, init_local_disks};
+use rustfs_ecstore::store_api::{MakeBucketOptions, ObjectIO, ObjectOptions, PutObjReader, StorageAPI};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+// The pools whose drives hold `object`.
+fn pools_of(root: &Path, bucket: &str, object: &str) -> Vec<usize> {
+    (0..2)
+        .filter(|idx| root.join(format!("p{}/d1/{bucket}/{object}", idx + 1)).exists())
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_decommissioning_pool_skipped_for_placement() {
+    let root: PathBuf = std::env::temp_dir().join(format!("rustfs-ecstore-placement-{}", std::process::id()));
+    let mut volumes = Vec::new();
+    for pool in 1..=2 {
+        for drive in 1..=4 {
+            std::fs::create_dir_all(root.join(format!("p{pool}/d{drive}"))).unwrap();
+        }
+        volumes.push(root.join(format!("p{pool}/d{{1...4}}")).to_string_lossy().to_string());
+    }
+
+    let address: SocketAddr = "127.0.0.1:9000".parse().unwrap();
+    let (endpoint_pools, setup_type) = EndpointServerPools::from_volumes(&address.to_string(), volumes).unwrap();
+    set_global_endpoints(endpoint_pools.as_ref().clone());
+    update_erasure_type(setup_type).await;
+    init_local_disks(endpoint_pools.clone()).await.unwrap();
+    let store = ECStore::new(address, endpoint_pools).await.unwrap();
+    init_bucket_metadata_sys(store.clone(), Vec::new()).await;
+    assert_eq!(store.pools.len(), 2);
+
+    let bucket = "draining-pool";
+    store.make_bucket(bucket, &MakeBucketOptions::default()).await.unwrap();
+    let mut data = PutObjReader::from_vec(b"before drain".to_vec());
+    store
+        .put_object(bucket, "existing", &mut data, &ObjectOptions::default())
+        .await
+        .unwrap();
+    let drained = pools_of(&root, bucket, "existing")[0];
+    let remaining = 1 - drained;
+
+    store.set_pool_decommissioning(drained, true).await.unwrap();
+    assert!(store.pool_meta.read().await.is_suspended(drained));
+
+    // new objects only land on the other pool
+    for i in 0..8 {
+        let object = format!("new-{i}");
+        let mut data = PutObjReader::from_vec(object.as_bytes().to_vec());
+        store
+            .put_object(bucket, &object, &mut data, &ObjectOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(pools_of(&root, bucket, &object), vec![remaining]);
+    }
+
+    // while objects already on the pool are still read from it
+    let info = store
+        .get_object_info(bucket, "existing", &ObjectOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(info.size, 12);
+
+    // a new version of one of them lands on the other pool too
+    let mut data = PutObjReader::from_vec(b"after drain".to_vec());
+    store
+        .put_object(bucket, "existing", &mut data, &ObjectOptions::default())
+        .await
+        .unwrap();
+    assert!(pools_of(&root, bucket, "existing").contains(&remaining));
+
+    store.set_pool_decommissioning(drained, false).await.unwrap();
+    assert!(!store.pool_meta.read().await.is_suspended(drained));
+}
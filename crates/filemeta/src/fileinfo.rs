@@ -324,6 +324,10 @@ impl FileInfo {
         self.metadata.insert(RUSTFS_HEALING.to_string(), "true".to_string());
     }
 
+    pub fn healing(&self) -> bool {
+        self.metadata.get(RUSTFS_HEALING).is_some_and(|v| v == "true")
+    }
+
     pub fn set_tier_free_version_id(&mut self, version_id: &str) {
         self.metadata
             .insert(format!("{RESERVED_METADATA_PREFIX_LOWER}{TIER_FV_ID}"), version_id.to_string());
@@ -397,6 +397,7 @@ impl FileMeta {
     }
 
     pub fn update_object_version(&mut self, fi: FileInfo) -> Result<()> {
+        let mut mod_time_changed = false;
         for version in self.versions.iter_mut() {
             match version.header.version_type {
                 VersionType::Invalid | VersionType::Legacy => (),
@@ -415,6 +416,7 @@ impl FileMeta {
                         }
 
                         // Update
+                        mod_time_changed |= version.header.mod_time != ver.header().mod_time;
                         version.header = ver.header();
                         version.meta = ver.marshal_msg()?;
                     }
@@ -427,17 +429,21 @@ impl FileMeta {
             }
         }
 
+        // newest first, the order add_version keeps, metadata only updates leave it alone
+        if !mod_time_changed {
+            return Ok(());
+        }
         self.versions.sort_by(|a, b| {
             if a.header.mod_time != b.header.mod_time {
-                a.header.mod_time.cmp(&b.header.mod_time)
+                b.header.mod_time.cmp(&a.header.mod_time)
             } else if a.header.version_type != b.header.version_type {
-                a.header.version_type.cmp(&b.header.version_type)
+                b.header.version_type.cmp(&a.header.version_type)
             } else if a.header.version_id != b.header.version_id {
-                a.header.version_id.cmp(&b.header.version_id)
+                b.header.version_id.cmp(&a.header.version_id)
             } else if a.header.flags != b.header.flags {
-                a.header.flags.cmp(&b.header.flags)
+                b.header.flags.cmp(&a.header.flags)
             } else {
-                a.cmp(b)
+                b.cmp(a)
             }
         });
         Ok(())
@@ -554,7 +560,13 @@ impl FileMeta {
 
             match ver.header.version_type {
                 VersionType::Invalid | VersionType::Legacy => return Err(Error::other("invalid file meta version")),
-                VersionType::Delete => return Ok(None),
+                VersionType::Delete => {
+                    self.versions.remove(i);
+                    if fi.deleted {
+                        self.add_version_filemata(ventry)?;
+                    }
+                    return Ok(None);
+                }
                 VersionType::Object => {
                     let v = self.get_idx(i)?;
 
@@ -600,6 +612,7 @@ impl FileMeta {
 
         if fi.deleted {
             self.add_version_filemata(ventry)?;
+            return Ok(None);
         }
 
         Err(Error::FileVersionNotFound)
@@ -961,7 +974,8 @@ impl FileMetaVersion {
 
     pub fn get_version_id(&self) -> Option<Uuid> {
         match self.version_type {
-            VersionType::Object | VersionType::Delete => self.object.as_ref().map(|v| v.version_id).unwrap_or_default(),
+            VersionType::Object => self.object.as_ref().map(|v| v.version_id).unwrap_or_default(),
+            VersionType::Delete => self.delete_marker.as_ref().map(|v| v.version_id).unwrap_or_default(),
             _ => None,
         }
     }
@@ -2217,6 +2231,48 @@ mod test {
         assert_eq!(fm, newfm)
     }
 
+    #[test]
+    fn test_update_object_version_keeps_order() {
+        let mut fm = FileMeta::new();
+        let base = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        // the last two share a mod time, their order is only kept, not derived
+        for secs in [0, 10, 10] {
+            let mut fi = FileInfo::new("obj", 3, 2);
+            fi.version_id = Some(Uuid::new_v4());
+            fi.mod_time = Some(base + time::Duration::seconds(secs));
+            fm.add_version(fi).unwrap();
+        }
+        let order = |fm: &FileMeta| fm.versions.iter().map(|v| v.header.version_id).collect::<Vec<_>>();
+        let before = order(&fm);
+
+        for idx in 0..3 {
+            let (_, ver) = fm.find_version(before[idx]).unwrap();
+            let mod_time = ver.get_mod_time();
+
+            // a tagging update carries no mod time
+            let mut tagged = FileInfo::new("obj", 3, 2);
+            tagged.version_id = before[idx];
+            tagged.metadata.insert("x-amz-tagging".to_string(), "k=v".to_string());
+            fm.update_object_version(tagged).unwrap();
+            assert_eq!(order(&fm), before);
+
+            // a metadata update passes the version's own mod time back
+            let mut updated = FileInfo::new("obj", 3, 2);
+            updated.version_id = before[idx];
+            updated.mod_time = mod_time;
+            updated.metadata.insert("x-amz-meta-k".to_string(), "v".to_string());
+            fm.update_object_version(updated).unwrap();
+            assert_eq!(order(&fm), before);
+        }
+
+        // a new mod time moves the version to its place
+        let mut touched = FileInfo::new("obj", 3, 2);
+        touched.version_id = before[2];
+        touched.mod_time = Some(base + time::Duration::seconds(20));
+        fm.update_object_version(touched).unwrap();
+        assert_eq!(order(&fm)[0], before[2]);
+    }
+
     #[test]
     fn test_marshal_metaobject() {
         let obj = MetaObject {
@@ -3013,6 +3069,40 @@ mod test {
             assert_eq!(obj2.meta_user.get(key), Some(&expected_value.to_string()));
         }
     }
+
+    #[test]
+    fn test_delete_version_markers() {
+        let mut fm = FileMeta::new();
+        let mut fi = FileInfo::new("obj", 3, 2);
+        fi.version_id = Some(Uuid::new_v4());
+        fi.mod_time = Some(OffsetDateTime::now_utc());
+        fm.add_version(fi.clone()).unwrap();
+
+        // A new marker is recorded under its own version id.
+        let marker = FileInfo {
+            name: "obj".to_string(),
+            version_id: Some(Uuid::new_v4()),
+            mod_time: Some(OffsetDateTime::now_utc()),
+            deleted: true,
+            ..Default::default()
+        };
+        assert_eq!(fm.delete_version(&marker).unwrap(), None);
+        assert_eq!(fm.versions.len(), 2);
+        let marker_id = marker.version_id.unwrap().to_string();
+        let got = fm.into_fileinfo("bucket", "obj", &marker_id, false, false).unwrap();
+        assert!(got.deleted);
+        assert_eq!(got.version_id, marker.version_id);
+
+        // Deleting the marker by version id removes it and leaves the object.
+        let remove = FileInfo {
+            name: "obj".to_string(),
+            version_id: marker.version_id,
+            ..Default::default()
+        };
+        assert_eq!(fm.delete_version(&remove).unwrap(), None);
+        assert_eq!(fm.versions.len(), 1);
+        assert_eq!(fm.versions[0].header.version_id, fi.version_id);
+    }
 }
 
 #[tokio::test]
@@ -442,13 +442,29 @@ impl MetaCacheEntriesSorted {
         entries
     }
 
+    /// Drop every entry up to and including `marker`.
     pub fn forward_past(&mut self, marker: Option<String>) {
         if let Some(val) = marker {
-            if let Some(idx) = self.o.0.iter().flatten().position(|v| v.name > val) {
-                self.o.0 = self.o.0.split_off(idx);
-            }
+            self.skip_while(|name| name <= val.as_str());
         }
     }
+
+    /// Drop every entry sorting before `marker`, keeping the marker entry itself.
+    pub fn forward_to(&mut self, marker: Option<String>) {
+        if let Some(val) = marker {
+            self.skip_while(|name| name < val.as_str());
+        }
+    }
+
+    fn skip_while(&mut self, skip: impl Fn(&str) -> bool) {
+        let idx = self
+            .o
+            .0
+            .iter()
+            .position(|v| v.as_ref().is_some_and(|v| !skip(&v.name)))
+            .unwrap_or(self.o.0.len());
+        self.o.0.drain(..idx);
+    }
 }
 
 const METACACHE_STREAM_VERSION: u8 = 2;
@@ -885,4 +901,39 @@ mod tests {
 
         assert_eq!(objs, nobjs);
     }
+
+    #[test]
+    fn test_forward_past_and_to() {
+        let sorted = || MetaCacheEntriesSorted {
+            o: MetaCacheEntries(
+                ["a", "b", "c"]
+                    .iter()
+                    .map(|name| {
+                        Some(MetaCacheEntry {
+                            name: name.to_string(),
+                            ..Default::default()
+                        })
+                    })
+                    .collect(),
+            ),
+            ..Default::default()
+        };
+        let names = |s: &MetaCacheEntriesSorted| s.entries().iter().map(|e| e.name.clone()).collect::<Vec<_>>();
+
+        let mut s = sorted();
+        s.forward_past(Some("b".to_string()));
+        assert_eq!(names(&s), ["c"]);
+
+        let mut s = sorted();
+        s.forward_to(Some("b".to_string()));
+        assert_eq!(names(&s), ["b", "c"]);
+
+        let mut s = sorted();
+        s.forward_past(Some("c".to_string()));
+        assert!(names(&s).is_empty());
+
+        let mut s = sorted();
+        s.forward_to(None);
+        assert_eq!(names(&s), ["a", "b", "c"]);
+    }
 }
@@ -182,6 +182,7 @@ impl DRWMutex {
             owner: self.owner.clone(),
             source: source.to_string(),
             quorum,
+            ..Default::default()
         };
 
         for (index, locker) in self.lockers.iter_mut().enumerate() {
@@ -35,6 +35,7 @@ struct LockRequesterInfo {
     owner: String,
     quorum: usize,
     idx: usize,
+    lease: Option<Duration>,
 }
 
 impl Default for LockRequesterInfo {
@@ -50,6 +51,7 @@ impl Default for LockRequesterInfo {
             owner: Default::default(),
             quorum: Default::default(),
             idx: Default::default(),
+            lease: Default::default(),
         }
     }
 }
@@ -110,10 +112,18 @@ impl LocalLocker {
         lock_copy
     }
 
-    fn expire_old_locks(&mut self, interval: Duration) {
-        self.lock_map.iter_mut().for_each(|(_, lris)| {
+    // Drops locks on the resources whose lease ran out, their holder stopped refreshing them.
+    fn expire_old_locks(&mut self, resources: &[String]) {
+        let now = Instant::now();
+        for resource in resources {
+            let Some(lris) = self.lock_map.get_mut(resource) else {
+                continue;
+            };
             lris.retain(|lri| {
-                if Instant::now().duration_since(lri.time_last_refresh) > interval {
+                if lri
+                    .lease
+                    .is_some_and(|lease| now.duration_since(lri.time_last_refresh) > lease)
+                {
                     let mut key = lri.uid.to_string();
                     format_uuid(&mut key, &lri.idx);
                     self.lock_uid.remove(&key);
@@ -122,7 +132,10 @@ impl LocalLocker {
 
                 true
             });
-        });
+            if lris.is_empty() {
+                self.lock_map.remove(resource);
+            }
+        }
     }
 }
 
@@ -135,6 +148,7 @@ impl Locker for LocalLocker {
             )));
         }
 
+        self.expire_old_locks(&args.resources);
         if !self.can_take_lock(&args.resources) {
             return Ok(false);
         }
@@ -151,6 +165,7 @@ impl Locker for LocalLocker {
                     group: args.resources.len() > 1,
                     quorum: args.quorum,
                     idx,
+                    lease: args.lease(),
                     ..Default::default()
                 }],
             );
@@ -213,6 +228,7 @@ impl Locker for LocalLocker {
         }
 
         let resource = &args.resources[0];
+        self.expire_old_locks(&args.resources);
         match self.lock_map.get_mut(resource) {
             Some(lri) => {
                 if !is_write_lock(lri) {
@@ -223,6 +239,7 @@ impl Locker for LocalLocker {
                         owner: args.owner.to_string(),
                         uid: args.uid.to_string(),
                         quorum: args.quorum,
+                        lease: args.lease(),
                         ..Default::default()
                     });
                 } else {
@@ -239,6 +256,7 @@ impl Locker for LocalLocker {
                         owner: args.owner.to_string(),
                         uid: args.uid.to_string(),
                         quorum: args.quorum,
+                        lease: args.lease(),
                         ..Default::default()
                     }],
                 );
@@ -296,7 +314,12 @@ impl Locker for LocalLocker {
                 let mut resource = resource;
                 loop {
                     match self.lock_map.get_mut(resource) {
-                        Some(_lris) => {}
+                        Some(lris) => {
+                            let now = Instant::now();
+                            lris.iter_mut()
+                                .filter(|lri| lri.uid == args.uid)
+                                .for_each(|lri| lri.time_last_refresh = now);
+                        }
                         None => {
                             let mut key = args.uid.to_string();
                             format_uuid(&mut key, &0);
@@ -414,6 +437,7 @@ mod test {
             owner: "dd".to_string(),
             source: "".to_string(),
             quorum: 3,
+            ..Default::default()
         };
         local_locker.lock(&args).await?;
 
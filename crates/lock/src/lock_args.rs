@@ -14,6 +14,7 @@
 
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
+use std::time::Duration;
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct LockArgs {
@@ -22,6 +23,15 @@ pub struct LockArgs {
     pub owner: String,
     pub source: String,
     pub quorum: usize,
+    // Milliseconds the lock stays granted without a refresh, 0 holds it until unlocked.
+    #[serde(default)]
+    pub lease_ms: u64,
+}
+
+impl LockArgs {
+    pub fn lease(&self) -> Option<Duration> {
+        (self.lease_ms > 0).then(|| Duration::from_millis(self.lease_ms))
+    }
 }
 
 impl Display for LockArgs {
@@ -13,13 +13,18 @@
 // limitations under the License.
 
 use async_trait::async_trait;
+use futures::future::join_all;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::{collections::HashMap, path::Path, sync::Arc, time::Duration};
-use tokio::sync::RwLock;
+use tokio::sync::{OwnedRwLockReadGuard, OwnedRwLockWriteGuard, RwLock};
+use tokio::task::JoinHandle;
+use tracing::warn;
 use uuid::Uuid;
 
 use crate::{
-    LockApi,
+    LockApi, Locker,
     drwmutex::{DRWMutex, Options},
+    lock_args::LockArgs,
     lrwmutex::LRWMutex,
 };
 use std::io::Result;
@@ -267,6 +272,333 @@ impl RWLocker for LocalLockInstance {
     }
 }
 
+/// Locks namespace resources, usually `bucket/object` paths, for the lifetime of the returned guard.
+#[async_trait]
+pub trait NamespaceLock: std::fmt::Debug + Send + Sync {
+    /// Takes exclusive locks on all resources, failing with `TimedOut` if they are not all held in time.
+    async fn lock(&self, resources: &[String], timeout: Duration) -> Result<LockGuard>;
+    /// Takes shared locks on all resources, concurrent readers do not wait for each other.
+    async fn rlock(&self, resources: &[String], timeout: Duration) -> Result<LockGuard>;
+}
+
+type ResourceMap = Arc<std::sync::Mutex<HashMap<String, Arc<RwLock<()>>>>>;
+
+enum ResourceGuard {
+    Read(OwnedRwLockReadGuard<()>),
+    Write(OwnedRwLockWriteGuard<()>),
+}
+
+/// Holds the locks taken through a [`NamespaceLock`], they are released on drop.
+pub struct LockGuard {
+    guards: Vec<ResourceGuard>,
+    resources: Vec<String>,
+    map: Option<ResourceMap>,
+    refresh: Option<JoinHandle<()>>,
+    release: Option<Box<dyn FnOnce() + Send + Sync>>,
+    lost: Arc<AtomicBool>,
+}
+
+impl LockGuard {
+    fn local(resources: Vec<String>, map: ResourceMap) -> Self {
+        Self {
+            guards: Vec::with_capacity(resources.len()),
+            resources,
+            map: Some(map),
+            refresh: None,
+            release: None,
+            lost: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Reports whether the lock expired under its holder, writes done under it are no longer protected.
+    pub fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for LockGuard {
+    fn drop(&mut self) {
+        self.guards.clear();
+        if let Some(refresh) = self.refresh.take() {
+            refresh.abort();
+        }
+        if let Some(release) = self.release.take() {
+            release();
+        }
+
+        // forget resources nobody else holds or waits on
+        if let Some(map) = &self.map {
+            let mut map = map.lock().unwrap();
+            for resource in self.resources.iter() {
+                if map.get(resource).is_some_and(|l| Arc::strong_count(l) == 1) {
+                    map.remove(resource);
+                }
+            }
+        }
+    }
+}
+
+/// In-process [`NamespaceLock`], enough for a single node deployment.
+#[derive(Debug, Default, Clone)]
+pub struct LocalNamespaceLock {
+    map: ResourceMap,
+}
+
+impl LocalNamespaceLock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    async fn acquire(&self, resources: &[String], timeout: Duration, read_lock: bool) -> Result<LockGuard> {
+        // a fixed order keeps two callers locking overlapping sets from deadlocking
+        let mut resources = resources.to_vec();
+        resources.sort();
+        resources.dedup();
+
+        let locks: Vec<Arc<RwLock<()>>> = {
+            let mut map = self.map.lock().unwrap();
+            resources.iter().map(|r| map.entry(r.clone()).or_default().clone()).collect()
+        };
+
+        let mut guard = LockGuard::local(resources, self.map.clone());
+
+        let acquire_all = async {
+            for lock in locks {
+                guard.guards.push(if read_lock {
+                    ResourceGuard::Read(lock.read_owned().await)
+                } else {
+                    ResourceGuard::Write(lock.write_owned().await)
+                });
+            }
+        };
+
+        if tokio::time::timeout(timeout, acquire_all).await.is_err() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("lock on {:?} not acquired within {timeout:?}", guard.resources),
+            ));
+        }
+
+        Ok(guard)
+    }
+}
+
+#[async_trait]
+impl NamespaceLock for LocalNamespaceLock {
+    async fn lock(&self, resources: &[String], timeout: Duration) -> Result<LockGuard> {
+        self.acquire(resources, timeout, false).await
+    }
+
+    async fn rlock(&self, resources: &[String], timeout: Duration) -> Result<LockGuard> {
+        self.acquire(resources, timeout, true).await
+    }
+}
+
+// How long lock servers keep a distributed lock its holder stopped refreshing.
+const DIST_LOCK_LEASE: Duration = Duration::from_secs(30);
+const DIST_LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// [`NamespaceLock`] granted by a majority of the lock servers of a cluster, so two nodes
+/// can not both hold a write lock on the same object.
+///
+/// The guard refreshes the lock for as long as it lives. Lock servers drop locks whose lease ran
+/// out, which frees the resources of a node that died while holding them.
+#[derive(Debug)]
+pub struct DistNamespaceLock<L = LockApi> {
+    owner: String,
+    lockers: Vec<L>,
+    lease: Duration,
+}
+
+impl<L: Locker + Clone + Send + Sync + 'static> DistNamespaceLock<L> {
+    pub fn new(owner: String, lockers: Vec<L>) -> Self {
+        Self {
+            owner,
+            lockers,
+            lease: DIST_LOCK_LEASE,
+        }
+    }
+
+    pub fn with_lease(mut self, lease: Duration) -> Self {
+        self.lease = lease;
+        self
+    }
+
+    // Any write quorum overlaps every other write quorum and every read quorum.
+    fn quorum(&self, read_lock: bool) -> usize {
+        let n = self.lockers.len();
+        if read_lock { n - n / 2 } else { n / 2 + 1 }
+    }
+
+    /// Takes a write lock on the resources on a majority of the lock servers.
+    pub async fn dist_lock(&self, resources: &[String], timeout: Duration) -> Result<LockGuard> {
+        self.acquire(resources, timeout, false).await
+    }
+
+    async fn acquire(&self, resources: &[String], timeout: Duration, read_lock: bool) -> Result<LockGuard> {
+        if self.lockers.is_empty() {
+            return Err(std::io::Error::other("no lock servers to take the lock on"));
+        }
+
+        let mut resources = resources.to_vec();
+        resources.sort();
+        resources.dedup();
+
+        let uid = Uuid::new_v4().to_string();
+        let quorum = self.quorum(read_lock);
+        let base = LockArgs {
+            uid: uid.clone(),
+            owner: self.owner.clone(),
+            quorum,
+            lease_ms: self.lease.as_millis() as u64,
+            ..Default::default()
+        };
+        // lock servers only read lock one resource per request
+        let requests: Vec<LockArgs> = if read_lock {
+            resources
+                .iter()
+                .enumerate()
+                .map(|(idx, resource)| LockArgs {
+                    uid: format!("{uid}:{idx}"),
+                    resources: vec![resource.clone()],
+                    ..base.clone()
+                })
+                .collect()
+        } else {
+            vec![LockArgs {
+                resources: resources.clone(),
+                ..base
+            }]
+        };
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let results = join_all(
+                self.lockers
+                    .iter()
+                    .map(|locker| lock_on(locker.clone(), &requests, read_lock)),
+            )
+            .await;
+            let granted: Vec<L> = self
+                .lockers
+                .iter()
+                .zip(results)
+                .filter(|(_, locked)| *locked)
+                .map(|(locker, _)| locker.clone())
+                .collect();
+
+            if granted.len() >= quorum {
+                let lost = Arc::new(AtomicBool::new(false));
+                let refresh =
+                    tokio::spawn(refresh_dist_lock(granted.clone(), requests.clone(), quorum, self.lease, lost.clone()));
+                return Ok(LockGuard {
+                    guards: Vec::new(),
+                    resources,
+                    map: None,
+                    refresh: Some(refresh),
+                    release: Some(Box::new(move || {
+                        tokio::spawn(async move { release_on(&granted, &requests, read_lock).await });
+                    })),
+                    lost,
+                });
+            }
+
+            release_on(&granted, &requests, read_lock).await;
+            if tokio::time::Instant::now() + DIST_LOCK_RETRY_INTERVAL > deadline {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    format!("lock on {resources:?} not granted by {quorum} lock servers within {timeout:?}"),
+                ));
+            }
+            tokio::time::sleep(DIST_LOCK_RETRY_INTERVAL).await;
+        }
+    }
+}
+
+#[async_trait]
+impl<L: Locker + Clone + std::fmt::Debug + Send + Sync + 'static> NamespaceLock for DistNamespaceLock<L> {
+    async fn lock(&self, resources: &[String], timeout: Duration) -> Result<LockGuard> {
+        self.dist_lock(resources, timeout).await
+    }
+
+    async fn rlock(&self, resources: &[String], timeout: Duration) -> Result<LockGuard> {
+        self.acquire(resources, timeout, true).await
+    }
+}
+
+// Takes every request on one lock server, or none of them.
+async fn lock_on<L: Locker>(mut locker: L, requests: &[LockArgs], read_lock: bool) -> bool {
+    for (idx, args) in requests.iter().enumerate() {
+        let locked = if read_lock {
+            locker.rlock(args).await
+        } else {
+            locker.lock(args).await
+        };
+        if !locked.unwrap_or_default() {
+            release_requests(&mut locker, &requests[..idx], read_lock).await;
+            return false;
+        }
+    }
+    true
+}
+
+async fn release_on<L: Locker + Clone>(lockers: &[L], requests: &[LockArgs], read_lock: bool) {
+    join_all(
+        lockers
+            .iter()
+            .map(|locker| async move { release_requests(&mut locker.clone(), requests, read_lock).await }),
+    )
+    .await;
+}
+
+async fn release_requests<L: Locker>(locker: &mut L, requests: &[LockArgs], read_lock: bool) {
+    for args in requests {
+        let _ = if read_lock {
+            locker.runlock(args).await
+        } else {
+            locker.unlock(args).await
+        };
+    }
+}
+
+// Keeps the lease alive, flags the lock as lost once fewer than quorum servers still hold it.
+async fn refresh_dist_lock<L: Locker + Clone>(
+    lockers: Vec<L>,
+    requests: Vec<LockArgs>,
+    quorum: usize,
+    lease: Duration,
+    lost: Arc<AtomicBool>,
+) {
+    let mut interval = tokio::time::interval(lease / 3);
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+
+        let refreshed = join_all(lockers.iter().map(|locker| {
+            let mut locker = locker.clone();
+            let requests = &requests;
+            async move {
+                let mut refreshed = true;
+                for args in requests.iter() {
+                    refreshed &= locker.refresh(args).await.unwrap_or_default();
+                }
+                refreshed
+            }
+        }))
+        .await;
+        let held = refreshed.into_iter().filter(|refreshed| *refreshed).count();
+
+        if held < quorum {
+            warn!(
+                "distributed lock on {:?} lost, only {held} of {quorum} lock servers still hold it",
+                requests[0].resources
+            );
+            lost.store(true, Ordering::SeqCst);
+            return;
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::{sync::Arc, time::Duration};
@@ -275,10 +607,145 @@ mod test {
     use tokio::sync::RwLock;
 
     use crate::{
+        Locker,
         drwmutex::Options,
-        namespace_lock::{NsLockMap, new_nslock},
+        local_locker::LocalLocker,
+        lock_args::LockArgs,
+        namespace_lock::{DistNamespaceLock, LocalNamespaceLock, LockGuard, NamespaceLock, NsLockMap, new_nslock},
     };
 
+    // A lock server on another node, backed by the same locker the real servers run.
+    #[derive(Debug, Clone, Default)]
+    struct MockPeer {
+        locker: Arc<tokio::sync::Mutex<LocalLocker>>,
+        offline: Arc<std::sync::atomic::AtomicBool>,
+    }
+
+    impl MockPeer {
+        fn check(&self) -> Result<()> {
+            if self.offline.load(std::sync::atomic::Ordering::SeqCst) {
+                return Err(std::io::Error::other("peer offline"));
+            }
+            Ok(())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Locker for MockPeer {
+        async fn lock(&mut self, args: &LockArgs) -> Result<bool> {
+            self.check()?;
+            self.locker.lock().await.lock(args).await
+        }
+        async fn unlock(&mut self, args: &LockArgs) -> Result<bool> {
+            self.check()?;
+            self.locker.lock().await.unlock(args).await
+        }
+        async fn rlock(&mut self, args: &LockArgs) -> Result<bool> {
+            self.check()?;
+            self.locker.lock().await.rlock(args).await
+        }
+        async fn runlock(&mut self, args: &LockArgs) -> Result<bool> {
+            self.check()?;
+            self.locker.lock().await.runlock(args).await
+        }
+        async fn refresh(&mut self, args: &LockArgs) -> Result<bool> {
+            self.check()?;
+            self.locker.lock().await.refresh(args).await
+        }
+        async fn force_unlock(&mut self, args: &LockArgs) -> Result<bool> {
+            self.check()?;
+            self.locker.lock().await.force_unlock(args).await
+        }
+        async fn close(&self) {}
+        async fn is_online(&self) -> bool {
+            self.check().is_ok()
+        }
+        async fn is_local(&self) -> bool {
+            false
+        }
+    }
+
+    // The holder goes away without releasing or refreshing its lock.
+    fn crash(mut guard: LockGuard) {
+        guard.refresh.take().unwrap().abort();
+        guard.release = None;
+    }
+
+    #[tokio::test]
+    async fn test_dist_namespace_lock() -> Result<()> {
+        let peers: Vec<MockPeer> = (0..3).map(|_| MockPeer::default()).collect();
+        let lease = Duration::from_millis(300);
+        let node1 = DistNamespaceLock::new("node1".to_string(), peers.clone()).with_lease(lease);
+        let node2 = DistNamespaceLock::new("node2".to_string(), peers.clone()).with_lease(lease);
+        let key = vec!["bucket/object".to_string()];
+
+        // contention, the second node waits for the first
+        let guard = node1.dist_lock(&key, Duration::from_secs(1)).await?;
+        let err = node2.dist_lock(&key, Duration::from_millis(200)).await.err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+        assert!(node2.rlock(&key, Duration::from_millis(200)).await.is_err());
+
+        // a held lock survives past its lease because the guard refreshes it
+        tokio::time::sleep(lease * 2).await;
+        assert!(!guard.is_lost());
+        assert!(node2.dist_lock(&key, Duration::from_millis(200)).await.is_err());
+        drop(guard);
+        let guard = node2.dist_lock(&key, Duration::from_secs(2)).await?;
+        drop(guard);
+
+        // a crashed holder blocks the object until its lease runs out on the peers
+        crash(node1.dist_lock(&key, Duration::from_secs(1)).await?);
+        assert!(node2.dist_lock(&key, Duration::from_millis(100)).await.is_err());
+        let guard = node2.dist_lock(&key, Duration::from_secs(2)).await?;
+
+        // one peer down still leaves a majority, two down loses the lock
+        peers[0].offline.store(true, std::sync::atomic::Ordering::SeqCst);
+        tokio::time::sleep(lease).await;
+        assert!(!guard.is_lost());
+        peers[1].offline.store(true, std::sync::atomic::Ordering::SeqCst);
+        tokio::time::sleep(lease).await;
+        assert!(guard.is_lost());
+        assert!(node1.dist_lock(&key, Duration::from_millis(200)).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_local_namespace_lock() -> Result<()> {
+        let ns = LocalNamespaceLock::new();
+        let key = vec!["bucket/object".to_string()];
+        let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        // the second writer only gets in once the first one dropped its guard
+        let first = ns.lock(&key, Duration::from_secs(1)).await?;
+        let second = {
+            let (ns, key, order) = (ns.clone(), key.clone(), order.clone());
+            tokio::spawn(async move {
+                let _guard = ns.lock(&key, Duration::from_secs(5)).await.unwrap();
+                order.lock().unwrap().push("second");
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        order.lock().unwrap().push("first");
+        drop(first);
+        second.await.unwrap();
+        assert_eq!(*order.lock().unwrap(), vec!["first", "second"]);
+
+        // a reader conflicts with a writer, readers share
+        let writer = ns.lock(&key, Duration::from_secs(1)).await?;
+        let err = ns.rlock(&key, Duration::from_millis(50)).await.err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+        drop(writer);
+
+        let reader = ns.rlock(&key, Duration::from_secs(1)).await?;
+        let _other = ns.rlock(&key, Duration::from_secs(1)).await?;
+        assert!(ns.lock(&key, Duration::from_millis(50)).await.is_err());
+        drop(reader);
+
+        // other keys are not affected
+        ns.lock(&["bucket/other".to_string()], Duration::from_millis(50)).await?;
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_local_instance() -> Result<()> {
         let ns_lock_map = Arc::new(RwLock::new(NsLockMap::default()));
@@ -187,3 +187,57 @@ impl RulesMap {
         self.total_events_mask |= event_name.mask(); // Update only the relevant bitmask
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::pattern::new_pattern;
+
+    fn target(name: &str) -> TargetID {
+        TargetID::new("1".to_string(), name.to_string())
+    }
+
+    #[test]
+    fn test_match_rules_suffix_only() {
+        let mut rules = RulesMap::new();
+        rules.add_rule_config(&[EventName::ObjectCreatedPut], new_pattern(None, Some(".jpg")), target("jpg"));
+
+        assert_eq!(
+            rules.match_rules(EventName::ObjectCreatedPut, "a/b/photo.jpg"),
+            TargetIdSet::from([target("jpg")])
+        );
+        assert!(rules.match_rules(EventName::ObjectCreatedPut, "a/b/photo.png").is_empty());
+        assert!(rules.match_rules(EventName::ObjectRemovedDelete, "a/b/photo.jpg").is_empty());
+    }
+
+    #[test]
+    fn test_match_rules_prefix_and_suffix() {
+        let mut rules = RulesMap::new();
+        rules.add_rule_config(&[EventName::ObjectCreatedPut], new_pattern(Some("logs/"), Some(".gz")), target("logs"));
+
+        assert_eq!(
+            rules.match_rules(EventName::ObjectCreatedPut, "logs/app.gz"),
+            TargetIdSet::from([target("logs")])
+        );
+        assert!(rules.match_rules(EventName::ObjectCreatedPut, "logs/app.txt").is_empty());
+        assert!(rules.match_rules(EventName::ObjectCreatedPut, "data/app.gz").is_empty());
+    }
+
+    #[test]
+    fn test_match_rules_wildcard_and_overlap() {
+        let mut rules = RulesMap::new();
+        rules.add_rule_config(&[EventName::ObjectCreatedAll], String::new(), target("created"));
+        rules.add_rule_config(&[EventName::ObjectCreatedPut], new_pattern(Some("images/"), None), target("images"));
+
+        // s3:ObjectCreated:* covers every creation event, overlapping rules each add their target
+        assert_eq!(
+            rules.match_rules(EventName::ObjectCreatedPut, "images/cat.png"),
+            TargetIdSet::from([target("created"), target("images")])
+        );
+        assert_eq!(
+            rules.match_rules(EventName::ObjectCreatedCopy, "images/cat.png"),
+            TargetIdSet::from([target("created")])
+        );
+        assert!(rules.match_rules(EventName::ObjectRemovedDelete, "images/cat.png").is_empty());
+    }
+}
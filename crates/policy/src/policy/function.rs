@@ -172,9 +172,6 @@ impl PartialEq for Functions {
     }
 }
 
-#[derive(Clone, Serialize, Deserialize)]
-pub struct Value;
-
 #[cfg(test)]
 mod tests {
     use crate::policy::Functions;
@@ -28,6 +28,11 @@ impl AddrFunc {
                 let Ok(ip) = r.parse::<IpAddr>() else {
                     return false;
                 };
+                // an IPv4 client seen through a dual-stack listener
+                let ip = match ip {
+                    IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+                    ip => ip,
+                };
 
                 for ip_net in inner.values.0.iter() {
                     if ip_net.contains(ip) {
@@ -12,11 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use super::function::key_name::{AwsKeyName, KeyName, S3KeyName};
 use super::{Effect, Error as IamError, ID, Statement, action::Action, statement::BPStatement};
 use crate::error::{Error, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
 
 /// DEFAULT_VERSION is the default version.
 /// https://docs.aws.amazon.com/IAM/latest/UserGuide/reference_policies_elements_version.html
@@ -178,6 +180,39 @@ pub struct BucketPolicyArgs<'a> {
     pub object: &'a str,
 }
 
+/// Request facts behind the `aws:SourceIp`, `aws:SecureTransport`, `s3:prefix` and
+/// `s3:max-keys` condition keys.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConditionArgs {
+    pub source_ip: Option<IpAddr>,
+    pub secure_transport: bool,
+    /// The `prefix` of a listing request.
+    pub prefix: Option<String>,
+    /// The `max-keys` of a listing request.
+    pub max_keys: Option<i32>,
+}
+
+impl ConditionArgs {
+    /// Adds the condition values for these facts to `conditions`, the values passed to `is_allowed`.
+    ///
+    /// Keys left unset stay absent, so a condition that requires them denies the request.
+    pub fn insert_conditions(&self, conditions: &mut HashMap<String, Vec<String>>) {
+        if let Some(ip) = self.source_ip {
+            conditions.insert(KeyName::Aws(AwsKeyName::AWSSourceIP).name().to_owned(), vec![ip.to_string()]);
+        }
+        conditions.insert(
+            KeyName::Aws(AwsKeyName::AWSSecureTransport).name().to_owned(),
+            vec![self.secure_transport.to_string()],
+        );
+        if let Some(prefix) = &self.prefix {
+            conditions.insert(KeyName::S3(S3KeyName::S3Prefix).name().to_owned(), vec![prefix.clone()]);
+        }
+        if let Some(max_keys) = self.max_keys {
+            conditions.insert(KeyName::S3(S3KeyName::S3MaxKeys).name().to_owned(), vec![max_keys.to_string()]);
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Default, Debug)]
 pub struct BucketPolicy {
     #[serde(default, rename = "ID")]
@@ -531,4 +566,116 @@ mod test {
         // assert_eq!(p, p2);
         Ok(())
     }
+
+    fn bucket_policy_allowed(policy: &BucketPolicy, action: &str, object: &str, condition_args: &ConditionArgs) -> bool {
+        let mut conditions = HashMap::new();
+        condition_args.insert_conditions(&mut conditions);
+        policy.is_allowed(&BucketPolicyArgs {
+            account: "",
+            groups: &None,
+            action: Action::try_from(action).unwrap(),
+            bucket: "office",
+            conditions: &conditions,
+            is_owner: false,
+            object,
+        })
+    }
+
+    #[test]
+    fn test_bucket_policy_source_ip() {
+        let data = r#"
+{
+  "Version": "2012-10-17",
+  "Statement": [
+    {
+      "Effect": "Allow",
+      "Principal": {"AWS": ["*"]},
+      "Action": ["s3:GetObject"],
+      "Resource": ["arn:aws:s3:::office/*"],
+      "Condition": {
+        "IpAddress": {"aws:SourceIp": ["192.168.10.0/24", "2001:db8::/32"]}
+      }
+    }
+  ]
+}
+"#;
+        let policy: BucketPolicy = serde_json::from_str(data).unwrap();
+
+        let from = |ip: &str| ConditionArgs {
+            source_ip: Some(ip.parse().unwrap()),
+            ..Default::default()
+        };
+        assert!(bucket_policy_allowed(&policy, "s3:GetObject", "report.pdf", &from("192.168.10.25")));
+        assert!(bucket_policy_allowed(
+            &policy,
+            "s3:GetObject",
+            "report.pdf",
+            &from("::ffff:192.168.10.25")
+        ));
+        assert!(bucket_policy_allowed(&policy, "s3:GetObject", "report.pdf", &from("2001:db8::1")));
+        assert!(!bucket_policy_allowed(&policy, "s3:GetObject", "report.pdf", &from("192.168.11.25")));
+        assert!(!bucket_policy_allowed(&policy, "s3:GetObject", "report.pdf", &from("10.0.0.1")));
+        // without a known source address the condition is not met
+        assert!(!bucket_policy_allowed(&policy, "s3:GetObject", "report.pdf", &ConditionArgs::default()));
+    }
+
+    #[test]
+    fn test_bucket_policy_secure_transport_and_listing() {
+        let data = r#"
+{
+  "Version": "2012-10-17",
+  "Statement": [
+    {
+      "Effect": "Allow",
+      "Principal": {"AWS": ["*"]},
+      "Action": ["s3:ListBucket"],
+      "Resource": ["arn:aws:s3:::office"],
+      "Condition": {
+        "StringLike": {"s3:prefix": ["public/*"]},
+        "NumericLessThanEquals": {"s3:max-keys": "100"}
+      }
+    },
+    {
+      "Effect": "Deny",
+      "Principal": {"AWS": ["*"]},
+      "Action": ["s3:*"],
+      "Resource": ["arn:aws:s3:::office", "arn:aws:s3:::office/*"],
+      "Condition": {
+        "Bool": {"aws:SecureTransport": "false"}
+      }
+    }
+  ]
+}
+"#;
+        let policy: BucketPolicy = serde_json::from_str(data).unwrap();
+
+        let listing = ConditionArgs {
+            secure_transport: true,
+            prefix: Some("public/docs/".to_string()),
+            max_keys: Some(50),
+            ..Default::default()
+        };
+        assert!(bucket_policy_allowed(&policy, "s3:ListBucket", "", &listing));
+
+        let plain_http = ConditionArgs {
+            secure_transport: false,
+            ..listing.clone()
+        };
+        assert!(!bucket_policy_allowed(&policy, "s3:ListBucket", "", &plain_http));
+
+        let private = ConditionArgs {
+            prefix: Some("private/".to_string()),
+            ..listing.clone()
+        };
+        assert!(!bucket_policy_allowed(&policy, "s3:ListBucket", "", &private));
+
+        let too_many = ConditionArgs {
+            max_keys: Some(1000),
+            ..listing.clone()
+        };
+        assert!(!bucket_policy_allowed(&policy, "s3:ListBucket", "", &too_many));
+
+        let no_prefix = ConditionArgs { prefix: None, ..listing };
+        assert!(!bucket_policy_allowed(&policy, "s3:ListBucket", "", &no_prefix));
+    }
 }
@@ -27,6 +27,9 @@ use tonic::{
     transport::{Channel, Endpoint},
 };
 
+/// NodeService protocol version advertised in Ping, bump it whenever RPCs are added
+pub const NODE_SERVICE_VERSION: u64 = 4;
+
 // Default 100 MB
 pub const DEFAULT_GRPC_SERVER_MESSAGE_LEN: usize = 100 * 1024 * 1024;
 
@@ -280,13 +280,13 @@ where
                     return Poll::Ready(Err(e));
                 }
             }
-            if *this.header_read < HEADER_LEN {
-                return Poll::Pending;
-            }
         }
         if !*this.header_done && *this.header_read == 0 {
             return Poll::Ready(Ok(()));
         }
+        if !*this.header_done && *this.header_read < HEADER_LEN {
+            return Poll::Ready(Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated block header")));
+        }
         let typ = this.header_buf[0];
         let len = (this.header_buf[1] as usize) | ((this.header_buf[2] as usize) << 8) | ((this.header_buf[3] as usize) << 16);
         let crc = (this.header_buf[4] as u32)
@@ -321,6 +321,9 @@ where
                 }
             }
         }
+        if *this.compressed_read < *this.compressed_len {
+            return Poll::Ready(Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated compressed block")));
+        }
         let (uncompress_len, uvarint) = uvarint(&compressed_buf[0..16]);
         let compressed_data = &compressed_buf[uvarint as usize..];
         let decompressed = if typ == COMPRESS_TYPE_COMPRESSED {
@@ -451,6 +454,36 @@ mod tests {
         assert_eq!(&decompressed, data);
     }
 
+    #[tokio::test]
+    async fn test_decompress_reader_partial_reads() {
+        use tokio::io::AsyncWriteExt;
+
+        let data: Vec<u8> = (0..10_000u32).flat_map(|i| i.to_le_bytes()).collect();
+        let mut compressed = Vec::new();
+        CompressReader::new(WarpReader::new(Cursor::new(data.clone())), CompressionAlgorithm::Zstd)
+            .read_to_end(&mut compressed)
+            .await
+            .unwrap();
+
+        // a tiny pipe splits block headers across reads
+        let (rd, mut wd) = tokio::io::duplex(3);
+        tokio::spawn(async move { wd.write_all(&compressed).await });
+        let mut decompressed = Vec::new();
+        DecompressReader::new(rd, CompressionAlgorithm::Zstd)
+            .read_to_end(&mut decompressed)
+            .await
+            .unwrap();
+        assert_eq!(decompressed, data);
+
+        // a stream cut inside a header is an error, not a hang
+        let mut truncated = Vec::new();
+        let err = DecompressReader::new(Cursor::new(vec![0u8; 3]), CompressionAlgorithm::Zstd)
+            .read_to_end(&mut truncated)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
     #[tokio::test]
     async fn test_compress_reader_basic_deflate() {
         let data = b"hello world, hello world, hello world!";
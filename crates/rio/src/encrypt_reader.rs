@@ -249,24 +249,22 @@ where
                 }
                 Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
             }
+        }
+        if !*this.header_done {
+            if *this.header_read == 0 {
+                return Poll::Ready(Ok(()));
+            }
             if *this.header_read < 8 {
-                return Poll::Pending;
+                return Poll::Ready(Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "truncated block header")));
             }
-        }
-        if !*this.header_done && *this.header_read == 8 {
             *this.header_done = true;
         }
-        if !*this.header_done {
-            return Poll::Pending;
-        }
         let typ = this.header_buf[0];
         let len = (this.header_buf[1] as usize) | ((this.header_buf[2] as usize) << 8) | ((this.header_buf[3] as usize) << 16);
         let crc = (this.header_buf[4] as u32)
             | ((this.header_buf[5] as u32) << 8)
             | ((this.header_buf[6] as u32) << 16)
             | ((this.header_buf[7] as u32) << 24);
-        *this.header_read = 0;
-        *this.header_done = false;
         if typ == 0xFF {
             *this.finished = true;
             return Poll::Ready(Ok(()));
@@ -298,8 +296,11 @@ where
             }
         }
         if *this.ciphertext_read < *this.ciphertext_len {
-            return Poll::Pending;
+            return Poll::Ready(Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "truncated encrypted block")));
         }
+        // the header is kept until its block is complete, a read that was pending resumes the block
+        *this.header_read = 0;
+        *this.header_done = false;
         // Parse uvarint for plaintext length
         let (plaintext_len, uvarint_len) = rustfs_utils::uvarint(&ciphertext_buf[0..16]);
         let ciphertext = &ciphertext_buf[uvarint_len as usize..];
@@ -447,4 +448,35 @@ mod tests {
 
         assert_eq!(&decrypted, &data);
     }
+
+    #[tokio::test]
+    async fn test_decrypt_reader_partial_reads() {
+        use tokio::io::AsyncWriteExt;
+
+        let data: Vec<u8> = (0..100_000u32).flat_map(|i| i.to_le_bytes()).collect();
+        let key = [3u8; 32];
+        let nonce = [5u8; 12];
+        let mut encrypted = Vec::new();
+        EncryptReader::new(WarpReader::new(Cursor::new(data.clone())), key, nonce)
+            .read_to_end(&mut encrypted)
+            .await
+            .unwrap();
+
+        // a tiny pipe splits block headers and blocks across reads
+        let (rd, mut wd) = tokio::io::duplex(5);
+        tokio::spawn(async move { wd.write_all(&encrypted).await });
+        let mut decrypted = Vec::new();
+        DecryptReader::new(WarpReader::new(rd), key, nonce)
+            .read_to_end(&mut decrypted)
+            .await
+            .unwrap();
+        assert_eq!(decrypted, data);
+
+        // a stream cut inside a header is an error, not a hang
+        let err = DecryptReader::new(WarpReader::new(Cursor::new(vec![0u8; 3])), key, nonce)
+            .read_to_end(&mut Vec::new())
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
 }
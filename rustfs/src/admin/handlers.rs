@@ -114,7 +114,7 @@ impl Operation for AccountInfoHandler {
         let claims = cred.claims.as_ref().unwrap_or(&default_claims);
 
         let cred_clone = cred.clone();
-        let conditions = get_condition_values(&req.headers, &cred_clone);
+        let conditions = get_condition_values(&req.uri, &req.headers, &req.extensions, &cred_clone);
         let cred_clone = Arc::new(cred_clone);
         let conditions = Arc::new(conditions);
 
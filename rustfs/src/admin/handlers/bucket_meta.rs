@@ -29,13 +29,14 @@ use rustfs_ecstore::{
     bucket::{
         metadata::{
             BUCKET_LIFECYCLE_CONFIG, BUCKET_NOTIFICATION_CONFIG, BUCKET_POLICY_CONFIG, BUCKET_QUOTA_CONFIG_FILE,
-            BUCKET_REPLICATION_CONFIG, BUCKET_SSECONFIG, BUCKET_TAGGING_CONFIG, BUCKET_TARGETS_FILE, BUCKET_VERSIONING_CONFIG,
-            BucketMetadata, OBJECT_LOCK_CONFIG,
+            BUCKET_REPLICATION_CONFIG, BUCKET_SSECONFIG, BUCKET_TAGGING_CONFIG, BUCKET_TARGETS_FILE, BUCKET_TARGETS_META_FILE,
+            BUCKET_VERSIONING_CONFIG, BucketMetadata, OBJECT_LOCK_CONFIG,
         },
         metadata_sys,
         quota::BucketQuota,
         target::BucketTargets,
     },
+    cmd::bucket_targets::get_target_rtts,
     error::StorageError,
     new_object_layer_fn,
     store_api::BucketOptions,
@@ -118,6 +119,7 @@ impl Operation for ExportBucketMetadata {
             BUCKET_VERSIONING_CONFIG,
             BUCKET_REPLICATION_CONFIG,
             BUCKET_TARGETS_FILE,
+            BUCKET_TARGETS_META_FILE,
         ];
 
         for bucket in buckets {
@@ -328,6 +330,23 @@ impl Operation for ExportBucketMetadata {
                             .write_all(&config_json)
                             .map_err(|e| s3_error!(InternalError, "write file failed: {e}"))?;
                     }
+                    BUCKET_TARGETS_META_FILE => {
+                        // probed target RTTs, exported for admins and not imported back
+                        let rtts = get_target_rtts(&bucket.name).await;
+                        if rtts.is_empty() {
+                            continue;
+                        }
+
+                        let rtts_json =
+                            serde_json::to_vec(&rtts).map_err(|e| s3_error!(InternalError, "serialize config failed: {e}"))?;
+
+                        zip_writer
+                            .start_file(conf_path, SimpleFileOptions::default())
+                            .map_err(|e| s3_error!(InternalError, "start file failed: {e}"))?;
+                        zip_writer
+                            .write_all(&rtts_json)
+                            .map_err(|e| s3_error!(InternalError, "write file failed: {e}"))?;
+                    }
                     _ => {}
                 }
             }
@@ -121,7 +121,7 @@ impl Operation for AddUser {
         }
 
         let deny_only = ak == cred.access_key;
-        let conditions = get_condition_values(&req.headers, &cred);
+        let conditions = get_condition_values(&req.uri, &req.headers, &req.extensions, &cred);
         if !iam_store
             .is_allowed(&Args {
                 account: &cred.access_key,
@@ -347,7 +347,7 @@ impl Operation for GetUserInfo {
             check_key_valid(get_session_token(&req.uri, &req.headers).unwrap_or_default(), &input_cred.access_key).await?;
 
         let deny_only = ak == cred.access_key;
-        let conditions = get_condition_values(&req.headers, &cred);
+        let conditions = get_condition_values(&req.uri, &req.headers, &req.extensions, &cred);
         if !iam_store
             .is_allowed(&Args {
                 account: &cred.access_key,
@@ -214,6 +214,9 @@ impl Operation for PutFile {
             let result = file.write_all(&bytes).await;
             result.map_err(|e| s3_error!(InternalError, "write file err {}", e))?;
         }
+        file.shutdown()
+            .await
+            .map_err(|e| s3_error!(InternalError, "write file err {}", e))?;
 
         Ok(S3Response::new((StatusCode::OK, Body::empty())))
     }
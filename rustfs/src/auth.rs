@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use http::Extensions;
 use http::HeaderMap;
 use http::Uri;
 use rustfs_ecstore::global::get_global_action_cred;
@@ -19,6 +20,7 @@ use rustfs_iam::error::Error as IamError;
 use rustfs_iam::sys::SESSION_POLICY_NAME;
 use rustfs_policy::auth;
 use rustfs_policy::auth::get_claims_from_token_with_secret;
+use rustfs_policy::policy::ConditionArgs;
 use s3s::S3Error;
 use s3s::S3ErrorCode;
 use s3s::S3Result;
@@ -28,6 +30,7 @@ use s3s::auth::SimpleAuth;
 use s3s::s3_error;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::net::SocketAddr;
 
 pub struct IAMAuth {
     simple_auth: SimpleAuth,
@@ -167,7 +170,19 @@ pub fn get_session_token<'a>(uri: &'a Uri, hds: &'a HeaderMap) -> Option<&'a str
         .or_else(|| get_query_param(uri.query().unwrap_or_default(), "x-amz-security-token"))
 }
 
-pub fn get_condition_values(header: &HeaderMap, cred: &auth::Credentials) -> HashMap<String, Vec<String>> {
+/// The client connection a request arrived on, added to the request extensions when it is accepted.
+#[derive(Debug, Clone, Copy)]
+pub struct ConnectionInfo {
+    pub remote_addr: SocketAddr,
+    pub secure: bool,
+}
+
+pub fn get_condition_values(
+    uri: &Uri,
+    header: &HeaderMap,
+    extensions: &Extensions,
+    cred: &auth::Credentials,
+) -> HashMap<String, Vec<String>> {
     let username = if cred.is_temp() || cred.is_service_account() {
         cred.parent_user.clone()
     } else {
@@ -281,6 +296,16 @@ pub fn get_condition_values(header: &HeaderMap, cred: &auth::Credentials) -> Has
     //     }
     // }
 
+    let query = uri.query().unwrap_or_default();
+    let conn = extensions.get::<ConnectionInfo>();
+    ConditionArgs {
+        source_ip: conn.map(|conn| conn.remote_addr.ip()),
+        secure_transport: conn.is_some_and(|conn| conn.secure),
+        prefix: get_query_param(query, "prefix").and_then(|v| urlencoding::decode(v).ok().map(|v| v.into_owned())),
+        max_keys: get_query_param(query, "max-keys").and_then(|v| v.parse().ok()),
+    }
+    .insert_conditions(&mut args);
+
     if let Some(claims) = &cred.claims {
         for (k, v) in claims {
             if let Some(v_str) = v.as_str() {
@@ -14,6 +14,9 @@
 
 use clap::Parser;
 use const_str::concat;
+use rustfs_ecstore::disk::fault::DEFAULT_FAULT_THRESHOLD;
+use rustfs_ecstore::global::{DEFAULT_MAX_LIST_KEYS, DEFAULT_MAX_USER_METADATA_SIZE};
+use rustfs_ecstore::heal::data_scanner::ScannerSpeed;
 use std::string::ToString;
 shadow_rs::shadow!(build);
 
@@ -86,6 +89,94 @@ pub struct Opt {
 
     #[arg(long, env = "RUSTFS_LICENSE")]
     pub license: Option<String>,
+
+    /// Key the secret keys of remote bucket targets are encrypted with at rest, the root secret key if not set
+    #[arg(long, env = "RUSTFS_TARGETS_SECRET_KEY")]
+    pub targets_secret_key: Option<String>,
+
+    /// Master key SSE-KMS data keys are sealed with, 32 bytes in unpadded URL-safe base64, SSE-KMS is off if not set
+    #[arg(long, env = "RUSTFS_KMS_MASTER_KEY")]
+    pub kms_master_key: Option<String>,
+
+    /// Id of the KMS master key, SSE-KMS uploads that name no key are sealed with it
+    #[arg(long, default_value = "rustfs-default-key", env = "RUSTFS_KMS_KEY_ID")]
+    pub kms_key_id: String,
+
+    /// Largest object or part size in bytes accepted by uploads, unlimited if not set
+    #[arg(long, env = "RUSTFS_MAX_OBJECT_SIZE")]
+    pub max_object_size: Option<u64>,
+
+    /// Largest combined size in bytes of user metadata keys and values accepted by uploads
+    #[arg(long, default_value_t = DEFAULT_MAX_USER_METADATA_SIZE, env = "RUSTFS_MAX_USER_METADATA_SIZE")]
+    pub max_user_metadata_size: u64,
+
+    /// Hard ceiling on the keys returned by one ListObjectsV2 page
+    #[arg(long, default_value_t = DEFAULT_MAX_LIST_KEYS, env = "RUSTFS_MAX_LIST_KEYS")]
+    pub max_list_keys: i32,
+
+    /// Versions an object may keep, a new version beyond it prunes the oldest noncurrent one, 0 keeps every version
+    #[arg(long, default_value_t = 0, env = "RUSTFS_MAX_VERSIONS_PER_OBJECT")]
+    pub max_versions_per_object: usize,
+
+    /// Size in bytes from which files on local drives are read and written with O_DIRECT, 0 keeps buffered IO
+    #[arg(long, default_value_t = 0, env = "RUSTFS_DISK_DIRECT_IO_THRESHOLD")]
+    pub disk_direct_io_threshold: usize,
+
+    /// Bytes local drives read sequential object streams ahead by, 0 reads only what is asked for
+    #[arg(long, default_value_t = 0, env = "RUSTFS_DISK_READ_AHEAD")]
+    pub disk_read_ahead: usize,
+
+    /// Keep a backup of each object's xl.meta on local drives, read when the primary is corrupt, at the cost of a second synced write per metadata update
+    #[arg(long, default_value_t = false, env = "RUSTFS_DISK_META_BACKUP")]
+    pub disk_meta_backup: bool,
+
+    /// Bytes each local drive keeps free, uploads that would cut into it fail with a disk full error
+    #[arg(long, default_value_t = 0, env = "RUSTFS_DISK_MIN_FREE_BYTES")]
+    pub disk_min_free_bytes: u64,
+
+    /// Percentage of each local drive kept free, the larger of this and disk_min_free_bytes applies
+    #[arg(long, default_value_t = 0, env = "RUSTFS_DISK_MIN_FREE_PCT")]
+    pub disk_min_free_pct: u64,
+
+    /// Background scanner pacing: slow, default or fast
+    #[arg(long, default_value = "default", env = "RUSTFS_SCANNER_SPEED")]
+    pub scanner_speed: ScannerSpeed,
+
+    /// Seconds before stale temp and trash files on local drives are removed by the scanner, 0 disables it
+    #[arg(long, default_value_t = 24 * 60 * 60, env = "RUSTFS_TMP_GC_AGE")]
+    pub tmp_gc_age: u64,
+
+    /// Consecutive drive faults before the drive is taken offline and queued for healing
+    #[arg(long, default_value_t = DEFAULT_FAULT_THRESHOLD, env = "RUSTFS_DISK_FAULT_THRESHOLD")]
+    pub disk_fault_threshold: u32,
+
+    /// Percent a bucket's object count or size must change between scans to be scanned again on the next cycle, 0 scans every cycle
+    #[arg(long, default_value_t = 0, env = "RUSTFS_SCANNER_COLD_BUCKET_THRESHOLD")]
+    pub scanner_cold_bucket_threshold: u64,
+
+    /// Number of "object not found" answers kept in memory, 0 disables the cache
+    #[arg(long, default_value_t = 0, env = "RUSTFS_NEGATIVE_CACHE_SIZE")]
+    pub negative_cache_size: usize,
+
+    /// Milliseconds a cached "object not found" answer is served for
+    #[arg(long, default_value_t = 1000, env = "RUSTFS_NEGATIVE_CACHE_TTL_MS")]
+    pub negative_cache_ttl_ms: u64,
+
+    /// Number of listing pages kept in memory, 0 disables the cache
+    #[arg(long, default_value_t = 0, env = "RUSTFS_LIST_CACHE_SIZE")]
+    pub list_cache_size: usize,
+
+    /// Milliseconds a cached listing page is served for
+    #[arg(long, default_value_t = 1000, env = "RUSTFS_LIST_CACHE_TTL_MS")]
+    pub list_cache_ttl_ms: u64,
+
+    /// Seconds a bucket config timestamp may be ahead of the local clock before updates are refused, 0 disables the check
+    #[arg(long, default_value_t = 15 * 60, env = "RUSTFS_MAX_CLOCK_SKEW")]
+    pub max_clock_skew: u64,
+
+    /// Seconds a request may spend reading from disks before it fails with a timeout, 0 leaves requests unbounded
+    #[arg(long, default_value_t = 0, env = "RUSTFS_REQUEST_TIMEOUT")]
+    pub request_timeout: u64,
 }
 
 // lazy_static::lazy_static! {
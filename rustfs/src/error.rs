@@ -81,6 +81,20 @@ impl From<StorageError> for ApiError {
             StorageError::DataMovementOverwriteErr(_, _, _) => S3ErrorCode::InvalidArgument,
             StorageError::ObjectExistsAsDirectory(_, _) => S3ErrorCode::InvalidArgument,
             StorageError::InvalidPart(_, _, _) => S3ErrorCode::InvalidPart,
+            StorageError::ReadOnlyMode => S3ErrorCode::ServiceUnavailable,
+            StorageError::EntityTooLarge => S3ErrorCode::EntityTooLarge,
+            StorageError::MetadataTooLarge => S3ErrorCode::MetadataTooLarge,
+            StorageError::PreconditionFailed => S3ErrorCode::PreconditionFailed,
+            StorageError::BadDigest => S3ErrorCode::BadDigest,
+            StorageError::EntityTooSmall => S3ErrorCode::EntityTooSmall,
+            StorageError::InvalidPartOrder => S3ErrorCode::InvalidPartOrder,
+            StorageError::InvalidRange => S3ErrorCode::InvalidRange,
+            StorageError::ObjectLocked(_, _) => S3ErrorCode::AccessDenied,
+            StorageError::ComplianceModeLocked(_, _) => S3ErrorCode::AccessDenied,
+            StorageError::InvalidParity(_, _) => S3ErrorCode::InvalidStorageClass,
+            StorageError::TooManyVersions(_, _) => S3ErrorCode::InvalidRequest,
+            StorageError::VersionIdConflict(_, _, _) => S3ErrorCode::InvalidRequest,
+            StorageError::DeadlineExceeded => S3ErrorCode::RequestTimeout,
             _ => S3ErrorCode::InternalError,
         };
 
@@ -211,6 +225,26 @@ mod tests {
             (StorageError::BucketExists("test".into()), S3ErrorCode::BucketAlreadyExists),
             (StorageError::StorageFull, S3ErrorCode::ServiceUnavailable),
             (StorageError::SlowDown, S3ErrorCode::SlowDown),
+            (StorageError::ReadOnlyMode, S3ErrorCode::ServiceUnavailable),
+            (StorageError::EntityTooLarge, S3ErrorCode::EntityTooLarge),
+            (StorageError::MetadataTooLarge, S3ErrorCode::MetadataTooLarge),
+            (StorageError::PreconditionFailed, S3ErrorCode::PreconditionFailed),
+            (StorageError::BadDigest, S3ErrorCode::BadDigest),
+            (StorageError::EntityTooSmall, S3ErrorCode::EntityTooSmall),
+            (StorageError::InvalidPartOrder, S3ErrorCode::InvalidPartOrder),
+            (StorageError::InvalidRange, S3ErrorCode::InvalidRange),
+            (StorageError::ObjectLocked("test".into(), "test".into()), S3ErrorCode::AccessDenied),
+            (
+                StorageError::ComplianceModeLocked("test".into(), "test".into()),
+                S3ErrorCode::AccessDenied,
+            ),
+            (StorageError::InvalidParity(4, 2), S3ErrorCode::InvalidStorageClass),
+            (StorageError::TooManyVersions("test".into(), "test".into()), S3ErrorCode::InvalidRequest),
+            (
+                StorageError::VersionIdConflict("test".into(), "test".into(), "test".into()),
+                S3ErrorCode::InvalidRequest,
+            ),
+            (StorageError::DeadlineExceeded, S3ErrorCode::RequestTimeout),
             (StorageError::PrefixAccessDenied("test".into(), "test".into()), S3ErrorCode::AccessDenied),
             (StorageError::ObjectNotFound("test".into(), "test".into()), S3ErrorCode::NoSuchKey),
             (StorageError::ConfigNotFound, S3ErrorCode::NoSuchKey),
@@ -26,7 +26,7 @@ mod service;
 mod storage;
 mod update_checker;
 
-use crate::auth::IAMAuth;
+use crate::auth::{ConnectionInfo, IAMAuth};
 use crate::console::{CONSOLE_CONFIG, init_console_cfg};
 // Ensure the correct path for parse_license is imported
 use crate::event::shutdown_event_notifier;
@@ -44,21 +44,36 @@ use hyper_util::{
 use license::init_license;
 use rustfs_common::globals::set_global_addr;
 use rustfs_config::{DEFAULT_ACCESS_KEY, DEFAULT_SECRET_KEY, RUSTFS_TLS_CERT, RUSTFS_TLS_KEY};
+use rustfs_ecstore::bucket::metadata::set_max_clock_skew;
 use rustfs_ecstore::bucket::metadata_sys::init_bucket_metadata_sys;
+use rustfs_ecstore::cache_value::list_cache::set_list_cache;
+use rustfs_ecstore::cache_value::negative_cache::set_negative_cache;
 use rustfs_ecstore::cmd::bucket_replication::init_bucket_replication_pool;
 use rustfs_ecstore::config as ecconfig;
 use rustfs_ecstore::config::GLOBAL_ConfigSys;
-use rustfs_ecstore::heal::background_heal_ops::init_auto_heal;
+use rustfs_ecstore::disk::fault::set_fault_threshold;
+use rustfs_ecstore::disk::with_request_id;
+use rustfs_ecstore::global::set_global_disk_direct_io_threshold;
+use rustfs_ecstore::global::set_global_disk_meta_backup;
+use rustfs_ecstore::global::set_global_disk_min_free;
+use rustfs_ecstore::global::set_global_disk_read_ahead;
+use rustfs_ecstore::global::set_global_request_timeout;
+use rustfs_ecstore::global::set_global_targets_secret_key;
+use rustfs_ecstore::heal::background_heal_ops::{init_auto_heal, init_disk_fault_handler};
+use rustfs_ecstore::heal::data_scanner::{set_scanner_cold_bucket_threshold, set_scanner_throttle, set_tmp_gc_age};
+use rustfs_ecstore::kms::{LocalKms, set_kms_provider};
 use rustfs_ecstore::rpc::make_server;
+use rustfs_ecstore::set_global_max_versions_per_object;
 use rustfs_ecstore::store_api::BucketOptions;
 use rustfs_ecstore::{
     StorageAPI, endpoints::EndpointServerPools, global::set_global_rustfs_port, heal::data_scanner::init_data_scanner,
-    notification_sys::new_global_notification_sys, set_global_endpoints, store::ECStore, store::init_local_disks,
-    update_erasure_type,
+    new_object_layer_fn, notification_sys::new_global_notification_sys, set_global_endpoints, set_global_max_list_keys,
+    set_global_max_object_size, set_global_max_user_metadata_size, store::ECStore, store::init_local_disks, update_erasure_type,
 };
 use rustfs_iam::init_iam_sys;
 use rustfs_obs::{SystemObserver, init_obs, set_global_guard};
 use rustfs_protos::proto_gen::node_service::node_service_server::NodeServiceServer;
+use rustfs_utils::crypto::base64_decode;
 use rustfs_utils::net::parse_and_resolve_address;
 use rustls::ServerConfig;
 use s3s::service::S3Service;
@@ -75,10 +90,12 @@ use tokio::signal::unix::{SignalKind, signal};
 use tokio_rustls::TlsAcceptor;
 use tonic::{Request, Status, metadata::MetadataValue};
 use tower::ServiceBuilder;
+use tower_http::add_extension::AddExtensionLayer;
 use tower_http::catch_panic::CatchPanicLayer;
 use tower_http::cors::CorsLayer;
 use tower_http::trace::TraceLayer;
 use tracing::{Span, debug, error, info, instrument, warn};
+use uuid::Uuid;
 
 const MI_B: usize = 1024 * 1024;
 
@@ -184,6 +201,31 @@ async fn run(opt: config::Opt) -> Result<()> {
     rustfs_ecstore::global::init_global_action_cred(Some(opt.access_key.clone()), Some(opt.secret_key.clone()));
 
     set_global_rustfs_port(server_port);
+    set_global_max_object_size(opt.max_object_size);
+    set_global_max_user_metadata_size(opt.max_user_metadata_size);
+    set_global_max_list_keys(opt.max_list_keys);
+    set_global_max_versions_per_object((opt.max_versions_per_object > 0).then_some(opt.max_versions_per_object));
+    set_global_disk_direct_io_threshold((opt.disk_direct_io_threshold > 0).then_some(opt.disk_direct_io_threshold));
+    set_global_disk_read_ahead((opt.disk_read_ahead > 0).then_some(opt.disk_read_ahead));
+    set_global_disk_meta_backup(opt.disk_meta_backup);
+    set_global_disk_min_free(opt.disk_min_free_bytes, opt.disk_min_free_pct);
+    if let Some(key) = opt.targets_secret_key.clone() {
+        set_global_targets_secret_key(key);
+    }
+    if let Some(master_key) = opt.kms_master_key.as_deref() {
+        let master_key: [u8; 32] = base64_decode(master_key.as_bytes())
+            .ok()
+            .and_then(|key| key.try_into().ok())
+            .ok_or_else(|| Error::other("RUSTFS_KMS_MASTER_KEY must be 32 bytes in unpadded URL-safe base64"))?;
+        set_kms_provider(Arc::new(LocalKms::new(&opt.kms_key_id, master_key)), &opt.kms_key_id);
+    }
+    set_scanner_throttle(opt.scanner_speed.into());
+    set_tmp_gc_age(Duration::from_secs(opt.tmp_gc_age));
+    set_scanner_cold_bucket_threshold(opt.scanner_cold_bucket_threshold);
+    set_negative_cache(opt.negative_cache_size, Duration::from_millis(opt.negative_cache_ttl_ms));
+    set_list_cache(opt.list_cache_size, Duration::from_millis(opt.list_cache_ttl_ms));
+    set_max_clock_skew(Duration::from_secs(opt.max_clock_skew));
+    set_global_request_timeout((opt.request_timeout > 0).then(|| Duration::from_secs(opt.request_timeout)));
 
     // The listening address and port are obtained from the parameters
     let listener = TcpListener::bind(server_address.clone()).await?;
@@ -307,7 +349,7 @@ async fn run(opt: config::Opt) -> Result<()> {
 
         loop {
             debug!("Waiting for new connection...");
-            let (socket, _) = {
+            let (socket, remote_addr) = {
                 #[cfg(unix)]
                 {
                     tokio::select! {
@@ -373,7 +415,14 @@ async fn run(opt: config::Opt) -> Result<()> {
                 warn!(?err, "Failed to set set_send_buffer_size");
             }
 
-            process_connection(socket, tls_acceptor.clone(), http_server.clone(), s3_service.clone(), graceful.clone());
+            process_connection(
+                socket,
+                remote_addr,
+                tls_acceptor.clone(),
+                http_server.clone(),
+                s3_service.clone(),
+                graceful.clone(),
+            );
         }
 
         worker_state_manager.update(ServiceState::Stopping);
@@ -432,6 +481,8 @@ async fn run(opt: config::Opt) -> Result<()> {
     init_data_scanner().await;
     // init auto heal
     init_auto_heal().await;
+    set_fault_threshold(opt.disk_fault_threshold);
+    init_disk_fault_handler();
     // init console configuration
     init_console_cfg(local_ip, server_port);
 
@@ -515,6 +566,7 @@ async fn run(opt: config::Opt) -> Result<()> {
 #[instrument(skip_all, fields(peer_addr = %socket.peer_addr().map(|a| a.to_string()).unwrap_or_else(|_| "unknown".to_string())))]
 fn process_connection(
     socket: TcpStream,
+    remote_addr: SocketAddr,
     tls_acceptor: Option<Arc<TlsAcceptor>>,
     http_server: Arc<ConnBuilder<TokioExecutor>>,
     s3_service: S3Service,
@@ -524,7 +576,14 @@ fn process_connection(
         // Build services inside each connected task to avoid passing complex service types across tasks,
         // It also ensures that each connection has an independent service instance.
         let rpc_service = NodeServiceServer::with_interceptor(make_server(), check_auth);
+        let conn_info = ConnectionInfo {
+            remote_addr,
+            secure: tls_acceptor.is_some(),
+        };
         let hybrid_service = ServiceBuilder::new()
+            .layer(AddExtensionLayer::new(conn_info))
+            // tags the disk calls each request makes so their spans can be told apart
+            .map_future(|fut| with_request_id(Uuid::new_v4().to_string(), fut))
             .layer(CatchPanicLayer::new())
             .layer(
                 TraceLayer::new_for_http()
@@ -611,6 +670,13 @@ async fn handle_shutdown(state_manager: &ServiceStateManager, shutdown_tx: &toki
     // Stop the notification system
     shutdown_event_notifier().await;
 
+    // Stop background work and save pending MRF operations before the drives are closed
+    if let Some(store) = new_object_layer_fn() {
+        if let Err(err) = store.shutdown(false).await {
+            error!("Failed to shut down the object layer: {}", err);
+        }
+    }
+
     info!("Server is stopping...");
     let _ = shutdown_tx.send(());
 
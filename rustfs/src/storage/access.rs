@@ -36,7 +36,7 @@ pub(crate) struct ReqInfo {
 
 /// Authorizes the request based on the action and credentials.
 pub async fn authorize_request<T>(req: &mut S3Request<T>, action: Action) -> S3Result<()> {
-    let req_info = req.extensions.get_mut::<ReqInfo>().expect("ReqInfo not found");
+    let req_info = req.extensions.get::<ReqInfo>().expect("ReqInfo not found");
 
     if let Some(cred) = &req_info.cred {
         let Ok(iam_store) = rustfs_iam::get() else {
@@ -48,7 +48,7 @@ pub async fn authorize_request<T>(req: &mut S3Request<T>, action: Action) -> S3R
 
         let default_claims = HashMap::new();
         let claims = cred.claims.as_ref().unwrap_or(&default_claims);
-        let conditions = get_condition_values(&req.headers, cred);
+        let conditions = get_condition_values(&req.uri, &req.headers, &req.extensions, cred);
 
         if action != Action::S3Action(S3Action::DeleteObjectAction)
             && req_info.version_id.is_some()
@@ -104,7 +104,7 @@ pub async fn authorize_request<T>(req: &mut S3Request<T>, action: Action) -> S3R
             return Ok(());
         }
     } else {
-        let conditions = get_condition_values(&req.headers, &auth::Credentials::default());
+        let conditions = get_condition_values(&req.uri, &req.headers, &req.extensions, &auth::Credentials::default());
 
         if action != Action::S3Action(S3Action::ListAllMyBucketsAction) {
             if PolicySys::is_allowed(&BucketPolicyArgs {
@@ -397,6 +397,18 @@ impl S3Access for FS {
         let req_info = req.extensions.get_mut::<ReqInfo>().expect("ReqInfo not found");
         req_info.bucket = Some(req.input.bucket.clone());
         req_info.object = Some(req.input.key.clone());
+
+        // checked before the version id is recorded, with one DeleteObjectVersion would stand in for it
+        if req.input.bypass_governance_retention == Some(true)
+            && authorize_request(req, Action::S3Action(S3Action::BypassGovernanceRetentionAction))
+                .await
+                .is_err()
+        {
+            // without the permission the retention applies as if no bypass was asked for
+            req.input.bypass_governance_retention = None;
+        }
+
+        let req_info = req.extensions.get_mut::<ReqInfo>().expect("ReqInfo not found");
         req_info.version_id = req.input.version_id.clone();
 
         authorize_request(req, Action::S3Action(S3Action::DeleteObjectAction)).await
@@ -417,7 +429,18 @@ impl S3Access for FS {
     /// Checks whether the DeleteObjects request has accesses to the resources.
     ///
     /// This method returns `Ok(())` by default.
-    async fn delete_objects(&self, _req: &mut S3Request<DeleteObjectsInput>) -> S3Result<()> {
+    async fn delete_objects(&self, req: &mut S3Request<DeleteObjectsInput>) -> S3Result<()> {
+        let req_info = req.extensions.get_mut::<ReqInfo>().expect("ReqInfo not found");
+        req_info.bucket = Some(req.input.bucket.clone());
+
+        if req.input.bypass_governance_retention == Some(true)
+            && authorize_request(req, Action::S3Action(S3Action::BypassGovernanceRetentionAction))
+                .await
+                .is_err()
+        {
+            // without the permission the retention applies as if no bypass was asked for
+            req.input.bypass_governance_retention = None;
+        }
         Ok(())
     }
 
@@ -38,7 +38,7 @@ use futures::StreamExt;
 use http::HeaderMap;
 use lazy_static::lazy_static;
 use rustfs_ecstore::bucket::lifecycle::bucket_lifecycle_ops::validate_transition_tier;
-use rustfs_ecstore::bucket::lifecycle::lifecycle::Lifecycle;
+use rustfs_ecstore::bucket::lifecycle::lifecycle::{Lifecycle, expiration_header};
 use rustfs_ecstore::bucket::metadata::BUCKET_LIFECYCLE_CONFIG;
 use rustfs_ecstore::bucket::metadata::BUCKET_NOTIFICATION_CONFIG;
 use rustfs_ecstore::bucket::metadata::BUCKET_POLICY_CONFIG;
@@ -52,7 +52,9 @@ use rustfs_ecstore::bucket::policy_sys::PolicySys;
 use rustfs_ecstore::bucket::tagging::decode_tags;
 use rustfs_ecstore::bucket::tagging::encode_tags;
 use rustfs_ecstore::bucket::utils::serialize;
+use rustfs_ecstore::bucket::utils::validate_bucket_name;
 use rustfs_ecstore::bucket::versioning_sys::BucketVersioningSys;
+use rustfs_ecstore::checksum::ChecksumMode;
 use rustfs_ecstore::cmd::bucket_replication::ReplicationStatusType;
 use rustfs_ecstore::cmd::bucket_replication::ReplicationType;
 use rustfs_ecstore::cmd::bucket_replication::get_must_replicate_options;
@@ -63,6 +65,8 @@ use rustfs_ecstore::compress::is_compressible;
 use rustfs_ecstore::error::StorageError;
 use rustfs_ecstore::new_object_layer_fn;
 use rustfs_ecstore::set_disk::DEFAULT_READ_BUFFER_SIZE;
+use rustfs_ecstore::store_api::BUCKET_OWNER_DISPLAY_NAME;
+use rustfs_ecstore::store_api::BUCKET_OWNER_ID;
 use rustfs_ecstore::store_api::BucketOptions;
 use rustfs_ecstore::store_api::CompletePart;
 use rustfs_ecstore::store_api::DeleteBucketOptions;
@@ -70,6 +74,7 @@ use rustfs_ecstore::store_api::HTTPRangeSpec;
 use rustfs_ecstore::store_api::MakeBucketOptions;
 use rustfs_ecstore::store_api::MultipartUploadResult;
 use rustfs_ecstore::store_api::ObjectIO;
+use rustfs_ecstore::store_api::ObjectInfo;
 use rustfs_ecstore::store_api::ObjectOptions;
 use rustfs_ecstore::store_api::ObjectToDelete;
 use rustfs_ecstore::store_api::PutObjReader;
@@ -128,11 +133,32 @@ macro_rules! try_ {
 
 lazy_static! {
     static ref RUSTFS_OWNER: Owner = Owner {
-        display_name: Some("rustfs".to_owned()),
-        id: Some("c19050dbcee97fda828689dda99097a6321af2248fa760517237346e5d9c8a66".to_owned()),
+        display_name: Some(BUCKET_OWNER_DISPLAY_NAME.to_owned()),
+        id: Some(BUCKET_OWNER_ID.to_owned()),
     };
 }
 
+// The x-amz-expiration value for an object the bucket lifecycle is going to expire.
+fn object_expiration(info: &ObjectInfo) -> Option<String> {
+    let (due, rule_id) = info.expiration.as_ref()?;
+    expiration_header(*due, rule_id)
+}
+
+// The stored checksum as the CRC32, CRC32C, SHA1 and SHA256 response fields.
+fn stored_checksums(info: &ObjectInfo) -> [Option<String>; 4] {
+    let mut fields = [None, None, None, None];
+    if let Some((mode, value)) = info.checksum() {
+        let idx = match mode {
+            ChecksumMode::ChecksumCRC32 => 0,
+            ChecksumMode::ChecksumCRC32C => 1,
+            ChecksumMode::ChecksumSHA1 => 2,
+            _ => 3,
+        };
+        fields[idx] = Some(value);
+    }
+    fields
+}
+
 #[derive(Debug, Clone)]
 pub struct FS {
     // pub store: ECStore,
@@ -305,6 +331,8 @@ impl S3 for FS {
             ..
         } = req.input;
 
+        validate_bucket_name(&bucket).map_err(ApiError::from)?;
+
         let Some(store) = new_object_layer_fn() else {
             return Err(S3Error::with_message(S3ErrorCode::InternalError, "Not init".to_string()));
         };
@@ -531,14 +559,21 @@ impl S3 for FS {
     #[tracing::instrument(level = "debug", skip(self, req))]
     async fn delete_object(&self, req: S3Request<DeleteObjectInput>) -> S3Result<S3Response<DeleteObjectOutput>> {
         let DeleteObjectInput {
-            bucket, key, version_id, ..
+            bucket,
+            key,
+            version_id,
+            if_match,
+            bypass_governance_retention,
+            ..
         } = req.input.clone();
 
         let metadata = extract_metadata(&req.headers);
 
-        let opts: ObjectOptions = del_opts(&bucket, &key, version_id, &req.headers, metadata)
+        let mut opts: ObjectOptions = del_opts(&bucket, &key, version_id, &req.headers, metadata)
             .await
             .map_err(ApiError::from)?;
+        opts.if_match = if_match;
+        opts.bypass_governance = bypass_governance_retention.unwrap_or_default();
 
         let version_id = opts.version_id.as_ref().map(|v| Uuid::parse_str(v).ok()).unwrap_or_default();
         let dobj = ObjectToDelete {
@@ -551,7 +586,21 @@ impl S3 for FS {
         let Some(store) = new_object_layer_fn() else {
             return Err(S3Error::with_message(S3ErrorCode::InternalError, "Not init".to_string()));
         };
-        let (dobjs, _errs) = store.delete_objects(&bucket, objects, opts).await.map_err(ApiError::from)?;
+        // the precondition is checked against the current object, which only the single object path looks up
+        let dobjs = if opts.if_match.is_some() {
+            let oi = store.delete_object(&bucket, &key, opts).await.map_err(ApiError::from)?;
+            let version_id = oi.version_id.map(|v| v.to_string());
+            vec![rustfs_ecstore::store_api::DeletedObject {
+                delete_marker: oi.delete_marker,
+                delete_marker_version_id: if oi.delete_marker { version_id.clone() } else { None },
+                object_name: key.clone(),
+                version_id: if oi.delete_marker { None } else { version_id },
+                delete_marker_mtime: oi.mod_time,
+            }]
+        } else {
+            let (dobjs, _errs) = store.delete_objects(&bucket, objects, opts).await.map_err(ApiError::from)?;
+            dobjs
+        };
 
         // TODO: let errors;
 
@@ -561,7 +610,8 @@ impl S3 for FS {
                 .map(|v| {
                     let delete_marker = { if v.delete_marker { Some(true) } else { None } };
 
-                    let version_id = v.version_id.clone();
+                    // a new delete marker is reported by its own version id
+                    let version_id = v.version_id.clone().or_else(|| v.delete_marker_version_id.clone());
 
                     (delete_marker, version_id)
                 })
@@ -607,7 +657,12 @@ impl S3 for FS {
     async fn delete_objects(&self, req: S3Request<DeleteObjectsInput>) -> S3Result<S3Response<DeleteObjectsOutput>> {
         // info!("delete_objects args {:?}", req.input);
 
-        let DeleteObjectsInput { bucket, delete, .. } = req.input;
+        let DeleteObjectsInput {
+            bucket,
+            delete,
+            bypass_governance_retention,
+            ..
+        } = req.input;
 
         let objects: Vec<ObjectToDelete> = delete
             .objects
@@ -627,9 +682,10 @@ impl S3 for FS {
 
         let metadata = extract_metadata(&req.headers);
 
-        let opts: ObjectOptions = del_opts(&bucket, "", None, &req.headers, metadata)
+        let mut opts: ObjectOptions = del_opts(&bucket, "", None, &req.headers, metadata)
             .await
             .map_err(ApiError::from)?;
+        opts.bypass_governance = bypass_governance_retention.unwrap_or_default();
 
         let (dobjs, errs) = store.delete_objects(&bucket, objects, opts).await.map_err(ApiError::from)?;
 
@@ -792,11 +848,18 @@ impl S3 for FS {
             info.size as usize,
         )));
 
+        let [checksum_crc32, checksum_crc32c, checksum_sha1, checksum_sha256] = stored_checksums(&event_info);
         let output = GetObjectOutput {
             body,
             content_length: Some(info.size as i64),
             last_modified,
             content_type,
+            checksum_crc32,
+            checksum_crc32c,
+            checksum_sha1,
+            checksum_sha256,
+            restore: info.restore_status.as_ref().map(|s| s.to_string()),
+            expiration: object_expiration(&info),
             ..Default::default()
         };
 
@@ -909,6 +972,9 @@ impl S3 for FS {
 
         let content_length = info.get_actual_size().map_err(ApiError::from)?;
 
+        let [checksum_crc32, checksum_crc32c, checksum_sha1, checksum_sha256] = stored_checksums(&info);
+        let expiration = object_expiration(&info);
+
         let metadata = info.user_defined;
 
         let output = HeadObjectOutput {
@@ -918,6 +984,12 @@ impl S3 for FS {
             e_tag: info.etag,
             metadata: Some(metadata),
             version_id: info.version_id.map(|v| v.to_string()),
+            checksum_crc32,
+            checksum_crc32c,
+            checksum_sha1,
+            checksum_sha256,
+            restore: info.restore_status.as_ref().map(|s| s.to_string()),
+            expiration,
             // metadata: object_metadata,
             ..Default::default()
         };
@@ -1053,22 +1125,16 @@ impl S3 for FS {
             .objects
             .iter()
             .filter(|v| !v.name.is_empty())
-            .map(|v| {
-                let mut obj = Object {
-                    key: Some(v.name.to_owned()),
-                    last_modified: v.mod_time.map(Timestamp::from),
-                    size: Some(v.get_actual_size().unwrap_or_default()),
-                    e_tag: v.etag.clone(),
-                    ..Default::default()
-                };
-
-                if fetch_owner.is_some_and(|v| v) {
-                    obj.owner = Some(Owner {
-                        display_name: Some("rustfs".to_owned()),
-                        id: Some("v0.1".to_owned()),
-                    });
-                }
-                obj
+            .map(|v| Object {
+                key: Some(v.name.to_owned()),
+                last_modified: v.mod_time.map(Timestamp::from),
+                size: Some(v.get_actual_size().unwrap_or_default()),
+                e_tag: v.etag.clone(),
+                owner: v.owner.as_ref().map(|owner| Owner {
+                    display_name: Some(owner.display_name.clone()),
+                    id: Some(owner.id.clone()),
+                }),
+                ..Default::default()
             })
             .collect();
 
@@ -1085,7 +1151,7 @@ impl S3 for FS {
             continuation_token: object_infos.continuation_token,
             next_continuation_token: object_infos.next_continuation_token,
             key_count: Some(key_count),
-            max_keys: Some(key_count),
+            max_keys: Some(object_infos.max_keys),
             contents: Some(objects),
             delimiter,
             name: Some(bucket),
@@ -1124,7 +1190,14 @@ impl S3 for FS {
         };
 
         let object_infos = store
-            .list_object_versions(&bucket, &prefix, key_marker, version_id_marker, delimiter.clone(), max_keys)
+            .list_object_versions(
+                &bucket,
+                &prefix,
+                key_marker.clone(),
+                version_id_marker.clone(),
+                delimiter.clone(),
+                max_keys,
+            )
             .await
             .map_err(ApiError::from)?;
 
@@ -1154,7 +1227,11 @@ impl S3 for FS {
             .collect();
 
         let output = ListObjectVersionsOutput {
-            // is_truncated: Some(object_infos.is_truncated),
+            is_truncated: Some(object_infos.is_truncated),
+            key_marker,
+            version_id_marker,
+            next_key_marker: object_infos.next_marker,
+            next_version_id_marker: object_infos.next_version_idmarker,
             max_keys: Some(key_count),
             delimiter,
             name: Some(bucket),
@@ -1194,11 +1271,35 @@ impl S3 for FS {
             tagging,
             metadata,
             version_id,
+            checksum_algorithm,
+            checksum_crc32,
+            checksum_crc32c,
+            checksum_sha1,
+            checksum_sha256,
             ..
         } = input;
 
         let Some(body) = body else { return Err(s3_error!(IncompleteBody)) };
 
+        let checksum = {
+            let values = [
+                (ChecksumMode::ChecksumCRC32, checksum_crc32),
+                (ChecksumMode::ChecksumCRC32C, checksum_crc32c),
+                (ChecksumMode::ChecksumSHA1, checksum_sha1),
+                (ChecksumMode::ChecksumSHA256, checksum_sha256),
+            ];
+            match checksum_algorithm {
+                Some(algorithm) => {
+                    let Some(mode) = ChecksumMode::from_algorithm(algorithm.as_str()) else {
+                        return Err(s3_error!(InvalidArgument, "unsupported checksum algorithm"));
+                    };
+                    let value = values.into_iter().find(|(m, _)| *m == mode).and_then(|(_, v)| v);
+                    Some((mode, value))
+                }
+                None => values.into_iter().find(|(_, v)| v.is_some()),
+            }
+        };
+
         let mut size = match content_length {
             Some(c) => c,
             None => {
@@ -1235,7 +1336,8 @@ impl S3 for FS {
 
         let actual_size = size;
 
-        if is_compressible(&req.headers, &key) && size > MIN_COMPRESSIBLE_SIZE as i64 {
+        // the checksum is verified against the stored bytes, so such uploads are kept uncompressed
+        if checksum.is_none() && is_compressible(&req.headers, &key) && size > MIN_COMPRESSIBLE_SIZE as i64 {
             metadata.insert(
                 format!("{RESERVED_METADATA_PREFIX_LOWER}compression"),
                 CompressionAlgorithm::default().to_string(),
@@ -1259,6 +1361,10 @@ impl S3 for FS {
         let mut opts: ObjectOptions = put_opts(&bucket, &key, version_id, &req.headers, mt)
             .await
             .map_err(ApiError::from)?;
+        if let Some((mode, value)) = checksum {
+            opts.checksum_algorithm = Some(mode);
+            opts.checksum_value = value;
+        }
 
         let repoptions =
             get_must_replicate_options(&mt2, "", ReplicationStatusType::Unknown, ReplicationType::ObjectReplicationType, &opts);
@@ -1280,6 +1386,7 @@ impl S3 for FS {
             .map_err(ApiError::from)?;
         let event_info = obj_info.clone();
         let e_tag = obj_info.etag.clone();
+        let expiration = object_expiration(&obj_info);
 
         let repoptions =
             get_must_replicate_options(&mt2, "", ReplicationStatusType::Unknown, ReplicationType::ObjectReplicationType, &opts);
@@ -1293,6 +1400,7 @@ impl S3 for FS {
 
         let output = PutObjectOutput {
             e_tag,
+            expiration,
             ..Default::default()
         };
 
@@ -1435,8 +1543,13 @@ impl S3 for FS {
             return Err(S3Error::with_message(S3ErrorCode::InternalError, "Not init".to_string()));
         };
 
+        // only the upload metadata is needed here, listing every part would make each upload O(parts)
+        let info_opts = ObjectOptions {
+            skip_parts: true,
+            ..Default::default()
+        };
         let fi = store
-            .get_multipart_info(&bucket, &key, &upload_id, &opts)
+            .get_multipart_info(&bucket, &key, &upload_id, &info_opts)
             .await
             .map_err(ApiError::from)?;
 
@@ -1817,7 +1930,7 @@ impl S3 for FS {
             .await
             .map_err(ApiError::from)?;
 
-        let conditions = get_condition_values(&req.headers, &auth::Credentials::default());
+        let conditions = get_condition_values(&req.uri, &req.headers, &req.extensions, &auth::Credentials::default());
 
         let read_olny = PolicySys::is_allowed(&BucketPolicyArgs {
             bucket: &bucket,
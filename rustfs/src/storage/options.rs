@@ -17,6 +17,7 @@ use lazy_static::lazy_static;
 use rustfs_ecstore::bucket::versioning_sys::BucketVersioningSys;
 use rustfs_ecstore::error::Result;
 use rustfs_ecstore::error::StorageError;
+use rustfs_ecstore::global::get_global_request_deadline;
 use rustfs_ecstore::store_api::ObjectOptions;
 use rustfs_utils::path::is_dir_object;
 use std::collections::HashMap;
@@ -171,6 +172,7 @@ pub fn get_default_opts(
 ) -> Result<ObjectOptions> {
     Ok(ObjectOptions {
         user_defined: metadata,
+        deadline: get_global_request_deadline(),
         ..Default::default()
     })
 }